@@ -0,0 +1,398 @@
+use crate::{
+    models::{GameMetadata, ImageSource},
+    paths,
+    transfer::{self, TransferKind, TransferManager},
+};
+use anyhow::Result;
+use image::{imageops::FilterType, DynamicImage, ImageFormat};
+use log::{info, warn};
+use serde::Deserialize;
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    fs,
+    hash::{Hash, Hasher},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+pub mod palette;
+pub mod placeholder;
+
+/// Of the blurred backdrops, in pixels, they're stretched over the screen.
+const BACKDROP_WIDTH: u32 = 480;
+/// Of the blur, at `BACKDROP_WIDTH`.
+const BACKDROP_SIGMA: f32 = 12.0;
+
+fn default_disk_mb() -> u64 {
+    1024
+}
+
+fn default_memory_mb() -> u64 {
+    256
+}
+
+/// How much art is kept, the least recently shown goes first.
+///
+/// ```yaml
+/// # Of the cache on disk.
+/// disk_mb: 1024
+/// # Of the covers loaded for the home screen.
+/// memory_mb: 256
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArtConfig {
+    #[serde(default = "default_disk_mb")]
+    pub disk_mb: u64,
+    #[serde(default = "default_memory_mb")]
+    pub memory_mb: u64,
+}
+
+impl Default for ArtConfig {
+    fn default() -> Self {
+        Self {
+            disk_mb: default_disk_mb(),
+            memory_mb: default_memory_mb(),
+        }
+    }
+}
+
+impl ArtConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn disk_budget(&self) -> u64 {
+        self.disk_mb * 1_000_000
+    }
+
+    pub fn memory_budget(&self) -> u64 {
+        self.memory_mb * 1_000_000
+    }
+}
+
+/// On disk cache of remote art (covers, backgrounds), keyed by URL.
+/// Downloads are queued on the transfer manager. Files are touched when
+/// used, and the least recently used go once it's over its budget. The
+/// backgrounds also get a blurred, smaller backdrop made with the download,
+/// for the screens showing one not to blur it as they're shown.
+#[derive(Clone)]
+pub struct ArtCache {
+    dir: PathBuf,
+    transfers: TransferManager,
+    /// In bytes.
+    budget: u64,
+    /// Files being made by a queued download, not to queue them again.
+    pending: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl ArtCache {
+    pub fn new(dir: PathBuf, transfers: TransferManager, budget: u64) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            transfers,
+            budget,
+            pending: Default::default(),
+        })
+    }
+
+    /// `$XDG_CACHE_HOME/anubis/art`, or `~/.cache/anubis/art`.
+    pub fn default_dir() -> PathBuf {
+        paths::cache_dir().join("art")
+    }
+
+    /// Where the art of the URL is, or will be, cached.
+    pub fn cached_path(&self, url: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        url.hash(&mut hasher);
+        self.dir.join(format!("{:016x}", hasher.finish()))
+    }
+
+    /// Queue downloads of the covers missing of the first `count` games, so
+    /// the home screen has them sooner. Returns how many were queued.
+    pub fn warm<'a>(&self, games: impl Iterator<Item = &'a GameMetadata>, count: usize) -> usize {
+        games
+            .take(count)
+            .filter_map(|g| match &g.cover_art {
+                Some(ImageSource::Url(url)) => Some((g, url)),
+                _ => None,
+            })
+            .filter(|(g, url)| self.fetch(&g.uuid, url).is_none())
+            .count()
+    }
+
+    /// Where the blurred backdrop of the art is, or will be, cached. Local
+    /// art is keyed by its path.
+    fn backdrop_path(&self, key: &str) -> PathBuf {
+        let mut path = self.cached_path(key).into_os_string();
+        path.push("-backdrop");
+        path.into()
+    }
+
+    /// Returns the cached file if present, otherwise queue a download for
+    /// the game and return None.
+    pub fn fetch(&self, uuid: &str, url: &str) -> Option<PathBuf> {
+        self.fetch_then(uuid, url, false)
+    }
+
+    /// `fetch` for a background, its backdrop is made with the download.
+    pub fn fetch_background(&self, uuid: &str, url: &str) -> Option<PathBuf> {
+        self.fetch_then(uuid, url, true)
+    }
+
+    fn fetch_then(&self, uuid: &str, url: &str, backdrop: bool) -> Option<PathBuf> {
+        let path = self.cached_path(url);
+        if touch(&path) {
+            return Some(path);
+        }
+        let backdrop = backdrop.then(|| self.backdrop_path(url));
+        self.queue(uuid, Some(url), path, backdrop);
+        None
+    }
+
+    /// The blurred backdrop of a game's background if it's been made,
+    /// otherwise queue making it, downloading the background first if need
+    /// be, and return None.
+    pub fn backdrop(&self, uuid: &str, background: &ImageSource) -> Option<PathBuf> {
+        let (url, key, path) = match background {
+            ImageSource::Url(url) => (Some(url.as_str()), url, self.cached_path(url)),
+            ImageSource::FilePath(path) => (None, path, PathBuf::from(path)),
+            ImageSource::Base64(_) => return None,
+        };
+        let backdrop = self.backdrop_path(key);
+        if touch(&backdrop) {
+            return Some(backdrop);
+        }
+        let url = url.filter(|_| !path.exists());
+        self.queue(uuid, url, path, Some(backdrop));
+        None
+    }
+
+    /// Download `url` to `path` if there's one, then blur what's at `path`
+    /// into `backdrop` if there's one. Not when either is already on its way,
+    /// whichever game it's for.
+    fn queue(&self, uuid: &str, url: Option<&str>, path: PathBuf, backdrop: Option<PathBuf>) {
+        let targets: Vec<PathBuf> = url
+            .map(|_| path.clone())
+            .into_iter()
+            .chain(backdrop.clone())
+            .collect();
+        let Some(pending) = Pending::claim(&self.pending, targets) else {
+            return;
+        };
+        let url = url.map(str::to_owned);
+        let (dir, budget) = (self.dir.clone(), self.budget);
+        self.transfers.enqueue(
+            TransferKind::Art,
+            uuid,
+            Box::new(move |ctx| {
+                // Released with the job.
+                let _claimed = &pending;
+                if let Some(url) = &url {
+                    transfer::download(ctx, url, &path)?;
+                }
+                if let Some(backdrop) = &backdrop {
+                    blur(&path, backdrop, BACKDROP_WIDTH, BACKDROP_SIGMA)?;
+                }
+                let freed = evict(&dir, budget)?;
+                if freed > 0 {
+                    info!("evicted {} bytes of art", freed);
+                }
+                Ok(())
+            }),
+        );
+    }
+
+    /// Bytes the cache takes.
+    pub fn usage(&self) -> Result<u64> {
+        Ok(cached_files(&self.dir)?.iter().map(|f| f.1).sum())
+    }
+
+    /// Remove all of it, returns how many bytes that freed.
+    pub fn clear(&self) -> Result<u64> {
+        evict(&self.dir, 0)
+    }
+}
+
+/// Files claimed by a queued download, released once it's done with, or
+/// cancelled, as the job goes.
+struct Pending {
+    set: Arc<Mutex<HashSet<PathBuf>>>,
+    paths: Vec<PathBuf>,
+}
+
+impl Pending {
+    /// None if one of them is already claimed.
+    fn claim(set: &Arc<Mutex<HashSet<PathBuf>>>, paths: Vec<PathBuf>) -> Option<Self> {
+        let mut claimed = set.lock().unwrap();
+        if paths.iter().any(|p| claimed.contains(p)) {
+            return None;
+        }
+        claimed.extend(paths.iter().cloned());
+        Some(Self {
+            set: set.clone(),
+            paths,
+        })
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        let mut claimed = self.set.lock().unwrap();
+        for path in &self.paths {
+            claimed.remove(path);
+        }
+    }
+}
+
+/// Mark the file as recently used, the last to go. False if it's not there.
+fn touch(path: &Path) -> bool {
+    if !path.exists() {
+        return false;
+    }
+    let touched = fs::File::options()
+        .append(true)
+        .open(path)
+        .and_then(|f| f.set_modified(SystemTime::now()));
+    if let Err(e) = touched {
+        warn!("failed to touch {:?}: {:?}", path, e);
+    }
+    true
+}
+
+/// Decoded by what's in it, cached art has no extension to tell.
+fn open_image(path: &Path) -> Result<DynamicImage> {
+    Ok(image::io::Reader::open(path)?
+        .with_guessed_format()?
+        .decode()?)
+}
+
+/// Shrink the image to `width` and blur it, as a PNG. Written whole or not
+/// at all, so a half written one is never shown.
+fn blur(from: &Path, to: &Path, width: u32, sigma: f32) -> Result<()> {
+    let image = open_image(from)?;
+    let height = (image.height() as u64 * width as u64 / image.width().max(1) as u64).max(1);
+    let blurred = image
+        .resize_exact(width, height as u32, FilterType::Triangle)
+        .blur(sigma);
+    let part = to.with_extension("part");
+    blurred.save_with_format(&part, ImageFormat::Png)?;
+    fs::rename(&part, to)?;
+    Ok(())
+}
+
+/// The files of the cache with their size, the least recently used first.
+/// Those being written aren't, they're renamed into place once done.
+fn cached_files(dir: &Path) -> Result<Vec<(PathBuf, u64)>> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.path().extension().is_some_and(|e| e == "part") {
+            continue;
+        }
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            // Evicted meanwhile by another download, or a clear.
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        if metadata.is_file() {
+            files.push((metadata.modified()?, entry.path(), metadata.len()));
+        }
+    }
+    files.sort();
+    Ok(files
+        .into_iter()
+        .map(|(_, path, len)| (path, len))
+        .collect())
+}
+
+/// Remove the least recently used files until the cache fits in `budget`
+/// bytes, returns how many bytes that freed.
+fn evict(dir: &Path, budget: u64) -> Result<u64> {
+    let files = cached_files(dir)?;
+    let mut usage: u64 = files.iter().map(|f| f.1).sum();
+    let mut freed = 0;
+    for (path, len) in files {
+        if usage <= budget {
+            break;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => freed += len,
+            // Already gone, another download evicted it as well.
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        usage -= len;
+    }
+    Ok(freed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn evicts_the_least_recently_used() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("anubis-art-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let now = SystemTime::now();
+        for (name, age) in [("old", 30), ("new", 10), ("mid", 20)] {
+            let path = dir.join(name);
+            fs::write(&path, [0; 100])?;
+            let file = fs::File::options().append(true).open(&path)?;
+            file.set_modified(now - Duration::from_secs(age))?;
+        }
+        // Being downloaded, neither counted nor evicted.
+        fs::write(dir.join("next.part"), [0; 100])?;
+        assert_eq!(evict(&dir, 300)?, 0);
+        assert_eq!(evict(&dir, 250)?, 100);
+        assert!(!dir.join("old").exists());
+        assert!(dir.join("mid").exists());
+        assert_eq!(evict(&dir, 0)?, 200);
+        assert_eq!(cached_files(&dir)?, []);
+        assert!(dir.join("next.part").exists());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn queues_each_file_once() {
+        let set = Arc::new(Mutex::new(HashSet::new()));
+        let (cover, backdrop) = (PathBuf::from("cover"), PathBuf::from("backdrop"));
+        let first = Pending::claim(&set, vec![cover.clone()]).unwrap();
+        assert!(Pending::claim(&set, vec![cover.clone()]).is_none());
+        // Another file of the same game.
+        let second = Pending::claim(&set, vec![backdrop.clone()]).unwrap();
+        drop(first);
+        assert!(Pending::claim(&set, vec![cover, backdrop.clone()]).is_none());
+        drop(second);
+        assert!(Pending::claim(&set, vec![backdrop]).is_some());
+    }
+
+    #[test]
+    fn blurs_a_smaller_backdrop() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("anubis-backdrop-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let (from, to) = (dir.join("background"), dir.join("backdrop"));
+        // Black, with a white dot in the middle.
+        let mut image = image::RgbImage::new(40, 20);
+        image.put_pixel(20, 10, image::Rgb([255, 255, 255]));
+        image.save_with_format(&from, ImageFormat::Png)?;
+
+        blur(&from, &to, 20, 2.0)?;
+        let backdrop = open_image(&to)?.to_rgb8();
+        assert_eq!(backdrop.dimensions(), (20, 10));
+        let (middle, near) = (backdrop.get_pixel(10, 5)[0], backdrop.get_pixel(11, 5)[0]);
+        assert!(0 < near && near < middle && middle < 255);
+        assert!(!to.with_extension("part").exists());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
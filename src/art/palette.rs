@@ -0,0 +1,114 @@
+use anyhow::{anyhow, Result};
+use std::{collections::HashMap, path::Path};
+
+// The colors of a cover, for the UI to take on those of the focused game.
+// The cover is shrunk and its colors put into buckets, the most common is
+// the dominant one and the most vivid of the others, if any stands out, the
+// accent. The shade is the dominant color darkened, for gradients to end in.
+
+/// Covers are shrunk to this before counting, plenty to tell the colors.
+const SAMPLE_SIZE: u32 = 64;
+/// Bits kept of each channel for the buckets.
+const BUCKET_BITS: u8 = 4;
+/// Less transparent than this, a pixel counts.
+const MIN_ALPHA: u8 = 128;
+/// Of the pixels, for a color to be the accent.
+const MIN_ACCENT_SHARE: f32 = 0.02;
+/// Of the difference between the strongest and weakest channels, duller
+/// colors don't make an accent.
+const MIN_ACCENT_CHROMA: u8 = 64;
+/// How much of the dominant color is left in the shade.
+const SHADE: f32 = 0.3;
+
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub dominant: Rgb,
+    /// None when the cover is dull all over.
+    pub accent: Option<Rgb>,
+    pub shade: Rgb,
+}
+
+fn chroma(c: Rgb) -> u8 {
+    c.iter().max().unwrap() - c.iter().min().unwrap()
+}
+
+/// Of the pixels, RGBA rows. None without an opaque one.
+pub fn extract(rgba: &[u8]) -> Option<Palette> {
+    let mut buckets: HashMap<Rgb, (u32, [u32; 3])> = HashMap::new();
+    for px in rgba.chunks_exact(4).filter(|px| px[3] >= MIN_ALPHA) {
+        let key = [0, 1, 2].map(|c| px[c] >> (8 - BUCKET_BITS));
+        let (count, sums) = buckets.entry(key).or_default();
+        *count += 1;
+        for c in 0..3 {
+            sums[c] += px[c] as u32;
+        }
+    }
+    let total: u32 = buckets.values().map(|(count, _)| count).sum();
+    // The average of each bucket, the most common first.
+    let mut colors: Vec<(u32, Rgb)> = buckets
+        .into_values()
+        .map(|(count, sums)| (count, sums.map(|s| (s / count) as u8)))
+        .collect();
+    colors.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    let dominant = colors.first()?.1;
+    let accent = colors
+        .iter()
+        .filter(|(count, c)| {
+            *count as f32 >= total as f32 * MIN_ACCENT_SHARE && chroma(*c) >= MIN_ACCENT_CHROMA
+        })
+        // Bright and saturated.
+        .max_by_key(|(_, c)| chroma(*c) as u32 * *c.iter().max().unwrap() as u32)
+        .map(|(_, c)| *c);
+    Some(Palette {
+        dominant,
+        accent,
+        shade: dominant.map(|c| (c as f32 * SHADE) as u8),
+    })
+}
+
+/// Of an image file, PNG or JPEG.
+pub fn of_file(path: &Path) -> Result<Palette> {
+    let sample = super::open_image(path)?
+        .thumbnail(SAMPLE_SIZE, SAMPLE_SIZE)
+        .to_rgba8();
+    extract(&sample).ok_or_else(|| anyhow!("{:?} is transparent", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixels(colors: &[(usize, [u8; 4])]) -> Vec<u8> {
+        colors
+            .iter()
+            .flat_map(|&(n, px)| std::iter::repeat_n(px, n))
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn picks_the_dominant_and_the_vivid() {
+        let cover = pixels(&[
+            (70, [30, 40, 60, 255]),
+            (20, [250, 120, 10, 255]),
+            (10, [240, 240, 240, 255]),
+            // Not seen.
+            (200, [0, 255, 0, 0]),
+        ]);
+        assert_eq!(
+            extract(&cover),
+            Some(Palette {
+                dominant: [30, 40, 60],
+                accent: Some([250, 120, 10]),
+                shade: [9, 12, 18],
+            })
+        );
+        let grey = pixels(&[(10, [100, 100, 100, 255]), (1, [255, 0, 0, 255])]);
+        assert_eq!(extract(&grey).unwrap().accent, Some([255, 0, 0]));
+        let dull = pixels(&[(10, [100, 100, 100, 255])]);
+        assert_eq!(extract(&dull).unwrap().accent, None);
+        assert_eq!(extract(&pixels(&[(5, [1, 2, 3, 0])])), None);
+    }
+}
@@ -0,0 +1,80 @@
+use super::palette::Rgb;
+
+// What games without a cover show instead of a blank tile: the initials of
+// the title over a color of their own. The color comes from a hash of the
+// UUID, so a game keeps its color from one start to the next and games next
+// to each other usually differ. Dark enough for white initials.
+
+/// Of the placeholder colors, in [0, 1].
+const SATURATION: f32 = 0.45;
+const LIGHTNESS: f32 = 0.35;
+/// Initials shown at most.
+const MAX_INITIALS: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    pub initials: String,
+    pub color: Rgb,
+}
+
+impl Placeholder {
+    pub fn new(uuid: &str, title: &str) -> Self {
+        let hue = crc32fast::hash(uuid.as_bytes()) % 360;
+        Self {
+            initials: initials(title),
+            color: hsl(hue as f32, SATURATION, LIGHTNESS),
+        }
+    }
+}
+
+/// Of the first words, "?" without any.
+fn initials(title: &str) -> String {
+    let initials: String = title
+        .split_whitespace()
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .take(MAX_INITIALS)
+        .flat_map(char::to_uppercase)
+        .collect();
+    if initials.is_empty() {
+        "?".to_owned()
+    } else {
+        initials
+    }
+}
+
+fn hsl(hue: f32, saturation: f32, lightness: f32) -> Rgb {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let h = hue / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    [r, g, b].map(|c| ((c + m) * 255.0).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_game_same_placeholder() {
+        let sut = Placeholder::new("0190a8e2-7c1d", "the legend of zelda");
+        assert_eq!(sut.initials, "TL");
+        assert_eq!(
+            sut,
+            Placeholder::new("0190a8e2-7c1d", "the legend of zelda")
+        );
+        assert_ne!(sut.color, Placeholder::new("0190a8e2-7c1e", "").color);
+        assert_eq!(initials("  ...Ys: Origin"), "YO");
+        assert_eq!(initials("ōkami"), "Ō");
+        assert_eq!(initials("- -"), "?");
+        assert_eq!(hsl(0.0, 0.45, 0.35), [129, 49, 49]);
+        assert_eq!(hsl(120.0, 1.0, 0.5), [0, 255, 0]);
+    }
+}
@@ -0,0 +1,104 @@
+use log::info;
+use std::time::{Duration, Instant};
+
+// Startup in stages, so the window comes up right away with a splash saying
+// what's being done, instead of staying blank until everything's loaded. The
+// stages run on their own thread and the home screen is shown once they're
+// all done. What's slow and not needed to show it, like downloading covers, is
+// only queued on the way.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// The config files.
+    Config,
+    /// The library and its roots.
+    Library,
+    /// The gamepad.
+    Input,
+    /// Covers of the first games, queued for download if missing.
+    Art,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [Stage::Config, Stage::Library, Stage::Input, Stage::Art];
+
+    /// Shown on the splash while it's going, in the translations.
+    pub fn message_id(self) -> &'static str {
+        match self {
+            Stage::Config => "boot-config",
+            Stage::Library => "boot-library",
+            Stage::Input => "boot-input",
+            Stage::Art => "boot-art",
+        }
+    }
+}
+
+/// Times the stages, reporting each as it starts with how far along startup
+/// is. A stage lasts until the next one starts.
+pub struct Pipeline<F: Fn(Stage, f32)> {
+    report: F,
+    started: Instant,
+    current: Option<(Stage, Instant)>,
+    timings: Vec<(Stage, Duration)>,
+}
+
+impl<F: Fn(Stage, f32)> Pipeline<F> {
+    pub fn new(report: F) -> Self {
+        Self {
+            report,
+            started: Instant::now(),
+            current: None,
+            timings: vec![],
+        }
+    }
+
+    pub fn start(&mut self, stage: Stage) {
+        self.end_stage();
+        let done = Stage::ALL.iter().position(|s| *s == stage).unwrap_or(0);
+        (self.report)(stage, done as f32 / Stage::ALL.len() as f32);
+        self.current = Some((stage, Instant::now()));
+    }
+
+    fn end_stage(&mut self) {
+        if let Some((stage, started)) = self.current.take() {
+            self.timings.push((stage, started.elapsed()));
+        }
+    }
+
+    /// How long each stage took, logged as well.
+    pub fn finish(mut self) -> Vec<(Stage, Duration)> {
+        self.end_stage();
+        for (stage, took) in &self.timings {
+            info!("boot: {:?} took {:?}", stage, took);
+        }
+        info!("boot: ready after {:?}", self.started.elapsed());
+        self.timings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn reports_stages_with_progress() {
+        let reported = RefCell::new(vec![]);
+        let mut pipeline =
+            Pipeline::new(|stage, progress| reported.borrow_mut().push((stage, progress)));
+        pipeline.start(Stage::Config);
+        pipeline.start(Stage::Library);
+        pipeline.start(Stage::Art);
+        let timings = pipeline.finish();
+        assert_eq!(
+            reported.into_inner(),
+            [
+                (Stage::Config, 0.0),
+                (Stage::Library, 0.25),
+                (Stage::Art, 0.75)
+            ]
+        );
+        let stages: Vec<Stage> = timings.iter().map(|(s, _)| *s).collect();
+        assert_eq!(stages, [Stage::Config, Stage::Library, Stage::Art]);
+    }
+}
@@ -0,0 +1,105 @@
+use serde::Serialize;
+use std::sync::{mpsc, Arc, Mutex};
+
+// What happens in the launcher, for whoever outside follows along, e.g. the
+// companion apps over the remote control's websocket. The navigation
+// publishes, each subscriber gets the state as it is, then every event.
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// The focus moved, `game` is the UUID when it's on one.
+    Focus {
+        screen: String,
+        id: String,
+        game: Option<String>,
+        title: Option<String>,
+    },
+    /// Started, or back from suspended.
+    Playing { uuid: String, title: String },
+    /// Nothing is played anymore.
+    Idle,
+    /// A toast.
+    Notification { message: String },
+}
+
+impl Event {
+    /// Events of the same kind replace each other in the state, toasts
+    /// aren't part of it.
+    fn kind(&self) -> Option<&'static str> {
+        match self {
+            Event::Focus { .. } => Some("focus"),
+            Event::Playing { .. } | Event::Idle => Some("playing"),
+            Event::Notification { .. } => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Subscribers {
+    senders: Vec<mpsc::Sender<Event>>,
+    /// The last event of each kind.
+    state: Vec<Event>,
+}
+
+#[derive(Clone, Default)]
+pub struct Bus {
+    subscribers: Arc<Mutex<Subscribers>>,
+}
+
+impl Bus {
+    /// Unsubscribed once the receiver is dropped.
+    pub fn subscribe(&self) -> mpsc::Receiver<Event> {
+        let (tx, rx) = mpsc::channel();
+        let mut subscribers = self.subscribers.lock().unwrap();
+        for event in &subscribers.state {
+            let _ = tx.send(event.clone());
+        }
+        subscribers.senders.push(tx);
+        rx
+    }
+
+    /// Sent only if the state changed, e.g. not when the focus stayed.
+    pub fn publish(&self, event: Event) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if let Some(kind) = event.kind() {
+            if subscribers.state.contains(&event) {
+                return;
+            }
+            subscribers.state.retain(|e| e.kind() != Some(kind));
+            subscribers.state.push(event.clone());
+        }
+        subscribers
+            .senders
+            .retain(|tx| tx.send(event.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscribers_get_the_state_then_changes() {
+        let bus = Bus::default();
+        let playing = Event::Playing {
+            uuid: "a".to_owned(),
+            title: "Celeste".to_owned(),
+        };
+        bus.publish(playing.clone());
+        let rx = bus.subscribe();
+        bus.publish(playing.clone());
+        bus.publish(Event::Idle);
+        let toast = Event::Notification {
+            message: "Saved".to_owned(),
+        };
+        bus.publish(toast.clone());
+        let got: Vec<_> = rx.try_iter().collect();
+        assert_eq!(got, [playing.clone(), Event::Idle, toast]);
+
+        drop(rx);
+        bus.publish(playing.clone());
+        assert_eq!(bus.subscribe().try_iter().collect::<Vec<_>>(), [playing]);
+        assert_eq!(bus.subscribers.lock().unwrap().senders.len(), 1);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{controller::input_log::PadInput, paths};
+use anyhow::{anyhow, bail, Result};
+use gilrs::Button;
+use log::info;
+use serde::Deserialize;
+use std::{
+    fs,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::mpsc,
+};
+
+// The TV remote over HDMI-CEC, read from `cec-client` of libcec. Its keys go
+// through the same pipeline as the gamepad's: the arrows are the D-pad, OK is
+// A, back is B, and the colored keys are the face buttons of that color on an
+// Xbox pad.
+
+/// What a user control pressed message is, the key follows.
+const PRESSED: u8 = 0x44;
+const RELEASED: u8 = 0x45;
+
+fn default_osd_name() -> String {
+    "Anubis".to_owned()
+}
+
+/// Off unless enabled.
+///
+/// ```yaml
+/// enabled: true
+/// # From `cec-client -l`, the first one found without.
+/// adapter: /dev/cec0
+/// # What the TV lists the launcher as.
+/// osd_name: Anubis
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct CecConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub adapter: Option<String>,
+    #[serde(default = "default_osd_name")]
+    pub osd_name: String,
+}
+
+impl Default for CecConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            adapter: None,
+            osd_name: default_osd_name(),
+        }
+    }
+}
+
+impl CecConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::config_dir().join("cec.yaml")
+    }
+}
+
+/// The CEC user control code of the key.
+fn button(key: u8) -> Option<Button> {
+    Some(match key {
+        0x00 => Button::South,
+        0x01 => Button::DPadUp,
+        0x02 => Button::DPadDown,
+        0x03 => Button::DPadLeft,
+        0x04 => Button::DPadRight,
+        // Root and setup menu.
+        0x09 | 0x0a => Button::Start,
+        // Exit, labeled back on most remotes.
+        0x0d => Button::East,
+        0x71 => Button::West,
+        0x72 => Button::East,
+        0x73 => Button::South,
+        0x74 => Button::North,
+        _ => return None,
+    })
+}
+
+/// Reads the traffic `cec-client` logs, e.g. `TRAFFIC: [ 1234] >> 01:44:01`.
+#[derive(Debug, Default)]
+struct Keys {
+    /// Released with the next message, it doesn't say which.
+    pressed: Option<Button>,
+}
+
+impl Keys {
+    fn line(&mut self, line: &str) -> Vec<PadInput> {
+        let Some((_, message)) = line.split_once(">> ") else {
+            return vec![];
+        };
+        let bytes: Vec<u8> = message
+            .trim()
+            .split(':')
+            .map_while(|b| u8::from_str_radix(b, 16).ok())
+            .collect();
+        let mut inputs = vec![];
+        match bytes[..] {
+            [_, PRESSED, key, ..] => {
+                let pressed = button(key);
+                // Held down, the TV repeats the press. The arrows move again,
+                // the other keys are held, e.g. A to pick up.
+                let arrow = matches!(
+                    pressed,
+                    Some(Button::DPadUp | Button::DPadDown | Button::DPadLeft | Button::DPadRight)
+                );
+                if pressed.is_some() && pressed == self.pressed && !arrow {
+                    return vec![];
+                }
+                if let Some(b) = self.pressed.filter(|&b| Some(b) != pressed) {
+                    inputs.push(PadInput::Released(b));
+                }
+                if let Some(b) = pressed {
+                    inputs.push(PadInput::Pressed(b));
+                }
+                self.pressed = pressed;
+            }
+            [_, RELEASED, ..] => inputs.extend(self.pressed.take().map(PadInput::Released)),
+            _ => {}
+        }
+        inputs
+    }
+}
+
+pub struct Cec {
+    config: CecConfig,
+    inputs: mpsc::Sender<PadInput>,
+}
+
+impl Cec {
+    /// The keys go to `inputs`, for the gamepad's pipeline.
+    pub fn new(config: CecConfig, inputs: mpsc::Sender<PadInput>) -> Self {
+        Self { config, inputs }
+    }
+
+    /// Until `cec-client` exits, or the pipeline is gone. Meant to run on a
+    /// thread of its own, see the supervisor.
+    pub fn run(&self) -> Result<()> {
+        let mut command = Command::new("cec-client");
+        // A playback device, logging the traffic.
+        command.args(["-t", "p", "-d", "8", "-o", &self.config.osd_name]);
+        if let Some(ref adapter) = self.config.adapter {
+            command.arg(adapter);
+        }
+        // It quits once its input is closed, kept open with the child.
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow!("failed to run cec-client, is libcec installed? {}", e))?;
+        let stdout = child.stdout.take().ok_or(anyhow!("no output"))?;
+        info!("reading the TV remote over CEC");
+        let mut keys = Keys::default();
+        for line in BufReader::new(stdout).lines() {
+            for input in keys.line(&line?) {
+                if self.inputs.send(input).is_err() {
+                    let _ = child.kill();
+                    return Ok(());
+                }
+            }
+        }
+        bail!("cec-client exited with {}", child.wait()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_remote_keys() {
+        let mut keys = Keys::default();
+        assert_eq!(
+            keys.line("TRAFFIC: [  8040]\t>> 01:44:01"),
+            [PadInput::Pressed(Button::DPadUp)]
+        );
+        assert_eq!(
+            keys.line("TRAFFIC: [  8290]\t>> 01:44:01"),
+            [PadInput::Pressed(Button::DPadUp)]
+        );
+        assert_eq!(
+            keys.line("TRAFFIC: [  8310]\t>> 01:44:00"),
+            [
+                PadInput::Released(Button::DPadUp),
+                PadInput::Pressed(Button::South)
+            ]
+        );
+        assert!(keys.line("TRAFFIC: [  8350]\t>> 01:44:00").is_empty());
+        assert_eq!(
+            keys.line("TRAFFIC: [  8400]\t>> 01:45"),
+            [PadInput::Released(Button::South)]
+        );
+        assert!(keys.line("TRAFFIC: [  8500]\t>> 01:45").is_empty());
+        assert!(keys.line("TRAFFIC: [  8600]\t<< 10:47:41:6e").is_empty());
+        assert!(keys
+            .line("DEBUG:   [  8700]\tkey released: up (1)")
+            .is_empty());
+    }
+}
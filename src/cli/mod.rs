@@ -0,0 +1,836 @@
+use crate::{
+    controller::{self, replay, Router, Screen},
+    i18n::Translations,
+    instance::{deeplink, SingleInstance},
+    integrations::{itch, legendary, moonlight, steam, steam_shortcuts},
+    kiosk::{Compositor, KioskConfig},
+    launcher::{compat, ProfileConfig},
+    library::{
+        backup::{Backup, RestoreMode},
+        matches::{self, Candidate, Matches, MAX_CANDIDATES},
+        roms,
+        roots::{LibraryRoot, RootsConfig},
+        scrape::{Scraper, Source},
+        Library,
+    },
+    mdns,
+    models::{ArtKind, CompatConfig, GameMetadata, ImageSource, WebAppConfig},
+    paths,
+    remote::tokens::{RemoteTokens, SharedTokens},
+    saves::{SaveBackups, SaveConfig},
+    sync::{keys::DeviceKey, Peer, SyncConfig, Syncer},
+};
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Datelike;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::{
+    env,
+    ffi::OsString,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+// Library management without the UI, e.g. over SSH. Runs as `anubis cli ...`,
+// or `anubis-cli ...` when symlinked under that name.
+
+#[derive(Debug, Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub struct Args {
+    /// Launch a game, by the running launcher if there's one.
+    #[arg(long, value_name = "UUID")]
+    pub launch: Option<String>,
+    /// An `anubis://` link to open.
+    #[arg(conflicts_with = "launch")]
+    pub uri: Option<String>,
+    /// Save the D-pad presses to a file, see `anubis cli replay`.
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+    /// Save what the gamepad does, with when, to play it back with
+    /// `--play-input`.
+    #[arg(long, value_name = "PATH")]
+    pub record_input: Option<PathBuf>,
+    /// Play back input saved with `--record-input` instead of waiting for
+    /// the gamepad, at the pace it was recorded.
+    #[arg(long, value_name = "PATH")]
+    pub play_input: Option<PathBuf>,
+    /// Lay screens out from the JSON files in a directory, reloaded when
+    /// they change. For working on layouts.
+    #[arg(long, value_name = "DIR")]
+    pub layouts: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Manage the library without starting the UI.
+    Cli {
+        /// Library file, defaults to the one the UI uses.
+        #[arg(long, global = true)]
+        library: Option<PathBuf>,
+        #[command(subcommand)]
+        command: CliCommand,
+    },
+    /// Run as the display session of a kiosk, see `anubis cli kiosk`.
+    Session {
+        /// In the compositor already, restarting anubis when it crashes.
+        #[arg(long, hide = true)]
+        inner: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CliCommand {
+    /// Add games from a store.
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+    /// Add the ROMs found in a directory.
+    ScanRoms {
+        dir: PathBuf,
+        /// Platform of all the ROMs, guessed from the extension otherwise.
+        #[arg(long)]
+        platform: Option<String>,
+    },
+    /// Keep games in another place, e.g. an SD card. Its games stay in the
+    /// library while it's unplugged.
+    AddRoot {
+        /// What the games remember it as, e.g. `sd-card`.
+        name: String,
+        path: PathBuf,
+        /// Platform of all the ROMs under it, guessed from the extension
+        /// otherwise.
+        #[arg(long)]
+        platform: Option<String>,
+    },
+    /// Stop scanning a root, its games are kept.
+    RemoveRoot { name: String },
+    /// List the roots and whether their drive is plugged in.
+    Roots,
+    /// Set up kiosk mode, for booting into anubis with `anubis session`.
+    Kiosk {
+        /// Turn it off, the window can be closed again.
+        #[arg(long)]
+        disable: bool,
+        #[arg(long, value_enum)]
+        compositor: Option<Compositor>,
+        /// Asked to exit to the desktop, digits only.
+        #[arg(long, conflicts_with = "no_pin")]
+        pin: Option<String>,
+        #[arg(long)]
+        no_pin: bool,
+    },
+    /// Add the new ROMs of the roots plugged in.
+    ScanRoots {
+        /// Only this root.
+        name: Option<String>,
+    },
+    /// Fill in missing metadata from the stores.
+    Scrape {
+        /// Only this game, all the games otherwise.
+        uuid: Option<String>,
+        /// Overwrite the existing metadata.
+        #[arg(long)]
+        force: bool,
+    },
+    /// List the games.
+    List {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Set the art of a game to a file or an URL.
+    SetArt {
+        uuid: String,
+        kind: ArtArg,
+        source: String,
+    },
+    /// Save a copy of the library as JSON.
+    Backup {
+        /// Defaults to a new file in the backups dir.
+        path: Option<PathBuf>,
+        /// Put the art files in the backup.
+        #[arg(long)]
+        with_art: bool,
+    },
+    /// Restore a backup, merged with the library.
+    Restore {
+        path: PathBuf,
+        /// Drop the library instead.
+        #[arg(long)]
+        replace: bool,
+    },
+    /// List the Proton and Wine versions installed.
+    CompatTools,
+    /// Run a game through a Proton or Wine version, natively without one.
+    SetCompat {
+        uuid: String,
+        /// As listed by `compat-tools`.
+        tool: Option<String>,
+        /// Wine prefix, one per game under the data dir by default.
+        #[arg(long, requires = "tool")]
+        prefix: Option<PathBuf>,
+    },
+    /// Launch a game with a profile from the settings, or none.
+    SetProfile {
+        uuid: String,
+        profile: Option<String>,
+    },
+    /// Back up the saves of a game from this location, or stop. Either a path,
+    /// which can start with `{home}`, `{xdg_data}`, `{xdg_config}` or
+    /// `{windows_home}`, or an engine and the game's folder, e.g.
+    /// `unity:Team Cherry/Hollow Knight`, for unity, godot, renpy,
+    /// gamemaker and unreal.
+    SetSavePath { uuid: String, path: Option<String> },
+    /// Add games to Steam as non-Steam shortcuts, with their art. Close
+    /// Steam first, it overwrites them otherwise.
+    ExportSteamShortcuts {
+        /// Defaults to the games not from Steam.
+        uuids: Vec<String>,
+        /// `userdata/${USER}/config` of the Steam account, all by default.
+        #[arg(long)]
+        config_dir: Vec<PathBuf>,
+    },
+    /// Add a game played in the browser, e.g. on Xbox Cloud Gaming.
+    AddWebApp {
+        title: String,
+        url: String,
+        /// Browser profile, games with the same one share their logins.
+        #[arg(long)]
+        profile: Option<String>,
+        /// Don't let the page see the controllers.
+        #[arg(long)]
+        no_gamepad: bool,
+    },
+    /// Open the `anubis://` links with this executable.
+    RegisterUriHandler,
+    /// Replay a session recorded with `anubis --record`, stops where the
+    /// navigation goes somewhere else than recorded.
+    Replay { path: PathBuf },
+    /// List the last changes to the library.
+    Journal {
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Take back the last change made to the library on this device, again
+    /// for the one before.
+    Undo,
+    /// Print the command pairing another device with this one, to run there.
+    SyncId,
+    /// Sync the library with a device, by what `sync-id` printed there.
+    SyncPair {
+        device: String,
+        key: String,
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Sync with the paired devices found on the LAN now.
+    Sync,
+    /// List the apps paired with the remote control.
+    RemoteTokens,
+    /// Unpair an app from the remote control, by the ID `remote-tokens`
+    /// printed.
+    RemoteRevoke { id: String },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ImportSource {
+    /// Games installed by the Steam client.
+    Steam {
+        /// Extra steamapps directory, e.g. on an SD card.
+        #[arg(long)]
+        steamapps: Vec<PathBuf>,
+    },
+    /// Games installed by legendary, with their store metadata when signed in.
+    Epic,
+    /// Games installed by the itch app.
+    Itch {
+        /// Extra install location, set up in the itch app.
+        #[arg(long)]
+        apps: Vec<PathBuf>,
+    },
+    /// Apps of the Sunshine or GameStream hosts on the network, streamed
+    /// with Moonlight. It has to be paired with them first.
+    Moonlight {
+        /// Host to list instead of looking for them, as Moonlight knows it.
+        #[arg(long)]
+        host: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ArtArg {
+    Cover,
+    Background,
+}
+
+impl From<ArtArg> for ArtKind {
+    fn from(a: ArtArg) -> Self {
+        match a {
+            ArtArg::Cover => ArtKind::Cover,
+            ArtArg::Background => ArtKind::Background,
+        }
+    }
+}
+
+/// The command line, with `anubis-cli ...` turned into `anubis cli ...`.
+pub fn args() -> Vec<OsString> {
+    let mut args: Vec<OsString> = env::args_os().collect();
+    let invoked_as_cli = args
+        .first()
+        .map(PathBuf::from)
+        .and_then(|p| p.file_name().map(|n| n == "anubis-cli"))
+        .unwrap_or_default();
+    if invoked_as_cli {
+        args.insert(1, "cli".into());
+    }
+    args
+}
+
+/// Add the games which aren't in the library yet, returns how many were added.
+fn add_new(library: &mut Library, games: Vec<GameMetadata>) -> Result<usize> {
+    let mut added = 0;
+    for game in games {
+        let source = game.install_source.clone().unwrap_or_default();
+        if library.find_by_source(&source).is_some() || library.is_trashed(&source) {
+            continue;
+        }
+        println!("+ {}", game.title);
+        library.insert(game)?;
+        added += 1;
+    }
+    Ok(added)
+}
+
+/// Fill in the metadata of a steam game, only the missing bits unless forced.
+/// The Steam app of a game without a store of its own, see
+/// `library::matches`. None when nothing's close, or when the player is to
+/// pick one of several.
+fn find_steam_app(game: &GameMetadata, matches: &mut Matches) -> Result<Option<steam::AppId>> {
+    let mut candidates: Vec<Candidate> = steam::search_store(&game.title)?
+        .into_iter()
+        .take(MAX_CANDIDATES)
+        .map(|found| Candidate {
+            app_id: found.id,
+            name: found.name,
+            image: None,
+            year: None,
+        })
+        .collect();
+    if let Some(app_id) = matches::clear_match(&game.title, &candidates).map(|c| c.app_id) {
+        matches.resolve(&game.uuid, app_id);
+        return Ok(Some(app_id));
+    }
+    if candidates.is_empty() {
+        eprintln!("no metadata source for {}", game.title);
+        return Ok(None);
+    }
+    // To tell them apart on the matches screen.
+    for candidate in &mut candidates {
+        if let Ok(details) = steam::store_details(candidate.app_id, "english") {
+            candidate.image = details.header_image;
+            candidate.year = details.release_date.map(|d| d.year());
+        }
+    }
+    println!(
+        "{} could be one of {} games, pick it on the matches screen",
+        game.title,
+        candidates.len()
+    );
+    matches.queue(&game.uuid, candidates);
+    Ok(None)
+}
+
+fn scrape(library: &mut Library, uuid: Option<String>, force: bool) -> Result<()> {
+    let uuids: Vec<String> = match uuid {
+        Some(uuid) => vec![uuid],
+        None => library.iter().map(|g| g.uuid.clone()).collect(),
+    };
+    let locale = Translations::load(&paths::config_dir().join("locale.yaml")).locale();
+    let mut scraper = Scraper::new(locale);
+    let mut matches = Matches::load(&Matches::default_path())?;
+    for uuid in uuids {
+        let game = library.get_mut(&uuid).ok_or(anyhow!("no game {}", uuid))?;
+        let source = match Source::of(game, &matches) {
+            Some(source) => source,
+            None => match find_steam_app(game, &mut matches) {
+                Ok(Some(app_id)) => Source::Steam(app_id),
+                Ok(None) => continue,
+                Err(e) => {
+                    eprintln!("failed to look {} up: {}", game.title, e);
+                    continue;
+                }
+            },
+        };
+        match scraper.fetch(&source) {
+            Ok(details) => {
+                scraper.apply(game, details, force);
+                println!("scraped {}", game.title);
+            }
+            Err(e) => eprintln!("failed to scrape {}: {}", game.title, e),
+        }
+    }
+    matches.save(&Matches::default_path())
+}
+
+fn list(library: &Library, json: bool) -> Result<()> {
+    if json {
+        let games: Vec<_> = library.iter().collect();
+        println!("{}", serde_json::to_string_pretty(&games)?);
+        return Ok(());
+    }
+    for game in library.iter() {
+        println!(
+            "{}\t{}\t{}\t{}",
+            game.uuid,
+            game.title,
+            game.platform.as_deref().unwrap_or("-"),
+            game.install_source.as_deref().unwrap_or("-"),
+        );
+    }
+    Ok(())
+}
+
+fn set_art(library: &mut Library, uuid: &str, kind: ArtKind, source: &str) -> Result<()> {
+    let source = if source.starts_with("http://") || source.starts_with("https://") {
+        ImageSource::Url(source.to_owned())
+    } else {
+        let path = PathBuf::from(source);
+        if !path.is_file() {
+            bail!("{} is not a file", source);
+        }
+        ImageSource::FilePath(path.canonicalize()?.to_string_lossy().into_owned())
+    };
+    library
+        .get_mut(uuid)
+        .ok_or(anyhow!("no game {}", uuid))?
+        .set_art(kind, Some(source));
+    Ok(())
+}
+
+fn set_compat(
+    library: &mut Library,
+    uuid: &str,
+    tool: Option<String>,
+    prefix: Option<PathBuf>,
+) -> Result<()> {
+    let game = library.get_mut(uuid).ok_or(anyhow!("no game {}", uuid))?;
+    if let Some(tool) = &tool {
+        if !compat::installed().iter().any(|t| &t.name == tool) {
+            bail!("{} isn't installed, see `anubis cli compat-tools`", tool);
+        }
+    }
+    game.compat = tool.map(|tool| CompatConfig {
+        tool,
+        prefix: prefix.map(|p| p.to_string_lossy().into_owned()),
+    });
+    Ok(())
+}
+
+fn set_profile(library: &Library, uuid: &str, profile: Option<String>) -> Result<()> {
+    library.get(uuid).ok_or(anyhow!("no game {}", uuid))?;
+    let path = paths::config_dir().join("profiles.yaml");
+    let mut config = ProfileConfig::load(&path)?;
+    match profile {
+        Some(profile) => {
+            if !config.profiles.contains_key(&profile) {
+                bail!("no profile {}", profile);
+            }
+            config.games.insert(uuid.to_owned(), profile);
+        }
+        None => {
+            config.games.remove(uuid);
+        }
+    }
+    config.save(&path)
+}
+
+fn set_save_path(library: &Library, uuid: &str, path: Option<String>) -> Result<()> {
+    let game = library.get(uuid).ok_or(anyhow!("no game {}", uuid))?;
+    let config_path = SaveConfig::default_path();
+    let mut config = SaveConfig::load(&config_path)?;
+    match path {
+        Some(path) => {
+            config.games.insert(uuid.to_owned(), path);
+            let dir = SaveBackups::new(config.clone()).save_dir(game).unwrap()?;
+            println!("{}", dir.display());
+        }
+        None => {
+            config.games.remove(uuid);
+        }
+    }
+    config.save(&config_path)
+}
+
+pub fn run(library_path: Option<PathBuf>, command: CliCommand) -> Result<()> {
+    match command {
+        CliCommand::RegisterUriHandler => {
+            println!("registered {}", deeplink::register()?.display());
+            return Ok(());
+        }
+        CliCommand::CompatTools => {
+            for tool in compat::installed() {
+                println!("{}\t{:?}\t{}", tool.name, tool.kind, tool.path.display());
+            }
+            return Ok(());
+        }
+        CliCommand::AddRoot {
+            name,
+            path,
+            platform,
+        } => {
+            let mut config = RootsConfig::load(&RootsConfig::default_path())?;
+            if config.get(&name).is_some() {
+                bail!("there's already a root named {}", name);
+            }
+            config.roots.push(LibraryRoot {
+                name,
+                path: path.canonicalize()?,
+                platform,
+            });
+            return config.save(&RootsConfig::default_path());
+        }
+        CliCommand::RemoveRoot { name } => {
+            let mut config = RootsConfig::load(&RootsConfig::default_path())?;
+            if config.get(&name).is_none() {
+                bail!("no root {}", name);
+            }
+            config.roots.retain(|r| r.name != name);
+            return config.save(&RootsConfig::default_path());
+        }
+        CliCommand::Roots => {
+            for root in RootsConfig::load(&RootsConfig::default_path())?.roots {
+                let state = if root.is_available() {
+                    "plugged in"
+                } else {
+                    "unplugged"
+                };
+                println!("{}\t{}\t{}", root.name, root.path.display(), state);
+            }
+            return Ok(());
+        }
+        CliCommand::Kiosk {
+            disable,
+            compositor,
+            pin,
+            no_pin,
+        } => {
+            let mut config = KioskConfig::load(&KioskConfig::default_path())?;
+            config.enabled = !disable;
+            if let Some(compositor) = compositor {
+                config.compositor = compositor;
+            }
+            if pin.is_some() || no_pin {
+                config.set_pin(pin.as_deref())?;
+            }
+            if config.enabled && !config.has_pin() {
+                eprintln!("warning: no PIN, anyone can exit to the desktop");
+            }
+            return config.save(&KioskConfig::default_path());
+        }
+        CliCommand::SyncPair { device, key, name } => {
+            if STANDARD.decode(&key).map_or(true, |k| k.len() != 32) {
+                bail!("{} isn't an Ed25519 public key", key);
+            }
+            let mut config = SyncConfig::load(&SyncConfig::default_path())?;
+            let name = name.unwrap_or_else(|| device.clone());
+            println!("paired with {}, enable sync in the config of both", name);
+            config.pair(Peer { device, name, key });
+            return config.save(&SyncConfig::default_path());
+        }
+        CliCommand::RemoteTokens => {
+            for token in RemoteTokens::load(&RemoteTokens::default_path())?.tokens {
+                println!("{}\t{}\t{}", token.id, token.name, token.paired_at);
+            }
+            return Ok(());
+        }
+        CliCommand::RemoteRevoke { id } => {
+            // The running launcher picks it up as the file changes.
+            let tokens = SharedTokens::load(RemoteTokens::default_path())?;
+            if !tokens.revoke(&id)? {
+                bail!("no remote token {}", id);
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+    // The launcher would overwrite the changes when it saves.
+    let read_only = matches!(
+        command,
+        CliCommand::List { .. }
+            | CliCommand::Backup { .. }
+            | CliCommand::Replay { .. }
+            | CliCommand::Journal { .. }
+            | CliCommand::SyncId
+            | CliCommand::ExportSteamShortcuts { .. }
+    );
+    if library_path.is_none()
+        && !read_only
+        && SingleInstance::is_running(&SingleInstance::default_path())
+    {
+        bail!("the launcher is running, close it first");
+    }
+    let mut library = Library::open(&library_path.unwrap_or_else(Library::default_path))?;
+    match command {
+        CliCommand::Import {
+            source: ImportSource::Steam { steamapps },
+        } => {
+            let mut dirs = steam::default_steamapps_dirs();
+            dirs.extend(
+                steam::SteamConfig::load(&steam::SteamConfig::default_path())?.steamapps_dirs,
+            );
+            dirs.extend(steamapps);
+            let games = steam::installed_apps(&dirs)
+                .into_iter()
+                .map(|app| {
+                    let install_source = format!("{}{}", steam::INSTALL_SOURCE_PREFIX, app.app_id);
+                    GameMetadata {
+                        title: app.name,
+                        platform: Some("pc".to_owned()),
+                        install_source: Some(install_source),
+                        ..Default::default()
+                    }
+                })
+                .collect();
+            let added = add_new(&mut library, games)?;
+            println!("imported {} games", added);
+        }
+        CliCommand::Import {
+            source: ImportSource::Epic,
+        } => {
+            let mut games = legendary::installed_games()?;
+            match legendary::library_details() {
+                Ok(mut details) => {
+                    for game in &mut games {
+                        let app_name = game
+                            .install_source
+                            .as_deref()
+                            .and_then(legendary::app_name_from_install_source);
+                        if let Some(details) = app_name.and_then(|a| details.remove(a)) {
+                            legendary::apply_details(game, details, false);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("no store metadata: {:#}", e),
+            }
+            let added = add_new(&mut library, games)?;
+            println!("imported {} games", added);
+        }
+        CliCommand::Import {
+            source: ImportSource::Itch { apps },
+        } => {
+            let mut dirs = itch::default_apps_dirs();
+            dirs.extend(apps);
+            let added = add_new(&mut library, itch::installed_games(&dirs))?;
+            println!("imported {} games", added);
+        }
+        CliCommand::Import {
+            source: ImportSource::Moonlight { host },
+        } => {
+            let hosts = if host.is_empty() {
+                moonlight::discover()?.into_iter().map(|h| h.name).collect()
+            } else {
+                host
+            };
+            if hosts.is_empty() {
+                bail!("no hosts found, pass them with --host");
+            }
+            let mut games = vec![];
+            for host in hosts {
+                match moonlight::games(&host) {
+                    Ok(found) => games.extend(found),
+                    Err(e) => eprintln!("skipping {}: {:#}", host, e),
+                }
+            }
+            let added = add_new(&mut library, games)?;
+            println!("imported {} streamed games", added);
+        }
+        CliCommand::ScanRoms { dir, platform } => {
+            let added = add_new(&mut library, roms::scan(&dir, platform.as_deref())?)?;
+            println!("added {} roms", added);
+        }
+        CliCommand::ScanRoots { name } => {
+            let config = RootsConfig::load(&RootsConfig::default_path())?;
+            if name.as_ref().is_some_and(|n| config.get(n).is_none()) {
+                bail!("no root {}", name.unwrap_or_default());
+            }
+            for root in &config.roots {
+                if name.as_ref().is_some_and(|n| *n != root.name) {
+                    continue;
+                }
+                if !root.is_available() {
+                    println!("{} is unplugged, skipped", root.name);
+                    continue;
+                }
+                println!("added {} roms from {}", library.scan_root(root)?, root.name);
+            }
+        }
+        CliCommand::Scrape { uuid, force } => scrape(&mut library, uuid, force)?,
+        CliCommand::List { json } => return list(&library, json),
+        CliCommand::SetArt { uuid, kind, source } => {
+            set_art(&mut library, &uuid, kind.into(), &source)?
+        }
+        CliCommand::Backup { path, with_art } => {
+            let backup = Backup::new(&library, with_art);
+            let path = path.unwrap_or_else(|| Backup::default_dir().join(backup.file_name()));
+            backup.save(&path)?;
+            println!("saved {} games to {}", backup.games.len(), path.display());
+            return Ok(());
+        }
+        CliCommand::Restore { path, replace } => {
+            let mode = if replace {
+                RestoreMode::Replace
+            } else {
+                RestoreMode::Merge
+            };
+            let (added, merged) = library.restore(Backup::load(&path)?, mode)?;
+            println!("added {} games, merged {}", added, merged);
+        }
+        CliCommand::Replay { path } => {
+            // Only the games are laid out, like the home screen on start.
+            let mut router = Router::new()?;
+            controller::layout_games(
+                router.controller_for(Screen::Home)?,
+                library.iter().map(|g| g.uuid.as_str()),
+            )?;
+            let result = replay::replay(&mut router, &replay::load(&path)?)?;
+            println!(
+                "replayed {} steps, skipped {}",
+                result.replayed, result.skipped
+            );
+            return Ok(());
+        }
+        CliCommand::Journal { limit } => {
+            let entries = library.journal()?;
+            for entry in &entries[entries.len().saturating_sub(limit)..] {
+                println!("{}", entry.describe());
+            }
+            return Ok(());
+        }
+        CliCommand::Undo => match library.undo()? {
+            Some(entry) => println!("undid {}", entry.describe()),
+            None => {
+                println!("nothing to undo");
+                return Ok(());
+            }
+        },
+        CliCommand::SyncId => {
+            let device = library
+                .device()
+                .ok_or(anyhow!("the library has no journal"))?;
+            let key = DeviceKey::load_or_create(&DeviceKey::default_path())?;
+            println!(
+                "anubis cli sync-pair {} {} --name {}",
+                device,
+                key.public_key(),
+                mdns::hostname()
+            );
+            return Ok(());
+        }
+        CliCommand::Sync => {
+            let config = SyncConfig::load(&SyncConfig::default_path())?;
+            if config.peers.is_empty() {
+                bail!("no devices paired, see sync-pair");
+            }
+            let key = DeviceKey::load_or_create(&DeviceKey::default_path())?;
+            let syncer = Syncer::new(Arc::new(Mutex::new(library)), config, key)?;
+            println!("synced {} games", syncer.sync()?.len());
+            return Ok(());
+        }
+        CliCommand::SetCompat { uuid, tool, prefix } => {
+            set_compat(&mut library, &uuid, tool, prefix)?
+        }
+        CliCommand::ExportSteamShortcuts { uuids, config_dir } => {
+            let games = if uuids.is_empty() {
+                library
+                    .iter()
+                    .filter(|g| {
+                        let source = g.install_source.as_deref().unwrap_or_default();
+                        steam::app_id_from_install_source(source).is_none()
+                    })
+                    .cloned()
+                    .collect()
+            } else {
+                uuids
+                    .iter()
+                    .map(|u| library.get(u).cloned().ok_or(anyhow!("no game {}", u)))
+                    .collect::<Result<Vec<_>>>()?
+            };
+            let dirs = if config_dir.is_empty() {
+                steam_shortcuts::default_user_config_dirs()
+            } else {
+                config_dir
+            };
+            if dirs.is_empty() {
+                bail!("no Steam accounts found, pass their dir with --config-dir");
+            }
+            for dir in dirs {
+                steam_shortcuts::export(&dir, &games)?;
+                println!("exported {} games to {}", games.len(), dir.display());
+            }
+            return Ok(());
+        }
+        CliCommand::AddWebApp {
+            title,
+            url,
+            profile,
+            no_gamepad,
+        } => {
+            let uuid = library.insert(GameMetadata {
+                title,
+                platform: Some("web".to_owned()),
+                web_app: Some(WebAppConfig {
+                    url,
+                    profile,
+                    gamepad: !no_gamepad,
+                }),
+                ..Default::default()
+            })?;
+            println!("added {}", uuid);
+        }
+        CliCommand::SetProfile { uuid, profile } => {
+            set_profile(&library, &uuid, profile)?;
+            return Ok(());
+        }
+        CliCommand::SetSavePath { uuid, path } => {
+            set_save_path(&library, &uuid, path)?;
+            return Ok(());
+        }
+        CliCommand::RegisterUriHandler
+        | CliCommand::CompatTools
+        | CliCommand::AddRoot { .. }
+        | CliCommand::RemoveRoot { .. }
+        | CliCommand::Roots
+        | CliCommand::Kiosk { .. }
+        | CliCommand::SyncPair { .. }
+        | CliCommand::RemoteTokens
+        | CliCommand::RemoteRevoke { .. } => unreachable!(),
+    }
+    library.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_subcommands() {
+        let args =
+            Args::try_parse_from(["anubis", "cli", "scan-roms", "/roms", "--platform", "snes"])
+                .unwrap();
+        match args.command {
+            Some(Command::Cli {
+                command: CliCommand::ScanRoms { dir, platform },
+                ..
+            }) => {
+                assert_eq!(dir, PathBuf::from("/roms"));
+                assert_eq!(platform.as_deref(), Some("snes"));
+            }
+            c => panic!("unexpected command {:?}", c),
+        }
+        assert!(Args::try_parse_from(["anubis"]).unwrap().command.is_none());
+        let args = Args::try_parse_from(["anubis", "anubis://game/aaaa"]).unwrap();
+        assert_eq!(args.uri.as_deref(), Some("anubis://game/aaaa"));
+    }
+}
@@ -0,0 +1,132 @@
+use super::FocusId;
+use anyhow::{bail, Result};
+
+// A single row which wraps around, with the focused item in the middle, for
+// rows like recently played. The focus doesn't move along its cells, the row
+// turns under it instead: the cells show the items around the focused one,
+// and going past either end comes back around from the other. Up and down
+// leave it like any other layout. See `LayoutGridBuilder::set_carousel`.
+
+#[derive(Debug, Clone)]
+pub struct Carousel {
+    items: Vec<FocusId>,
+    /// Of the focused item.
+    index: usize,
+    /// Cells in the row, odd for there to be a middle.
+    slots: usize,
+}
+
+impl Carousel {
+    pub fn new(slots: usize) -> Result<Self> {
+        if slots.is_multiple_of(2) {
+            bail!("a carousel needs an odd number of cells, not {}", slots);
+        }
+        Ok(Self {
+            items: vec![],
+            index: 0,
+            slots,
+        })
+    }
+
+    /// The cell of the focused item.
+    pub fn middle(&self) -> usize {
+        self.slots / 2
+    }
+
+    /// How many items go around.
+    pub fn count(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Add an item at the end, just before the first one around the row.
+    pub fn insert(&mut self, focus_id: FocusId) -> Result<()> {
+        if self.items.contains(&focus_id) {
+            bail!("{} is already in the carousel", focus_id);
+        }
+        self.items.push(focus_id);
+        Ok(())
+    }
+
+    /// Take all the items off, back to the start.
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.index = 0;
+    }
+
+    /// Turn by `steps` items, to the right when positive.
+    pub fn turn(&mut self, steps: isize) {
+        if self.items.is_empty() {
+            return;
+        }
+        let len = self.items.len() as isize;
+        self.index = (self.index as isize + steps).rem_euclid(len) as usize;
+    }
+
+    /// Turn until the item is in the middle, false when it isn't there.
+    pub fn turn_to(&mut self, focus_id: &FocusId) -> bool {
+        match self.items.iter().position(|i| i == focus_id) {
+            Some(index) => {
+                self.index = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// What each cell shows, left to right. With fewer items than cells,
+    /// each is shown once, those closest to the focused one first.
+    pub fn window(&self) -> Vec<Option<FocusId>> {
+        let mut cells = vec![None; self.slots];
+        let len = self.items.len() as isize;
+        let middle = self.middle() as isize;
+        let offsets = (1..=middle).flat_map(|d| [d, -d]);
+        for offset in std::iter::once(0).chain(offsets).take(len as usize) {
+            let item = (self.index as isize + offset).rem_euclid(len) as usize;
+            cells[(middle + offset) as usize] = Some(self.items[item].clone());
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shown(sut: &Carousel) -> Vec<String> {
+        sut.window()
+            .iter()
+            .map(|c| {
+                c.as_ref()
+                    .map_or("-".to_owned(), |id| id.payload().to_owned())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wraps_around_the_focused_item() {
+        let mut sut = Carousel::new(5).unwrap();
+        assert!(Carousel::new(4).is_err());
+        assert_eq!(shown(&sut), ["-", "-", "-", "-", "-"]);
+        for id in ["a", "b"] {
+            sut.insert(FocusId::game(id)).unwrap();
+        }
+        assert_eq!(shown(&sut), ["-", "-", "a", "b", "-"]);
+        assert!(sut.insert(FocusId::game("a")).is_err());
+
+        for id in ["c", "d", "e", "f"] {
+            sut.insert(FocusId::game(id)).unwrap();
+        }
+        assert_eq!(shown(&sut), ["e", "f", "a", "b", "c"]);
+        sut.turn(-1);
+        assert_eq!(shown(&sut), ["d", "e", "f", "a", "b"]);
+        sut.turn(8);
+        assert!(sut.turn_to(&FocusId::game("c")));
+        assert_eq!(shown(&sut), ["a", "b", "c", "d", "e"]);
+        assert!(!sut.turn_to(&FocusId::game("z")));
+
+        sut.clear();
+        assert_eq!(sut.count(), 0);
+        sut.insert(FocusId::game("z")).unwrap();
+        assert_eq!(shown(&sut), ["-", "-", "z", "-", "-"]);
+    }
+}
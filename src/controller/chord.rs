@@ -0,0 +1,277 @@
+use super::sequence::SequenceConfig;
+use anyhow::{bail, Result};
+use gilrs::Button;
+use serde::{de::Error as _, Deserialize, Deserializer};
+use std::{collections::HashSet, fs, path::Path};
+
+// Buttons pressed together which do something no matter the screen, e.g.
+// Select+Start quits the game. The button pressed last completes the chord,
+// the ones before it still count as presses of their own, so chords are
+// best made of buttons that do nothing alone.
+
+/// What a chord or a sequence does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Stop the game being played.
+    ExitGame,
+    /// Pause the game being played to resume it later, see
+    /// `launcher::suspend`.
+    SuspendGame,
+    Screenshot,
+    /// See `cursor::Cursor`.
+    ToggleCursor,
+    /// Where the focus is and such, over everything.
+    ToggleDebugOverlay,
+    /// The details of the focused game.
+    GameOptions,
+    /// Open or close the quick settings over the screen.
+    QuickSettings,
+    /// Focus the first game under the previous letter with games, see
+    /// `library::index`.
+    PreviousLetter,
+    /// Same, the next letter.
+    NextLetter,
+}
+
+pub fn parse_button(name: &str) -> Result<Button> {
+    Ok(match name.to_lowercase().as_str() {
+        "south" | "a" => Button::South,
+        "east" | "b" => Button::East,
+        "north" | "y" => Button::North,
+        "west" | "x" => Button::West,
+        "select" | "back" => Button::Select,
+        "start" => Button::Start,
+        "mode" | "guide" => Button::Mode,
+        "l1" | "lb" => Button::LeftTrigger,
+        "r1" | "rb" => Button::RightTrigger,
+        "l2" | "lt" => Button::LeftTrigger2,
+        "r2" | "rt" => Button::RightTrigger2,
+        "l3" => Button::LeftThumb,
+        "r3" => Button::RightThumb,
+        "up" => Button::DPadUp,
+        "down" => Button::DPadDown,
+        "left" => Button::DPadLeft,
+        "right" => Button::DPadRight,
+        _ => bail!("unknown button {}", name),
+    })
+}
+
+/// The first name `parse_button` takes for the button, None for those it
+/// doesn't know.
+pub fn button_name(b: Button) -> Option<&'static str> {
+    Some(match b {
+        Button::South => "south",
+        Button::East => "east",
+        Button::North => "north",
+        Button::West => "west",
+        Button::Select => "select",
+        Button::Start => "start",
+        Button::Mode => "mode",
+        Button::LeftTrigger => "l1",
+        Button::RightTrigger => "r1",
+        Button::LeftTrigger2 => "l2",
+        Button::RightTrigger2 => "r2",
+        Button::LeftThumb => "l3",
+        Button::RightThumb => "r3",
+        Button::DPadUp => "up",
+        Button::DPadDown => "down",
+        Button::DPadLeft => "left",
+        Button::DPadRight => "right",
+        _ => return None,
+    })
+}
+
+/// A button by name, see `buttons`.
+pub fn button<'de, D: Deserializer<'de>>(d: D) -> Result<Button, D::Error> {
+    parse_button(&String::deserialize(d)?).map_err(D::Error::custom)
+}
+
+/// Buttons by name, in Xbox terms or by position.
+pub fn buttons<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Button>, D::Error> {
+    Vec::<String>::deserialize(d)?
+        .iter()
+        .map(|name| parse_button(name).map_err(D::Error::custom))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Chord {
+    #[serde(deserialize_with = "buttons")]
+    pub buttons: Vec<Button>,
+    pub action: Action,
+}
+
+fn default_chords() -> Vec<Chord> {
+    vec![
+        Chord {
+            buttons: vec![Button::Select, Button::Start],
+            action: Action::ExitGame,
+        },
+        Chord {
+            buttons: vec![Button::Select, Button::RightTrigger],
+            action: Action::Screenshot,
+        },
+        Chord {
+            buttons: vec![Button::Select, Button::LeftTrigger],
+            action: Action::SuspendGame,
+        },
+        Chord {
+            buttons: vec![Button::Mode, Button::DPadDown],
+            action: Action::QuickSettings,
+        },
+        Chord {
+            buttons: vec![Button::LeftTrigger, Button::DPadUp],
+            action: Action::PreviousLetter,
+        },
+        Chord {
+            buttons: vec![Button::LeftTrigger, Button::DPadDown],
+            action: Action::NextLetter,
+        },
+        Chord {
+            buttons: vec![Button::LeftThumb, Button::RightThumb],
+            action: Action::ToggleCursor,
+        },
+    ]
+}
+
+/// The chords and sequences, replacing the default ones when set.
+///
+/// ```yaml
+/// chords:
+///   - buttons: [select, start]
+///     action: exit_game
+///   - buttons: [l3, r3]
+///     action: screenshot
+/// sequences:
+///   - buttons: [up, down, up, down, y]
+///     action: toggle_debug_overlay
+/// # Milliseconds between two presses of a sequence.
+/// sequence_timeout: 1000
+/// # Run with where the screenshot goes as the last argument.
+/// screenshot_command: [grim]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChordConfig {
+    #[serde(default = "default_chords")]
+    pub chords: Vec<Chord>,
+    #[serde(flatten)]
+    pub sequences: SequenceConfig,
+    #[serde(default)]
+    pub screenshot_command: Vec<String>,
+}
+
+impl Default for ChordConfig {
+    fn default() -> Self {
+        Self {
+            chords: default_chords(),
+            sequences: SequenceConfig::default(),
+            screenshot_command: vec![],
+        }
+    }
+}
+
+impl ChordConfig {
+    /// Load the config, a missing file means the default chords.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let config: Self = serde_yaml::from_str(&fs::read_to_string(path)?)?;
+        if let Some(c) = config.chords.iter().find(|c| c.buttons.len() < 2) {
+            bail!("the chord for {:?} needs two buttons or more", c.action);
+        }
+        Ok(config)
+    }
+}
+
+/// Tells chords from the presses and releases of a gamepad.
+pub struct ChordDetector {
+    chords: Vec<Chord>,
+    pressed: HashSet<Button>,
+    /// Buttons of a chord which went off, their releases aren't presses.
+    swallowed: HashSet<Button>,
+}
+
+impl ChordDetector {
+    pub fn new(chords: Vec<Chord>) -> Self {
+        Self {
+            chords,
+            pressed: HashSet::new(),
+            swallowed: HashSet::new(),
+        }
+    }
+
+    /// The action of the chord this press completes, if any. The longest
+    /// chord wins when a few are held.
+    pub fn press(&mut self, b: Button) -> Option<Action> {
+        self.pressed.insert(b);
+        let chord = self
+            .chords
+            .iter()
+            .filter(|c| c.buttons.contains(&b))
+            .filter(|c| c.buttons.iter().all(|c| self.pressed.contains(c)))
+            .max_by_key(|c| c.buttons.len())?;
+        self.swallowed.extend(&chord.buttons);
+        Some(chord.action)
+    }
+
+    /// True when the button was part of a chord, so the release is not
+    /// handled as a press.
+    pub fn release(&mut self, b: Button) -> bool {
+        self.pressed.remove(&b);
+        self.swallowed.remove(&b)
+    }
+
+    pub fn is_swallowed(&self, b: Button) -> bool {
+        self.swallowed.contains(&b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_chords() {
+        let mut sut = ChordDetector::new(ChordConfig::default().chords);
+        assert_eq!(sut.press(Button::Select), None);
+        assert_eq!(sut.press(Button::Start), Some(Action::ExitGame));
+        assert!(sut.is_swallowed(Button::Select));
+        assert!(sut.release(Button::Start));
+        assert!(sut.release(Button::Select));
+
+        // Released in between, not a chord.
+        assert_eq!(sut.press(Button::LeftThumb), None);
+        assert!(!sut.release(Button::LeftThumb));
+        assert_eq!(sut.press(Button::RightThumb), None);
+        assert_eq!(sut.press(Button::LeftThumb), Some(Action::ToggleCursor));
+
+        // Held, the shoulder goes with each direction pressed.
+        assert_eq!(sut.press(Button::LeftTrigger), None);
+        assert_eq!(sut.press(Button::DPadDown), Some(Action::NextLetter));
+        assert!(sut.release(Button::DPadDown));
+        assert_eq!(sut.press(Button::DPadDown), Some(Action::NextLetter));
+        assert_eq!(sut.press(Button::DPadUp), Some(Action::PreviousLetter));
+    }
+
+    #[test]
+    fn loads_chords() {
+        let config: ChordConfig = serde_yaml::from_str(
+            "chords:\n  - buttons: [l3, r3]\n    action: screenshot\nscreenshot_command: [grim]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.chords,
+            [Chord {
+                buttons: vec![Button::LeftThumb, Button::RightThumb],
+                action: Action::Screenshot,
+            }]
+        );
+        assert_eq!(config.screenshot_command, ["grim"]);
+        assert!(serde_yaml::from_str::<ChordConfig>(
+            "chords:\n  - buttons: [l3, r4]\n    action: screenshot\n"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use super::{FocusRect, LayoutId};
+use std::time::Duration;
+
+// A free moving cursor on the right stick, for screens where stepping from
+// element to element is a chore. It lives in cells of a layout like the
+// focus does, the focus follows whatever element it's over. L3+R3 turns it
+// on and off, see `chord::Action::ToggleCursor`.
+//
+// gilrs doesn't expose gyros or trackpads, so it's the stick only for now.
+
+/// Stick tilts below this are drift.
+pub const DEADZONE: f32 = 0.2;
+/// Cells per second at full tilt.
+const SPEED: f32 = 4.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    pub layout: LayoutId,
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Cursor {
+    /// In the middle of the focused element.
+    pub fn on(focus: &FocusRect) -> Self {
+        let r = focus.rect;
+        Self {
+            layout: focus.layout_id.clone(),
+            x: r.x() as f32 + r.width() as f32 / 2.0,
+            y: r.y() as f32 + r.height() as f32 / 2.0,
+        }
+    }
+
+    /// Move back onto the focus if it went elsewhere, e.g. with the D-pad.
+    pub fn follow(&mut self, focus: &FocusRect) {
+        let r = focus.rect;
+        let inside = self.layout == focus.layout_id
+            && (r.x() as f32..(r.x() + r.width()) as f32).contains(&self.x)
+            && (r.y() as f32..(r.y() + r.height()) as f32).contains(&self.y);
+        if !inside {
+            *self = Self::on(focus);
+        }
+    }
+
+    /// Move for a stick tilt held for `dt`, staying in a layout of `size`
+    /// cells. Stick up is positive, like gilrs has it.
+    pub fn nudge(&mut self, stick: (f32, f32), dt: Duration, size: (usize, usize)) -> bool {
+        let (x, y) = stick;
+        let tilt = x.hypot(y);
+        if tilt < DEADZONE {
+            return false;
+        }
+        // Slow near the deadzone for aiming, squared so it ramps up.
+        let scale = ((tilt - DEADZONE) / (1.0 - DEADZONE)).min(1.0).powi(2);
+        let step = SPEED * scale * dt.as_secs_f32() / tilt;
+        // Just short of the far edge, it's the last cell's.
+        let max = |n: usize| (n as f32 - 0.001).max(0.0);
+        self.x = (self.x + x * step).clamp(0.0, max(size.0));
+        self.y = (self.y - y * step).clamp(0.0, max(size.1));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::grid::Rect;
+
+    #[test]
+    fn moves_with_the_stick() {
+        let focus = FocusRect {
+            layout_id: LayoutId::sublayout("Home", "Games"),
+            rect: Rect::new(2, 2, 1, 1).unwrap(),
+            placement: None,
+        };
+        let mut sut = Cursor::on(&focus);
+        assert_eq!((sut.x, sut.y), (2.5, 1.5));
+
+        let second = Duration::from_secs(1);
+        assert!(!sut.nudge((0.1, -0.1), second, (7, 3)));
+        assert!(sut.nudge((1.0, 0.0), second, (7, 3)));
+        assert_eq!((sut.x, sut.y), (6.5, 1.5));
+        // Up and out, stops at the edge.
+        assert!(sut.nudge((0.0, 1.0), second, (7, 3)));
+        assert_eq!(sut.y, 0.0);
+
+        sut.follow(&focus);
+        assert_eq!((sut.x, sut.y), (2.5, 1.5));
+        sut.x = 2.9;
+        sut.follow(&focus);
+        assert_eq!(sut.x, 2.9);
+    }
+}
@@ -1,12 +1,20 @@
+use super::carousel::Carousel;
+use super::list::{List, ListHeader};
+use super::payload::Payloads;
+use super::tabs::Tabs;
+use super::id::{FocusId, LayoutId};
 use anyhow::{anyhow, bail, Ok, Result};
 use gilrs::Button;
 use log::debug;
+use serde::{Deserialize, Serialize};
 use std::{
-    borrow::BorrowMut, collections::HashMap, ops::Deref, sync::{Arc, Mutex, Weak}
+    any::Any, borrow::BorrowMut, collections::HashMap, ops::Deref, sync::{Arc, Mutex, Weak}
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-/// Describes a rectangle, inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "[usize; 4]", into = "[usize; 4]")]
+/// Describes a rectangle, inclusive. `[x_start, x_end, y_start, y_end]` in
+/// layout files.
 pub struct Rect {
     x_start: usize,
     x_end: usize,
@@ -14,6 +22,20 @@ pub struct Rect {
     y_end: usize,
 }
 
+impl TryFrom<[usize; 4]> for Rect {
+    type Error = anyhow::Error;
+
+    fn try_from([x_start, x_end, y_start, y_end]: [usize; 4]) -> Result<Self> {
+        Rect::new(x_start, x_end, y_start, y_end)
+    }
+}
+
+impl From<Rect> for [usize; 4] {
+    fn from(r: Rect) -> Self {
+        [r.x_start, r.x_end, r.y_start, r.y_end]
+    }
+}
+
 impl Rect {
     pub fn new(x_start: usize, x_end: usize, y_start: usize, y_end: usize) -> Result<Self> {
         if x_end < x_start || y_end < y_start {
@@ -30,6 +52,22 @@ impl Rect {
         })
     }
 
+    pub fn x(self) -> usize {
+        self.x_start
+    }
+
+    pub fn y(self) -> usize {
+        self.y_start
+    }
+
+    pub fn width(self) -> usize {
+        self.x_end - self.x_start + 1
+    }
+
+    pub fn height(self) -> usize {
+        self.y_end - self.y_start + 1
+    }
+
     fn top_left(self) -> Point {
         Point {
             x: self.x_start as i32,
@@ -59,12 +97,54 @@ impl Rect {
     }
 
     fn transpose(self) -> Self {
-        // Maybe do it in place?
         Self {
             x_start: self.y_start,
             x_end: self.y_end,
-            y_start: self.y_start,
-            y_end: self.y_end,
+            y_start: self.x_start,
+            y_end: self.x_end,
+        }
+    }
+
+    pub fn contains_point(self, pt: Point) -> bool {
+        pt.x >= self.x_start as i32
+            && pt.x <= self.x_end as i32
+            && pt.y >= self.y_start as i32
+            && pt.y <= self.y_end as i32
+    }
+
+    /// Whether `other` is entirely inside this rect.
+    pub fn contains(self, other: Rect) -> bool {
+        self.contains_point(other.top_left()) && self.contains_point(other.bottom_right())
+    }
+
+    pub fn intersects(self, other: Rect) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// The overlapping area, if any.
+    pub fn intersection(self, other: Rect) -> Option<Rect> {
+        let x_start = self.x_start.max(other.x_start);
+        let x_end = self.x_end.min(other.x_end);
+        let y_start = self.y_start.max(other.y_start);
+        let y_end = self.y_end.min(other.y_end);
+        Rect::new(x_start, x_end, y_start, y_end).ok()
+    }
+
+    /// The smallest rect covering both.
+    pub fn union(self, other: Rect) -> Rect {
+        Self {
+            x_start: self.x_start.min(other.x_start),
+            x_end: self.x_end.max(other.x_end),
+            y_start: self.y_start.min(other.y_start),
+            y_end: self.y_end.max(other.y_end),
+        }
+    }
+
+    /// The middle cell, rounding towards the top left.
+    pub fn center(self) -> Point {
+        Point {
+            x: ((self.x_start + self.x_end) / 2) as i32,
+            y: ((self.y_start + self.y_end) / 2) as i32,
         }
     }
 }
@@ -85,16 +165,16 @@ impl Point {
     }
 }
 
-pub type LayoutID = String;
-pub type FocusID = String;
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum SpecialHandlerAction {
+/// What a button does in a layout instead of being passed on, see
+/// `LayoutGridBuilder::on_button`.
+pub enum SpecialHandlerAction {
     NavigateOutRight, // Maybe maps to right shoulder button.
     NavigateOutLeft,  // Maybe maps to left shoulder button.
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 /// For focus, we only handle these actions.
 pub enum Direction {
     Up,
@@ -187,20 +267,62 @@ where
                 self.grid[x].push(None);
             }
         }
+        self.x_size = new_x_size;
+        self.y_size = new_y_size;
+        Ok(())
+    }
+
+    /// The whole grid as a rect.
+    fn bounds(&self) -> Rect {
+        Rect {
+            x_start: 0,
+            x_end: self.x_size - 1,
+            y_start: 0,
+            y_end: self.y_size - 1,
+        }
+    }
+
+    /// Trim to the new size, the trimmed cells must be empty.
+    fn shrink(&mut self, new_x_size: usize, new_y_size: usize) -> Result<()> {
+        if new_x_size == 0 || new_y_size == 0 {
+            bail!("invalid grid size");
+        }
+        if new_x_size > self.x_size || new_y_size > self.y_size {
+            bail!(
+                "can't shrink {}x{} to {}x{}",
+                self.x_size,
+                self.y_size,
+                new_x_size,
+                new_y_size
+            );
+        }
+        for (x, col) in self.grid.iter().enumerate() {
+            for (y, cell) in col.iter().enumerate() {
+                if (x >= new_x_size || y >= new_y_size) && cell.is_some() {
+                    bail!("can't shrink over the item at {}, {}", x, y);
+                }
+            }
+        }
+        self.grid.truncate(new_x_size);
+        for col in self.grid.iter_mut() {
+            col.truncate(new_y_size);
+        }
+        self.x_size = new_x_size;
+        self.y_size = new_y_size;
         Ok(())
     }
 
     fn within_bounds(&self, x: i32, y: i32) -> bool {
-        !(x >= self.x_size as i32 || x < 0 || y >= self.y_size as i32 || y < 0)
+        self.within_bounds_point(Point { x, y })
     }
 
     fn within_bounds_point(&self, pt: Point) -> bool {
-        !(pt.x >= self.x_size as i32 || pt.x < 0 || pt.y >= self.y_size as i32 || pt.y < 0)
+        self.bounds().contains_point(pt)
     }
 
     // Fill a rect area with item.
     fn fill(&mut self, rect: Rect, elem: T) -> Result<()> {
-        if rect.x_end > self.x_size || rect.y_end > self.y_size {
+        if !self.bounds().contains(rect) {
             bail!("oversized rect detected");
         }
         // Range end is not inclusive.
@@ -222,6 +344,36 @@ where
     }
 
     // Get the element at a point.
+    /// Fill, expanding first if the rect is out of bounds.
+    fn expand_and_fill(&mut self, rect: Rect, elem: T) -> Result<()> {
+        if !self.bounds().contains(rect) {
+            self.expand(
+                self.x_size.max(rect.x_end + 1),
+                self.y_size.max(rect.y_end + 1),
+            )?;
+        }
+        self.fill(rect, elem)
+    }
+
+    fn clear(&mut self) {
+        for col in self.grid.iter_mut() {
+            col.fill(None);
+        }
+    }
+
+    /// The filled cells, column by column.
+    fn cells(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+        self.grid.iter().enumerate().flat_map(|(x, col)| {
+            col.iter().enumerate().filter_map(move |(y, cell)| {
+                let pt = Point {
+                    x: x as i32,
+                    y: y as i32,
+                };
+                cell.as_ref().map(|item| (pt, item))
+            })
+        })
+    }
+
     fn at(&self, x: usize, y: usize) -> Result<Option<T>> {
         if x >= self.x_size || y >= self.y_size {
             bail!("invalid coordinate {}, {}", x, y);
@@ -230,7 +382,8 @@ where
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
 /// Defines the growing direction of a grid.
 pub enum GrowDirection {
     /// Fill item from left -> right. Expand Y if full.
@@ -239,35 +392,207 @@ pub enum GrowDirection {
     GrowY,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// The order items fill a growable grid in.
+pub enum FillStrategy {
+    /// Left -> right, then the next row.
+    RowMajor,
+    /// Top -> bottom, then the next column.
+    ColumnMajor,
+}
+
+impl FillStrategy {
+    /// The order which only ever adds to the end of the grid.
+    pub fn natural(dir: &GrowDirection) -> Self {
+        match dir {
+            GrowDirection::GrowX => FillStrategy::RowMajor,
+            GrowDirection::GrowY => FillStrategy::ColumnMajor,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// Where the last, incomplete, row or column of a growable grid sits.
+pub enum Alignment {
+    Start,
+    Center,
+    End,
+}
+
 #[derive(Debug, Clone)]
 /// Defines the grow size and the direction for a grid.
 struct GrowConfig {
     item_x: usize,
     item_y: usize,
     grow_direction: GrowDirection,
+    fill: FillStrategy,
+    alignment: Alignment,
     current_grow_point: Point,
 }
 
+impl GrowConfig {
+    /// Slots across the axis which doesn't grow.
+    fn lanes(&self, x_size: usize, y_size: usize) -> usize {
+        match self.grow_direction {
+            GrowDirection::GrowX => (x_size / self.item_x).max(1),
+            GrowDirection::GrowY => (y_size / self.item_y).max(1),
+        }
+    }
+
+    /// Whether items can be appended without moving the others.
+    fn is_incremental(&self) -> bool {
+        self.fill == FillStrategy::natural(&self.grow_direction)
+            && self.alignment == Alignment::Start
+    }
+
+    /// Items per row (or column) in the fill order, for `n` items.
+    fn line_len(&self, lanes: usize, n: usize) -> usize {
+        if self.fill == FillStrategy::natural(&self.grow_direction) {
+            lanes
+        } else {
+            // E.g. filling columns of a grid growing downwards, spread the
+            // items over as many rows as filling the columns needs.
+            n.div_ceil(lanes).max(1)
+        }
+    }
+
+    /// Where `n` items go, in fill order.
+    fn placements(&self, lanes: usize, n: usize) -> Vec<Rect> {
+        let line_len = self.line_len(lanes, n);
+        let (along, across) = match self.fill {
+            FillStrategy::RowMajor => (self.item_x, self.item_y),
+            FillStrategy::ColumnMajor => (self.item_y, self.item_x),
+        };
+        (0..n)
+            .map(|i| {
+                let (line, pos) = (i / line_len, i % line_len);
+                let free = (line_len - line_len.min(n - line * line_len)) * along;
+                let offset = match self.alignment {
+                    Alignment::Start => 0,
+                    Alignment::Center => free / 2,
+                    Alignment::End => free,
+                };
+                let (x, y) = match self.fill {
+                    FillStrategy::RowMajor => (pos * along + offset, line * across),
+                    FillStrategy::ColumnMajor => (line * across, pos * along + offset),
+                };
+                Rect {
+                    x_start: x,
+                    x_end: x + self.item_x - 1,
+                    y_start: y,
+                    y_end: y + self.item_y - 1,
+                }
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LayoutGrid {
     grid: Grid2D<Arc<Mutex<GridItem>>>,
     layout_state: Option<Point>,
     special_handler: HashMap<Button, SpecialHandlerAction>,
     parent: Option<Weak<Mutex<LayoutGrid>>>,
-    layout_id: LayoutID,
-    sublayouts: HashMap<LayoutID, Weak<Mutex<GridItem>>>,
+    layout_id: LayoutId,
+    sublayouts: HashMap<LayoutId, Weak<Mutex<GridItem>>>,
     grow_config: Option<GrowConfig>,
+    /// Set for a carousel, see `carousel`.
+    carousel: Option<Carousel>,
+    /// Set for a list, see `list`.
+    list: Option<List>,
+    /// Set for a tab container, see `tabs`.
+    tabs: Option<Tabs<Arc<Mutex<GridItem>>>>,
+    /// Its two sublayouts are a list and the details of what's picked in
+    /// it, see `LayoutGridBuilder::set_split`.
+    split: bool,
+    held: Option<HeldItem>,
+    /// Drawn over the grid, the highest z first.
+    layers: Vec<Layer>,
+    /// Of its elements, see `set_payload`.
+    payloads: Payloads,
+}
+
+#[derive(Debug, Clone)]
+/// Elements drawn over a layout, e.g. a sidebar over the games. Where a
+/// visible layer has an element, it hides whatever is under it.
+struct Layer {
+    name: String,
+    z: u32,
+    visible: bool,
+    grid: Grid2D<Arc<Mutex<GridItem>>>,
+}
+
+#[derive(Debug, Clone)]
+/// An element picked up for reordering in a growable grid.
+struct HeldItem {
+    focus_id: FocusId,
+    /// Index it was picked up from, to put it back on cancel.
+    picked_from: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An element moved in a growable grid, indices are in insertion order.
+pub struct Reorder {
+    pub focus_id: FocusId,
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Where a focusable element is, in the cells of its layout.
+pub struct FocusRect {
+    pub layout_id: LayoutId,
+    pub rect: Rect,
+    /// Where the layout is in its parent, None for a root layout.
+    pub placement: Option<Rect>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A focus change, so the UI can animate it instead of snapping.
+pub struct FocusTransition {
+    /// None when nothing was focused before.
+    pub from: Option<FocusRect>,
+    pub to: FocusRect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What's in a cell, see `LayoutGrid::snapshot`.
+pub enum CellKind {
+    Empty,
+    Element,
+    Sublayout,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The cells of a layout and where its focus is, for the debug overlay.
+pub struct GridSnapshot {
+    pub layout_id: LayoutId,
+    pub width: usize,
+    pub height: usize,
+    /// Row by row.
+    pub cells: Vec<CellKind>,
+    pub focus: Option<(usize, usize)>,
 }
 
 #[derive(Debug, Clone)]
 /// A element in the grid.
 pub enum GridItem {
     /// An element that is focusable.
-    Element(FocusID, Rect),
+    Element(FocusId, Rect),
     /// A sublayout grid.
     Sublayout(Arc<Mutex<LayoutGrid>>, Rect),
 }
 
+impl GridItem {
+    pub fn rect(&self) -> Rect {
+        match *self {
+            GridItem::Element(_, rect) | GridItem::Sublayout(_, rect) => rect,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum NavigationDirective {
     Button(Button),
@@ -278,22 +603,24 @@ pub enum NavigationDirective {
 
 #[derive(Debug, Clone)]
 enum NavigateAcrossBundle {
-    NavigateToParent((f64, f64), NavigationDirective, LayoutID),
+    NavigateToParent((f64, f64), NavigationDirective, LayoutId),
     NavigateToChild((f64, f64), NavigationDirective),
+    /// Enter where the focus was last, or on the first element.
+    ResumeChild,
 }
 
 #[derive(Debug, Clone)]
 pub enum NavigationResult {
     /// Navigation within the layout.
-    WithinLayout(FocusID),
+    WithinLayout(FocusId),
     /// Navigation across some layout, can be multiple layouts.
-    AcrossLayout(FocusID, Weak<Mutex<LayoutGrid>>),
+    AcrossLayout(FocusId, Weak<Mutex<LayoutGrid>>),
     /// Terminal.
     NoNextItem,
 }
 
 impl LayoutGrid {
-    fn new(size_x: usize, size_y: usize, layout_id: LayoutID) -> Result<Self> {
+    fn new(size_x: usize, size_y: usize, layout_id: LayoutId) -> Result<Self> {
         Ok(Self {
             grid: Grid2D::new(size_x, size_y)?,
             layout_state: None,
@@ -302,6 +629,13 @@ impl LayoutGrid {
             layout_id: layout_id,
             sublayouts: HashMap::new(),
             grow_config: None,
+            carousel: None,
+            list: None,
+            tabs: None,
+            split: false,
+            held: None,
+            layers: vec![],
+            payloads: Payloads::default(),
         })
     }
 
@@ -309,13 +643,15 @@ impl LayoutGrid {
     fn new_growable(
         size_x: usize,
         size_y: usize,
-        layout_id: LayoutID,
+        layout_id: LayoutId,
         grow_x: usize,
         grow_y: usize,
         grow_dir: GrowDirection,
     ) -> Result<Self> {
         Ok(Self {
             grow_config: Some(GrowConfig {
+                fill: FillStrategy::natural(&grow_dir),
+                alignment: Alignment::Start,
                 grow_direction: grow_dir,
                 item_x: grow_x,
                 item_y: grow_y,
@@ -329,90 +665,101 @@ impl LayoutGrid {
         self.grow_config.is_some()
     }
 
-    fn get_sublayout_by_id(&self, id: &str) -> Result<Weak<Mutex<LayoutGrid>>> {
+    fn get_sublayout_by_id(&self, id: &LayoutId) -> Result<Weak<Mutex<LayoutGrid>>> {
         match self.sublayouts.get(id) {
             Some(i) => match *i.upgrade().unwrap().lock().unwrap() {
                 GridItem::Element(..) => bail!("unexpected element when getting layout"),
                 GridItem::Sublayout(ref s, r) => Ok(Arc::downgrade(s)),
             },
-            None => bail!("No sublayout {} found", id),
+            // The tabs of a tab container, shown or not.
+            None => self
+                .tabs_of_sublayouts()
+                .find_map(|tabs| tabs.lock().unwrap().get_sublayout_by_id(id).ok())
+                .ok_or(anyhow!("No sublayout {} found", id)),
         }
     }
 
+    /// The direct sublayouts which are tab containers.
+    fn tabs_of_sublayouts(&self) -> impl Iterator<Item = Arc<Mutex<LayoutGrid>>> + '_ {
+        self.sublayouts.values().filter_map(|i| match *i.upgrade()?.lock().unwrap() {
+            GridItem::Sublayout(ref s, _) if s.lock().unwrap().tabs.is_some() => Some(s.clone()),
+            _ => None,
+        })
+    }
+
+    /// Show another tab of a tab container, the one picked by `f`.
+    fn show_tab(
+        &mut self,
+        f: impl FnOnce(&mut Tabs<Arc<Mutex<GridItem>>>) -> Result<()>,
+    ) -> Result<Arc<Mutex<LayoutGrid>>> {
+        let layout_id = &self.layout_id;
+        let tabs = self.tabs.as_mut().ok_or(anyhow!("layout {} has no tabs", layout_id))?;
+        f(tabs)?;
+        let (_, item) = tabs.current().ok_or(anyhow!("layout {} has no tabs", layout_id))?;
+        let item = item.clone();
+        let tab = match *item.lock().unwrap() {
+            GridItem::Sublayout(ref s, _) => s.clone(),
+            GridItem::Element(..) => bail!("unexpected element in the tabs of {}", layout_id),
+        };
+        let bounds = self.grid.bounds();
+        self.grid.clear();
+        self.grid.fill(bounds, item)?;
+        Ok(tab)
+    }
+
     /// Grow the grid, assuming the config is correct.
-    pub fn insert_to_growable_grid(&mut self, focus_id: &str) -> Result<()> {
+    pub fn insert_to_growable_grid(&mut self, focus_id: FocusId) -> Result<()> {
         debug!(
             "insert focus {} into layout id {}",
             focus_id, self.layout_id
         );
+        if self.grow_config.as_ref().is_some_and(|gc| !gc.is_incremental()) {
+            // The others may move, lay everything out again.
+            let mut ids = self.growable_ids()?;
+            ids.push(focus_id);
+            return self.lay_out_growable(ids);
+        }
         if let Some(ref mut gc) = self.grow_config {
-            // Use the rect from the grow config.
-            // First, calculate the points.
-            // We expect the total grid size is a mutiple of the rect in the growing direction.
-
+            // The current grow point is the top left corner of the next slot.
             // We either have to:
             // 1. Add the item to the next available place in the grow direction.
             // 2. Shift in the non-growing direction because the row/col is full.
             //    Which might mean we have to expand the underlying Grid2D.
-            let mut new_rect = match gc.grow_direction {
-                GrowDirection::GrowX => Rect::new(
-                    gc.current_grow_point.x as usize,
-                    gc.current_grow_point.x as usize + gc.item_x - 1,
-                    gc.current_grow_point.y as usize,
-                    gc.current_grow_point.y as usize,
-                )?,
-                GrowDirection::GrowY => Rect::new(
-                    gc.current_grow_point.x as usize,
-                    gc.current_grow_point.x as usize,
-                    gc.current_grow_point.y as usize,
-                    gc.current_grow_point.y as usize + gc.item_y - 1,
-                )?,
+            let fits = match gc.grow_direction {
+                GrowDirection::GrowX => {
+                    gc.current_grow_point.x as usize + gc.item_x <= self.grid.x_size
+                }
+                GrowDirection::GrowY => {
+                    gc.current_grow_point.y as usize + gc.item_y <= self.grid.y_size
+                }
             };
-
-            if !self.grid.within_bounds_point(new_rect.top_left()) {
+            if !fits {
                 // Regardless, we have to start in a diff row/col.
-                new_rect = match gc.grow_direction {
-                    GrowDirection::GrowX => Rect::new(
-                        0,
-                        gc.item_x,
-                        gc.current_grow_point.y as usize,
-                        gc.current_grow_point.y as usize + gc.item_y,
-                    )?,
-                    GrowDirection::GrowY => Rect::new(
-                        gc.current_grow_point.x as usize,
-                        gc.current_grow_point.x as usize + gc.item_x,
-                        0,
-                        gc.item_y,
-                    )?,
-                };
-                if !self.grid.within_bounds_point(new_rect.top_left()) {
-                    // If we are still OOB, then it means we need to grow by 1 unit in the direction.
-                    match gc.grow_direction {
-                        GrowDirection::GrowX => {
-                            self.grid
-                                .expand(self.grid.x_size, self.grid.y_size + gc.item_y)?;
-                        }
-                        GrowDirection::GrowY => {
-                            self.grid
-                                .expand(self.grid.x_size + gc.item_x, self.grid.y_size)?;
-                        }
+                match gc.grow_direction {
+                    GrowDirection::GrowX => {
+                        gc.current_grow_point.x = 0;
+                        gc.current_grow_point.y += gc.item_y as i32;
+                    }
+                    GrowDirection::GrowY => {
+                        gc.current_grow_point.x += gc.item_x as i32;
+                        gc.current_grow_point.y = 0;
                     }
                 }
             }
 
-            // Finally, fill the rect.
-            let item = Arc::new(Mutex::new(GridItem::Element(focus_id.to_owned(), new_rect)));
-            self.grid.fill(new_rect, item.clone())?;
+            let new_rect = Rect::new(
+                gc.current_grow_point.x as usize,
+                gc.current_grow_point.x as usize + gc.item_x - 1,
+                gc.current_grow_point.y as usize,
+                gc.current_grow_point.y as usize + gc.item_y - 1,
+            )?;
+
+            let item = Arc::new(Mutex::new(GridItem::Element(focus_id, new_rect)));
+            self.grid.expand_and_fill(new_rect, item)?;
             // Update our current pos.
             match gc.grow_direction {
-                GrowDirection::GrowX => {
-                    gc.current_grow_point.x = new_rect.x_end as i32 + 1;
-                    gc.current_grow_point.y = new_rect.y_end as i32;
-                }
-                GrowDirection::GrowY => {
-                    gc.current_grow_point.x = new_rect.x_end as i32;
-                    gc.current_grow_point.y = new_rect.y_end as i32 + 1;
-                }
+                GrowDirection::GrowX => gc.current_grow_point.x += gc.item_x as i32,
+                GrowDirection::GrowY => gc.current_grow_point.y += gc.item_y as i32,
             }
 
             Ok(())
@@ -421,7 +768,453 @@ impl LayoutGrid {
         }
     }
 
-    /// Process a NavigationDirective and returns the next FocusID, with a
+    /// Add an item to a carousel, after the last one.
+    pub fn insert_to_carousel(&mut self, focus_id: FocusId) -> Result<()> {
+        let layout_id = &self.layout_id;
+        self.carousel
+            .as_mut()
+            .ok_or(anyhow!("layout {} isn't a carousel", layout_id))?
+            .insert(focus_id)?;
+        self.lay_out_carousel()
+    }
+
+    /// Take all the items off a carousel, e.g. to fill it again.
+    pub fn clear_carousel(&mut self) -> Result<()> {
+        let layout_id = &self.layout_id;
+        self.carousel
+            .as_mut()
+            .ok_or(anyhow!("layout {} isn't a carousel", layout_id))?
+            .clear();
+        self.lay_out_carousel()
+    }
+
+    /// Put the items around the focused one in the cells, the focused one in
+    /// the middle.
+    fn lay_out_carousel(&mut self) -> Result<()> {
+        let Some(ref carousel) = self.carousel else {
+            return Ok(());
+        };
+        let (window, middle) = (carousel.window(), carousel.middle());
+        self.grid.clear();
+        for (x, id) in window.into_iter().enumerate() {
+            if let Some(id) = id {
+                let rect = Rect::new(x, x, 0, 0)?;
+                self.grid.fill(rect, Arc::new(Mutex::new(GridItem::Element(id, rect))))?;
+            }
+        }
+        if self.layout_state.is_some() {
+            self.set_point(middle, 0)?;
+        }
+        Ok(())
+    }
+
+    /// Turn a carousel, or bring the item focused to its middle when
+    /// `steps` is 0.
+    fn turn_carousel(&mut self, steps: isize) -> Result<NavigationResult> {
+        let focused = self.current_item().ok().map(|(id, _)| id);
+        let Some(ref mut carousel) = self.carousel else {
+            bail!("layout {} isn't a carousel", self.layout_id);
+        };
+        if let Some(ref id) = focused {
+            carousel.turn_to(id);
+        }
+        carousel.turn(steps);
+        let middle = carousel.middle();
+        self.set_point(middle, 0)?;
+        self.lay_out_carousel()?;
+        match self.current_item() {
+            core::result::Result::Ok((id, _)) => Ok(NavigationResult::WithinLayout(id)),
+            Err(_) => Ok(NavigationResult::NoNextItem),
+        }
+    }
+
+    /// Start a group of a list, with its header on the next row.
+    pub fn add_list_group(&mut self, title: &str) -> Result<()> {
+        let layout_id = &self.layout_id;
+        self.list
+            .as_mut()
+            .ok_or(anyhow!("layout {} isn't a list", layout_id))?
+            .add_group(title)?;
+        self.lay_out_list()
+    }
+
+    /// Add an item to a list, at the end of its last group.
+    pub fn insert_to_list(&mut self, focus_id: FocusId) -> Result<()> {
+        let layout_id = &self.layout_id;
+        self.list
+            .as_mut()
+            .ok_or(anyhow!("layout {} isn't a list", layout_id))?
+            .push(focus_id)?;
+        self.lay_out_list()
+    }
+
+    /// The headers of a list, none for other layouts.
+    pub fn list_headers(&self) -> Vec<ListHeader> {
+        self.list.as_ref().map(|l| l.headers()).unwrap_or_default()
+    }
+
+    /// One item per row across the layout, header rows left empty.
+    fn lay_out_list(&mut self) -> Result<()> {
+        let Some(ref list) = self.list else {
+            return Ok(());
+        };
+        let width = self.grid.bounds().x_end;
+        self.grid.clear();
+        for (y, id) in list.rows().into_iter().enumerate() {
+            let rect = Rect::new(0, width, y, y)?;
+            match id {
+                Some(id) => {
+                    let item = Arc::new(Mutex::new(GridItem::Element(id, rect)));
+                    self.grid.expand_and_fill(rect, item)?;
+                }
+                None => self.grid.expand(self.grid.x_size, self.grid.y_size.max(y + 1))?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Elements of a growable grid, in insertion order.
+    fn growable_items(&self) -> Result<Vec<Arc<Mutex<GridItem>>>> {
+        let gc = self
+            .grow_config
+            .as_ref()
+            .ok_or(anyhow!("no grow_config set for layoutId {}", self.layout_id))?;
+        let mut items = self.item_refs();
+        if gc.fill == FillStrategy::ColumnMajor {
+            items.sort_by_key(|(rect, _)| (rect.x_start, rect.y_start));
+        }
+        Ok(items.into_iter().map(|(_, item)| item).collect())
+    }
+
+    /// Each item once, instead of once per cell it covers, the visible
+    /// layers' included. Row by row, by the top left cell.
+    fn item_refs(&self) -> Vec<(Rect, Arc<Mutex<GridItem>>)> {
+        let visible_layers = self.layers.iter().filter(|l| l.visible);
+        let mut items: Vec<(Rect, Arc<Mutex<GridItem>>)> = std::iter::once(&self.grid)
+            .chain(visible_layers.map(|l| &l.grid))
+            .flat_map(|grid| grid.cells())
+            .filter_map(|(pt, item)| {
+                let rect = item.lock().unwrap().rect();
+                (rect.top_left() == pt).then(|| (rect, item.clone()))
+            })
+            .collect();
+        items.sort_by_key(|(rect, _)| (rect.y_start, rect.x_start));
+        items
+    }
+
+    /// The items of this layout and its visible layers, row by row.
+    /// Sublayouts aren't looked into.
+    pub fn items(&self) -> impl Iterator<Item = GridItem> {
+        self.item_refs()
+            .into_iter()
+            .map(|(_, item)| item.lock().unwrap().clone())
+    }
+
+    /// The focusable elements of this layout, row by row.
+    pub fn elements(&self) -> impl Iterator<Item = (FocusId, Rect)> {
+        self.items().filter_map(|item| match item {
+            GridItem::Element(id, rect) => Some((id, rect)),
+            GridItem::Sublayout(..) => None,
+        })
+    }
+
+    /// The direct sublayouts, with where they are in this layout.
+    pub fn sublayouts(&self) -> impl Iterator<Item = (Arc<Mutex<LayoutGrid>>, Rect)> {
+        self.items().filter_map(|item| match item {
+            GridItem::Sublayout(sub, rect) => Some((sub, rect)),
+            GridItem::Element(..) => None,
+        })
+    }
+
+    /// Where the element is, sublayouts are not searched.
+    pub fn find_rect(&self, focus_id: &FocusId) -> Option<Rect> {
+        self.elements()
+            .find(|(id, _)| id == focus_id)
+            .map(|(_, rect)| rect)
+    }
+
+    /// Attach typed data to an element of the layout, e.g. what it does,
+    /// dropped with the element.
+    pub fn set_payload<T: Any + Send + Sync>(
+        &mut self,
+        focus_id: &FocusId,
+        payload: T,
+    ) -> Result<()> {
+        if self.find_rect(focus_id).is_none() {
+            bail!("no {} in {} to attach to", focus_id, self.layout_id);
+        }
+        self.payloads.insert(focus_id.clone(), payload);
+        Ok(())
+    }
+
+    /// What's attached to the element, if it's a T.
+    pub fn payload<T: Any + Clone>(&self, focus_id: &FocusId) -> Option<T> {
+        self.payloads.get(focus_id)
+    }
+
+    /// The element at a point given in cells, fractions count as the cell
+    /// they're in. Sublayouts aren't looked into.
+    pub fn element_at(&self, x: f32, y: f32) -> Option<(FocusId, Rect)> {
+        let bounds = self.grid.bounds();
+        if !(x >= 0.0 && y >= 0.0) {
+            return None;
+        }
+        let (x, y) = (x.floor() as usize, y.floor() as usize);
+        if x > bounds.x_end || y > bounds.y_end {
+            return None;
+        }
+        match *self.item_at(x, y).ok()??.lock().unwrap() {
+            GridItem::Element(ref id, rect) => Some((id.clone(), rect)),
+            GridItem::Sublayout(..) => None,
+        }
+    }
+
+    pub fn layout_id(&self) -> &LayoutId {
+        &self.layout_id
+    }
+
+    fn growable_ids(&self) -> Result<Vec<FocusId>> {
+        self.growable_items()?
+            .iter()
+            .map(|item| match *item.lock().unwrap() {
+                GridItem::Element(ref id, _) => Ok(id.clone()),
+                GridItem::Sublayout(..) => bail!("unexpected sublayout in a growable grid"),
+            })
+            .collect()
+    }
+
+    /// Put the elements of a growable grid where the fill says, in order.
+    /// The focus follows the focused element.
+    fn lay_out_growable(&mut self, ids: Vec<FocusId>) -> Result<()> {
+        let gc = self
+            .grow_config
+            .clone()
+            .ok_or(anyhow!("no grow_config set for layoutId {}", self.layout_id))?;
+        let focused = self.current_item().ok().map(|(id, _)| id);
+        let lanes = gc.lanes(self.grid.x_size, self.grid.y_size);
+        let rects = gc.placements(lanes, ids.len());
+        // Where the next incremental insert goes.
+        let next = gc.placements(lanes, ids.len() + 1).pop().unwrap();
+        self.grid.clear();
+        for (id, rect) in ids.into_iter().zip(rects) {
+            if focused.as_ref() == Some(&id) {
+                self.layout_state = Some(rect.top_left());
+            }
+            let item = Arc::new(Mutex::new(GridItem::Element(id, rect)));
+            self.grid.expand_and_fill(rect, item)?;
+        }
+        if let Some(ref mut gc) = self.grow_config {
+            gc.current_grow_point = next.top_left();
+        }
+        Ok(())
+    }
+
+    /// Take an element out of a growable grid, the ones after it move up.
+    /// When it was focused, the one taking its place is focused instead.
+    pub fn remove_from_growable_grid(&mut self, focus_id: &FocusId) -> Result<()> {
+        let was_focused = self
+            .current_item()
+            .is_ok_and(|(id, _)| id == *focus_id);
+        let mut ids = self.growable_ids()?;
+        let index = self.growable_index(focus_id)?;
+        ids.remove(index);
+        if self.held().is_some_and(|held| held == focus_id) {
+            self.held = None;
+        }
+        self.payloads.remove(focus_id);
+        self.lay_out_growable(ids)?;
+        self.shrink_to_fit()?;
+        if was_focused {
+            let items = self.growable_items()?;
+            self.layout_state = items
+                .get(index.min(items.len().saturating_sub(1)))
+                .map(|item| item.lock().unwrap().rect().top_left());
+        }
+        Ok(())
+    }
+
+    /// Trim the empty rows (or columns) the growable grid has grown past,
+    /// e.g. after removing elements.
+    pub fn shrink_to_fit(&mut self) -> Result<()> {
+        let gc = self
+            .grow_config
+            .clone()
+            .ok_or(anyhow!("no grow_config set for layoutId {}", self.layout_id))?;
+        let mut used = (0, 0);
+        for item in self.growable_items()? {
+            let rect = item.lock().unwrap().rect();
+            used = (used.0.max(rect.x_end + 1), used.1.max(rect.y_end + 1));
+        }
+        // Keep a line for the next insert, and the size across.
+        match gc.grow_direction {
+            GrowDirection::GrowX => self.grid.shrink(self.grid.x_size, used.1.max(gc.item_y))?,
+            GrowDirection::GrowY => self.grid.shrink(used.0.max(gc.item_x), self.grid.y_size)?,
+        }
+        let n = self.growable_items()?.len();
+        let lanes = gc.lanes(self.grid.x_size, self.grid.y_size);
+        if let Some(ref mut gc) = self.grow_config {
+            gc.current_grow_point = gc.placements(lanes, n + 1).pop().unwrap().top_left();
+        }
+        if let Some(pt) = self.layout_state {
+            if !self.grid.within_bounds_point(pt) {
+                self.layout_state = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// What's in each cell, visible layers included.
+    pub fn snapshot(&self) -> GridSnapshot {
+        let (width, height) = self.size();
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let kind = match self.item_at(x, y).ok().flatten() {
+                    None => CellKind::Empty,
+                    Some(item) => match *item.lock().unwrap() {
+                        GridItem::Element(..) => CellKind::Element,
+                        GridItem::Sublayout(..) => CellKind::Sublayout,
+                    },
+                };
+                cells.push(kind);
+            }
+        }
+        GridSnapshot {
+            layout_id: self.layout_id.clone(),
+            width,
+            height,
+            cells,
+            focus: self.layout_state.map(|p| (p.x as usize, p.y as usize)),
+        }
+    }
+
+    /// Columns and rows of the grid.
+    pub fn size(&self) -> (usize, usize) {
+        (self.grid.x_size, self.grid.y_size)
+    }
+
+    /// Change how the growable grid fills, laying it out again.
+    pub fn set_fill(&mut self, fill: FillStrategy, alignment: Alignment) -> Result<()> {
+        let ids = self.growable_ids()?;
+        if let Some(ref mut gc) = self.grow_config {
+            gc.fill = fill;
+            gc.alignment = alignment;
+        }
+        self.lay_out_growable(ids)
+    }
+
+    /// Move the element at `from` to `to`, shifting the ones in between.
+    /// The focus follows the moved element.
+    fn move_growable_item(&mut self, from: usize, to: usize) -> Result<()> {
+        let items = self.growable_items()?;
+        let mut ids = self.growable_ids()?;
+        if from >= ids.len() || to >= ids.len() {
+            bail!("can't move {} to {}, only {} items", from, to, ids.len());
+        }
+        let id = ids.remove(from);
+        ids.insert(to, id);
+        for (item, id) in items.iter().zip(ids) {
+            if let GridItem::Element(ref mut elem_id, _) = *item.lock().unwrap() {
+                *elem_id = id;
+            }
+        }
+        if let GridItem::Element(_, rect) = *items[to].lock().unwrap() {
+            let pt = rect.top_left();
+            self.set_point(pt.x as usize, pt.y as usize)?;
+        }
+        Ok(())
+    }
+
+    /// Index of the element in insertion order.
+    fn growable_index(&self, focus_id: &FocusId) -> Result<usize> {
+        self.growable_items()?
+            .iter()
+            .position(|item| match *item.lock().unwrap() {
+                GridItem::Element(ref id, _) => id == focus_id,
+                GridItem::Sublayout(..) => false,
+            })
+            .ok_or(anyhow!("no element {} in layout {}", focus_id, self.layout_id))
+    }
+
+    /// Pick up the focused element to reorder it.
+    pub fn pick_up(&mut self) -> Result<FocusId> {
+        if !self.is_growable() {
+            bail!("layout {} can't be reordered", self.layout_id);
+        }
+        let (focus_id, _) = self.current_item()?;
+        let picked_from = self.growable_index(&focus_id)?;
+        self.held = Some(HeldItem {
+            focus_id: focus_id.clone(),
+            picked_from,
+        });
+        Ok(focus_id)
+    }
+
+    pub fn held(&self) -> Option<&FocusId> {
+        self.held.as_ref().map(|h| &h.focus_id)
+    }
+
+    /// Move the held element by one slot, None when it's already at the edge.
+    pub fn move_held(&mut self, d: Direction) -> Result<Option<Reorder>> {
+        let focus_id = match self.held {
+            Some(ref h) => h.focus_id.clone(),
+            None => bail!("nothing held in layout {}", self.layout_id),
+        };
+        let count = self.growable_items()?.len();
+        let gc = self.grow_config.as_ref().unwrap();
+        let lanes = gc.lanes(self.grid.x_size, self.grid.y_size);
+        let per_line = gc.line_len(lanes, count) as i32;
+        let step = match (gc.fill, d) {
+            (FillStrategy::RowMajor, Direction::Left)
+            | (FillStrategy::ColumnMajor, Direction::Up) => -1,
+            (FillStrategy::RowMajor, Direction::Right)
+            | (FillStrategy::ColumnMajor, Direction::Down) => 1,
+            (FillStrategy::RowMajor, Direction::Up)
+            | (FillStrategy::ColumnMajor, Direction::Left) => -per_line,
+            (FillStrategy::RowMajor, Direction::Down)
+            | (FillStrategy::ColumnMajor, Direction::Right) => per_line,
+        };
+        let from = self.growable_index(&focus_id)?;
+        let to = from as i32 + step;
+        if to < 0 || to as usize >= count {
+            return Ok(None);
+        }
+        self.move_growable_item(from, to as usize)?;
+        Ok(Some(Reorder {
+            focus_id,
+            from,
+            to: to as usize,
+        }))
+    }
+
+    /// Drop the held element where it is, returns where it moved overall.
+    pub fn drop_held(&mut self) -> Result<Option<Reorder>> {
+        let Some(held) = self.held.take() else {
+            return Ok(None);
+        };
+        let to = self.growable_index(&held.focus_id)?;
+        Ok(Some(Reorder {
+            focus_id: held.focus_id,
+            from: held.picked_from,
+            to,
+        }))
+    }
+
+    /// Put the held element back where it was picked up.
+    pub fn cancel_held(&mut self) -> Result<Option<Reorder>> {
+        let Some(held) = self.held.take() else {
+            return Ok(None);
+        };
+        let from = self.growable_index(&held.focus_id)?;
+        self.move_growable_item(from, held.picked_from)?;
+        Ok(Some(Reorder {
+            focus_id: held.focus_id,
+            from,
+            to: held.picked_from,
+        }))
+    }
+
+    /// Process a NavigationDirective and returns the next FocusId, with a
     /// weak reference to the next LayoutGrid.
     fn navigate(&mut self, directive: NavigationDirective) -> Result<NavigationResult> {
         // Check for special handler first.
@@ -449,6 +1242,17 @@ impl LayoutGrid {
             }
         }
 
+        // A carousel turns sideways instead, up and down leave it as usual.
+        if let NavigationDirective::Direction(d) = directive {
+            if self.carousel.as_ref().is_some_and(|c| c.count() > 1) {
+                match d {
+                    Direction::Left => return self.turn_carousel(-1),
+                    Direction::Right => return self.turn_carousel(1),
+                    Direction::Up | Direction::Down => {}
+                }
+            }
+        }
+
         // Grid navigation.
         // First, check if we are navigating out.
         if let NavigationDirective::Direction(d) = directive {
@@ -500,7 +1304,7 @@ impl LayoutGrid {
                     while self.grid.within_bounds(dir_point.x, dir_point.y) {
                         // Check what's at loc.
                         // Prohibits sublayout when doing sideway navigation.
-                        match self.grid.at(dir_point.x as usize, dir_point.y as usize)? {
+                        match self.item_at(dir_point.x as usize, dir_point.y as usize)? {
                             Some(item) => match *item.clone().lock().unwrap() {
                                 GridItem::Sublayout(..) => {
                                     break;
@@ -529,7 +1333,11 @@ impl LayoutGrid {
             return Ok(NavigationResult::NoNextItem);
         }
 
-        // Noop directive.
+        // Noop directive. What's focused in a carousel, e.g. clicked, comes
+        // to the middle.
+        if self.carousel.is_some() {
+            self.turn_carousel(0)?;
+        }
         let (focus_id, _) = self.current_item()?;
         Ok(NavigationResult::WithinLayout(focus_id.to_owned()))
     }
@@ -546,7 +1354,7 @@ impl LayoutGrid {
             "try navigate to x{}, y{}, with {:?}, layout_id {}",
             x, y, directive, self.layout_id
         );
-        match self.grid.at(x, y)? {
+        match self.item_at(x, y)? {
             Some(item) => match *item.clone().lock().unwrap() {
                 GridItem::Element(ref focus_id, _) => {
                     self.set_point(x, y)?;
@@ -554,15 +1362,21 @@ impl LayoutGrid {
                 }
                 GridItem::Sublayout(ref sublayout, rect) => {
                     // Calculate the x, y value relative to child.
-                    let x_in = (x as i32 - rect.x_start as i32) as f64
-                        / (rect.x_end as i32 - rect.x_start as i32) as f64;
-                    let y_in = (y as i32 - rect.y_start as i32) as f64
-                        / (rect.y_end as i32 - rect.y_start as i32) as f64;
-
-                    match sublayout.lock().unwrap().navigate_into(
-                        NavigateAcrossBundle::NavigateToChild((x_in, y_in), directive),
-                    )? {
-                        // Maps within layout to across layout.
+                    let x_in = fraction(x, rect.x_start, rect.x_end);
+                    let y_in = fraction(y, rect.y_start, rect.y_end);
+                    // Across a split, the rows of a pane have nothing to do
+                    // with those of the other.
+                    let bundle = match directive {
+                        NavigationDirective::Direction(Direction::Left | Direction::Right)
+                            if self.split =>
+                        {
+                            NavigateAcrossBundle::ResumeChild
+                        }
+                        _ => NavigateAcrossBundle::NavigateToChild((x_in, y_in), directive),
+                    };
+
+                    match sublayout.lock().unwrap().navigate_into(bundle)? {
+                        // Maps within layout to across layout.
                         NavigationResult::WithinLayout(s) => Ok(Some(
                             NavigationResult::AcrossLayout(s, Arc::downgrade(&sublayout)),
                         )),
@@ -570,7 +1384,8 @@ impl LayoutGrid {
                         NavigationResult::AcrossLayout(s, w) => {
                             Ok(Some(NavigationResult::AcrossLayout(s, w)))
                         }
-                        NavigationResult::NoNextItem => Ok(Some(NavigationResult::NoNextItem)),
+                        // Nothing that way in the child, look past it.
+                        NavigationResult::NoNextItem => Ok(None),
                     }
                 }
             },
@@ -578,14 +1393,89 @@ impl LayoutGrid {
         }
     }
 
-    fn current_item(&self) -> Result<(FocusID, Rect)> {
+    /// Where the current item is.
+    fn focus_rect(&self) -> Option<FocusRect> {
+        let (_, rect) = self.current_item().ok()?;
+        Some(self.focus_rect_of(rect))
+    }
+
+    fn focus_rect_of(&self, rect: Rect) -> FocusRect {
+        let placement = self.parent.as_ref().and_then(|p| {
+            let parent = p.upgrade()?;
+            let parent = parent.lock().unwrap();
+            let item = parent.sublayouts.get(&self.layout_id)?.upgrade()?;
+            let item = item.lock().unwrap();
+            match *item {
+                GridItem::Sublayout(_, rect) => Some(rect),
+                GridItem::Element(..) => None,
+            }
+        });
+        FocusRect {
+            layout_id: self.layout_id.clone(),
+            rect,
+            placement,
+        }
+    }
+
+    /// What's at the point, the topmost visible layer having something
+    /// there wins over the grid.
+    fn item_at(&self, x: usize, y: usize) -> Result<Option<Arc<Mutex<GridItem>>>> {
+        for layer in self.layers.iter().filter(|l| l.visible) {
+            if let Some(item) = layer.grid.at(x, y)? {
+                return Ok(Some(item));
+            }
+        }
+        self.grid.at(x, y)
+    }
+
+    /// Add a layer over the grid, higher `z` is drawn over lower ones.
+    fn add_layer(&mut self, name: &str, z: u32, visible: bool) -> Result<()> {
+        if self.is_growable() {
+            bail!("layout {} is growable, it can't have layers", self.layout_id);
+        }
+        if self.layers.iter().any(|l| l.name == name) {
+            bail!("layout {} already has a layer {}", self.layout_id, name);
+        }
+        let layer = Layer {
+            name: name.to_owned(),
+            z,
+            visible,
+            grid: Grid2D::new(self.grid.x_size, self.grid.y_size)?,
+        };
+        let at = self.layers.partition_point(|l| l.z >= z);
+        self.layers.insert(at, layer);
+        Ok(())
+    }
+
+    fn layer_mut(&mut self, name: &str) -> Result<&mut Layer> {
+        let layout_id = &self.layout_id;
+        self.layers
+            .iter_mut()
+            .find(|l| l.name == name)
+            .ok_or(anyhow!("no layer {} in layout {}", name, layout_id))
+    }
+
+    /// Show or hide a layer. When the focus was on an element of a layer
+    /// being hidden, it goes to what's under it, or the first element.
+    pub fn set_layer_visible(&mut self, name: &str, visible: bool) -> Result<()> {
+        self.layer_mut(name)?.visible = visible;
+        if self.layout_state.is_some() && self.current_item().is_err() {
+            self.layout_state = self.elements().next().map(|(_, rect)| rect.top_left());
+        }
+        Ok(())
+    }
+
+    fn current_item(&self) -> Result<(FocusId, Rect)> {
         let curr_point = self.layout_state.ok_or(anyhow!("no layout state"))?;
-        match self.grid.at(curr_point.x as usize, curr_point.y as usize)? {
+        match self.item_at(curr_point.x as usize, curr_point.y as usize)? {
             Some(elem) => match *elem.lock().unwrap() {
                 GridItem::Element(ref id, ref rect) => Ok((id.clone(), rect.clone())),
-                GridItem::Sublayout(ref layout, _) => bail!(
-                    "layout id: {} is a sublayout, cannot set focus",
-                    layout.lock().unwrap().layout_id
+                // The sublayout may be locked by whoever is navigating out of it.
+                GridItem::Sublayout(_, rect) => bail!(
+                    "{} {} is a sublayout at {:?}, cannot set focus",
+                    curr_point.x,
+                    curr_point.y,
+                    rect
                 ),
             },
             None => bail!("No element at {} {}", curr_point.x, curr_point.y),
@@ -615,8 +1505,8 @@ impl LayoutGrid {
         if let Some(p) = self.parent.clone() {
             if let Some(g) = p.upgrade() {
                 // Calculate the out percentage.
-                let x_out = out_from.x as f64 / self.grid.x_size as f64;
-                let y_out = out_from.y as f64 / self.grid.y_size as f64;
+                let x_out = fraction(out_from.x as usize, 0, self.grid.x_size - 1);
+                let y_out = fraction(out_from.y as usize, 0, self.grid.y_size - 1);
                 return match g.lock().unwrap().navigate_into(
                     NavigateAcrossBundle::NavigateToParent(
                         (x_out, y_out),
@@ -666,40 +1556,114 @@ impl LayoutGrid {
                         bail!("unexpected element when looking for sublayout")
                     }
                     GridItem::Sublayout(_, rect) => {
-                        // Calculate the new point relative to self.
-                        self.set_point(
-                            ((rect.x_end as f64 - rect.x_start as f64) * exit_x) as usize,
-                            ((rect.y_end as f64 - rect.y_start as f64) * exit_y) as usize,
-                        )?;
+                        // Calculate the new point relative to self, on the
+                        // edge we are leaving from.
+                        let pt = edge_point(rect, (exit_x, exit_y), &directive, true);
+                        self.set_point(pt.x as usize, pt.y as usize)?;
                     }
                 }
-                // Check if we landed on something.
-                match self.try_navigate_to_point(
-                    self.layout_state.unwrap().x as usize,
-                    self.layout_state.unwrap().y as usize,
-                    directive.clone(),
-                )? {
-                    Some(r) => return Ok(r),
-                    None => {}
-                }
-                // If not, process the directive again within the child.
+                // Carry on from the edge. Landing on the sublayout again would
+                // lock the child, which is still navigating.
                 self.navigate(directive)
             }
             // For parent -> child, parent need to tell the child the location of entry.
+            // A carousel is entered on the item in the middle, wherever from.
+            NavigateAcrossBundle::NavigateToChild(_, _) if self.carousel.is_some() => {
+                let middle = self.carousel.as_ref().unwrap().middle();
+                self.set_point(middle, 0)?;
+                self.turn_carousel(0)
+            }
             NavigateAcrossBundle::NavigateToChild((in_x, in_y), directive) => {
-                let x = (self.grid.x_size - 1) * in_x as usize;
-                let y = (self.grid.y_size - 1) * in_y as usize;
-                self.set_point(x, y)?;
-                // Check if we landed on something.
-                match self.try_navigate_to_point(x, y, directive.clone())? {
-                    Some(r) => return Ok(r),
-                    None => {
-                        // If not, process the directive again within the child.
-                        self.navigate(directive)
+                let bounds = self.grid.bounds();
+                let entry = edge_point(bounds, (in_x, in_y), &directive, false);
+                self.set_point(entry.x as usize, entry.y as usize)?;
+                self.enter_from(entry, directive)
+            }
+            NavigateAcrossBundle::ResumeChild => match self.current_item() {
+                core::result::Result::Ok((id, _)) => Ok(NavigationResult::WithinLayout(id)),
+                Err(_) => {
+                    let down = NavigationDirective::Direction(Direction::Down);
+                    self.set_point(0, 0)?;
+                    self.enter_from(Point::default(), down)
+                }
+            },
+        }
+    }
+
+    /// Find the element to focus when entering, line by line from the entry
+    /// in the direction, the closest to the entry first on each line. Never
+    /// leaves the layout, the parent is still navigating.
+    fn enter_from(
+        &mut self,
+        entry: Point,
+        directive: NavigationDirective,
+    ) -> Result<NavigationResult> {
+        let d = match directive {
+            NavigationDirective::Direction(d) => d,
+            _ => Direction::Down,
+        };
+        let (x_dir, y_dir) = d.as_dir_vector();
+        let ((side_x, side_y), _) = d.as_side_dir_vectors();
+        let (side_x, side_y) = (side_x.abs() as i32, side_y.abs() as i32);
+        let span = self.grid.x_size.max(self.grid.y_size) as i32;
+        let mut line = entry;
+        while self.grid.within_bounds_point(line) {
+            for offset in 0..span {
+                for sign in [-1, 1] {
+                    if offset == 0 && sign == 1 {
+                        continue;
+                    }
+                    let pt = line.add(side_x * offset * sign, side_y * offset * sign);
+                    if !self.grid.within_bounds_point(pt) {
+                        continue;
+                    }
+                    if let Some(r) =
+                        self.try_navigate_to_point(pt.x as usize, pt.y as usize, directive.clone())?
+                    {
+                        return Ok(r);
                     }
                 }
             }
+            line = line.add(x_dir as i32, y_dir as i32);
         }
+        Ok(NavigationResult::NoNextItem)
+    }
+}
+
+/// Where the cell at `pos` starts within the cells `start..=end`, from 0
+/// (included) to 1 (excluded).
+fn fraction(pos: usize, start: usize, end: usize) -> f64 {
+    (pos - start) as f64 / (end - start + 1) as f64
+}
+
+/// The cell of `start..=end` at the fraction.
+fn scale(start: usize, end: usize, fraction: f64) -> usize {
+    let cells = end - start + 1;
+    start + ((cells as f64 * fraction.clamp(0.0, 1.0)) as usize).min(cells - 1)
+}
+
+/// The cell of the rect to enter or leave from. It's on the side facing the
+/// direction, e.g. leaving downwards is from the bottom row, entering
+/// downwards is from the top one.
+fn edge_point(
+    rect: Rect,
+    (x_frac, y_frac): (f64, f64),
+    directive: &NavigationDirective,
+    leaving: bool,
+) -> Point {
+    let mut x = scale(rect.x_start, rect.x_end, x_frac);
+    let mut y = scale(rect.y_start, rect.y_end, y_frac);
+    if let NavigationDirective::Direction(d) = directive {
+        match (d, leaving) {
+            (Direction::Down, false) | (Direction::Up, true) => y = rect.y_start,
+            (Direction::Down, true) | (Direction::Up, false) => y = rect.y_end,
+            (Direction::Right, false) | (Direction::Left, true) => x = rect.x_start,
+            (Direction::Right, true) | (Direction::Left, false) => x = rect.x_end,
+        }
+    }
+    Point {
+        x: x as i32,
+        y: y as i32,
     }
 }
 
@@ -707,15 +1671,44 @@ impl LayoutGrid {
 pub struct LayoutGridBuilder {
     size_x: usize,
     size_y: usize,
-    rects: Vec<(Rect, FocusID)>,
-    sublayouts: Vec<(Rect, LayoutID, LayoutGridBuilder)>,
-    layout_id: LayoutID,
+    rects: Vec<(Rect, FocusId)>,
+    sublayouts: Vec<(Rect, LayoutId, LayoutGridBuilder)>,
+    layout_id: LayoutId,
     is_root_builder: bool,
     growable_config: Option<(usize, usize, GrowDirection)>,
+    growable_fill: Option<(FillStrategy, Alignment)>,
+    carousel: bool,
+    list: bool,
+    tabs: bool,
+    split: bool,
+    special: Vec<(Button, SpecialHandlerAction)>,
+    layers: Vec<LayerBuilder>,
+}
+
+#[derive(Debug)]
+/// Elements of a layer, see `LayoutGridBuilder::with_layer`.
+pub struct LayerBuilder {
+    name: String,
+    z: u32,
+    visible: bool,
+    rects: Vec<(Rect, FocusId)>,
+}
+
+impl LayerBuilder {
+    pub fn add_element(&mut self, rect: Rect, focus_id: FocusId) -> Result<&mut Self> {
+        self.rects.push((rect, focus_id));
+        Ok(self)
+    }
+
+    /// Start hidden, e.g. a menu shown on a button press.
+    pub fn hidden(&mut self) -> &mut Self {
+        self.visible = false;
+        self
+    }
 }
 
 impl LayoutGridBuilder {
-    pub fn new(size_x: usize, size_y: usize, layout_id: LayoutID) -> Self {
+    pub fn new(size_x: usize, size_y: usize, layout_id: LayoutId) -> Self {
         Self {
             size_x,
             size_y,
@@ -724,10 +1717,17 @@ impl LayoutGridBuilder {
             layout_id,
             is_root_builder: true,
             growable_config: None,
+            growable_fill: None,
+            carousel: false,
+            list: false,
+            tabs: false,
+            split: false,
+            special: vec![],
+            layers: vec![],
         }
     }
 
-    fn new_sub(size_x: usize, size_y: usize, layout_id: LayoutID) -> Self {
+    fn new_sub(size_x: usize, size_y: usize, layout_id: LayoutId) -> Self {
         Self {
             is_root_builder: false,
             ..LayoutGridBuilder::new(size_x, size_y, layout_id)
@@ -743,12 +1743,85 @@ impl LayoutGridBuilder {
         if !self.rects.is_empty() {
             bail!("can't set growable when elements are added");
         }
+        if self.carousel || self.list || self.tabs {
+            bail!("a carousel, a list or tabs can't be growable too");
+        }
         self.growable_config = Some((size_x, size_y, dir));
         Ok(self)
     }
 
-    pub fn add_element(&mut self, rect: Rect, focus_id: FocusID) -> Result<&mut Self> {
-        if self.growable_config.is_some() {
+    /// Make the layout a carousel, a single row of an odd number of cells.
+    /// Its items are added with `LayoutGrid::insert_to_carousel`.
+    pub fn set_carousel(&mut self) -> Result<&mut Self> {
+        if !self.rects.is_empty() || self.growable_config.is_some() || self.list || self.tabs {
+            bail!("{} can't be a carousel, it has elements or grows", self.layout_id);
+        }
+        if self.size_y != 1 {
+            bail!("a carousel is a single row, {} has {}", self.layout_id, self.size_y);
+        }
+        self.carousel = true;
+        Ok(self)
+    }
+
+    /// Make the layout a vertical list, one item per row under group
+    /// headers. Its groups and items are added with
+    /// `LayoutGrid::add_list_group` and `LayoutGrid::insert_to_list`, and it
+    /// grows a row at a time.
+    pub fn set_list(&mut self) -> Result<&mut Self> {
+        if !self.rects.is_empty() || self.growable_config.is_some() || self.carousel || self.tabs {
+            bail!("{} can't be a list, it has elements or grows", self.layout_id);
+        }
+        self.list = true;
+        Ok(self)
+    }
+
+    /// Make the layout a tab container, its sublayouts are tabs which all
+    /// take the whole of it, shown one at a time with
+    /// `NavigationController::cycle_tab` or `select_tab`. The first one
+    /// added is shown to start with.
+    pub fn set_tabs(&mut self) -> Result<&mut Self> {
+        if !self.rects.is_empty() || self.growable_config.is_some() || self.carousel || self.list {
+            bail!("{} can't be a tab container, it has elements or grows", self.layout_id);
+        }
+        self.tabs = true;
+        Ok(self)
+    }
+
+    /// Make the layout a master-detail split, its only two sublayouts side by
+    /// side: a list on the left and the details of what's picked in it on the
+    /// right. Going across, the focus goes back to where it was in the other
+    /// pane, or its first element, instead of the row level with it.
+    pub fn set_split(&mut self) -> Result<&mut Self> {
+        if self.growable_config.is_some() || self.carousel || self.list || self.tabs {
+            bail!("{} can't be split, it's already another kind of layout", self.layout_id);
+        }
+        self.split = true;
+        Ok(self)
+    }
+
+    /// Handle the button in the layout, e.g. a shoulder button jumping to
+    /// its edge.
+    pub fn on_button(&mut self, button: Button, action: SpecialHandlerAction) -> &mut Self {
+        self.special.push((button, action));
+        self
+    }
+
+    /// Fill order and alignment of a growable grid, the default fills in
+    /// the grow direction, aligned to the start.
+    pub fn set_growable_fill(
+        &mut self,
+        fill: FillStrategy,
+        alignment: Alignment,
+    ) -> Result<&mut Self> {
+        if self.growable_config.is_none() {
+            bail!("can't set the fill of a grid which isn't growable");
+        }
+        self.growable_fill = Some((fill, alignment));
+        Ok(self)
+    }
+
+    pub fn add_element(&mut self, rect: Rect, focus_id: FocusId) -> Result<&mut Self> {
+        if self.growable_config.is_some() || self.carousel || self.list || self.tabs {
             bail!("can't add when elements are added, instead, call the grow_element method on the controller");
         }
         self.rects.push((rect, focus_id));
@@ -758,7 +1831,7 @@ impl LayoutGridBuilder {
     pub fn with_sublayout<'a>(
         &'a mut self,
         rect: Rect,
-        layout_id: LayoutID,
+        layout_id: LayoutId,
         size_x: usize,
         size_y: usize,
     ) -> &'a mut Self {
@@ -778,24 +1851,106 @@ impl LayoutGridBuilder {
         self.build_sub(None)
     }
 
+    /// A layer drawn over the layout, higher `z` over lower ones. Its
+    /// elements can overlap the layout's, but not each other.
+    pub fn with_layer(&mut self, name: &str, z: u32) -> &mut LayerBuilder {
+        self.layers.push(LayerBuilder {
+            name: name.to_owned(),
+            z,
+            visible: true,
+            rects: vec![],
+        });
+        self.layers.last_mut().unwrap()
+    }
+
+    /// Everything must fit in the layout without overlapping.
+    fn validate(&self, bounds: Rect) -> Result<()> {
+        if self.split {
+            let panes = match self.sublayouts.as_slice() {
+                [(left, _, _), (right, _, _)] => left.x_end < right.x_start,
+                _ => false,
+            };
+            if !panes || !self.rects.is_empty() {
+                bail!("{} must only have two panes side by side to be split", self.layout_id);
+            }
+        }
+        if self.tabs {
+            if let Some((rect, id, _)) = self.sublayouts.iter().find(|(r, _, _)| *r != bounds) {
+                bail!("tab {} at {:?} doesn't take the whole of {}", id, rect, self.layout_id);
+            }
+            return Ok(());
+        }
+        let items: Vec<(Rect, String)> = self
+            .rects
+            .iter()
+            .map(|(rect, focus_id)| (*rect, focus_id.to_string()))
+            .chain(
+                self.sublayouts
+                    .iter()
+                    .map(|(rect, layout_id, _)| (*rect, layout_id.to_string())),
+            )
+            .collect();
+        for (i, (rect, name)) in items.iter().enumerate() {
+            if !bounds.contains(*rect) {
+                bail!("{} at {:?} is outside of {}", name, rect, self.layout_id);
+            }
+            for (other, other_name) in &items[i + 1..] {
+                if let Some(overlap) = rect.intersection(*other) {
+                    bail!("{} and {} overlap at {:?}", name, other_name, overlap);
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn build_sub(self, parent: Option<Weak<Mutex<LayoutGrid>>>) -> Result<Arc<Mutex<LayoutGrid>>> {
         let mut this_layout = match self.growable_config {
-            Some((x, y, dir)) => {
-                LayoutGrid::new_growable(self.size_x, self.size_y, self.layout_id, x, y, dir)?
-            }
-            None => LayoutGrid::new(self.size_x, self.size_y, self.layout_id)?,
+            Some((x, y, ref dir)) => LayoutGrid::new_growable(
+                self.size_x,
+                self.size_y,
+                self.layout_id.clone(),
+                x,
+                y,
+                dir.clone(),
+            )?,
+            None => LayoutGrid::new(self.size_x, self.size_y, self.layout_id.clone())?,
         };
 
         // Set parent.
         if let Some(ref parent_ref) = parent {
             this_layout.parent = Some(parent_ref.clone());
         }
+        if self.carousel {
+            this_layout.carousel = Some(Carousel::new(self.size_x)?);
+        }
+        if self.list {
+            this_layout.list = Some(List::default());
+        }
+        if self.tabs {
+            this_layout.tabs = Some(Tabs::default());
+        }
+        this_layout.split = self.split;
+
+        self.validate(this_layout.grid.bounds())?;
+        this_layout.special_handler.extend(self.special);
+        if let Some((fill, alignment)) = self.growable_fill {
+            this_layout.set_fill(fill, alignment)?;
+        }
 
         for (rect, focus_id) in self.rects {
             let e = Arc::new(Mutex::new(GridItem::Element(focus_id, rect)));
             this_layout.grid.fill(rect, e)?;
         }
 
+        for layer in self.layers {
+            this_layout.add_layer(&layer.name, layer.z, layer.visible)?;
+            let grid = &mut this_layout.layer_mut(&layer.name)?.grid;
+            for (rect, focus_id) in layer.rects {
+                let e = Arc::new(Mutex::new(GridItem::Element(focus_id, rect)));
+                grid.fill(rect, e)?;
+            }
+        }
+
         let this_layout_arc = Arc::new(Mutex::new(this_layout));
         for (sub_rect, sub_layout_id, sub_builder) in self.sublayouts {
             let sub_layout = sub_builder.build_sub(Some(Arc::downgrade(&this_layout_arc)))?;
@@ -803,7 +1958,15 @@ impl LayoutGridBuilder {
             let e = Arc::new(Mutex::new(GridItem::Sublayout(sub_layout, sub_rect)));
 
             let mut ref_parent_layout = this_layout_arc.lock().unwrap();
-            // Fill area with sublayouts too.
+            // Fill area with sublayouts too. Tabs are kept aside but the
+            // first one, see `show_tab`.
+            if let Some(ref mut tabs) = ref_parent_layout.tabs {
+                tabs.push(sub_layout_id.clone(), e.clone());
+                if tabs.current().is_some_and(|(id, _)| *id != sub_layout_id) {
+                    ref_parent_layout.sublayouts.insert(sub_layout_id, Arc::downgrade(&e));
+                    continue;
+                }
+            }
             ref_parent_layout.grid.fill(sub_rect, e.clone())?;
             // Now, add this sublayout to the parent map.
             ref_parent_layout
@@ -814,10 +1977,47 @@ impl LayoutGridBuilder {
         Ok(this_layout_arc)
     }
 }
+/// The layout containing the element, with the top left of the element.
+fn find_in_layout(
+    layout: &Arc<Mutex<LayoutGrid>>,
+    focus_id: &FocusId,
+) -> Option<(Arc<Mutex<LayoutGrid>>, Point)> {
+    let subs: Vec<_> = {
+        let l = layout.lock().unwrap();
+        if let Some(rect) = l.find_rect(focus_id) {
+            return Some((layout.clone(), rect.top_left()));
+        }
+        l.sublayouts().collect()
+    };
+    subs.iter().find_map(|(sub, _)| find_in_layout(sub, focus_id))
+}
+
+/// Whether the layout is the other one or inside of it.
+fn is_within(layout: &Arc<Mutex<LayoutGrid>>, other: &Arc<Mutex<LayoutGrid>>) -> bool {
+    let mut layout = Some(layout.clone());
+    while let Some(l) = layout {
+        if Arc::ptr_eq(&l, other) {
+            return true;
+        }
+        layout = l.lock().unwrap().parent.as_ref().and_then(|p| p.upgrade());
+    }
+    false
+}
+
+/// The layout and its sublayouts, parents first.
+fn collect_layouts(layout: &Arc<Mutex<LayoutGrid>>, out: &mut Vec<Arc<Mutex<LayoutGrid>>>) {
+    out.push(layout.clone());
+    let subs: Vec<_> = layout.lock().unwrap().sublayouts().collect();
+    for (sub, _) in subs {
+        collect_layouts(&sub, out);
+    }
+}
+
 pub struct NavigationController {
     root_layout: Arc<Mutex<LayoutGrid>>,
     current_layout_ref: Weak<Mutex<LayoutGrid>>,
-    current_focus_id: Option<String>,
+    current_focus_id: Option<FocusId>,
+    last_transition: Option<FocusTransition>,
 }
 
 impl NavigationController {
@@ -826,6 +2026,7 @@ impl NavigationController {
             root_layout: root_layout.clone(),
             current_layout_ref: Arc::downgrade(&root_layout),
             current_focus_id: None,
+            last_transition: None,
         };
 
         // Layout must have 0, 0 to be something as default.
@@ -834,12 +2035,174 @@ impl NavigationController {
         Ok(ret)
     }
 
-    pub fn get_sublayout_by_id(&self, id: &str) -> Result<Weak<Mutex<LayoutGrid>>> {
+    /// Every layout, the root first and parents before their sublayouts.
+    pub fn layouts(&self) -> Vec<Arc<Mutex<LayoutGrid>>> {
+        let mut layouts = vec![];
+        collect_layouts(&self.root_layout, &mut layouts);
+        layouts
+    }
+
+    fn find_layout(&self, id: &LayoutId) -> Result<Arc<Mutex<LayoutGrid>>> {
+        self.layouts()
+            .into_iter()
+            .find(|l| l.lock().unwrap().layout_id() == id)
+            .ok_or(anyhow!("no layout {}", id))
+    }
+
+    /// Show or hide a layer of a layout, the focus moves off the elements
+    /// of a layer being hidden.
+    pub fn set_layer_visible(&mut self, id: &LayoutId, name: &str, visible: bool) -> Result<()> {
+        let layout = self.find_layout(id)?;
+        layout.lock().unwrap().set_layer_visible(name, visible)?;
+        if self
+            .tracked(|c| c.navigate_untracked(NavigationDirective::Noop))
+            .is_err()
+        {
+            self.reset()?;
+        }
+        Ok(())
+    }
+
+    /// Where an element is, looking through the sublayouts too.
+    pub fn find_rect(&self, focus_id: &FocusId) -> Option<FocusRect> {
+        let (layout, _) = find_in_layout(&self.root_layout, focus_id)?;
+        let layout = layout.lock().unwrap();
+        let rect = layout.find_rect(focus_id)?;
+        Some(layout.focus_rect_of(rect))
+    }
+
+    /// Move the focus back to the top-left of the root layout.
+    pub fn reset(&mut self) -> Result<()> {
+        self.tracked(|c| {
+            c.current_layout_ref = Arc::downgrade(&c.root_layout);
+            c.root_layout.lock().unwrap().layout_state = Some(Point::default());
+            c.navigate_untracked(NavigationDirective::Noop)?;
+            Ok(())
+        })
+    }
+
+    /// Focus an element, looking through the sublayouts too.
+    pub fn focus(&mut self, focus_id: &FocusId) -> Result<()> {
+        let (layout, point) = find_in_layout(&self.root_layout, focus_id)
+            .ok_or(anyhow!("no element {} in the layout", focus_id))?;
+        self.tracked(|c| {
+            layout.lock().unwrap().layout_state = Some(point);
+            c.current_layout_ref = Arc::downgrade(&layout);
+            c.navigate_untracked(NavigationDirective::Noop)?;
+            Ok(())
+        })
+    }
+
+    /// Focus an element of a sublayout known to have it, e.g. a game tile,
+    /// without looking through the whole tree.
+    pub fn focus_by_id(&mut self, id: &LayoutId, focus_id: &FocusId) -> Result<()> {
+        let layout = self
+            .get_sublayout_by_id(id)?
+            .upgrade()
+            .ok_or(anyhow!("no layout {}", id))?;
+        let point = layout
+            .lock()
+            .unwrap()
+            .find_rect(focus_id)
+            .ok_or(anyhow!("no element {} in {}", focus_id, id))?
+            .top_left();
+        self.tracked(|c| {
+            layout.lock().unwrap().layout_state = Some(point);
+            c.current_layout_ref = Arc::downgrade(&layout);
+            c.navigate_untracked(NavigationDirective::Noop)?;
+            Ok(())
+        })
+    }
+
+    /// The tab a tab container shows.
+    pub fn current_tab(&self, id: &LayoutId) -> Result<LayoutId> {
+        let container = self.find_layout(id)?;
+        let container = container.lock().unwrap();
+        let tabs = container.tabs.as_ref().ok_or(anyhow!("layout {} has no tabs", id))?;
+        let (tab, _) = tabs.current().ok_or(anyhow!("layout {} has no tabs", id))?;
+        Ok(tab.clone())
+    }
+
+    /// Show the tab of a container `steps` tabs to the right, wrapping
+    /// around, see `show_tab`.
+    pub fn cycle_tab(&mut self, id: &LayoutId, steps: isize) -> Result<LayoutId> {
+        self.show_tab(id, |tabs| {
+            tabs.cycle(steps);
+            Ok(())
+        })
+    }
+
+    /// Show a tab of a container, see `show_tab`.
+    pub fn select_tab(&mut self, id: &LayoutId, tab: &LayoutId) -> Result<()> {
+        self.show_tab(id, |tabs| tabs.select(tab))?;
+        Ok(())
+    }
+
+    /// With the focus in the tab hidden, it goes back to where it was in the
+    /// one shown, or to its first element. Without any, it goes back to the
+    /// top-left of the root layout.
+    fn show_tab(
+        &mut self,
+        id: &LayoutId,
+        f: impl FnOnce(&mut Tabs<Arc<Mutex<GridItem>>>) -> Result<()>,
+    ) -> Result<LayoutId> {
+        let container = self.find_layout(id)?;
+        let focus_in_tabs = self
+            .current_layout_ref
+            .upgrade()
+            .is_some_and(|l| is_within(&l, &container));
+        let tab = container.lock().unwrap().show_tab(f)?;
+        let tab_id = tab.lock().unwrap().layout_id().clone();
+        if !focus_in_tabs {
+            return Ok(tab_id);
+        }
+        self.tracked(|c| {
+            c.current_layout_ref = Arc::downgrade(&tab);
+            let restored = tab.lock().unwrap().layout_state.is_some()
+                && c.navigate_untracked(NavigationDirective::Noop).is_ok();
+            if restored {
+                return Ok(());
+            }
+            let entered = tab.lock().unwrap().enter_from(
+                Point::default(),
+                NavigationDirective::Direction(Direction::Down),
+            )?;
+            match entered {
+                NavigationResult::NoNextItem => c.reset(),
+                entered => c.follow(entered).map(|_| ()),
+            }
+        })?;
+        Ok(tab_id)
+    }
+
+    /// How many cells wide and high a layout is.
+    pub fn layout_size(&self, id: &LayoutId) -> Result<(usize, usize)> {
+        Ok(self.find_layout(id)?.lock().unwrap().size())
+    }
+
+    /// Focus what's under the pointer, `x` and `y` are in cells of the
+    /// layout, e.g. pixels over the cell size. Nothing happens over an
+    /// empty cell or outside of the layout.
+    pub fn focus_at_point(&mut self, id: &LayoutId, x: f32, y: f32) -> Result<Option<FocusId>> {
+        let layout = self.find_layout(id)?;
+        let Some((focus_id, rect)) = layout.lock().unwrap().element_at(x, y) else {
+            return Ok(None);
+        };
+        self.tracked(|c| {
+            layout.lock().unwrap().layout_state = Some(rect.top_left());
+            c.current_layout_ref = Arc::downgrade(&layout);
+            c.navigate_untracked(NavigationDirective::Noop)?;
+            Ok(())
+        })?;
+        Ok(Some(focus_id))
+    }
+
+    pub fn get_sublayout_by_id(&self, id: &LayoutId) -> Result<Weak<Mutex<LayoutGrid>>> {
         // Search down the tree? Really, I just want to keep a small ref to the layout I need.
         return self.root_layout.lock().unwrap().get_sublayout_by_id(id);
     }
 
-    pub fn with_sublayout<F, T>(&self, id: &str, f: F ) -> Result<T> where F: FnOnce(&mut LayoutGrid) -> T {
+    pub fn with_sublayout<F, T>(&self, id: &LayoutId, f: F ) -> Result<T> where F: FnOnce(&mut LayoutGrid) -> T {
         let s = self.get_sublayout_by_id(id)?.upgrade();
         match s {
             Some(l) =>  {
@@ -850,11 +2213,55 @@ impl NavigationController {
         }
     }
 
-    pub fn get_current_focus_id(&self) -> &Option<String> {
+    /// The layout the focus is in.
+    pub fn current_layout_id(&self) -> Option<LayoutId> {
+        let layout = self.current_layout_ref.upgrade()?;
+        let id = layout.lock().unwrap().layout_id().clone();
+        Some(id)
+    }
+
+    /// Attach typed data to an element, wherever it is, see
+    /// `LayoutGrid::set_payload`.
+    pub fn set_payload<T: Any + Send + Sync>(&self, focus_id: &FocusId, payload: T) -> Result<()> {
+        let (layout, _) = find_in_layout(&self.root_layout, focus_id)
+            .ok_or(anyhow!("no {} to attach to", focus_id))?;
+        let mut layout = layout.lock().unwrap();
+        layout.set_payload(focus_id, payload)
+    }
+
+    /// What's attached to an element, if it's a T.
+    pub fn payload<T: Any + Clone>(&self, focus_id: &FocusId) -> Option<T> {
+        let (layout, _) = find_in_layout(&self.root_layout, focus_id)?;
+        let payload = layout.lock().unwrap().payload(focus_id);
+        payload
+    }
+
+    /// What's attached to the focused element, e.g. the one navigated to.
+    pub fn current_payload<T: Any + Clone>(&self) -> Option<T> {
+        let focus_id = self.current_focus_id.as_ref()?;
+        let payload = self.current_layout_ref.upgrade()?.lock().unwrap().payload(focus_id);
+        payload
+    }
+
+    pub fn get_current_focus_id(&self) -> &Option<FocusId> {
         &self.current_focus_id
     }
 
-    pub fn insert_elem(&self, focus_id: &str) -> Result<()> {
+    /// Remove an element of a growable sublayout, the focus moves to the
+    /// element taking its place, or back to the start if there's none.
+    pub fn remove_elem(&mut self, id: &LayoutId, focus_id: &FocusId) -> Result<()> {
+        self.with_sublayout(id, |l| l.remove_from_growable_grid(focus_id))??;
+        if self.current_focus_id.as_ref() == Some(focus_id)
+            && self
+                .tracked(|c| c.navigate_untracked(NavigationDirective::Noop))
+                .is_err()
+        {
+            self.reset()?;
+        }
+        Ok(())
+    }
+
+    pub fn insert_elem(&self, focus_id: FocusId) -> Result<()> {
         self.current_layout_ref
             .upgrade()
             .ok_or(anyhow!("unexpected result when getting layout"))?
@@ -863,15 +2270,83 @@ impl NavigationController {
             .insert_to_growable_grid(focus_id)
     }
 
+    fn with_current_layout<T>(&self, f: impl FnOnce(&mut LayoutGrid) -> Result<T>) -> Result<T> {
+        let layout = self
+            .current_layout_ref
+            .upgrade()
+            .ok_or(anyhow!("unexpected result when getting layout"))?;
+        let mut layout = layout.lock().unwrap();
+        f(&mut layout)
+    }
+
+    /// Pick up the focused element, its layout must be growable.
+    pub fn pick_up(&mut self) -> Result<FocusId> {
+        self.with_current_layout(|l| l.pick_up())
+    }
+
+    /// The element being reordered, if any.
+    pub fn held(&self) -> Option<FocusId> {
+        self.with_current_layout(|l| Ok(l.held().cloned()))
+            .ok()
+            .flatten()
+    }
+
+    pub fn move_held(&mut self, d: Direction) -> Result<Option<Reorder>> {
+        self.tracked(|c| c.with_current_layout(|l| l.move_held(d)))
+    }
+
+    pub fn drop_held(&mut self) -> Result<Option<Reorder>> {
+        self.with_current_layout(|l| l.drop_held())
+    }
+
+    pub fn cancel_held(&mut self) -> Result<Option<Reorder>> {
+        self.tracked(|c| c.with_current_layout(|l| l.cancel_held()))
+    }
+
+    /// The cells of the layout the focus is in.
+    pub fn current_snapshot(&self) -> Option<GridSnapshot> {
+        let layout = self.current_layout_ref.upgrade()?;
+        let snapshot = layout.lock().unwrap().snapshot();
+        Some(snapshot)
+    }
+
+    /// The last focus change, including moving a held element.
+    pub fn last_transition(&self) -> Option<&FocusTransition> {
+        self.last_transition.as_ref()
+    }
+
+    fn current_focus_rect(&self) -> Option<FocusRect> {
+        self.with_current_layout(|l| Ok(l.focus_rect())).ok().flatten()
+    }
+
+    /// Run something that moves the focus, remembering where from and to.
+    fn tracked<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let from = self.current_focus_rect();
+        let ret = f(self)?;
+        if let Some(to) = self.current_focus_rect() {
+            self.last_transition = Some(FocusTransition { from, to });
+        }
+        Ok(ret)
+    }
+
     pub fn navigate(&mut self, directive: NavigationDirective) -> Result<NavigationResult> {
-        match self
+        self.tracked(|c| c.navigate_untracked(directive))
+    }
+
+    fn navigate_untracked(&mut self, directive: NavigationDirective) -> Result<NavigationResult> {
+        let result = self
             .current_layout_ref
             .upgrade()
             .ok_or(anyhow!("unexpected result when getting layout"))?
             .lock()
             .unwrap()
-            .navigate(directive)?
-        {
+            .navigate(directive)?;
+        self.follow(result)
+    }
+
+    /// Move the focus to where a navigation of the current layout ended.
+    fn follow(&mut self, result: NavigationResult) -> Result<NavigationResult> {
+        match result {
             NavigationResult::WithinLayout(ref s) => {
                 self.current_focus_id = Some(s.to_owned());
                 Ok(NavigationResult::WithinLayout(s.to_owned()))
@@ -925,22 +2400,22 @@ mod tests {
     use super::*;
 
     fn simple_layout() -> Result<Arc<Mutex<LayoutGrid>>> {
-        let mut builder = LayoutGridBuilder::new(10, 5, "L0".to_owned());
+        let mut builder = LayoutGridBuilder::new(10, 5, LayoutId::root("L0"));
         builder
-            .add_element(Rect::new(0, 1, 0, 1)?, "0_alpha".to_owned())?
-            .add_element(Rect::new(2, 2, 0, 1)?, "0_beta".to_owned())?;
+            .add_element(Rect::new(0, 1, 0, 1)?, FocusId::button("0_alpha"))?
+            .add_element(Rect::new(2, 2, 0, 1)?, FocusId::button("0_beta"))?;
         builder.build()
     }
 
     fn nested_layout() -> Result<Arc<Mutex<LayoutGrid>>> {
-        let mut builder = LayoutGridBuilder::new(10, 5, "L0".to_owned());
+        let mut builder = LayoutGridBuilder::new(10, 5, LayoutId::root("L0"));
         builder
-            .add_element(Rect::new(0, 1, 0, 1)?, "0_alpha".to_owned())?
-            .add_element(Rect::new(2, 2, 0, 1)?, "0_beta".to_owned())?;
+            .add_element(Rect::new(0, 1, 0, 1)?, FocusId::button("0_alpha"))?
+            .add_element(Rect::new(2, 2, 0, 1)?, FocusId::button("0_beta"))?;
         builder
-            .with_sublayout(Rect::new(0, 9, 2, 4)?, "L1".to_owned(), 7, 10)
-            .add_element(Rect::new(0, 0, 0, 9)?, "1_alpha".to_owned())?
-            .add_element(Rect::new(1, 1, 0, 9)?, "1_beta".to_owned())?;
+            .with_sublayout(Rect::new(0, 9, 2, 4)?, LayoutId::sublayout("L0", "L1"), 7, 10)
+            .add_element(Rect::new(0, 0, 0, 9)?, FocusId::button("1_alpha"))?
+            .add_element(Rect::new(1, 1, 0, 9)?, FocusId::button("1_beta"))?;
 
         builder.build()
     }
@@ -972,6 +2447,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn rect_geometry() {
+        let r = Rect::new(1, 4, 2, 3).unwrap();
+        assert_eq!(r.transpose(), Rect::new(2, 3, 1, 4).unwrap());
+        assert_eq!(r.transpose().transpose(), r);
+        assert_eq!((r.width(), r.height()), (4, 2));
+        assert_eq!(r.center(), Point { x: 2, y: 2 });
+
+        assert!(r.contains_point(Point { x: 4, y: 3 }));
+        assert!(!r.contains_point(Point { x: 5, y: 3 }));
+        assert!(!r.contains_point(Point { x: 0, y: -1 }));
+        assert!(r.contains(Rect::new(2, 3, 2, 2).unwrap()));
+        assert!(!r.contains(Rect::new(2, 5, 2, 2).unwrap()));
+
+        let other = Rect::new(3, 6, 0, 2).unwrap();
+        assert_eq!(r.intersection(other), Some(Rect::new(3, 4, 2, 2).unwrap()));
+        assert_eq!(r.union(other), Rect::new(1, 6, 0, 3).unwrap());
+        // Rects are inclusive, sharing a corner cell is overlapping.
+        assert!(r.intersects(Rect::new(4, 4, 3, 5).unwrap()));
+        assert!(!r.intersects(Rect::new(5, 5, 0, 9).unwrap()));
+    }
+
+    #[test]
+    fn building_rejects_bad_layouts() -> Result<()> {
+        let mut builder = LayoutGridBuilder::new(3, 3, LayoutId::root("L0"));
+        builder
+            .add_element(Rect::new(0, 1, 0, 0)?, FocusId::button("a"))?
+            .add_element(Rect::new(1, 2, 0, 1)?, FocusId::button("b"))?;
+        let err = builder.build().unwrap_err().to_string();
+        assert!(err.contains("BTN@a and BTN@b overlap"), "{}", err);
+
+        let mut builder = LayoutGridBuilder::new(3, 3, LayoutId::root("L0"));
+        builder.add_element(Rect::new(0, 0, 0, 0)?, FocusId::button("a"))?;
+        builder.with_sublayout(Rect::new(1, 3, 0, 0)?, LayoutId::sublayout("L0", "L1"), 1, 1);
+        assert!(builder.build().is_err());
+        Ok(())
+    }
+
     #[test]
     fn sample_grid_has_expected_items() {
         let sut = simple_layout().unwrap();
@@ -979,14 +2492,143 @@ mod tests {
         element_in_rect_is(
             sut.clone(),
             &Rect::new(0, 1, 0, 1).unwrap(),
-            &GridItem::Element("0_alpha".to_owned(), Rect::new(0, 1, 0, 1).unwrap()),
+            &GridItem::Element(FocusId::button("0_alpha"), Rect::new(0, 1, 0, 1).unwrap()),
         );
 
         element_in_rect_is(
             sut.clone(),
             &Rect::new(2, 2, 0, 1).unwrap(),
-            &GridItem::Element("0_beta".to_owned(), Rect::new(2, 2, 0, 1).unwrap()),
+            &GridItem::Element(FocusId::button("0_beta"), Rect::new(2, 2, 0, 1).unwrap()),
+        );
+    }
+
+    #[test]
+    fn queries_items_and_layouts() {
+        let root = nested_layout().unwrap();
+        let controller = NavigationController::new(root.clone()).unwrap();
+        let l0 = root.lock().unwrap();
+        // Each once, not once per cell.
+        assert_eq!(l0.items().count(), 3);
+        let ids: Vec<String> = l0.elements().map(|(id, _)| id.to_string()).collect();
+        assert_eq!(ids, ["BTN@0_alpha", "BTN@0_beta"]);
+        assert_eq!(
+            l0.find_rect(&FocusId::button("0_beta")),
+            Some(Rect::new(2, 2, 0, 1).unwrap())
         );
+        // Only this layout is searched.
+        assert_eq!(l0.find_rect(&FocusId::button("1_beta")), None);
+        drop(l0);
+
+        let ids: Vec<String> = controller
+            .layouts()
+            .iter()
+            .map(|l| l.lock().unwrap().layout_id().to_string())
+            .collect();
+        assert_eq!(ids, ["L0", "L0@L1"]);
+        let found = controller.find_rect(&FocusId::button("1_beta")).unwrap();
+        assert_eq!(found.rect, Rect::new(1, 1, 0, 9).unwrap());
+        assert_eq!(found.placement, Some(Rect::new(0, 9, 2, 4).unwrap()));
+        assert!(controller.find_rect(&FocusId::button("nope")).is_none());
+    }
+
+    #[test]
+    fn focuses_the_element_under_the_pointer() -> Result<()> {
+        let mut sut = NavigationController::new(nested_layout()?)?;
+        let l0 = LayoutId::root("L0");
+        let l1 = LayoutId::sublayout("L0", "L1");
+        let focused = |c: &NavigationController| c.get_current_focus_id().clone().unwrap();
+        assert_eq!(
+            sut.focus_at_point(&l0, 2.5, 1.2)?,
+            Some(FocusId::button("0_beta"))
+        );
+        assert_eq!(focused(&sut), FocusId::button("0_beta"));
+        assert_eq!(
+            sut.focus_at_point(&l1, 1.3, 9.9)?,
+            Some(FocusId::button("1_beta"))
+        );
+        assert_eq!(focused(&sut), FocusId::button("1_beta"));
+        assert_eq!(sut.current_layout_id(), Some(l1));
+        // The sublayout itself, an empty cell and outside don't move it.
+        for (x, y) in [(5.0, 3.0), (4.0, 0.0), (-0.5, 0.0), (10.0, 0.0), (0.0, 5.0)] {
+            assert_eq!(sut.focus_at_point(&l0, x, y)?, None);
+        }
+        assert_eq!(focused(&sut), FocusId::button("1_beta"));
+        assert!(sut.focus_at_point(&LayoutId::root("nope"), 0.0, 0.0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn snapshots_the_current_layout() -> Result<()> {
+        let sut = NavigationController::new(nested_layout()?)?;
+        let snapshot = sut.current_snapshot().unwrap();
+        assert_eq!(snapshot.layout_id, LayoutId::root("L0"));
+        assert_eq!((snapshot.width, snapshot.height), (10, 5));
+        assert_eq!(snapshot.focus, Some((0, 0)));
+        use CellKind::*;
+        assert_eq!(snapshot.cells[..4], [Element, Element, Element, Empty]);
+        assert_eq!(snapshot.cells[20], Sublayout);
+        assert_eq!(snapshot.cells.iter().filter(|&&c| c == Empty).count(), 14);
+        Ok(())
+    }
+
+    #[test]
+    fn topmost_layer_wins_navigation() -> Result<()> {
+        let focused = |c: &NavigationController| c.get_current_focus_id().clone().unwrap();
+        let focused = |c: &NavigationController| focused(c).to_string();
+        let mut builder = LayoutGridBuilder::new(3, 1, LayoutId::root("L0"));
+        builder
+            .add_element(Rect::new(0, 0, 0, 0)?, FocusId::button("a"))?
+            .add_element(Rect::new(1, 1, 0, 0)?, FocusId::button("b"))?
+            .add_element(Rect::new(2, 2, 0, 0)?, FocusId::button("c"))?;
+        builder
+            .with_layer("side", 1)
+            .hidden()
+            .add_element(Rect::new(0, 0, 0, 0)?, FocusId::button("side"))?;
+        builder
+            .with_layer("top", 2)
+            .hidden()
+            .add_element(Rect::new(0, 1, 0, 0)?, FocusId::button("top"))?;
+        let mut sut = NavigationController::new(builder.build()?)?;
+        let root = LayoutId::root("L0");
+        assert_eq!(focused(&sut), "BTN@a");
+        assert!(sut.focus(&FocusId::button("side")).is_err());
+
+        sut.set_layer_visible(&root, "side", true)?;
+        sut.navigate(NavigationDirective::Direction(Direction::Right))?;
+        sut.navigate(NavigationDirective::Direction(Direction::Left))?;
+        assert_eq!(focused(&sut), "BTN@side");
+
+        // Shown over the focus, it takes the focus.
+        sut.set_layer_visible(&root, "top", true)?;
+        assert_eq!(focused(&sut), "BTN@top");
+        sut.navigate(NavigationDirective::Direction(Direction::Right))?;
+        assert_eq!(focused(&sut), "BTN@c");
+
+        sut.set_layer_visible(&root, "top", false)?;
+        sut.navigate(NavigationDirective::Direction(Direction::Left))?;
+        assert_eq!(focused(&sut), "BTN@b");
+        sut.navigate(NavigationDirective::Direction(Direction::Left))?;
+        // Hidden under the focus, what's under it is focused.
+        sut.set_layer_visible(&root, "side", false)?;
+        assert_eq!(focused(&sut), "BTN@a");
+        assert!(sut.set_layer_visible(&root, "nope", true).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn building_rejects_bad_layers() -> Result<()> {
+        let mut builder = LayoutGridBuilder::new(2, 1, LayoutId::root("L0"));
+        builder
+            .with_layer("menu", 1)
+            .add_element(Rect::new(0, 1, 0, 0)?, FocusId::button("a"))?
+            .add_element(Rect::new(1, 1, 0, 0)?, FocusId::button("b"))?;
+        assert!(builder.build().is_err());
+
+        let mut builder = LayoutGridBuilder::new(2, 1, LayoutId::root("L0"));
+        builder.set_growable(1, 1, GrowDirection::GrowX)?;
+        builder.with_layer("menu", 1);
+        assert!(builder.build().is_err());
+        Ok(())
     }
 
     #[test]
@@ -994,6 +2636,343 @@ mod tests {
         nested_layout().unwrap();
     }
 
+    #[test]
+    fn growable_grid_wraps_and_expands() {
+        let mut sut =
+            LayoutGrid::new_growable(3, 1, LayoutId::root("L0"), 1, 1, GrowDirection::GrowX)
+                .unwrap();
+        for i in 0..5 {
+            sut.insert_to_growable_grid(FocusId::button(&format!("item_{}", i))).unwrap();
+        }
+        assert_eq!(sut.grid.y_size, 2);
+        let sut = Arc::new(Mutex::new(sut));
+        element_at_is(
+            sut.clone(),
+            2,
+            0,
+            &GridItem::Element(FocusId::button("item_2"), Rect::new(2, 2, 0, 0).unwrap()),
+        );
+        element_at_is(
+            sut.clone(),
+            1,
+            1,
+            &GridItem::Element(FocusId::button("item_4"), Rect::new(1, 1, 1, 1).unwrap()),
+        );
+    }
+
+    #[test]
+    fn growable_grid_fills_and_aligns() {
+        let mut sut =
+            LayoutGrid::new_growable(4, 1, LayoutId::root("L0"), 1, 1, GrowDirection::GrowX)
+                .unwrap();
+        sut.set_fill(FillStrategy::RowMajor, Alignment::Center).unwrap();
+        for i in 0..6 {
+            sut.insert_to_growable_grid(FocusId::button(&format!("item_{}", i))).unwrap();
+        }
+        let id_at = |sut: &LayoutGrid, x, y| {
+            let item = sut.grid.at(x, y).unwrap().unwrap();
+            let item = item.lock().unwrap();
+            match *item {
+                GridItem::Element(ref id, _) => id.to_string(),
+                GridItem::Sublayout(..) => panic!("sublayout at {} {}", x, y),
+            }
+        };
+        // The last row is centered.
+        assert!(sut.grid.at(0, 1).unwrap().is_none());
+        assert_eq!(id_at(&sut, 1, 1), "BTN@item_4");
+        assert_eq!(id_at(&sut, 2, 1), "BTN@item_5");
+
+        // Filling columns of the 4 wide grid takes 2 rows, the focus follows.
+        sut.set_point(1, 1).unwrap();
+        sut.set_fill(FillStrategy::ColumnMajor, Alignment::Start).unwrap();
+        assert_eq!(id_at(&sut, 0, 1), "BTN@item_1");
+        assert_eq!(id_at(&sut, 2, 0), "BTN@item_4");
+        assert_eq!(sut.current_item().unwrap().1, Rect::new(2, 2, 0, 0).unwrap());
+        sut.insert_to_growable_grid(FocusId::button("item_6")).unwrap();
+        assert_eq!(id_at(&sut, 3, 0), "BTN@item_6");
+
+        // Down is the next one when filling columns.
+        sut.pick_up().unwrap();
+        assert_eq!(
+            sut.move_held(Direction::Down).unwrap().map(|r| (r.from, r.to)),
+            Some((4, 5))
+        );
+    }
+
+    #[test]
+    fn growable_grid_shrinks_after_removals() {
+        let mut sut =
+            LayoutGrid::new_growable(3, 1, LayoutId::root("L0"), 1, 1, GrowDirection::GrowX)
+                .unwrap();
+        for i in 0..7 {
+            sut.insert_to_growable_grid(FocusId::button(&format!("item_{}", i))).unwrap();
+        }
+        assert_eq!(sut.size(), (3, 3));
+        sut.set_point(0, 2).unwrap();
+        sut.remove_from_growable_grid(&FocusId::button("item_6")).unwrap();
+        assert_eq!(sut.size(), (3, 2));
+        // The focus falls back to the one before it.
+        assert_eq!(sut.current_item().unwrap().0, FocusId::button("item_5"));
+
+        for i in [0, 4, 5] {
+            sut.remove_from_growable_grid(&FocusId::button(&format!("item_{}", i))).unwrap();
+        }
+        assert_eq!(sut.size(), (3, 1));
+        assert_eq!(sut.current_item().unwrap().0, FocusId::button("item_3"));
+        assert!(sut.remove_from_growable_grid(&FocusId::button("item_0")).is_err());
+
+        // Carries on growing from where the items end.
+        sut.insert_to_growable_grid(FocusId::button("item_7")).unwrap();
+        assert_eq!(sut.size(), (3, 2));
+        assert_eq!(sut.growable_index(&FocusId::button("item_7")).unwrap(), 3);
+        assert!(sut.grid.shrink(3, 1).is_err());
+    }
+
+    #[test]
+    fn removing_the_focused_element_moves_the_focus() -> Result<()> {
+        let mut builder = LayoutGridBuilder::new(1, 2, LayoutId::root("L0"));
+        builder.add_element(Rect::new(0, 0, 0, 0)?, FocusId::button("top"))?;
+        builder
+            .with_sublayout(Rect::new(0, 0, 1, 1)?, LayoutId::sublayout("L0", "L1"), 2, 1)
+            .set_growable(1, 1, GrowDirection::GrowX)?;
+        let mut sut = NavigationController::new(builder.build()?)?;
+        let list = LayoutId::sublayout("L0", "L1");
+        sut.with_sublayout(&list, |l| -> Result<()> {
+            l.insert_to_growable_grid(FocusId::button("a"))?;
+            l.insert_to_growable_grid(FocusId::button("b"))
+        })??;
+
+        sut.focus_by_id(&list, &FocusId::button("b"))?;
+        assert_eq!(*sut.get_current_focus_id(), Some(FocusId::button("b")));
+        assert!(sut.focus_by_id(&list, &FocusId::button("top")).is_err());
+        sut.focus(&FocusId::button("a"))?;
+        sut.remove_elem(&list, &FocusId::button("a"))?;
+        assert_eq!(*sut.get_current_focus_id(), Some(FocusId::button("b")));
+        sut.remove_elem(&list, &FocusId::button("b"))?;
+        assert_eq!(*sut.get_current_focus_id(), Some(FocusId::button("top")));
+        Ok(())
+    }
+
+    #[test]
+    fn carousel_wraps_and_keeps_the_focus_in_the_middle() -> Result<()> {
+        let focused = |c: &NavigationController| c.get_current_focus_id().clone().unwrap();
+        let mut builder = LayoutGridBuilder::new(5, 2, LayoutId::root("L0"));
+        builder.add_element(Rect::new(0, 4, 0, 0)?, FocusId::button("top"))?;
+        let row = LayoutId::sublayout("L0", "Row");
+        builder.with_sublayout(Rect::new(0, 4, 1, 1)?, row.clone(), 5, 1).set_carousel()?;
+        assert!(LayoutGridBuilder::new(5, 2, LayoutId::root("L1")).set_carousel().is_err());
+        let mut sut = NavigationController::new(builder.build()?)?;
+        sut.with_sublayout(&row, |l| -> Result<()> {
+            for id in ["a", "b", "c", "d", "e", "f"] {
+                l.insert_to_carousel(FocusId::game(id))?;
+            }
+            Ok(())
+        })??;
+
+        let middle = |c: &NavigationController, id: &str| {
+            c.find_rect(&FocusId::game(id)).is_some_and(|r| r.rect.x() == 2)
+        };
+        sut.navigate(NavigationDirective::Direction(Direction::Down))?;
+        assert_eq!(focused(&sut), FocusId::game("a"));
+        assert!(middle(&sut, "a"));
+        sut.navigate(NavigationDirective::Direction(Direction::Left))?;
+        assert_eq!(focused(&sut), FocusId::game("f"));
+        assert!(middle(&sut, "f"));
+        sut.navigate(NavigationDirective::Direction(Direction::Right))?;
+        sut.navigate(NavigationDirective::Direction(Direction::Right))?;
+        assert_eq!(focused(&sut), FocusId::game("b"));
+
+        // Out and back in, on the same item.
+        sut.navigate(NavigationDirective::Direction(Direction::Up))?;
+        assert_eq!(focused(&sut), FocusId::button("top"));
+        sut.navigate(NavigationDirective::Direction(Direction::Down))?;
+        assert_eq!(focused(&sut), FocusId::game("b"));
+
+        // Focused off the middle, it comes to the middle.
+        sut.focus(&FocusId::game("c"))?;
+        assert_eq!(focused(&sut), FocusId::game("c"));
+        assert!(middle(&sut, "c"));
+        Ok(())
+    }
+
+    #[test]
+    fn list_skips_its_headers_next_to_a_grid() -> Result<()> {
+        let focused = |c: &NavigationController| c.get_current_focus_id().clone().unwrap();
+        let mut builder = LayoutGridBuilder::new(2, 2, LayoutId::root("L0"));
+        builder
+            .add_element(Rect::new(0, 0, 0, 0)?, FocusId::button("back"))?
+            .add_element(Rect::new(1, 1, 1, 1)?, FocusId::button("side"))?;
+        let list = LayoutId::sublayout("L0", "List");
+        builder.with_sublayout(Rect::new(0, 0, 1, 1)?, list.clone(), 1, 1).set_list()?;
+        let mut sut = NavigationController::new(builder.build()?)?;
+        sut.with_sublayout(&list, |l| -> Result<()> {
+            l.add_list_group("first")?;
+            l.insert_to_list(FocusId::button("a"))?;
+            l.insert_to_list(FocusId::button("b"))?;
+            l.add_list_group("second")?;
+            l.insert_to_list(FocusId::button("c"))?;
+            Ok(())
+        })??;
+
+        let down = NavigationDirective::Direction(Direction::Down);
+        sut.navigate(down.clone())?;
+        assert_eq!(focused(&sut), FocusId::button("a"));
+        sut.navigate(down.clone())?;
+        sut.navigate(down.clone())?;
+        assert_eq!(focused(&sut), FocusId::button("c"));
+        assert_eq!(sut.find_rect(&FocusId::button("c")).unwrap().rect.y(), 4);
+        sut.navigate(NavigationDirective::Direction(Direction::Right))?;
+        assert_eq!(focused(&sut), FocusId::button("side"));
+
+        let headers = sut.with_sublayout(&list, |l| l.list_headers())?;
+        let rows: Vec<_> = headers.iter().map(|h| (h.title.as_str(), h.row, h.end)).collect();
+        assert_eq!(rows, [("first", 0, 2), ("second", 3, 4)]);
+        Ok(())
+    }
+
+    #[test]
+    fn tabs_keep_their_focus() -> Result<()> {
+        let focused = |c: &NavigationController| c.get_current_focus_id().clone().unwrap();
+        let mut builder = LayoutGridBuilder::new(1, 2, LayoutId::root("L0"));
+        builder.add_element(Rect::new(0, 0, 0, 0)?, FocusId::button("back"))?;
+        let tabs = LayoutId::sublayout("L0", "Tabs");
+        let (a, b, c) = ["A", "B", "C"].map(|t| LayoutId::sublayout("L0", t)).into();
+        let container = builder.with_sublayout(Rect::new(0, 0, 1, 1)?, tabs.clone(), 1, 1);
+        container.set_tabs()?;
+        let tab = container.with_sublayout(Rect::new(0, 0, 0, 0)?, a.clone(), 1, 3);
+        for y in 0..3 {
+            tab.add_element(Rect::new(0, 0, y, y)?, FocusId::button(&format!("a{}", y)))?;
+        }
+        container.with_sublayout(Rect::new(0, 0, 0, 0)?, b.clone(), 1, 1);
+        container
+            .with_sublayout(Rect::new(0, 0, 0, 0)?, c.clone(), 1, 2)
+            .add_element(Rect::new(0, 0, 0, 0)?, FocusId::button("c0"))?
+            .add_element(Rect::new(0, 0, 1, 1)?, FocusId::button("c1"))?;
+        let mut sut = NavigationController::new(builder.build()?)?;
+
+        let down = NavigationDirective::Direction(Direction::Down);
+        sut.navigate(down.clone())?;
+        sut.navigate(down.clone())?;
+        assert_eq!(focused(&sut), FocusId::button("a1"));
+        assert!(sut.find_rect(&FocusId::button("c0")).is_none());
+        assert_eq!(sut.with_sublayout(&c, |l| l.size())?, (1, 2));
+
+        // Nothing to focus in B.
+        assert_eq!(sut.cycle_tab(&tabs, 1)?, b);
+        assert_eq!(focused(&sut), FocusId::button("back"));
+        assert_eq!(sut.cycle_tab(&tabs, 1)?, c);
+        assert_eq!(focused(&sut), FocusId::button("back"));
+        sut.navigate(down.clone())?;
+        sut.navigate(down.clone())?;
+        assert_eq!(focused(&sut), FocusId::button("c1"));
+
+        assert_eq!(sut.cycle_tab(&tabs, -2)?, a);
+        assert_eq!(focused(&sut), FocusId::button("a1"));
+        sut.cycle_tab(&tabs, 2)?;
+        assert_eq!(focused(&sut), FocusId::button("c1"));
+        sut.select_tab(&tabs, &a)?;
+        assert_eq!(sut.current_tab(&tabs)?, a);
+        assert_eq!(focused(&sut), FocusId::button("a1"));
+        sut.navigate(NavigationDirective::Direction(Direction::Up))?;
+        sut.navigate(NavigationDirective::Direction(Direction::Up))?;
+        assert_eq!(focused(&sut), FocusId::button("back"));
+        Ok(())
+    }
+
+    #[test]
+    fn split_goes_back_to_where_it_was_across() -> Result<()> {
+        let focused = |c: &NavigationController| c.get_current_focus_id().clone().unwrap();
+        let mut builder = LayoutGridBuilder::new(1, 2, LayoutId::root("L0"));
+        builder.add_element(Rect::new(0, 0, 0, 0)?, FocusId::button("back"))?;
+        let split = builder.with_sublayout(
+            Rect::new(0, 0, 1, 1)?,
+            LayoutId::sublayout("L0", "Split"),
+            2,
+            1,
+        );
+        split.set_split()?;
+        let master =
+            split.with_sublayout(Rect::new(0, 0, 0, 0)?, LayoutId::sublayout("L0", "Master"), 1, 4);
+        for y in 0..4 {
+            master.add_element(Rect::new(0, 0, y, y)?, FocusId::button(&format!("m{}", y)))?;
+        }
+        split
+            .with_sublayout(Rect::new(1, 1, 0, 0)?, LayoutId::sublayout("L0", "Detail"), 1, 2)
+            .add_element(Rect::new(0, 0, 0, 0)?, FocusId::button("d0"))?
+            .add_element(Rect::new(0, 0, 1, 1)?, FocusId::button("d1"))?;
+        let mut sut = NavigationController::new(builder.build()?)?;
+
+        let go = |sut: &mut NavigationController, d| {
+            sut.navigate(NavigationDirective::Direction(d)).unwrap();
+        };
+        for _ in 0..4 {
+            go(&mut sut, Direction::Down);
+        }
+        assert_eq!(focused(&sut), FocusId::button("m3"));
+        // The first detail, not the one level with it.
+        go(&mut sut, Direction::Right);
+        assert_eq!(focused(&sut), FocusId::button("d0"));
+        go(&mut sut, Direction::Down);
+        go(&mut sut, Direction::Left);
+        assert_eq!(focused(&sut), FocusId::button("m3"));
+        go(&mut sut, Direction::Up);
+        go(&mut sut, Direction::Right);
+        assert_eq!(focused(&sut), FocusId::button("d1"));
+
+        let mut builder = LayoutGridBuilder::new(2, 1, LayoutId::root("L1"));
+        builder.set_split()?.add_element(Rect::new(0, 0, 0, 0)?, FocusId::button("a"))?;
+        assert!(builder.build().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn growable_grid_reorders_held_item() {
+        let mut sut =
+            LayoutGrid::new_growable(3, 1, LayoutId::root("L0"), 1, 1, GrowDirection::GrowX)
+                .unwrap();
+        for i in 0..5 {
+            sut.insert_to_growable_grid(FocusId::button(&format!("item_{}", i))).unwrap();
+        }
+        sut.set_point(1, 0).unwrap();
+        assert_eq!(sut.pick_up().unwrap(), FocusId::button("item_1"));
+        // Down a row, then right, past the end of the last row.
+        assert_eq!(
+            sut.move_held(Direction::Down).unwrap(),
+            Some(Reorder {
+                focus_id: FocusId::button("item_1"),
+                from: 1,
+                to: 4
+            })
+        );
+        assert_eq!(sut.move_held(Direction::Right).unwrap(), None);
+        let (focus_id, rect) = sut.current_item().unwrap();
+        assert_eq!(focus_id, FocusId::button("item_1"));
+        assert_eq!(rect, Rect::new(1, 1, 1, 1).unwrap());
+        let sut = Arc::new(Mutex::new(sut));
+        element_at_is(
+            sut.clone(),
+            1,
+            0,
+            &GridItem::Element(FocusId::button("item_2"), Rect::new(1, 1, 0, 0).unwrap()),
+        );
+
+        assert_eq!(
+            sut.lock().unwrap().drop_held().unwrap().map(|r| (r.from, r.to)),
+            Some((1, 4))
+        );
+        assert!(sut.lock().unwrap().held().is_none());
+
+        // Cancelling puts it back.
+        let mut sut = sut.lock().unwrap();
+        sut.pick_up().unwrap();
+        sut.move_held(Direction::Left).unwrap();
+        assert_eq!(
+            sut.cancel_held().unwrap().map(|r| (r.from, r.to)),
+            Some((3, 4))
+        );
+        assert_eq!(sut.current_item().unwrap().0, FocusId::button("item_1"));
+    }
+
     mod navigation_controller_test {
         use super::*;
 
@@ -1009,7 +2988,7 @@ mod tests {
                 .navigate(NavigationDirective::Direction(Direction::Right))
                 .unwrap();
             if let NavigationResult::WithinLayout(ref id) = res {
-                assert_eq!(id, "0_beta");
+                assert_eq!(id, &FocusId::button("0_beta"));
             } else {
                 panic!("unexpected navigation result {:?}", res)
             }
@@ -1027,12 +3006,29 @@ mod tests {
                 .navigate(NavigationDirective::Direction(Direction::Down))
                 .unwrap();
             if let NavigationResult::AcrossLayout(ref id, _) = res {
-                assert_eq!(id, "1_alpha");
+                assert_eq!(id, &FocusId::button("1_alpha"));
             } else {
                 panic!("unexpected navigation result {:?}", res)
             }
         }
 
+        #[test]
+        fn navigation_reports_transitions() {
+            let mut controller = NavigationController::new(nested_layout().unwrap()).unwrap();
+            controller
+                .navigate(NavigationDirective::Direction(Direction::Down))
+                .unwrap();
+            let t = controller.last_transition().unwrap();
+            let from = t.from.as_ref().unwrap();
+            assert_eq!(from.layout_id, LayoutId::root("L0"));
+            assert_eq!(from.rect, Rect::new(0, 1, 0, 1).unwrap());
+            assert_eq!(from.placement, None);
+            assert_eq!(t.to.layout_id, LayoutId::sublayout("L0", "L1"));
+            assert_eq!(t.to.rect, Rect::new(0, 0, 0, 9).unwrap());
+            assert_eq!(t.to.placement, Some(Rect::new(0, 9, 2, 4).unwrap()));
+            assert_eq!((t.to.rect.width(), t.to.rect.height()), (1, 10));
+        }
+
         #[test]
         fn navigation_into_sublayout_then_out() {
             let mut controller = NavigationController::new(nested_layout().unwrap()).unwrap();
@@ -1040,7 +3036,7 @@ mod tests {
                 .navigate(NavigationDirective::Direction(Direction::Down))
                 .unwrap();
             if let NavigationResult::AcrossLayout(ref id, _) = res {
-                assert_eq!(id, "1_alpha");
+                assert_eq!(id, &FocusId::button("1_alpha"));
             } else {
                 panic!("unexpected navigation result {:?}", res)
             }
@@ -1049,10 +3045,415 @@ mod tests {
                 .navigate(NavigationDirective::Direction(Direction::Up))
                 .unwrap();
             if let NavigationResult::AcrossLayout(ref id, _) = res {
-                assert_eq!(id, "0_alpha");
+                assert_eq!(id, &FocusId::button("0_alpha"));
             } else {
                 panic!("unexpected navigation result {:?}", res)
             }
         }
+
+        #[test]
+        fn focus_from_sublayout() {
+            let mut controller = NavigationController::new(nested_layout().unwrap()).unwrap();
+            controller
+                .navigate(NavigationDirective::Direction(Direction::Down))
+                .unwrap();
+            controller.focus(&FocusId::button("0_beta")).unwrap();
+            assert_eq!(
+                controller.get_current_focus_id().as_ref(),
+                Some(&FocusId::button("0_beta"))
+            );
+            // Navigation carries on from the root layout.
+            controller
+                .navigate(NavigationDirective::Direction(Direction::Left))
+                .unwrap();
+            assert_eq!(
+                controller.get_current_focus_id().as_ref(),
+                Some(&FocusId::button("0_alpha"))
+            );
+            // Elements of sublayouts can be focused too.
+            controller.focus(&FocusId::button("1_beta")).unwrap();
+            controller
+                .navigate(NavigationDirective::Direction(Direction::Left))
+                .unwrap();
+            assert_eq!(
+                controller.get_current_focus_id().as_ref(),
+                Some(&FocusId::button("1_alpha"))
+            );
+            assert!(controller.focus(&FocusId::button("nope")).is_err());
+        }
+    }
+
+    // Random layouts and directions, checking what should always hold.
+    mod proptests {
+        use super::*;
+        use proptest::{collection::vec, option, prelude::*};
+
+        #[derive(Debug, Clone)]
+        enum Content {
+            Elements(Vec<Rect>),
+            Growable {
+                count: usize,
+                item: (usize, usize),
+                grow_x: bool,
+                fill: (FillStrategy, Alignment),
+            },
+        }
+
+        #[derive(Debug, Clone)]
+        struct ChildSpec {
+            size: (usize, usize),
+            content: Content,
+        }
+
+        #[derive(Debug, Clone)]
+        struct LayoutSpec {
+            size: (usize, usize),
+            /// Elements, or sublayouts when there's a child. Overlapping ones
+            /// are left out.
+            items: Vec<(Rect, Option<ChildSpec>)>,
+        }
+
+        fn rect_in(size_x: usize, size_y: usize) -> impl Strategy<Value = Rect> {
+            (0..size_x, 0..size_y)
+                .prop_flat_map(move |(x, y)| {
+                    (
+                        Just(x),
+                        Just(y),
+                        0..(size_x - x).min(3),
+                        0..(size_y - y).min(3),
+                    )
+                })
+                .prop_map(|(x, y, w, h)| Rect::new(x, x + w, y, y + h).unwrap())
+        }
+
+        fn fill() -> impl Strategy<Value = (FillStrategy, Alignment)> {
+            (
+                prop_oneof![Just(FillStrategy::RowMajor), Just(FillStrategy::ColumnMajor)],
+                prop_oneof![
+                    Just(Alignment::Start),
+                    Just(Alignment::Center),
+                    Just(Alignment::End)
+                ],
+            )
+        }
+
+        fn child_spec() -> impl Strategy<Value = ChildSpec> {
+            prop_oneof![
+                (1usize..6, 1usize..6)
+                    .prop_flat_map(|(x, y)| (Just((x, y)), vec(rect_in(x, y), 0..6)))
+                    .prop_map(|(size, rects)| ChildSpec {
+                        size,
+                        content: Content::Elements(rects),
+                    }),
+                (
+                    (1usize..6, 1usize..6, 0usize..12),
+                    (1usize..3, 1usize..3, any::<bool>()),
+                    fill(),
+                )
+                    .prop_map(|((x, y, count), (item_x, item_y, grow_x), fill)| ChildSpec {
+                        size: (x, y),
+                        content: Content::Growable {
+                            count,
+                            item: (item_x, item_y),
+                            grow_x,
+                            fill,
+                        },
+                    }),
+            ]
+        }
+
+        fn layout_spec() -> impl Strategy<Value = LayoutSpec> {
+            (2usize..8, 2usize..8)
+                .prop_flat_map(|(x, y)| {
+                    (
+                        Just((x, y)),
+                        vec((rect_in(x, y), option::of(child_spec())), 0..8),
+                    )
+                })
+                .prop_map(|(size, items)| LayoutSpec { size, items })
+        }
+
+        fn direction() -> impl Strategy<Value = Direction> {
+            prop_oneof![
+                Just(Direction::Up),
+                Just(Direction::Down),
+                Just(Direction::Left),
+                Just(Direction::Right),
+            ]
+        }
+
+        /// Rects which don't overlap the ones taken so far.
+        fn take_free(taken: &mut Vec<Rect>, rect: Rect) -> bool {
+            let overlaps = taken.iter().any(|t| t.intersects(rect));
+            if !overlaps {
+                taken.push(rect);
+            }
+            !overlaps
+        }
+
+        /// The controller, with the top left always focusable.
+        fn build(spec: &LayoutSpec) -> Result<NavigationController> {
+            let mut n = 0;
+            let mut next_id = || {
+                n += 1;
+                FocusId::button(&format!("e{}", n))
+            };
+            let mut builder = LayoutGridBuilder::new(spec.size.0, spec.size.1, LayoutId::root("R"));
+            let corner = Rect::new(0, 0, 0, 0)?;
+            let mut taken = vec![corner];
+            builder.add_element(corner, next_id())?;
+            let mut growable = vec![];
+            for (i, (rect, child)) in spec.items.iter().enumerate() {
+                if !take_free(&mut taken, *rect) {
+                    continue;
+                }
+                let Some(child) = child else {
+                    builder.add_element(*rect, next_id())?;
+                    continue;
+                };
+                let id = LayoutId::sublayout("R", &format!("S{}", i));
+                let sub = builder.with_sublayout(*rect, id.clone(), child.size.0, child.size.1);
+                match child.content {
+                    Content::Elements(ref rects) => {
+                        let mut taken = vec![];
+                        for rect in rects {
+                            if take_free(&mut taken, *rect) {
+                                sub.add_element(*rect, next_id())?;
+                            }
+                        }
+                    }
+                    Content::Growable {
+                        count,
+                        item,
+                        grow_x,
+                        fill,
+                    } => {
+                        let dir = if grow_x {
+                            GrowDirection::GrowX
+                        } else {
+                            GrowDirection::GrowY
+                        };
+                        sub.set_growable(item.0, item.1, dir)?
+                            .set_growable_fill(fill.0, fill.1)?;
+                        growable.push((id, count));
+                    }
+                }
+            }
+            let controller = NavigationController::new(builder.build()?)?;
+            for (id, count) in growable {
+                controller.with_sublayout(&id, |l| -> Result<()> {
+                    for _ in 0..count {
+                        l.insert_to_growable_grid(next_id())?;
+                    }
+                    Ok(())
+                })??;
+            }
+            Ok(controller)
+        }
+
+        /// The focus is on an element of the current layout, and the
+        /// layout's point is in bounds.
+        fn assert_focus_is_real(controller: &NavigationController) {
+            let focus_id = controller.get_current_focus_id().clone().expect("no focus");
+            let layout = controller.current_layout_ref.upgrade().expect("no layout");
+            let layout = layout.lock().unwrap();
+            let point = layout.layout_state.expect("no layout state");
+            assert!(layout.grid.within_bounds_point(point), "{:?} out of bounds", point);
+            let (current, _) = layout.current_item().expect("no current item");
+            assert_eq!(current, focus_id);
+            drop(layout);
+            let found = controller.find_rect(&focus_id).expect("focus not in the layouts");
+            assert!(found.rect.contains_point(point), "{:?} not in {:?}", point, found);
+        }
+
+        proptest! {
+            #[test]
+            fn focus_stays_on_elements(
+                spec in layout_spec(),
+                directions in vec(direction(), 0..40),
+            ) {
+                let mut controller = build(&spec).unwrap();
+                assert_focus_is_real(&controller);
+                for d in directions {
+                    controller.navigate(NavigationDirective::Direction(d)).unwrap();
+                    assert_focus_is_real(&controller);
+                }
+            }
+
+            #[test]
+            fn building_never_panics(
+                size in (0usize..5, 0usize..5),
+                rects in vec((0usize..7, 0usize..7, 0usize..7, 0usize..7), 0..6),
+            ) {
+                let mut builder = LayoutGridBuilder::new(size.0, size.1, LayoutId::root("R"));
+                for (i, (x_start, x_end, y_start, y_end)) in rects.into_iter().enumerate() {
+                    let rect = Rect::new(x_start, x_end, y_start, y_end);
+                    if let core::result::Result::Ok(rect) = rect {
+                        builder.add_element(rect, FocusId::button(&format!("e{}", i))).unwrap();
+                    }
+                }
+                let _ = builder.build();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use super::*;
+    use crate::controller::{
+        create_bulk_menu_controller, create_clock_controller, create_confirm_controller,
+        create_downloads_controller,
+        create_duplicates_controller, create_game_details_controller, create_game_menu_controller,
+        create_home_window_controller, create_keyboard_controller, create_matches_controller,
+        create_pin_controller,
+        create_profiles_controller, create_quick_settings_controller, create_saves_controller,
+        create_settings_controller, create_stats_controller, create_storage_controller,
+        create_suspended_controller, create_sync_dialog_controller, create_trash_controller,
+        create_update_controller,
+    };
+    use std::collections::BTreeMap;
+
+    // Built layouts as YAML, a change to the builder shows up as a diff of
+    // the snapshots. `cargo insta review` to accept it.
+
+    #[derive(Serialize)]
+    struct Tree {
+        id: String,
+        size: String,
+        /// Item size, direction, fill and alignment.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        grow: Option<String>,
+        /// A key per cell, `.` where there's nothing.
+        occupancy: Vec<String>,
+        /// By lowercase key, with where they are.
+        elements: BTreeMap<char, String>,
+        /// By uppercase key.
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        sublayouts: BTreeMap<char, Sublayout>,
+    }
+
+    #[derive(Serialize)]
+    struct Sublayout {
+        at: String,
+        layout: Tree,
+    }
+
+    fn rect(r: Rect) -> String {
+        format!("{:?}", <[usize; 4]>::from(r))
+    }
+
+    fn item_id(item: &GridItem) -> String {
+        match item {
+            GridItem::Element(id, _) => id.to_string(),
+            GridItem::Sublayout(sub, _) => sub.lock().unwrap().layout_id().to_string(),
+        }
+    }
+
+    fn tree(layout: &LayoutGrid) -> Tree {
+        let mut keys: HashMap<String, char> = HashMap::new();
+        let mut elements = BTreeMap::new();
+        let mut sublayouts = BTreeMap::new();
+        for item in layout.items() {
+            match item {
+                GridItem::Element(ref id, r) => {
+                    let key = (b'a' + elements.len() as u8 % 26) as char;
+                    keys.insert(item_id(&item), key);
+                    elements.insert(key, format!("{} {}", id, rect(r)));
+                }
+                GridItem::Sublayout(ref sub, r) => {
+                    let key = (b'A' + sublayouts.len() as u8 % 26) as char;
+                    keys.insert(item_id(&item), key);
+                    let layout = tree(&sub.lock().unwrap());
+                    sublayouts.insert(key, Sublayout { at: rect(r), layout });
+                }
+            }
+        }
+        let (width, height) = layout.size();
+        let occupancy = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| match layout.item_at(x, y).unwrap() {
+                        Some(item) => keys[&item_id(&item.lock().unwrap())],
+                        None => '.',
+                    })
+                    .collect()
+            })
+            .collect();
+        let grow = layout.grow_config.as_ref().map(|gc| {
+            format!(
+                "{}x{} {:?} {:?} {:?}",
+                gc.item_x, gc.item_y, gc.grow_direction, gc.fill, gc.alignment
+            )
+        });
+        Tree {
+            id: layout.layout_id().to_string(),
+            size: format!("{}x{}", width, height),
+            grow,
+            occupancy,
+            elements,
+            sublayouts,
+        }
+    }
+
+    fn snapshot(controller: &NavigationController) -> Tree {
+        tree(&controller.root_layout.lock().unwrap())
+    }
+
+    #[test]
+    fn screen_layouts() -> Result<()> {
+        insta::assert_yaml_snapshot!("home", snapshot(&create_home_window_controller()?));
+        insta::assert_yaml_snapshot!("downloads", snapshot(&create_downloads_controller()?));
+        insta::assert_yaml_snapshot!("game_details", snapshot(&create_game_details_controller()?));
+        insta::assert_yaml_snapshot!("sync_dialog", snapshot(&create_sync_dialog_controller()?));
+        insta::assert_yaml_snapshot!("settings", snapshot(&create_settings_controller()?));
+        insta::assert_yaml_snapshot!("duplicates", snapshot(&create_duplicates_controller()?));
+        insta::assert_yaml_snapshot!("clock", snapshot(&create_clock_controller()?));
+        insta::assert_yaml_snapshot!("update", snapshot(&create_update_controller()?));
+        insta::assert_yaml_snapshot!("profiles", snapshot(&create_profiles_controller()?));
+        insta::assert_yaml_snapshot!("saves", snapshot(&create_saves_controller()?));
+        insta::assert_yaml_snapshot!("storage", snapshot(&create_storage_controller()?));
+        insta::assert_yaml_snapshot!("stats", snapshot(&create_stats_controller()?));
+        insta::assert_yaml_snapshot!("matches", snapshot(&create_matches_controller()?));
+        insta::assert_yaml_snapshot!("trash", snapshot(&create_trash_controller()?));
+        insta::assert_yaml_snapshot!("pin", snapshot(&create_pin_controller()?));
+        insta::assert_yaml_snapshot!("keyboard", snapshot(&create_keyboard_controller()?));
+        insta::assert_yaml_snapshot!("suspended", snapshot(&create_suspended_controller()?));
+        insta::assert_yaml_snapshot!("game_menu", snapshot(&create_game_menu_controller()?));
+        insta::assert_yaml_snapshot!("bulk_menu", snapshot(&create_bulk_menu_controller()?));
+        insta::assert_yaml_snapshot!("confirm", snapshot(&create_confirm_controller()?));
+        let quick_settings = create_quick_settings_controller()?;
+        insta::assert_yaml_snapshot!("quick_settings", snapshot(&quick_settings));
+        Ok(())
+    }
+
+    #[test]
+    fn home_with_games() -> Result<()> {
+        let controller = create_home_window_controller()?;
+        let uuids: Vec<String> = (0..9).map(|i| format!("game-{}", i)).collect();
+        crate::controller::layout_games(&controller, uuids.iter().map(|s| s.as_str()))?;
+        crate::controller::layout_picks(&controller, ["game-0", "game-4"])?;
+        crate::controller::layout_picks(&controller, ["game-2", "game-5", "game-8"])?;
+        insta::assert_yaml_snapshot!(snapshot(&controller));
+        Ok(())
+    }
+
+    #[test]
+    fn layers_and_alignment() -> Result<()> {
+        let mut builder = LayoutGridBuilder::new(3, 3, LayoutId::root("L0"));
+        builder
+            .add_element(Rect::new(0, 2, 0, 0)?, FocusId::button("top"))?
+            .with_layer("side", 1)
+            .add_element(Rect::new(2, 2, 0, 2)?, FocusId::button("side"))?;
+        builder
+            .with_sublayout(Rect::new(0, 1, 1, 2)?, LayoutId::sublayout("L0", "G"), 3, 2)
+            .set_growable(1, 1, GrowDirection::GrowX)?
+            .set_growable_fill(FillStrategy::ColumnMajor, Alignment::Center)?;
+        let controller = NavigationController::new(builder.build()?)?;
+        controller.with_sublayout(&LayoutId::sublayout("L0", "G"), |l| {
+            (0..4).try_for_each(|i| l.insert_to_growable_grid(FocusId::button(&i.to_string())))
+        })??;
+        insta::assert_yaml_snapshot!(snapshot(&controller));
+        Ok(())
     }
 }
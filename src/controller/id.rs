@@ -0,0 +1,243 @@
+use anyhow::{anyhow, bail, Error, Result};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+// IDs shared with the UI, formatted as `KIND@PAYLOAD` for focusable elements
+// and `SCREEN@SECTION` for layouts.
+
+/// What a focusable element is, the part before the `@`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FocusKind {
+    /// `BTN@${NAME}`
+    Button,
+    /// `GAME@${UUID}`
+    Game,
+    /// `TAB@${NAME}`
+    Tab,
+    /// `ACH@${INDEX}`
+    Achievement,
+    /// `SHOT@${INDEX}`
+    Screenshot,
+    /// `NEWS@${INDEX}`
+    News,
+    /// `SESSION@${INDEX}`, a time the game was played.
+    Session,
+    /// `PICK@${UUID}`, a suggestion of what to play.
+    Pick,
+    /// `MATCH@${INDEX}`, a store result a game could be.
+    Candidate,
+    /// `DL_PAUSE@${TRANSFER_ID}`
+    DownloadPause,
+    /// `DL_CANCEL@${TRANSFER_ID}`
+    DownloadCancel,
+}
+
+impl FocusKind {
+    const ALL: [FocusKind; 11] = [
+        FocusKind::Button,
+        FocusKind::Game,
+        FocusKind::Tab,
+        FocusKind::Achievement,
+        FocusKind::Screenshot,
+        FocusKind::News,
+        FocusKind::Session,
+        FocusKind::Pick,
+        FocusKind::Candidate,
+        FocusKind::DownloadPause,
+        FocusKind::DownloadCancel,
+    ];
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            FocusKind::Button => "BTN",
+            FocusKind::Game => "GAME",
+            FocusKind::Tab => "TAB",
+            FocusKind::Achievement => "ACH",
+            FocusKind::Screenshot => "SHOT",
+            FocusKind::News => "NEWS",
+            FocusKind::Session => "SESSION",
+            FocusKind::Pick => "PICK",
+            FocusKind::Candidate => "MATCH",
+            FocusKind::DownloadPause => "DL_PAUSE",
+            FocusKind::DownloadCancel => "DL_CANCEL",
+        }
+    }
+}
+
+/// ID of a focusable element.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FocusId {
+    kind: FocusKind,
+    payload: String,
+}
+
+impl FocusId {
+    pub fn new(kind: FocusKind, payload: impl Into<String>) -> Self {
+        Self {
+            kind,
+            payload: payload.into(),
+        }
+    }
+
+    pub fn button(name: &str) -> Self {
+        Self::new(FocusKind::Button, name)
+    }
+
+    pub fn game(uuid: &str) -> Self {
+        Self::new(FocusKind::Game, uuid)
+    }
+
+    pub fn tab(name: &str) -> Self {
+        Self::new(FocusKind::Tab, name)
+    }
+
+    pub fn achievement(index: usize) -> Self {
+        Self::new(FocusKind::Achievement, index.to_string())
+    }
+
+    pub fn screenshot(index: usize) -> Self {
+        Self::new(FocusKind::Screenshot, index.to_string())
+    }
+
+    pub fn news(index: usize) -> Self {
+        Self::new(FocusKind::News, index.to_string())
+    }
+
+    pub fn session(index: usize) -> Self {
+        Self::new(FocusKind::Session, index.to_string())
+    }
+
+    pub fn pick(uuid: &str) -> Self {
+        Self::new(FocusKind::Pick, uuid)
+    }
+
+    pub fn candidate(index: usize) -> Self {
+        Self::new(FocusKind::Candidate, index.to_string())
+    }
+
+    pub fn kind(&self) -> FocusKind {
+        self.kind
+    }
+
+    pub fn payload(&self) -> &str {
+        &self.payload
+    }
+}
+
+impl FromStr for FocusId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, payload) = s
+            .split_once('@')
+            .ok_or(anyhow!("focus id {} has no kind", s))?;
+        let kind = FocusKind::ALL
+            .into_iter()
+            .find(|k| k.prefix() == kind)
+            .ok_or(anyhow!("unknown focus kind {}", kind))?;
+        if payload.is_empty() {
+            bail!("focus id {} has no payload", s);
+        }
+        Ok(Self::new(kind, payload))
+    }
+}
+
+impl fmt::Display for FocusId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.kind.prefix(), self.payload)
+    }
+}
+
+// Saved as `KIND@PAYLOAD`, e.g. in navigation recordings.
+impl Serialize for FocusId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for FocusId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// ID of a layout, `Home` for the root layout of the home screen and
+/// `Home@Games` for its games.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LayoutId {
+    screen: String,
+    section: Option<String>,
+}
+
+impl LayoutId {
+    pub fn root(screen: &str) -> Self {
+        Self {
+            screen: screen.to_owned(),
+            section: None,
+        }
+    }
+
+    pub fn sublayout(screen: &str, section: &str) -> Self {
+        Self {
+            screen: screen.to_owned(),
+            section: Some(section.to_owned()),
+        }
+    }
+}
+
+impl FromStr for LayoutId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once('@') {
+            Some((screen, section)) if !screen.is_empty() && !section.is_empty() => {
+                Ok(Self::sublayout(screen, section))
+            }
+            None if !s.is_empty() => Ok(Self::root(s)),
+            _ => bail!("bad layout id {}", s),
+        }
+    }
+}
+
+impl fmt::Display for LayoutId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.section {
+            Some(ref section) => write!(f, "{}@{}", self.screen, section),
+            None => write!(f, "{}", self.screen),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_focus_ids() {
+        let id: FocusId = "GAME@aaaa".parse().unwrap();
+        assert_eq!(id, FocusId::game("aaaa"));
+        assert_eq!(id.to_string(), "GAME@aaaa");
+        let id: FocusId = "DL_CANCEL@3".parse().unwrap();
+        assert_eq!(id.kind(), FocusKind::DownloadCancel);
+        assert_eq!(id.payload(), "3");
+        // Only the first @ separates.
+        assert_eq!(
+            "BTN@a@b".parse::<FocusId>().unwrap(),
+            FocusId::button("a@b")
+        );
+        for bad in ["GAME", "GAME@", "NOPE@x", "@x"] {
+            assert!(bad.parse::<FocusId>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn parses_layout_ids() {
+        let id: LayoutId = "Home@Games".parse().unwrap();
+        assert_eq!(id, LayoutId::sublayout("Home", "Games"));
+        assert_eq!(id.to_string(), "Home@Games");
+        assert_eq!("Home".parse::<LayoutId>().unwrap(), LayoutId::root("Home"));
+        assert!("Home@".parse::<LayoutId>().is_err());
+    }
+}
@@ -0,0 +1,449 @@
+use super::{
+    chord::{button, Action},
+    LayoutId,
+};
+use anyhow::{bail, Result};
+use gilrs::Button;
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+// Rate limiting between the gamepad and the navigation, so mashing the D-pad
+// doesn't queue up moves faster than the highlight can follow.
+//
+// Also long and double presses: a button bound to one is held back until it's
+// clear which press it is, the others go through right away.
+
+fn default_direction_interval() -> u64 {
+    80
+}
+
+fn default_button_debounce() -> u64 {
+    40
+}
+
+fn default_long_press() -> u64 {
+    500
+}
+
+fn default_double_press() -> u64 {
+    300
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PressKind {
+    Short,
+    /// Held down for `long_press`.
+    Long,
+    /// Pressed again within `double_press` of letting go.
+    Double,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PressBinding {
+    #[serde(deserialize_with = "button")]
+    pub button: Button,
+    pub press: PressKind,
+    pub action: Action,
+}
+
+fn default_bindings() -> Vec<PressBinding> {
+    vec![
+        PressBinding {
+            button: Button::North,
+            press: PressKind::Long,
+            action: Action::GameOptions,
+        },
+        PressBinding {
+            button: Button::Mode,
+            press: PressKind::Double,
+            action: Action::ToggleDebugOverlay,
+        },
+    ]
+}
+
+/// Long and double presses, in milliseconds, part of `InputConfig`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PressConfig {
+    #[serde(default = "default_long_press")]
+    pub long_press: u64,
+    #[serde(default = "default_double_press")]
+    pub double_press: u64,
+    /// Short presses can't be bound, they're the button itself.
+    #[serde(default = "default_bindings")]
+    pub presses: Vec<PressBinding>,
+}
+
+impl Default for PressConfig {
+    fn default() -> Self {
+        Self {
+            long_press: default_long_press(),
+            double_press: default_double_press(),
+            presses: default_bindings(),
+        }
+    }
+}
+
+impl PressConfig {
+    pub fn action_for(&self, b: Button, kind: PressKind) -> Option<Action> {
+        self.presses
+            .iter()
+            .find(|p| p.button == b && p.press == kind)
+            .map(|p| p.action)
+    }
+}
+
+/// Limits per kind of input, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct InputLimits {
+    /// Minimum time between two moves. Moves in between are coalesced into
+    /// the last one, sent once the time is up.
+    #[serde(default = "default_direction_interval")]
+    pub direction_interval: u64,
+    /// Presses of the same button closer than this are dropped.
+    #[serde(default = "default_button_debounce")]
+    pub button_debounce: u64,
+}
+
+impl Default for InputLimits {
+    fn default() -> Self {
+        Self {
+            direction_interval: default_direction_interval(),
+            button_debounce: default_button_debounce(),
+        }
+    }
+}
+
+/// Overrides for a layout, unset ones are the top level limits.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LayoutLimits {
+    pub direction_interval: Option<u64>,
+    pub button_debounce: Option<u64>,
+}
+
+/// Input rate limits, per layout if needed, and what long and double presses
+/// do.
+///
+/// ```yaml
+/// direction_interval: 80
+/// button_debounce: 40
+/// layouts:
+///   Home@Games:
+///     direction_interval: 50
+/// long_press: 500
+/// double_press: 300
+/// presses:
+///   - button: y
+///     press: long
+///     action: game_options
+///   - button: guide
+///     press: double
+///     action: toggle_debug_overlay
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InputConfig {
+    #[serde(flatten)]
+    pub limits: InputLimits,
+    #[serde(default)]
+    pub layouts: HashMap<String, LayoutLimits>,
+    #[serde(flatten)]
+    pub presses: PressConfig,
+}
+
+impl InputConfig {
+    /// Load the config, a missing file means the default limits.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let config: Self = serde_yaml::from_str(&fs::read_to_string(path)?)?;
+        for p in &config.presses.presses {
+            if p.button == Button::South {
+                bail!("A can't be bound, holding it picks up the games");
+            }
+            if p.press == PressKind::Short {
+                bail!("the short press of {:?} is the button itself", p.button);
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn limits_for(&self, layout: Option<&LayoutId>) -> InputLimits {
+        let Some(o) = layout.and_then(|l| self.layouts.get(&l.to_string())) else {
+            return self.limits;
+        };
+        InputLimits {
+            direction_interval: o
+                .direction_interval
+                .unwrap_or(self.limits.direction_interval),
+            button_debounce: o.button_debounce.unwrap_or(self.limits.button_debounce),
+        }
+    }
+}
+
+fn is_direction(b: Button) -> bool {
+    matches!(
+        b,
+        Button::DPadUp | Button::DPadDown | Button::DPadLeft | Button::DPadRight
+    )
+}
+
+/// Decides which presses get through, see `InputConfig`.
+pub struct InputLimiter {
+    config: InputConfig,
+    last_move: Option<Instant>,
+    /// The last move held back, until the interval is up.
+    pending: Option<Button>,
+    last_press: HashMap<Button, Instant>,
+}
+
+impl InputLimiter {
+    pub fn new(config: InputConfig) -> Self {
+        Self {
+            config,
+            last_move: None,
+            pending: None,
+            last_press: HashMap::new(),
+        }
+    }
+
+    /// The buttons to handle now, in order. A move held back comes before
+    /// another button, so e.g. A hits the element the move lands on.
+    pub fn press(&mut self, layout: Option<&LayoutId>, b: Button, now: Instant) -> Vec<Button> {
+        let limits = self.config.limits_for(layout);
+        if is_direction(b) {
+            let interval = Duration::from_millis(limits.direction_interval);
+            if self
+                .last_move
+                .is_some_and(|t| now.duration_since(t) < interval)
+            {
+                self.pending = Some(b);
+                return vec![];
+            }
+            self.pending = None;
+            self.last_move = Some(now);
+            return vec![b];
+        }
+
+        let debounce = Duration::from_millis(limits.button_debounce);
+        if self
+            .last_press
+            .get(&b)
+            .is_some_and(|&t| now.duration_since(t) < debounce)
+        {
+            return vec![];
+        }
+        self.last_press.insert(b, now);
+        let mut buttons: Vec<Button> = self.pending.take().into_iter().collect();
+        buttons.push(b);
+        buttons
+    }
+
+    /// When the move held back is due, if there's one.
+    pub fn deadline(&self, layout: Option<&LayoutId>) -> Option<Instant> {
+        self.pending?;
+        let interval = self.config.limits_for(layout).direction_interval;
+        Some(self.last_move? + Duration::from_millis(interval))
+    }
+
+    /// The move held back, once it's due.
+    pub fn due(&mut self, layout: Option<&LayoutId>, now: Instant) -> Option<Button> {
+        if self.deadline(layout)? > now {
+            return None;
+        }
+        self.last_move = Some(now);
+        self.pending.take()
+    }
+}
+
+/// Tells short, long and double presses apart, for the buttons bound to a
+/// long or double press.
+pub struct PressClassifier {
+    config: PressConfig,
+    /// Bound buttons held down, since when.
+    down: HashMap<Button, Instant>,
+    /// Let go of once, waiting on a second press, since when.
+    released: HashMap<Button, Instant>,
+    /// Long or double presses still held, their releases aren't presses.
+    swallowed: HashSet<Button>,
+}
+
+impl PressClassifier {
+    pub fn new(config: PressConfig) -> Self {
+        Self {
+            config,
+            down: HashMap::new(),
+            released: HashMap::new(),
+            swallowed: HashSet::new(),
+        }
+    }
+
+    pub fn action_for(&self, b: Button, kind: PressKind) -> Option<Action> {
+        self.config.action_for(b, kind)
+    }
+
+    fn is_bound(&self, b: Button, kind: PressKind) -> bool {
+        self.action_for(b, kind).is_some()
+    }
+
+    /// Drop the presses of buttons taken by a chord.
+    pub fn forget(&mut self, taken: impl Fn(Button) -> bool) {
+        self.down.retain(|&b, _| !taken(b));
+        self.released.retain(|&b, _| !taken(b));
+    }
+
+    /// Whether presses of the button go through here, the others are
+    /// short presses as soon as they're pressed.
+    pub fn handles(&self, b: Button) -> bool {
+        self.config.presses.iter().any(|p| p.button == b)
+    }
+
+    pub fn press(&mut self, b: Button, now: Instant) -> Vec<(Button, PressKind)> {
+        let mut presses = self.due(now);
+        if self.released.remove(&b).is_some() {
+            self.swallowed.insert(b);
+            presses.push((b, PressKind::Double));
+        } else {
+            self.down.insert(b, now);
+        }
+        presses
+    }
+
+    pub fn release(&mut self, b: Button, now: Instant) -> Vec<(Button, PressKind)> {
+        let mut presses = self.due(now);
+        if self.swallowed.remove(&b) || self.down.remove(&b).is_none() {
+            return presses;
+        }
+        if self.is_bound(b, PressKind::Double) {
+            self.released.insert(b, now);
+        } else {
+            presses.push((b, PressKind::Short));
+        }
+        presses
+    }
+
+    /// The presses told apart by now: held long enough, or not pressed
+    /// again in time.
+    pub fn due(&mut self, now: Instant) -> Vec<(Button, PressKind)> {
+        let long = Duration::from_millis(self.config.long_press);
+        let double = Duration::from_millis(self.config.double_press);
+        let mut presses = vec![];
+        let held: Vec<Button> = self
+            .down
+            .iter()
+            .filter(|(&b, &t)| self.is_bound(b, PressKind::Long) && now.duration_since(t) >= long)
+            .map(|(&b, _)| b)
+            .collect();
+        for b in held {
+            self.down.remove(&b);
+            self.swallowed.insert(b);
+            presses.push((b, PressKind::Long));
+        }
+        self.released.retain(|&b, &mut t| {
+            let waited = now.duration_since(t) >= double;
+            if waited {
+                presses.push((b, PressKind::Short));
+            }
+            !waited
+        });
+        presses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(start: Instant, ms: u64) -> Instant {
+        start + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn coalesces_moves_and_debounces_buttons() {
+        let mut sut = InputLimiter::new(InputConfig::default());
+        let t = Instant::now();
+        assert_eq!(sut.press(None, Button::DPadRight, t), [Button::DPadRight]);
+        // Mashed within the interval, only the last one is kept.
+        assert!(sut.press(None, Button::DPadRight, ms(t, 10)).is_empty());
+        assert!(sut.press(None, Button::DPadDown, ms(t, 20)).is_empty());
+        assert_eq!(sut.deadline(None), Some(ms(t, 80)));
+        assert_eq!(sut.due(None, ms(t, 79)), None);
+        assert_eq!(sut.due(None, ms(t, 80)), Some(Button::DPadDown));
+        assert_eq!(sut.deadline(None), None);
+
+        assert_eq!(sut.press(None, Button::East, ms(t, 100)), [Button::East]);
+        assert!(sut.press(None, Button::East, ms(t, 120)).is_empty());
+        assert_eq!(sut.press(None, Button::East, ms(t, 140)), [Button::East]);
+
+        // A held back move goes first.
+        assert!(sut.press(None, Button::DPadLeft, ms(t, 150)).is_empty());
+        assert_eq!(
+            sut.press(None, Button::South, ms(t, 151)),
+            [Button::DPadLeft, Button::South]
+        );
+        assert_eq!(sut.due(None, ms(t, 500)), None);
+    }
+
+    #[test]
+    fn overrides_limits_per_layout() {
+        let config: InputConfig = serde_yaml::from_str(
+            "button_debounce: 10\nlayouts:\n  Home@Games:\n    direction_interval: 0\n",
+        )
+        .unwrap();
+        let games = LayoutId::sublayout("Home", "Games");
+        assert_eq!(
+            config.limits_for(Some(&games)),
+            InputLimits {
+                direction_interval: 0,
+                button_debounce: 10
+            }
+        );
+        assert_eq!(config.limits_for(None).direction_interval, 80);
+
+        let mut sut = InputLimiter::new(config);
+        let t = Instant::now();
+        for i in 0..3 {
+            assert_eq!(
+                sut.press(Some(&games), Button::DPadUp, ms(t, i)),
+                [Button::DPadUp]
+            );
+        }
+    }
+
+    #[test]
+    fn classifies_presses() {
+        let mut sut = PressClassifier::new(PressConfig::default());
+        let t = Instant::now();
+        assert!(!sut.handles(Button::South));
+        assert!(sut.handles(Button::North));
+
+        // Y: short when let go in time, long once held.
+        assert!(sut.press(Button::North, t).is_empty());
+        assert_eq!(
+            sut.release(Button::North, ms(t, 100)),
+            [(Button::North, PressKind::Short)]
+        );
+        assert!(sut.press(Button::North, ms(t, 200)).is_empty());
+        assert!(sut.due(ms(t, 699)).is_empty());
+        assert_eq!(sut.due(ms(t, 700)), [(Button::North, PressKind::Long)]);
+        assert!(sut.release(Button::North, ms(t, 900)).is_empty());
+
+        // Guide: short once no second press comes, double when it does.
+        assert!(sut.press(Button::Mode, ms(t, 1000)).is_empty());
+        assert!(sut.release(Button::Mode, ms(t, 1050)).is_empty());
+        assert_eq!(sut.due(ms(t, 1350)), [(Button::Mode, PressKind::Short)]);
+        assert!(sut.press(Button::Mode, ms(t, 1400)).is_empty());
+        assert!(sut.release(Button::Mode, ms(t, 1450)).is_empty());
+        assert_eq!(
+            sut.press(Button::Mode, ms(t, 1500)),
+            [(Button::Mode, PressKind::Double)]
+        );
+        assert!(sut.release(Button::Mode, ms(t, 1550)).is_empty());
+        assert!(sut.due(ms(t, 2000)).is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+use super::PadKind;
+use anyhow::{anyhow, Result};
+use gilrs::Button;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+// The raw gamepad input of a session, recorded with `anubis --record-input`
+// and played back with `--play-input` through the same processing as a real
+// gamepad's, chords and long presses included. Each input has when it
+// happened from the start of the recording, and playback feeds them on a
+// clock of its own stepping as often as the gamepads are read, so it plays
+// out the same every time. Recordings are JSON lines, one input each.
+
+/// How long playback keeps going after the last input, for the presses
+/// still being told apart to be let through.
+const TAIL: Duration = Duration::from_secs(2);
+
+/// What a gamepad did.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PadInput {
+    /// A different gamepad is in use.
+    Connected(PadKind),
+    Pressed(#[serde(with = "button")] Button),
+    Released(#[serde(with = "button")] Button),
+    /// The right stick, read `dt` after the last time.
+    Stick {
+        x: f32,
+        y: f32,
+        dt: Duration,
+    },
+}
+
+mod button {
+    use crate::controller::chord;
+    use gilrs::Button;
+    use serde::{ser::Error as _, Serializer};
+
+    pub use chord::button as deserialize;
+
+    pub fn serialize<S: Serializer>(b: &Button, s: S) -> Result<S::Ok, S::Error> {
+        match chord::button_name(*b) {
+            Some(name) => s.serialize_str(name),
+            None => Err(S::Error::custom(format!("{:?} has no name", b))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recorded {
+    /// From the start of the recording.
+    pub at_ms: u64,
+    pub input: PadInput,
+}
+
+/// Writes the input as it comes, so a crash keeps it.
+pub struct InputRecorder {
+    out: BufWriter<File>,
+    started: Instant,
+}
+
+impl InputRecorder {
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(Self {
+            out: BufWriter::new(File::create(path)?),
+            started: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, input: PadInput, now: Instant) -> Result<()> {
+        let recorded = Recorded {
+            at_ms: now.saturating_duration_since(self.started).as_millis() as u64,
+            input,
+        };
+        // Whole lines only, even when it can't be written.
+        let line = serde_json::to_string(&recorded)?;
+        writeln!(self.out, "{}", line)?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+pub fn load(path: &Path) -> Result<Vec<Recorded>> {
+    fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| serde_json::from_str(l).map_err(|e| anyhow!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// The playback clock, each step gives when it is from the start and the
+/// inputs due by then.
+pub struct Playback {
+    recorded: Vec<Recorded>,
+    step: Duration,
+    next: u32,
+    played: usize,
+}
+
+impl Playback {
+    pub fn new(mut recorded: Vec<Recorded>, step: Duration) -> Self {
+        recorded.sort_by_key(|r| r.at_ms);
+        Self {
+            recorded,
+            step,
+            next: 0,
+            played: 0,
+        }
+    }
+}
+
+impl Iterator for Playback {
+    type Item = (Duration, Vec<PadInput>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let at = self.step * self.next;
+        let end = self
+            .recorded
+            .last()
+            .map_or(Duration::ZERO, |r| Duration::from_millis(r.at_ms))
+            + TAIL;
+        if at > end {
+            return None;
+        }
+        self.next += 1;
+        let due = self.recorded[self.played..]
+            .iter()
+            .take_while(|r| Duration::from_millis(r.at_ms) <= at)
+            .map(|r| r.input)
+            .collect::<Vec<_>>();
+        self.played += due.len();
+        Some((at, due))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_and_plays_back_on_its_clock() {
+        let path = std::env::temp_dir().join(format!("anubis-input-{}.jsonl", std::process::id()));
+        let mut recorder = InputRecorder::create(&path).unwrap();
+        let started = recorder.started;
+        let at = |ms| started + Duration::from_millis(ms);
+        recorder
+            .record(PadInput::Connected(PadKind::DualShock), at(0))
+            .unwrap();
+        recorder
+            .record(PadInput::Pressed(Button::DPadDown), at(5))
+            .unwrap();
+        recorder
+            .record(PadInput::Released(Button::DPadDown), at(7))
+            .unwrap();
+        assert!(recorder
+            .record(PadInput::Pressed(Button::C), at(9))
+            .is_err());
+        let recorded = load(&path).unwrap();
+        assert_eq!(recorded.len(), 3);
+        assert_eq!(recorded[1].input, PadInput::Pressed(Button::DPadDown));
+
+        let steps: Vec<_> = Playback::new(recorded, Duration::from_millis(4)).collect();
+        assert_eq!(
+            steps[0],
+            (
+                Duration::ZERO,
+                vec![PadInput::Connected(PadKind::DualShock)]
+            )
+        );
+        assert!(steps[1].1.is_empty());
+        // Both by the third step.
+        assert_eq!(
+            steps[2].1,
+            [
+                PadInput::Pressed(Button::DPadDown),
+                PadInput::Released(Button::DPadDown)
+            ]
+        );
+        assert_eq!(steps.last().unwrap().0, Duration::from_millis(2004));
+        assert!(steps[3..].iter().all(|(_, due)| due.is_empty()));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,254 @@
+use super::{
+    grid::{Alignment, FillStrategy, GrowDirection, LayoutGridBuilder, Rect},
+    FocusId, LayoutId, NavigationController, Screen,
+};
+use anyhow::{Context, Result};
+use log::warn;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+// Screen layouts as JSON files instead of code, for working on them without
+// rebuilding. `anubis --layouts <dir>` loads `<dir>/<Screen>.json` where
+// there's one, e.g. `Home.json`:
+//
+// {
+//   "size": [4, 6],
+//   "elements": [{ "rect": [0, 0, 0, 0], "id": "BTN@GAMES" }],
+//   "sublayouts": [{
+//     "rect": [0, 3, 1, 5], "name": "Games", "size": [7, 10],
+//     "growable": { "size": [1, 1], "direction": "grow_x" }
+//   }]
+// }
+//
+// A single row sublayout can be a carousel instead, with its items in order:
+// `"carousel": { "items": ["BTN@A", "BTN@B"] }`. Or a list, in groups under
+// headers: `"list": [{ "title": "library", "items": ["BTN@A"] }]`.
+
+/// How often the files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ElementDef {
+    pub rect: Rect,
+    pub id: FocusId,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GrowableDef {
+    /// Cells per item.
+    pub size: (usize, usize),
+    pub direction: GrowDirection,
+    pub fill: Option<FillStrategy>,
+    pub alignment: Option<Alignment>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CarouselDef {
+    #[serde(default)]
+    pub items: Vec<FocusId>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListGroupDef {
+    pub title: String,
+    #[serde(default)]
+    pub items: Vec<FocusId>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SublayoutDef {
+    pub rect: Rect,
+    /// The section of the layout id, `Games` for `Home@Games`.
+    pub name: String,
+    pub size: (usize, usize),
+    #[serde(default)]
+    pub elements: Vec<ElementDef>,
+    pub growable: Option<GrowableDef>,
+    pub carousel: Option<CarouselDef>,
+    pub list: Option<Vec<ListGroupDef>>,
+}
+
+/// The layout of a screen.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LayoutDef {
+    pub size: (usize, usize),
+    #[serde(default)]
+    pub elements: Vec<ElementDef>,
+    #[serde(default)]
+    pub sublayouts: Vec<SublayoutDef>,
+}
+
+impl LayoutDef {
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json).with_context(|| format!("bad layout {}", path.display()))
+    }
+
+    pub fn build(&self, screen: Screen) -> Result<NavigationController> {
+        let mut builder =
+            LayoutGridBuilder::new(self.size.0, self.size.1, LayoutId::root(screen.name()));
+        for e in &self.elements {
+            builder.add_element(e.rect, e.id.clone())?;
+        }
+        for s in &self.sublayouts {
+            let id = LayoutId::sublayout(screen.name(), &s.name);
+            let sub = builder.with_sublayout(s.rect, id, s.size.0, s.size.1);
+            if let Some(ref g) = s.growable {
+                sub.set_growable(g.size.0, g.size.1, g.direction.clone())?;
+                if g.fill.is_some() || g.alignment.is_some() {
+                    let fill = g.fill.unwrap_or(FillStrategy::natural(&g.direction));
+                    sub.set_growable_fill(fill, g.alignment.unwrap_or(Alignment::Start))?;
+                }
+            }
+            if s.carousel.is_some() {
+                sub.set_carousel()?;
+            }
+            if s.list.is_some() {
+                sub.set_list()?;
+            }
+            for e in &s.elements {
+                sub.add_element(e.rect, e.id.clone())?;
+            }
+        }
+        let controller = NavigationController::new(builder.build()?)?;
+        for s in &self.sublayouts {
+            let id = LayoutId::sublayout(screen.name(), &s.name);
+            if let Some(ref carousel) = s.carousel {
+                controller.with_sublayout(&id, |l| {
+                    carousel
+                        .items
+                        .iter()
+                        .try_for_each(|i| l.insert_to_carousel(i.clone()))
+                })??;
+            }
+            if let Some(ref groups) = s.list {
+                controller.with_sublayout(&id, |l| {
+                    groups.iter().try_for_each(|g| {
+                        l.add_list_group(&g.title)?;
+                        g.items.iter().try_for_each(|i| l.insert_to_list(i.clone()))
+                    })
+                })??;
+            }
+        }
+        Ok(controller)
+    }
+}
+
+/// Where the layout of a screen is in the directory.
+pub fn path_for(dir: &Path, screen: Screen) -> PathBuf {
+    dir.join(format!("{}.json", screen.name()))
+}
+
+/// The screen's layout from the directory, None when it has no file.
+pub fn load_controller(dir: &Path, screen: Screen) -> Result<Option<NavigationController>> {
+    let path = path_for(dir, screen);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(LayoutDef::load(&path)?.build(screen)?))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Call `changed` with the screens whose file changed, checking in the
+/// background until `changed` returns false.
+pub fn watch(dir: PathBuf, changed: impl Fn(Screen) -> bool + Send + 'static) -> Result<()> {
+    let stamps = |dir: &Path| -> HashMap<Screen, Option<SystemTime>> {
+        Screen::ALL
+            .iter()
+            .map(|&s| (s, modified(&path_for(dir, s))))
+            .collect()
+    };
+    let mut last = stamps(&dir);
+    thread::Builder::new()
+        .name("layout-watcher".to_owned())
+        .spawn(move || loop {
+            thread::sleep(POLL_INTERVAL);
+            let now = stamps(&dir);
+            for screen in Screen::ALL {
+                if now[&screen] != last[&screen] && !changed(screen) {
+                    warn!("stopped watching the layouts in {}", dir.display());
+                    return;
+                }
+            }
+            last = now;
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_layouts_from_json() {
+        let def: LayoutDef = serde_json::from_str(
+            r#"{
+                "size": [2, 2],
+                "elements": [{ "rect": [0, 1, 0, 0], "id": "BTN@BACK" }],
+                "sublayouts": [{
+                    "rect": [0, 1, 1, 1], "name": "List", "size": [2, 5],
+                    "growable": { "size": [1, 1], "direction": "grow_x" }
+                }]
+            }"#,
+        )
+        .unwrap();
+        let mut controller = def.build(Screen::Downloads).unwrap();
+        assert_eq!(
+            controller.get_current_focus_id(),
+            &Some(FocusId::button("BACK"))
+        );
+        let list = LayoutId::sublayout("Downloads", "List");
+        controller
+            .with_sublayout(&list, |l| l.insert_to_growable_grid(FocusId::button("a")))
+            .unwrap()
+            .unwrap();
+        controller.focus(&FocusId::button("a")).unwrap();
+        assert_eq!(controller.current_layout_id(), Some(list));
+
+        let def: LayoutDef = serde_json::from_str(
+            r#"{
+                "size": [3, 2],
+                "elements": [{ "rect": [0, 2, 0, 0], "id": "BTN@BACK" }],
+                "sublayouts": [{
+                    "rect": [0, 2, 1, 1], "name": "Row", "size": [3, 1],
+                    "carousel": { "items": ["BTN@A", "BTN@B"] }
+                }]
+            }"#,
+        )
+        .unwrap();
+        let mut controller = def.build(Screen::Downloads).unwrap();
+        controller.focus(&FocusId::button("B")).unwrap();
+        let rect = controller.find_rect(&FocusId::button("B")).unwrap();
+        assert_eq!(rect.rect.x(), 1);
+
+        let def: LayoutDef = serde_json::from_str(
+            r#"{
+                "size": [1, 2],
+                "elements": [{ "rect": [0, 0, 0, 0], "id": "BTN@BACK" }],
+                "sublayouts": [{
+                    "rect": [0, 0, 1, 1], "name": "List", "size": [1, 1],
+                    "list": [{ "title": "library", "items": ["BTN@A", "BTN@B"] }]
+                }]
+            }"#,
+        )
+        .unwrap();
+        let controller = def.build(Screen::Settings).unwrap();
+        let rect = controller.find_rect(&FocusId::button("B")).unwrap();
+        assert_eq!(rect.rect.y(), 2);
+
+        // Ends before it starts.
+        assert!(serde_json::from_str::<LayoutDef>(
+            r#"{ "size": [1, 1], "elements": [{ "rect": [1, 0, 0, 0], "id": "BTN@A" }] }"#
+        )
+        .is_err());
+    }
+}
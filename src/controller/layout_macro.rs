@@ -0,0 +1,131 @@
+// `layout!` puts a screen together from what goes in which cells, instead
+// of builder calls and `Rect::new(x_start, x_end, y_start, y_end)`:
+//
+//     layout! {
+//         Home 5 x 6 {
+//             (0, 0) => button "GAMES",
+//             (1..=4, 0) => button "SETTINGS",
+//             (0..=4, 1..=5) => Games 7 x 10 { growable(1, 1, GrowX) },
+//             on LeftTrigger => NavigateOutLeft,
+//         }
+//     }
+//
+// Cells are `(x, y)`, each one cell or an inclusive range of them. An
+// element is the kind of its FocusId with the payload, a sublayout is its
+// name and size, with what's in it written the same way. Inside a layout,
+// `growable(x, y, direction)`, `carousel`, `list`, `tabs` and `split` set
+// its kind, and `on` handles a button, see `LayoutGridBuilder`. Sublayouts
+// are named after the screen, so `Games` above is `Home@Games`.
+//
+// It builds the screen's controller and gives a Result of it, the errors of
+// the builder are returned early with `?`.
+
+macro_rules! layout {
+    ($screen:ident $w:literal x $h:literal { $($body:tt)* }) => {{
+        let mut builder = $crate::controller::grid::LayoutGridBuilder::new(
+            $w,
+            $h,
+            $crate::controller::LayoutId::root(stringify!($screen)),
+        );
+        layout!(@body $screen builder $($body)*);
+        $crate::controller::NavigationController::new(builder.build()?)
+    }};
+
+    (@body $screen:ident $b:ident) => {};
+    (@body $screen:ident $b:ident on $button:ident => $action:ident $(, $($rest:tt)*)?) => {
+        $b.on_button(
+            gilrs::Button::$button,
+            $crate::controller::grid::SpecialHandlerAction::$action,
+        );
+        layout!(@body $screen $b $($($rest)*)?);
+    };
+    (@body $screen:ident $b:ident
+        growable($x:literal, $y:literal, $dir:ident) $(, $($rest:tt)*)?) => {
+        $b.set_growable($x, $y, $crate::controller::grid::GrowDirection::$dir)?;
+        layout!(@body $screen $b $($($rest)*)?);
+    };
+    (@body $screen:ident $b:ident $kind:ident $(, $($rest:tt)*)?) => {
+        layout!(@kind $b $kind);
+        layout!(@body $screen $b $($($rest)*)?);
+    };
+    (@body $screen:ident $b:ident $cell:tt => $kind:ident $payload:literal
+        $(, $($rest:tt)*)?) => {
+        $b.add_element(layout!(@rect $cell), $crate::controller::FocusId::$kind($payload))?;
+        layout!(@body $screen $b $($($rest)*)?);
+    };
+    (@body $screen:ident $b:ident $cell:tt => $name:ident $w:literal x $h:literal
+        { $($sub:tt)* } $(, $($rest:tt)*)?) => {
+        {
+            #[allow(unused_variables)]
+            let sub = $b.with_sublayout(
+                layout!(@rect $cell),
+                $crate::controller::LayoutId::sublayout(stringify!($screen), stringify!($name)),
+                $w,
+                $h,
+            );
+            layout!(@body $screen sub $($sub)*);
+        }
+        layout!(@body $screen $b $($($rest)*)?);
+    };
+
+    (@kind $b:ident carousel) => { $b.set_carousel()? };
+    (@kind $b:ident list) => { $b.set_list()? };
+    (@kind $b:ident tabs) => { $b.set_tabs()? };
+    (@kind $b:ident split) => { $b.set_split()? };
+
+    (@rect ($x0:literal $(..= $x1:literal)?, $y0:literal $(..= $y1:literal)?)) => {
+        $crate::controller::grid::Rect::new(
+            $x0,
+            layout!(@last $x0 $($x1)?),
+            $y0,
+            layout!(@last $y0 $($y1)?),
+        )?
+    };
+    (@last $a:literal) => { $a };
+    (@last $a:literal $b:literal) => { $b };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::grid::Rect;
+    use super::super::{Direction, FocusId, LayoutId, NavigationController, NavigationDirective};
+    use anyhow::Result;
+    use gilrs::Button;
+
+    fn sample() -> Result<NavigationController> {
+        layout! {
+            Sample 3 x 4 {
+                (0, 0) => button "BACK",
+                (1..=2, 0) => tab "WIDE",
+                (0..=2, 1..=3) => Tabs 1 x 1 {
+                    tabs,
+                    (0, 0) => Grid 2 x 2 { growable(1, 1, GrowX) },
+                    (0, 0) => Other 1 x 1 {},
+                },
+                on LeftTrigger => NavigateOutLeft,
+            }
+        }
+    }
+
+    #[test]
+    fn lays_out_what_it_says() -> Result<()> {
+        let mut sut = sample()?;
+        assert_eq!(sut.find_rect(&FocusId::tab("WIDE")).unwrap().rect, Rect::new(1, 2, 0, 0)?);
+        let tabs = LayoutId::sublayout("Sample", "Tabs");
+        assert_eq!(sut.layout_size(&tabs)?, (1, 1));
+        assert_eq!(sut.current_tab(&tabs)?, LayoutId::sublayout("Sample", "Grid"));
+        sut.with_sublayout(&LayoutId::sublayout("Sample", "Grid"), |l| {
+            l.insert_to_growable_grid(FocusId::game("a"))
+        })??;
+        let other = sut.with_sublayout(&LayoutId::sublayout("Sample", "Other"), |l| l.size())?;
+        assert_eq!(other, (1, 1));
+
+        sut.navigate(NavigationDirective::Direction(Direction::Down))?;
+        assert_eq!(sut.get_current_focus_id(), &Some(FocusId::game("a")));
+        // Back to the left edge, then a step in.
+        sut.reset()?;
+        sut.navigate(NavigationDirective::Button(Button::LeftTrigger))?;
+        assert_eq!(sut.get_current_focus_id(), &Some(FocusId::tab("WIDE")));
+        Ok(())
+    }
+}
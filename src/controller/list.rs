@@ -0,0 +1,102 @@
+use super::FocusId;
+use anyhow::{bail, Result};
+
+// A vertical list of items in groups, like the settings, one item per row
+// across the whole layout. Each group starts with a header row, which the
+// focus skips over since there's nothing in its cells, but which the UI is
+// told about to draw the header of the group scrolled into, stuck at the top.
+// See `LayoutGridBuilder::set_list`.
+
+/// A group's header, for the UI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListHeader {
+    pub title: String,
+    /// Of the header itself.
+    pub row: usize,
+    /// The last row of the group.
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct List {
+    groups: Vec<(String, Vec<FocusId>)>,
+}
+
+impl List {
+    /// Start a group, the items added next go under it.
+    pub fn add_group(&mut self, title: &str) -> Result<()> {
+        if self.groups.iter().any(|(t, _)| t == title) {
+            bail!("there's already a group {}", title);
+        }
+        self.groups.push((title.to_owned(), vec![]));
+        Ok(())
+    }
+
+    /// Add an item at the end of the last group.
+    pub fn push(&mut self, focus_id: FocusId) -> Result<()> {
+        if self
+            .groups
+            .iter()
+            .any(|(_, items)| items.contains(&focus_id))
+        {
+            bail!("{} is already in the list", focus_id);
+        }
+        match self.groups.last_mut() {
+            Some((_, items)) => items.push(focus_id),
+            None => bail!("{} needs a group to go in first", focus_id),
+        }
+        Ok(())
+    }
+
+    /// What each row has, None for headers.
+    pub fn rows(&self) -> Vec<Option<FocusId>> {
+        self.groups
+            .iter()
+            .flat_map(|(_, items)| std::iter::once(None).chain(items.iter().cloned().map(Some)))
+            .collect()
+    }
+
+    pub fn headers(&self) -> Vec<ListHeader> {
+        let mut row = 0;
+        self.groups
+            .iter()
+            .map(|(title, items)| {
+                let header = ListHeader {
+                    title: title.clone(),
+                    row,
+                    end: row + items.len(),
+                };
+                row = header.end + 1;
+                header
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn puts_a_header_row_before_each_group() {
+        let mut sut = List::default();
+        assert!(sut.push(FocusId::button("A")).is_err());
+        sut.add_group("first").unwrap();
+        sut.push(FocusId::button("A")).unwrap();
+        sut.push(FocusId::button("B")).unwrap();
+        assert!(sut.add_group("first").is_err());
+        sut.add_group("empty").unwrap();
+        sut.add_group("last").unwrap();
+        sut.push(FocusId::button("C")).unwrap();
+        assert!(sut.push(FocusId::button("A")).is_err());
+
+        let rows: Vec<_> = sut
+            .rows()
+            .iter()
+            .map(|r| r.as_ref().map_or("-", |id| id.payload()).to_owned())
+            .collect();
+        assert_eq!(rows, ["-", "A", "B", "-", "-", "C"]);
+        let headers: Vec<_> = sut.headers().iter().map(|h| (h.row, h.end)).collect();
+        assert_eq!(headers, [(0, 2), (3, 3), (4, 5)]);
+    }
+}
@@ -1,40 +1,660 @@
-use self::grid::Rect;
 use anyhow::Result;
 
+mod carousel;
+pub mod chord;
+pub mod cursor;
 mod grid;
+mod id;
+pub mod input;
+pub mod input_log;
+pub mod layout_file;
+#[macro_use]
+mod layout_macro;
+mod list;
+mod pad;
+mod payload;
+pub mod replay;
+mod router;
+pub mod sequence;
+mod tabs;
+mod ui_bridge;
+pub mod viewport;
 
-pub use self::grid::{Direction, NavigationController, NavigationDirective, NavigationResult};
-
-// ╔═════════╦════════════════╦═════════╦══════════╦══╦══╦══╦══╦══╦══╗
-// ║ Games   ║ RecentlyPlayed ║         ║ Settings ║  ║  ║  ║  ║  ║  ║
-// ╠═════════╬════════════════╬═════════╬══════════╬══╬══╬══╬══╬══╬══╣
-// ║ S_Games ║ S_Games        ║ S_Games ║ S_Games  ║  ║  ║  ║  ║  ║  ║
-// ╠═════════╬════════════════╬═════════╬══════════╬══╬══╬══╬══╬══╬══╣
-// ║ S_Games ║ S_Games        ║ S_Games ║ S_Games  ║  ║  ║  ║  ║  ║  ║
-// ╠═════════╬════════════════╬═════════╬══════════╬══╬══╬══╬══╬══╬══╣
-// ║ S_Games ║ S_Games        ║ S_Games ║ S_Games  ║  ║  ║  ║  ║  ║  ║
-// ╠═════════╬════════════════╬═════════╬══════════╬══╬══╬══╬══╬══╬══╣
-// ║ S_Games ║ S_Games        ║ S_Games ║ S_Games  ║  ║  ║  ║  ║  ║  ║
-// ╠═════════╬════════════════╬═════════╬══════════╬══╬══╬══╬══╬══╬══╣
-// ║ S_Games ║ S_Games        ║ S_Games ║ S_Games  ║  ║  ║  ║  ║  ║  ║
-// ╠═════════╬════════════════╬═════════╬══════════╬══╬══╬══╬══╬══╬══╣
-// ║         ║                ║         ║          ║  ║  ║  ║  ║  ║  ║
-// ╠═════════╬════════════════╬═════════╬══════════╬══╬══╬══╬══╬══╬══╣
-// ║         ║                ║         ║          ║  ║  ║  ║  ║  ║  ║
-// ╠═════════╬════════════════╬═════════╬══════════╬══╬══╬══╬══╬══╬══╣
-// ║         ║                ║         ║          ║  ║  ║  ║  ║  ║  ║
-// ╠═════════╬════════════════╬═════════╬══════════╬══╬══╬══╬══╬══╬══╣
-// ║         ║                ║         ║          ║  ║  ║  ║  ║  ║  ║
-// ╚═════════╩════════════════╩═════════╩══════════╩══╩══╩══╩══╩══╩══╝
+pub use self::grid::{
+    CellKind, Direction, FocusRect, NavigationController, NavigationDirective, NavigationResult,
+};
+pub use self::id::{FocusId, FocusKind, LayoutId};
+pub use self::pad::PadKind;
+pub use self::router::{Router, Screen};
+pub use self::ui_bridge::{FrameCoalescer, ModelUpdate, UiBridge};
+
+// ╔═════════╦════════════════╦═══════════╦═══════════╦══════════╗
+// ║ Games   ║ RecentlyPlayed ║ Suspended ║ Downloads ║ Settings ║
+// ╠═════════╬════════════════╬═══════════╬═══════════╬══════════╣
+// ║ S_Picks ║ S_Picks        ║ S_Picks   ║ S_Picks   ║ S_Picks  ║
+// ╠═════════╬════════════════╬═══════════╬═══════════╬══════════╣
+// ║ S_Games ║ S_Games        ║ S_Games   ║ S_Games   ║ S_Games  ║
+// ╠═════════╬════════════════╬═══════════╬═══════════╬══════════╣
+// ║ S_Games ║ S_Games        ║ S_Games   ║ S_Games   ║ S_Games  ║
+// ╠═════════╬════════════════╬═══════════╬═══════════╬══════════╣
+// ║ S_Games ║ S_Games        ║ S_Games   ║ S_Games   ║ S_Games  ║
+// ╠═════════╬════════════════╬═══════════╬═══════════╬══════════╣
+// ║ S_Games ║ S_Games        ║ S_Games   ║ S_Games   ║ S_Games  ║
+// ╠═════════╬════════════════╬═══════════╬═══════════╬══════════╣
+// ║ S_Games ║ S_Games        ║ S_Games   ║ S_Games   ║ S_Games  ║
+// ╚═════════╩════════════════╩═══════════╩═══════════╩══════════╝
+//
+// S_Picks is a carousel of what to play, empty until worked out, see
+// `recommend`.
 
 pub fn create_home_window_controller() -> Result<NavigationController> {
-    let mut builder = grid::LayoutGridBuilder::new(4, 6, "Home".to_owned());
-    builder
-        .add_element(Rect::new(0, 0, 0, 0)?, "BTN@GAMES".to_owned())?
-        .add_element(Rect::new(1, 1, 0, 0)?, "BTN@RECENTLY_PLAYED".to_owned())?
-        .add_element(Rect::new(3, 3, 0, 0)?, "BTN@SETTINGS".to_owned())?;
-    let sub = builder.with_sublayout(Rect::new(0, 3, 1, 5)?, "Home@Games".to_owned(), 7, 10);
-    sub.set_growable(1, 1, grid::GrowDirection::GrowX)?;
-    let controller = grid::NavigationController::new(builder.build()?);
-    controller
+    layout! {
+        Home 5 x 7 {
+            (0, 0) => button "GAMES",
+            (1, 0) => button "RECENTLY_PLAYED",
+            (2, 0) => button "SUSPENDED",
+            (3, 0) => button "DOWNLOADS",
+            (4, 0) => button "SETTINGS",
+            (0..=4, 1) => Picks 5 x 1 { carousel },
+            (0..=4, 2..=6) => Games 7 x 10 { growable(1, 1, GrowX) },
+        }
+    }
+}
+
+/// Add the game tiles to the home screen, in library order.
+pub fn layout_games<'a>(
+    controller: &NavigationController,
+    uuids: impl IntoIterator<Item = &'a str>,
+) -> Result<()> {
+    controller.with_sublayout(&LayoutId::sublayout("Home", "Games"), |l| {
+        for uuid in uuids {
+            l.insert_to_growable_grid(FocusId::game(uuid))?;
+        }
+        // The grid starts bigger than a few games need.
+        l.shrink_to_fit()
+    })?
+}
+
+/// Put the suggestions of what to play on the home screen, in place of
+/// those there were.
+pub fn layout_picks<'a>(
+    controller: &NavigationController,
+    uuids: impl IntoIterator<Item = &'a str>,
+) -> Result<()> {
+    controller.with_sublayout(&LayoutId::sublayout("Home", "Picks"), |l| {
+        l.clear_carousel()?;
+        uuids
+            .into_iter()
+            .try_for_each(|uuid| l.insert_to_carousel(FocusId::pick(uuid)))
+    })?
+}
+
+/// Take a game tile off the home screen, e.g. after merging it away.
+pub fn remove_game(controller: &mut NavigationController, uuid: &str) -> Result<()> {
+    controller.remove_elem(&LayoutId::sublayout("Home", "Games"), &FocusId::game(uuid))
+}
+
+/// Focus a game tile of the home screen, straight in its sublayout.
+pub fn focus_game(controller: &mut NavigationController, uuid: &str) -> Result<()> {
+    controller.focus_by_id(&LayoutId::sublayout("Home", "Games"), &FocusId::game(uuid))
+}
+
+/// Rows of game tiles on the home screen, for the scroll range.
+pub fn games_rows(controller: &NavigationController) -> Result<usize> {
+    controller.with_sublayout(&LayoutId::sublayout("Home", "Games"), |l| l.size().1)
+}
+
+// ╔══════╦══╦══╦══╗
+// ║ Back ║  ║  ║  ║
+// ╠══════╩══╩══╩══╣
+// ║ S_Downloads   ║
+// ╠═══════════════╣
+// ║ S_Downloads   ║
+// ╠═══════════════╣
+// ║ ...           ║
+// ╚═══════════════╝
+//
+// Each row of S_Downloads has a pause and a cancel button.
+
+pub fn create_downloads_controller() -> Result<NavigationController> {
+    layout! {
+        Downloads 4 x 11 {
+            (0, 0) => button "BACK",
+            // Pause and cancel per row, expanding downwards.
+            (0..=3, 1..=10) => List 2 x 10 { growable(1, 1, GrowX) },
+        }
+    }
+}
+
+// ╔════════╦════════════╦═════════════╗
+// ║ Cancel ║ Keep local ║ Keep remote ║
+// ╚════════╩════════════╩═════════════╝
+//
+// The keep buttons only show up on a conflict.
+
+pub fn create_sync_dialog_controller() -> Result<NavigationController> {
+    layout! {
+        SyncDialog 3 x 1 {
+            (0, 0) => button "SYNC_CANCEL",
+            (1, 0) => button "SYNC_KEEP_LOCAL",
+            (2, 0) => button "SYNC_KEEP_REMOTE",
+        }
+    }
+}
+
+// ╔══════╦══════╦═════════╦═══════╦═══════╦══════════╦══════════════╦═════════════╦══════╦══════════╗
+// ║ Back ║ Play ║ Overlay ║ Saves ║ Notes ║ Overview ║ Achievements ║ Screenshots ║ News ║ Sessions ║
+// ╠══════╩══════╩═════════╩═══════╩═══════╩══════════╩══════════════╩═════════════╩══════╩══════════╣
+// ║ S_Tabs                                                                                          ║
+// ╠═════════════════════════════════════════════════════════════════════════════════════════════════╣
+// ║ ...                                                                                             ║
+// ╚═════════════════════════════════════════════════════════════════════════════════════════════════╝
+//
+// S_Tabs shows one of S_Overview, which has nothing to focus,
+// S_Achievements, S_Screenshots, S_News and S_Sessions, switched with the
+// tab buttons or L1/R1.
+
+pub fn create_game_details_controller() -> Result<NavigationController> {
+    layout! {
+        GameDetails 10 x 11 {
+            (0, 0) => button "BACK",
+            (1, 0) => button "PLAY",
+            (2, 0) => button "OVERLAY",
+            (3, 0) => button "SAVES",
+            (4, 0) => button "NOTES",
+            (5, 0) => tab "OVERVIEW",
+            (6, 0) => tab "ACHIEVEMENTS",
+            (7, 0) => tab "SCREENSHOTS",
+            (8, 0) => tab "NEWS",
+            (9, 0) => tab "SESSIONS",
+            (0..=9, 1..=10) => Tabs 1 x 1 {
+                tabs,
+                (0, 0) => Overview 1 x 1 {},
+                // One achievement per row.
+                (0, 0) => Achievements 1 x 10 { growable(1, 1, GrowX) },
+                // Three screenshots per row.
+                (0, 0) => Screenshots 3 x 3 { growable(1, 1, GrowX) },
+                // One news item per row.
+                (0, 0) => News 1 x 10 { growable(1, 1, GrowX) },
+                // One session per row, the newest first.
+                (0, 0) => Sessions 1 x 10 { growable(1, 1, GrowX) },
+            },
+        }
+    }
+}
+
+// ╔═══════════════════╗
+// ║ Back              ║
+// ╠═══════════════════╣
+// ║ Library           ║
+// ║ Back up library   ║
+// ║ Restore (merge)   ║
+// ║ ...               ║
+// ║ System            ║
+// ║ Language          ║
+// ║ ...               ║
+// ╚═══════════════════╝
+//
+// The buttons are a list under headers, see `SETTINGS`.
+
+/// Groups of the settings list, by the string of their header.
+pub const SETTINGS: [(&str, &[&str]); 5] = [
+    (
+        "library",
+        &[
+            "BACKUP_LIBRARY",
+            "RESTORE_MERGE",
+            "RESTORE_REPLACE",
+            "FIND_DUPLICATES",
+            "RESOLVE_MATCHES",
+            "TRASH",
+        ],
+    ),
+    (
+        "system",
+        &[
+            "LANGUAGE",
+            "CLOCK",
+            "SOFTWARE_UPDATE",
+            "DIAGNOSTICS",
+            "STORAGE",
+            "CLEAR_ART_CACHE",
+        ],
+    ),
+    (
+        "playing",
+        &["PROFILES", "RESUME_LAST_GAME", "QUIET_HOURS", "PLAY_STATS"],
+    ),
+    (
+        "home-automation",
+        &["MQTT", "MQTT_BROKER", "MQTT_USERNAME", "MQTT_PASSWORD"],
+    ),
+    ("power", &["EXIT_TO_DESKTOP"]),
+];
+
+pub fn create_settings_controller() -> Result<NavigationController> {
+    let controller = layout! {
+        Settings 1 x 2 {
+            (0, 0) => button "BACK",
+            (0, 1) => List 1 x 1 { list },
+        }
+    }?;
+    controller.with_sublayout(&LayoutId::sublayout("Settings", "List"), |l| -> Result<()> {
+        for (title, buttons) in SETTINGS {
+            l.add_list_group(title)?;
+            for button in buttons {
+                l.insert_to_list(FocusId::button(button))?;
+            }
+        }
+        Ok(())
+    })??;
+    Ok(controller)
+}
+
+// ╔══════╦═══════╦══════╗
+// ║ Back ║ Merge ║ Skip ║
+// ╚══════╩═══════╩══════╝
+//
+// One group of duplicates is reviewed at a time.
+
+pub fn create_duplicates_controller() -> Result<NavigationController> {
+    layout! {
+        Duplicates 3 x 1 {
+            (0, 0) => button "BACK",
+            (1, 0) => button "MERGE_DUPLICATES",
+            (2, 0) => button "SKIP_DUPLICATES",
+        }
+    }
+}
+
+// ╔══════╦════════════════════╦══════╗
+// ║ Back ║ Which one is Doom? ║ Skip ║
+// ╠══════╩════════════════════╩══════╣
+// ║ S_Candidates                     ║
+// ╚══════════════════════════════════╝
+//
+// The games waiting for a pick are shown one at a time, S_Candidates has a
+// card per store result it could be.
+
+pub fn create_matches_controller() -> Result<NavigationController> {
+    layout! {
+        Matches 3 x 2 {
+            (0, 0) => button "BACK",
+            (2, 0) => button "SKIP_MATCH",
+            (0..=2, 1) => Candidates 5 x 1 { growable(1, 1, GrowX) },
+        }
+    }
+}
+
+// ╔══════╦═════════╦═══╗
+// ║ Back ║         ║   ║
+// ╠══════╬═════════╬═══╣
+// ║ ◀    ║ Europe/ ║ ▶ ║
+// ╠══════╩═════════╩═══╣
+// ║ Sync time          ║
+// ╚════════════════════╝
+//
+// The time zone in the middle is shown, not focused.
+
+pub fn create_clock_controller() -> Result<NavigationController> {
+    layout! {
+        Clock 3 x 3 {
+            (0, 0) => button "BACK",
+            (0, 1) => button "TZ_PREV",
+            (2, 1) => button "TZ_NEXT",
+            (0..=2, 2) => button "NTP",
+        }
+    }
+}
+
+// ╔══════╦═════════════════╗
+// ║ Back ║ Install update  ║
+// ╚══════╩═════════════════╝
+//
+// The changelog below is shown, not focused.
+
+pub fn create_update_controller() -> Result<NavigationController> {
+    layout! {
+        Update 2 x 1 {
+            (0, 0) => button "BACK",
+            (1, 0) => button "INSTALL_UPDATE",
+        }
+    }
+}
+
+// ╔══════╦═════════════╦═════╗
+// ║ Back ║             ║ New ║
+// ╠══════╬═════════════╬═════╣
+// ║ ◀    ║ Performance ║ ▶   ║
+// ╠══════╩═════════════╩═════╣
+// ║ CPU governor             ║
+// ╠══════════════════════════╣
+// ║ HDR                      ║
+// ╠══════════════════════════╣
+// ║ Variable refresh rate    ║
+// ╠══════════════════════════╣
+// ║ Compositor bypass        ║
+// ╚══════════════════════════╝
+//
+// The settings below the name are of the profile shown.
+
+pub fn create_profiles_controller() -> Result<NavigationController> {
+    layout! {
+        Profiles 3 x 6 {
+            (0, 0) => button "BACK",
+            (2, 0) => button "NEW_PROFILE",
+            (0, 1) => button "PROFILE_PREV",
+            (2, 1) => button "PROFILE_NEXT",
+            (0..=2, 2) => button "GOVERNOR",
+            (0..=2, 3) => button "HDR",
+            (0..=2, 4) => button "VRR",
+            (0..=2, 5) => button "BYPASS",
+        }
+    }
+}
+
+// ╔══════╦═══════════════════╦═════════════╗
+// ║ Back ║                   ║ Empty trash ║
+// ╠══════╬═══════════════════╬═════════════╣
+// ║ ◀    ║ Celeste, deleted  ║ ▶           ║
+// ╠══════╩═══════════════════╬═════════════╣
+// ║ Restore                  ║ Purge       ║
+// ╚══════════════════════════╩═════════════╝
+//
+// What was deleted or scraped over, one at a time, see `library::trash`.
+
+pub fn create_trash_controller() -> Result<NavigationController> {
+    layout! {
+        Trash 3 x 3 {
+            (0, 0) => button "BACK",
+            (2, 0) => button "EMPTY_TRASH",
+            (0, 1) => button "TRASH_PREV",
+            (2, 1) => button "TRASH_NEXT",
+            (0..=1, 2) => button "RESTORE_TRASHED",
+            (2, 2) => button "PURGE_TRASHED",
+        }
+    }
+}
+
+// ╔══════╦═══════════════════╦═════════╗
+// ║ Back ║                   ║ Back up ║
+// ╠══════╬═══════════════════╬═════════╣
+// ║ ◀    ║ 2024-05-01 20:13  ║ ▶       ║
+// ╠══════╩═══════════════════╩═════════╣
+// ║ Restore                            ║
+// ╚════════════════════════════════════╝
+//
+// Restore puts back the snapshot shown.
+
+pub fn create_saves_controller() -> Result<NavigationController> {
+    layout! {
+        Saves 3 x 3 {
+            (0, 0) => button "BACK",
+            (2, 0) => button "BACKUP_SAVES",
+            (0, 1) => button "SAVE_PREV",
+            (2, 1) => button "SAVE_NEXT",
+            (0..=2, 2) => button "RESTORE_SAVE",
+        }
+    }
+}
+
+// ╔══════╦════════╦═════════╗
+// ║ Back ║ Weekly ║ Monthly ║
+// ╚══════╩════════╩═════════╝
+//
+// The playtime of the last weeks or months is charted under, then listed by
+// genre and by game, nothing in them can be focused.
+
+pub fn create_stats_controller() -> Result<NavigationController> {
+    layout! {
+        Stats 3 x 1 {
+            (0, 0) => button "BACK",
+            (1, 0) => button "STATS_WEEKLY",
+            (2, 0) => button "STATS_MONTHLY",
+        }
+    }
+}
+
+// ╔══════╦══════════════════╦════════════╗
+// ║ Back ║ Storage          ║ Scan again ║
+// ╠══════╬══════════════════╬════════════╣
+// ║ ◀    ║ Unplayed, 40 GB  ║ ▶          ║
+// ╠══════╩══════════════════╩════════════╣
+// ║ Uninstall                            ║
+// ╚══════════════════════════════════════╝
+//
+// The drives and the largest games are listed between the first two rows,
+// nothing in them can be focused.
+
+pub fn create_storage_controller() -> Result<NavigationController> {
+    layout! {
+        Storage 3 x 3 {
+            (0, 0) => button "BACK",
+            (2, 0) => button "RESCAN_STORAGE",
+            (0, 1) => button "SUGGESTION_PREV",
+            (2, 1) => button "SUGGESTION_NEXT",
+            (0..=2, 2) => button "UNINSTALL_SUGGESTED",
+        }
+    }
+}
+
+// ╔══════╦════════════════════╦═══════╗
+// ║ Back ║                    ║       ║
+// ╠══════╬════════════════════╬═══════╣
+// ║ ◀    ║ Celeste, 12 min    ║ ▶     ║
+// ╠══════╬════════════════════╬═══════╣
+// ║ Resume                    ║ Quit  ║
+// ╚═══════════════════════════╩═══════╝
+//
+// The games paused to go back to the launcher, see `launcher::suspend`.
+
+pub fn create_suspended_controller() -> Result<NavigationController> {
+    layout! {
+        Suspended 3 x 3 {
+            (0, 0) => button "BACK",
+            (0, 1) => button "SUSPENDED_PREV",
+            (2, 1) => button "SUSPENDED_NEXT",
+            (0..=1, 2) => button "RESUME_SUSPENDED",
+            (2, 2) => button "QUIT_SUSPENDED",
+        }
+    }
+}
+
+// ╔════════╦═════════╗
+// ║ Cancel ║ Confirm ║
+// ╚════════╩═════════╝
+//
+// The question is above the buttons. Cancel is focused first, so a stray A
+// doesn't confirm.
+
+pub fn create_confirm_controller() -> Result<NavigationController> {
+    layout! {
+        Confirm 2 x 1 {
+            (0, 0) => button "CONFIRM_CANCEL",
+            (1, 0) => button "CONFIRM_OK",
+        }
+    }
+}
+
+// ╔════════════╗
+// ║ Play       ║
+// ╠════════════╣
+// ║ Favorite   ║
+// ╠════════════╣
+// ║ Status     ║
+// ╠════════════╣
+// ║ Update     ║
+// ╠════════════╣
+// ║ Uninstall  ║
+// ╠════════════╣
+// ║ Properties ║
+// ╚════════════╝
+//
+// Opened with X on a game tile, next to it. There's no metadata editor to
+// list yet.
+
+pub fn create_game_menu_controller() -> Result<NavigationController> {
+    layout! {
+        GameMenu 1 x 6 {
+            (0, 0) => button "MENU_PLAY",
+            (0, 1) => button "MENU_FAVORITE",
+            (0, 2) => button "MENU_STATUS",
+            (0, 3) => button "MENU_UPDATE",
+            (0, 4) => button "MENU_UNINSTALL",
+            (0, 5) => button "MENU_PROPERTIES",
+        }
+    }
+}
+
+// ╔════════════════════╗
+// ║ Rescrape           ║
+// ╠════════════════════╣
+// ║ Add tag            ║
+// ╠════════════════════╣
+// ║ Move to collection ║
+// ╠════════════════════╣
+// ║ Delete             ║
+// ╠════════════════════╣
+// ║ Clear selection    ║
+// ╚════════════════════╝
+//
+// Opened with X while games are selected with Y, for all of them, see
+// `library::bulk`.
+
+pub fn create_bulk_menu_controller() -> Result<NavigationController> {
+    layout! {
+        BulkMenu 1 x 5 {
+            (0, 0) => button "BULK_RESCRAPE",
+            (0, 1) => button "BULK_TAG",
+            (0, 2) => button "BULK_COLLECTION",
+            (0, 3) => button "BULK_DELETE",
+            (0, 4) => button "BULK_CLEAR",
+        }
+    }
+}
+
+// ╔══════════════╦══════════════╗
+// ║ Brightness - ║ Brightness + ║
+// ╠══════════════╬══════════════╣
+// ║ Volume -     ║ Volume +     ║
+// ╠══════════════╬══════════════╣
+// ║ Wi-Fi        ║ Bluetooth    ║
+// ╠══════════════╩══════════════╣
+// ║ Quiet hours                 ║
+// ╚═════════════════════════════╝
+//
+// Pulled down over the current screen, B closes it.
+
+pub fn create_quick_settings_controller() -> Result<NavigationController> {
+    layout! {
+        QuickSettings 2 x 4 {
+            (0, 0) => button "BRIGHTNESS_DOWN",
+            (1, 0) => button "BRIGHTNESS_UP",
+            (0, 1) => button "VOLUME_DOWN",
+            (1, 1) => button "VOLUME_UP",
+            (0, 2) => button "WIFI",
+            (1, 2) => button "BLUETOOTH",
+            (0..=1, 3) => button "QUIET_HOURS",
+        }
+    }
+}
+
+// ╔══════╦═══╦════╗
+// ║ Back ║   ║    ║
+// ╠══════╬═══╬════╣
+// ║ 1    ║ 2 ║ 3  ║
+// ╠══════╬═══╬════╣
+// ║ 4    ║ 5 ║ 6  ║
+// ╠══════╬═══╬════╣
+// ║ 7    ║ 8 ║ 9  ║
+// ╠══════╬═══╬════╣
+// ║ ⌫    ║ 0 ║ OK ║
+// ╚══════╩═══╩════╝
+//
+// The PIN asked to exit to the desktop in kiosk mode, see `kiosk`.
+
+pub fn create_pin_controller() -> Result<NavigationController> {
+    layout! {
+        Pin 3 x 5 {
+            (0, 0) => button "BACK",
+            (0, 1) => button "PIN_1",
+            (1, 1) => button "PIN_2",
+            (2, 1) => button "PIN_3",
+            (0, 2) => button "PIN_4",
+            (1, 2) => button "PIN_5",
+            (2, 2) => button "PIN_6",
+            (0, 3) => button "PIN_7",
+            (1, 3) => button "PIN_8",
+            (2, 3) => button "PIN_9",
+            (0, 4) => button "PIN_DEL",
+            (1, 4) => button "PIN_0",
+            (2, 4) => button "PIN_OK",
+        }
+    }
+}
+
+// ╔═══════╦═══════════════════════╦═══════╗
+// ║ Back  ║                       ║ OK    ║
+// ╠═══╦═══╬═══╦═══╦═══╦═══╦═══╦═══╬═══╦═══╣
+// ║ 1 ║ 2 ║ 3 ║ 4 ║ 5 ║ 6 ║ 7 ║ 8 ║ 9 ║ 0 ║
+// ║ q ║ w ║ e ║ r ║ t ║ y ║ u ║ i ║ o ║ p ║
+// ║ a ║ s ║ d ║ f ║ g ║ h ║ j ║ k ║ l ║ : ║
+// ║ z ║ x ║ c ║ v ║ b ║ n ║ m ║ , ║ . ║ ? ║
+// ╠═══╩═══╬═══╩═══╩═══╩═══╩═══╩═══╬═══╬═══╣
+// ║ Shift ║ Space                 ║ ↵ ║ ⌫ ║
+// ╚═══════╩═══════════════════════╩═══╩═══╝
+//
+// The on-screen keyboard, see `keyboard`.
+
+pub fn create_keyboard_controller() -> Result<NavigationController> {
+    layout! {
+        Keyboard 10 x 6 {
+            (0..=1, 0) => button "BACK",
+            (8..=9, 0) => button "KEY_OK",
+            (0, 1) => button "KEY_1",
+            (1, 1) => button "KEY_2",
+            (2, 1) => button "KEY_3",
+            (3, 1) => button "KEY_4",
+            (4, 1) => button "KEY_5",
+            (5, 1) => button "KEY_6",
+            (6, 1) => button "KEY_7",
+            (7, 1) => button "KEY_8",
+            (8, 1) => button "KEY_9",
+            (9, 1) => button "KEY_0",
+            (0, 2) => button "KEY_q",
+            (1, 2) => button "KEY_w",
+            (2, 2) => button "KEY_e",
+            (3, 2) => button "KEY_r",
+            (4, 2) => button "KEY_t",
+            (5, 2) => button "KEY_y",
+            (6, 2) => button "KEY_u",
+            (7, 2) => button "KEY_i",
+            (8, 2) => button "KEY_o",
+            (9, 2) => button "KEY_p",
+            (0, 3) => button "KEY_a",
+            (1, 3) => button "KEY_s",
+            (2, 3) => button "KEY_d",
+            (3, 3) => button "KEY_f",
+            (4, 3) => button "KEY_g",
+            (5, 3) => button "KEY_h",
+            (6, 3) => button "KEY_j",
+            (7, 3) => button "KEY_k",
+            (8, 3) => button "KEY_l",
+            (9, 3) => button "KEY_:",
+            (0, 4) => button "KEY_z",
+            (1, 4) => button "KEY_x",
+            (2, 4) => button "KEY_c",
+            (3, 4) => button "KEY_v",
+            (4, 4) => button "KEY_b",
+            (5, 4) => button "KEY_n",
+            (6, 4) => button "KEY_m",
+            (7, 4) => button "KEY_,",
+            (8, 4) => button "KEY_.",
+            (9, 4) => button "KEY_?",
+            (0..=1, 5) => button "KEY_SHIFT",
+            (2..=7, 5) => button "KEY_SPACE",
+            (8, 5) => button "KEY_ENTER",
+            (9, 5) => button "KEY_DEL",
+        }
+    }
 }
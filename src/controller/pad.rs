@@ -0,0 +1,115 @@
+use gilrs::{Button, Gamepad};
+use serde::{Deserialize, Serialize};
+
+// Which kind of gamepad is in use, so the UI shows the glyphs printed on it.
+// gilrs names buttons by position, South is A on an Xbox pad but B on a
+// Switch Pro controller.
+
+const MICROSOFT: u16 = 0x045e;
+const SONY: u16 = 0x054c;
+const NINTENDO: u16 = 0x057e;
+
+/// The button layout of a gamepad, anything unknown gets the Xbox one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PadKind {
+    #[default]
+    Xbox,
+    DualShock,
+    SwitchPro,
+}
+
+impl PadKind {
+    pub fn of(gamepad: &Gamepad) -> Self {
+        // The SDL style UUID has the USB vendor in it, zero when the
+        // platform doesn't know it.
+        let uuid = gamepad.uuid();
+        let vendor = u16::from_le_bytes([uuid[4], uuid[5]]);
+        Self::detect(Some(vendor).filter(|&v| v != 0), gamepad.name())
+    }
+
+    /// From the USB vendor, or the name when the vendor doesn't tell.
+    pub fn detect(vendor: Option<u16>, name: &str) -> Self {
+        match vendor {
+            Some(MICROSOFT) => return PadKind::Xbox,
+            Some(SONY) => return PadKind::DualShock,
+            // Joy-Cons are laid out the same.
+            Some(NINTENDO) => return PadKind::SwitchPro,
+            _ => {}
+        }
+        let name = name.to_lowercase();
+        if ["playstation", "dualshock", "dualsense", "ps3", "ps4", "ps5"]
+            .iter()
+            .any(|n| name.contains(n))
+        {
+            PadKind::DualShock
+        } else if ["nintendo", "switch", "pro controller", "joy-con"]
+            .iter()
+            .any(|n| name.contains(n))
+        {
+            PadKind::SwitchPro
+        } else {
+            PadKind::Xbox
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PadKind::Xbox => "xbox",
+            PadKind::DualShock => "dualshock",
+            PadKind::SwitchPro => "switch_pro",
+        }
+    }
+
+    /// What's printed on the button, e.g. for "Press Ⓐ".
+    pub fn glyph(self, b: Button) -> &'static str {
+        match (self, b) {
+            (PadKind::Xbox, Button::South) => "Ⓐ",
+            (PadKind::Xbox, Button::East) => "Ⓑ",
+            (PadKind::Xbox, Button::North) => "Ⓨ",
+            (PadKind::Xbox, Button::West) => "Ⓧ",
+            (PadKind::DualShock, Button::South) => "✕",
+            (PadKind::DualShock, Button::East) => "○",
+            (PadKind::DualShock, Button::North) => "△",
+            (PadKind::DualShock, Button::West) => "□",
+            (PadKind::SwitchPro, Button::South) => "Ⓑ",
+            (PadKind::SwitchPro, Button::East) => "Ⓐ",
+            (PadKind::SwitchPro, Button::North) => "Ⓧ",
+            (PadKind::SwitchPro, Button::West) => "Ⓨ",
+            (_, Button::DPadUp) => "↑",
+            (_, Button::DPadDown) => "↓",
+            (_, Button::DPadLeft) => "←",
+            (_, Button::DPadRight) => "→",
+            _ => "?",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_pads() {
+        assert_eq!(
+            PadKind::detect(Some(SONY), "Wireless Controller"),
+            PadKind::DualShock
+        );
+        assert_eq!(
+            PadKind::detect(Some(NINTENDO), "Pro Controller"),
+            PadKind::SwitchPro
+        );
+        assert_eq!(
+            PadKind::detect(None, "Sony PLAYSTATION(R)3 Controller"),
+            PadKind::DualShock
+        );
+        assert_eq!(
+            PadKind::detect(Some(0x28de), "Steam Controller"),
+            PadKind::Xbox
+        );
+        // Same position, different glyph.
+        assert_eq!(PadKind::Xbox.glyph(Button::South), "Ⓐ");
+        assert_eq!(PadKind::DualShock.glyph(Button::South), "✕");
+        assert_eq!(PadKind::SwitchPro.glyph(Button::South), "Ⓑ");
+    }
+}
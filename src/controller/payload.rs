@@ -0,0 +1,58 @@
+use super::FocusId;
+use std::{any::Any, collections::HashMap, fmt, sync::Arc};
+
+// Typed data going with the elements of a layout, like the tab a button
+// shows or the row of an achievement, so handlers don't have to parse it
+// back out of the FocusId's payload string. Anything can be attached, and
+// it's given back when asked for as the same type. See
+// `LayoutGrid::set_payload`.
+
+#[derive(Clone, Default)]
+pub struct Payloads {
+    payloads: HashMap<FocusId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl Payloads {
+    /// Attach the payload to the element, replacing what it had.
+    pub fn insert<T: Any + Send + Sync>(&mut self, focus_id: FocusId, payload: T) {
+        self.payloads.insert(focus_id, Arc::new(payload));
+    }
+
+    /// None if there's nothing attached, or it's of another type.
+    pub fn get<T: Any + Clone>(&self, focus_id: &FocusId) -> Option<T> {
+        self.payloads.get(focus_id)?.downcast_ref().cloned()
+    }
+
+    pub fn remove(&mut self, focus_id: &FocusId) {
+        self.payloads.remove(focus_id);
+    }
+}
+
+impl fmt::Debug for Payloads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.payloads.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Action {
+        Open,
+    }
+
+    #[test]
+    fn gives_back_what_was_attached() {
+        let mut sut = Payloads::default();
+        sut.insert(FocusId::game("a"), Action::Open);
+        sut.insert(FocusId::achievement(2), 2usize);
+        assert_eq!(sut.get(&FocusId::game("a")), Some(Action::Open));
+        assert_eq!(sut.get::<usize>(&FocusId::achievement(2)), Some(2));
+        // Not as another type.
+        assert_eq!(sut.get::<u32>(&FocusId::achievement(2)), None);
+        sut.remove(&FocusId::game("a"));
+        assert_eq!(sut.get::<Action>(&FocusId::game("a")), None);
+    }
+}
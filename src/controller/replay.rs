@@ -0,0 +1,196 @@
+use super::{Direction, FocusId, NavigationDirective, Router, Screen};
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+// Scripted navigation for tests, and recordings of real sessions to replay
+// when reproducing a bug. Recordings are JSON lines, one step per D-pad press.
+
+/// `down right right` gives the directions, e.g. to script a test.
+#[cfg(test)]
+pub fn parse_script(script: &str) -> Result<Vec<Direction>> {
+    script
+        .split_whitespace()
+        .map(|w| match w.to_lowercase().as_str() {
+            "up" => Ok(Direction::Up),
+            "down" => Ok(Direction::Down),
+            "left" => Ok(Direction::Left),
+            "right" => Ok(Direction::Right),
+            _ => bail!("unknown direction {}", w),
+        })
+        .collect()
+}
+
+/// Feed the directions to the controller, returns the focus after each.
+#[cfg(test)]
+pub fn focus_path(
+    controller: &mut super::NavigationController,
+    directions: &[Direction],
+) -> Result<Vec<FocusId>> {
+    let mut path = vec![];
+    for &d in directions {
+        controller.navigate(NavigationDirective::Direction(d))?;
+        let focus_id = controller
+            .get_current_focus_id()
+            .clone()
+            .ok_or(anyhow!("nothing focused after {:?}", d))?;
+        path.push(focus_id);
+    }
+    Ok(path)
+}
+
+/// A D-pad press in a recorded session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Step {
+    pub screen: Screen,
+    /// Focus before the press.
+    pub from: Option<FocusId>,
+    pub direction: Direction,
+    pub to: Option<FocusId>,
+}
+
+/// Writes the steps of a session as they happen, so a crash keeps them.
+pub struct Recorder {
+    out: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(Self {
+            out: BufWriter::new(File::create(path)?),
+        })
+    }
+
+    pub fn record(&mut self, step: &Step) -> Result<()> {
+        serde_json::to_writer(&mut self.out, step)?;
+        self.out.write_all(b"\n")?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+pub fn load(path: &Path) -> Result<Vec<Step>> {
+    fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| serde_json::from_str(l).map_err(|e| anyhow!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// How a replay went.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Replay {
+    pub replayed: usize,
+    /// Steps starting from an element the layouts don't have, e.g. a
+    /// download which isn't there anymore.
+    pub skipped: usize,
+}
+
+/// Replay the steps, each from its recorded focus. Fails on the first step
+/// landing somewhere else than recorded.
+pub fn replay(router: &mut Router, steps: &[Step]) -> Result<Replay> {
+    let mut result = Replay::default();
+    for (i, step) in steps.iter().enumerate() {
+        let controller = router.controller_for(step.screen)?;
+        if let Some(ref from) = step.from {
+            if controller.focus(from).is_err() {
+                result.skipped += 1;
+                continue;
+            }
+        }
+        controller.navigate(NavigationDirective::Direction(step.direction))?;
+        let to = controller.get_current_focus_id();
+        if *to != step.to {
+            bail!(
+                "step {}: {:?} from {} on {} went to {}, recorded {}",
+                i + 1,
+                step.direction,
+                display(&step.from),
+                step.screen.name(),
+                display(to),
+                display(&step.to),
+            );
+        }
+        result.replayed += 1;
+    }
+    Ok(result)
+}
+
+fn display(focus_id: &Option<FocusId>) -> String {
+    focus_id
+        .as_ref()
+        .map_or("nothing".to_owned(), FocusId::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::create_home_window_controller;
+
+    fn ids(ids: &[&str]) -> Vec<FocusId> {
+        ids.iter().map(|id| id.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn follows_scripts() {
+        let mut controller = create_home_window_controller().unwrap();
+        crate::controller::layout_games(&controller, ["a", "b", "c"]).unwrap();
+        let path = focus_path(
+            &mut controller,
+            &parse_script("right left down right Right left").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            path,
+            ids(&[
+                "BTN@RECENTLY_PLAYED",
+                "BTN@GAMES",
+                "GAME@a",
+                "GAME@b",
+                "GAME@c",
+                "GAME@b"
+            ])
+        );
+        assert!(parse_script("down sideways").is_err());
+    }
+
+    #[test]
+    fn records_and_replays() {
+        let path = std::env::temp_dir().join(format!("anubis-replay-{}.jsonl", std::process::id()));
+        let step = |from: &str, direction, to: &str| Step {
+            screen: Screen::Settings,
+            from: Some(from.parse().unwrap()),
+            direction,
+            to: Some(to.parse().unwrap()),
+        };
+        let mut recorder = Recorder::create(&path).unwrap();
+        recorder
+            .record(&step("BTN@BACK", Direction::Down, "BTN@BACKUP_LIBRARY"))
+            .unwrap();
+        recorder
+            .record(&step("BTN@GONE", Direction::Down, "BTN@BACK"))
+            .unwrap();
+        let steps = load(&path).unwrap();
+        assert_eq!(steps.len(), 2);
+
+        let mut router = Router::new().unwrap();
+        assert_eq!(
+            replay(&mut router, &steps).unwrap(),
+            Replay {
+                replayed: 1,
+                skipped: 1
+            }
+        );
+        let wrong = step("BTN@BACK", Direction::Down, "BTN@RESTORE_MERGE");
+        assert!(replay(&mut router, &[wrong]).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
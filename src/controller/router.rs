@@ -0,0 +1,221 @@
+use super::{
+    create_bulk_menu_controller, create_clock_controller, create_confirm_controller,
+    create_downloads_controller, create_duplicates_controller, create_game_details_controller,
+    create_game_menu_controller, create_home_window_controller, create_keyboard_controller,
+    create_matches_controller, create_pin_controller, create_profiles_controller,
+    create_quick_settings_controller, create_saves_controller, create_settings_controller,
+    create_stats_controller, create_storage_controller, create_suspended_controller,
+    create_sync_dialog_controller, create_trash_controller, create_update_controller, layout_file,
+    NavigationController,
+};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Top level screens, each has its own layout tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Screen {
+    Home,
+    Downloads,
+    GameDetails,
+    /// Modal shown while syncing saves around a launch.
+    SyncDialog,
+    Settings,
+    /// Review of the duplicated games.
+    Duplicates,
+    /// Time zone and network time.
+    Clock,
+    /// Updates of anubis itself.
+    Update,
+    /// The editor of the launch profiles.
+    Profiles,
+    /// The save backups of a game.
+    Saves,
+    /// Disk usage of the installed games.
+    Storage,
+    /// Asked before exiting to the desktop in kiosk mode.
+    Pin,
+    /// The games paused to resume later.
+    Suspended,
+    /// Brightness, volume and such, over the screen it was opened from.
+    QuickSettings,
+    /// What can be done with a game, next to its tile.
+    GameMenu,
+    /// What can be done with the selected games, see `library::bulk`.
+    BulkMenu,
+    /// Asked before something that can't be undone, see `dialogs`.
+    Confirm,
+    /// Typing with the gamepad, see `keyboard`.
+    Keyboard,
+    /// Playtime by week or month, see `sessions`.
+    Stats,
+    /// Picking which store result a game is, see `library::matches`.
+    Matches,
+    /// Deleted games and overwritten metadata, see `library::trash`.
+    Trash,
+}
+
+impl Screen {
+    pub const ALL: [Screen; 21] = [
+        Screen::Home,
+        Screen::Downloads,
+        Screen::GameDetails,
+        Screen::SyncDialog,
+        Screen::Settings,
+        Screen::Duplicates,
+        Screen::Clock,
+        Screen::Update,
+        Screen::Profiles,
+        Screen::Saves,
+        Screen::Storage,
+        Screen::Pin,
+        Screen::Suspended,
+        Screen::QuickSettings,
+        Screen::GameMenu,
+        Screen::BulkMenu,
+        Screen::Confirm,
+        Screen::Keyboard,
+        Screen::Stats,
+        Screen::Matches,
+        Screen::Trash,
+    ];
+
+    /// Name of the screen, as used by the UI.
+    pub fn name(self) -> &'static str {
+        match self {
+            Screen::Home => "Home",
+            Screen::Downloads => "Downloads",
+            Screen::GameDetails => "GameDetails",
+            Screen::SyncDialog => "SyncDialog",
+            Screen::Settings => "Settings",
+            Screen::Duplicates => "Duplicates",
+            Screen::Clock => "Clock",
+            Screen::Update => "Update",
+            Screen::Profiles => "Profiles",
+            Screen::Saves => "Saves",
+            Screen::Storage => "Storage",
+            Screen::Pin => "Pin",
+            Screen::Suspended => "Suspended",
+            Screen::QuickSettings => "QuickSettings",
+            Screen::GameMenu => "GameMenu",
+            Screen::BulkMenu => "BulkMenu",
+            Screen::Confirm => "Confirm",
+            Screen::Keyboard => "Keyboard",
+            Screen::Stats => "Stats",
+            Screen::Matches => "Matches",
+            Screen::Trash => "Trash",
+        }
+    }
+
+    /// Drawn over the screen underneath, which stays shown.
+    pub fn is_modal(self) -> bool {
+        matches!(
+            self,
+            Screen::QuickSettings | Screen::GameMenu | Screen::BulkMenu | Screen::Confirm
+        )
+    }
+}
+
+fn create_controller(screen: Screen, layout_dir: Option<&Path>) -> Result<NavigationController> {
+    if let Some(controller) = layout_dir
+        .map(|dir| layout_file::load_controller(dir, screen))
+        .transpose()?
+        .flatten()
+    {
+        return Ok(controller);
+    }
+    match screen {
+        Screen::Home => create_home_window_controller(),
+        Screen::Downloads => create_downloads_controller(),
+        Screen::GameDetails => create_game_details_controller(),
+        Screen::SyncDialog => create_sync_dialog_controller(),
+        Screen::Settings => create_settings_controller(),
+        Screen::Duplicates => create_duplicates_controller(),
+        Screen::Clock => create_clock_controller(),
+        Screen::Update => create_update_controller(),
+        Screen::Profiles => create_profiles_controller(),
+        Screen::Saves => create_saves_controller(),
+        Screen::Storage => create_storage_controller(),
+        Screen::Pin => create_pin_controller(),
+        Screen::Suspended => create_suspended_controller(),
+        Screen::QuickSettings => create_quick_settings_controller(),
+        Screen::GameMenu => create_game_menu_controller(),
+        Screen::BulkMenu => create_bulk_menu_controller(),
+        Screen::Confirm => create_confirm_controller(),
+        Screen::Keyboard => create_keyboard_controller(),
+        Screen::Stats => create_stats_controller(),
+        Screen::Matches => create_matches_controller(),
+        Screen::Trash => create_trash_controller(),
+    }
+}
+
+/// Keeps a controller per screen and the stack of visited screens.
+/// Focus is preserved per screen when going back and forth.
+pub struct Router {
+    controllers: HashMap<Screen, NavigationController>,
+    stack: Vec<Screen>,
+    /// Layout files replacing the built-in layouts, see `layout_file`.
+    layout_dir: Option<PathBuf>,
+}
+
+impl Router {
+    pub fn new() -> Result<Self> {
+        Self::with_layout_dir(None)
+    }
+
+    /// Screens with a file in `layout_dir` are laid out from it.
+    pub fn with_layout_dir(layout_dir: Option<PathBuf>) -> Result<Self> {
+        let mut controllers = HashMap::new();
+        for screen in Screen::ALL {
+            controllers.insert(screen, create_controller(screen, layout_dir.as_deref())?);
+        }
+        Ok(Self {
+            controllers,
+            stack: vec![Screen::Home],
+            layout_dir,
+        })
+    }
+
+    pub fn current_screen(&self) -> Screen {
+        *self.stack.last().unwrap()
+    }
+
+    /// The controller of the current screen.
+    pub fn controller(&mut self) -> &mut NavigationController {
+        let screen = self.current_screen();
+        self.controller_for(screen).unwrap()
+    }
+
+    pub fn controller_for(&mut self, screen: Screen) -> Result<&mut NavigationController> {
+        self.controllers
+            .get_mut(&screen)
+            .ok_or(anyhow!("no controller for screen {:?}", screen))
+    }
+
+    /// Start the screen over with an empty layout, e.g. when it shows something else.
+    pub fn rebuild(&mut self, screen: Screen) -> Result<()> {
+        let controller = create_controller(screen, self.layout_dir.as_deref())?;
+        self.controllers.insert(screen, controller);
+        Ok(())
+    }
+
+    /// Show a screen, no-op if it's already shown.
+    pub fn push(&mut self, screen: Screen) {
+        if self.current_screen() != screen {
+            self.stack.push(screen);
+        }
+    }
+
+    /// Go back to the previous screen, returns false if already at the root.
+    pub fn pop(&mut self) -> bool {
+        if self.stack.len() > 1 {
+            self.stack.pop();
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use super::chord::{buttons, Action};
+use gilrs::Button;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+// Buttons pressed one after the other, like the Konami code. Unlike chords
+// the presses still do what they do, the sequence goes off on the last one.
+
+fn default_timeout() -> u64 {
+    1000
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Sequence {
+    #[serde(deserialize_with = "buttons")]
+    pub buttons: Vec<Button>,
+    pub action: Action,
+}
+
+pub fn default_sequences() -> Vec<Sequence> {
+    use Button::*;
+    vec![Sequence {
+        buttons: vec![
+            DPadUp, DPadUp, DPadDown, DPadDown, DPadLeft, DPadRight, DPadLeft, DPadRight, East,
+            South,
+        ],
+        action: Action::ToggleDebugOverlay,
+    }]
+}
+
+/// Sequences and how long the wait between two presses can be, in
+/// milliseconds, part of `ChordConfig`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SequenceConfig {
+    #[serde(default = "default_sequences")]
+    pub sequences: Vec<Sequence>,
+    #[serde(default = "default_timeout")]
+    pub sequence_timeout: u64,
+}
+
+impl Default for SequenceConfig {
+    fn default() -> Self {
+        Self {
+            sequences: default_sequences(),
+            sequence_timeout: default_timeout(),
+        }
+    }
+}
+
+/// Watches the presses for the sequences registered.
+pub struct SequenceMatcher {
+    sequences: Vec<Sequence>,
+    timeout: Duration,
+    /// The latest presses, as many as the longest sequence.
+    presses: Vec<Button>,
+    last_press: Option<Instant>,
+}
+
+impl SequenceMatcher {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            sequences: vec![],
+            timeout,
+            presses: vec![],
+            last_press: None,
+        }
+    }
+
+    pub fn from_config(config: SequenceConfig) -> Self {
+        let mut matcher = Self::new(Duration::from_millis(config.sequence_timeout));
+        for s in config.sequences {
+            matcher.register(s.buttons, s.action);
+        }
+        matcher
+    }
+
+    /// Do `action` when the buttons are pressed in this order. Empty
+    /// sequences are ignored.
+    pub fn register(&mut self, buttons: Vec<Button>, action: Action) {
+        if !buttons.is_empty() {
+            self.sequences.push(Sequence { buttons, action });
+        }
+    }
+
+    /// The action of the sequence this press ends, if any. Waiting too long
+    /// between two presses starts over.
+    pub fn press(&mut self, b: Button, now: Instant) -> Option<Action> {
+        if self
+            .last_press
+            .is_some_and(|t| now.duration_since(t) > self.timeout)
+        {
+            self.presses.clear();
+        }
+        self.last_press = Some(now);
+        self.presses.push(b);
+        let longest = self.sequences.iter().map(|s| s.buttons.len()).max()?;
+        if self.presses.len() > longest {
+            self.presses.remove(0);
+        }
+        let action = self
+            .sequences
+            .iter()
+            .find(|s| self.presses.ends_with(&s.buttons))?
+            .action;
+        self.presses.clear();
+        Some(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_sequences() {
+        let mut sut = SequenceMatcher::from_config(SequenceConfig::default());
+        sut.register(vec![Button::North, Button::North], Action::Screenshot);
+        let t = Instant::now();
+        let at = |ms| t + Duration::from_millis(ms);
+
+        // Presses before the sequence don't matter.
+        let konami = &default_sequences()[0].buttons;
+        let presses = [Button::DPadUp].iter().chain(konami);
+        let mut fired = vec![];
+        for (i, &b) in presses.enumerate() {
+            fired.extend(sut.press(b, at(i as u64 * 100)));
+        }
+        assert_eq!(fired, [Action::ToggleDebugOverlay]);
+
+        // Too slow.
+        assert_eq!(sut.press(Button::North, at(5000)), None);
+        assert_eq!(sut.press(Button::North, at(6001)), None);
+        assert_eq!(sut.press(Button::North, at(6500)), Some(Action::Screenshot));
+        // Starts over after going off.
+        assert_eq!(sut.press(Button::North, at(6600)), None);
+    }
+}
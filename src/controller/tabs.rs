@@ -0,0 +1,77 @@
+use super::LayoutId;
+use anyhow::{anyhow, Result};
+
+// Layouts taking turns in the same place, like the tabs of the game page.
+// Only the one shown is in the grid of their container, the others are kept
+// as they were, focus included, until they're shown again. See
+// `LayoutGridBuilder::set_tabs`.
+
+#[derive(Debug, Clone)]
+pub struct Tabs<T> {
+    tabs: Vec<(LayoutId, T)>,
+    /// Of the tab shown.
+    current: usize,
+}
+
+impl<T> Default for Tabs<T> {
+    fn default() -> Self {
+        Self {
+            tabs: vec![],
+            current: 0,
+        }
+    }
+}
+
+impl<T> Tabs<T> {
+    /// Add a tab after the others, the first one added is shown.
+    pub fn push(&mut self, id: LayoutId, tab: T) {
+        self.tabs.push((id, tab));
+    }
+
+    pub fn current(&self) -> Option<&(LayoutId, T)> {
+        self.tabs.get(self.current)
+    }
+
+    /// Show the tab `steps` to the right, wrapping around.
+    pub fn cycle(&mut self, steps: isize) {
+        if self.tabs.is_empty() {
+            return;
+        }
+        let len = self.tabs.len() as isize;
+        self.current = (self.current as isize + steps).rem_euclid(len) as usize;
+    }
+
+    pub fn select(&mut self, id: &LayoutId) -> Result<()> {
+        self.current = self
+            .tabs
+            .iter()
+            .position(|(i, _)| i == id)
+            .ok_or(anyhow!("no tab {}", id))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_around_the_tabs() {
+        let mut sut = Tabs::default();
+        sut.cycle(1);
+        assert!(sut.current().is_none());
+        for name in ["A", "B", "C"] {
+            sut.push(LayoutId::sublayout("L0", name), name);
+        }
+        let shown = |sut: &Tabs<&str>| sut.current().unwrap().1.to_owned();
+        assert_eq!(shown(&sut), "A");
+        sut.cycle(-1);
+        assert_eq!(shown(&sut), "C");
+        sut.cycle(4);
+        assert_eq!(shown(&sut), "A");
+        sut.select(&LayoutId::sublayout("L0", "B")).unwrap();
+        assert_eq!(shown(&sut), "B");
+        assert!(sut.select(&LayoutId::sublayout("L0", "D")).is_err());
+        assert_eq!(shown(&sut), "B");
+    }
+}
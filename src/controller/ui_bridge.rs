@@ -0,0 +1,231 @@
+use super::{FocusId, FocusRect, Screen};
+use crate::metrics::Metrics;
+use anyhow::Result;
+use log::warn;
+use std::{
+    mem,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+// What the navigation needs of the UI, so it doesn't need the window Slint
+// generates. The window is one, the tests have one which only records the
+// focus.
+
+/// About a frame at 60Hz.
+pub const FRAME: Duration = Duration::from_millis(16);
+
+/// A change to what the UI shows, run with the window on the UI thread.
+pub type ModelUpdate<W> = Box<dyn FnOnce(&W) + Send>;
+
+pub trait UiBridge: Send {
+    /// What model updates get to change.
+    type Window;
+
+    /// Show the screen with the element highlighted.
+    fn set_focus(&self, screen: Screen, focus: &FocusId) -> Result<()>;
+
+    /// Scroll so `to` is in view, moving over from `from`.
+    fn set_scroll(&self, from: Option<&FocusRect>, to: &FocusRect) -> Result<()>;
+
+    fn push_model_update(&self, update: ModelUpdate<Self::Window>) -> Result<()>;
+}
+
+impl<W> dyn UiBridge<Window = W> {
+    /// `push_model_update` without the boxing.
+    pub fn update(&self, f: impl FnOnce(&W) + Send + 'static) -> Result<()> {
+        self.push_model_update(Box::new(f))
+    }
+}
+
+/// What changed since the last frame.
+struct Pending<W> {
+    focus: Option<(Screen, FocusId)>,
+    scroll: Option<(Option<FocusRect>, FocusRect)>,
+    models: Vec<ModelUpdate<W>>,
+}
+
+impl<W> Default for Pending<W> {
+    fn default() -> Self {
+        Self {
+            focus: None,
+            scroll: None,
+            models: vec![],
+        }
+    }
+}
+
+impl<W> Pending<W> {
+    fn is_empty(&self) -> bool {
+        self.focus.is_none() && self.scroll.is_none() && self.models.is_empty()
+    }
+}
+
+/// Holds changes back to pass them on once a frame, so holding a direction
+/// down doesn't flood the UI thread. Only the last focus of a frame is
+/// shown, and the scroll goes from where it was to where it ended up. The
+/// model updates all run, in order, as one, timed.
+pub struct FrameCoalescer<W> {
+    pending: Arc<Mutex<Pending<W>>>,
+    /// Wakes the flushing thread up, once per frame with changes.
+    wake: mpsc::Sender<()>,
+}
+
+impl<W: 'static> FrameCoalescer<W> {
+    pub fn new(inner: impl UiBridge<Window = W> + 'static, metrics: Metrics) -> Result<Self> {
+        let pending = Arc::new(Mutex::new(Pending::default()));
+        let (wake, woken) = mpsc::channel();
+        let flushed = pending.clone();
+        let start = Instant::now();
+        thread::Builder::new()
+            .name("ui-frames".to_owned())
+            .spawn(move || {
+                // Until the coalescer is dropped.
+                while woken.recv().is_ok() {
+                    // Changes wait for the end of the frame they're in.
+                    let frames = start.elapsed().as_nanos() / FRAME.as_nanos() + 1;
+                    let tick = start + FRAME * frames as u32;
+                    thread::sleep(tick.saturating_duration_since(Instant::now()));
+                    let p = mem::take(&mut *flushed.lock().unwrap());
+                    if let Err(e) = flush(&inner, p, &metrics) {
+                        warn!("failed to update the UI: {:?}", e);
+                    }
+                }
+            })?;
+        Ok(Self { pending, wake })
+    }
+
+    fn change(&self, f: impl FnOnce(&mut Pending<W>)) -> Result<()> {
+        let mut pending = self.pending.lock().unwrap();
+        let was_empty = pending.is_empty();
+        f(&mut pending);
+        if was_empty {
+            self.wake.send(())?;
+        }
+        Ok(())
+    }
+}
+
+fn flush<W: 'static>(
+    inner: &impl UiBridge<Window = W>,
+    p: Pending<W>,
+    metrics: &Metrics,
+) -> Result<()> {
+    if !p.models.is_empty() {
+        let models = p.models;
+        let metrics = metrics.clone();
+        inner.push_model_update(Box::new(move |w: &W| {
+            let started = Instant::now();
+            for update in models {
+                update(w);
+            }
+            metrics.record_frame(started.elapsed());
+        }))?;
+    }
+    if let Some((from, to)) = p.scroll {
+        inner.set_scroll(from.as_ref(), &to)?;
+    }
+    if let Some((screen, focus)) = p.focus {
+        inner.set_focus(screen, &focus)?;
+    }
+    Ok(())
+}
+
+impl<W: 'static> UiBridge for FrameCoalescer<W> {
+    type Window = W;
+
+    fn set_focus(&self, screen: Screen, focus: &FocusId) -> Result<()> {
+        self.change(|p| p.focus = Some((screen, focus.clone())))
+    }
+
+    fn set_scroll(&self, from: Option<&FocusRect>, to: &FocusRect) -> Result<()> {
+        self.change(|p| {
+            // From before the first move of the frame.
+            let from = match p.scroll.take() {
+                Some((first, _)) => first,
+                None => from.cloned(),
+            };
+            p.scroll = Some((from, to.clone()));
+        })
+    }
+
+    fn push_model_update(&self, update: ModelUpdate<W>) -> Result<()> {
+        self.change(|p| p.models.push(update))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    /// Logs what gets to it, the window is the log.
+    struct Recorder(Log);
+
+    impl UiBridge for Recorder {
+        type Window = Log;
+
+        fn set_focus(&self, screen: Screen, focus: &FocusId) -> Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{:?} {}", screen, focus));
+            Ok(())
+        }
+
+        fn set_scroll(&self, from: Option<&FocusRect>, to: &FocusRect) -> Result<()> {
+            let from = from.map(|f| f.layout_id.to_string());
+            let line = format!("scroll {:?} {}", from, to.layout_id);
+            self.0.lock().unwrap().push(line);
+            Ok(())
+        }
+
+        fn push_model_update(&self, update: ModelUpdate<Log>) -> Result<()> {
+            self.0.lock().unwrap().push("models".to_owned());
+            update(&self.0);
+            Ok(())
+        }
+    }
+
+    fn rect(layout: &str) -> FocusRect {
+        FocusRect {
+            layout_id: layout.parse().unwrap(),
+            rect: super::super::grid::Rect::new(0, 0, 0, 0).unwrap(),
+            placement: None,
+        }
+    }
+
+    #[test]
+    fn applies_a_frame_at_once() {
+        let log = Log::default();
+        let sut = FrameCoalescer::new(Recorder(log.clone()), Metrics::default()).unwrap();
+        for (i, to) in ["Home@Games", "Home", "Downloads"].iter().enumerate() {
+            let from = (i > 0).then(|| rect("Settings"));
+            sut.set_scroll(from.as_ref(), &rect(to)).unwrap();
+            sut.set_focus(Screen::Home, &FocusId::game(&i.to_string()))
+                .unwrap();
+            sut.push_model_update(Box::new(move |log: &Log| {
+                log.lock().unwrap().push(format!("model {}", i))
+            }))
+            .unwrap();
+        }
+        thread::sleep(FRAME * 4);
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "models",
+                "model 0",
+                "model 1",
+                "model 2",
+                "scroll None Downloads",
+                "Home GAME@2",
+            ]
+        );
+
+        // Nothing new, nothing to do.
+        thread::sleep(FRAME * 2);
+        assert_eq!(log.lock().unwrap().len(), 6);
+    }
+}
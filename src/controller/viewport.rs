@@ -0,0 +1,137 @@
+use super::{FocusRect, LayoutId};
+use std::time::{Duration, Instant};
+
+// Where the games grid is scrolled to, worked out here for the UI to animate
+// to. The focused row is kept in view with the one above it showing, and the
+// last rows stay at the bottom instead of scrolling past them. Moves coming
+// faster than a scroll animates, like a held D-pad repeating, are animated
+// linearly over the time between them: the grid keeps a steady pace instead
+// of easing in and out at every row, and stops on the focused row.
+
+/// Rows of tiles on screen at once.
+const VISIBLE_ROWS: usize = 3;
+/// Of a scroll on its own.
+const DURATION: Duration = Duration::from_millis(150);
+/// Repeats any faster are animated as if they came this often.
+const MIN_DURATION: Duration = Duration::from_millis(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    EaseOut,
+    Linear,
+}
+
+/// What the UI animates the scroll with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrollHint {
+    /// Row at the top of the viewport.
+    pub row: usize,
+    pub duration: Duration,
+    pub easing: Easing,
+    /// Rows per second, negative going up.
+    pub velocity: f32,
+}
+
+#[derive(Debug)]
+pub struct Viewport {
+    layout: LayoutId,
+    rows: usize,
+    row: usize,
+    last_scroll: Option<Instant>,
+}
+
+impl Viewport {
+    /// For the layout scrolled, the focus anywhere else scrolls to the top.
+    pub fn new(layout: LayoutId) -> Self {
+        Self {
+            layout,
+            rows: 0,
+            row: 0,
+            last_scroll: None,
+        }
+    }
+
+    /// Rows of the layout, the scroll stops at its last ones.
+    pub fn set_rows(&mut self, rows: usize) {
+        self.rows = rows;
+    }
+
+    /// Where to scroll to with the focus on `to`, None to stay where it is.
+    pub fn follow(&mut self, to: &FocusRect, now: Instant) -> Option<ScrollHint> {
+        let row = match to.layout_id == self.layout {
+            true => {
+                let last = self.rows.saturating_sub(VISIBLE_ROWS);
+                to.rect.y().saturating_sub(1).min(last)
+            }
+            false => 0,
+        };
+        if row == self.row {
+            return None;
+        }
+        let since = self.last_scroll.map(|t| now.duration_since(t));
+        let (duration, easing) = match since {
+            Some(since) if since < DURATION => (since.max(MIN_DURATION), Easing::Linear),
+            _ => (DURATION, Easing::EaseOut),
+        };
+        let velocity = (row as f32 - self.row as f32) / duration.as_secs_f32();
+        self.row = row;
+        self.last_scroll = Some(now);
+        Some(ScrollHint {
+            row,
+            duration,
+            easing,
+            velocity,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::grid::Rect;
+
+    #[test]
+    fn keeps_pace_with_held_moves() {
+        let games = LayoutId::sublayout("Home", "Games");
+        let at = |layout: &LayoutId, y| FocusRect {
+            layout_id: layout.clone(),
+            rect: Rect::new(0, 0, y, y).unwrap(),
+            placement: None,
+        };
+        let mut sut = Viewport::new(games.clone());
+        sut.set_rows(6);
+        let start = Instant::now();
+        assert_eq!(sut.follow(&at(&games, 1), start), None);
+
+        let hint = sut.follow(&at(&games, 2), start).unwrap();
+        assert_eq!((hint.row, hint.easing), (1, Easing::EaseOut));
+        assert_eq!(hint.duration, DURATION);
+
+        // Held down, each row animated over the time until the next.
+        let hint = sut
+            .follow(&at(&games, 3), start + Duration::from_millis(100))
+            .unwrap();
+        assert_eq!((hint.row, hint.easing), (2, Easing::Linear));
+        assert_eq!(hint.duration, Duration::from_millis(100));
+        assert_eq!(hint.velocity, 10.0);
+
+        // Not past the last rows.
+        let hint = sut
+            .follow(&at(&games, 5), start + Duration::from_millis(200))
+            .unwrap();
+        assert_eq!(hint.row, 3);
+        assert_eq!(
+            sut.follow(&at(&games, 4), start + Duration::from_millis(300)),
+            None
+        );
+
+        let top = sut
+            .follow(
+                &at(&LayoutId::root("Home"), 0),
+                start + Duration::from_secs(1),
+            )
+            .unwrap();
+        assert_eq!((top.row, top.easing), (0, Easing::EaseOut));
+        assert!(top.velocity < 0.0);
+    }
+}
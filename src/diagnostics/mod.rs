@@ -0,0 +1,292 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    backtrace::Backtrace,
+    fs, panic,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+// Opt-in crash reports and session stats, to find out what goes wrong on
+// consoles nobody is debugging. Nothing is recorded until turned on in the
+// settings. Reports are written locally, and sent on the next start when an
+// upload endpoint is set. They don't say which games were played, only how
+// many and how it went.
+
+/// Slower than this, a press is counted as slow. About two frames.
+const SLOW_NAVIGATION: Duration = Duration::from_millis(33);
+
+/// ```yaml
+/// enabled: true
+/// # Optional, reports are POSTed there as JSON.
+/// upload_url: https://example.com/anubis/reports
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiagnosticsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    pub upload_url: Option<String>,
+}
+
+impl DiagnosticsConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// How long presses took to handle, in milliseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Latency {
+    pub count: u64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    /// Over `SLOW_NAVIGATION`.
+    pub slow: u64,
+}
+
+impl Latency {
+    fn record(&mut self, took: Duration) {
+        let ms = took.as_secs_f64() * 1000.0;
+        self.mean_ms += (ms - self.mean_ms) / (self.count + 1) as f64;
+        self.count += 1;
+        self.max_ms = self.max_ms.max(ms);
+        if took > SLOW_NAVIGATION {
+            self.slow += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub duration_secs: u64,
+    pub navigation: Latency,
+    pub launches: u32,
+    pub launch_failures: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Crash {
+    pub message: String,
+    /// e.g. `src/main.rs:12:5`.
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    pub time: DateTime<Utc>,
+    pub session: SessionStats,
+    /// None for the report at the end of a session.
+    pub crash: Option<Crash>,
+}
+
+impl Report {
+    fn new(session: SessionStats, crash: Option<Crash>) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            os: std::env::consts::OS.to_owned(),
+            arch: std::env::consts::ARCH.to_owned(),
+            time: Utc::now(),
+            session,
+            crash,
+        }
+    }
+
+    fn file_name(&self) -> String {
+        let kind = if self.crash.is_some() {
+            "crash"
+        } else {
+            "session"
+        };
+        format!("{}-{}.yaml", kind, self.time.format("%Y%m%dT%H%M%S%.3f"))
+    }
+}
+
+struct Session {
+    enabled: bool,
+    started: Instant,
+    stats: SessionStats,
+}
+
+/// Shared by whatever records something, cheap to clone.
+#[derive(Clone)]
+pub struct Diagnostics {
+    /// Where reports are written.
+    dir: PathBuf,
+    session: Arc<Mutex<Session>>,
+}
+
+impl Diagnostics {
+    pub fn new(dir: PathBuf, enabled: bool) -> Self {
+        Self {
+            dir,
+            session: Arc::new(Mutex::new(Session {
+                enabled,
+                started: Instant::now(),
+                stats: SessionStats::default(),
+            })),
+        }
+    }
+
+    pub fn default_dir() -> PathBuf {
+        crate::paths::data_dir().join("diagnostics")
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.session.lock().unwrap().enabled
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.session.lock().unwrap().enabled = enabled;
+    }
+
+    fn record(&self, f: impl FnOnce(&mut SessionStats)) {
+        let mut session = self.session.lock().unwrap();
+        if session.enabled {
+            f(&mut session.stats);
+        }
+    }
+
+    /// A press, from it coming in to the UI being told.
+    pub fn record_navigation(&self, took: Duration) {
+        self.record(|s| s.navigation.record(took));
+    }
+
+    pub fn record_launch(&self, failed: bool) {
+        self.record(|s| {
+            s.launches += 1;
+            if failed {
+                s.launch_failures += 1;
+            }
+        });
+    }
+
+    /// The stats so far, None when turned off.
+    fn stats(session: &Session) -> Option<SessionStats> {
+        session.enabled.then(|| SessionStats {
+            duration_secs: session.started.elapsed().as_secs(),
+            ..session.stats.clone()
+        })
+    }
+
+    fn write(&self, report: &Report) -> Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(report.file_name());
+        fs::write(&path, serde_yaml::to_string(report)?)?;
+        Ok(path)
+    }
+
+    /// Write a report of the session, when on.
+    pub fn end_session(&self) -> Result<()> {
+        let Some(stats) = Self::stats(&self.session.lock().unwrap()) else {
+            return Ok(());
+        };
+        let path = self.write(&Report::new(stats, None))?;
+        info!("wrote the session report to {:?}", path);
+        Ok(())
+    }
+
+    /// Write a crash report on panics, when on. The default hook still runs.
+    pub fn install_panic_hook(&self) {
+        let this = self.clone();
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            default_hook(info);
+            // Not waiting on it, the panic may have come from under the lock.
+            let Some(stats) = this.session.try_lock().ok().and_then(|s| Self::stats(&s)) else {
+                return;
+            };
+            let message = match info.payload().downcast_ref::<&str>() {
+                Some(message) => message.to_string(),
+                None => info
+                    .payload()
+                    .downcast_ref::<String>()
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            let crash = Crash {
+                message,
+                location: info.location().map(|l| l.to_string()),
+                backtrace: Backtrace::force_capture().to_string(),
+            };
+            if let Err(e) = this.write(&Report::new(stats, Some(crash))) {
+                eprintln!("failed to write the crash report: {:?}", e);
+            }
+        }));
+    }
+
+    /// Send the reports written so far, removing the ones that went through.
+    pub fn upload(&self, url: &str) -> Result<()> {
+        if !self.dir.exists() {
+            return Ok(());
+        }
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("yaml") {
+                continue;
+            }
+            let report: Report = match serde_yaml::from_str(&fs::read_to_string(&path)?) {
+                Ok(report) => report,
+                Err(e) => {
+                    warn!("skipping the bad report {:?}: {:?}", path, e);
+                    continue;
+                }
+            };
+            ureq::post(url).send_json(&report)?;
+            fs::remove_file(&path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_only_when_enabled() {
+        let dir = std::env::temp_dir().join(format!("anubis-diagnostics-{}", std::process::id()));
+        let sut = Diagnostics::new(dir.clone(), false);
+        sut.record_launch(true);
+        sut.end_session().unwrap();
+        assert!(!dir.exists());
+
+        sut.set_enabled(true);
+        sut.record_launch(false);
+        sut.record_launch(true);
+        sut.record_navigation(Duration::from_millis(10));
+        sut.record_navigation(Duration::from_millis(50));
+        sut.end_session().unwrap();
+
+        let reports: Vec<Report> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| serde_yaml::from_str(&fs::read_to_string(e.unwrap().path()).unwrap()).unwrap())
+            .collect();
+        assert_eq!(reports.len(), 1);
+        let session = &reports[0].session;
+        assert_eq!((session.launches, session.launch_failures), (2, 1));
+        assert_eq!(session.navigation.count, 2);
+        assert_eq!(session.navigation.slow, 1);
+        assert!((session.navigation.mean_ms - 30.0).abs() < 0.01);
+        assert!((session.navigation.max_ms - 50.0).abs() < 0.01);
+        assert_eq!(reports[0].crash, None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
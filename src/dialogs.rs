@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+use tokio::sync::oneshot;
+
+// Questions asked before doing something that can't be undone, like
+// uninstalling a game. `confirm` asks, the navigation shows the dialog over
+// the current screen, and `answer` hands back what to do once confirmed.
+// What's done then needs the navigation itself, hence not a future. `ask` is
+// for the others, it gives the answer to whoever awaits it, and goes once
+// nobody does. Asked while another one is shown, a question waits its turn.
+
+#[derive(Debug)]
+pub struct Confirm<T> {
+    pub title: String,
+    pub body: String,
+    /// Done when confirmed, dropped when cancelled.
+    pub then: Option<T>,
+    /// Told the answer, see `ask`.
+    answered: Option<oneshot::Sender<bool>>,
+}
+
+#[derive(Debug)]
+pub struct Dialogs<T> {
+    asked: VecDeque<Confirm<T>>,
+}
+
+impl<T> Default for Dialogs<T> {
+    fn default() -> Self {
+        Self {
+            asked: VecDeque::new(),
+        }
+    }
+}
+
+impl<T> Dialogs<T> {
+    pub fn confirm(&mut self, title: String, body: String, then: T) {
+        self.asked.push_back(Confirm {
+            title,
+            body,
+            then: Some(then),
+            answered: None,
+        });
+    }
+
+    /// Resolved with whether it was confirmed, an error if the question is
+    /// dropped unanswered.
+    pub fn ask(&mut self, title: String, body: String) -> oneshot::Receiver<bool> {
+        let (answered, answer) = oneshot::channel();
+        self.asked.push_back(Confirm {
+            title,
+            body,
+            then: None,
+            answered: Some(answered),
+        });
+        answer
+    }
+
+    /// The question shown, the oldest unanswered.
+    pub fn current(&self) -> Option<&Confirm<T>> {
+        self.asked.front()
+    }
+
+    /// Answer the question shown, what to do if it was confirmed.
+    pub fn answer(&mut self, confirmed: bool) -> Option<T> {
+        let asked = self.asked.pop_front()?;
+        if let Some(answered) = asked.answered {
+            // Fine if nobody awaits it anymore.
+            let _ = answered.send(confirmed);
+        }
+        asked.then.filter(|_| confirmed)
+    }
+
+    /// Drop the questions asked with `ask` whose answer isn't awaited
+    /// anymore, true if the one shown was.
+    pub fn drop_unawaited(&mut self) -> bool {
+        let awaited = |c: &Confirm<T>| c.answered.as_ref().is_none_or(|a| !a.is_closed());
+        let shown = self.asked.front().is_some_and(|c| !awaited(c));
+        self.asked.retain(awaited);
+        shown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_in_turn() {
+        let mut sut = Dialogs::default();
+        assert_eq!(sut.answer(true), None);
+        sut.confirm("Uninstall".to_owned(), "Celeste?".to_owned(), 1);
+        sut.confirm("Exit".to_owned(), "Sure?".to_owned(), 2);
+        assert_eq!(sut.current().unwrap().body, "Celeste?");
+        assert_eq!(sut.answer(false), None);
+        assert_eq!(sut.current().unwrap().title, "Exit");
+        assert_eq!(sut.answer(true), Some(2));
+        assert!(sut.current().is_none());
+
+        let mut answer = sut.ask("Pair".to_owned(), "Pixel 8?".to_owned());
+        assert!(answer.try_recv().is_err());
+        assert_eq!(sut.answer(true), None);
+        assert_eq!(answer.try_recv(), Ok(true));
+
+        sut.confirm("Exit".to_owned(), "Sure?".to_owned(), 3);
+        let gone = sut.ask("Pair".to_owned(), "iPad?".to_owned());
+        let _waiting = sut.ask("Pair".to_owned(), "Pixel 8?".to_owned());
+        drop(gone);
+        assert!(!sut.drop_unawaited());
+        assert_eq!(sut.answer(true), Some(3));
+        assert_eq!(sut.current().unwrap().body, "Pixel 8?");
+    }
+}
@@ -0,0 +1,147 @@
+use crate::controller::{FocusId, FocusKind};
+use anyhow::Result;
+use std::collections::HashMap;
+
+// What pressing A on an element does, looked up instead of matched: the
+// handlers of each screen are registered once, by id, by the start of the
+// id for families of buttons like the keys of the PIN pad, or by kind for
+// the elements carrying something in their id like the game tiles. The most
+// specific one wins.
+
+type Handler<S> = Box<dyn Fn(&mut S, &FocusId) -> Result<()> + Send + Sync>;
+
+/// What handlers give back, nothing or a Result.
+pub trait Outcome {
+    fn into_result(self) -> Result<()>;
+}
+
+impl Outcome for () {
+    fn into_result(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Outcome for Result<()> {
+    fn into_result(self) -> Result<()> {
+        self
+    }
+}
+
+pub struct Dispatcher<S> {
+    by_id: HashMap<FocusId, Handler<S>>,
+    by_prefix: Vec<(FocusKind, String, Handler<S>)>,
+    by_kind: HashMap<FocusKind, Handler<S>>,
+}
+
+impl<S> Default for Dispatcher<S> {
+    fn default() -> Self {
+        Self {
+            by_id: HashMap::new(),
+            by_prefix: vec![],
+            by_kind: HashMap::new(),
+        }
+    }
+}
+
+fn boxed<S, R: Outcome>(
+    handler: impl Fn(&mut S, &FocusId) -> R + Send + Sync + 'static,
+) -> Handler<S> {
+    Box::new(move |state, focus_id| handler(state, focus_id).into_result())
+}
+
+impl<S> Dispatcher<S> {
+    /// Handle the element, replacing its handler if it had one.
+    pub fn on<R: Outcome>(
+        &mut self,
+        focus_id: FocusId,
+        handler: impl Fn(&mut S, &FocusId) -> R + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.by_id.insert(focus_id, boxed(handler));
+        self
+    }
+
+    /// Handle the button of that name, the usual case.
+    pub fn on_button<R: Outcome>(
+        &mut self,
+        name: &str,
+        handler: impl Fn(&mut S, &FocusId) -> R + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.on(FocusId::button(name), handler)
+    }
+
+    /// Handle the elements of the kind whose payload starts with `prefix`.
+    pub fn on_prefix<R: Outcome>(
+        &mut self,
+        kind: FocusKind,
+        prefix: &str,
+        handler: impl Fn(&mut S, &FocusId) -> R + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.by_prefix
+            .push((kind, prefix.to_owned(), boxed(handler)));
+        self
+    }
+
+    /// Handle every element of the kind which isn't handled otherwise.
+    pub fn on_kind<R: Outcome>(
+        &mut self,
+        kind: FocusKind,
+        handler: impl Fn(&mut S, &FocusId) -> R + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.by_kind.insert(kind, boxed(handler));
+        self
+    }
+
+    /// Run the handler of the element, false if there's none.
+    pub fn dispatch(&self, state: &mut S, focus_id: &FocusId) -> Result<bool> {
+        let handler = self.by_id.get(focus_id).or_else(|| {
+            // The longest prefix is the most specific.
+            self.by_prefix
+                .iter()
+                .filter(|(kind, prefix, _)| {
+                    *kind == focus_id.kind() && focus_id.payload().starts_with(prefix.as_str())
+                })
+                .max_by_key(|(_, prefix, _)| prefix.len())
+                .map(|(_, _, handler)| handler)
+                .or_else(|| self.by_kind.get(&focus_id.kind()))
+        });
+        match handler {
+            Some(handler) => handler(state, focus_id).map(|_| true),
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_most_specific_handler() -> Result<()> {
+        let mut sut = Dispatcher::<Vec<String>>::default();
+        sut.on_button("BACK", |log, _| log.push("back".to_owned()))
+            .on_prefix(FocusKind::Button, "PIN_", |log, f| {
+                log.push(format!("pin {}", f.payload()))
+            })
+            .on_prefix(FocusKind::Button, "PIN_O", |log, _| {
+                log.push("ok".to_owned())
+            })
+            .on_kind(FocusKind::Game, |log, f| {
+                log.push(format!("game {}", f.payload()))
+            })
+            .on_button("FAILS", |_, _| -> Result<()> { anyhow::bail!("failed") });
+
+        let mut log = vec![];
+        for id in [
+            FocusId::button("BACK"),
+            FocusId::button("PIN_1"),
+            FocusId::button("PIN_OK"),
+            FocusId::game("a"),
+        ] {
+            assert!(sut.dispatch(&mut log, &id)?);
+        }
+        assert_eq!(log, ["back", "pin PIN_1", "ok", "game a"]);
+        assert!(!sut.dispatch(&mut log, &FocusId::button("NOTHING"))?);
+        assert!(sut.dispatch(&mut log, &FocusId::button("FAILS")).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,431 @@
+use crate::{integrations::steam, models::GameMetadata};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+// News of the games in the library: the Steam news of Steam games, and RSS
+// or Atom feeds set up by hand for the others. Each game's news is cached on
+// disk and fetched again once it's older than the refresh interval, a stale
+// cache standing in while offline. The newest of all of them make the News
+// row of the home screen, a game's own the News tab of its details.
+
+const STEAM_NEWS_URL: &str = "https://api.steampowered.com/ISteamNews/GetNewsForApp/v2/";
+/// Summaries are cut to this many characters.
+const SUMMARY_LENGTH: usize = 300;
+
+fn default_refresh_minutes() -> u64 {
+    60
+}
+
+fn default_per_game() -> usize {
+    10
+}
+
+fn default_latest() -> usize {
+    4
+}
+
+fn default_steam() -> bool {
+    true
+}
+
+/// ```yaml
+/// # The Steam news of Steam games.
+/// steam: true
+/// # RSS or Atom feeds, by game UUID.
+/// feeds:
+///   0190a8e2-7c1d-7b3e-9f2a-1c2d3e4f5a6b: https://example.com/devlog.xml
+/// # Only the favorites' news on the home screen.
+/// only_favorites: false
+/// refresh_minutes: 60
+/// per_game: 10
+/// # Side by side on the home screen.
+/// latest: 4
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeedsConfig {
+    #[serde(default = "default_steam")]
+    pub steam: bool,
+    #[serde(default)]
+    pub feeds: HashMap<String, String>,
+    #[serde(default)]
+    pub only_favorites: bool,
+    #[serde(default = "default_refresh_minutes")]
+    pub refresh_minutes: u64,
+    #[serde(default = "default_per_game")]
+    pub per_game: usize,
+    #[serde(default = "default_latest")]
+    pub latest: usize,
+}
+
+impl Default for FeedsConfig {
+    fn default() -> Self {
+        Self {
+            steam: default_steam(),
+            feeds: HashMap::new(),
+            only_favorites: false,
+            refresh_minutes: default_refresh_minutes(),
+            per_game: default_per_game(),
+            latest: default_latest(),
+        }
+    }
+}
+
+impl FeedsConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn refresh(&self) -> Duration {
+        Duration::from_secs(self.refresh_minutes.max(1) * 60)
+    }
+
+    /// Where the news of the game comes from, none for most.
+    pub fn sources(&self, game: &GameMetadata) -> Vec<Source> {
+        let steam = game
+            .install_source
+            .as_deref()
+            .and_then(steam::app_id_from_install_source)
+            .filter(|_| self.steam)
+            .map(Source::Steam);
+        let feed = self.feeds.get(&game.uuid).cloned().map(Source::Feed);
+        steam.into_iter().chain(feed).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Steam(steam::AppId),
+    /// RSS or Atom.
+    Feed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewsItem {
+    pub title: String,
+    pub url: String,
+    /// None when the feed doesn't say.
+    pub published: Option<DateTime<Utc>>,
+    /// Plain text, markup taken out.
+    pub summary: String,
+}
+
+/// A news item on the home screen, with the game it's about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Headline {
+    pub uuid: String,
+    pub game: String,
+    pub item: NewsItem,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedNews {
+    fetched_at: DateTime<Utc>,
+    items: Vec<NewsItem>,
+}
+
+// GetNewsForApp response, only the bits we need.
+#[derive(Deserialize)]
+struct SteamNews {
+    appnews: SteamAppNews,
+}
+
+#[derive(Deserialize)]
+struct SteamAppNews {
+    #[serde(default)]
+    newsitems: Vec<SteamNewsItem>,
+}
+
+#[derive(Deserialize)]
+struct SteamNewsItem {
+    title: String,
+    url: String,
+    #[serde(default)]
+    contents: String,
+    /// Unix time.
+    date: i64,
+}
+
+fn parse_steam(body: &str) -> Result<Vec<NewsItem>> {
+    let news: SteamNews = serde_json::from_str(body)?;
+    Ok(news
+        .appnews
+        .newsitems
+        .into_iter()
+        .map(|n| NewsItem {
+            title: n.title,
+            url: n.url,
+            published: Utc.timestamp_opt(n.date, 0).single(),
+            summary: plain(&n.contents),
+        })
+        .collect())
+}
+
+/// The items of an RSS 2.0 or the entries of an Atom feed.
+fn parse_feed(body: &str) -> Result<Vec<NewsItem>> {
+    let doc = roxmltree::Document::parse(body)?;
+    let root = doc.root_element();
+    let child = |node: roxmltree::Node, name: &str| {
+        node.children()
+            .find(|c| c.tag_name().name() == name)
+            .map(|c| c.text().unwrap_or_default().trim().to_owned())
+    };
+    let items = match root.tag_name().name() {
+        "rss" => root
+            .descendants()
+            .filter(|n| n.tag_name().name() == "item")
+            .map(|n| NewsItem {
+                title: child(n, "title").unwrap_or_default(),
+                url: child(n, "link").unwrap_or_default(),
+                published: child(n, "pubDate")
+                    .and_then(|d| DateTime::parse_from_rfc2822(&d).ok())
+                    .map(|d| d.with_timezone(&Utc)),
+                summary: plain(&child(n, "description").unwrap_or_default()),
+            })
+            .collect(),
+        "feed" => root
+            .children()
+            .filter(|n| n.tag_name().name() == "entry")
+            .map(|n| NewsItem {
+                title: child(n, "title").unwrap_or_default(),
+                // The alternate link, the one without a rel otherwise.
+                url: n
+                    .children()
+                    .filter(|c| c.tag_name().name() == "link")
+                    .find(|c| c.attribute("rel").is_none_or(|r| r == "alternate"))
+                    .and_then(|c| c.attribute("href"))
+                    .unwrap_or_default()
+                    .to_owned(),
+                published: child(n, "published")
+                    .or_else(|| child(n, "updated"))
+                    .and_then(|d| DateTime::parse_from_rfc3339(&d).ok())
+                    .map(|d| d.with_timezone(&Utc)),
+                summary: plain(
+                    &child(n, "summary")
+                        .or_else(|| child(n, "content"))
+                        .unwrap_or_default(),
+                ),
+            })
+            .collect(),
+        other => bail!("not a feed but a <{}>", other),
+    };
+    Ok(items)
+}
+
+/// The text of a summary without its HTML or Steam's BBCode, on one line and
+/// cut short.
+fn plain(text: &str) -> String {
+    let mut out = String::new();
+    let mut markup = None;
+    for c in text.chars() {
+        match (markup, c) {
+            (None, '<') => markup = Some('>'),
+            (None, '[') => markup = Some(']'),
+            (Some(end), c) if c == end => {
+                markup = None;
+                out.push(' ');
+            }
+            (Some(_), _) => {}
+            (None, c) => out.push(c),
+        }
+    }
+    let out = out
+        .replace("&nbsp;", " ")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    let words = out.split_whitespace().collect::<Vec<_>>().join(" ");
+    match words.char_indices().nth(SUMMARY_LENGTH) {
+        Some((cut, _)) => format!("{}…", words[..cut].trim_end()),
+        None => words,
+    }
+}
+
+/// Newest first, undated last.
+fn newest_first(items: &mut [NewsItem]) {
+    items.sort_by_key(|i| Reverse(i.published));
+}
+
+/// Fetches the news of the games, cached on disk.
+#[derive(Debug, Clone)]
+pub struct Feeds {
+    config: FeedsConfig,
+    cache_dir: PathBuf,
+}
+
+impl Feeds {
+    pub fn new(config: FeedsConfig, cache_dir: PathBuf) -> Self {
+        Self { config, cache_dir }
+    }
+
+    fn cache_path(&self, uuid: &str) -> PathBuf {
+        self.cache_dir.join(format!("{}.json", uuid))
+    }
+
+    fn read_cache(&self, uuid: &str) -> Option<CachedNews> {
+        let data = fs::read(self.cache_path(uuid)).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    fn write_cache(&self, uuid: &str, items: &[NewsItem]) -> Result<()> {
+        fs::create_dir_all(&self.cache_dir)?;
+        let cached = CachedNews {
+            fetched_at: Utc::now(),
+            items: items.to_vec(),
+        };
+        fs::write(self.cache_path(uuid), serde_json::to_vec(&cached)?)?;
+        Ok(())
+    }
+
+    fn fetch(&self, source: &Source) -> Result<Vec<NewsItem>> {
+        match source {
+            Source::Steam(app_id) => {
+                let body = ureq::get(STEAM_NEWS_URL)
+                    .query("appid", &app_id.to_string())
+                    .query("count", &self.config.per_game.to_string())
+                    .query("format", "json")
+                    .call()?
+                    .into_string()?;
+                parse_steam(&body)
+            }
+            Source::Feed(url) => parse_feed(&ureq::get(url).call()?.into_string()?),
+        }
+    }
+
+    /// Whether the game has any news to follow.
+    pub fn follows(&self, game: &GameMetadata) -> bool {
+        !self.config.sources(game).is_empty()
+    }
+
+    /// News of the game, newest first, from the cache if it's recent enough.
+    /// A stale cache is used when the news can't be fetched.
+    /// Blocks on the network, call it off the UI thread.
+    pub fn news(&self, game: &GameMetadata) -> Result<Vec<NewsItem>> {
+        let cached = self.read_cache(&game.uuid);
+        if let Some(ref c) = cached {
+            let age = (Utc::now() - c.fetched_at).to_std().unwrap_or_default();
+            if age < self.config.refresh() {
+                return Ok(c.items.clone());
+            }
+        }
+        let fetched = self
+            .config
+            .sources(game)
+            .iter()
+            .map(|s| self.fetch(s))
+            .collect::<Result<Vec<_>>>();
+        match fetched {
+            Ok(fetched) => {
+                let mut items: Vec<_> = fetched.into_iter().flatten().collect();
+                newest_first(&mut items);
+                items.truncate(self.config.per_game);
+                if let Err(e) = self.write_cache(&game.uuid, &items) {
+                    warn!("failed to cache the news of {}: {:?}", game.uuid, e);
+                }
+                Ok(items)
+            }
+            Err(e) => cached.map(|c| c.items).ok_or(anyhow!(
+                "failed to fetch the news of {}: {}",
+                game.title,
+                e
+            )),
+        }
+    }
+
+    /// The newest news of the followed games, for the home screen.
+    pub fn latest(&self, games: &[GameMetadata]) -> Vec<Headline> {
+        let mut headlines: Vec<_> = games
+            .iter()
+            .filter(|g| g.favorate || !self.config.only_favorites)
+            .filter(|g| self.follows(g))
+            .flat_map(|g| {
+                let items = self.news(g).unwrap_or_else(|e| {
+                    warn!("{:?}", e);
+                    vec![]
+                });
+                items.into_iter().map(|item| Headline {
+                    uuid: g.uuid.clone(),
+                    game: g.title.clone(),
+                    item,
+                })
+            })
+            .collect();
+        headlines.sort_by_key(|h| Reverse(h.item.published));
+        headlines.truncate(self.config.latest);
+        headlines
+    }
+
+    /// Give `emit` the latest news every refresh interval, until it gives
+    /// false. Blocks, run it on a thread of its own.
+    pub fn follow(
+        &self,
+        games: impl Fn() -> Vec<GameMetadata>,
+        mut emit: impl FnMut(Vec<Headline>) -> bool,
+    ) -> Result<()> {
+        loop {
+            if !emit(self.latest(&games())) {
+                return Ok(());
+            }
+            thread::sleep(self.config.refresh());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_steam_news_rss_and_atom() {
+        let steam = r#"{"appnews": {"appid": 440, "newsitems": [
+            {"gid": "1", "title": "Patch", "url": "https://store.steampowered.com/news/1",
+             "contents": "[b]Fixed[/b] the <i>crash</i> &amp; more", "date": 1700000000}
+        ]}}"#;
+        let items = parse_steam(steam).unwrap();
+        assert_eq!(items[0].summary, "Fixed the crash & more");
+        assert_eq!(
+            items[0].published,
+            Some(Utc.with_ymd_and_hms(2023, 11, 14, 22, 13, 20).unwrap())
+        );
+
+        let rss = r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Devlog</title>
+            <item><title>Old</title><link>https://example.com/1</link>
+              <pubDate>Mon, 02 Jan 2023 10:00:00 +0000</pubDate></item>
+            <item><title>Undated</title><link>https://example.com/2</link>
+              <description>&lt;p&gt;Hello&lt;/p&gt;</description></item>
+            <item><title>New</title><link>https://example.com/3</link>
+              <pubDate>Tue, 03 Jan 2023 10:00:00 +0100</pubDate></item>
+            </channel></rss>"#;
+        let mut items = parse_feed(rss).unwrap();
+        assert_eq!(items[1].summary, "Hello");
+        newest_first(&mut items);
+        let titles: Vec<_> = items.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, ["New", "Old", "Undated"]);
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>Blog</title>
+            <entry><title>Launch</title>
+              <link rel="self" href="https://example.com/self"/>
+              <link href="https://example.com/launch"/>
+              <updated>2023-01-05T12:00:00Z</updated>
+              <summary>We're out!</summary></entry></feed>"#;
+        let items = parse_feed(atom).unwrap();
+        assert_eq!(items[0].url, "https://example.com/launch");
+        assert_eq!(items[0].summary, "We're out!");
+        assert!(parse_feed("<html></html>").is_err());
+        assert!(plain(&"word ".repeat(100)).ends_with("word…"));
+    }
+}
@@ -0,0 +1,243 @@
+language-name = English
+
+## Buttons and headings
+
+games = Games
+recently-played = Recently Played
+achievements-loading = Loading...
+achievements-not-set-up = Set up RetroAchievements to see the achievements.
+no-achievements = No achievements for this game.
+news-loading = Loading...
+no-news = No news for this game.
+tasks = Tasks
+settings = Settings
+back = Back
+cancel = Cancel
+close = Close
+library = Library
+back-up-library = Back up library
+restore-merge = Restore latest backup (merge)
+restore-replace = Restore latest backup (replace)
+find-duplicates = Find duplicates
+resolve-matches = Pick store matches
+trash = Trash
+empty-trash = Empty the trash
+pair-remote = Pair a remote
+trash-empty = Nothing in the trash
+restore = Restore
+purge = Delete for good
+trashed-deleted = Deleted { $date }
+trashed-overwritten = Metadata before the rescrape of { $date }
+which-game = Which one is { $title }?
+matches-left = { $count ->
+    [0] Nothing to pick, scraping queues the games it isn't sure about.
+    [one] 1 game to pick for
+   *[other] { $count } games to pick for
+}
+language = Language
+system = System
+playing = Playing
+power = Power
+duplicates = Duplicates
+merge = Merge
+skip = Skip
+play = Play
+overview = Overview
+achievements = Achievements
+screenshots = Screenshots
+no-screenshots = No screenshots of this game yet.
+news = News
+sessions = Sessions
+no-sessions = Not played yet.
+play-stats = Playtime stats
+weekly = Weekly
+monthly = Monthly
+stats-playtime = { $duration } played in { $sessions }
+stats-streak = { $days ->
+    [one] 1 day
+   *[other] { $days } days
+} in a row, { $longest } at most
+by-genre = By genre
+most-played = Most played
+stat-sessions = { $count ->
+    [one] 1 session
+   *[other] { $count } sessions
+}
+what-to-play = What to Play
+pick-unfinished-favorite = Unfinished favorite
+pick-long-unplayed = Not played in { $days } days
+pick-not-played-lately = Not played lately
+pick-similar = Like { $title }
+overlay = Overlay
+locked = Locked
+points = points
+not-installed = Not installed
+streamed = Streamed
+unavailable = Drive unplugged
+status-backlog = Backlog
+status-playing = Playing
+status-completed = Completed
+status-abandoned = Abandoned
+menu-status = Status: { $status }
+please-wait = Please wait...
+keep-local = Keep local
+keep-remote = Keep remote
+date-and-time = Date & time
+time-zone = Time zone
+sync-time = Set the time from the internet
+on = On
+off = Off
+not-synced-yet = Not set from the internet yet.
+software-update = Software update
+update-available = Update available
+install-update = Install update
+up-to-date = Up to date
+restart-to-update = Restart anubis to finish updating.
+whats-new = What's new
+diagnostics = Crash reports and usage stats
+launch-profiles = Launch profiles
+new-profile = New profile
+no-profiles = No profiles yet
+cpu-governor = CPU governor
+hdr = HDR
+vrr = Variable refresh rate
+compositor-bypass = Compositor bypass
+unchanged = Unchanged
+saves = Saves
+backup-saves = Back up now
+restore-save = Restore this backup
+no-save-location = No save location set, see `anubis cli set-save-path`
+no-snapshots = No backups yet
+storage = Storage
+rescan-storage = Scan again
+clear-art-cache = Clear cached art
+largest-unplayed = Largest unplayed games
+no-suggestions = No unplayed games to uninstall
+uninstall = Uninstall
+resume-last-game = Resume the last game on startup
+exit-to-desktop = Exit to desktop
+home-automation = Home automation
+mqtt = MQTT
+mqtt-broker = MQTT broker
+mqtt-username = MQTT username
+mqtt-password = MQTT password
+enter-pin = Enter the PIN
+notes = Notes
+notes-for = Notes for { $title }
+space = Space
+wrong-pin = Wrong PIN
+pin-locked = Too many tries, wait { $seconds } s
+suspended = Suspended
+no-suspended = No suspended games
+resume = Resume
+quit-game = Quit
+quiet-hours = Quiet hours
+auto = Auto
+quick-settings = Quick settings
+brightness = Brightness
+volume = Volume
+wifi = Wi-Fi
+bluetooth = Bluetooth
+not-available = Not available
+add-favorite = Add to favorites
+remove-favorite = Remove from favorites
+properties = Properties
+confirm = Confirm
+update = Update
+rescrape = Rescrape
+add-tag = Add tag
+move-to-collection = Move to collection
+delete = Delete
+clear-selection = Clear selection
+tag-for = Tag for { $title }
+collection-for = Collection for { $title }
+type-mqtt-broker = Address of the MQTT broker, e.g. homeassistant.local:1883
+games-selected = { $count ->
+    [one] 1 game selected
+   *[other] { $count } games selected
+}
+game-count = { $count ->
+    [one] 1 game
+   *[other] { $count } games
+}
+
+## Toasts
+
+game-unavailable = { $title } is on a drive that isn't plugged in.
+game-importing = This game is still being added.
+component-restarted = { $component } stopped and was restarted.
+component-gave-up = { $component } keeps stopping and was turned off.
+component-gamepad = Gamepad input
+component-navigation = Navigation
+component-remote = Remote control
+game-suspended = { $title } is suspended, resume it from Suspended.
+resuming = Starting { $title } in { $seconds } s, press any button to cancel
+bulk-done = Done with { $title }.
+bulk-failed = { $failed } of { $title } failed, see the log.
+games-deleted = { $title } moved to the trash, press Select to undo.
+games-restored = { $title } restored.
+
+## Confirmations
+
+confirm-uninstall = Uninstall { $title }? Its files are deleted.
+confirm-exit = Leave anubis for the desktop?
+confirm-restore-replace = Replace the library with the latest backup? Games added since are removed.
+confirm-delete-games = Remove { $title } from the library? Their files stay on disk.
+confirm-empty-trash = Empty the trash? What's in it is gone for good.
+confirm-pair-remote = Let { $name } control anubis? Check it shows the code { $code }.
+
+## Accounts
+
+epic-signed-in = Epic Games: signed in as { $account }
+epic-signed-out = Epic Games: not signed in, run `legendary auth`
+
+## Save backups
+
+saves-backed-up = Backed up.
+saves-unchanged = Nothing changed since the last backup.
+saves-restored = Restored, the saves it replaced were backed up.
+saves-game-running = Quit the game before restoring its saves.
+save-size = { $size } KB
+
+## Storage
+
+storage-scanning = Scanning the installed games…
+storage-scanned = Sizes as of { $date }
+art-cache-cleared = Cleared { $size } of cached art
+drive-details = { $games } of games, { $free } free of { $size }
+uninstalling = Uninstalling { $title }.
+
+## Startup
+
+boot-config = Loading the settings
+boot-library = Opening the library
+boot-input = Setting up the controllers
+boot-art = Getting the covers ready
+
+## Game details
+
+released = Released { $date }
+played = Played for { $duration }
+suspended-for = Suspended for { $duration }
+
+## Formatting
+
+# e.g. March 4, 2021
+date = { $month } { $day }, { $year }
+month-1 = January
+month-2 = February
+month-3 = March
+month-4 = April
+month-5 = May
+month-6 = June
+month-7 = July
+month-8 = August
+month-9 = September
+month-10 = October
+month-11 = November
+month-12 = December
+duration =
+    { $hours ->
+        [0] { $minutes } min
+       *[other] { $hours } h { $minutes } min
+    }
@@ -0,0 +1,247 @@
+language-name = Français
+
+## Buttons and headings
+
+games = Jeux
+recently-played = Joués récemment
+achievements-loading = Chargement...
+achievements-not-set-up = Configurez RetroAchievements pour voir les succès.
+no-achievements = Pas de succès pour ce jeu.
+news-loading = Chargement...
+no-news = Pas d'actualités pour ce jeu.
+tasks = Tâches
+settings = Paramètres
+back = Retour
+cancel = Annuler
+close = Fermer
+library = Bibliothèque
+back-up-library = Sauvegarder la bibliothèque
+restore-merge = Restaurer la dernière sauvegarde (fusionner)
+restore-replace = Restaurer la dernière sauvegarde (remplacer)
+find-duplicates = Chercher les doublons
+resolve-matches = Choisir les correspondances
+trash = Corbeille
+empty-trash = Vider la corbeille
+pair-remote = Associer une télécommande
+trash-empty = La corbeille est vide
+restore = Restaurer
+purge = Supprimer définitivement
+trashed-deleted = Supprimé le { $date }
+trashed-overwritten = Métadonnées d'avant la récupération du { $date }
+which-game = Lequel est { $title } ?
+matches-left = { $count ->
+    [0] Rien à choisir, la récupération des métadonnées met ici les jeux dont elle n'est pas sûre.
+    [one] 1 jeu à choisir
+   *[other] { $count } jeux à choisir
+}
+language = Langue
+system = Système
+playing = Jeu
+power = Alimentation
+duplicates = Doublons
+merge = Fusionner
+skip = Passer
+play = Jouer
+overview = Aperçu
+achievements = Succès
+screenshots = Captures d'écran
+no-screenshots = Pas encore de captures d'écran de ce jeu.
+news = Actualités
+sessions = Sessions
+no-sessions = Pas encore joué.
+play-stats = Statistiques de jeu
+weekly = Par semaine
+monthly = Par mois
+stats-playtime = { $duration } de jeu en { $sessions }
+stats-streak = { $days ->
+    [one] 1 jour
+   *[other] { $days } jours
+} d'affilée, { $longest } au plus
+by-genre = Par genre
+most-played = Les plus joués
+stat-sessions = { $count ->
+    [one] 1 session
+   *[other] { $count } sessions
+}
+what-to-play = À jouer
+pick-unfinished-favorite = Favori pas terminé
+pick-long-unplayed = Pas joué depuis { $days } jours
+pick-not-played-lately = Pas joué récemment
+pick-similar = Comme { $title }
+overlay = Incrustation
+locked = Verrouillé
+points = points
+not-installed = Non installé
+streamed = En streaming
+unavailable = Disque débranché
+status-backlog = À faire
+status-playing = En cours
+status-completed = Terminé
+status-abandoned = Abandonné
+menu-status = Statut : { $status }
+please-wait = Veuillez patienter...
+keep-local = Garder la version locale
+keep-remote = Garder la version distante
+date-and-time = Date et heure
+time-zone = Fuseau horaire
+sync-time = Régler l'heure par internet
+on = Activé
+off = Désactivé
+not-synced-yet = Pas encore réglée par internet.
+software-update = Mise à jour du logiciel
+update-available = Mise à jour disponible
+install-update = Installer la mise à jour
+up-to-date = À jour
+restart-to-update = Redémarrez anubis pour terminer la mise à jour.
+whats-new = Nouveautés
+diagnostics = Rapports de plantage et statistiques
+launch-profiles = Profils de lancement
+new-profile = Nouveau profil
+no-profiles = Aucun profil pour l'instant
+cpu-governor = Gouverneur CPU
+hdr = HDR
+vrr = Taux de rafraîchissement variable
+compositor-bypass = Contournement du compositeur
+unchanged = Inchangé
+saves = Sauvegardes
+backup-saves = Sauvegarder maintenant
+restore-save = Restaurer cette sauvegarde
+no-save-location = Emplacement des sauvegardes non défini, voir `anubis cli set-save-path`
+no-snapshots = Aucune sauvegarde pour l'instant
+storage = Stockage
+rescan-storage = Analyser à nouveau
+clear-art-cache = Vider le cache des images
+largest-unplayed = Plus gros jeux jamais lancés
+no-suggestions = Aucun jeu jamais lancé à désinstaller
+uninstall = Désinstaller
+resume-last-game = Relancer le dernier jeu au démarrage
+exit-to-desktop = Quitter vers le bureau
+home-automation = Domotique
+mqtt = MQTT
+mqtt-broker = Serveur MQTT
+mqtt-username = Utilisateur MQTT
+mqtt-password = Mot de passe MQTT
+enter-pin = Saisissez le code PIN
+notes = Notes
+notes-for = Notes pour { $title }
+space = Espace
+wrong-pin = Code PIN incorrect
+pin-locked = Trop d'essais, patientez { $seconds } s
+suspended = En pause
+no-suspended = Aucun jeu en pause
+resume = Reprendre
+quit-game = Quitter
+quiet-hours = Heures calmes
+auto = Auto
+quick-settings = Réglages rapides
+brightness = Luminosité
+volume = Volume
+wifi = Wi-Fi
+bluetooth = Bluetooth
+not-available = Indisponible
+add-favorite = Ajouter aux favoris
+remove-favorite = Retirer des favoris
+properties = Propriétés
+confirm = Confirmer
+update = Mettre à jour
+rescrape = Récupérer les métadonnées
+add-tag = Ajouter une étiquette
+move-to-collection = Déplacer dans une collection
+delete = Supprimer
+clear-selection = Vider la sélection
+tag-for = Étiquette pour { $title }
+collection-for = Collection pour { $title }
+type-mqtt-broker = Adresse du serveur MQTT, par ex. homeassistant.local:1883
+games-selected = { $count ->
+    [one] 1 jeu sélectionné
+   *[other] { $count } jeux sélectionnés
+}
+game-count = { $count ->
+    [one] 1 jeu
+   *[other] { $count } jeux
+}
+
+## Toasts
+
+game-unavailable = { $title } est sur un disque débranché.
+game-importing = Ce jeu est encore en cours d'ajout.
+component-restarted = Relancé après un arrêt : { $component }.
+component-gave-up = Désactivé après des arrêts répétés : { $component }.
+component-gamepad = entrée manette
+component-navigation = navigation
+component-remote = télécommande
+game-suspended = { $title } est en pause, reprenez-le depuis En pause.
+resuming = Lancement de { $title } dans { $seconds } s, appuyez sur un bouton pour annuler
+bulk-done = Terminé pour { $title }.
+bulk-failed = Échec pour { $failed } sur { $title }, voir le journal.
+games-deleted = { $title } dans la corbeille, appuyez sur Select pour annuler.
+games-restored = { $title } restauré(s).
+
+## Confirmations
+
+confirm-uninstall = Désinstaller { $title } ? Ses fichiers seront supprimés.
+confirm-exit = Quitter anubis pour le bureau ?
+confirm-restore-replace = Remplacer la bibliothèque par la dernière sauvegarde ? Les jeux ajoutés depuis seront retirés.
+confirm-delete-games = Retirer { $title } de la bibliothèque ? Leurs fichiers restent sur le disque.
+confirm-empty-trash = Vider la corbeille ? Son contenu sera perdu définitivement.
+confirm-pair-remote = Laisser { $name } contrôler anubis ? Vérifiez qu'il affiche le code { $code }.
+
+## Accounts
+
+epic-signed-in = Epic Games : compte { $account }
+epic-signed-out = Epic Games : aucun compte, lancez `legendary auth`
+
+## Save backups
+
+saves-backed-up = Sauvegarde effectuée.
+saves-unchanged = Rien n'a changé depuis la dernière sauvegarde.
+saves-restored = Restauré, les fichiers remplacés ont été sauvegardés.
+saves-game-running = Quittez le jeu avant de restaurer ses sauvegardes.
+save-size = { $size } Ko
+
+## Storage
+
+storage-scanning = Analyse des jeux installés…
+storage-scanned = Tailles au { $date }
+art-cache-cleared = { $size } d'images en cache supprimés
+drive-details = { $games } de jeux, { $free } libres sur { $size }
+uninstalling = Désinstallation de { $title }.
+
+## Startup
+
+boot-config = Chargement des paramètres
+boot-library = Ouverture de la bibliothèque
+boot-input = Préparation des manettes
+boot-art = Préparation des jaquettes
+
+## Game details
+
+released = Sorti le { $date }
+played = Joué { $duration }
+suspended-for = En pause depuis { $duration }
+
+## Formatting
+
+# e.g. 4 mars 2021
+date =
+    { $day ->
+        [1] 1er
+       *[other] { $day }
+    } { $month } { $year }
+month-1 = janvier
+month-2 = février
+month-3 = mars
+month-4 = avril
+month-5 = mai
+month-6 = juin
+month-7 = juillet
+month-8 = août
+month-9 = septembre
+month-10 = octobre
+month-11 = novembre
+month-12 = décembre
+duration =
+    { $hours ->
+        [0] { $minutes } min
+       *[other] { $hours } h { $minutes } min
+    }
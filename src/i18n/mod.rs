@@ -0,0 +1,193 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Utc};
+use fluent::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{env, fs, path::Path};
+use unic_langid::LanguageIdentifier;
+
+// Translations of the UI, from the Fluent files next to this one, built in.
+// English fills in for whatever a locale is missing.
+
+const FALLBACK: &str = "en-US";
+const LOCALES: &[(&str, &str)] = &[
+    ("en-US", include_str!("en-US.ftl")),
+    ("fr", include_str!("fr.ftl")),
+];
+
+/// The locale picked in the settings, the system's when unset.
+///
+/// ```yaml
+/// locale: fr
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LocaleConfig {
+    pub locale: Option<String>,
+}
+
+impl LocaleConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+fn bundle(locale: &str) -> FluentBundle<FluentResource> {
+    let (_, ftl) = LOCALES.iter().find(|(l, _)| *l == locale).unwrap();
+    // The files are built in, the tests make sure they parse.
+    let resource = FluentResource::try_new(ftl.to_string()).unwrap();
+    let mut bundle = FluentBundle::new_concurrent(vec![locale.parse().unwrap()]);
+    // No invisible isolation marks around arguments, Slint shows them.
+    bundle.set_use_isolating(false);
+    bundle.add_resource(resource).unwrap();
+    bundle
+}
+
+/// The locale closest to `wanted`, e.g. `fr` for `fr_CA.UTF-8`.
+fn negotiate(wanted: &str) -> &'static str {
+    // POSIX style, like $LANG has it.
+    let wanted = wanted
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-");
+    let Ok(wanted) = wanted.parse::<LanguageIdentifier>() else {
+        return FALLBACK;
+    };
+    let locales = || LOCALES.iter().map(|(l, _)| *l);
+    locales()
+        .find(|l| l.parse::<LanguageIdentifier>().is_ok_and(|l| l == wanted))
+        .or_else(|| {
+            locales().find(|l| {
+                l.parse::<LanguageIdentifier>()
+                    .is_ok_and(|l| l.language == wanted.language)
+            })
+        })
+        .unwrap_or(FALLBACK)
+}
+
+pub struct Translations {
+    locale: &'static str,
+    bundle: FluentBundle<FluentResource>,
+    fallback: FluentBundle<FluentResource>,
+}
+
+impl Translations {
+    pub fn new(wanted: &str) -> Self {
+        let locale = negotiate(wanted);
+        Self {
+            locale,
+            bundle: bundle(locale),
+            fallback: bundle(FALLBACK),
+        }
+    }
+
+    /// In the configured locale, or the system's.
+    pub fn load(path: &Path) -> Self {
+        let config = LocaleConfig::load(path).unwrap_or_else(|e| {
+            warn!("failed to load the locale config: {:?}", e);
+            LocaleConfig::default()
+        });
+        let system = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|v| env::var(v).ok().filter(|v| !v.is_empty()));
+        Self::new(&config.locale.or(system).unwrap_or_default())
+    }
+
+    pub fn locale(&self) -> &'static str {
+        self.locale
+    }
+
+    /// The locale after this one, for going through them in the settings.
+    pub fn next(&self) -> Self {
+        let i = LOCALES.iter().position(|(l, _)| *l == self.locale);
+        let next = i.map_or(0, |i| (i + 1) % LOCALES.len());
+        Self::new(LOCALES[next].0)
+    }
+
+    pub fn tr(&self, id: &str) -> String {
+        self.tr_args(id, None)
+    }
+
+    /// The message with `id`, the id itself if there's none.
+    pub fn tr_args(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        for bundle in [&self.bundle, &self.fallback] {
+            let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) else {
+                continue;
+            };
+            let mut errors = vec![];
+            let text = bundle.format_pattern(pattern, args, &mut errors);
+            if !errors.is_empty() {
+                warn!("bad translation {} in {}: {:?}", id, self.locale, errors);
+            }
+            return text.into_owned();
+        }
+        warn!("no translation for {}", id);
+        id.to_owned()
+    }
+
+    pub fn format_date(&self, date: &DateTime<Utc>) -> String {
+        let mut args = FluentArgs::new();
+        args.set("day", date.day());
+        args.set("month", self.tr(&format!("month-{}", date.month())));
+        args.set("year", date.year());
+        self.tr_args("date", Some(&args))
+    }
+
+    pub fn format_duration(&self, duration: &chrono::Duration) -> String {
+        let mut args = FluentArgs::new();
+        args.set("hours", duration.num_hours());
+        args.set("minutes", duration.num_minutes() % 60);
+        self.tr_args("duration", Some(&args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn translates() {
+        // Every locale has every message of the fallback.
+        let ids = LOCALES[0]
+            .1
+            .lines()
+            .filter_map(|l| l.split_once(" =").map(|(id, _)| id))
+            .filter(|id| !id.starts_with(['#', ' ']));
+        for (locale, _) in LOCALES {
+            let t = Translations::new(locale);
+            assert_eq!(t.locale(), *locale);
+            for id in ids.clone() {
+                assert!(t.bundle.has_message(id), "no {} in {}", id, locale);
+            }
+        }
+
+        assert_eq!(Translations::new("fr_CA.UTF-8").locale(), "fr");
+        assert_eq!(Translations::new("en-GB").locale(), "en-US");
+        assert_eq!(Translations::new("C").locale(), "en-US");
+        assert_eq!(Translations::new("en-US").next().locale(), "fr");
+
+        let date = Utc.with_ymd_and_hms(2021, 3, 1, 0, 0, 0).unwrap();
+        let playtime = chrono::Duration::minutes(125);
+        let en = Translations::new("en-US");
+        assert_eq!(en.tr("settings"), "Settings");
+        assert_eq!(en.format_date(&date), "March 1, 2021");
+        assert_eq!(en.format_duration(&playtime), "2 h 5 min");
+        let fr = Translations::new("fr");
+        assert_eq!(fr.format_date(&date), "1er mars 2021");
+        assert_eq!(fr.format_duration(&playtime), "2 h 5 min");
+        assert_eq!(fr.format_duration(&chrono::Duration::minutes(40)), "40 min");
+        assert_eq!(fr.tr("no-such-message"), "no-such-message");
+    }
+}
@@ -0,0 +1,85 @@
+use super::Message;
+use crate::paths;
+use anyhow::{anyhow, bail, Result};
+use std::{env, fs, path::PathBuf, process::Command};
+
+// `anubis://` links, e.g. from a browser or a desktop shortcut.
+//
+// anubis://game/${UUID}         opens the details of the game
+// anubis://game/${UUID}/launch  launches it
+
+pub const SCHEME: &str = "anubis";
+
+const DESKTOP_FILE: &str = "anubis-uri.desktop";
+
+/// What the link asks the launcher to do.
+pub fn parse(uri: &str) -> Result<Message> {
+    let rest = uri
+        .strip_prefix(SCHEME)
+        .and_then(|r| r.strip_prefix("://"))
+        .ok_or(anyhow!("not an {} link: {}", SCHEME, uri))?;
+    let path = rest.split(['?', '#']).next().unwrap_or_default();
+    let parts: Vec<_> = path.split('/').filter(|p| !p.is_empty()).collect();
+    Ok(match parts[..] {
+        ["game", uuid] => Message::Open {
+            uuid: uuid.to_owned(),
+        },
+        ["game", uuid, "launch"] => Message::Launch {
+            uuid: uuid.to_owned(),
+        },
+        _ => bail!("unknown link {}", uri),
+    })
+}
+
+/// Make the desktop open the links with this executable.
+/// Returns the desktop entry written.
+pub fn register() -> Result<PathBuf> {
+    let exe = env::current_exe()?;
+    let entry = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Anubis\n\
+         NoDisplay=true\n\
+         Exec=\"{}\" %u\n\
+         MimeType=x-scheme-handler/{};\n",
+        exe.display(),
+        SCHEME
+    );
+    let dir = paths::applications_dir();
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(DESKTOP_FILE);
+    fs::write(&path, entry)?;
+
+    let status = Command::new("xdg-mime")
+        .args(["default", DESKTOP_FILE])
+        .arg(format!("x-scheme-handler/{}", SCHEME))
+        .status()
+        .map_err(|e| anyhow!("failed to run xdg-mime: {}", e))?;
+    if !status.success() {
+        bail!("xdg-mime failed with {}", status);
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_links() {
+        assert_eq!(
+            parse("anubis://game/aaaa").unwrap(),
+            Message::Open {
+                uuid: "aaaa".to_owned()
+            }
+        );
+        assert_eq!(
+            parse("anubis://game/aaaa/launch/?from=browser").unwrap(),
+            Message::Launch {
+                uuid: "aaaa".to_owned()
+            }
+        );
+        assert!(parse("anubis://settings").is_err());
+        assert!(parse("https://game/aaaa").is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::paths;
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+};
+
+pub mod deeplink;
+
+// Only one launcher runs at a time, a second one hands its arguments over to
+// the first one through a local socket then exits. Two launchers would read
+// the same gamepads and overwrite each other's library.
+
+/// Sent by a new instance to the running one, a JSON object per line.
+///
+/// ```json
+/// {"message": "activate"}
+/// {"message": "launch", "uuid": "0123456789abcdef"}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "message", rename_all = "snake_case")]
+pub enum Message {
+    /// Bring the window to the front.
+    Activate,
+    /// Bring the window to the front and open the details of the game.
+    Open { uuid: String },
+    /// Bring the window to the front and launch the game.
+    Launch { uuid: String },
+}
+
+/// The running instance, owns the socket.
+pub struct SingleInstance {
+    listener: UnixListener,
+}
+
+impl SingleInstance {
+    pub fn default_path() -> PathBuf {
+        paths::runtime_dir().join("instance.sock")
+    }
+
+    /// Whether a launcher is listening on the socket.
+    pub fn is_running(path: &Path) -> bool {
+        UnixStream::connect(path).is_ok()
+    }
+
+    /// Become the running instance, or hand the message over to the one
+    /// already running, in which case None is returned.
+    pub fn acquire(path: &Path, message: &Message) -> Result<Option<Self>> {
+        match UnixStream::connect(path) {
+            Ok(mut stream) => {
+                let mut line = serde_json::to_string(message)?;
+                line.push('\n');
+                stream.write_all(line.as_bytes())?;
+                return Ok(None);
+            }
+            // Left behind by a crash.
+            Err(e) if e.kind() == ErrorKind::ConnectionRefused => fs::remove_file(path)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(anyhow!("failed to connect to {:?}: {}", path, e)),
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(Some(Self {
+            listener: UnixListener::bind(path)?,
+        }))
+    }
+
+    /// Forward the messages of the next instances in the background.
+    pub fn spawn(self, tx: mpsc::Sender<Message>) {
+        thread::spawn(move || {
+            for stream in self.listener.incoming() {
+                let stream = match stream {
+                    Ok(s) => s,
+                    Err(e) => {
+                        warn!("failed to accept an instance connection: {:?}", e);
+                        continue;
+                    }
+                };
+                // Anything but a message, e.g. `is_running`, is ignored.
+                for line in BufReader::new(stream).lines().map_while(io::Result::ok) {
+                    match serde_json::from_str(&line) {
+                        Ok(message) => {
+                            if tx.send(message).is_err() {
+                                return;
+                            }
+                        }
+                        Err(e) => debug!("bad instance message {:?}: {:?}", line, e),
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hands_over_to_the_running_instance() {
+        let path =
+            std::env::temp_dir().join(format!("anubis-instance-test-{}.sock", std::process::id()));
+        // A stale socket, nobody listens on it.
+        drop(UnixListener::bind(&path).unwrap());
+
+        let instance = SingleInstance::acquire(&path, &Message::Activate)
+            .unwrap()
+            .unwrap();
+        let (tx, rx) = mpsc::channel();
+        instance.spawn(tx);
+        assert!(SingleInstance::is_running(&path));
+
+        let launch = Message::Launch {
+            uuid: "aaaa".to_owned(),
+        };
+        assert!(SingleInstance::acquire(&path, &launch).unwrap().is_none());
+        assert_eq!(rx.recv(), Ok(launch));
+        fs::remove_file(&path).unwrap();
+    }
+}
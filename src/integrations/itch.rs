@@ -0,0 +1,230 @@
+use crate::models::{GameMetadata, ImageSource};
+use flate2::read::GzDecoder;
+use log::debug;
+use serde::Deserialize;
+use std::{
+    fs,
+    io::Read,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+// Games installed by the itch app. Each install folder has a receipt written
+// by butler (`.itch/receipt.json.gz`) with the game as itch.io has it, and may
+// have a `.itch.toml` manifest telling what to run. Without one, the
+// executable is guessed from the installed files like the app does.
+
+/// Prefix of `GameMetadata::install_source` for itch.io games.
+pub const INSTALL_SOURCE_PREFIX: &str = "itch:";
+
+/// The install locations of the itch app, native and Flatpak.
+pub fn default_apps_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        dirs.push(home.join(".config/itch/apps"));
+        dirs.push(home.join(".var/app/io.itch.itch/config/itch/apps"));
+    }
+    dirs
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Game {
+    id: u64,
+    title: String,
+    short_text: Option<String>,
+    cover_url: Option<String>,
+    /// Not animated, for gifs.
+    still_cover_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Receipt {
+    game: Game,
+    #[serde(default)]
+    files: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Action {
+    name: String,
+    path: String,
+    #[serde(default)]
+    args: Vec<String>,
+    platform: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    actions: Vec<Action>,
+}
+
+fn read_receipt(dir: &Path) -> anyhow::Result<Receipt> {
+    let mut json = String::new();
+    GzDecoder::new(fs::File::open(dir.join(".itch/receipt.json.gz"))?).read_to_string(&mut json)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// The play action of the manifest, links like the manual are skipped.
+fn manifest_command(dir: &Path) -> Option<Vec<String>> {
+    let manifest: Manifest = toml::from_str(&fs::read_to_string(dir.join(".itch.toml")).ok()?)
+        .map_err(|e| debug!("bad manifest in {:?}: {:?}", dir, e))
+        .ok()?;
+    let action = manifest
+        .actions
+        .into_iter()
+        .filter(|a| !a.path.contains("://"))
+        .filter(|a| a.platform.as_deref().is_none_or(|p| p == "linux"))
+        .min_by_key(|a| a.name != "play")?;
+    let path = dir.join(&action.path).to_string_lossy().into_owned();
+    Some([path].into_iter().chain(action.args).collect())
+}
+
+/// The executable least deep in the install, Linux builds' own names first.
+fn guess_executable(dir: &Path, files: &[String]) -> Option<String> {
+    files
+        .iter()
+        .filter(|f| {
+            fs::metadata(dir.join(f))
+                .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        })
+        .min_by_key(|f| {
+            let linux = f.ends_with(".x86_64") || f.ends_with(".sh");
+            (f.matches('/').count(), !linux, f.len())
+        })
+        .map(|f| dir.join(f).to_string_lossy().into_owned())
+}
+
+fn game_in(dir: &Path) -> Option<GameMetadata> {
+    let receipt = read_receipt(dir)
+        .map_err(|e| debug!("no receipt in {:?}: {:?}", dir, e))
+        .ok()?;
+    let launch_options = manifest_command(dir)
+        .or_else(|| guess_executable(dir, &receipt.files).map(|e| vec![e]))
+        .unwrap_or_default();
+    let game = receipt.game;
+    Some(GameMetadata {
+        title: game.title,
+        desc: game.short_text,
+        platform: Some("pc".to_owned()),
+        cover_art: game
+            .still_cover_url
+            .or(game.cover_url)
+            .map(ImageSource::Url),
+        install_source: Some(format!("{}{}", INSTALL_SOURCE_PREFIX, game.id)),
+        launch_options,
+        ..Default::default()
+    })
+}
+
+/// Games with a receipt in the install locations, each listed once.
+pub fn installed_games(apps_dirs: &[PathBuf]) -> Vec<GameMetadata> {
+    let mut games: Vec<GameMetadata> = vec![];
+    for dir in apps_dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Some(game) = game_in(&entry.path()) else {
+                continue;
+            };
+            if !games
+                .iter()
+                .any(|g| g.install_source == game.install_source)
+            {
+                games.push(game);
+            }
+        }
+    }
+    games.sort_by(|a, b| a.title.cmp(&b.title));
+    games
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    fn install(dir: &Path, receipt: &str, files: &[(&str, u32)]) {
+        fs::create_dir_all(dir.join(".itch")).unwrap();
+        let mut gz = GzEncoder::new(
+            fs::File::create(dir.join(".itch/receipt.json.gz")).unwrap(),
+            Compression::default(),
+        );
+        gz.write_all(receipt.as_bytes()).unwrap();
+        gz.finish().unwrap();
+        for (file, mode) in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(*mode)).unwrap();
+        }
+    }
+
+    #[test]
+    fn reads_the_installed_games() {
+        let apps = std::env::temp_dir().join(format!("anubis-itch-test-{}", std::process::id()));
+        install(
+            &apps.join("celeste-classic"),
+            r#"{"game": {"id": 62, "title": "Celeste Classic", "coverUrl": "https://img.itch.zone/a.png"},
+                "files": ["readme.txt", "data/helper", "Celeste.x86_64", "launch"]}"#,
+            &[
+                ("readme.txt", 0o644),
+                ("data/helper", 0o755),
+                ("Celeste.x86_64", 0o755),
+                ("launch", 0o755),
+            ],
+        );
+        install(
+            &apps.join("overland"),
+            r#"{"game": {"id": 7, "title": "Overland", "shortText": "Post-apocalyptic road trip",
+                "coverUrl": "https://img.itch.zone/b.gif", "stillCoverUrl": "https://img.itch.zone/b.png"}}"#,
+            &[],
+        );
+        fs::write(
+            apps.join("overland/.itch.toml"),
+            r#"
+[[actions]]
+name = "manual"
+path = "https://example.com/manual"
+
+[[actions]]
+name = "play"
+path = "Overland.exe"
+platform = "windows"
+
+[[actions]]
+name = "play"
+path = "bin/overland"
+args = ["--fullscreen"]
+"#,
+        )
+        .unwrap();
+        fs::create_dir_all(apps.join("not-a-game")).unwrap();
+
+        let games = installed_games(&[apps.clone(), apps.clone()]);
+        let root = apps.to_string_lossy();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].title, "Celeste Classic");
+        assert_eq!(games[0].install_source.as_deref(), Some("itch:62"));
+        assert_eq!(
+            games[0].launch_options,
+            [format!("{}/celeste-classic/Celeste.x86_64", root)]
+        );
+        assert_eq!(games[1].desc.as_deref(), Some("Post-apocalyptic road trip"));
+        assert_eq!(
+            games[1].cover_art,
+            Some(ImageSource::Url("https://img.itch.zone/b.png".to_owned()))
+        );
+        assert_eq!(
+            games[1].launch_options,
+            [
+                format!("{}/overland/bin/overland", root),
+                "--fullscreen".to_owned()
+            ]
+        );
+        fs::remove_dir_all(apps).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+use crate::models::{GameMetadata, ImageSource};
+use anyhow::{bail, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{collections::HashMap, env, process::Command};
+
+// Epic Games Store games, through legendary (https://github.com/derrod/legendary).
+// It's signed in with `legendary auth` and installs the games, we only list
+// them, read their store metadata and launch them, all with its JSON output.
+
+/// Prefix of `GameMetadata::install_source` for Epic games, followed by
+/// legendary's app name.
+pub const INSTALL_SOURCE_PREFIX: &str = "epic:";
+/// What `legendary status` says when signed out.
+const NOT_LOGGED_IN: &str = "<not logged in>";
+
+pub fn app_name_from_install_source(source: &str) -> Option<&str> {
+    source.strip_prefix(INSTALL_SOURCE_PREFIX)
+}
+
+pub fn is_installed() -> bool {
+    env::var_os("PATH").is_some_and(|p| env::split_paths(&p).any(|d| d.join("legendary").is_file()))
+}
+
+fn run_json<T: DeserializeOwned>(args: &[&str]) -> Result<T> {
+    let output = Command::new("legendary")
+        .args(args)
+        .output()
+        .context("failed to run legendary")?;
+    if !output.status.success() {
+        bail!(
+            "legendary {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+#[derive(Debug, Deserialize)]
+struct Installed {
+    app_name: String,
+    title: String,
+    #[serde(default)]
+    is_dlc: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Status {
+    account: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyImage {
+    #[serde(rename = "type")]
+    kind: String,
+    url: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Metadata {
+    description: Option<String>,
+    developer: Option<String>,
+    #[serde(default)]
+    key_images: Vec<KeyImage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Owned {
+    app_name: String,
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+/// Store metadata of a game.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Details {
+    pub description: Option<String>,
+    pub developer: Option<String>,
+    pub cover: Option<String>,
+    pub background: Option<String>,
+}
+
+impl From<Metadata> for Details {
+    fn from(metadata: Metadata) -> Self {
+        let image = |kind: &str| {
+            metadata
+                .key_images
+                .iter()
+                .find(|i| i.kind == kind)
+                .map(|i| i.url.clone())
+        };
+        Self {
+            cover: image("DieselGameBoxTall"),
+            background: image("DieselGameBox"),
+            description: metadata.description,
+            developer: metadata.developer,
+        }
+    }
+}
+
+/// The name of the account signed in, None when signed out.
+pub fn account() -> Result<Option<String>> {
+    let status: Status = run_json(&["status", "--json"])?;
+    Ok(Some(status.account).filter(|a| a != NOT_LOGGED_IN))
+}
+
+fn games_of(installed: Vec<Installed>) -> Vec<GameMetadata> {
+    installed
+        .into_iter()
+        .filter(|g| !g.is_dlc)
+        .map(|g| GameMetadata {
+            title: g.title,
+            platform: Some("pc".to_owned()),
+            install_source: Some(format!("{}{}", INSTALL_SOURCE_PREFIX, g.app_name)),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// The games installed by legendary, DLCs left out.
+pub fn installed_games() -> Result<Vec<GameMetadata>> {
+    Ok(games_of(run_json(&["list-installed", "--json"])?))
+}
+
+/// Store metadata of the games owned, by app name. Needs to be signed in.
+pub fn library_details() -> Result<HashMap<String, Details>> {
+    let owned: Vec<Owned> = run_json(&["list", "--json"])?;
+    Ok(owned
+        .into_iter()
+        .map(|g| (g.app_name, g.metadata.into()))
+        .collect())
+}
+
+/// Fill in the metadata of a game, only the missing bits unless forced.
+pub fn apply_details(game: &mut GameMetadata, details: Details, force: bool) {
+    if force || game.desc.is_none() {
+        game.desc = details.description;
+    }
+    if force || game.developers.is_empty() {
+        game.developers = details.developer.into_iter().collect();
+    }
+    if force || game.cover_art.is_none() {
+        game.cover_art = details.cover.map(ImageSource::Url);
+    }
+    if force || game.bg_art.is_none() {
+        game.bg_art = details.background.map(ImageSource::Url);
+    }
+}
+
+pub fn command(app_name: &str) -> Command {
+    let mut cmd = Command::new("legendary");
+    cmd.args(["launch", app_name]);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_games_and_their_metadata() {
+        let installed: Vec<Installed> = serde_json::from_str(
+            r#"[
+                {"app_name": "Fortnite", "title": "Fortnite", "is_dlc": false, "version": "1.0"},
+                {"app_name": "a1b2", "title": "Some DLC", "is_dlc": true}
+            ]"#,
+        )
+        .unwrap();
+        let games = games_of(installed);
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].install_source.as_deref(), Some("epic:Fortnite"));
+        assert_eq!(
+            app_name_from_install_source("epic:Fortnite"),
+            Some("Fortnite")
+        );
+
+        let owned: Owned = serde_json::from_str(
+            r#"{"app_name": "Fortnite", "app_title": "Fortnite", "metadata": {
+                "description": "Battle royale", "developer": "Epic Games",
+                "keyImages": [
+                    {"type": "DieselGameBox", "url": "https://cdn/wide.jpg"},
+                    {"type": "DieselGameBoxTall", "url": "https://cdn/tall.jpg"}
+                ]}}"#,
+        )
+        .unwrap();
+        let mut game = games[0].clone();
+        game.desc = Some("Mine".to_owned());
+        apply_details(&mut game, owned.metadata.into(), false);
+        assert_eq!(game.desc.as_deref(), Some("Mine"));
+        assert_eq!(game.developers, ["Epic Games"]);
+        assert_eq!(
+            game.cover_art,
+            Some(ImageSource::Url("https://cdn/tall.jpg".to_owned()))
+        );
+        assert_eq!(
+            game.bg_art,
+            Some(ImageSource::Url("https://cdn/wide.jpg".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,22 @@
+pub mod itch;
+pub mod legendary;
+pub mod moonlight;
+pub mod retroachievements;
+pub mod steam;
+pub mod steam_shortcuts;
+
+/// Install progress, as reported by the store backends.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstallState {
+    /// Progress is within [0, 1].
+    Downloading {
+        progress: f32,
+        downloaded: u64,
+        total: u64,
+    },
+    Verifying {
+        progress: f32,
+    },
+    Installed,
+    Failed(String),
+}
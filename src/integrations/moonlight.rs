@@ -0,0 +1,194 @@
+use crate::models::GameMetadata;
+use anyhow::{bail, Context, Result};
+use std::{env, process::Command};
+
+// Games streamed from another PC running Sunshine or GeForce Experience, played
+// through the Moonlight client. Hosts announce themselves over mDNS, found with
+// `avahi-browse`. Their apps are listed by Moonlight, which has to be paired
+// with the host for it, and become library entries like installed games.
+
+/// Prefix of `GameMetadata::install_source` for streamed games, followed by
+/// `${HOST}/${APP}`.
+pub const INSTALL_SOURCE_PREFIX: &str = "moonlight:";
+/// The mDNS service of GameStream hosts, Sunshine announces the same.
+const SERVICE: &str = "_nvstream._tcp";
+/// Moonlight when it's installed from Flathub.
+const FLATPAK_ID: &str = "com.moonlight_stream.Moonlight";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Host {
+    /// The computer name, what Moonlight knows it by.
+    pub name: String,
+    pub address: String,
+}
+
+pub fn install_source(host: &str, app: &str) -> String {
+    format!("{}{}/{}", INSTALL_SOURCE_PREFIX, host, app)
+}
+
+/// The host and app of a streamed game.
+pub fn stream_from_install_source(source: &str) -> Option<(&str, &str)> {
+    source.strip_prefix(INSTALL_SOURCE_PREFIX)?.split_once('/')
+}
+
+pub fn is_streamed(game: &GameMetadata) -> bool {
+    game.install_source
+        .as_deref()
+        .and_then(stream_from_install_source)
+        .is_some()
+}
+
+/// `\032` and such in names, avahi escapes them as decimal bytes.
+fn unescape(s: &str) -> String {
+    let mut bytes = vec![];
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let code = tail
+            .get(..3)
+            .filter(|d| b == b'\\' && d.iter().all(u8::is_ascii_digit))
+            .and_then(|d| std::str::from_utf8(d).ok()?.parse::<u8>().ok());
+        match code {
+            Some(code) => {
+                bytes.push(code);
+                rest = &tail[3..];
+            }
+            None => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// The resolved services of `avahi-browse -rpt`, e.g.
+/// `=;eth0;IPv4;Gaming\032PC;_nvstream._tcp;local;gaming-pc.local;192.168.1.5;47989;`.
+/// Hosts seen over IPv4 and IPv6 are only listed once, IPv4 first.
+fn parse_avahi(output: &str) -> Vec<Host> {
+    let mut found: Vec<(bool, Host)> = output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(';').collect();
+            if fields.len() < 9 || fields[0] != "=" {
+                return None;
+            }
+            let host = Host {
+                name: unescape(fields[3]),
+                address: fields[7].to_owned(),
+            };
+            Some((fields[2] != "IPv4", host))
+        })
+        .collect();
+    found.sort_by_key(|(ipv6, _)| *ipv6);
+    let mut hosts: Vec<Host> = vec![];
+    for (_, host) in found {
+        if !hosts.iter().any(|h| h.name == host.name) {
+            hosts.push(host);
+        }
+    }
+    hosts
+}
+
+/// The hosts on the local network.
+pub fn discover() -> Result<Vec<Host>> {
+    let output = Command::new("avahi-browse")
+        .args(["-rpt", SERVICE])
+        .output()
+        .context("failed to run avahi-browse")?;
+    if !output.status.success() {
+        bail!(
+            "avahi-browse failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(parse_avahi(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// The Moonlight client, from the PATH or Flathub.
+fn client() -> Command {
+    let on_path = env::var_os("PATH")
+        .is_some_and(|p| env::split_paths(&p).any(|d| d.join("moonlight").is_file()));
+    if on_path {
+        return Command::new("moonlight");
+    }
+    let mut cmd = Command::new("flatpak");
+    cmd.args(["run", FLATPAK_ID]);
+    cmd
+}
+
+/// The apps of a paired host.
+pub fn apps(host: &str) -> Result<Vec<String>> {
+    let output = client()
+        .args(["list", host])
+        .output()
+        .context("failed to run moonlight")?;
+    if !output.status.success() {
+        bail!(
+            "moonlight couldn't list the apps of {}, is it paired? {}",
+            host,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_owned)
+        .collect())
+}
+
+/// The apps of a host as library entries.
+pub fn games(host: &str) -> Result<Vec<GameMetadata>> {
+    Ok(apps(host)?
+        .into_iter()
+        .map(|app| GameMetadata {
+            install_source: Some(install_source(host, &app)),
+            title: app,
+            platform: Some("pc".to_owned()),
+            ..Default::default()
+        })
+        .collect())
+}
+
+/// Stream an app, the client exits once the stream ends.
+pub fn command(host: &str, app: &str) -> Command {
+    let mut cmd = client();
+    cmd.args(["stream", host, app]);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_hosts_and_their_games() {
+        let output = "\
++;eth0;IPv6;Gaming\\032PC;_nvstream._tcp;local
+=;eth0;IPv6;Gaming\\032PC;_nvstream._tcp;local;gaming-pc.local;fe80::1;47989;
+=;eth0;IPv4;Gaming\\032PC;_nvstream._tcp;local;gaming-pc.local;192.168.1.5;47989;
+=;eth0;IPv4;htpc;_nvstream._tcp;local;htpc.local;192.168.1.7;47989;\"SUNSHINE\"
+";
+        assert_eq!(
+            parse_avahi(output),
+            [
+                Host {
+                    name: "Gaming PC".to_owned(),
+                    address: "192.168.1.5".to_owned(),
+                },
+                Host {
+                    name: "htpc".to_owned(),
+                    address: "192.168.1.7".to_owned(),
+                },
+            ]
+        );
+
+        let source = install_source("Gaming PC", "Steam Big Picture/Desktop");
+        assert_eq!(source, "moonlight:Gaming PC/Steam Big Picture/Desktop");
+        assert_eq!(
+            stream_from_install_source(&source),
+            Some(("Gaming PC", "Steam Big Picture/Desktop"))
+        );
+        assert_eq!(stream_from_install_source("steam:440"), None);
+    }
+}
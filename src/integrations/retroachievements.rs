@@ -0,0 +1,284 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use log::warn;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Game ID on retroachievements.org.
+pub type GameId = u32;
+
+const API_URL: &str = "https://retroachievements.org/API";
+const BADGE_URL: &str = "https://media.retroachievements.org/Badge";
+/// How long before the cached progress is fetched again.
+const CACHE_TTL_MINUTES: i64 = 10;
+
+/// Credentials, the API key is found in the settings page of the site.
+///
+/// ```yaml
+/// username: deck
+/// api_key: 0123456789abcdef
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetroAchievementsConfig {
+    pub username: String,
+    pub api_key: String,
+}
+
+impl RetroAchievementsConfig {
+    /// Load the config, a missing file means the integration is off.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_yaml::from_str(&fs::read_to_string(path)?)?))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Achievement {
+    pub id: u32,
+    pub title: String,
+    pub description: String,
+    pub points: u32,
+    pub badge_url: String,
+    /// None while locked.
+    pub unlocked_at: Option<DateTime<Utc>>,
+    /// Unlocked in hardcore mode, i.e. without save states.
+    pub hardcore: bool,
+}
+
+/// Achievements of a game, with the unlock state of the user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameProgress {
+    pub game_id: GameId,
+    pub title: String,
+    /// In display order.
+    pub achievements: Vec<Achievement>,
+}
+
+impl GameProgress {
+    pub fn unlocked(&self) -> usize {
+        self.achievements
+            .iter()
+            .filter(|a| a.unlocked_at.is_some())
+            .count()
+    }
+
+    /// e.g. "5/23 unlocked, 40/300 points".
+    pub fn summary(&self) -> String {
+        let points: u32 = self.achievements.iter().map(|a| a.points).sum();
+        let earned: u32 = self
+            .achievements
+            .iter()
+            .filter(|a| a.unlocked_at.is_some())
+            .map(|a| a.points)
+            .sum();
+        format!(
+            "{}/{} unlocked, {}/{} points",
+            self.unlocked(),
+            self.achievements.len(),
+            earned,
+            points
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedProgress {
+    fetched_at: DateTime<Utc>,
+    progress: GameProgress,
+}
+
+// API_GetGameInfoAndUserProgress.php response, only the bits we need.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ApiGameProgress {
+    #[serde(rename = "ID")]
+    id: GameId,
+    title: String,
+    #[serde(default, deserialize_with = "map_or_empty_list")]
+    achievements: HashMap<String, ApiAchievement>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ApiAchievement {
+    #[serde(rename = "ID")]
+    id: u32,
+    title: String,
+    description: String,
+    points: u32,
+    badge_name: String,
+    display_order: i32,
+    date_earned: Option<String>,
+    date_earned_hardcore: Option<String>,
+}
+
+/// PHP encodes an empty map as `[]`.
+fn map_or_empty_list<'de, D>(d: D) -> std::result::Result<HashMap<String, ApiAchievement>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(d)? {
+        serde_json::Value::Array(_) => Ok(HashMap::new()),
+        v => serde_json::from_value(v).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Dates are UTC, formatted as `2023-01-31 20:15:00`.
+fn parse_date(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|d| Utc.from_utc_datetime(&d))
+}
+
+impl From<ApiGameProgress> for GameProgress {
+    fn from(api: ApiGameProgress) -> Self {
+        let mut achievements: Vec<_> = api.achievements.into_values().collect();
+        achievements.sort_by_key(|a| (a.display_order, a.id));
+        Self {
+            game_id: api.id,
+            title: api.title,
+            achievements: achievements
+                .into_iter()
+                .map(|a| {
+                    let hardcore = a.date_earned_hardcore.as_deref().and_then(parse_date);
+                    Achievement {
+                        id: a.id,
+                        title: a.title,
+                        description: a.description,
+                        points: a.points,
+                        badge_url: format!("{}/{}.png", BADGE_URL, a.badge_name),
+                        unlocked_at: hardcore
+                            .or_else(|| a.date_earned.as_deref().and_then(parse_date)),
+                        hardcore: hardcore.is_some(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Fetches the achievements of emulated games, cached on disk.
+#[derive(Debug, Clone)]
+pub struct RetroAchievements {
+    config: RetroAchievementsConfig,
+    cache_dir: PathBuf,
+}
+
+impl RetroAchievements {
+    pub fn new(config: RetroAchievementsConfig, cache_dir: PathBuf) -> Self {
+        Self { config, cache_dir }
+    }
+
+    fn cache_path(&self, game_id: GameId) -> PathBuf {
+        self.cache_dir.join(format!("{}.json", game_id))
+    }
+
+    fn read_cache(&self, game_id: GameId) -> Option<CachedProgress> {
+        let data = fs::read(self.cache_path(game_id)).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    fn write_cache(&self, progress: &GameProgress) -> Result<()> {
+        fs::create_dir_all(&self.cache_dir)?;
+        let cached = CachedProgress {
+            fetched_at: Utc::now(),
+            progress: progress.clone(),
+        };
+        fs::write(
+            self.cache_path(progress.game_id),
+            serde_json::to_vec(&cached)?,
+        )?;
+        Ok(())
+    }
+
+    fn fetch(&self, game_id: GameId) -> Result<GameProgress> {
+        let api: ApiGameProgress =
+            ureq::get(&format!("{}/API_GetGameInfoAndUserProgress.php", API_URL))
+                .query("z", &self.config.username)
+                .query("y", &self.config.api_key)
+                .query("u", &self.config.username)
+                .query("g", &game_id.to_string())
+                .call()?
+                .into_json()?;
+        Ok(api.into())
+    }
+
+    /// Achievements of the game, from the cache if it's recent enough.
+    /// A stale cache is used when the site can't be reached.
+    /// Blocks on the network, call it off the UI thread.
+    pub fn progress(&self, game_id: GameId) -> Result<GameProgress> {
+        let cached = self.read_cache(game_id);
+        if let Some(ref c) = cached {
+            if Utc::now() - c.fetched_at < chrono::Duration::minutes(CACHE_TTL_MINUTES) {
+                return Ok(c.progress.clone());
+            }
+        }
+        match self.fetch(game_id) {
+            Ok(progress) => {
+                if let Err(e) = self.write_cache(&progress) {
+                    warn!("failed to cache achievements of {}: {:?}", game_id, e);
+                }
+                Ok(progress)
+            }
+            Err(e) => cached.map(|c| c.progress).ok_or(anyhow!(
+                "failed to fetch achievements of {}: {}",
+                game_id,
+                e
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_game_progress() {
+        let body = r#"{
+            "ID": 1, "Title": "Sonic the Hedgehog", "NumAchievements": 2,
+            "Achievements": {
+                "9": {"ID": 9, "Title": "Speed", "Description": "Go fast", "Points": 10,
+                      "BadgeName": "250336", "DisplayOrder": 2,
+                      "DateEarned": "2023-01-31 20:15:00",
+                      "DateEarnedHardcore": "2023-01-31 20:15:00"},
+                "7": {"ID": 7, "Title": "Rings", "Description": "Get 100 rings", "Points": 5,
+                      "BadgeName": "250337", "DisplayOrder": 1}
+            }
+        }"#;
+        let progress: GameProgress = serde_json::from_str::<ApiGameProgress>(body)
+            .unwrap()
+            .into();
+        assert_eq!(progress.game_id, 1);
+        assert_eq!(
+            progress
+                .achievements
+                .iter()
+                .map(|a| a.id)
+                .collect::<Vec<_>>(),
+            vec![7, 9]
+        );
+        assert_eq!(progress.achievements[0].unlocked_at, None);
+        assert_eq!(
+            progress.achievements[1].unlocked_at,
+            Some(Utc.with_ymd_and_hms(2023, 1, 31, 20, 15, 0).unwrap())
+        );
+        assert!(progress.achievements[1].hardcore);
+        assert_eq!(progress.summary(), "1/2 unlocked, 10/15 points");
+    }
+
+    #[test]
+    fn parses_game_without_achievements() {
+        let body = r#"{"ID": 2, "Title": "Homebrew", "Achievements": []}"#;
+        let progress: GameProgress = serde_json::from_str::<ApiGameProgress>(body)
+            .unwrap()
+            .into();
+        assert!(progress.achievements.is_empty());
+    }
+}
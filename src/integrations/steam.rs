@@ -0,0 +1,838 @@
+use super::InstallState;
+use crate::{
+    models::{Esrb, GameMetadata, ImageSource, LocalizedText},
+    paths,
+    transfer::{TransferContext, TransferId, TransferKind, TransferManager},
+};
+use anyhow::{anyhow, bail, Result};
+use log::debug;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+// Steam games are installed either by `steamcmd`, where the progress is parsed
+// from its stdout, or by handing a `steam://` URL to the running Steam client.
+// The client does not report anything back to us, so instead the app manifest
+// (steamapps/appmanifest_${APP_ID}.acf) is polled for the download progress.
+// Jobs run through the transfer manager; pausing kills steamcmd, and resuming
+// runs `app_update` again which continues from the partial download.
+
+pub type AppId = u32;
+
+/// Prefix of `GameMetadata::install_source` for Steam games.
+pub const INSTALL_SOURCE_PREFIX: &str = "steam:";
+
+/// How often the app manifest is polled when using the Steam client.
+const MANIFEST_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Give up if the client never starts the job, e.g. the user dismissed the dialog.
+const CLIENT_START_TIMEOUT: Duration = Duration::from_secs(300);
+/// Give up once started if the manifest shows no progress for this long, e.g.
+/// the client got closed or the download got stuck.
+const CLIENT_STALL_TIMEOUT: Duration = Duration::from_secs(900);
+/// `StateFlags` value of a fully installed app.
+const STATE_FLAG_FULLY_INSTALLED: u32 = 4;
+
+/// Parse the app id out of an install source like `steam:440`.
+pub fn app_id_from_install_source(source: &str) -> Option<AppId> {
+    source.strip_prefix(INSTALL_SOURCE_PREFIX)?.parse().ok()
+}
+
+/// The app the Steam client reports as running, if any.
+pub fn running_app_id() -> Option<AppId> {
+    let home = std::env::var_os("HOME").map(PathBuf::from)?;
+    let contents = fs::read_to_string(home.join(".steam/registry.vdf")).ok()?;
+    read_manifest_value(&contents, "RunningAppID")?
+        .parse()
+        .ok()
+        .filter(|id| *id != 0)
+}
+
+/// Where the Steam client keeps its app manifests.
+pub fn default_steamapps_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        dirs.push(home.join(".steam/steam/steamapps"));
+        dirs.push(home.join(".local/share/Steam/steamapps"));
+    }
+    dirs
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledApp {
+    pub app_id: AppId,
+    pub name: String,
+}
+
+/// Apps with a manifest in the steamapps dirs. The default dirs usually
+/// point to the same place, so apps are only listed once.
+pub fn installed_apps(steamapps_dirs: &[PathBuf]) -> Vec<InstalledApp> {
+    let mut apps: Vec<InstalledApp> = vec![];
+    for dir in steamapps_dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !(name.starts_with("appmanifest_") && name.ends_with(".acf")) {
+                continue;
+            }
+            let Ok(contents) = fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            let app = read_manifest_value(&contents, "appid")
+                .and_then(|id| id.parse().ok())
+                .zip(read_manifest_value(&contents, "name"));
+            if let Some((app_id, name)) = app {
+                if !apps.iter().any(|a| a.app_id == app_id) {
+                    apps.push(InstalledApp { app_id, name });
+                }
+            }
+        }
+    }
+    apps
+}
+
+/// Where an installed app's files are, `steamapps/common/${installdir}`.
+pub fn install_dir(steamapps_dirs: &[PathBuf], app_id: AppId) -> Option<PathBuf> {
+    steamapps_dirs.iter().find_map(|dir| {
+        let manifest = dir.join(format!("appmanifest_{}.acf", app_id));
+        let contents = fs::read_to_string(manifest).ok()?;
+        let name = read_manifest_value(&contents, "installdir")?;
+        Some(dir.join("common").join(name)).filter(|d| d.is_dir())
+    })
+}
+
+/// Metadata from the Steam store page of an app.
+#[derive(Debug, Clone, Default)]
+pub struct StoreDetails {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub genres: Vec<String>,
+    pub developers: Vec<String>,
+    pub publishers: Vec<String>,
+    pub release_date: Option<chrono::DateTime<chrono::Utc>>,
+    pub header_image: Option<String>,
+    pub background: Option<String>,
+    pub esrb: Option<Esrb>,
+    pub pegi: Option<u8>,
+}
+
+#[derive(Deserialize)]
+struct StoreResponse {
+    success: bool,
+    data: Option<StoreData>,
+}
+
+#[derive(Deserialize)]
+struct StoreData {
+    name: Option<String>,
+    short_description: Option<String>,
+    #[serde(default)]
+    genres: Vec<StoreGenre>,
+    #[serde(default)]
+    developers: Vec<String>,
+    #[serde(default)]
+    publishers: Vec<String>,
+    release_date: Option<StoreReleaseDate>,
+    header_image: Option<String>,
+    background: Option<String>,
+    ratings: Option<StoreRatings>,
+}
+
+/// By board, there are more than these two.
+#[derive(Deserialize)]
+struct StoreRatings {
+    esrb: Option<StoreRating>,
+    pegi: Option<StoreRating>,
+}
+
+#[derive(Deserialize)]
+struct StoreRating {
+    rating: String,
+}
+
+#[derive(Deserialize)]
+struct StoreGenre {
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct StoreReleaseDate {
+    date: String,
+}
+
+fn parse_store_details(app_id: AppId, body: &str) -> Result<StoreDetails> {
+    let mut res: HashMap<String, StoreResponse> = serde_json::from_str(body)?;
+    let data = match res.remove(&app_id.to_string()) {
+        Some(StoreResponse {
+            success: true,
+            data: Some(data),
+        }) => data,
+        _ => bail!("app {} is not on the store", app_id),
+    };
+    let (esrb, pegi) = data.ratings.map_or((None, None), |r| (r.esrb, r.pegi));
+    Ok(StoreDetails {
+        name: data.name.filter(|n| !n.is_empty()),
+        description: data.short_description.filter(|d| !d.is_empty()),
+        genres: data.genres.into_iter().map(|g| g.description).collect(),
+        developers: data.developers,
+        publishers: data.publishers,
+        // e.g. "10 Oct, 2007", missing for unreleased games.
+        release_date: data
+            .release_date
+            .and_then(|r| chrono::NaiveDate::parse_from_str(&r.date, "%d %b, %Y").ok())
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|d| d.and_utc()),
+        header_image: data.header_image,
+        background: data.background,
+        esrb: esrb.and_then(|r| Esrb::parse(&r.rating)),
+        pegi: pegi.and_then(|r| r.rating.parse().ok()),
+    })
+}
+
+/// An app found by name on the store.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StoreMatch {
+    pub id: AppId,
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+struct StoreSearch {
+    #[serde(default)]
+    items: Vec<StoreMatch>,
+}
+
+fn parse_store_search(body: &str) -> Result<Vec<StoreMatch>> {
+    Ok(serde_json::from_str::<StoreSearch>(body)?.items)
+}
+
+/// Look apps up on the store by name, the best matches first. Blocks on the
+/// network.
+pub fn search_store(term: &str) -> Result<Vec<StoreMatch>> {
+    let body = ureq::get("https://store.steampowered.com/api/storesearch/")
+        .query("term", term)
+        .query("l", "english")
+        .query("cc", "US")
+        .call()?
+        .into_string()?;
+    parse_store_search(&body)
+}
+
+/// The store's name for the language of a locale, English for those it
+/// doesn't have.
+pub fn store_language(locale: &str) -> &'static str {
+    match locale.split(['-', '_']).next().unwrap_or_default() {
+        "fr" => "french",
+        "de" => "german",
+        "es" => "spanish",
+        "it" => "italian",
+        "pt" => "portuguese",
+        "ja" => "japanese",
+        _ => "english",
+    }
+}
+
+/// Look the app up on the store, in the language given by `store_language`.
+/// Blocks on the network.
+pub fn store_details(app_id: AppId, language: &str) -> Result<StoreDetails> {
+    let body = ureq::get("https://store.steampowered.com/api/appdetails")
+        .query("appids", &app_id.to_string())
+        .query("l", language)
+        .call()?
+        .into_string()?;
+    parse_store_details(app_id, &body)
+}
+
+/// The store page in English, and in the language of the locale unless
+/// that's English or the store doesn't have it. Blocks on the network.
+pub fn store_details_in(
+    app_id: AppId,
+    locale: &str,
+) -> Result<(StoreDetails, Option<StoreDetails>)> {
+    let details = store_details(app_id, "english")?;
+    let localized = match store_language(locale) {
+        "english" => None,
+        // The English ones are shown instead.
+        language => store_details(app_id, language)
+            .map_err(|e| debug!("no {} details for {}: {}", language, app_id, e))
+            .ok(),
+    };
+    Ok((details, localized))
+}
+
+/// Fill in the metadata of a game, only the missing bits unless forced. The
+/// title and description in the language of the locale are kept apart.
+pub fn apply_details(
+    game: &mut GameMetadata,
+    details: StoreDetails,
+    localized: Option<StoreDetails>,
+    locale: &str,
+    force: bool,
+) {
+    if force || game.desc.is_none() {
+        game.desc = details.description;
+    }
+    if force || game.genres.is_empty() {
+        game.genres = details.genres.iter().map(|g| g.to_lowercase()).collect();
+    }
+    if force || game.developers.is_empty() {
+        game.developers = details.developers;
+    }
+    if force || game.publishers.is_empty() {
+        game.publishers = details.publishers;
+    }
+    if force || game.relase_date.is_none() {
+        game.relase_date = details.release_date;
+    }
+    if force || game.cover_art.is_none() {
+        game.cover_art = details.header_image.map(ImageSource::Url);
+    }
+    if force || game.bg_art.is_none() {
+        game.bg_art = details.background.map(ImageSource::Url);
+    }
+    if force || game.esrb.is_none() {
+        game.esrb = details.esrb;
+    }
+    if force || game.pegi.is_none() {
+        game.pegi = details.pegi;
+    }
+    if let Some(localized) = localized.filter(|_| force || !game.localized.contains_key(locale)) {
+        let text = LocalizedText {
+            title: localized.name,
+            desc: localized.description,
+        };
+        game.localized.insert(locale.to_owned(), text);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SteamBackend {
+    /// Use `steamcmd`, logging in as the given user.
+    SteamCmd { binary: PathBuf, username: String },
+    /// Use the `steam://` URL protocol of the Steam client.
+    ClientUrl,
+}
+
+impl Default for SteamBackend {
+    fn default() -> Self {
+        SteamBackend::SteamCmd {
+            binary: default_steamcmd(),
+            username: default_username(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    #[default]
+    Steamcmd,
+    Client,
+}
+
+fn default_steamcmd() -> PathBuf {
+    PathBuf::from("steamcmd")
+}
+
+fn default_username() -> String {
+    "anonymous".to_owned()
+}
+
+/// How games are installed, steamcmd logged in anonymously without.
+///
+/// ```yaml
+/// # Or client, to have the running Steam client do it.
+/// backend: steamcmd
+/// steamcmd: /usr/games/steamcmd
+/// username: anonymous
+/// # Libraries on other drives, on top of the default ones.
+/// steamapps_dirs:
+///   - /mnt/games/SteamLibrary/steamapps
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct SteamConfig {
+    #[serde(default)]
+    pub backend: BackendKind,
+    #[serde(default = "default_steamcmd")]
+    pub steamcmd: PathBuf,
+    #[serde(default = "default_username")]
+    pub username: String,
+    #[serde(default)]
+    pub steamapps_dirs: Vec<PathBuf>,
+}
+
+impl Default for SteamConfig {
+    fn default() -> Self {
+        Self {
+            backend: BackendKind::default(),
+            steamcmd: default_steamcmd(),
+            username: default_username(),
+            steamapps_dirs: vec![],
+        }
+    }
+}
+
+impl SteamConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::config_dir().join("steam.yaml")
+    }
+
+    pub fn backend(&self) -> SteamBackend {
+        match self.backend {
+            BackendKind::Steamcmd => SteamBackend::SteamCmd {
+                binary: self.steamcmd.clone(),
+                username: self.username.clone(),
+            },
+            BackendKind::Client => SteamBackend::ClientUrl,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SteamInstaller {
+    backend: SteamBackend,
+    /// All the steamapps directories to look for app manifests.
+    steamapps_dirs: Vec<PathBuf>,
+    transfers: TransferManager,
+}
+
+impl SteamInstaller {
+    pub fn new(backend: SteamBackend, transfers: TransferManager) -> Self {
+        Self {
+            backend,
+            steamapps_dirs: default_steamapps_dirs(),
+            transfers,
+        }
+    }
+
+    pub fn with_steamapps_dir(mut self, dir: PathBuf) -> Self {
+        self.steamapps_dirs.push(dir);
+        self
+    }
+
+    /// With the config's, see `SteamConfig`.
+    pub fn from_config(config: &SteamConfig, transfers: TransferManager) -> Self {
+        config
+            .steamapps_dirs
+            .iter()
+            .fold(Self::new(config.backend(), transfers), |s, dir| {
+                s.with_steamapps_dir(dir.clone())
+            })
+    }
+
+    pub fn steamapps_dirs(&self) -> &[PathBuf] {
+        &self.steamapps_dirs
+    }
+
+    fn manifest_path(&self, app_id: AppId) -> Option<PathBuf> {
+        self.steamapps_dirs
+            .iter()
+            .map(|d| d.join(format!("appmanifest_{}.acf", app_id)))
+            .find(|p| p.exists())
+    }
+
+    pub fn is_installed(&self, app_id: AppId) -> bool {
+        self.manifest_path(app_id)
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|c| read_manifest_value(&c, "StateFlags"))
+            .and_then(|v| v.parse::<u32>().ok())
+            .is_some_and(|f| f & STATE_FLAG_FULLY_INSTALLED != 0)
+    }
+
+    /// Queue an install of the game.
+    pub fn install(&self, uuid: &str, app_id: AppId) -> TransferId {
+        self.enqueue(uuid, app_id, TransferKind::Install)
+    }
+
+    /// Queue an update (and validation) of an installed game.
+    pub fn update(&self, uuid: &str, app_id: AppId) -> TransferId {
+        self.enqueue(uuid, app_id, TransferKind::Update)
+    }
+
+    /// Queue an uninstall of the game.
+    pub fn uninstall(&self, uuid: &str, app_id: AppId) -> TransferId {
+        self.enqueue(uuid, app_id, TransferKind::Uninstall)
+    }
+
+    fn enqueue(&self, uuid: &str, app_id: AppId, kind: TransferKind) -> TransferId {
+        let this = self.clone();
+        self.transfers.enqueue(
+            kind,
+            uuid,
+            Box::new(move |ctx| match (&this.backend, kind) {
+                (SteamBackend::SteamCmd { .. }, TransferKind::Uninstall) => {
+                    this.steamcmd_uninstall(app_id)
+                }
+                (SteamBackend::SteamCmd { .. }, _) => this.steamcmd_install(ctx, app_id),
+                (SteamBackend::ClientUrl, TransferKind::Uninstall) => {
+                    this.client_uninstall(ctx, app_id)
+                }
+                (SteamBackend::ClientUrl, _) => this.client_install(ctx, app_id),
+            }),
+        )
+    }
+
+    fn steamcmd(&self, args: &[String]) -> Result<Command> {
+        match self.backend {
+            SteamBackend::SteamCmd {
+                ref binary,
+                ref username,
+            } => {
+                let mut cmd = Command::new(binary);
+                cmd.arg("+login").arg(username).args(args).arg("+quit");
+                Ok(cmd)
+            }
+            SteamBackend::ClientUrl => bail!("not using steamcmd"),
+        }
+    }
+
+    fn steamcmd_install(&self, ctx: &TransferContext, app_id: AppId) -> Result<()> {
+        let mut child = self
+            .steamcmd(&[
+                "+app_update".to_owned(),
+                app_id.to_string(),
+                "validate".to_owned(),
+            ])?
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or(anyhow!("no stdout from steamcmd"))?;
+
+        let mut last_error = None;
+        // Progress lines can be terminated by \r.
+        for chunk in BufReader::new(stdout).split(b'\n') {
+            let chunk = chunk?;
+            for line in String::from_utf8_lossy(&chunk).split('\r') {
+                debug!("steamcmd: {}", line);
+                match parse_steamcmd_line(line) {
+                    Some(InstallState::Failed(e)) => last_error = Some(e),
+                    Some(state) => report(ctx, &state),
+                    None => {}
+                }
+            }
+            if ctx.should_stop() {
+                return stop(child);
+            }
+        }
+
+        let status = child.wait()?;
+        if let Some(e) = last_error {
+            bail!(e);
+        }
+        if !status.success() {
+            bail!("steamcmd exited with {}", status);
+        }
+        Ok(())
+    }
+
+    fn steamcmd_uninstall(&self, app_id: AppId) -> Result<()> {
+        let status = self
+            .steamcmd(&["+app_uninstall".to_owned(), app_id.to_string()])?
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        if !status.success() {
+            bail!("steamcmd exited with {}", status);
+        }
+        Ok(())
+    }
+
+    fn open_url(url: &str) -> Result<()> {
+        let status = Command::new("xdg-open").arg(url).status()?;
+        if !status.success() {
+            bail!("failed to open {}", url);
+        }
+        Ok(())
+    }
+
+    fn client_install(&self, ctx: &TransferContext, app_id: AppId) -> Result<()> {
+        Self::open_url(&format!("steam://install/{}", app_id))?;
+        let started = Instant::now();
+        // Since the manifest last changed.
+        let (mut last, mut progressed) = (None, Instant::now());
+        // The client keeps going on its own, we only stop tracking it.
+        while !ctx.should_stop() {
+            thread::sleep(MANIFEST_POLL_INTERVAL);
+            let Some(path) = self.manifest_path(app_id) else {
+                if started.elapsed() > CLIENT_START_TIMEOUT {
+                    bail!("timed out waiting for steam to start the install");
+                }
+                continue;
+            };
+            let contents = fs::read_to_string(path)?;
+            let state = parse_manifest_progress(&contents);
+            if state != last {
+                (last, progressed) = (state.clone(), Instant::now());
+            } else if progressed.elapsed() > CLIENT_STALL_TIMEOUT {
+                bail!(
+                    "steam made no progress installing for {:?}",
+                    CLIENT_STALL_TIMEOUT
+                );
+            }
+            match state {
+                Some(InstallState::Installed) => return Ok(()),
+                Some(state) => report(ctx, &state),
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn client_uninstall(&self, ctx: &TransferContext, app_id: AppId) -> Result<()> {
+        Self::open_url(&format!("steam://uninstall/{}", app_id))?;
+        let started = Instant::now();
+        while self.manifest_path(app_id).is_some() && !ctx.should_stop() {
+            if started.elapsed() > CLIENT_START_TIMEOUT {
+                bail!("timed out waiting for steam to uninstall");
+            }
+            thread::sleep(MANIFEST_POLL_INTERVAL);
+        }
+        Ok(())
+    }
+}
+
+fn report(ctx: &TransferContext, state: &InstallState) {
+    match *state {
+        InstallState::Downloading {
+            progress,
+            downloaded,
+            total,
+        } => {
+            ctx.set_phase(None);
+            if total > 0 {
+                ctx.report(downloaded, total);
+            } else {
+                ctx.report_progress(progress);
+            }
+        }
+        InstallState::Verifying { progress } => {
+            ctx.set_phase(Some("Verifying"));
+            ctx.report_progress(progress);
+        }
+        _ => {}
+    }
+}
+
+/// Kill steamcmd when the transfer is paused or cancelled.
+fn stop(mut child: Child) -> Result<()> {
+    child.kill()?;
+    child.wait()?;
+    Ok(())
+}
+
+/// Parse a line of steamcmd output, for example:
+/// ` Update state (0x61) downloading, progress: 43.38 (4572826089 / 10541436510)`
+fn parse_steamcmd_line(line: &str) -> Option<InstallState> {
+    let line = line.trim();
+    if let Some(e) = line.strip_prefix("Error!") {
+        return Some(InstallState::Failed(e.trim().to_owned()));
+    }
+    if line.starts_with("Success!") {
+        return Some(InstallState::Installed);
+    }
+
+    let rest = line.strip_prefix("Update state (")?;
+    let (_, rest) = rest.split_once(')')?;
+    let (phase, rest) = rest.split_once(',')?;
+    let rest = rest.trim().strip_prefix("progress:")?.trim();
+    let (percent, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+    let progress = (percent.parse::<f32>().ok()? / 100.0).clamp(0.0, 1.0);
+
+    match phase.trim() {
+        "verifying install" | "verifying update" => Some(InstallState::Verifying { progress }),
+        // Preallocating, downloading, committing etc. all count towards the download.
+        _ => {
+            let (downloaded, total) = rest
+                .trim()
+                .strip_prefix('(')
+                .and_then(|r| r.strip_suffix(')'))
+                .and_then(|r| r.split_once('/'))
+                .and_then(|(d, t)| Some((d.trim().parse().ok()?, t.trim().parse().ok()?)))
+                .unwrap_or((0, 0));
+            Some(InstallState::Downloading {
+                progress,
+                downloaded,
+                total,
+            })
+        }
+    }
+}
+
+/// Read a value out of a vdf/acf file, the format is a flat-ish list of
+/// `"key"\t\t"value"` lines.
+fn read_manifest_value(contents: &str, key: &str) -> Option<String> {
+    let quoted_key = format!("\"{}\"", key);
+    contents.lines().find_map(|l| {
+        let rest = l.trim().strip_prefix(&quoted_key)?;
+        Some(rest.trim().trim_matches('"').to_owned())
+    })
+}
+
+fn parse_manifest_progress(contents: &str) -> Option<InstallState> {
+    let flags: u32 = read_manifest_value(contents, "StateFlags")?.parse().ok()?;
+    let total: u64 = read_manifest_value(contents, "BytesToDownload")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let downloaded: u64 = read_manifest_value(contents, "BytesDownloaded")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    if flags == STATE_FLAG_FULLY_INSTALLED && downloaded >= total {
+        return Some(InstallState::Installed);
+    }
+    let progress = if total == 0 {
+        0.0
+    } else {
+        downloaded as f32 / total as f32
+    };
+    Some(InstallState::Downloading {
+        progress,
+        downloaded,
+        total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_config() {
+        let config: SteamConfig =
+            serde_yaml::from_str("backend: client\nsteamapps_dirs: [/mnt/steamapps]").unwrap();
+        assert!(matches!(config.backend(), SteamBackend::ClientUrl));
+        let (tx, _) = std::sync::mpsc::channel();
+        let installer = SteamInstaller::from_config(&config, TransferManager::new(tx));
+        assert_eq!(
+            installer.steamapps_dirs().last(),
+            Some(&PathBuf::from("/mnt/steamapps"))
+        );
+        assert!(matches!(
+            SteamConfig::default().backend(),
+            SteamBackend::SteamCmd { username, .. } if username == "anonymous"
+        ));
+    }
+
+    #[test]
+    fn parses_install_source() {
+        assert_eq!(app_id_from_install_source("steam:440"), Some(440));
+        assert_eq!(app_id_from_install_source("gog:440"), None);
+        assert_eq!(app_id_from_install_source("steam:abc"), None);
+    }
+
+    #[test]
+    fn parses_steamcmd_download_progress() {
+        let state = parse_steamcmd_line(
+            " Update state (0x61) downloading, progress: 43.38 (4572826089 / 10541436510)",
+        );
+        if let Some(InstallState::Downloading {
+            progress,
+            downloaded,
+            total,
+        }) = state
+        {
+            assert!((progress - 0.4338).abs() < 1e-6);
+            assert_eq!(downloaded, 4572826089);
+            assert_eq!(total, 10541436510);
+        } else {
+            panic!("unexpected state {:?}", state)
+        }
+    }
+
+    #[test]
+    fn parses_steamcmd_other_lines() {
+        assert_eq!(
+            parse_steamcmd_line(" Update state (0x5) verifying install, progress: 50.00 (1 / 2)"),
+            Some(InstallState::Verifying { progress: 0.5 })
+        );
+        assert_eq!(
+            parse_steamcmd_line("Success! App '440' fully installed."),
+            Some(InstallState::Installed)
+        );
+        assert_eq!(
+            parse_steamcmd_line("Error! App '440' state is 0x202 after update job."),
+            Some(InstallState::Failed(
+                "App '440' state is 0x202 after update job.".to_owned()
+            ))
+        );
+        assert_eq!(parse_steamcmd_line("Loading Steam API...OK"), None);
+    }
+
+    #[test]
+    fn parses_store_details() {
+        let body = r#"{"440": {"success": true, "data": {
+            "name": "Team Fortress 2",
+            "short_description": "Nine distinct classes.",
+            "genres": [{"id": "1", "description": "Action"}],
+            "developers": ["Valve"], "publishers": ["Valve"],
+            "release_date": {"coming_soon": false, "date": "10 Oct, 2007"},
+            "header_image": "https://example.com/header.jpg",
+            "ratings": {"esrb": {"rating": "m"}, "pegi": {"rating": "18"}}
+        }}}"#;
+        let details = parse_store_details(440, body).unwrap();
+        assert_eq!(details.name.as_deref(), Some("Team Fortress 2"));
+        assert_eq!(details.genres, vec!["Action"]);
+        assert_eq!((details.esrb, details.pegi), (Some(Esrb::Mature), Some(18)));
+        assert_eq!(
+            details.release_date.map(|d| d.date_naive()),
+            chrono::NaiveDate::from_ymd_opt(2007, 10, 10)
+        );
+        assert!(parse_store_details(1, r#"{"1": {"success": false}}"#).is_err());
+        assert_eq!(store_language("fr-CA"), "french");
+        assert_eq!(store_language("en-US"), "english");
+
+        let found = parse_store_search(
+            r#"{"total": 1, "items": [{"type": "app", "name": "Portal", "id": 400}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            found,
+            [StoreMatch {
+                id: 400,
+                name: "Portal".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_manifest_progress() {
+        let manifest = r#""AppState"
+{
+	"appid"		"440"
+	"StateFlags"		"1026"
+	"BytesToDownload"		"200"
+	"BytesDownloaded"		"50"
+}"#;
+        assert_eq!(
+            parse_manifest_progress(manifest),
+            Some(InstallState::Downloading {
+                progress: 0.25,
+                downloaded: 50,
+                total: 200,
+            })
+        );
+        let installed = manifest.replace("1026", "4").replace("\"50\"", "\"200\"");
+        assert_eq!(
+            parse_manifest_progress(&installed),
+            Some(InstallState::Installed)
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use crate::models::{GameMetadata, ImageSource};
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::warn;
+use std::{
+    env, fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+// Games of the library as non-Steam shortcuts, so the Steam client lists them
+// and Steam Input profiles can be set up for them. They run `anubis --launch`,
+// which works for every kind of game. Shortcuts live in the binary VDF file
+// `userdata/${USER}/config/shortcuts.vdf`, one per Steam account, and their
+// art next to it in `grid`. Steam rewrites the file when it exits, so it has
+// to be closed while exporting.
+
+const MAP: u8 = 0x00;
+const STRING: u8 = 0x01;
+const INT: u8 = 0x02;
+const UINT64: u8 = 0x07;
+const END: u8 = 0x08;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Map(Vec<(String, Value)>),
+    String(String),
+    Int(u32),
+    /// Not written by us, kept as read.
+    Uint64(u64),
+}
+
+fn read_str(data: &[u8], pos: &mut usize) -> Result<String> {
+    let len = data[*pos..]
+        .iter()
+        .position(|b| *b == 0)
+        .ok_or(anyhow!("unterminated string at {}", pos))?;
+    let s = String::from_utf8_lossy(&data[*pos..*pos + len]).into_owned();
+    *pos += len + 1;
+    Ok(s)
+}
+
+fn read_bytes<const N: usize>(data: &[u8], pos: &mut usize) -> Result<[u8; N]> {
+    let bytes = data
+        .get(*pos..*pos + N)
+        .ok_or(anyhow!("truncated at {}", pos))?;
+    *pos += N;
+    Ok(bytes.try_into()?)
+}
+
+fn read_map(data: &[u8], pos: &mut usize) -> Result<Vec<(String, Value)>> {
+    let mut entries = vec![];
+    loop {
+        let kind = *data.get(*pos).ok_or(anyhow!("truncated at {}", pos))?;
+        *pos += 1;
+        if kind == END {
+            return Ok(entries);
+        }
+        let key = read_str(data, pos)?;
+        let value = match kind {
+            MAP => Value::Map(read_map(data, pos)?),
+            STRING => Value::String(read_str(data, pos)?),
+            INT => Value::Int(u32::from_le_bytes(read_bytes(data, pos)?)),
+            UINT64 => Value::Uint64(u64::from_le_bytes(read_bytes(data, pos)?)),
+            _ => bail!("unknown type {:#x} at {}", kind, pos),
+        };
+        entries.push((key, value));
+    }
+}
+
+fn write_map(out: &mut Vec<u8>, entries: &[(String, Value)]) {
+    for (key, value) in entries {
+        let kind = match value {
+            Value::Map(_) => MAP,
+            Value::String(_) => STRING,
+            Value::Int(_) => INT,
+            Value::Uint64(_) => UINT64,
+        };
+        out.push(kind);
+        out.extend(key.as_bytes());
+        out.push(0);
+        match value {
+            Value::Map(entries) => write_map(out, entries),
+            Value::String(s) => {
+                out.extend(s.as_bytes());
+                out.push(0);
+            }
+            Value::Int(i) => out.extend(i.to_le_bytes()),
+            Value::Uint64(i) => out.extend(i.to_le_bytes()),
+        }
+    }
+    out.push(END);
+}
+
+/// The shortcut ID Steam gives, also naming the art.
+fn app_id(exe: &str, name: &str) -> u32 {
+    crc32fast::hash(format!("{}{}", exe, name).as_bytes()) | 0x8000_0000
+}
+
+fn launch_options(uuid: &str) -> String {
+    format!("--launch {}", uuid)
+}
+
+fn shortcut(exe: &str, start_dir: &str, game: &GameMetadata) -> Vec<(String, Value)> {
+    let s = |v: &str| Value::String(v.to_owned());
+    let tags = game
+        .tags
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (i.to_string(), s(t)))
+        .collect();
+    [
+        ("appid", Value::Int(app_id(exe, &game.title))),
+        ("AppName", s(&game.title)),
+        ("Exe", s(exe)),
+        ("StartDir", s(start_dir)),
+        ("icon", s("")),
+        ("ShortcutPath", s("")),
+        ("LaunchOptions", s(&launch_options(&game.uuid))),
+        ("IsHidden", Value::Int(0)),
+        ("AllowDesktopConfig", Value::Int(1)),
+        ("AllowOverlay", Value::Int(1)),
+        ("OpenVR", Value::Int(0)),
+        ("Devkit", Value::Int(0)),
+        ("DevkitGameID", s("")),
+        ("DevkitOverrideAppID", Value::Int(0)),
+        ("LastPlayTime", Value::Int(0)),
+        ("FlatpakAppID", s("")),
+        ("tags", Value::Map(tags)),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_owned(), v))
+    .collect()
+}
+
+/// The shortcuts file with the games in, replacing the ones exported before.
+fn merge(existing: &[u8], exe: &str, start_dir: &str, games: &[GameMetadata]) -> Result<Vec<u8>> {
+    let mut root = if existing.is_empty() {
+        vec![]
+    } else {
+        read_map(existing, &mut 0)?
+    };
+    if root.is_empty() {
+        root.push(("shortcuts".to_owned(), Value::Map(vec![])));
+    }
+    let Some((_, Value::Map(shortcuts))) = root.iter_mut().find(|(k, _)| k == "shortcuts") else {
+        bail!("no shortcuts in the file");
+    };
+    let ours: Vec<String> = games.iter().map(|g| launch_options(&g.uuid)).collect();
+    shortcuts.retain(|(_, shortcut)| {
+        let Value::Map(fields) = shortcut else {
+            return true;
+        };
+        !fields.iter().any(|(k, v)| {
+            k.eq_ignore_ascii_case("LaunchOptions")
+                && matches!(v, Value::String(o) if ours.contains(o))
+        })
+    });
+    shortcuts.extend(
+        games
+            .iter()
+            .map(|g| (String::new(), Value::Map(shortcut(exe, start_dir, g)))),
+    );
+    // Keyed by their index.
+    for (i, (key, _)) in shortcuts.iter_mut().enumerate() {
+        *key = i.to_string();
+    }
+    let mut out = vec![];
+    write_map(&mut out, &root);
+    Ok(out)
+}
+
+/// The config dirs of the Steam accounts on this device.
+pub fn default_user_config_dirs() -> Vec<PathBuf> {
+    let Some(home) = env::var_os("HOME").map(PathBuf::from) else {
+        return vec![];
+    };
+    let mut dirs: Vec<PathBuf> = vec![];
+    for steam in [".steam/steam", ".local/share/Steam"] {
+        let Ok(users) = fs::read_dir(home.join(steam).join("userdata")) else {
+            continue;
+        };
+        for user in users.flatten() {
+            // The two are usually the same.
+            let Ok(dir) = user.path().join("config").canonicalize() else {
+                continue;
+            };
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+    dirs
+}
+
+fn art_bytes(source: &ImageSource) -> Result<Vec<u8>> {
+    Ok(match source {
+        ImageSource::FilePath(path) => fs::read(path)?,
+        ImageSource::Base64(data) => STANDARD.decode(data)?,
+        ImageSource::Url(url) => {
+            let mut data = vec![];
+            ureq::get(url)
+                .call()?
+                .into_reader()
+                .read_to_end(&mut data)?;
+            data
+        }
+    })
+}
+
+/// Steam goes by the extension.
+fn image_extension(data: &[u8]) -> &'static str {
+    if data.starts_with(b"\x89PNG") {
+        "png"
+    } else {
+        "jpg"
+    }
+}
+
+fn write_art(grid: &Path, app_id: u32, game: &GameMetadata) -> Result<()> {
+    for (source, suffix) in [(&game.cover_art, "p"), (&game.bg_art, "_hero")] {
+        let Some(source) = source else {
+            continue;
+        };
+        let data = art_bytes(source)?;
+        fs::create_dir_all(grid)?;
+        let name = format!("{}{}.{}", app_id, suffix, image_extension(&data));
+        fs::write(grid.join(name), data)?;
+    }
+    Ok(())
+}
+
+/// Whether the Steam client of `home` is running, by the PID it leaves.
+fn steam_running(home: &Path) -> bool {
+    fs::read_to_string(home.join(".steam/steam.pid"))
+        .ok()
+        .and_then(|pid| pid.trim().parse::<u32>().ok())
+        .is_some_and(|pid| Path::new(&format!("/proc/{}", pid)).exists())
+}
+
+/// Add the games to the shortcuts of a Steam account, with their art. Not
+/// while Steam runs, it would put its own back as it exits.
+pub fn export(config_dir: &Path, games: &[GameMetadata]) -> Result<()> {
+    if let Some(home) = env::var_os("HOME") {
+        if steam_running(Path::new(&home)) {
+            bail!("Steam is running, close it first");
+        }
+    }
+    let exe = match env::var_os("APPIMAGE") {
+        Some(path) => PathBuf::from(path),
+        None => env::current_exe()?,
+    };
+    let start_dir = exe.parent().unwrap_or(Path::new("/"));
+    // Quoted like Steam does when adding one.
+    let exe = format!("\"{}\"", exe.display());
+    let start_dir = format!("\"{}\"", start_dir.display());
+
+    let path = config_dir.join("shortcuts.vdf");
+    let existing = match fs::read(&path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(e) => return Err(e.into()),
+    };
+    // Whole or not at all, Steam loses every shortcut to a half written one.
+    let part = path.with_extension("part");
+    fs::write(&part, merge(&existing, &exe, &start_dir, games)?)?;
+    fs::rename(&part, &path)?;
+    for game in games {
+        let app_id = app_id(&exe, &game.title);
+        if let Err(e) = write_art(&config_dir.join("grid"), app_id, game) {
+            warn!("failed to export the art of {}: {:?}", game.title, e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_the_exported_shortcuts() {
+        let game = |uuid: &str, title: &str| GameMetadata {
+            uuid: uuid.to_owned(),
+            title: title.to_owned(),
+            tags: vec!["rpg".to_owned()],
+            ..Default::default()
+        };
+        // One added in Steam by hand.
+        let mut existing = vec![];
+        write_map(
+            &mut existing,
+            &[(
+                "shortcuts".to_owned(),
+                Value::Map(vec![(
+                    "0".to_owned(),
+                    Value::Map(vec![
+                        ("appid".to_owned(), Value::Int(1)),
+                        ("AppName".to_owned(), Value::String("Firefox".to_owned())),
+                        ("LastPlayTime".to_owned(), Value::Uint64(7)),
+                    ]),
+                )]),
+            )],
+        );
+
+        let first = merge(
+            &existing,
+            "\"/bin/anubis\"",
+            "\"/bin\"",
+            &[game("a", "Old")],
+        )
+        .unwrap();
+        let again = merge(
+            &first,
+            "\"/bin/anubis\"",
+            "\"/bin\"",
+            &[game("a", "New"), game("b", "Other")],
+        )
+        .unwrap();
+
+        let root = read_map(&again, &mut 0).unwrap();
+        let Value::Map(shortcuts) = &root[0].1 else {
+            panic!("not a map");
+        };
+        let names: Vec<_> = shortcuts
+            .iter()
+            .map(|(key, shortcut)| {
+                let Value::Map(fields) = shortcut else {
+                    panic!("not a map");
+                };
+                let name = fields.iter().find(|(k, _)| k == "AppName").unwrap();
+                (key.as_str(), name.1.clone())
+            })
+            .collect();
+        let s = |v: &str| Value::String(v.to_owned());
+        assert_eq!(
+            names,
+            [("0", s("Firefox")), ("1", s("New")), ("2", s("Other"))]
+        );
+        let Value::Map(new) = &shortcuts[1].1 else {
+            panic!("not a map");
+        };
+        assert!(new.contains(&("LaunchOptions".to_owned(), s("--launch a"))));
+        assert!(new.contains(&(
+            "appid".to_owned(),
+            Value::Int(app_id("\"/bin/anubis\"", "New"))
+        )));
+        assert!(app_id("\"/bin/anubis\"", "New") >= 0x8000_0000);
+
+        let home = env::temp_dir().join(format!("anubis-steam-{}", std::process::id()));
+        fs::create_dir_all(home.join(".steam")).unwrap();
+        assert!(!steam_running(&home));
+        // Steam left it behind when it crashed.
+        fs::write(home.join(".steam/steam.pid"), format!("{}\n", u32::MAX)).unwrap();
+        assert!(!steam_running(&home));
+        fs::write(
+            home.join(".steam/steam.pid"),
+            std::process::id().to_string(),
+        )
+        .unwrap();
+        assert!(steam_running(&home));
+        fs::remove_dir_all(&home).unwrap();
+    }
+}
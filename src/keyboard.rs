@@ -0,0 +1,94 @@
+// The on-screen keyboard, for typing with the gamepad, e.g. a game's notes.
+// Its keys are buttons named `KEY_${CHAR}` for the characters, given in lower
+// case and typed in upper case after shift, and `KEY_SHIFT`, `KEY_SPACE`,
+// `KEY_ENTER`, `KEY_DEL` and `KEY_OK`.
+
+/// Longest text typed, in characters.
+pub const MAX_CHARS: usize = 2000;
+
+/// What the text is typed for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Typing {
+    /// The notes of the game with this UUID.
+    Notes(String),
+    /// Added to the selected games, see `library::bulk`.
+    Tag,
+    /// Where the selected games are moved.
+    Collection,
+    /// Of the MQTT broker, see `mqtt`.
+    MqttBroker,
+    MqttUsername,
+    MqttPassword,
+}
+
+#[derive(Debug, Clone)]
+pub struct Keyboard {
+    pub typing: Typing,
+    text: String,
+    /// For the next character only.
+    shift: bool,
+}
+
+impl Keyboard {
+    pub fn new(typing: Typing, text: &str) -> Self {
+        Self {
+            typing,
+            text: text.to_owned(),
+            shift: false,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn shift(&self) -> bool {
+        self.shift
+    }
+
+    /// Type the key, the text is given back once it's OK.
+    pub fn press(&mut self, key: &str) -> Option<String> {
+        let typed = match key {
+            "OK" => return Some(self.text.clone()),
+            "SHIFT" => {
+                self.shift = !self.shift;
+                return None;
+            }
+            "DEL" => {
+                self.text.pop();
+                return None;
+            }
+            "SPACE" => " ".to_owned(),
+            "ENTER" => "\n".to_owned(),
+            c if self.shift => c.to_uppercase(),
+            c => c.to_owned(),
+        };
+        if self.text.chars().count() < MAX_CHARS {
+            self.text.push_str(&typed);
+        }
+        self.shift = false;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn types_until_ok() {
+        let mut keyboard = Keyboard::new(Typing::Notes("a".to_owned()), "Save");
+        for key in [
+            "SPACE", "SHIFT", "a", "t", "DEL", "ENTER", "SHIFT", "SHIFT", "b", "?",
+        ] {
+            assert_eq!(keyboard.press(key), None);
+        }
+        assert!(!keyboard.shift());
+        assert_eq!(keyboard.text(), "Save A\nb?");
+        assert_eq!(keyboard.press("OK").as_deref(), Some("Save A\nb?"));
+
+        let mut keyboard = Keyboard::new(Typing::Notes("a".to_owned()), &"x".repeat(MAX_CHARS));
+        keyboard.press("y");
+        assert_eq!(keyboard.text().len(), MAX_CHARS);
+    }
+}
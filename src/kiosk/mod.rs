@@ -0,0 +1,325 @@
+use crate::paths;
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::ValueEnum;
+use log::{info, warn};
+use ring::{
+    digest::{digest, SHA256},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+    thread,
+    time::{Duration, Instant},
+};
+
+// Kiosk mode, for HTPCs booting straight into anubis. `anubis session` is the
+// display session: it runs a compositor made for a single fullscreen app, and
+// in it `anubis session --inner`, which starts anubis again when it crashes.
+// The compositor stays up meanwhile, so the screen doesn't go back to the
+// display manager. The window can't be closed with
+// the usual shortcuts then, the way out is "Exit to desktop" in the settings,
+// behind a PIN when one is set. Typed wrong too often, the PIN screen locks
+// for a while, longer each time. Leaving that way quits anubis cleanly, which
+// ends the session and goes back to the display manager.
+//
+// Display managers list the session with a file like
+// `/usr/share/wayland-sessions/anubis.desktop`:
+//
+// [Desktop Entry]
+// Name=Anubis
+// Exec=anubis session
+// Type=Application
+
+/// Before the first restart, doubled after each quick crash.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+/// Ran at least this long, the crash isn't counted as a quick one.
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+/// Of PBKDF2, for the PIN not to be guessed quickly from its hash.
+const PIN_ITERATIONS: u32 = 100_000;
+/// Wrong PINs typed in a row before the PIN screen locks.
+const PIN_TRIES: u32 = 3;
+/// Locked for this long after `PIN_TRIES` wrong PINs, doubled after each
+/// wrong one since.
+const PIN_LOCKOUT: Duration = Duration::from_secs(30);
+const MAX_PIN_LOCKOUT: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Compositor {
+    #[default]
+    Cage,
+    /// Valve's, as on the Steam Deck.
+    Gamescope,
+}
+
+impl Compositor {
+    /// Running the inner session of `program` fullscreen.
+    fn command(self, program: &Path) -> Command {
+        let mut cmd = match self {
+            Compositor::Cage => Command::new("cage"),
+            Compositor::Gamescope => {
+                let mut cmd = Command::new("gamescope");
+                cmd.arg("--fullscreen");
+                cmd
+            }
+        };
+        cmd.arg("--").arg(program).args(["session", "--inner"]);
+        cmd
+    }
+}
+
+/// ```yaml
+/// enabled: true
+/// compositor: gamescope
+/// # Of the PIN, see `anubis cli kiosk --pin`.
+/// pin:
+///   salt: 3q2+7wAAAAAAAAAAAAAAAA==
+///   pbkdf2_sha256: lsLoUgM/C/UxWxsjFn1QZZlfhOAI6RycWBFncrl3pFg=
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KioskConfig {
+    pub enabled: bool,
+    pub compositor: Compositor,
+    /// So the PIN isn't in the file as is.
+    pub pin: Option<PinHash>,
+    /// Hex, of a PIN set before they were salted. Still checked until the
+    /// PIN is set again.
+    pub pin_sha256: Option<String>,
+}
+
+/// Of a PIN, in base64.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinHash {
+    /// Random, for each PIN set.
+    salt: String,
+    pbkdf2_sha256: String,
+}
+
+impl PinHash {
+    fn new(pin: &str) -> Self {
+        let mut salt = [0; 16];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .expect("no randomness from the system");
+        let mut hash = [0; 32];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(PIN_ITERATIONS).unwrap(),
+            &salt,
+            pin.as_bytes(),
+            &mut hash,
+        );
+        Self {
+            salt: STANDARD.encode(salt),
+            pbkdf2_sha256: STANDARD.encode(hash),
+        }
+    }
+
+    fn matches(&self, pin: &str) -> bool {
+        let (Ok(salt), Ok(hash)) = (
+            STANDARD.decode(&self.salt),
+            STANDARD.decode(&self.pbkdf2_sha256),
+        ) else {
+            warn!("the kiosk PIN hash isn't base64");
+            return false;
+        };
+        pbkdf2::verify(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(PIN_ITERATIONS).unwrap(),
+            &salt,
+            pin.as_bytes(),
+            &hash,
+        )
+        .is_ok()
+    }
+}
+
+/// Of `pin_sha256`.
+fn sha256_hex(pin: &str) -> String {
+    digest(&SHA256, pin.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl KioskConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::config_dir().join("kiosk.yaml")
+    }
+
+    /// None to exit to the desktop without one.
+    pub fn set_pin(&mut self, pin: Option<&str>) -> Result<()> {
+        if let Some(pin) = pin {
+            if pin.is_empty() || !pin.chars().all(|c| c.is_ascii_digit()) {
+                bail!("the PIN must be digits, it's typed on a keypad");
+            }
+        }
+        self.pin = pin.map(PinHash::new);
+        self.pin_sha256 = None;
+        Ok(())
+    }
+
+    pub fn has_pin(&self) -> bool {
+        self.pin.is_some() || self.pin_sha256.is_some()
+    }
+
+    /// Whether `pin` lets out of kiosk mode, any does without a PIN set.
+    pub fn check_pin(&self, pin: &str) -> bool {
+        match (&self.pin, &self.pin_sha256) {
+            (Some(hash), _) => hash.matches(pin),
+            (None, Some(sha256)) => *sha256 == sha256_hex(pin),
+            (None, None) => true,
+        }
+    }
+}
+
+/// Wrong PINs typed in a row, to lock the PIN screen for a while after a
+/// few, longer each time.
+#[derive(Debug, Default)]
+pub struct PinLock {
+    wrong: u32,
+    until: Option<Instant>,
+}
+
+impl PinLock {
+    /// How long before a PIN can be typed again, None if it can now.
+    pub fn locked(&self, now: Instant) -> Option<Duration> {
+        self.until
+            .map(|until| until.saturating_duration_since(now))
+            .filter(|left| !left.is_zero())
+    }
+
+    /// `KioskConfig::check_pin`, always false while locked.
+    pub fn check(&mut self, config: &KioskConfig, pin: &str, now: Instant) -> bool {
+        if self.locked(now).is_some() {
+            return false;
+        }
+        if config.check_pin(pin) {
+            *self = Self::default();
+            return true;
+        }
+        self.wrong += 1;
+        if self.wrong >= PIN_TRIES {
+            let doublings = (self.wrong - PIN_TRIES).min(16);
+            self.until = Some(now + (PIN_LOCKOUT * (1 << doublings)).min(MAX_PIN_LOCKOUT));
+        }
+        false
+    }
+}
+
+/// Crashed or killed, as opposed to quit from "Exit to desktop".
+fn crashed(status: ExitStatus) -> bool {
+    !status.success()
+}
+
+/// Run the compositor with the inner session in it, until it's over.
+pub fn run_session(config: &KioskConfig) -> Result<()> {
+    let status = config
+        .compositor
+        .command(&program()?)
+        .status()
+        .with_context(|| format!("failed to run {:?}", config.compositor))?;
+    if !status.success() {
+        bail!("{:?} {}", config.compositor, status);
+    }
+    Ok(())
+}
+
+fn program() -> Result<PathBuf> {
+    Ok(match env::var_os("APPIMAGE") {
+        Some(path) => PathBuf::from(path),
+        None => env::current_exe()?,
+    })
+}
+
+/// Run anubis until it's quit, starting it again when it crashes. Waits
+/// longer between quick crashes, so a broken install doesn't spin.
+pub fn run_inner_session() -> Result<()> {
+    let program = program()?;
+    let mut delay = RESTART_DELAY;
+    loop {
+        let started = Instant::now();
+        let status = Command::new(&program)
+            .status()
+            .with_context(|| format!("failed to run {:?}", program))?;
+        if !crashed(status) {
+            info!("exited to the desktop");
+            return Ok(());
+        }
+        if started.elapsed() >= STABLE_AFTER {
+            delay = RESTART_DELAY;
+        }
+        warn!("anubis {}, restarting in {:?}", status, delay);
+        thread::sleep(delay);
+        delay = (delay * 2).min(MAX_RESTART_DELAY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+
+    #[test]
+    fn guards_the_exit_with_the_pin() {
+        let mut config = KioskConfig::default();
+        assert!(config.check_pin(""));
+        config.set_pin(Some("1234")).unwrap();
+        assert!(config.check_pin("1234"));
+        assert!(!config.check_pin("4321"));
+        assert!(!serde_yaml::to_string(&config).unwrap().contains("1234"));
+        assert!(config.set_pin(Some("12ab")).is_err());
+        // Set before they were salted.
+        let legacy = format!("pin_sha256: {}\n", sha256_hex("1234"));
+        let legacy: KioskConfig = serde_yaml::from_str(&legacy).unwrap();
+        assert!(legacy.has_pin() && legacy.check_pin("1234") && !legacy.check_pin("4321"));
+
+        let (mut lock, now) = (PinLock::default(), Instant::now());
+        assert!(!lock.check(&legacy, "1", now));
+        assert!(!lock.check(&legacy, "2", now));
+        assert_eq!(lock.locked(now), None);
+        assert!(!lock.check(&legacy, "3", now));
+        assert_eq!(lock.locked(now), Some(PIN_LOCKOUT));
+        // Not even the right one meanwhile.
+        assert!(!lock.check(&legacy, "1234", now));
+        let later = now + PIN_LOCKOUT;
+        assert!(!lock.check(&legacy, "4", later));
+        assert_eq!(lock.locked(later), Some(PIN_LOCKOUT * 2));
+        assert!(lock.check(&legacy, "1234", later + PIN_LOCKOUT * 2));
+        assert_eq!(lock.locked(later), None);
+
+        let cmd = Compositor::Gamescope.command(Path::new("/bin/anubis"));
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(
+            args,
+            ["--fullscreen", "--", "/bin/anubis", "session", "--inner"]
+        );
+        assert!(!crashed(ExitStatus::from_raw(0)));
+        // Killed by SIGSEGV.
+        assert!(crashed(ExitStatus::from_raw(11)));
+    }
+}
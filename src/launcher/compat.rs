@@ -0,0 +1,218 @@
+use crate::{models::CompatConfig, paths};
+use anyhow::{anyhow, Result};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// Running Windows games through Proton or Wine, with the version picked per
+// game. Proton builds are found where Steam keeps them, Wine builds where
+// Lutris does, plus whatever `wine` is on the PATH.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompatKind {
+    Proton,
+    Wine,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompatTool {
+    /// e.g. `GE-Proton8-25`, `Proton 8.0` or `wine`.
+    pub name: String,
+    pub kind: CompatKind,
+    /// The `proton` script or the `wine` binary.
+    pub path: PathBuf,
+}
+
+/// Where Steam is, `STEAM_COMPAT_CLIENT_INSTALL_PATH` for Proton.
+fn steam_root() -> Option<PathBuf> {
+    let home = PathBuf::from(env::var_os("HOME")?);
+    [".steam/root", ".local/share/Steam"]
+        .iter()
+        .map(|d| home.join(d))
+        .find(|d| d.is_dir())
+}
+
+/// The directories holding a tool each.
+fn tool_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Some(steam) = steam_root() {
+        dirs.push(steam.join("compatibilitytools.d"));
+        dirs.push(steam.join("steamapps/common"));
+    }
+    if let Some(home) = env::var_os("HOME").map(PathBuf::from) {
+        dirs.push(home.join(".local/share/lutris/runners/wine"));
+    }
+    dirs
+}
+
+/// The tools in `dirs`, sorted by name. The same name is only listed once.
+fn scan(dirs: &[PathBuf]) -> Vec<CompatTool> {
+    let mut tools: Vec<CompatTool> = vec![];
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let root = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let tool = if root.join("proton").is_file() {
+                CompatTool {
+                    name,
+                    kind: CompatKind::Proton,
+                    path: root.join("proton"),
+                }
+            } else if root.join("bin/wine").is_file() {
+                CompatTool {
+                    name,
+                    kind: CompatKind::Wine,
+                    path: root.join("bin/wine"),
+                }
+            } else {
+                continue;
+            };
+            if !tools.iter().any(|t| t.name == tool.name) {
+                tools.push(tool);
+            }
+        }
+    }
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    tools
+}
+
+/// `wine` if it's on the PATH.
+fn system_wine() -> Option<CompatTool> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|d| d.join("wine"))
+        .find(|p| p.is_file())
+        .map(|path| CompatTool {
+            name: "wine".to_owned(),
+            kind: CompatKind::Wine,
+            path,
+        })
+}
+
+/// Every Proton and Wine version found.
+pub fn installed() -> Vec<CompatTool> {
+    let mut tools = scan(&tool_dirs());
+    tools.extend(system_wine());
+    tools
+}
+
+/// The prefix of a game without one set.
+pub fn default_prefix(uuid: &str) -> PathBuf {
+    paths::data_dir().join("prefixes").join(uuid)
+}
+
+/// The command running `program` through the tool of the config.
+pub fn command(
+    config: &CompatConfig,
+    uuid: &str,
+    program: &str,
+    args: &[String],
+) -> Result<Command> {
+    let tools = installed();
+    let tool = tools
+        .iter()
+        .find(|t| t.name == config.tool)
+        .ok_or_else(|| anyhow!("{} isn't installed", config.tool))?;
+    let prefix = config
+        .prefix
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| default_prefix(uuid));
+    fs::create_dir_all(&prefix)?;
+    Ok(command_with(
+        tool,
+        &prefix,
+        steam_root().as_deref(),
+        program,
+        args,
+    ))
+}
+
+fn command_with(
+    tool: &CompatTool,
+    prefix: &Path,
+    steam_root: Option<&Path>,
+    program: &str,
+    args: &[String],
+) -> Command {
+    let mut cmd = Command::new(&tool.path);
+    match tool.kind {
+        CompatKind::Proton => {
+            // Proton makes its Wine prefix in `pfx` under this.
+            cmd.env("STEAM_COMPAT_DATA_PATH", prefix);
+            if let Some(steam) = steam_root {
+                cmd.env("STEAM_COMPAT_CLIENT_INSTALL_PATH", steam);
+            }
+            cmd.arg("run");
+        }
+        CompatKind::Wine => {
+            cmd.env("WINEPREFIX", prefix);
+        }
+    }
+    cmd.arg(program).args(args);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_tools_and_wraps_games() {
+        let dir = env::temp_dir().join(format!("anubis-compat-test-{}", std::process::id()));
+        let steam = dir.join("compatibilitytools.d");
+        let lutris = dir.join("wine");
+        for (root, file) in [
+            (steam.join("GE-Proton8-25"), "proton"),
+            (steam.join("Not a tool"), "readme.txt"),
+            (lutris.join("lutris-7.2"), "bin/wine"),
+            (lutris.join("GE-Proton8-25"), "proton"),
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let tools = scan(&[steam.clone(), lutris.clone(), dir.join("missing")]);
+        let names: Vec<_> = tools.iter().map(|t| (t.name.as_str(), t.kind)).collect();
+        assert_eq!(
+            names,
+            [
+                ("GE-Proton8-25", CompatKind::Proton),
+                ("lutris-7.2", CompatKind::Wine)
+            ]
+        );
+        assert_eq!(tools[0].path, steam.join("GE-Proton8-25/proton"));
+
+        let args = ["-windowed".to_owned()];
+        let proton = command_with(
+            &tools[0],
+            Path::new("/prefixes/game"),
+            Some(Path::new("/steam")),
+            "game.exe",
+            &args,
+        );
+        let proton_args: Vec<_> = proton.get_args().collect();
+        assert_eq!(proton_args, ["run", "game.exe", "-windowed"]);
+        assert!(proton
+            .get_envs()
+            .any(|(k, v)| k == "STEAM_COMPAT_DATA_PATH" && v == Some("/prefixes/game".as_ref())));
+
+        let wine = command_with(
+            &tools[1],
+            Path::new("/prefixes/game"),
+            None,
+            "game.exe",
+            &[],
+        );
+        assert_eq!(wine.get_program(), lutris.join("lutris-7.2/bin/wine"));
+        assert!(wine
+            .get_envs()
+            .any(|(k, v)| k == "WINEPREFIX" && v == Some("/prefixes/game".as_ref())));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
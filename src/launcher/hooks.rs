@@ -0,0 +1,79 @@
+use crate::models::GameMetadata;
+use anyhow::{bail, Result};
+use std::process::Command;
+
+/// When a hook runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    PreLaunch,
+    PostExit,
+}
+
+impl HookStage {
+    pub fn name(self) -> &'static str {
+        match self {
+            HookStage::PreLaunch => "pre",
+            HookStage::PostExit => "post",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookOutcome {
+    Done,
+    /// Both sides changed since the last sync, the user has to decide.
+    Conflict(String),
+}
+
+/// Something to run around a game launch, e.g. syncing the saves.
+pub trait LaunchHook: Send {
+    fn name(&self) -> &str;
+
+    fn run(&self, stage: HookStage, game: &GameMetadata) -> Result<HookOutcome>;
+}
+
+/// Exit code of a script reporting a conflict, its stdout is the detail.
+pub const SCRIPT_CONFLICT_EXIT_CODE: i32 = 3;
+
+/// Runs a shell command per stage. The game is passed via the
+/// `ANUBIS_GAME_UUID`, `ANUBIS_GAME_TITLE` and `ANUBIS_HOOK_STAGE` env vars.
+#[derive(Debug, Clone)]
+pub struct ScriptHook {
+    pub pre_launch: Option<String>,
+    pub post_exit: Option<String>,
+}
+
+impl LaunchHook for ScriptHook {
+    fn name(&self) -> &str {
+        "script"
+    }
+
+    fn run(&self, stage: HookStage, game: &GameMetadata) -> Result<HookOutcome> {
+        let script = match stage {
+            HookStage::PreLaunch => &self.pre_launch,
+            HookStage::PostExit => &self.post_exit,
+        };
+        let Some(script) = script else {
+            return Ok(HookOutcome::Done);
+        };
+
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .env("ANUBIS_GAME_UUID", &game.uuid)
+            .env("ANUBIS_GAME_TITLE", &game.title)
+            .env("ANUBIS_HOOK_STAGE", stage.name())
+            .output()?;
+        match output.status.code() {
+            Some(0) => Ok(HookOutcome::Done),
+            Some(SCRIPT_CONFLICT_EXIT_CODE) => Ok(HookOutcome::Conflict(
+                String::from_utf8_lossy(&output.stdout).trim().to_owned(),
+            )),
+            _ => bail!(
+                "script exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use crate::{
+    integrations::{legendary, moonlight, steam},
+    models::GameMetadata,
+};
+use anyhow::{bail, Result};
+use log::{info, warn};
+use std::{
+    collections::{HashMap, HashSet},
+    os::unix::process::CommandExt,
+    process::Command,
+    sync::{mpsc, Arc, Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+pub mod compat;
+pub mod hooks;
+pub mod overlay;
+pub mod profile;
+pub mod resume;
+pub mod suspend;
+pub mod sync;
+pub mod web_app;
+
+pub use self::hooks::HookStage;
+use self::hooks::HookOutcome;
+use self::overlay::OverlayConfig;
+pub use self::profile::ProfileConfig;
+use self::profile::Applied;
+use self::suspend::{SuspendConfig, SuspendedGame};
+pub use self::sync::{ConflictSide, SyncConfig};
+
+/// How long to wait for Steam to report the game as running.
+const STEAM_START_TIMEOUT: Duration = Duration::from_secs(60);
+const STEAM_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How often the memory is checked while games are suspended.
+const MEMORY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub enum LaunchEvent {
+    /// A sync of the game from a previous session is still running.
+    WaitingForSync { uuid: String },
+    /// Hooks of the stage are running.
+    Syncing { uuid: String, stage: HookStage },
+    /// A hook reported a conflict, see `Launcher::resolve_conflict`.
+    Conflict {
+        uuid: String,
+        stage: HookStage,
+        detail: String,
+    },
+    Running { uuid: String },
+    /// Paused to go back to the launcher, see `Launcher::suspend`.
+    Suspended { uuid: String },
+    Resumed { uuid: String },
+    Exited { uuid: String },
+    /// Post exit hooks are done.
+    Done { uuid: String },
+    Failed { uuid: String, error: String },
+}
+
+/// Games with hooks running, with a condvar to wait on them.
+type SyncingGames = Arc<(Mutex<HashSet<String>>, Condvar)>;
+
+/// Launches games, running the sync hooks around them.
+#[derive(Clone)]
+pub struct Launcher {
+    sync_config: Arc<SyncConfig>,
+    /// Edited in the settings while games run.
+    profiles: Arc<Mutex<ProfileConfig>>,
+    events: mpsc::Sender<LaunchEvent>,
+    syncing: SyncingGames,
+    /// Process ids of the games running, by uuid.
+    running: Arc<Mutex<HashMap<String, u32>>>,
+    suspend_config: Arc<SuspendConfig>,
+    /// Those of the games running which are paused, the oldest first.
+    suspended: Arc<Mutex<Vec<SuspendedGame>>>,
+}
+
+impl Launcher {
+    pub fn new(
+        sync_config: SyncConfig,
+        suspend_config: SuspendConfig,
+        profiles: Arc<Mutex<ProfileConfig>>,
+        events: mpsc::Sender<LaunchEvent>,
+    ) -> Self {
+        Self {
+            sync_config: Arc::new(sync_config),
+            profiles,
+            events,
+            syncing: Arc::new((Mutex::new(HashSet::new()), Condvar::new())),
+            running: Arc::new(Mutex::new(HashMap::new())),
+            suspend_config: Arc::new(suspend_config),
+            suspended: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Ask the games being played to quit, not the suspended ones. Steam
+    /// games aren't ours to stop, the process started is gone once Steam has
+    /// the game.
+    pub fn stop_all(&self) -> Result<()> {
+        let mut running = self.running.lock().unwrap().clone();
+        for game in self.suspended.lock().unwrap().iter() {
+            running.remove(&game.uuid);
+        }
+        if running.is_empty() {
+            bail!("no game to stop");
+        }
+        for (uuid, pid) in running {
+            info!("stopping {}", uuid);
+            let status = Command::new("kill").arg(pid.to_string()).status()?;
+            if !status.success() {
+                bail!("failed to stop {}, kill exited with {}", uuid, status);
+            }
+        }
+        Ok(())
+    }
+
+    /// Pause the game being played, quitting the oldest suspended ones first
+    /// if there are too many. Returns its uuid.
+    pub fn suspend(&self) -> Result<String> {
+        let running = self.running.lock().unwrap().clone();
+        let mut suspended = self.suspended.lock().unwrap();
+        let Some((uuid, pid)) = running
+            .into_iter()
+            .find(|(uuid, _)| !suspended.iter().any(|s| s.uuid == *uuid))
+        else {
+            bail!("no game to suspend");
+        };
+        if self.suspend_config.max_suspended == 0 {
+            bail!("suspending is turned off");
+        }
+        if suspend::available_memory().is_some_and(|m| self.suspend_config.under_pressure(m)) {
+            bail!("not enough memory left to keep a game suspended");
+        }
+        while suspended.len() >= self.suspend_config.max_suspended {
+            let oldest = suspended.remove(0);
+            info!("too many games suspended, quitting {}", oldest.uuid);
+            quit_stopped(oldest.pid)?;
+        }
+        info!("suspending {}", uuid);
+        suspend::signal_group(pid, "STOP")?;
+        suspended.push(SuspendedGame {
+            uuid: uuid.clone(),
+            pid,
+            since: chrono::Utc::now(),
+        });
+        self.send(LaunchEvent::Suspended { uuid: uuid.clone() });
+        Ok(uuid)
+    }
+
+    /// Carry on playing a suspended game.
+    pub fn resume(&self, uuid: &str) -> Result<()> {
+        let game = self.take_suspended(uuid)?;
+        info!("resuming {}", uuid);
+        suspend::signal_group(game.pid, "CONT")?;
+        self.send(LaunchEvent::Resumed {
+            uuid: uuid.to_owned(),
+        });
+        Ok(())
+    }
+
+    /// Ask a suspended game to quit, it exits like it would when played.
+    pub fn quit_suspended(&self, uuid: &str) -> Result<()> {
+        quit_stopped(self.take_suspended(uuid)?.pid)
+    }
+
+    fn take_suspended(&self, uuid: &str) -> Result<SuspendedGame> {
+        let mut suspended = self.suspended.lock().unwrap();
+        let Some(i) = suspended.iter().position(|s| s.uuid == uuid) else {
+            bail!("{} isn't suspended", uuid);
+        };
+        Ok(suspended.remove(i))
+    }
+
+    /// The games suspended, the oldest first.
+    pub fn suspended(&self) -> Vec<SuspendedGame> {
+        self.suspended.lock().unwrap().clone()
+    }
+
+    pub fn is_suspended(&self, uuid: &str) -> bool {
+        self.suspended.lock().unwrap().iter().any(|s| s.uuid == uuid)
+    }
+
+    /// Quit the oldest suspended games while memory runs low, in the
+    /// background.
+    pub fn watch_memory(&self) {
+        let this = self.clone();
+        thread::spawn(move || loop {
+            thread::sleep(MEMORY_POLL_INTERVAL);
+            let low = suspend::available_memory()
+                .is_some_and(|m| this.suspend_config.under_pressure(m));
+            let oldest = this.suspended.lock().unwrap().first().cloned();
+            if let (true, Some(oldest)) = (low, oldest) {
+                warn!("memory is running low, quitting the suspended {}", oldest.uuid);
+                if let Err(e) = this.quit_suspended(&oldest.uuid) {
+                    warn!("failed to quit {}: {:?}", oldest.uuid, e);
+                }
+            }
+        });
+    }
+
+    /// Launch the game in the background, progress is reported via the events channel.
+    pub fn launch(&self, game: GameMetadata) -> Result<()> {
+        self.spawn(game, move |this, game, uuid| this.run(game, uuid, true))
+    }
+
+    /// Resolve a conflict by keeping one side for all the providers of the
+    /// game, then carry on where the conflict happened.
+    pub fn resolve_conflict(
+        &self,
+        game: GameMetadata,
+        stage: HookStage,
+        keep: ConflictSide,
+    ) -> Result<()> {
+        self.spawn(game, move |this, game, uuid| {
+            this.with_syncing(uuid, || {
+                this.send(LaunchEvent::Syncing {
+                    uuid: uuid.to_owned(),
+                    stage,
+                });
+                for provider in this.sync_config.providers_for(uuid) {
+                    provider.resolve(game, keep)?;
+                }
+                Ok(())
+            })?;
+            match stage {
+                HookStage::PreLaunch => this.run(game, uuid, false),
+                HookStage::PostExit => {
+                    this.send(LaunchEvent::Done {
+                        uuid: uuid.to_owned(),
+                    });
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn spawn<F>(&self, game: GameMetadata, f: F) -> Result<()>
+    where
+        F: FnOnce(&Launcher, &GameMetadata, &str) -> Result<()> + Send + 'static,
+    {
+        if game.uuid.is_empty() {
+            bail!("game {} has no uuid", game.title);
+        }
+        let uuid = game.uuid.clone();
+        let this = self.clone();
+        thread::Builder::new()
+            .name(format!("launch-{}", uuid))
+            .spawn(move || {
+                if let Err(e) = f(&this, &game, &uuid) {
+                    warn!("launching {} failed: {:?}", uuid, e);
+                    this.send(LaunchEvent::Failed {
+                        uuid,
+                        error: e.to_string(),
+                    });
+                }
+            })?;
+        Ok(())
+    }
+
+    fn send(&self, event: LaunchEvent) {
+        // The receiver is gone when the application is shutting down.
+        let _ = self.events.send(event);
+    }
+
+    /// Run f while marking the game as syncing, waiting for any sync of the
+    /// game which is still running first.
+    fn with_syncing<T>(&self, uuid: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        {
+            let (lock, cvar) = &*self.syncing;
+            let mut syncing = lock.lock().unwrap();
+            if syncing.contains(uuid) {
+                self.send(LaunchEvent::WaitingForSync {
+                    uuid: uuid.to_owned(),
+                });
+                syncing = cvar.wait_while(syncing, |s| s.contains(uuid)).unwrap();
+            }
+            syncing.insert(uuid.to_owned());
+        }
+        let res = f();
+        let (lock, cvar) = &*self.syncing;
+        lock.lock().unwrap().remove(uuid);
+        cvar.notify_all();
+        res
+    }
+
+    /// Run the hooks of a stage, returns false if there was a conflict.
+    fn run_hooks(&self, game: &GameMetadata, uuid: &str, stage: HookStage) -> Result<bool> {
+        let providers = self.sync_config.providers_for(uuid);
+        self.with_syncing(uuid, || {
+            if !providers.is_empty() {
+                self.send(LaunchEvent::Syncing {
+                    uuid: uuid.to_owned(),
+                    stage,
+                });
+            }
+            for provider in providers {
+                match provider.run(stage, game) {
+                    Ok(HookOutcome::Done) => {}
+                    Ok(HookOutcome::Conflict(detail)) => {
+                        self.send(LaunchEvent::Conflict {
+                            uuid: uuid.to_owned(),
+                            stage,
+                            detail,
+                        });
+                        return Ok(false);
+                    }
+                    // Being offline shouldn't prevent playing, only complain
+                    // when the new saves couldn't be pushed.
+                    Err(e) if stage == HookStage::PreLaunch => {
+                        warn!("{} hook failed for {}: {:?}", provider.name(), uuid, e)
+                    }
+                    Err(e) => bail!("{} failed to sync: {}", provider.name(), e),
+                }
+            }
+            Ok(true)
+        })
+    }
+
+    fn run(&self, game: &GameMetadata, uuid: &str, pre_launch_hooks: bool) -> Result<()> {
+        if pre_launch_hooks && !self.run_hooks(game, uuid, HookStage::PreLaunch)? {
+            return Ok(());
+        }
+
+        // Put back once dropped, whichever way the game ends.
+        let profile = self.profiles.lock().unwrap().profile_for(uuid).cloned();
+        let applied = profile.as_ref().map(Applied::apply);
+        info!("launching {}", game.title);
+        let mut cmd = command_for(game)?;
+        if game.overlay {
+            match OverlayConfig::load(&OverlayConfig::default_path()) {
+                Ok(overlay) => overlay.apply(&mut cmd),
+                Err(e) => warn!("failed to load the overlay config: {:?}", e),
+            }
+        }
+        // So suspending it pauses what it started too.
+        cmd.process_group(0);
+        let mut child = cmd.spawn()?;
+        self.send(LaunchEvent::Running {
+            uuid: uuid.to_owned(),
+        });
+        self.running.lock().unwrap().insert(uuid.to_owned(), child.id());
+        let res = child.wait();
+        self.running.lock().unwrap().remove(uuid);
+        // Killed from outside while suspended.
+        self.suspended.lock().unwrap().retain(|s| s.uuid != uuid);
+        res?;
+        // Steam hands the launch off to the client, wait on the client instead.
+        if let Some(app_id) = steam_app_id(game) {
+            wait_for_steam_app(app_id);
+        }
+        drop(applied);
+        self.send(LaunchEvent::Exited {
+            uuid: uuid.to_owned(),
+        });
+
+        if self.run_hooks(game, uuid, HookStage::PostExit)? {
+            self.send(LaunchEvent::Done {
+                uuid: uuid.to_owned(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A stopped process only gets the TERM once it's continued.
+fn quit_stopped(pid: u32) -> Result<()> {
+    suspend::signal_group(pid, "TERM")?;
+    suspend::signal_group(pid, "CONT")
+}
+
+fn steam_app_id(game: &GameMetadata) -> Option<steam::AppId> {
+    game.install_source
+        .as_deref()
+        .and_then(steam::app_id_from_install_source)
+}
+
+/// The launch options are the command line, otherwise it's up to the install source.
+fn command_for(game: &GameMetadata) -> Result<Command> {
+    if let Some((program, args)) = game.launch_options.split_first() {
+        if let Some(config) = &game.compat {
+            return compat::command(config, &game.uuid, program, args);
+        }
+        let mut cmd = Command::new(program);
+        cmd.args(args);
+        return Ok(cmd);
+    }
+    if let Some(config) = &game.web_app {
+        return web_app::command(config, &game.uuid);
+    }
+    let stream = game
+        .install_source
+        .as_deref()
+        .and_then(moonlight::stream_from_install_source);
+    if let Some((host, app)) = stream {
+        return Ok(moonlight::command(host, app));
+    }
+    let epic = game
+        .install_source
+        .as_deref()
+        .and_then(legendary::app_name_from_install_source);
+    if let Some(app_name) = epic {
+        return Ok(legendary::command(app_name));
+    }
+    if let Some(app_id) = steam_app_id(game) {
+        let mut cmd = Command::new("steam");
+        cmd.arg(format!("steam://rungameid/{}", app_id));
+        return Ok(cmd);
+    }
+    bail!("don't know how to launch {}", game.title)
+}
+
+fn wait_for_steam_app(app_id: steam::AppId) {
+    let started = Instant::now();
+    while steam::running_app_id() != Some(app_id) {
+        if started.elapsed() > STEAM_START_TIMEOUT {
+            warn!("steam never reported app {} as running", app_id);
+            return;
+        }
+        thread::sleep(STEAM_POLL_INTERVAL);
+    }
+    while steam::running_app_id() == Some(app_id) {
+        thread::sleep(STEAM_POLL_INTERVAL);
+    }
+}
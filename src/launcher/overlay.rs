@@ -0,0 +1,70 @@
+use crate::paths;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// The performance overlay, MangoHud unless set up otherwise. It's turned on by
+// environment variables, so anything reading them works, e.g. `DXVK_HUD`.
+// Games launched through the Steam client don't get them, Steam starts those.
+
+/// ```yaml
+/// env:
+///   MANGOHUD: "1"
+///   MANGOHUD_CONFIG: fps,frametime,position=top-right
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverlayConfig {
+    /// Set on the games with the overlay on.
+    pub env: BTreeMap<String, String>,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            env: BTreeMap::from([("MANGOHUD".to_owned(), "1".to_owned())]),
+        }
+    }
+}
+
+impl OverlayConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::config_dir().join("overlay.yaml")
+    }
+
+    pub fn apply(&self, cmd: &mut Command) {
+        cmd.envs(&self.env);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_the_overlay_env() {
+        let mut cmd = Command::new("game");
+        OverlayConfig::default().apply(&mut cmd);
+        let config: OverlayConfig = serde_yaml::from_str("env:\n  DXVK_HUD: fps\n").unwrap();
+        config.apply(&mut cmd);
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert_eq!(
+            envs,
+            [
+                ("DXVK_HUD".as_ref(), Some("fps".as_ref())),
+                ("MANGOHUD".as_ref(), Some("1".as_ref()))
+            ]
+        );
+    }
+}
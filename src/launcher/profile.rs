@@ -0,0 +1,331 @@
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// System tweaks for while a game runs, like the performance CPU governor or
+// HDR, grouped in profiles picked per game. What's changed is put back once
+// the game exits. The display ones go through KDE's tools, which is what
+// consoles running a desktop session have.
+
+/// Where the CPU frequency settings are.
+const CPU_SYSFS: &str = "/sys/devices/system/cpu";
+/// The governors gone through in the editor.
+pub const GOVERNORS: [&str; 3] = ["performance", "schedutil", "powersave"];
+
+/// What a profile changes, unset ones are left alone.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// CPU frequency governor, e.g. `performance`.
+    pub governor: Option<String>,
+    pub hdr: Option<bool>,
+    /// Variable refresh rate.
+    pub vrr: Option<bool>,
+    /// Suspend compositing, games get the display to themselves.
+    pub compositor_bypass: Option<bool>,
+}
+
+/// The profiles, and the one of each game by UUID.
+///
+/// ```yaml
+/// profiles:
+///   performance:
+///     governor: performance
+///     vrr: true
+/// games:
+///   0a1b2c3d: performance
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    pub profiles: BTreeMap<String, Profile>,
+    pub games: HashMap<String, String>,
+}
+
+impl ProfileConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn profile_for(&self, uuid: &str) -> Option<&Profile> {
+        self.games
+            .get(uuid)
+            .and_then(|name| self.profiles.get(name))
+    }
+
+    /// Add an empty profile named after the ones there are, returns its name.
+    pub fn add(&mut self) -> String {
+        let name = (1..)
+            .map(|i| format!("Profile {}", i))
+            .find(|n| !self.profiles.contains_key(n))
+            .unwrap();
+        self.profiles.insert(name.clone(), Profile::default());
+        name
+    }
+}
+
+/// The governor after `current` in `GOVERNORS`, unset after the last one.
+pub fn next_governor(current: Option<&str>) -> Option<String> {
+    let next = match current.and_then(|c| GOVERNORS.iter().position(|g| *g == c)) {
+        Some(i) => GOVERNORS.get(i + 1),
+        None if current.is_some() => None,
+        None => GOVERNORS.first(),
+    };
+    next.map(|g| g.to_string())
+}
+
+/// Unset, on, off, and round again.
+pub fn next_toggle(current: Option<bool>) -> Option<bool> {
+    match current {
+        None => Some(true),
+        Some(true) => Some(false),
+        Some(false) => None,
+    }
+}
+
+/// A display as kscreen-doctor has it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Output {
+    name: String,
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    hdr: bool,
+    /// 0 never, 1 always, 2 automatic.
+    #[serde(default)]
+    vrr_policy: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct Outputs {
+    outputs: Vec<Output>,
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("failed to run {}", program))?;
+    if !output.status.success() {
+        bail!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn outputs() -> Result<Vec<Output>> {
+    let outputs: Outputs = serde_json::from_str(&run("kscreen-doctor", &["-j"])?)?;
+    Ok(outputs.outputs.into_iter().filter(|o| o.enabled).collect())
+}
+
+fn set_hdr(output: &str, on: bool) -> Result<()> {
+    let state = if on { "enable" } else { "disable" };
+    run(
+        "kscreen-doctor",
+        &[&format!("output.{}.hdr.{}", output, state)],
+    )?;
+    Ok(())
+}
+
+fn set_vrr_policy(output: &str, policy: u8) -> Result<()> {
+    let policy = match policy {
+        0 => "never",
+        1 => "always",
+        _ => "automatic",
+    };
+    run(
+        "kscreen-doctor",
+        &[&format!("output.{}.vrrpolicy.{}", output, policy)],
+    )?;
+    Ok(())
+}
+
+fn set_compositing(suspended: bool) -> Result<()> {
+    let method = if suspended { "suspend" } else { "resume" };
+    run(
+        "dbus-send",
+        &[
+            "--session",
+            "--type=method_call",
+            "--dest=org.kde.KWin",
+            "/Compositor",
+            &format!("org.kde.kwin.Compositing.{}", method),
+        ],
+    )?;
+    Ok(())
+}
+
+/// The scaling governor file of each CPU.
+fn governor_files(cpu_sysfs: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(cpu_sysfs) else {
+        return vec![];
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            name.strip_prefix("cpu")
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(|e| e.path().join("cpufreq/scaling_governor"))
+        .filter(|p| p.exists())
+        .collect();
+    files.sort();
+    files
+}
+
+/// What a profile changed, put back when dropped. Tweaks that fail are
+/// skipped, a game shouldn't fail to start over them.
+#[derive(Debug, Default)]
+pub struct Applied {
+    governors: Vec<(PathBuf, String)>,
+    /// Outputs with their HDR and VRR settings from before.
+    outputs: Vec<Output>,
+    compositor_suspended: bool,
+}
+
+impl Applied {
+    pub fn apply(profile: &Profile) -> Self {
+        Self::apply_with(profile, Path::new(CPU_SYSFS))
+    }
+
+    fn apply_with(profile: &Profile, cpu_sysfs: &Path) -> Self {
+        let mut applied = Self::default();
+        if let Some(governor) = &profile.governor {
+            for file in governor_files(cpu_sysfs) {
+                let before = match fs::read_to_string(&file) {
+                    Ok(before) => before.trim().to_owned(),
+                    Err(e) => {
+                        warn!("failed to read {:?}: {:?}", file, e);
+                        continue;
+                    }
+                };
+                match fs::write(&file, governor) {
+                    Ok(()) => applied.governors.push((file, before)),
+                    Err(e) => warn!("failed to set the governor in {:?}: {:?}", file, e),
+                }
+            }
+        }
+        if profile.hdr.is_some() || profile.vrr.is_some() {
+            match outputs() {
+                Ok(outputs) => {
+                    for output in outputs {
+                        if let Err(e) = Self::set_output(&output, profile) {
+                            warn!("failed to set up {}: {:?}", output.name, e);
+                        }
+                        applied.outputs.push(output);
+                    }
+                }
+                Err(e) => warn!("failed to read the displays: {:?}", e),
+            }
+        }
+        if let Some(bypass) = profile.compositor_bypass {
+            match set_compositing(bypass) {
+                Ok(()) => applied.compositor_suspended = bypass,
+                Err(e) => warn!("failed to change the compositing: {:?}", e),
+            }
+        }
+        info!("applied {:?}", profile);
+        applied
+    }
+
+    fn set_output(output: &Output, profile: &Profile) -> Result<()> {
+        if let Some(hdr) = profile.hdr {
+            set_hdr(&output.name, hdr)?;
+        }
+        if let Some(vrr) = profile.vrr {
+            set_vrr_policy(&output.name, if vrr { 1 } else { 0 })?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Applied {
+    fn drop(&mut self) {
+        for (file, governor) in &self.governors {
+            if let Err(e) = fs::write(file, governor) {
+                warn!("failed to put the governor back in {:?}: {:?}", file, e);
+            }
+        }
+        for output in &self.outputs {
+            let reverted = set_hdr(&output.name, output.hdr)
+                .and_then(|()| set_vrr_policy(&output.name, output.vrr_policy));
+            if let Err(e) = reverted {
+                warn!("failed to put {} back: {:?}", output.name, e);
+            }
+        }
+        if self.compositor_suspended {
+            if let Err(e) = set_compositing(false) {
+                warn!("failed to resume compositing: {:?}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_the_governor_while_applied() {
+        let dir = std::env::temp_dir().join(format!("anubis-profile-test-{}", std::process::id()));
+        for cpu in ["cpu0", "cpu1", "cpufreq", "cpuidle"] {
+            fs::create_dir_all(dir.join(cpu).join("cpufreq")).unwrap();
+            fs::write(
+                dir.join(cpu).join("cpufreq/scaling_governor"),
+                "powersave\n",
+            )
+            .unwrap();
+        }
+        let read =
+            |cpu: &str| fs::read_to_string(dir.join(cpu).join("cpufreq/scaling_governor")).unwrap();
+
+        let config: ProfileConfig = serde_yaml::from_str(
+            "profiles:\n  fast:\n    governor: performance\ngames:\n  aaaa: fast\n  bbbb: gone\n",
+        )
+        .unwrap();
+        assert_eq!(config.profile_for("bbbb"), None);
+        let profile = config.profile_for("aaaa").unwrap();
+        let applied = Applied::apply_with(profile, &dir);
+        assert_eq!(read("cpu0"), "performance");
+        assert_eq!(read("cpu1"), "performance");
+        // Not CPUs.
+        assert_eq!(read("cpufreq"), "powersave\n");
+        drop(applied);
+        assert_eq!(read("cpu0"), "powersave");
+        fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(next_governor(None).as_deref(), Some("performance"));
+        assert_eq!(
+            next_governor(Some("schedutil")).as_deref(),
+            Some("powersave")
+        );
+        assert_eq!(next_governor(Some("powersave")), None);
+        assert_eq!(next_governor(Some("ondemand")), None);
+        let mut config = ProfileConfig::default();
+        assert_eq!(config.add(), "Profile 1");
+        assert_eq!(config.add(), "Profile 2");
+    }
+}
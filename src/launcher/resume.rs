@@ -0,0 +1,120 @@
+use crate::paths;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+// Picks up where the last session left off: the game played last is launched
+// again on startup, after a countdown any button cancels. Meant for HTPCs
+// which are turned off with the game still running. Off unless turned on in
+// the settings.
+
+/// Seconds before the game is launched.
+pub const COUNTDOWN: u32 = 5;
+
+/// ```yaml
+/// enabled: true
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResumeConfig {
+    pub enabled: bool,
+}
+
+impl ResumeConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::config_dir().join("resume.yaml")
+    }
+}
+
+/// What's kept of a session for the next, in the data dir.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    /// UUID of the game launched last.
+    pub last_game: Option<String>,
+}
+
+impl SessionState {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::data_dir().join("session.yaml")
+    }
+
+    /// The game to launch on startup, if it's turned on and the game is
+    /// still in the library.
+    pub fn to_resume(&self, config: &ResumeConfig, exists: impl Fn(&str) -> bool) -> Option<&str> {
+        self.last_game
+            .as_deref()
+            .filter(|uuid| config.enabled && exists(uuid))
+    }
+}
+
+/// Call `tick` with the seconds left, every second down to 0, until it
+/// returns false.
+pub fn countdown(tick: impl Fn(u32) -> bool + Send + 'static) {
+    thread::spawn(move || {
+        for left in (0..=COUNTDOWN).rev() {
+            if !tick(left) || left == 0 {
+                return;
+            }
+            thread::sleep(Duration::from_secs(1));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resumes_the_last_game_when_on() {
+        let path = std::env::temp_dir().join(format!("anubis-session-{}.yaml", std::process::id()));
+        assert_eq!(SessionState::load(&path).unwrap(), SessionState::default());
+        let state = SessionState {
+            last_game: Some("a".to_owned()),
+        };
+        state.save(&path).unwrap();
+        let state = SessionState::load(&path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let on = ResumeConfig { enabled: true };
+        assert_eq!(state.to_resume(&on, |_| true), Some("a"));
+        assert_eq!(state.to_resume(&ResumeConfig::default(), |_| true), None);
+        // Removed from the library meanwhile.
+        assert_eq!(state.to_resume(&on, |_| false), None);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::paths;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// Quick resume: the game being played can be paused with SIGSTOP to go back
+// to the launcher, and picked up where it was later. Stopped games keep all
+// their memory, so only a few are kept, and the oldest is quit when memory
+// runs low rather than leaving the kernel to kill something at random. Games
+// run in their own process group, so what they started is paused with them.
+// Steam games can't be, they're the client's once it has them.
+
+/// ```yaml
+/// max_suspended: 2
+/// # Below this, the oldest suspended game is quit.
+/// min_available_mb: 1024
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SuspendConfig {
+    pub max_suspended: usize,
+    pub min_available_mb: u64,
+}
+
+impl Default for SuspendConfig {
+    fn default() -> Self {
+        Self {
+            max_suspended: 2,
+            min_available_mb: 1024,
+        }
+    }
+}
+
+impl SuspendConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::config_dir().join("suspend.yaml")
+    }
+
+    /// Whether memory is too low to keep games suspended.
+    pub fn under_pressure(&self, available_bytes: u64) -> bool {
+        available_bytes < self.min_available_mb * 1024 * 1024
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuspendedGame {
+    pub uuid: String,
+    /// Of the process group.
+    pub pid: u32,
+    pub since: DateTime<Utc>,
+}
+
+/// `MemAvailable` of `/proc/meminfo`, in bytes.
+fn parse_meminfo(meminfo: &str) -> Option<u64> {
+    let line = meminfo.lines().find(|l| l.starts_with("MemAvailable:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+pub fn available_memory() -> Option<u64> {
+    parse_meminfo(&fs::read_to_string("/proc/meminfo").ok()?)
+}
+
+/// Send a signal, e.g. `STOP`, to the process group of a game.
+pub fn signal_group(pid: u32, signal: &str) -> Result<()> {
+    let status = Command::new("kill")
+        .arg(format!("-{}", signal))
+        .arg("--")
+        .arg(format!("-{}", pid))
+        .status()?;
+    if !status.success() {
+        bail!("kill -{} exited with {}", signal, status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_memory_available() {
+        let meminfo = "MemTotal:       16000000 kB\n\
+                       MemFree:          500000 kB\n\
+                       MemAvailable:     800000 kB\n";
+        let available = parse_meminfo(meminfo).unwrap();
+        assert_eq!(available, 800_000 * 1024);
+        assert!(SuspendConfig::default().under_pressure(available));
+        assert!(!SuspendConfig::default().under_pressure(2 * available));
+        assert_eq!(parse_meminfo("MemTotal: 1 kB\n"), None);
+    }
+}
@@ -0,0 +1,524 @@
+use super::hooks::{HookOutcome, HookStage, LaunchHook, ScriptHook};
+use crate::models::GameMetadata;
+use anyhow::{anyhow, bail, Result};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    thread,
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+
+// Save sync providers, run as launch hooks.
+//
+// To detect conflicts, each synced save directory gets a marker file holding
+// what both sides had at the last successful sync. If both the local
+// directory and the remote changed since, it's a conflict and the user has to
+// pick a side. Changes aren't marked as seen until they're copied.
+
+/// Name of the marker file in the local save directory.
+const MARKER_FILE: &str = ".anubis-sync";
+/// How long to wait for Syncthing to finish syncing a folder.
+const SYNCTHING_TIMEOUT: Duration = Duration::from_secs(120);
+const SYNCTHING_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Which side of a conflict to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictSide {
+    Local,
+    Remote,
+}
+
+/// A hook which can also resolve the conflicts it reports.
+pub trait SyncProvider: LaunchHook {
+    fn resolve(&self, game: &GameMetadata, keep: ConflictSide) -> Result<()>;
+}
+
+fn default_syncthing_url() -> String {
+    "http://127.0.0.1:8384".to_owned()
+}
+
+/// Sync settings of a game, as in the config file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SyncProviderConfig {
+    /// Sync a local directory with an rclone remote, e.g. `gdrive:saves/celeste`.
+    Rclone { remote: String, local: PathBuf },
+    /// Wait for a Syncthing folder to be in sync.
+    Syncthing {
+        folder: String,
+        local: PathBuf,
+        #[serde(default = "default_syncthing_url")]
+        api_url: String,
+        api_key: String,
+    },
+    /// Custom scripts, see `ScriptHook`.
+    Script {
+        pre_launch: Option<String>,
+        post_exit: Option<String>,
+    },
+}
+
+/// Per game sync settings, keyed by the game UUID.
+///
+/// ```yaml
+/// games:
+///   0a1b2c3d:
+///     - type: rclone
+///       remote: gdrive:saves/celeste
+///       local: /home/deck/.local/share/Celeste/Saves
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SyncConfig {
+    #[serde(default)]
+    pub games: HashMap<String, Vec<SyncProviderConfig>>,
+}
+
+impl SyncConfig {
+    /// Load the config, a missing file means no sync.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn providers_for(&self, uuid: &str) -> Vec<Box<dyn SyncProvider>> {
+        self.games
+            .get(uuid)
+            .map(|configs| {
+                configs
+                    .iter()
+                    .map(|c| -> Box<dyn SyncProvider> {
+                        match c.clone() {
+                            SyncProviderConfig::Rclone { remote, local } => {
+                                Box::new(RcloneSync { remote, local })
+                            }
+                            SyncProviderConfig::Syncthing {
+                                folder,
+                                local,
+                                api_url,
+                                api_key,
+                            } => Box::new(SyncthingFolder {
+                                folder,
+                                local,
+                                api_url,
+                                api_key,
+                            }),
+                            SyncProviderConfig::Script {
+                                pre_launch,
+                                post_exit,
+                            } => Box::new(ScriptHook {
+                                pre_launch,
+                                post_exit,
+                            }),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// What each side had at the last sync, see `MARKER_FILE`.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Marker {
+    local: Listing,
+    remote: Listing,
+}
+
+/// A fingerprint of each file, by its path in the directory.
+type Listing = BTreeMap<String, String>;
+
+fn read_marker(local: &Path) -> Option<Marker> {
+    let contents = fs::read_to_string(local.join(MARKER_FILE)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn write_marker(local: &Path, marker: &Marker) -> Result<()> {
+    fs::create_dir_all(local)?;
+    fs::write(local.join(MARKER_FILE), serde_json::to_string(marker)?)?;
+    Ok(())
+}
+
+/// The files in the directory, recursively, by size and modification time.
+fn local_listing(dir: &Path) -> Result<Listing> {
+    fn walk(dir: &Path, prefix: &str, listing: &mut Listing) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name == MARKER_FILE {
+                continue;
+            }
+            let path = format!("{}{}", prefix, name);
+            if entry.file_type()?.is_dir() {
+                walk(&entry.path(), &format!("{}/", path), listing)?;
+            } else {
+                let meta = entry.metadata()?;
+                let mtime = meta.modified()?.duration_since(UNIX_EPOCH)?.as_nanos();
+                listing.insert(path, format!("{} {}", meta.len(), mtime));
+            }
+        }
+        Ok(())
+    }
+    let mut listing = Listing::new();
+    if dir.exists() {
+        walk(dir, "", &mut listing)?;
+    }
+    Ok(listing)
+}
+
+/// Whether a side changed since the last sync. Without a previous sync, any
+/// content counts as a change.
+fn changed(now: &Listing, synced: Option<&Listing>) -> bool {
+    match synced {
+        Some(synced) => now != synced,
+        None => !now.is_empty(),
+    }
+}
+
+pub struct RcloneSync {
+    remote: String,
+    local: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RcloneEntry {
+    path: String,
+    size: i64,
+    mod_time: String,
+    /// Those the remote has, none on some.
+    #[serde(default)]
+    hashes: BTreeMap<String, String>,
+}
+
+/// Of `rclone lsjson --hash`, by hash when the remote has them. Copies keep
+/// the modification time, it can be older than the last sync.
+fn parse_remote_listing(json: &[u8]) -> Result<Listing> {
+    let entries: Vec<RcloneEntry> = serde_json::from_slice(json)?;
+    Ok(entries
+        .into_iter()
+        .map(|e| {
+            let fingerprint = if e.hashes.is_empty() {
+                format!("{} {}", e.size, e.mod_time)
+            } else {
+                let hashes: Vec<String> = e
+                    .hashes
+                    .iter()
+                    .map(|(k, v)| format!("{}:{}", k, v))
+                    .collect();
+                hashes.join(" ")
+            };
+            (e.path, fingerprint)
+        })
+        .collect())
+}
+
+impl RcloneSync {
+    fn remote_listing(&self) -> Result<Listing> {
+        let output = Command::new("rclone")
+            .args([
+                "lsjson",
+                "--recursive",
+                "--files-only",
+                "--hash",
+                &self.remote,
+            ])
+            .output()?;
+        if !output.status.success() {
+            // A remote which doesn't exist yet has nothing.
+            debug!(
+                "rclone lsjson {} failed: {}",
+                self.remote,
+                String::from_utf8_lossy(&output.stderr)
+            );
+            return Ok(Listing::new());
+        }
+        parse_remote_listing(&output.stdout)
+    }
+
+    /// Both sides as they are now, in sync.
+    fn mark(&self) -> Result<()> {
+        let marker = Marker {
+            local: local_listing(&self.local)?,
+            remote: self.remote_listing()?,
+        };
+        write_marker(&self.local, &marker)
+    }
+
+    fn copy(&self, from: &str, to: &str) -> Result<()> {
+        let status = Command::new("rclone")
+            .args(["copy", "--exclude", MARKER_FILE, from, to])
+            .status()?;
+        if !status.success() {
+            bail!("rclone copy {} {} exited with {}", from, to, status);
+        }
+        Ok(())
+    }
+
+    fn local_str(&self) -> Result<&str> {
+        self.local
+            .to_str()
+            .ok_or(anyhow!("invalid path {:?}", self.local))
+    }
+}
+
+impl LaunchHook for RcloneSync {
+    fn name(&self) -> &str {
+        "rclone"
+    }
+
+    fn run(&self, stage: HookStage, _game: &GameMetadata) -> Result<HookOutcome> {
+        let marker = read_marker(&self.local);
+        let local_changed = changed(
+            &local_listing(&self.local)?,
+            marker.as_ref().map(|m| &m.local),
+        );
+        let remote_changed = changed(&self.remote_listing()?, marker.as_ref().map(|m| &m.remote));
+
+        if local_changed && remote_changed {
+            return Ok(HookOutcome::Conflict(format!(
+                "{} and {} both changed since the last sync",
+                self.local.display(),
+                self.remote
+            )));
+        }
+        match stage {
+            HookStage::PreLaunch if remote_changed => self.copy(&self.remote, self.local_str()?)?,
+            HookStage::PostExit if local_changed => self.copy(self.local_str()?, &self.remote)?,
+            // What wasn't copied stays a change, for the next time.
+            _ => return Ok(HookOutcome::Done),
+        }
+        self.mark()?;
+        Ok(HookOutcome::Done)
+    }
+}
+
+impl SyncProvider for RcloneSync {
+    fn resolve(&self, _game: &GameMetadata, keep: ConflictSide) -> Result<()> {
+        match keep {
+            ConflictSide::Local => self.copy(self.local_str()?, &self.remote)?,
+            ConflictSide::Remote => self.copy(&self.remote, self.local_str()?)?,
+        }
+        self.mark()
+    }
+}
+
+pub struct SyncthingFolder {
+    folder: String,
+    local: PathBuf,
+    api_url: String,
+    api_key: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncthingFolderStatus {
+    state: String,
+    need_bytes: u64,
+}
+
+impl SyncthingFolder {
+    fn status(&self) -> Result<SyncthingFolderStatus> {
+        Ok(ureq::get(&format!("{}/rest/db/status", self.api_url))
+            .query("folder", &self.folder)
+            .set("X-API-Key", &self.api_key)
+            .call()?
+            .into_json()?)
+    }
+
+    fn wait_until_idle(&self) -> Result<()> {
+        let started = Instant::now();
+        loop {
+            let status = self.status()?;
+            if status.state == "idle" && status.need_bytes == 0 {
+                return Ok(());
+            }
+            if started.elapsed() > SYNCTHING_TIMEOUT {
+                bail!("syncthing folder {} is still {}", self.folder, status.state);
+            }
+            thread::sleep(SYNCTHING_POLL_INTERVAL);
+        }
+    }
+
+    /// Syncthing keeps both versions, the losing one is renamed to
+    /// `${NAME}.sync-conflict-${DATE}-${TIME}-${DEVICE}.${EXT}`.
+    fn conflict_files(dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut found = vec![];
+        if !dir.exists() {
+            return Ok(found);
+        }
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                found.extend(Self::conflict_files(&entry.path())?);
+            } else if entry
+                .file_name()
+                .to_string_lossy()
+                .contains(".sync-conflict-")
+            {
+                found.push(entry.path());
+            }
+        }
+        Ok(found)
+    }
+}
+
+impl LaunchHook for SyncthingFolder {
+    fn name(&self) -> &str {
+        "syncthing"
+    }
+
+    fn run(&self, stage: HookStage, _game: &GameMetadata) -> Result<HookOutcome> {
+        if stage == HookStage::PostExit {
+            // Pick up the new saves now instead of on the next periodic scan.
+            ureq::post(&format!("{}/rest/db/scan", self.api_url))
+                .query("folder", &self.folder)
+                .set("X-API-Key", &self.api_key)
+                .call()?;
+        }
+        self.wait_until_idle()?;
+
+        let conflicts = Self::conflict_files(&self.local)?;
+        if conflicts.is_empty() {
+            Ok(HookOutcome::Done)
+        } else {
+            Ok(HookOutcome::Conflict(format!(
+                "{} conflicting files in {}",
+                conflicts.len(),
+                self.local.display()
+            )))
+        }
+    }
+}
+
+impl SyncProvider for SyncthingFolder {
+    fn resolve(&self, _game: &GameMetadata, keep: ConflictSide) -> Result<()> {
+        for conflict in Self::conflict_files(&self.local)? {
+            match keep {
+                // The conflict copy holds the other device's version.
+                ConflictSide::Local => fs::remove_file(&conflict)?,
+                ConflictSide::Remote => {
+                    let name = conflict.to_string_lossy();
+                    let (stem, rest) = name
+                        .split_once(".sync-conflict-")
+                        .ok_or(anyhow!("unexpected conflict file {}", name))?;
+                    // Keep the extension, if any, after the conflict suffix.
+                    let original = match rest.split_once('.') {
+                        Some((_, ext)) => format!("{}.{}", stem, ext),
+                        None => stem.to_owned(),
+                    };
+                    fs::rename(&conflict, original)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SyncProvider for ScriptHook {
+    /// Runs the post exit script with `ANUBIS_HOOK_STAGE=resolve` and
+    /// `ANUBIS_CONFLICT_KEEP` set to `local` or `remote`.
+    fn resolve(&self, game: &GameMetadata, keep: ConflictSide) -> Result<()> {
+        let Some(ref script) = self.post_exit else {
+            bail!("no script to resolve the conflict with");
+        };
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .env("ANUBIS_GAME_UUID", &game.uuid)
+            .env("ANUBIS_GAME_TITLE", &game.title)
+            .env("ANUBIS_HOOK_STAGE", "resolve")
+            .env(
+                "ANUBIS_CONFLICT_KEEP",
+                match keep {
+                    ConflictSide::Local => "local",
+                    ConflictSide::Remote => "remote",
+                },
+            )
+            .status()?;
+        if !status.success() {
+            bail!("script exited with {}", status);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_config() {
+        let config: SyncConfig = serde_yaml::from_str(
+            r#"
+games:
+  aaaa:
+    - type: rclone
+      remote: gdrive:saves/aaaa
+      local: /tmp/aaaa
+    - type: syncthing
+      folder: abcd-1234
+      local: /tmp/aaaa
+      api_key: secret
+    - type: script
+      post_exit: echo hi
+"#,
+        )
+        .unwrap();
+        let providers = config.providers_for("aaaa");
+        assert_eq!(
+            providers.iter().map(|p| p.name()).collect::<Vec<_>>(),
+            vec!["rclone", "syncthing", "script"]
+        );
+        assert!(config.providers_for("bbbb").is_empty());
+        if let SyncProviderConfig::Syncthing { ref api_url, .. } = config.games["aaaa"][1] {
+            assert_eq!(api_url, "http://127.0.0.1:8384");
+        } else {
+            panic!("unexpected config {:?}", config)
+        }
+    }
+
+    #[test]
+    fn detects_changes_since_last_sync() {
+        let synced = parse_remote_listing(
+            br#"[{"Path": "slot1/save.dat", "Size": 4, "ModTime": "2024-01-02T00:00:00Z",
+                  "Hashes": {"md5": "8d777f385d3dfec8815d20f7496026dc"}}]"#,
+        )
+        .unwrap();
+        // Pushed from another device, older than this one's last sync.
+        let pushed = parse_remote_listing(
+            br#"[{"Path": "slot1/save.dat", "Size": 4, "ModTime": "2024-01-01T00:00:00Z",
+                  "Hashes": {"md5": "c5c5a1ec9a9e23c2ea8c9d2e8a9e3a5b"}}]"#,
+        )
+        .unwrap();
+        assert!(changed(&pushed, Some(&synced)));
+        assert!(!changed(&synced, Some(&synced)));
+        assert!(changed(&synced, None));
+        assert!(!changed(&Listing::new(), None));
+        let unhashed = parse_remote_listing(
+            br#"[{"Path": "save.dat", "Size": 4, "ModTime": "2024-01-01T00:00:00Z"}]"#,
+        )
+        .unwrap();
+        assert_eq!(unhashed["save.dat"], "4 2024-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn local_changes_ignore_the_marker() {
+        let dir = std::env::temp_dir().join(format!("anubis-sync-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write_marker(&dir, &Marker::default()).unwrap();
+        assert!(local_listing(&dir).unwrap().is_empty());
+        assert_eq!(read_marker(&dir), Some(Marker::default()));
+
+        fs::create_dir_all(dir.join("slot1")).unwrap();
+        fs::write(dir.join("slot1/save.dat"), "data").unwrap();
+        let listing = local_listing(&dir).unwrap();
+        assert_eq!(listing.keys().collect::<Vec<_>>(), ["slot1/save.dat"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{models::WebAppConfig, paths};
+use anyhow::{anyhow, Result};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// Games played in the browser, like Xbox Cloud Gaming or GeForce NOW. The page
+// is opened full screen without any browser UI, in a Chromium based browser
+// since Firefox has no app mode. Each profile keeps its own logins.
+
+/// Looked for in order on the PATH.
+const BROWSERS: [&str; 6] = [
+    "chromium",
+    "chromium-browser",
+    "google-chrome-stable",
+    "google-chrome",
+    "microsoft-edge-stable",
+    "brave-browser",
+];
+/// Then as Flatpaks.
+const FLATPAK_BROWSERS: [&str; 4] = [
+    "org.chromium.Chromium",
+    "com.google.Chrome",
+    "com.microsoft.Edge",
+    "com.brave.Browser",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Browser {
+    /// The executable.
+    Native(PathBuf),
+    /// The app ID.
+    Flatpak(String),
+}
+
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|d| d.join(name))
+        .find(|p| p.is_file())
+}
+
+fn flatpak_installed(app_id: &str) -> bool {
+    let user = env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share/flatpak"));
+    [Some(PathBuf::from("/var/lib/flatpak")), user]
+        .into_iter()
+        .flatten()
+        .any(|d| d.join("app").join(app_id).is_dir())
+}
+
+pub fn find_browser() -> Option<Browser> {
+    BROWSERS
+        .iter()
+        .find_map(|b| find_on_path(b))
+        .map(Browser::Native)
+        .or_else(|| {
+            FLATPAK_BROWSERS
+                .iter()
+                .find(|id| flatpak_installed(id))
+                .map(|id| Browser::Flatpak(id.to_string()))
+        })
+}
+
+/// Where the profile of a web app is kept.
+pub fn profile_dir(config: &WebAppConfig, uuid: &str) -> PathBuf {
+    let name = config.profile.as_deref().unwrap_or(uuid);
+    paths::data_dir().join("browser-profiles").join(name)
+}
+
+/// The browser opening the page of the config.
+pub fn command(config: &WebAppConfig, uuid: &str) -> Result<Command> {
+    let browser = find_browser().ok_or(anyhow!("no Chromium based browser found"))?;
+    let profile = profile_dir(config, uuid);
+    fs::create_dir_all(&profile)?;
+    Ok(command_with(&browser, config, &profile))
+}
+
+fn command_with(browser: &Browser, config: &WebAppConfig, profile: &Path) -> Command {
+    let mut cmd = match browser {
+        Browser::Native(path) => Command::new(path),
+        Browser::Flatpak(app_id) => {
+            let mut cmd = Command::new("flatpak");
+            cmd.arg("run")
+                .arg(format!("--filesystem={}", profile.display()));
+            // The sandbox only has the input devices with this.
+            if config.gamepad {
+                cmd.arg("--device=all");
+            }
+            cmd.arg(app_id);
+            cmd
+        }
+    };
+    cmd.arg("--kiosk")
+        .arg("--no-first-run")
+        .arg(format!("--user-data-dir={}", profile.display()))
+        .arg(format!("--app={}", config.url));
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_the_page_in_kiosk_mode() {
+        let config = WebAppConfig {
+            url: "https://www.xbox.com/play".to_owned(),
+            profile: Some("xbox".to_owned()),
+            gamepad: true,
+        };
+        let profile = Path::new("/data/browser-profiles/xbox");
+        let args = |cmd: &Command| -> Vec<String> {
+            cmd.get_args()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect()
+        };
+
+        let native = command_with(
+            &Browser::Native("/usr/bin/chromium".into()),
+            &config,
+            profile,
+        );
+        assert_eq!(native.get_program(), "/usr/bin/chromium");
+        assert_eq!(
+            args(&native),
+            [
+                "--kiosk",
+                "--no-first-run",
+                "--user-data-dir=/data/browser-profiles/xbox",
+                "--app=https://www.xbox.com/play",
+            ]
+        );
+
+        let flatpak = command_with(
+            &Browser::Flatpak("com.google.Chrome".to_owned()),
+            &config,
+            profile,
+        );
+        assert_eq!(
+            args(&flatpak)[..4],
+            [
+                "run",
+                "--filesystem=/data/browser-profiles/xbox",
+                "--device=all",
+                "com.google.Chrome",
+            ]
+        );
+        let no_gamepad = WebAppConfig {
+            gamepad: false,
+            ..config
+        };
+        let flatpak = command_with(
+            &Browser::Flatpak("com.google.Chrome".to_owned()),
+            &no_gamepad,
+            profile,
+        );
+        assert!(!args(&flatpak).contains(&"--device=all".to_owned()));
+    }
+}
@@ -0,0 +1,212 @@
+use super::{migrations, Library};
+use crate::{
+    models::{GameMetadata, ImageSource},
+    paths,
+};
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Bumped when older versions can't read the backups anymore.
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Portable copy of the library, saved as JSON.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Backup {
+    pub version: u32,
+    /// Of the games, see `library::migrations`.
+    pub schema_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub games: Vec<GameMetadata>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreMode {
+    /// Add the missing games, and combine the ones in both.
+    Merge,
+    /// Drop the library, use the backup instead.
+    Replace,
+}
+
+/// Replace the art file with its content, so the backup works elsewhere.
+fn embed_art(source: &mut Option<ImageSource>) {
+    if let Some(ImageSource::FilePath(path)) = source {
+        match fs::read(&path) {
+            Ok(data) => *source = Some(ImageSource::Base64(STANDARD.encode(data))),
+            Err(e) => warn!("failed to embed art {}: {:?}", path, e),
+        }
+    }
+}
+
+impl Backup {
+    /// Copy of the library. Without the art, the art files are left as paths,
+    /// which may not exist when restoring on another device.
+    pub fn new(library: &Library, with_art: bool) -> Self {
+        let mut games: Vec<_> = library.iter().cloned().collect();
+        if with_art {
+            for game in games.iter_mut() {
+                embed_art(&mut game.cover_art);
+                embed_art(&mut game.bg_art);
+            }
+        }
+        Self {
+            version: BACKUP_VERSION,
+            schema_version: migrations::SCHEMA_VERSION,
+            exported_at: Utc::now(),
+            games,
+        }
+    }
+
+    /// `$XDG_DATA_HOME/anubis/backups`, where the settings screen puts them.
+    pub fn default_dir() -> PathBuf {
+        paths::data_dir().join("backups")
+    }
+
+    /// e.g. `library-20240131-201500.json`, sorts by date.
+    pub fn file_name(&self) -> String {
+        format!("library-{}.json", self.exported_at.format("%Y%m%d-%H%M%S"))
+    }
+
+    /// The most recent backup in the dir.
+    pub fn latest(dir: &Path) -> Option<PathBuf> {
+        fs::read_dir(dir)
+            .ok()?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().map(|e| e == "json").unwrap_or_default())
+            .max()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut saved: serde_json::Value = serde_json::from_slice(&fs::read(path)?)?;
+        migrations::migrate(&mut saved).map_err(|e| anyhow!("{:?}: {}", path, e))?;
+        let backup: Self = serde_json::from_value(saved)?;
+        if backup.version > BACKUP_VERSION {
+            bail!(
+                "{:?} is from a newer version of anubis (format {})",
+                path,
+                backup.version
+            );
+        }
+        Ok(backup)
+    }
+}
+
+/// Keep what's in the library, add what only the backup knows.
+fn merge(game: &mut GameMetadata, saved: GameMetadata) {
+    game.playtime = game.playtime.max(saved.playtime);
+    game.favorate |= saved.favorate;
+    for tag in saved.tags {
+        if !game.tags.contains(&tag) {
+            game.tags.push(tag);
+        }
+    }
+    if game.cover_art.is_none() {
+        game.cover_art = saved.cover_art;
+    }
+    if game.bg_art.is_none() {
+        game.bg_art = saved.bg_art;
+    }
+}
+
+impl Library {
+    /// Restore a backup, returns how many games were added and merged.
+    pub fn restore(&mut self, backup: Backup, mode: RestoreMode) -> Result<(usize, usize)> {
+        if mode == RestoreMode::Replace {
+            // Validate before dropping anything.
+            let mut restored = Library::new();
+            for game in backup.games {
+                restored.insert(game)?;
+            }
+            self.games = restored.games;
+            self.identities = restored.identities;
+            return Ok((self.games.len(), 0));
+        }
+        let (mut added, mut merged) = (0, 0);
+        for saved in backup.games {
+            match self.get_mut(&saved.uuid) {
+                Some(game) => {
+                    merge(game, saved);
+                    merged += 1;
+                }
+                None => {
+                    self.insert(saved)?;
+                    added += 1;
+                }
+            }
+        }
+        Ok((added, merged))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(uuid: &str, tags: &[&str], minutes: i64) -> GameMetadata {
+        GameMetadata {
+            title: uuid.to_owned(),
+            uuid: uuid.to_owned(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            playtime: Some(chrono::Duration::minutes(minutes)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn embeds_art() {
+        let art = std::env::temp_dir().join(format!("anubis-backup-art-{}", std::process::id()));
+        fs::write(&art, b"png").unwrap();
+        let mut library = Library::new();
+        let mut g = game("a", &[], 0);
+        g.cover_art = Some(ImageSource::FilePath(art.to_string_lossy().into_owned()));
+        library.insert(g).unwrap();
+
+        let backup = Backup::new(&library, true);
+        assert_eq!(
+            backup.games[0].cover_art,
+            Some(ImageSource::Base64("cG5n".to_owned()))
+        );
+        assert!(backup.file_name().starts_with("library-"));
+        fs::remove_file(&art).unwrap();
+    }
+
+    #[test]
+    fn merges_and_replaces() {
+        let mut library = Library::new();
+        library.insert(game("a", &["rpg"], 30)).unwrap();
+        let backup = || Backup {
+            version: BACKUP_VERSION,
+            schema_version: migrations::SCHEMA_VERSION,
+            exported_at: Utc::now(),
+            games: vec![game("a", &["rpg", "done"], 90), game("b", &[], 0)],
+        };
+
+        assert_eq!(
+            library.restore(backup(), RestoreMode::Merge).unwrap(),
+            (1, 1)
+        );
+        let a = library.get("a").unwrap();
+        assert_eq!(a.tags, vec!["rpg", "done"]);
+        assert_eq!(a.playtime, Some(chrono::Duration::minutes(90)));
+        assert!(library.get("b").is_some());
+
+        library.insert(game("c", &[], 0)).unwrap();
+        library.restore(backup(), RestoreMode::Replace).unwrap();
+        assert!(library.get("c").is_none());
+        assert_eq!(library.iter().count(), 2);
+    }
+}
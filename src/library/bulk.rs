@@ -0,0 +1,190 @@
+use super::{
+    matches::Matches,
+    scrape::{Scraper, Source},
+    Library,
+};
+use crate::{models::GameMetadata, transfer::TransferContext};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use log::warn;
+use std::{collections::VecDeque, sync::Mutex};
+
+// Changes to the games selected on the home screen, all at once. Run as a
+// task, the tasks screen shows how many are done. Paused, it goes on with
+// the games left on resume.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BulkAction {
+    /// Their metadata fetched again, see `scrape`. What it had goes to the
+    /// trash.
+    Rescrape,
+    AddTag(String),
+    /// Into the collection, out of the one it was in.
+    MoveTo(String),
+    /// Out of the library into the trash, their files stay on disk.
+    Delete,
+}
+
+impl BulkAction {
+    /// Of the task, e.g. "Add tag coop".
+    pub fn describe(&self) -> String {
+        match self {
+            BulkAction::Rescrape => "Rescrape".to_owned(),
+            BulkAction::AddTag(tag) => format!("Add tag {}", tag),
+            BulkAction::MoveTo(collection) => format!("Move to {}", collection),
+            BulkAction::Delete => "Delete".to_owned(),
+        }
+    }
+}
+
+/// Edit the game for the action, but the ones which aren't just an edit.
+/// False when it's unchanged.
+pub fn edit(game: &mut GameMetadata, action: &BulkAction) -> bool {
+    match action {
+        BulkAction::AddTag(tag) => {
+            if game.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                return false;
+            }
+            game.tags.push(tag.clone());
+            true
+        }
+        BulkAction::MoveTo(collection) => {
+            if game.collection.as_ref() == Some(collection) {
+                return false;
+            }
+            game.collection = Some(collection.clone());
+            true
+        }
+        BulkAction::Rescrape | BulkAction::Delete => false,
+    }
+}
+
+/// What a run did, for the tiles and the toast.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outcome {
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+    pub failed: usize,
+    /// Games selected, all runs together.
+    pub total: usize,
+    /// False when stopped before the last game.
+    pub finished: bool,
+}
+
+#[derive(Debug)]
+pub struct Bulk {
+    pub action: BulkAction,
+    /// By UUID.
+    left: VecDeque<String>,
+    total: usize,
+}
+
+impl Bulk {
+    pub fn new(action: BulkAction, uuids: Vec<String>) -> Self {
+        Self {
+            action,
+            total: uuids.len(),
+            left: uuids.into(),
+        }
+    }
+
+    /// Go through the games left until done or told to stop, blocking on the
+    /// network to rescrape. What's done is saved.
+    pub fn run(
+        &mut self,
+        ctx: &TransferContext,
+        library: &Mutex<Library>,
+        locale: &str,
+    ) -> Result<Outcome> {
+        let matches = match self.action {
+            BulkAction::Rescrape => Matches::load(&Matches::default_path())?,
+            _ => Matches::default(),
+        };
+        let mut scraper = Scraper::new(locale);
+        let mut outcome = Outcome {
+            total: self.total,
+            ..Default::default()
+        };
+        while let Some(uuid) = self.left.front().cloned() {
+            if ctx.should_stop() {
+                break;
+            }
+            let done = self.total - self.left.len();
+            ctx.set_phase(Some(&format!("{} of {}", done + 1, self.total)));
+            match self.apply(&uuid, library, &matches, &mut scraper) {
+                Ok(true) if self.action == BulkAction::Delete => outcome.removed.push(uuid),
+                Ok(true) => outcome.changed.push(uuid),
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("failed to {:?} {}: {:#}", self.action, uuid, e);
+                    outcome.failed += 1;
+                }
+            }
+            self.left.pop_front();
+            ctx.report_progress((done + 1) as f32 / self.total as f32);
+        }
+        library.lock().unwrap().save()?;
+        outcome.finished = self.left.is_empty();
+        Ok(outcome)
+    }
+
+    /// False when there was nothing to do, e.g. a game gone since.
+    fn apply(
+        &self,
+        uuid: &str,
+        library: &Mutex<Library>,
+        matches: &Matches,
+        scraper: &mut Scraper,
+    ) -> Result<bool> {
+        let details = match self.action {
+            BulkAction::Rescrape => {
+                let Some(game) = library.lock().unwrap().get(uuid).cloned() else {
+                    return Ok(false);
+                };
+                let source = Source::of(&game, matches)
+                    .ok_or(anyhow!("no metadata source for {}", game.title))?;
+                Some(scraper.fetch(&source)?)
+            }
+            _ => None,
+        };
+        let mut library = library.lock().unwrap();
+        if self.action == BulkAction::Delete {
+            return Ok(library.trash(uuid, Utc::now()));
+        }
+        if details.is_some() && !library.trash_metadata(uuid, Utc::now()) {
+            return Ok(false);
+        }
+        let Some(game) = library.get_mut(uuid) else {
+            return Ok(false);
+        };
+        match details {
+            Some(details) => {
+                scraper.apply(game, details, true);
+                Ok(true)
+            }
+            None => Ok(edit(game, &self.action)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_and_moves_games_once() {
+        let mut game = GameMetadata {
+            tags: vec!["Coop".to_owned()],
+            ..Default::default()
+        };
+        assert!(!edit(&mut game, &BulkAction::AddTag("coop".to_owned())));
+        assert!(edit(&mut game, &BulkAction::AddTag("couch".to_owned())));
+        assert_eq!(game.tags, ["Coop", "couch"]);
+
+        let move_to = BulkAction::MoveTo("Retro".to_owned());
+        assert!(edit(&mut game, &move_to));
+        assert!(!edit(&mut game, &move_to));
+        assert_eq!(game.collection.as_deref(), Some("Retro"));
+        assert!(!edit(&mut game, &BulkAction::Delete));
+    }
+}
@@ -0,0 +1,175 @@
+use super::Library;
+use crate::models::GameMetadata;
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+
+// The same game imported from several sources, e.g. Steam and a ROM dump,
+// shows up twice. Entries are considered the same when their normalized
+// titles and platforms match.
+
+/// Lower case, without symbols and a leading "the", e.g.
+/// `The Witcher® 3: Wild Hunt` gives `witcher 3 wild hunt`.
+pub fn normalize_title(title: &str) -> String {
+    let cleaned: String = title
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    let words: Vec<_> = cleaned.split_whitespace().collect();
+    match words[..] {
+        ["the", ref rest @ ..] if !rest.is_empty() => rest.join(" "),
+        _ => words.join(" "),
+    }
+}
+
+fn push_missing<T: PartialEq>(into: &mut Vec<T>, from: Vec<T>) {
+    for item in from {
+        if !into.contains(&item) {
+            into.push(item);
+        }
+    }
+}
+
+/// Fold a duplicate into the game. Playtimes add up, lists are combined,
+/// and the game keeps its own metadata where it has some.
+pub fn merge_into(game: &mut GameMetadata, duplicate: GameMetadata) {
+    game.playtime = match (game.playtime, duplicate.playtime) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    };
+    game.favorate |= duplicate.favorate;
+    game.overlay |= duplicate.overlay;
+    push_missing(&mut game.tags, duplicate.tags);
+    push_missing(&mut game.genres, duplicate.genres);
+    push_missing(&mut game.links, duplicate.links);
+    if game.cover_art.is_none() {
+        game.cover_art = duplicate.cover_art;
+    }
+    if game.bg_art.is_none() {
+        game.bg_art = duplicate.bg_art;
+    }
+    if game.desc.is_none() {
+        game.desc = duplicate.desc;
+    }
+    if game.developers.is_empty() {
+        game.developers = duplicate.developers;
+    }
+    if game.publishers.is_empty() {
+        game.publishers = duplicate.publishers;
+    }
+    if game.relase_date.is_none() {
+        game.relase_date = duplicate.relase_date;
+    }
+    if game.retroachievements_id.is_none() {
+        game.retroachievements_id = duplicate.retroachievements_id;
+    }
+    if game.compat.is_none() {
+        game.compat = duplicate.compat;
+    }
+    if game.web_app.is_none() {
+        game.web_app = duplicate.web_app;
+    }
+    if game.root.is_none() {
+        game.root = duplicate.root;
+    }
+}
+
+impl Library {
+    /// UUIDs of the games which look like the same one, in library order.
+    pub fn duplicates(&self) -> Vec<Vec<String>> {
+        let mut groups: Vec<Vec<String>> = vec![];
+        let mut by_key: HashMap<(String, String), usize> = HashMap::new();
+        for game in self.games.iter() {
+            let uuid = game.uuid.clone();
+            let key = (
+                normalize_title(&game.title),
+                game.platform.as_deref().unwrap_or_default().to_lowercase(),
+            );
+            match by_key.get(&key) {
+                Some(&i) => groups[i].push(uuid),
+                None => {
+                    by_key.insert(key, groups.len());
+                    groups.push(vec![uuid]);
+                }
+            }
+        }
+        groups.retain(|g| g.len() > 1);
+        groups
+    }
+
+    pub fn remove(&mut self, uuid: &str) -> Option<GameMetadata> {
+        let i = self.games.iter().position(|g| g.uuid == uuid)?;
+        Some(self.games.remove(i))
+    }
+
+    /// Merge the duplicate into the game kept, the duplicate is removed.
+    pub fn merge(&mut self, keep: &str, duplicate: &str) -> Result<()> {
+        if keep == duplicate {
+            bail!("can't merge {} into itself", keep);
+        }
+        if self.get(keep).is_none() {
+            bail!("no game {}", keep);
+        }
+        let duplicate = self
+            .remove(duplicate)
+            .ok_or(anyhow!("no game {}", duplicate))?;
+        // Importing the duplicate again lands on the game kept.
+        self.identities.reassign(&duplicate.uuid, keep);
+        merge_into(self.get_mut(keep).unwrap(), duplicate);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(uuid: &str, title: &str, platform: &str) -> GameMetadata {
+        GameMetadata {
+            title: title.to_owned(),
+            uuid: uuid.to_owned(),
+            platform: Some(platform.to_owned()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_duplicates() {
+        assert_eq!(
+            normalize_title("The Witcher® 3: Wild Hunt"),
+            "witcher 3 wild hunt"
+        );
+        assert_eq!(normalize_title("The"), "the");
+
+        let mut library = Library::new();
+        library.insert(game("a", "Celeste", "pc")).unwrap();
+        library.insert(game("b", "Hollow Knight", "pc")).unwrap();
+        library.insert(game("c", "CELESTE™", "PC")).unwrap();
+        library.insert(game("d", "Celeste", "switch")).unwrap();
+        assert_eq!(library.duplicates(), vec![vec!["a", "c"]]);
+    }
+
+    #[test]
+    fn merges_duplicates() {
+        let mut library = Library::new();
+        let mut a = game("a", "Celeste", "pc");
+        a.playtime = Some(chrono::Duration::minutes(30));
+        a.tags = vec!["platformer".to_owned()];
+        let mut b = game("b", "Celeste", "pc");
+        b.playtime = Some(chrono::Duration::minutes(60));
+        b.tags = vec!["platformer".to_owned(), "done".to_owned()];
+        b.favorate = true;
+        b.desc = Some("Climb".to_owned());
+        library.insert(a).unwrap();
+        library.insert(b).unwrap();
+
+        library.merge("a", "b").unwrap();
+        assert!(library.get("b").is_none());
+        let a = library.get("a").unwrap();
+        assert_eq!(a.playtime, Some(chrono::Duration::minutes(90)));
+        assert_eq!(a.tags, vec!["platformer", "done"]);
+        assert!(a.favorate);
+        assert_eq!(a.desc.as_deref(), Some("Climb"));
+        assert!(library.merge("a", "a").is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// Games are identified by a UUIDv7 given by the library on insert. The IDs
+// the integrations know a game by, e.g. the steam app ID, are mapped to it so
+// importing again, or after a merge, lands on the same game.
+
+/// A new game UUID, time ordered.
+pub fn new_uuid() -> String {
+    Uuid::now_v7().to_string()
+}
+
+/// `steam:440` gives `("steam", "440")`.
+fn split_source(install_source: &str) -> Option<(&str, &str)> {
+    install_source
+        .split_once(':')
+        .filter(|(integration, id)| !integration.is_empty() && !id.is_empty())
+}
+
+/// Source ID to UUID, per integration.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Identities {
+    #[serde(flatten)]
+    sources: HashMap<String, HashMap<String, String>>,
+}
+
+impl Identities {
+    pub fn lookup(&self, install_source: &str) -> Option<&str> {
+        let (integration, id) = split_source(install_source)?;
+        self.sources.get(integration)?.get(id).map(String::as_str)
+    }
+
+    pub fn record(&mut self, install_source: &str, uuid: &str) {
+        if let Some((integration, id)) = split_source(install_source) {
+            self.sources
+                .entry(integration.to_owned())
+                .or_default()
+                .insert(id.to_owned(), uuid.to_owned());
+        }
+    }
+
+    /// Point the sources of a game to another one, e.g. when merging.
+    pub fn reassign(&mut self, from: &str, to: &str) {
+        for ids in self.sources.values_mut() {
+            for uuid in ids.values_mut() {
+                if uuid == from {
+                    *uuid = to.to_owned();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_sources() {
+        let a = new_uuid();
+        let b = new_uuid();
+        assert_ne!(a, b);
+        assert_eq!(Uuid::parse_str(&a).unwrap().get_version_num(), 7);
+
+        let mut identities = Identities::default();
+        identities.record("steam:440", &a);
+        identities.record("rom:/roms/a.sfc", &b);
+        identities.record("nonsense", &b);
+        assert_eq!(identities.lookup("steam:440"), Some(a.as_str()));
+        assert_eq!(identities.lookup("steam:570"), None);
+
+        identities.reassign(&b, &a);
+        assert_eq!(identities.lookup("rom:/roms/a.sfc"), Some(a.as_str()));
+    }
+}
@@ -0,0 +1,102 @@
+use super::Library;
+
+// Games sorted by title, for the jump strip of the home screen to find the
+// first game starting with a letter without going through them all. Titles
+// are compared ignoring case, those not starting with a letter go under `#`.
+
+/// Where titles not starting with a letter go, before A.
+pub const OTHER: char = '#';
+
+/// The letters of the jump strip, in order.
+pub fn letters() -> impl Iterator<Item = char> {
+    std::iter::once(OTHER).chain('A'..='Z')
+}
+
+/// The letter a title goes under.
+pub fn letter_of(title: &str) -> char {
+    match title.trim_start().chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => c.to_ascii_uppercase(),
+        _ => OTHER,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TitleIndex {
+    /// Letter, lowercase title and UUID, sorted.
+    sorted: Vec<(char, String, String)>,
+}
+
+impl TitleIndex {
+    /// The first game, by title, under the letter.
+    pub fn first_with(&self, letter: char) -> Option<&str> {
+        let i = self.sorted.partition_point(|(l, _, _)| *l < letter);
+        self.sorted
+            .get(i)
+            .filter(|(l, _, _)| *l == letter)
+            .map(|(_, _, uuid)| uuid.as_str())
+    }
+
+    /// Whether any game goes under the letter.
+    pub fn has(&self, letter: char) -> bool {
+        self.first_with(letter).is_some()
+    }
+
+    /// The closest letter with games after `from`, or before it going back.
+    pub fn next_letter(&self, from: char, forward: bool) -> Option<char> {
+        let mut letters: Vec<char> = letters().filter(|&l| self.has(l)).collect();
+        if !forward {
+            letters.reverse();
+        }
+        letters
+            .into_iter()
+            .find(|&l| if forward { l > from } else { l < from })
+    }
+}
+
+impl Library {
+    pub fn title_index(&self) -> TitleIndex {
+        let mut sorted: Vec<_> = self
+            .visible()
+            .map(|g| {
+                let key = g.title.trim_start().to_lowercase();
+                (letter_of(&g.title), key, g.uuid.clone())
+            })
+            .collect();
+        sorted.sort();
+        TitleIndex { sorted }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::GameMetadata;
+
+    #[test]
+    fn finds_the_first_game_of_a_letter() {
+        let mut library = Library::new();
+        for (uuid, title) in [
+            ("c", "celeste"),
+            ("h", "Hades"),
+            ("a", "Celeste Classic"),
+            ("7", "7 Days to Die"),
+        ] {
+            library
+                .insert(GameMetadata {
+                    title: title.to_owned(),
+                    uuid: uuid.to_owned(),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let sut = library.title_index();
+        assert_eq!(sut.first_with('C'), Some("c"));
+        assert_eq!(sut.first_with('H'), Some("h"));
+        assert_eq!(sut.first_with(OTHER), Some("7"));
+        assert_eq!(sut.first_with('D'), None);
+        assert_eq!(sut.next_letter('C', true), Some('H'));
+        assert_eq!(sut.next_letter('C', false), Some(OTHER));
+        assert_eq!(sut.next_letter('H', true), None);
+        assert_eq!(sut.next_letter('Z', false), Some('H'));
+    }
+}
@@ -0,0 +1,476 @@
+use super::{identity, Library};
+use crate::models::GameMetadata;
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+// Every change to the games, appended to a journal next to the library as
+// it's saved, one JSON entry per line. The changes are found by comparing the
+// games with how they were saved last, so edits from anywhere end up in it.
+// `Library::undo` steps back through it, and another device can replay it to
+// catch up, see `Library::replay` and `sync`. The entries replayed are kept
+// too, under the device they're from, to pass them on.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    Added {
+        game: Box<GameMetadata>,
+    },
+    Edited {
+        before: Box<GameMetadata>,
+        after: Box<GameMetadata>,
+    },
+    /// Only the playtime changed, in seconds.
+    Played {
+        uuid: String,
+        title: String,
+        before: Option<i64>,
+        after: Option<i64>,
+    },
+    /// Into the trash.
+    Deleted {
+        game: Box<GameMetadata>,
+    },
+}
+
+impl Change {
+    pub fn uuid(&self) -> &str {
+        match self {
+            Change::Added { game } | Change::Deleted { game } => &game.uuid,
+            Change::Edited { after, .. } => &after.uuid,
+            Change::Played { uuid, .. } => uuid,
+        }
+    }
+
+    /// e.g. "played Celeste".
+    pub fn describe(&self) -> String {
+        match self {
+            Change::Added { game } => format!("added {}", game.title),
+            Change::Edited { after, .. } => format!("edited {}", after.title),
+            Change::Played { title, .. } => format!("played {}", title),
+            Change::Deleted { game } => format!("deleted {}", game.title),
+        }
+    }
+
+    /// The change taking this one back.
+    pub fn inverse(&self) -> Change {
+        match self.clone() {
+            Change::Added { game } => Change::Deleted { game },
+            Change::Deleted { game } => Change::Added { game },
+            Change::Edited { before, after } => Change::Edited {
+                before: after,
+                after: before,
+            },
+            Change::Played {
+                uuid,
+                title,
+                before,
+                after,
+            } => Change::Played {
+                uuid,
+                title,
+                before: after,
+                after: before,
+            },
+        }
+    }
+}
+
+/// The changes of one save.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// Counted per device.
+    pub seq: u64,
+    pub device: String,
+    pub at: DateTime<Utc>,
+    pub changes: Vec<Change>,
+    /// The entry of the same device this one took back.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undoes: Option<u64>,
+}
+
+impl Entry {
+    /// e.g. "2024-05-01 12:00 #3 on 9c1f7e2d: added Celeste, played Hades".
+    pub fn describe(&self) -> String {
+        let mut changes: Vec<_> = self.changes.iter().take(3).map(Change::describe).collect();
+        if self.changes.len() > 3 {
+            changes.push(format!("{} more", self.changes.len() - 3));
+        }
+        let undone = match self.undoes {
+            Some(seq) => format!(" undoing #{}", seq),
+            None => String::new(),
+        };
+        format!(
+            "{} #{} on {}{}: {}",
+            self.at.format("%Y-%m-%d %H:%M"),
+            self.seq,
+            // The random end, UUIDv7 start with the time.
+            &self.device[self.device.len().saturating_sub(8)..],
+            undone,
+            changes.join(", ")
+        )
+    }
+}
+
+/// What changed from the games before to after, in their order.
+pub fn diff(before: &[GameMetadata], after: &[GameMetadata]) -> Vec<Change> {
+    let old: HashMap<_, _> = before.iter().map(|g| (g.uuid.as_str(), g)).collect();
+    let kept: HashSet<_> = after.iter().map(|g| g.uuid.as_str()).collect();
+    let mut changes = vec![];
+    for game in after {
+        match old.get(game.uuid.as_str()) {
+            Some(was) => changes.extend(edit(was, game)),
+            None => changes.push(Change::Added {
+                game: Box::new(game.clone()),
+            }),
+        }
+    }
+    for game in before.iter().filter(|g| !kept.contains(g.uuid.as_str())) {
+        changes.push(Change::Deleted {
+            game: Box::new(game.clone()),
+        });
+    }
+    changes
+}
+
+/// Only the fields the edit changed onto the game, so the ones edited
+/// elsewhere meanwhile stay. False when it had them already.
+fn merge(game: &mut GameMetadata, before: &GameMetadata, after: &GameMetadata) -> Result<bool> {
+    let (Value::Object(before), Value::Object(after), Value::Object(mut fields)) = (
+        serde_json::to_value(before)?,
+        serde_json::to_value(after)?,
+        serde_json::to_value(&*game)?,
+    ) else {
+        bail!("the metadata of {} isn't an object", game.title);
+    };
+    let was = fields.clone();
+    for key in before.keys().chain(after.keys()) {
+        if before.get(key) == after.get(key) {
+            continue;
+        }
+        match after.get(key) {
+            Some(value) => fields.insert(key.clone(), value.clone()),
+            None => fields.remove(key),
+        };
+    }
+    if fields == was {
+        return Ok(false);
+    }
+    *game = serde_json::from_value(Value::Object(fields))?;
+    Ok(true)
+}
+
+fn edit(before: &GameMetadata, after: &GameMetadata) -> Option<Change> {
+    // No PartialEq on the metadata, compared as saved.
+    let value = |game: &GameMetadata| serde_json::to_value(game).ok();
+    if value(before) == value(after) {
+        return None;
+    }
+    let mut played = before.clone();
+    played.playtime = after.playtime;
+    if value(&played) != value(after) {
+        return Some(Change::Edited {
+            before: Box::new(before.clone()),
+            after: Box::new(after.clone()),
+        });
+    }
+    Some(Change::Played {
+        uuid: after.uuid.clone(),
+        title: after.title.clone(),
+        before: before.playtime.map(|d| d.num_seconds()),
+        after: after.playtime.map(|d| d.num_seconds()),
+    })
+}
+
+/// The entries in the file, oldest first. A line cut short by a crash is
+/// skipped.
+pub fn read(path: &Path) -> Result<Vec<Entry>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let entries = fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!("skipping a journal entry in {:?}: {}", path, e);
+                None
+            }
+        })
+        .collect();
+    Ok(entries)
+}
+
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    device: String,
+    /// The next seq of each device, what's in the journal from it.
+    seen: HashMap<String, u64>,
+}
+
+impl Journal {
+    /// `library.journal.jsonl` next to the library. The device is identified
+    /// by a UUID kept in `device-id` there, made up the first time.
+    pub fn open(library_path: &Path) -> Result<Self> {
+        let path = library_path.with_extension("journal.jsonl");
+        let id_path = library_path.with_file_name("device-id");
+        let device = match fs::read_to_string(&id_path) {
+            Ok(id) if !id.trim().is_empty() => id.trim().to_owned(),
+            _ => {
+                let id = identity::new_uuid();
+                if let Some(dir) = id_path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                fs::write(&id_path, &id)?;
+                id
+            }
+        };
+        let mut journal = Self {
+            path,
+            device,
+            seen: HashMap::new(),
+        };
+        for entry in journal.entries()? {
+            journal.saw(&entry);
+        }
+        Ok(journal)
+    }
+
+    pub fn device(&self) -> &str {
+        &self.device
+    }
+
+    pub fn entries(&self) -> Result<Vec<Entry>> {
+        read(&self.path)
+    }
+
+    pub fn seen(&self) -> &HashMap<String, u64> {
+        &self.seen
+    }
+
+    fn has_seen(&self, entry: &Entry) -> bool {
+        self.seen
+            .get(&entry.device)
+            .is_some_and(|next| entry.seq < *next)
+    }
+
+    fn saw(&mut self, entry: &Entry) {
+        let next = self.seen.entry(entry.device.clone()).or_default();
+        *next = (*next).max(entry.seq + 1);
+    }
+
+    fn append(&mut self, changes: Vec<Change>, undoes: Option<u64>) -> Result<()> {
+        let entry = Entry {
+            seq: self.seen.get(&self.device).copied().unwrap_or_default(),
+            device: self.device.clone(),
+            at: Utc::now(),
+            changes,
+            undoes,
+        };
+        self.write(&entry)
+    }
+
+    fn write(&mut self, entry: &Entry) -> Result<()> {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        self.saw(entry);
+        Ok(())
+    }
+}
+
+impl Library {
+    /// Append what changed since the last save to the journal, if the
+    /// library has one.
+    pub(super) fn record(&mut self) -> Result<()> {
+        let Some(ref mut journal) = self.journal else {
+            return Ok(());
+        };
+        let changes = diff(&self.saved, &self.games);
+        if !changes.is_empty() {
+            journal.append(changes, self.undoing)?;
+        }
+        self.undoing = None;
+        self.saved = self.games.clone();
+        Ok(())
+    }
+
+    /// Oldest first, empty when the library wasn't opened from a file.
+    pub fn journal(&self) -> Result<Vec<Entry>> {
+        match self.journal {
+            Some(ref journal) => journal.entries(),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// This device in the journal, None when the library has none.
+    pub fn device(&self) -> Option<&str> {
+        self.journal.as_ref().map(Journal::device)
+    }
+
+    /// The next seq of each device in the journal.
+    pub fn seen(&self) -> HashMap<String, u64> {
+        self.journal
+            .as_ref()
+            .map(|j| j.seen().clone())
+            .unwrap_or_default()
+    }
+
+    /// The entries of the journal another device hasn't seen, by what it has.
+    pub fn unseen_by(&self, seen: &HashMap<String, u64>) -> Result<Vec<Entry>> {
+        let mut entries = self.journal()?;
+        entries.retain(|e| seen.get(&e.device).is_none_or(|next| e.seq >= *next));
+        Ok(entries)
+    }
+
+    /// Take back the last entry of this device not taken back yet, returns
+    /// it. Journaled as undoing it on save.
+    pub fn undo(&mut self) -> Result<Option<Entry>> {
+        let journal = self
+            .journal
+            .as_ref()
+            .ok_or(anyhow!("the library has no journal"))?;
+        let device = journal.device().to_owned();
+        let entries: Vec<_> = journal
+            .entries()?
+            .into_iter()
+            .filter(|e| e.device == device)
+            .collect();
+        let undone: HashSet<_> = entries.iter().filter_map(|e| e.undoes).collect();
+        let Some(entry) = entries
+            .into_iter()
+            .rev()
+            .find(|e| e.undoes.is_none() && !undone.contains(&e.seq))
+        else {
+            return Ok(None);
+        };
+        for change in entry.changes.iter().rev() {
+            self.apply(&change.inverse(), Utc::now());
+        }
+        self.undoing = Some(entry.seq);
+        Ok(Some(entry))
+    }
+
+    /// Apply an entry from another device, e.g. synced, and journal it as
+    /// theirs. Returns the UUIDs of the games it changed, none when it was
+    /// replayed before.
+    pub fn replay(&mut self, entry: &Entry) -> Result<Vec<String>> {
+        if self.journal.as_ref().is_some_and(|j| j.has_seen(entry)) {
+            return Ok(vec![]);
+        }
+        // What changed here first, so it's not taken for the entry's.
+        self.record()?;
+        let mut changed = vec![];
+        for change in &entry.changes {
+            let uuid = change.uuid().to_owned();
+            if self.apply(change, entry.at) && !changed.contains(&uuid) {
+                changed.push(uuid.clone());
+            }
+            // As if saved already, so it's not in the next diff.
+            self.saved.retain(|g| g.uuid != uuid);
+            if let Some(game) = self.get(&uuid) {
+                self.saved.push(game.clone());
+            }
+        }
+        if let Some(ref mut journal) = self.journal {
+            journal.write(entry)?;
+        }
+        Ok(changed)
+    }
+
+    /// False when there was nothing to change.
+    fn apply(&mut self, change: &Change, at: DateTime<Utc>) -> bool {
+        match change {
+            Change::Added { game } => {
+                self.get(&game.uuid).is_none()
+                    && (self.untrash_game(&game.uuid).is_ok() || self.insert(*game.clone()).is_ok())
+            }
+            Change::Edited { before, after } => match self.get_mut(&after.uuid) {
+                Some(game) => merge(game, before, after).unwrap_or_else(|e| {
+                    warn!("failed to apply an edit of {}: {}", after.title, e);
+                    false
+                }),
+                None => self.insert(*after.clone()).is_ok(),
+            },
+            Change::Played {
+                uuid,
+                before,
+                after,
+                ..
+            } => match self.get_mut(uuid) {
+                Some(game) => {
+                    // By how much, so the time played on each device adds up.
+                    let played = after.unwrap_or_default() - before.unwrap_or_default();
+                    let total = game.playtime.map_or(0, |d| d.num_seconds()) + played;
+                    game.playtime = match after {
+                        None if total <= 0 => None,
+                        _ => Some(Duration::seconds(total.max(0))),
+                    };
+                    played != 0
+                }
+                None => false,
+            },
+            Change::Deleted { game } => self.trash(&game.uuid, at),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journals_undoes_and_replays_changes() {
+        let dir = std::env::temp_dir().join(format!("anubis-journal-test-{}", std::process::id()));
+        let path = dir.join("library.yaml");
+        let mut library = Library::open(&path).unwrap();
+        library
+            .insert(GameMetadata {
+                uuid: "a".to_owned(),
+                title: "Celeste".to_owned(),
+                ..Default::default()
+            })
+            .unwrap();
+        library.save().unwrap();
+        library.get_mut("a").unwrap().playtime = Some(Duration::minutes(5));
+        library.save().unwrap();
+        library.get_mut("a").unwrap().title = "Celeste 64".to_owned();
+        library.save().unwrap();
+        library.save().unwrap();
+
+        let entries = library.journal().unwrap();
+        let described: Vec<_> = entries.iter().map(|e| e.changes[0].describe()).collect();
+        assert_eq!(
+            described,
+            ["added Celeste", "played Celeste", "edited Celeste 64"]
+        );
+
+        assert_eq!(library.undo().unwrap().unwrap().seq, 2);
+        library.save().unwrap();
+        assert_eq!(library.undo().unwrap().unwrap().seq, 1);
+        library.save().unwrap();
+        let a = library.get("a").unwrap();
+        assert_eq!((a.title.as_str(), a.playtime), ("Celeste", None));
+
+        // Another device catching up.
+        let mut other = Library::new();
+        for entry in &library.journal().unwrap() {
+            other.replay(entry).unwrap();
+        }
+        assert_eq!(other.get("a").unwrap().title, "Celeste");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+use super::dedupe::normalize_title;
+use crate::{integrations::steam::AppId, paths};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+// Games without a store of their own, e.g. ROMs, are looked up on the Steam
+// store by title when scraping. When more than one result could be the game,
+// it waits for the player to pick the right one on the matches screen. Picks
+// are kept so rescans go straight to them. In the data dir.
+
+/// Offered at most for a game.
+pub const MAX_CANDIDATES: usize = 5;
+
+/// A store result the game could be.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candidate {
+    pub app_id: AppId,
+    pub name: String,
+    pub image: Option<String>,
+    /// Of the release.
+    pub year: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pending {
+    pub uuid: String,
+    pub candidates: Vec<Candidate>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Matches {
+    /// The oldest first.
+    pub pending: Vec<Pending>,
+    /// App picked by game.
+    pub resolved: HashMap<String, AppId>,
+}
+
+impl Matches {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::data_dir().join("matches.yaml")
+    }
+
+    /// Wait for a pick, in place of the candidates of an earlier scrape.
+    pub fn queue(&mut self, uuid: &str, candidates: Vec<Candidate>) {
+        self.pending.retain(|p| p.uuid != uuid);
+        self.pending.push(Pending {
+            uuid: uuid.to_owned(),
+            candidates,
+        });
+    }
+
+    /// The game is that app from now on.
+    pub fn resolve(&mut self, uuid: &str, app_id: AppId) {
+        self.pending.retain(|p| p.uuid != uuid);
+        self.resolved.insert(uuid.to_owned(), app_id);
+    }
+
+    /// None of them, it's asked again on the next scrape.
+    pub fn skip(&mut self, uuid: &str) {
+        self.pending.retain(|p| p.uuid != uuid);
+    }
+}
+
+/// The candidate which is clearly the game: the only one, or the only one
+/// with the same title.
+pub fn clear_match<'a>(title: &str, candidates: &'a [Candidate]) -> Option<&'a Candidate> {
+    if let [only] = candidates {
+        return Some(only);
+    }
+    let title = normalize_title(title);
+    let mut same = candidates
+        .iter()
+        .filter(|c| normalize_title(&c.name) == title);
+    match (same.next(), same.next()) {
+        (Some(candidate), None) => Some(candidate),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(app_id: AppId, name: &str) -> Candidate {
+        Candidate {
+            app_id,
+            name: name.to_owned(),
+            image: None,
+            year: None,
+        }
+    }
+
+    #[test]
+    fn queues_ambiguous_games_until_picked() {
+        let candidates = vec![
+            candidate(1, "Doom"),
+            candidate(2, "DOOM II"),
+            candidate(3, "Doom 3"),
+        ];
+        assert_eq!(clear_match("doom", &candidates).map(|c| c.app_id), Some(1));
+        assert_eq!(clear_match("Doom 64", &candidates), None);
+        assert_eq!(
+            clear_match("Doom 64", &candidates[2..]).map(|c| c.app_id),
+            Some(3)
+        );
+
+        let mut matches = Matches::default();
+        matches.queue("a", candidates.clone());
+        matches.queue("b", candidates.clone());
+        matches.queue("a", candidates[1..].to_vec());
+        assert_eq!(matches.pending.len(), 2);
+        assert_eq!(matches.pending[1].candidates.len(), 2);
+        matches.resolve("a", 2);
+        matches.skip("b");
+        assert!(matches.pending.is_empty());
+        assert_eq!(matches.resolved.get("a"), Some(&2));
+
+        let path = std::env::temp_dir().join(format!("anubis-matches-{}.yaml", std::process::id()));
+        matches.save(&path).unwrap();
+        assert_eq!(Matches::load(&path).unwrap(), matches);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::{anyhow, bail, Result};
+use serde_json::{Map, Value};
+
+// Saved libraries and backups carry the schema version of their games, older
+// ones are brought up to date before being deserialized. Changing how
+// `GameMetadata` is saved means bumping `SCHEMA_VERSION` and adding a
+// migration, adding an optional field doesn't need one.
+
+/// Version of the games as saved by this build.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Upgrades a game by one version.
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+
+/// `MIGRATIONS[i]` upgrades a game from version `i + 1`.
+const MIGRATIONS: &[Migration] = &[fix_field_names];
+
+/// 1 -> 2: the typos of the model aren't saved anymore.
+fn fix_field_names(game: &mut Map<String, Value>) -> Result<()> {
+    for (old, new) in [("relase_date", "release_date"), ("favorate", "favorite")] {
+        if let Some(v) = game.remove(old) {
+            game.insert(new.to_owned(), v);
+        }
+    }
+    Ok(())
+}
+
+fn upgrade(game: &mut Value, version: u32) -> Result<()> {
+    let game = game
+        .as_object_mut()
+        .ok_or(anyhow!("games should be objects"))?;
+    for migration in &MIGRATIONS[version as usize - 1..] {
+        migration(game)?;
+    }
+    Ok(())
+}
+
+/// Upgrade the games of a saved library or backup, i.e. an object with
+/// `schema_version` and `games`, and those of its trash. Files from before
+/// versioning are version 1.
+pub fn migrate(saved: &mut Value) -> Result<()> {
+    let saved = saved
+        .as_object_mut()
+        .ok_or(anyhow!("not a library, expected an object"))?;
+    let version = match saved.get("schema_version") {
+        Some(v) => v
+            .as_u64()
+            .ok_or(anyhow!("bad schema version {}", v))?
+            .try_into()?,
+        None => 1,
+    };
+    if version == 0 {
+        bail!("bad schema version 0");
+    }
+    if version > SCHEMA_VERSION {
+        bail!(
+            "saved by a newer version of anubis (schema {}, this build reads up to {})",
+            version,
+            SCHEMA_VERSION
+        );
+    }
+    if let Some(games) = saved.get_mut("games").and_then(Value::as_array_mut) {
+        for game in games.iter_mut() {
+            upgrade(game, version)?;
+        }
+    }
+    if let Some(trash) = saved.get_mut("trash").and_then(Value::as_array_mut) {
+        for game in trash.iter_mut().filter_map(|t| t.get_mut("game")) {
+            upgrade(game, version)?;
+        }
+    }
+    saved.insert("schema_version".to_owned(), SCHEMA_VERSION.into());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn migrates_old_games() {
+        let mut saved = json!({
+            "games": [{"title": "Celeste", "uuid": "a", "favorate": true}]
+        });
+        migrate(&mut saved).unwrap();
+        assert_eq!(
+            saved,
+            json!({
+                "schema_version": SCHEMA_VERSION,
+                "games": [{"title": "Celeste", "uuid": "a", "favorite": true}]
+            })
+        );
+        // Already up to date.
+        migrate(&mut saved).unwrap();
+        assert_eq!(saved["games"][0]["favorite"], json!(true));
+
+        assert!(migrate(&mut json!({"schema_version": SCHEMA_VERSION + 1})).is_err());
+    }
+}
@@ -0,0 +1,310 @@
+use self::{identity::Identities, journal::Journal, trash::Trashed};
+use crate::{
+    models::{CompletionStatus, GameMetadata},
+    paths,
+};
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub mod backup;
+pub mod bulk;
+pub mod dedupe;
+pub mod identity;
+pub mod index;
+pub mod journal;
+pub mod matches;
+pub mod migrations;
+pub mod parental;
+pub mod roms;
+pub mod roots;
+pub mod scrape;
+pub mod trash;
+
+/// All the games known to the launcher, keyed by their UUID.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Library {
+    games: Vec<GameMetadata>,
+    #[serde(default)]
+    identities: Identities,
+    /// Deleted or overwritten, see `trash`.
+    #[serde(default)]
+    trash: Vec<Trashed>,
+    /// Where the library is saved, see `Library::open`.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// The games as saved last, to journal what changed since.
+    #[serde(skip)]
+    saved: Vec<GameMetadata>,
+    #[serde(skip)]
+    journal: Option<Journal>,
+    /// The entry of the journal taken back since the last save, see
+    /// `Library::undo`.
+    #[serde(skip)]
+    undoing: Option<u64>,
+    /// Of the player, see `parental`.
+    #[serde(skip)]
+    max_age: Option<u8>,
+}
+
+/// The library as saved, with the schema version of its games.
+#[derive(Serialize)]
+struct Saved<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    library: &'a Library,
+}
+
+impl Library {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `$XDG_DATA_HOME/anubis/library.yaml`, shared by the UI and the CLI.
+    pub fn default_path() -> PathBuf {
+        paths::data_dir().join("library.yaml")
+    }
+
+    /// Load the library saved at path, empty if there's none yet.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut library = Self::new();
+        if path.exists() {
+            let mut saved: serde_json::Value = serde_yaml::from_str(&fs::read_to_string(path)?)?;
+            migrations::migrate(&mut saved).map_err(|e| anyhow!("{:?}: {}", path, e))?;
+            let saved: Library = serde_json::from_value(saved)?;
+            library.identities = saved.identities;
+            library.trash = saved.trash;
+            for game in saved.games {
+                if game.uuid.is_empty() {
+                    bail!("game {} has no uuid in {:?}", game.title, path);
+                }
+                library.insert(game)?;
+            }
+        }
+        library.path = Some(path.to_owned());
+        library.saved = library.games.clone();
+        library.journal = Some(Journal::open(path)?);
+        Ok(library)
+    }
+
+    /// Write the library back to where it was opened from, and what changed
+    /// to the journal.
+    pub fn save(&mut self) -> Result<()> {
+        let path = self
+            .path
+            .as_deref()
+            .ok_or(anyhow!("the library wasn't opened from a file"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write then rename, so a crash never leaves half a library behind.
+        let tmp = path.with_extension("yaml.tmp");
+        let saved = Saved {
+            schema_version: migrations::SCHEMA_VERSION,
+            library: self,
+        };
+        fs::write(&tmp, serde_yaml::to_string(&saved)?)?;
+        fs::rename(&tmp, path)?;
+        self.record()
+    }
+
+    /// The game the install source was imported as, even if the game has
+    /// another install source now, e.g. after a merge.
+    pub fn find_by_source(&self, install_source: &str) -> Option<&GameMetadata> {
+        match self.identities.lookup(install_source) {
+            Some(uuid) => self.get(uuid),
+            None => self
+                .games
+                .iter()
+                .find(|g| g.install_source.as_deref() == Some(install_source)),
+        }
+    }
+
+    /// Insert a game, returns its UUID. Games without one get the UUID their
+    /// install source was seen with before, or a new one.
+    pub fn insert(&mut self, mut game: GameMetadata) -> Result<String> {
+        if game.uuid.is_empty() {
+            game.uuid = self.uuid_for(&game);
+        }
+        if self.get(&game.uuid).is_some() {
+            bail!("duplicated uuid {}", game.uuid);
+        }
+        if let Some(ref source) = game.install_source {
+            self.identities.record(source, &game.uuid);
+        }
+        let uuid = game.uuid.clone();
+        self.games.push(game);
+        Ok(uuid)
+    }
+
+    /// The UUID a game would get inserted, the one it had before if it was
+    /// removed since.
+    pub fn uuid_for(&self, game: &GameMetadata) -> String {
+        game.install_source
+            .as_deref()
+            .and_then(|s| self.identities.lookup(s))
+            .filter(|uuid| self.get(uuid).is_none())
+            .map(str::to_owned)
+            .unwrap_or_else(identity::new_uuid)
+    }
+
+    pub fn get(&self, uuid: &str) -> Option<&GameMetadata> {
+        self.games.iter().find(|g| g.uuid == uuid)
+    }
+
+    pub fn get_mut(&mut self, uuid: &str) -> Option<&mut GameMetadata> {
+        self.games.iter_mut().find(|g| g.uuid == uuid)
+    }
+
+    /// Move a game to another position among those shown, the library is
+    /// shown and saved in this order.
+    pub fn move_game(&mut self, uuid: &str, to: usize) -> Result<()> {
+        let shown: Vec<usize> = (0..self.games.len())
+            .filter(|&i| self.games[i].suitable_for(self.max_age))
+            .collect();
+        let Some(&to) = shown.get(to) else {
+            bail!("can't move {} to {}, only {} games", uuid, to, shown.len());
+        };
+        let game = self.remove(uuid).ok_or(anyhow!("no game {}", uuid))?;
+        self.games.insert(to, game);
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &GameMetadata> {
+        self.games.iter()
+    }
+
+    /// Games shown with the query in their title, ignoring case, and with
+    /// the status if one is given.
+    pub fn search(&self, query: &str, status: Option<CompletionStatus>) -> Vec<&GameMetadata> {
+        let query = query.to_lowercase();
+        self.visible()
+            .filter(|g| g.title.to_lowercase().contains(&query))
+            .filter(|g| status.is_none_or(|s| g.status == s))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_and_opens() {
+        let path =
+            std::env::temp_dir().join(format!("anubis-library-test-{}.yaml", std::process::id()));
+        let mut library = Library::open(&path).unwrap();
+        library
+            .insert(GameMetadata {
+                title: "Celeste".to_owned(),
+                uuid: "a".to_owned(),
+                playtime: Some(chrono::Duration::minutes(90)),
+                ..Default::default()
+            })
+            .unwrap();
+        library.save().unwrap();
+
+        let library = Library::open(&path).unwrap();
+        let game = library.get("a").unwrap();
+        assert_eq!(game.title, "Celeste");
+        assert_eq!(game.playtime, Some(chrono::Duration::minutes(90)));
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(path.with_extension("journal.jsonl")).unwrap();
+    }
+
+    #[test]
+    fn opens_old_libraries() {
+        let path =
+            std::env::temp_dir().join(format!("anubis-library-v1-{}.yaml", std::process::id()));
+        fs::write(
+            &path,
+            "games:\n- title: Celeste\n  uuid: a\n  favorate: true\n  playtime: 60\n",
+        )
+        .unwrap();
+        let library = Library::open(&path).unwrap();
+        let game = library.get("a").unwrap();
+        assert!(game.favorate);
+        assert_eq!(game.playtime, Some(chrono::Duration::minutes(1)));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn searches_titles() {
+        let mut library = Library::new();
+        for (title, uuid, status) in [
+            ("Celeste", "a", CompletionStatus::Completed),
+            ("Hollow Knight", "b", CompletionStatus::Playing),
+            ("celeste 64", "c", CompletionStatus::Backlog),
+        ] {
+            library
+                .insert(GameMetadata {
+                    title: title.to_owned(),
+                    uuid: uuid.to_owned(),
+                    status,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let found = |query, status| -> Vec<String> {
+            library
+                .search(query, status)
+                .iter()
+                .map(|g| g.title.clone())
+                .collect()
+        };
+        assert_eq!(found("CELESTE", None), vec!["Celeste", "celeste 64"]);
+        assert_eq!(
+            found("celeste", Some(CompletionStatus::Completed)),
+            vec!["Celeste"]
+        );
+        assert_eq!(
+            found("", Some(CompletionStatus::Playing)),
+            vec!["Hollow Knight"]
+        );
+        assert!(found("", Some(CompletionStatus::Abandoned)).is_empty());
+    }
+
+    #[test]
+    fn moves_games() {
+        let mut library = Library::new();
+        for uuid in ["a", "b", "c"] {
+            library
+                .insert(GameMetadata {
+                    uuid: uuid.to_owned(),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        library.move_game("a", 2).unwrap();
+        let order: Vec<_> = library.iter().map(|g| g.uuid.as_str()).collect();
+        assert_eq!(order, vec!["b", "c", "a"]);
+        assert!(library.move_game("a", 3).is_err());
+        assert!(library.move_game("d", 0).is_err());
+    }
+
+    #[test]
+    fn assigns_uuids() {
+        let mut library = Library::new();
+        let steam_game = || GameMetadata {
+            title: "Team Fortress 2".to_owned(),
+            install_source: Some("steam:440".to_owned()),
+            ..Default::default()
+        };
+        let uuid = library.insert(steam_game()).unwrap();
+        assert!(!uuid.is_empty());
+        assert_eq!(library.find_by_source("steam:440").unwrap().uuid, uuid);
+
+        // Removed then imported again, same game.
+        library.remove(&uuid).unwrap();
+        assert_eq!(library.insert(steam_game()).unwrap(), uuid);
+        // Already in the library, a new game.
+        assert_ne!(library.insert(steam_game()).unwrap(), uuid);
+
+        let mut dup = steam_game();
+        dup.uuid = uuid;
+        assert!(library.insert(dup).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use super::Library;
+use crate::{models::GameMetadata, paths};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+// Parental controls: profiles of who plays, the restricted ones with the age
+// of the player. Games rated for older players are hidden from the library as
+// long as such a profile is the one used, unrated games are shown as there's
+// no telling.
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerProfile {
+    /// Restricted with one, to games rated for that age or younger.
+    pub max_age: Option<u8>,
+}
+
+/// The profiles, and the one used. Anything goes without one.
+///
+/// ```yaml
+/// profile: kids
+/// profiles:
+///   kids:
+///     max_age: 10
+///   grown-ups: {}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParentalConfig {
+    pub profile: Option<String>,
+    pub profiles: BTreeMap<String, PlayerProfile>,
+}
+
+impl ParentalConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::config_dir().join("parental.yaml")
+    }
+
+    /// Of the profile used.
+    pub fn max_age(&self) -> Result<Option<u8>> {
+        let Some(ref name) = self.profile else {
+            return Ok(None);
+        };
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or(anyhow!("no profile {}", name))?;
+        Ok(profile.max_age)
+    }
+}
+
+impl GameMetadata {
+    /// Whether it's rated for players of `max_age` or younger.
+    pub fn suitable_for(&self, max_age: Option<u8>) -> bool {
+        match (max_age, self.min_age()) {
+            (Some(max), Some(min)) => min <= max,
+            _ => true,
+        }
+    }
+}
+
+impl Library {
+    /// Hide the games rated for older players, see `visible`.
+    pub fn restrict(&mut self, max_age: Option<u8>) {
+        self.max_age = max_age;
+    }
+
+    /// The games shown to the player, in library order.
+    pub fn visible(&self) -> impl Iterator<Item = &GameMetadata> {
+        self.games.iter().filter(|g| g.suitable_for(self.max_age))
+    }
+
+    /// Whether the game is in the library and shown to the player, the
+    /// others can't be played nor looked at.
+    pub fn is_visible(&self, uuid: &str) -> bool {
+        self.get(uuid).is_some_and(|g| g.suitable_for(self.max_age))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Esrb;
+
+    #[test]
+    fn hides_mature_games_in_restricted_profiles() {
+        let config: ParentalConfig =
+            serde_yaml::from_str("profile: kids\nprofiles:\n  kids:\n    max_age: 10\n").unwrap();
+        assert_eq!(config.max_age().unwrap(), Some(10));
+
+        let mut library = Library::new();
+        for (title, uuid, esrb, pegi) in [
+            ("Celeste", "a", Some(Esrb::Everyone10), None),
+            ("Doom", "b", Some(Esrb::Mature), Some(18)),
+            ("Tetris", "c", None, None),
+            ("Hades", "d", None, Some(12)),
+        ] {
+            library
+                .insert(GameMetadata {
+                    title: title.to_owned(),
+                    uuid: uuid.to_owned(),
+                    esrb,
+                    pegi,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        library.restrict(config.max_age().unwrap());
+        let visible: Vec<_> = library.visible().map(|g| g.title.as_str()).collect();
+        assert_eq!(visible, ["Celeste", "Tetris"]);
+        assert!(library.search("doom", None).is_empty());
+        assert!(!library.is_visible("b"));
+        assert!(library.is_visible("c"));
+        assert!(!library.is_visible("nope"));
+
+        // Moved among the games shown, the others stay where they are.
+        library.move_game("a", 1).unwrap();
+        let order: Vec<_> = library.iter().map(|g| g.uuid.as_str()).collect();
+        assert_eq!(order, ["b", "c", "a", "d"]);
+
+        library.restrict(None);
+        assert_eq!(library.visible().count(), 4);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::models::GameMetadata;
+use anyhow::Result;
+use std::{fs, path::Path};
+
+/// Prefix of `GameMetadata::install_source` for ROMs, followed by the path.
+pub const INSTALL_SOURCE_PREFIX: &str = "rom:";
+
+/// Platforms by file extension, named like the EmulationStation system dirs.
+const ROM_EXTENSIONS: &[(&str, &str)] = &[
+    ("nes", "nes"),
+    ("sfc", "snes"),
+    ("smc", "snes"),
+    ("gb", "gb"),
+    ("gbc", "gbc"),
+    ("gba", "gba"),
+    ("n64", "n64"),
+    ("z64", "n64"),
+    ("v64", "n64"),
+    ("nds", "nds"),
+    ("md", "genesis"),
+    ("gen", "genesis"),
+    ("sms", "mastersystem"),
+    ("gg", "gamegear"),
+    ("pce", "pcengine"),
+    ("32x", "sega32x"),
+];
+
+/// Files sitting next to ROMs which aren't games.
+const IGNORED_EXTENSIONS: &[&str] = &[
+    "txt", "nfo", "xml", "cfg", "srm", "sav", "state", "png", "jpg", "jpeg",
+];
+
+fn extension(path: &Path) -> Option<String> {
+    Some(path.extension()?.to_str()?.to_lowercase())
+}
+
+pub fn platform_for(path: &Path) -> Option<&'static str> {
+    let ext = extension(path)?;
+    ROM_EXTENSIONS
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, platform)| *platform)
+}
+
+/// Drop the No-Intro style tags, e.g. `Super Mario World (USA) [!].sfc`
+/// becomes `Super Mario World`.
+pub fn title_from_file_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let title = stem
+        .split(['(', '['])
+        .next()
+        .unwrap_or_default()
+        .replace('_', " ");
+    let title = title.trim();
+    if title.is_empty() {
+        None
+    } else {
+        Some(title.to_owned())
+    }
+}
+
+/// Find the ROMs under dir, recursively. The platform is guessed from the
+/// extension, unless given, in which case any file counts as a ROM.
+pub fn scan(dir: &Path, platform: Option<&str>) -> Result<Vec<GameMetadata>> {
+    let mut found = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            found.extend(scan(&path, platform)?);
+            continue;
+        }
+        if extension(&path).is_some_and(|e| IGNORED_EXTENSIONS.contains(&e.as_str())) {
+            continue;
+        }
+        let Some(platform) = platform.or_else(|| platform_for(&path)) else {
+            continue;
+        };
+        let Some(title) = title_from_file_name(&path) else {
+            continue;
+        };
+        let install_source = format!(
+            "{}{}",
+            INSTALL_SOURCE_PREFIX,
+            path.canonicalize()?.display()
+        );
+        found.push(GameMetadata {
+            title,
+            platform: Some(platform.to_owned()),
+            install_source: Some(install_source),
+            ..Default::default()
+        });
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cleans_up_titles() {
+        assert_eq!(
+            title_from_file_name(Path::new("roms/Super Mario World (USA) [!].sfc")).as_deref(),
+            Some("Super Mario World")
+        );
+        assert_eq!(
+            title_from_file_name(Path::new("Mega_Man_2.nes")).as_deref(),
+            Some("Mega Man 2")
+        );
+        assert_eq!(title_from_file_name(Path::new("(Beta).gba")), None);
+    }
+
+    #[test]
+    fn guesses_platforms() {
+        assert_eq!(platform_for(Path::new("a.SFC")), Some("snes"));
+        assert_eq!(platform_for(Path::new("a.z64")), Some("n64"));
+        assert_eq!(platform_for(Path::new("a.iso")), None);
+    }
+}
@@ -0,0 +1,245 @@
+use super::{roms, Library};
+use crate::models::GameMetadata;
+use crate::paths;
+use anyhow::{bail, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+// Places games are kept in, e.g. the internal SSD, an SD card or an external
+// drive. The games found under a root remember it, so while its drive is
+// unplugged they're shown as unavailable instead of going away, and they come
+// back as they were, art and playtime included, once it's plugged back in.
+
+/// How often the roots are checked for drives coming and going.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryRoot {
+    /// What the games remember, e.g. `sd-card`.
+    pub name: String,
+    pub path: PathBuf,
+    /// Platform of all the ROMs under it, guessed from the extension otherwise.
+    #[serde(default)]
+    pub platform: Option<String>,
+}
+
+impl LibraryRoot {
+    /// The drive is mounted. Mount points left behind are empty directories,
+    /// so a root with nothing in it counts as unplugged too.
+    pub fn is_available(&self) -> bool {
+        fs::read_dir(&self.path).is_ok_and(|mut entries| entries.next().is_some())
+    }
+}
+
+/// ```yaml
+/// roots:
+///   - name: internal
+///     path: /home/deck/roms
+///   - name: sd-card
+///     path: /run/media/deck/SD/roms
+///     platform: gba
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RootsConfig {
+    pub roots: Vec<LibraryRoot>,
+}
+
+impl RootsConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::config_dir().join("roots.yaml")
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LibraryRoot> {
+        self.roots.iter().find(|r| r.name == name)
+    }
+
+    /// Names of the roots whose drive isn't there.
+    pub fn unavailable(&self) -> HashSet<String> {
+        self.roots
+            .iter()
+            .filter(|r| !r.is_available())
+            .map(|r| r.name.clone())
+            .collect()
+    }
+}
+
+impl Library {
+    /// Add the ROMs under the root that aren't in the library yet. Those
+    /// already in are left as they are, their drive may have just come back.
+    /// Returns how many were added.
+    pub fn scan_root(&mut self, root: &LibraryRoot) -> Result<usize> {
+        let found = self.new_in_root(root)?;
+        let added = found.len();
+        for game in found {
+            self.insert(game)?;
+        }
+        Ok(added)
+    }
+
+    /// The ROMs under the root that aren't in the library yet, with the UUID
+    /// they'll have once inserted. Those found before roots were set up are
+    /// given the root meanwhile.
+    pub fn new_in_root(&mut self, root: &LibraryRoot) -> Result<Vec<GameMetadata>> {
+        if !root.is_available() {
+            bail!("{} isn't plugged in, nothing in {:?}", root.name, root.path);
+        }
+        let mut found = vec![];
+        for mut game in roms::scan(&root.path, root.platform.as_deref())? {
+            let source = game.install_source.clone().unwrap_or_default();
+            if self.is_trashed(&source) {
+                continue;
+            }
+            if let Some(uuid) = self.find_by_source(&source).map(|g| g.uuid.clone()) {
+                if let Some(known) = self.get_mut(&uuid).filter(|g| g.root.is_none()) {
+                    known.root = Some(root.name.clone());
+                }
+                continue;
+            }
+            game.root = Some(root.name.clone());
+            game.uuid = self.uuid_for(&game);
+            found.push(game);
+        }
+        Ok(found)
+    }
+}
+
+/// What `watch` reports.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RootsEvent {
+    /// Games were found on a root that came back, by UUID. They're added to
+    /// the library one by one next.
+    Importing(Vec<String>),
+    /// One of those is in the library now.
+    Imported(String),
+    /// A root was plugged in or out, and its games added if any.
+    Changed,
+}
+
+/// Keep `unavailable` up to date with the drives plugged in. Roots coming back
+/// are scanned for games added to them meanwhile. `on_change` is called for
+/// each change, until it returns false.
+pub fn watch(
+    config: RootsConfig,
+    library: Arc<Mutex<Library>>,
+    unavailable: Arc<Mutex<HashSet<String>>>,
+    on_change: impl Fn(RootsEvent) -> bool + Send + 'static,
+) {
+    thread::spawn(move || loop {
+        let now = config.unavailable();
+        let returned: Vec<&LibraryRoot> = {
+            let mut unavailable = unavailable.lock().unwrap();
+            if *unavailable == now {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            let returned = config
+                .roots
+                .iter()
+                .filter(|r| unavailable.contains(&r.name) && !now.contains(&r.name))
+                .collect();
+            *unavailable = now;
+            returned
+        };
+        for root in returned {
+            info!("{} is back", root.name);
+            let found = library.lock().unwrap().new_in_root(root);
+            let found = match found {
+                Ok(found) if found.is_empty() => continue,
+                Ok(found) => found,
+                Err(e) => {
+                    warn!("failed to scan {}: {:?}", root.name, e);
+                    continue;
+                }
+            };
+            info!("found {} new games in {}", found.len(), root.name);
+            let uuids = found.iter().map(|g| g.uuid.clone()).collect();
+            if !on_change(RootsEvent::Importing(uuids)) {
+                return;
+            }
+            // Not all at once, the UI shows each as soon as it's in.
+            for game in found {
+                let uuid = game.uuid.clone();
+                if let Err(e) = library.lock().unwrap().insert(game) {
+                    warn!("failed to add {}: {:?}", uuid, e);
+                }
+                if !on_change(RootsEvent::Imported(uuid)) {
+                    return;
+                }
+            }
+            if let Err(e) = library.lock().unwrap().save() {
+                warn!("failed to save the library: {:?}", e);
+            }
+        }
+        if !on_change(RootsEvent::Changed) {
+            return;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_games_of_unplugged_roots() {
+        let dir = std::env::temp_dir().join(format!("anubis-roots-test-{}", std::process::id()));
+        let root = LibraryRoot {
+            name: "sd-card".to_owned(),
+            path: dir.join("sd"),
+            platform: None,
+        };
+        let config = RootsConfig {
+            roots: vec![root.clone()],
+        };
+        let mut library = Library::new();
+        assert!(library.scan_root(&root).is_err());
+        assert_eq!(config.unavailable(), HashSet::from(["sd-card".to_owned()]));
+
+        fs::create_dir_all(&root.path).unwrap();
+        fs::write(root.path.join("Metroid Fusion (USA).gba"), "").unwrap();
+        assert!(config.unavailable().is_empty());
+        assert_eq!(library.scan_root(&root).unwrap(), 1);
+        let uuid = library.iter().next().unwrap().uuid.clone();
+        library.get_mut(&uuid).unwrap().favorate = true;
+
+        // Unplugged and plugged back in, with another game on it.
+        fs::remove_dir_all(&root.path).unwrap();
+        assert!(library.get(&uuid).is_some());
+        fs::create_dir_all(&root.path).unwrap();
+        fs::write(root.path.join("Metroid Fusion (USA).gba"), "").unwrap();
+        fs::write(root.path.join("Golden Sun.gba"), "").unwrap();
+        let found = library.new_in_root(&root).unwrap();
+        assert_eq!(found.len(), 1);
+        let new_uuid = found[0].uuid.clone();
+        assert_eq!(library.insert(found[0].clone()).unwrap(), new_uuid);
+        let games: Vec<&GameMetadata> = library.iter().collect();
+        assert_eq!(games.len(), 2);
+        assert!(library.get(&uuid).unwrap().favorate);
+        assert!(games.iter().all(|g| g.root.as_deref() == Some("sd-card")));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,118 @@
+use super::matches::Matches;
+use crate::{
+    integrations::{legendary, steam},
+    models::GameMetadata,
+};
+use anyhow::{anyhow, Result};
+use log::warn;
+use std::collections::HashMap;
+
+// Filling in the metadata of games from the store they're from, or the Steam
+// app picked for them, see `matches`. For `anubis cli scrape` and the bulk
+// rescrape. Fetched without the library locked, applied once in.
+
+/// Where the metadata of a game comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Steam(steam::AppId),
+    /// By the app name of the game for legendary.
+    Epic(String),
+}
+
+impl Source {
+    /// None for games from elsewhere not matched to a Steam app yet.
+    pub fn of(game: &GameMetadata, matches: &Matches) -> Option<Self> {
+        let source = game.install_source.as_deref().unwrap_or_default();
+        if let Some(app_id) = steam::app_id_from_install_source(source) {
+            Some(Source::Steam(app_id))
+        } else if let Some(app_name) = legendary::app_name_from_install_source(source) {
+            Some(Source::Epic(app_name.to_owned()))
+        } else {
+            matches
+                .resolved
+                .get(&game.uuid)
+                .map(|id| Source::Steam(*id))
+        }
+    }
+}
+
+/// What a store has on a game.
+#[derive(Debug, Clone)]
+pub enum Details {
+    /// In English and in the language of the locale.
+    Steam(Box<(steam::StoreDetails, Option<steam::StoreDetails>)>),
+    Epic(legendary::Details),
+}
+
+pub struct Scraper {
+    locale: String,
+    /// Read once, on the first Epic game.
+    epic: Option<HashMap<String, legendary::Details>>,
+}
+
+impl Scraper {
+    pub fn new(locale: &str) -> Self {
+        Self {
+            locale: locale.to_owned(),
+            epic: None,
+        }
+    }
+
+    /// Blocks on the network.
+    pub fn fetch(&mut self, source: &Source) -> Result<Details> {
+        match source {
+            Source::Steam(app_id) => {
+                let details = steam::store_details_in(*app_id, &self.locale)?;
+                Ok(Details::Steam(Box::new(details)))
+            }
+            Source::Epic(app_name) => {
+                let library = self.epic.get_or_insert_with(|| {
+                    legendary::library_details().unwrap_or_else(|e| {
+                        warn!("failed to read the Epic library: {}", e);
+                        HashMap::new()
+                    })
+                });
+                let details = library.get(app_name).cloned();
+                details
+                    .map(Details::Epic)
+                    .ok_or(anyhow!("no Epic metadata for {}", app_name))
+            }
+        }
+    }
+
+    /// Only the missing bits unless forced.
+    pub fn apply(&self, game: &mut GameMetadata, details: Details, force: bool) {
+        match details {
+            Details::Steam(details) => {
+                let (details, localized) = *details;
+                steam::apply_details(game, details, localized, &self.locale, force)
+            }
+            Details::Epic(details) => legendary::apply_details(game, details, force),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_source_of_a_game() {
+        let game = |uuid: &str, source: Option<&str>| GameMetadata {
+            uuid: uuid.to_owned(),
+            install_source: source.map(str::to_owned),
+            ..Default::default()
+        };
+        let mut matches = Matches::default();
+        matches.resolve("rom", 70);
+        assert_eq!(
+            Source::of(&game("a", Some("steam:440")), &matches),
+            Some(Source::Steam(440))
+        );
+        assert_eq!(
+            Source::of(&game("rom", None), &matches),
+            Some(Source::Steam(70))
+        );
+        assert_eq!(Source::of(&game("other", None), &matches), None);
+    }
+}
@@ -0,0 +1,184 @@
+use super::Library;
+use crate::models::GameMetadata;
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+// Games taken out of the library, and metadata about to be scraped over, go
+// to the trash for `KEEP_DAYS` so they can be put back. Saved with the
+// library. A game in the trash is a tombstone for its install source, scans
+// don't add it back.
+
+/// Purged after, see `Library::purge_expired`.
+pub const KEEP_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Removal {
+    /// Out of the library.
+    Deleted,
+    /// The metadata as it was before a rescrape, the game stays.
+    Overwritten,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trashed {
+    pub game: GameMetadata,
+    pub removal: Removal,
+    pub at: DateTime<Utc>,
+    /// In the library, to put it back there.
+    pub position: usize,
+}
+
+impl Library {
+    /// The newest last.
+    pub fn trashed(&self) -> &[Trashed] {
+        &self.trash
+    }
+
+    /// Take the game out of the library. False when there's no such game.
+    pub fn trash(&mut self, uuid: &str, at: DateTime<Utc>) -> bool {
+        let Some(position) = self.games.iter().position(|g| g.uuid == uuid) else {
+            return false;
+        };
+        let game = self.games.remove(position);
+        self.trash.push(Trashed {
+            game,
+            removal: Removal::Deleted,
+            at,
+            position,
+        });
+        true
+    }
+
+    /// Keep the metadata of the game as it is, before it's overwritten.
+    pub fn trash_metadata(&mut self, uuid: &str, at: DateTime<Utc>) -> bool {
+        let Some(position) = self.games.iter().position(|g| g.uuid == uuid) else {
+            return false;
+        };
+        self.trash.push(Trashed {
+            game: self.games[position].clone(),
+            removal: Removal::Overwritten,
+            at,
+            position,
+        });
+        true
+    }
+
+    /// Put the entry back: the game where it was, or its metadata over the
+    /// current one, keeping the playtime. Returns the UUID of the game.
+    pub fn untrash(&mut self, i: usize) -> Result<String> {
+        let trashed = self
+            .trash
+            .get(i)
+            .ok_or(anyhow!("nothing at {} in the trash", i))?;
+        let uuid = trashed.game.uuid.clone();
+        match trashed.removal {
+            Removal::Deleted => {
+                if self.get(&uuid).is_some() {
+                    bail!("{} is in the library already", trashed.game.title);
+                }
+                let Trashed { game, position, .. } = self.trash.remove(i);
+                if let Some(ref source) = game.install_source {
+                    self.identities.record(source, &game.uuid);
+                }
+                self.games.insert(position.min(self.games.len()), game);
+            }
+            Removal::Overwritten => {
+                let Some(current) = self.get(&uuid) else {
+                    bail!("{} isn't in the library anymore", trashed.game.title);
+                };
+                let playtime = current.playtime;
+                let mut game = self.trash.remove(i).game;
+                game.playtime = playtime;
+                *self.get_mut(&uuid).unwrap() = game;
+            }
+        }
+        Ok(uuid)
+    }
+
+    /// Put back the game deleted last with the UUID, to undo.
+    pub fn untrash_game(&mut self, uuid: &str) -> Result<()> {
+        let i = self
+            .trash
+            .iter()
+            .rposition(|t| t.game.uuid == uuid && t.removal == Removal::Deleted)
+            .ok_or(anyhow!("{} isn't in the trash", uuid))?;
+        self.untrash(i).map(|_| ())
+    }
+
+    /// Gone for good.
+    pub fn purge(&mut self, i: usize) -> Result<Trashed> {
+        if i >= self.trash.len() {
+            bail!("nothing at {} in the trash", i);
+        }
+        Ok(self.trash.remove(i))
+    }
+
+    pub fn empty_trash(&mut self) {
+        self.trash.clear();
+    }
+
+    /// Purge what's been in the trash longer than `KEEP_DAYS`, returns how
+    /// many were.
+    pub fn purge_expired(&mut self, now: DateTime<Utc>) -> usize {
+        let before = self.trash.len();
+        self.trash.retain(|t| now - t.at < Duration::days(KEEP_DAYS));
+        before - self.trash.len()
+    }
+
+    /// Whether the game imported from there was deleted.
+    pub fn is_trashed(&self, install_source: &str) -> bool {
+        self.trash.iter().any(|t| {
+            t.removal == Removal::Deleted
+                && t.game.install_source.as_deref() == Some(install_source)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn game(uuid: &str, title: &str) -> GameMetadata {
+        GameMetadata {
+            uuid: uuid.to_owned(),
+            title: title.to_owned(),
+            install_source: Some(format!("steam:{}", uuid.len())),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn puts_trashed_games_back() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let mut library = Library::new();
+        for (uuid, title) in [("a", "Celeste"), ("bb", "Hades"), ("ccc", "Tunic")] {
+            library.insert(game(uuid, title)).unwrap();
+        }
+        assert!(library.trash("bb", now));
+        assert!(!library.trash("bb", now));
+        assert!(library.is_trashed("steam:2"));
+        assert!(library.trash_metadata("a", now));
+        library.get_mut("a").unwrap().title = "Scraped over".to_owned();
+        library.get_mut("a").unwrap().playtime = Some(Duration::hours(2));
+
+        library.untrash_game("bb").unwrap();
+        let order: Vec<_> = library.iter().map(|g| g.uuid.as_str()).collect();
+        assert_eq!(order, ["a", "bb", "ccc"]);
+        assert_eq!(library.untrash(0).unwrap(), "a");
+        let a = library.get("a").unwrap();
+        assert_eq!(
+            (a.title.as_str(), a.playtime),
+            ("Celeste", Some(Duration::hours(2)))
+        );
+        assert!(library.trashed().is_empty());
+
+        library.trash("ccc", now);
+        library.trash("a", now - Duration::days(KEEP_DAYS + 1));
+        assert_eq!(library.purge_expired(now), 1);
+        assert_eq!(library.purge(0).unwrap().game.title, "Tunic");
+        assert!(library.purge(0).is_err());
+    }
+}
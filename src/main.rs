@@ -1,100 +1,5776 @@
 #![feature(assert_matches)]
 slint::include_modules!();
 
-use gilrs::{Button, Event, EventType, Gilrs};
+use art::{
+    palette::{self, Palette},
+    placeholder::Placeholder,
+};
+use bus::Bus;
+use cec::{Cec, CecConfig};
+use clap::Parser;
+use controller::{
+    chord::{Action, ChordConfig, ChordDetector},
+    cursor::{self, Cursor},
+    input::{InputConfig, InputLimiter, PressClassifier, PressKind},
+    input_log::{self, InputRecorder, PadInput, Playback, Recorded},
+    layout_file, replay,
+    sequence::SequenceMatcher,
+    viewport::{Easing, ScrollHint, Viewport},
+    FocusId, FocusKind, FocusRect, FrameCoalescer, LayoutId, ModelUpdate, PadKind, Router, Screen,
+    UiBridge,
+};
+use diagnostics::{Diagnostics, DiagnosticsConfig};
+use dialogs::Dialogs;
+use dispatch::Dispatcher;
+use feeds::{Feeds, FeedsConfig, Headline, NewsItem};
+use gilrs::{Axis, Button, Event, EventType, GamepadId, Gilrs};
+use i18n::{LocaleConfig, Translations};
+use instance::SingleInstance;
+use integrations::{
+    legendary,
+    retroachievements::{GameProgress, RetroAchievements, RetroAchievementsConfig},
+    steam,
+};
+use keyboard::{Keyboard, Typing};
+use kiosk::{KioskConfig, PinLock};
+use launcher::{
+    profile::{self, Profile},
+    resume::{self, ResumeConfig, SessionState},
+    suspend::SuspendConfig,
+    ConflictSide, HookStage, LaunchEvent, Launcher, ProfileConfig, SyncConfig,
+};
+use library::{
+    backup::{Backup, RestoreMode},
+    bulk::{Bulk, BulkAction, Outcome},
+    index::{self, TitleIndex},
+    matches::Matches,
+    parental::ParentalConfig,
+    roots::{self, RootsConfig, RootsEvent},
+    trash::Removal,
+    Library,
+};
+use log::{info, warn};
+use metrics::{Metrics, MetricsConfig};
+use mqtt::{Mqtt, MqttCommand, MqttConfig};
+use playback::{Animation, Frame, PlaybackConfig, Player};
+use presence::{Presence, PresenceConfig};
+use quick_settings::QuickSettings;
+use quiet::QuietConfig;
+use recommend::{Pick, PlayLog, Reason};
+use remote::{
+    pairing::{PairRequest, Pairing},
+    RemoteCommand, RemoteConfig, RemoteServer,
+};
+use saves::{SaveBackups, SaveConfig, Snapshot};
+use sessions::{
+    stats::{self, Period},
+    Session, SessionLog,
+};
 use slint::Model;
-use std::{sync::mpsc, thread};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    os::unix::process::CommandExt,
+    path::PathBuf,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+use storage::Usage;
+use supervisor::{Restart, Supervisor};
+use sync::{keys::DeviceKey, Syncer};
+use tiles::{GameTiles, LibraryChange, TileSources};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use transfer::{TransferId, TransferInfo, TransferKind, TransferManager, TransferState};
+use updater::{Release, UpdateConfig, Updater};
 
+mod art;
+mod boot;
+mod bus;
+mod cec;
+mod cli;
 mod controller;
+mod diagnostics;
+mod dialogs;
+mod dispatch;
+mod feeds;
+mod i18n;
+mod instance;
+mod integrations;
+mod keyboard;
+mod kiosk;
+mod launcher;
+mod library;
+mod mdns;
+mod metrics;
 mod models;
+mod mqtt;
+mod paths;
+mod playback;
+mod presence;
+mod quick_settings;
+mod quiet;
+mod recommend;
+mod remote;
+mod runtime;
+mod saves;
+mod sessions;
+mod storage;
+mod supervisor;
+mod sync;
+mod tiles;
+mod timedate;
+mod transfer;
+mod updater;
 
-fn controller_loop(tx: mpsc::Sender<Button>) {
-    let mut gilrs = Gilrs::new().unwrap();
-    for (_id, gamepad) in gilrs.gamepads() {
-        println!("{} is {:?}", gamepad.name(), gamepad.power_info());
+/// How long A is held down to pick up a tile.
+const HOLD_DURATION: Duration = Duration::from_millis(500);
+/// Two clicks on the same tile closer than this play it.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+/// How often the gamepads are read.
+const INPUT_POLL: Duration = Duration::from_millis(4);
+/// How often the right stick is read for the cursor, about once a frame.
+const STICK_INTERVAL: Duration = Duration::from_millis(16);
+/// Before opening the gamepads again when they couldn't be.
+const PADS_RETRY: Duration = Duration::from_secs(5);
+/// How often the top bar clock is updated.
+const CLOCK_INTERVAL: Duration = Duration::from_secs(1);
+/// Games whose covers are queued for download on startup, the first rows.
+const WARM_COVERS: usize = 32;
+/// How often the app asking to pair is checked on, to take the dialog down
+/// once it stopped waiting.
+const PAIR_CHECK: Duration = Duration::from_secs(1);
+/// Typed on the PIN screen, longer ones can't be right.
+const MAX_PIN_DIGITS: usize = 12;
+/// Navigation events listed in the debug overlay.
+const DEBUG_EVENTS: usize = 8;
+/// Given to background checks, e.g. for updates, before giving up on them.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+/// Given to the diagnostics upload.
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+/// How long the layout files are left alone before reloading, saving one
+/// can take a few writes.
+const LAYOUT_SETTLE: Duration = Duration::from_millis(200);
+/// Cells of the suggestions row on the home screen.
+const PICK_CELLS: usize = 5;
+/// Weeks or months charted on the stats screen.
+const STATS_PERIODS: usize = 12;
+/// Genres and games listed on the stats screen.
+const STATS_TOP: usize = 5;
+/// Covers of the candidates on the matches screen are downloaded for this,
+/// then the app ID.
+const MATCH_ART: &str = "match-";
+/// How long a toast stays up.
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+/// How long the letter jumped to stays highlighted on the jump strip.
+const JUMP_HIGHLIGHT: Duration = Duration::from_millis(1500);
+
+/// What's made of the gamepad's input, the same for a real gamepad and one
+/// played back, given when each input came.
+struct InputPipeline {
+    tx: UnboundedSender<NavigationEvent>,
+    chords: ChordDetector,
+    sequences: SequenceMatcher,
+    presses: PressClassifier,
+    // A is sent when released, unless it was held long enough to pick up.
+    south_pressed: Option<Instant>,
+    holding: bool,
+    /// See `anubis --record-input`.
+    recorder: Option<Arc<Mutex<InputRecorder>>>,
+}
+
+impl InputPipeline {
+    fn input(&mut self, input: PadInput, now: Instant) {
+        if let Some(recorder) = &self.recorder {
+            if let Err(e) = recorder.lock().unwrap().record(input, now) {
+                warn!("failed to record {:?}: {:?}", input, e);
+            }
+        }
+        let tx = self.tx.clone();
+        match input {
+            PadInput::Connected(kind) => {
+                tx.send(NavigationEvent::Pad(kind)).unwrap();
+                return;
+            }
+            PadInput::Stick { x, y, dt } => {
+                tx.send(NavigationEvent::Stick { x, y, dt }).unwrap();
+                return;
+            }
+            PadInput::Pressed(b) => {
+                if let Some(action) = self.chords.press(b) {
+                    if self.chords.is_swallowed(Button::South) {
+                        self.south_pressed = None;
+                    }
+                    let chords = &self.chords;
+                    self.presses.forget(|b| chords.is_swallowed(b));
+                    tx.send(NavigationEvent::Action(action)).unwrap();
+                    return;
+                }
+                // The press still goes through.
+                if let Some(action) = self.sequences.press(b, now) {
+                    tx.send(NavigationEvent::Action(action)).unwrap();
+                }
+            }
+            PadInput::Released(b) if self.chords.release(b) => return,
+            PadInput::Released(_) => (),
+        }
+        // Bound to a long or double press, held back until it's clear which
+        // it is.
+        let classified = match input {
+            PadInput::Pressed(b) if self.presses.handles(b) => Some(self.presses.press(b, now)),
+            PadInput::Released(b) if self.presses.handles(b) => Some(self.presses.release(b, now)),
+            _ => None,
+        };
+        if let Some(classified) = classified {
+            self.send_presses(classified);
+            return;
+        }
+        match input {
+            PadInput::Pressed(Button::South) => self.south_pressed = Some(now),
+            PadInput::Released(Button::South) => {
+                if self.south_pressed.take().is_some() {
+                    tx.send(NavigationEvent::Button(Button::South)).unwrap();
+                } else if self.holding {
+                    self.holding = false;
+                    tx.send(NavigationEvent::Released(Button::South)).unwrap();
+                }
+            }
+            PadInput::Pressed(b) => tx.send(NavigationEvent::Button(b)).unwrap(),
+            _ => (),
+        }
+    }
+
+    /// Let through what's been waited on long enough by now.
+    fn tick(&mut self, now: Instant) {
+        let due = self.presses.due(now);
+        self.send_presses(due);
+        if self
+            .south_pressed
+            .is_some_and(|t| now.duration_since(t) >= HOLD_DURATION)
+        {
+            self.south_pressed = None;
+            self.holding = true;
+            self.tx.send(NavigationEvent::Held(Button::South)).unwrap();
+        }
+    }
+
+    /// A short press is the button itself, the others do what they're bound
+    /// to.
+    fn send_presses(&self, classified: Vec<(Button, PressKind)>) {
+        for (b, kind) in classified {
+            let event = match self.presses.action_for(b, kind) {
+                Some(action) => NavigationEvent::Action(action),
+                None => NavigationEvent::Button(b),
+            };
+            self.tx.send(event).unwrap();
+        }
+    }
+}
+
+/// `others` are the inputs of other devices, e.g. the TV remote, see `cec`.
+/// The gamepads, once they could be opened.
+struct Pads {
+    gilrs: Gilrs,
+    active: Option<GamepadId>,
+    last_stick: Instant,
+}
+
+impl Pads {
+    fn open(now: Instant) -> anyhow::Result<Self> {
+        let gilrs =
+            Gilrs::new().map_err(|e| anyhow::anyhow!("failed to open the gamepads: {}", e))?;
+        for (_id, gamepad) in gilrs.gamepads() {
+            println!("{} is {:?}", gamepad.name(), gamepad.power_info());
+        }
+        Ok(Self {
+            gilrs,
+            active: None,
+            last_stick: now,
+        })
+    }
+
+    /// Feed what happened since the last poll through the pipeline.
+    fn poll(&mut self, pipeline: &mut InputPipeline, now: Instant) {
+        // Examine new events
+        while let Some(Event { id, event, time }) = self.gilrs.next_event() {
+            println!("{:?} New event from {}: {:?}", time, id, event);
+            if self.active != Some(id) {
+                self.active = Some(id);
+                pipeline.input(
+                    PadInput::Connected(PadKind::of(&self.gilrs.gamepad(id))),
+                    now,
+                );
+            }
+            match event {
+                EventType::ButtonPressed(b, _) => pipeline.input(PadInput::Pressed(b), now),
+                EventType::ButtonReleased(b, _) => pipeline.input(PadInput::Released(b), now),
+                _ => (),
+            }
+        }
+        if now.duration_since(self.last_stick) >= STICK_INTERVAL {
+            let dt = now.duration_since(self.last_stick);
+            self.last_stick = now;
+            if let Some(id) = self.active {
+                let pad = self.gilrs.gamepad(id);
+                let (x, y) = (pad.value(Axis::RightStickX), pad.value(Axis::RightStickY));
+                if x.hypot(y) >= cursor::DEADZONE {
+                    pipeline.input(PadInput::Stick { x, y, dt }, now);
+                }
+            }
+        }
+    }
+}
+
+/// One poll of the input, of the gamepads if they could be opened and of the
+/// others, e.g. the TV remote, which work without.
+fn poll_inputs(
+    pipeline: &mut InputPipeline,
+    pads: Option<&mut Pads>,
+    others: &mpsc::Receiver<PadInput>,
+    now: Instant,
+) {
+    if let Some(pads) = pads {
+        pads.poll(pipeline, now);
+    }
+    for input in others.try_iter() {
+        pipeline.input(input, now);
+    }
+    pipeline.tick(now);
+}
+
+async fn input_task(
+    mut pipeline: InputPipeline,
+    others: &mpsc::Receiver<PadInput>,
+) -> anyhow::Result<()> {
+    let mut pads = None;
+    let mut tried: Option<Instant> = None;
+    let mut polls = tokio::time::interval(INPUT_POLL);
+
+    loop {
+        polls.tick().await;
+        let now = Instant::now();
+        // Tried again every so often, the other inputs go through meanwhile.
+        if pads.is_none() && tried.is_none_or(|t| now.duration_since(t) >= PADS_RETRY) {
+            match Pads::open(now) {
+                Ok(opened) => pads = Some(opened),
+                Err(e) if tried.is_none() => warn!("{:#}", e),
+                Err(_) => (),
+            }
+            tried = Some(now);
+        }
+        poll_inputs(&mut pipeline, pads.as_mut(), others, now);
+    }
+}
+
+/// Feed what was recorded with `--record-input` through the pipeline, on the
+/// playback's own clock so it comes out the same each time.
+async fn play_inputs(pipeline: &mut InputPipeline, recorded: Vec<Recorded>) {
+    let started = Instant::now();
+    let mut polls = tokio::time::interval(INPUT_POLL);
+    let mut played = 0;
+    for (at, inputs) in Playback::new(recorded, INPUT_POLL) {
+        polls.tick().await;
+        let now = started + at;
+        for input in inputs {
+            pipeline.input(input, now);
+            played += 1;
+        }
+        pipeline.tick(now);
+    }
+    info!("played back {} inputs", played);
+}
+
+/// Screenshots taken while playing the game, newest first.
+fn screenshots_of(uuid: &str) -> Vec<PathBuf> {
+    let dir = paths::data_dir().join("screenshots").join(uuid);
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut shots: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "png"))
+        .collect();
+    // Named after when they were taken.
+    shots.sort();
+    shots.reverse();
+    shots
+}
+
+/// What the cover of a candidate on the matches screen is downloaded for.
+fn match_art_key(app_id: steam::AppId) -> String {
+    format!("{}{}", MATCH_ART, app_id)
+}
+
+/// A news item as listed, `game` empty in the game's own news.
+fn news_data(item: &NewsItem, game: &str) -> NewsData {
+    NewsData {
+        title: item.title.clone().into(),
+        game: game.into(),
+        published: item
+            .published
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+            .into(),
+        summary: item.summary.clone().into(),
+    }
+}
+
+/// Set the UI's text in the language of `t`.
+fn show_strings(e: &HomeWindow, t: &Translations) {
+    let s = e.global::<Strings>();
+    s.set_games(t.tr("games").into());
+    s.set_recently_played(t.tr("recently-played").into());
+    s.set_tasks(t.tr("tasks").into());
+    s.set_settings(t.tr("settings").into());
+    s.set_back(t.tr("back").into());
+    s.set_cancel(t.tr("cancel").into());
+    s.set_close(t.tr("close").into());
+    s.set_library(t.tr("library").into());
+    s.set_back_up_library(t.tr("back-up-library").into());
+    s.set_restore_merge(t.tr("restore-merge").into());
+    s.set_restore_replace(t.tr("restore-replace").into());
+    s.set_find_duplicates(t.tr("find-duplicates").into());
+    s.set_resolve_matches(t.tr("resolve-matches").into());
+    s.set_trash(t.tr("trash").into());
+    s.set_empty_trash(t.tr("empty-trash").into());
+    s.set_trash_empty(t.tr("trash-empty").into());
+    s.set_restore(t.tr("restore").into());
+    s.set_purge(t.tr("purge").into());
+    s.set_language(t.tr("language").into());
+    s.set_language_name(t.tr("language-name").into());
+    s.set_duplicates(t.tr("duplicates").into());
+    s.set_merge(t.tr("merge").into());
+    s.set_skip(t.tr("skip").into());
+    s.set_play(t.tr("play").into());
+    s.set_overview(t.tr("overview").into());
+    s.set_achievements(t.tr("achievements").into());
+    s.set_screenshots(t.tr("screenshots").into());
+    s.set_no_screenshots(t.tr("no-screenshots").into());
+    s.set_news(t.tr("news").into());
+    s.set_sessions(t.tr("sessions").into());
+    s.set_no_sessions(t.tr("no-sessions").into());
+    s.set_play_stats(t.tr("play-stats").into());
+    s.set_weekly(t.tr("weekly").into());
+    s.set_monthly(t.tr("monthly").into());
+    s.set_by_genre(t.tr("by-genre").into());
+    s.set_most_played(t.tr("most-played").into());
+    s.set_what_to_play(t.tr("what-to-play").into());
+    s.set_overlay(t.tr("overlay").into());
+    s.set_locked(t.tr("locked").into());
+    s.set_points(t.tr("points").into());
+    s.set_not_installed(t.tr("not-installed").into());
+    s.set_status_playing(t.tr("status-playing").into());
+    s.set_status_completed(t.tr("status-completed").into());
+    s.set_status_abandoned(t.tr("status-abandoned").into());
+    s.set_streamed(t.tr("streamed").into());
+    s.set_unavailable(t.tr("unavailable").into());
+    s.set_please_wait(t.tr("please-wait").into());
+    s.set_keep_local(t.tr("keep-local").into());
+    s.set_keep_remote(t.tr("keep-remote").into());
+    s.set_date_and_time(t.tr("date-and-time").into());
+    s.set_time_zone(t.tr("time-zone").into());
+    s.set_sync_time(t.tr("sync-time").into());
+    s.set_on(t.tr("on").into());
+    s.set_off(t.tr("off").into());
+    s.set_not_synced_yet(t.tr("not-synced-yet").into());
+    s.set_software_update(t.tr("software-update").into());
+    s.set_update_available(t.tr("update-available").into());
+    s.set_install_update(t.tr("install-update").into());
+    s.set_up_to_date(t.tr("up-to-date").into());
+    s.set_restart_to_update(t.tr("restart-to-update").into());
+    s.set_whats_new(t.tr("whats-new").into());
+    s.set_diagnostics(t.tr("diagnostics").into());
+    s.set_launch_profiles(t.tr("launch-profiles").into());
+    s.set_new_profile(t.tr("new-profile").into());
+    s.set_no_profiles(t.tr("no-profiles").into());
+    s.set_cpu_governor(t.tr("cpu-governor").into());
+    s.set_hdr(t.tr("hdr").into());
+    s.set_vrr(t.tr("vrr").into());
+    s.set_compositor_bypass(t.tr("compositor-bypass").into());
+    s.set_saves(t.tr("saves").into());
+    s.set_backup_saves(t.tr("backup-saves").into());
+    s.set_restore_save(t.tr("restore-save").into());
+    s.set_no_save_location(t.tr("no-save-location").into());
+    s.set_no_snapshots(t.tr("no-snapshots").into());
+    s.set_storage(t.tr("storage").into());
+    s.set_rescan_storage(t.tr("rescan-storage").into());
+    s.set_clear_art_cache(t.tr("clear-art-cache").into());
+    s.set_largest_unplayed(t.tr("largest-unplayed").into());
+    s.set_no_suggestions(t.tr("no-suggestions").into());
+    s.set_uninstall(t.tr("uninstall").into());
+    s.set_exit_to_desktop(t.tr("exit-to-desktop").into());
+    s.set_mqtt(t.tr("mqtt").into());
+    s.set_mqtt_broker(t.tr("mqtt-broker").into());
+    s.set_mqtt_username(t.tr("mqtt-username").into());
+    s.set_mqtt_password(t.tr("mqtt-password").into());
+    s.set_suspended(t.tr("suspended").into());
+    s.set_no_suspended(t.tr("no-suspended").into());
+    s.set_resume(t.tr("resume").into());
+    s.set_quit_game(t.tr("quit-game").into());
+    s.set_quiet_hours(t.tr("quiet-hours").into());
+    s.set_auto(t.tr("auto").into());
+    s.set_quick_settings(t.tr("quick-settings").into());
+    s.set_brightness(t.tr("brightness").into());
+    s.set_volume(t.tr("volume").into());
+    s.set_wifi(t.tr("wifi").into());
+    s.set_bluetooth(t.tr("bluetooth").into());
+    s.set_not_available(t.tr("not-available").into());
+    s.set_add_favorite(t.tr("add-favorite").into());
+    s.set_remove_favorite(t.tr("remove-favorite").into());
+    s.set_properties(t.tr("properties").into());
+    s.set_confirm(t.tr("confirm").into());
+    s.set_enter_pin(t.tr("enter-pin").into());
+    s.set_notes(t.tr("notes").into());
+    s.set_space(t.tr("space").into());
+    s.set_update(t.tr("update").into());
+    s.set_rescrape(t.tr("rescrape").into());
+    s.set_add_tag(t.tr("add-tag").into());
+    s.set_move_to_collection(t.tr("move-to-collection").into());
+    s.set_delete(t.tr("delete").into());
+    s.set_clear_selection(t.tr("clear-selection").into());
+}
+
+fn focus_rect_data(r: &controller::FocusRect) -> FocusRectData {
+    let offset = r.placement.map_or((0, 0), |p| (p.x(), p.y()));
+    FocusRectData {
+        layout: r.layout_id.to_string().into(),
+        x: r.rect.x() as i32,
+        y: r.rect.y() as i32,
+        width: r.rect.width() as i32,
+        height: r.rect.height() as i32,
+        offset_x: offset.0 as i32,
+        offset_y: offset.1 as i32,
+    }
+}
+
+/// Input for the navigation thread.
+#[derive(Debug)]
+enum NavigationEvent {
+    Button(Button),
+    /// Held down for `HOLD_DURATION`.
+    Held(Button),
+    /// Let go after being held.
+    Released(Button),
+    Launch(LaunchEvent),
+    Remote(RemoteCommand),
+    /// From another instance.
+    Instance(instance::Message),
+    /// A different gamepad is in use.
+    Pad(PadKind),
+    /// A click or tap in the UI.
+    Pointer(PointerPress),
+    /// The right stick is tilted, `dt` since it was last read.
+    Stick {
+        x: f32,
+        y: f32,
+        dt: Duration,
+    },
+    /// A chord was pressed.
+    Action(Action),
+    /// The layout file of a screen changed, see `anubis --layouts`.
+    LayoutChanged(Screen),
+    Achievements {
+        uuid: String,
+        progress: Result<GameProgress, String>,
+    },
+    /// Releases newer than this one, see the updater.
+    Updates(Vec<Release>),
+    /// A thread was restarted, see the supervisor.
+    Restarted(Restart),
+    /// Who legendary is signed in as, if anyone.
+    EpicAccount(Option<String>),
+    /// A backup or restore of the saves of a game is done, with the message
+    /// saying how it went or why it failed.
+    Saves {
+        uuid: String,
+        status: Result<&'static str, String>,
+    },
+    /// The disk usage scan is done.
+    Storage(Result<Usage, String>),
+    /// Of a task started from the settings.
+    SettingsStatus(String),
+    /// A library root was plugged in or out, or its games are being added,
+    /// see `library::roots`.
+    Roots(RootsEvent),
+    /// Seconds left before the game played last is launched again, see
+    /// `launcher::resume`.
+    ResumeTick(u32),
+    /// Quiet hours started or ended, see `quiet`.
+    QuietHours(bool),
+    /// An entry of a game's menu was picked.
+    GameMenu {
+        uuid: String,
+        item: GameMenuItem,
+    },
+    /// The colors of a game's cover, see `art::palette`.
+    Palette {
+        uuid: String,
+        palette: Result<Palette, String>,
+    },
+    /// The news of the game shown in the details screen.
+    News {
+        uuid: String,
+        news: Result<Vec<NewsItem>, String>,
+    },
+    /// The newest news of the followed games, see `feeds`.
+    LatestNews(Vec<Headline>),
+    /// The suggestions of what to play for the day, see `recommend`.
+    Picks(Vec<Pick>),
+    /// The next frame of a game's animated background, see `playback`.
+    HeroFrame {
+        uuid: String,
+        frame: Frame,
+    },
+    /// A download of the art cache is done, by the key it was fetched for.
+    ArtDownloaded(String),
+    /// The store details of the app picked for a game, see
+    /// `library::matches`.
+    Matched {
+        uuid: String,
+        details: Result<Box<(steam::StoreDetails, Option<steam::StoreDetails>)>, String>,
+    },
+    /// A run of the bulk actions is over, see `library::bulk`.
+    Bulk(Result<Outcome, String>),
+    /// Changes from other devices were replayed, to the games, see `sync`.
+    Synced(Vec<String>),
+    /// From home automation.
+    Mqtt(MqttCommand),
+    /// A question of the dialogs isn't awaited anymore, see `Dialogs::ask`.
+    Unawaited,
+}
+
+/// Done once confirmed, see `dialogs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Confirmed {
+    Uninstall(String),
+    ExitToDesktop,
+    /// The library replaced by the latest backup.
+    RestoreReplace,
+    /// Out of the library, by UUID.
+    DeleteGames(Vec<String>),
+    EmptyTrash,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GameMenuItem {
+    Play,
+    Favorite,
+    /// On to the next completion status.
+    Status,
+    /// From Steam.
+    Update,
+    Uninstall,
+    /// The details screen.
+    Properties,
+}
+
+#[derive(Debug, Clone)]
+enum PointerPress {
+    /// Somewhere in a layout, in cells.
+    At { layout: LayoutId, x: f32, y: f32 },
+    /// On an element that knows its id, e.g. a button.
+    On(FocusId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DetailsTab {
+    Overview,
+    Achievements,
+    Screenshots,
+    News,
+    Sessions,
+}
+
+impl DetailsTab {
+    const ALL: [DetailsTab; 5] = [
+        DetailsTab::Overview,
+        DetailsTab::Achievements,
+        DetailsTab::Screenshots,
+        DetailsTab::News,
+        DetailsTab::Sessions,
+    ];
+
+    /// Also the section of its layout.
+    fn name(self) -> &'static str {
+        match self {
+            DetailsTab::Overview => "Overview",
+            DetailsTab::Achievements => "Achievements",
+            DetailsTab::Screenshots => "Screenshots",
+            DetailsTab::News => "News",
+            DetailsTab::Sessions => "Sessions",
+        }
+    }
+
+    fn layout(self) -> LayoutId {
+        LayoutId::sublayout("GameDetails", self.name())
+    }
+
+    /// Of its button.
+    fn focus_id(self) -> FocusId {
+        FocusId::tab(&self.name().to_uppercase())
+    }
+}
+
+/// The row of an achievement, a screenshot, a news item or a session in its
+/// tab, for scrolling to.
+#[derive(Debug, Clone, Copy)]
+struct DetailsRow(usize);
+
+/// The game shown in the details screen.
+struct GameDetails {
+    uuid: String,
+    tab: DetailsTab,
+    /// Number of achievements listed.
+    achievements: usize,
+    /// Taken while playing it, newest first.
+    screenshots: Vec<PathBuf>,
+    /// Number of news items listed.
+    news: usize,
+    /// Number of sessions listed.
+    sessions: usize,
+}
+
+/// The sync dialog being shown.
+struct SyncPrompt {
+    uuid: String,
+    stage: HookStage,
+    /// Waiting for the user to pick a side.
+    conflict: bool,
+    /// Hooks are running, input is ignored until they are done.
+    busy: bool,
+}
+
+/// The window as seen by the navigation.
+impl UiBridge for slint::Weak<HomeWindow> {
+    type Window = HomeWindow;
+
+    fn set_focus(&self, screen: Screen, focus: &FocusId) -> anyhow::Result<()> {
+        let focus = focus.to_string();
+        self.upgrade_in_event_loop(move |e| {
+            let state = e.global::<HomeWindowFocus>();
+            if screen.is_modal() {
+                state.set_modal_screen(screen.name().into());
+            } else {
+                state.set_active_screen(screen.name().into());
+                state.set_modal_screen("".into());
+            }
+            state.set_focused_id(focus.into());
+        })?;
+        Ok(())
+    }
+
+    fn set_scroll(&self, from: Option<&FocusRect>, to: &FocusRect) -> anyhow::Result<()> {
+        let from = from.map(focus_rect_data).unwrap_or_default();
+        let to = focus_rect_data(to);
+        // The screens scroll after focus-to.
+        self.upgrade_in_event_loop(move |e| {
+            let state = e.global::<HomeWindowFocus>();
+            state.set_focus_from(from);
+            state.set_focus_to(to);
+        })?;
+        Ok(())
+    }
+
+    fn push_model_update(&self, update: ModelUpdate<HomeWindow>) -> anyhow::Result<()> {
+        self.upgrade_in_event_loop(move |e| update(&e))?;
+        Ok(())
+    }
+}
+
+/// Owns the router and turns input into actions.
+struct Navigator {
+    ui: Box<dyn UiBridge<Window = HomeWindow>>,
+    events: UnboundedSender<NavigationEvent>,
+    router: Router,
+    library: Arc<Mutex<Library>>,
+    steam: steam::SteamInstaller,
+    transfers: TransferManager,
+    launcher: Launcher,
+    retroachievements: Option<RetroAchievements>,
+    feeds: Feeds,
+    presence: Presence,
+    listed_downloads: HashSet<TransferId>,
+    sync_prompt: Option<SyncPrompt>,
+    details: Option<GameDetails>,
+    /// Groups of duplicated games left to review.
+    duplicates: Vec<Vec<String>>,
+    /// The games waiting for a pick, read when the matches screen is opened.
+    matches: Matches,
+    /// D-pad presses are saved to replay them, see `anubis --record`.
+    recorder: Option<replay::Recorder>,
+    /// The last tile clicked, to tell double clicks.
+    last_click: Option<(FocusId, Instant)>,
+    /// The stick cursor, when it's on.
+    cursor: Option<Cursor>,
+    /// See `ChordConfig`.
+    screenshot_command: Vec<String>,
+    debug_overlay: bool,
+    /// The latest navigation events, for the debug overlay.
+    recent_events: VecDeque<String>,
+    i18n: Arc<Translations>,
+    /// The time zones to go through, read when first needed.
+    timezones: Vec<String>,
+    /// None unless updates are set up.
+    updater: Option<Updater>,
+    /// Newer than this one, the newest first.
+    releases: Vec<Release>,
+    diagnostics: Diagnostics,
+    /// Shared with the UI, which times the frames.
+    metrics: Metrics,
+    /// Shared with the launcher.
+    profiles: Arc<Mutex<ProfileConfig>>,
+    /// The profile shown in the editor.
+    profile: Option<String>,
+    saves: SaveBackups,
+    /// Of the game shown, newest first.
+    save_snapshots: Vec<Snapshot>,
+    /// The snapshot shown.
+    save_index: usize,
+    /// The last disk usage scan, read from the cache when first shown.
+    storage: Option<Usage>,
+    /// None when it couldn't be opened.
+    art: Option<art::ArtCache>,
+    /// The task of the last scan.
+    storage_scan: Option<TransferId>,
+    /// The uninstall suggestion shown.
+    suggestion: usize,
+    /// Names of the library roots whose drive is unplugged, shared with the
+    /// tiles.
+    unavailable_roots: Arc<Mutex<HashSet<String>>>,
+    /// Of the titles on the tiles, shared with them.
+    tile_locale: Arc<Mutex<String>>,
+    kiosk: KioskConfig,
+    playback_config: PlaybackConfig,
+    /// The game whose background is shown behind the home screen.
+    hero: Option<String>,
+    /// Playing it, when it's animated.
+    hero_player: Option<Player>,
+    /// Digits typed on the PIN screen.
+    pin_entry: String,
+    /// After wrong PINs.
+    pin_lock: PinLock,
+    /// What's typed on the on-screen keyboard, while it's shown.
+    keyboard: Option<Keyboard>,
+    /// UUIDs of the games picked with Y, see `library::bulk`.
+    selected: Arc<Mutex<HashSet<String>>>,
+    /// The games just deleted, with when, put back with Select while the
+    /// toast is up.
+    undo_delete: Option<(Vec<String>, Instant)>,
+    /// The entry of the trash shown, the newest first.
+    trash_index: usize,
+    /// Companion apps asking to pair, see `remote::pairing`.
+    pairing: Pairing,
+    /// Where focus, what's played and toasts go for the companion apps.
+    bus: Bus,
+    /// Shared with the MQTT client, which connects again when it changes.
+    mqtt_config: Arc<Mutex<MqttConfig>>,
+    resume_config: ResumeConfig,
+    /// The game launched once the countdown is over, None once cancelled.
+    resuming: Option<String>,
+    /// The suspended game shown.
+    suspended_index: usize,
+    quiet_config: QuietConfig,
+    /// Whether the schedule has it quiet now.
+    quiet_scheduled: bool,
+    /// Set from the settings, until the schedule next changes.
+    quiet_override: Option<bool>,
+    /// As read when the quick settings were opened, then as set.
+    quick_settings: QuickSettings,
+    /// Of the game menu shown.
+    menu_game: Option<String>,
+    dialogs: Dialogs<Confirmed>,
+    /// The games by title, for the jump strip.
+    title_index: TitleIndex,
+    /// Where the games grid is scrolled to.
+    viewport: Viewport,
+    /// Games being added from a library root, shown as placeholders.
+    importing: Vec<String>,
+    /// The game running, screenshots taken go with it.
+    playing: Option<String>,
+    /// What A does, shared so it can be run with the navigator.
+    handlers: Arc<Dispatcher<Navigator>>,
+    /// Fetching the achievements of the game shown.
+    achievements_job: Option<runtime::Job>,
+    /// Fetching the news of the game shown.
+    news_job: Option<runtime::Job>,
+    /// Working out the colors of the focused game's cover.
+    palette_job: Option<runtime::Job>,
+    /// Of the games' covers, None when they couldn't be read.
+    palettes: HashMap<String, Option<Palette>>,
+    /// When each game was last played, shared with the suggestions.
+    plays: Arc<Mutex<PlayLog>>,
+    /// Every time a game was played.
+    sessions: SessionLog,
+    /// The game running and since when, until it's paused or exits.
+    session_started: Option<(String, chrono::DateTime<chrono::Utc>)>,
+    /// What the stats screen adds the playtime up by.
+    stats_period: Period,
+    /// Suggested on the home screen, in order.
+    picks: Vec<Pick>,
+    /// Of the suggestions shown in each cell of the row, to only update it
+    /// when it turned.
+    picks_shown: Vec<Option<String>>,
+}
+
+/// What A does on each screen, see `dispatch`.
+fn handlers() -> Dispatcher<Navigator> {
+    let mut d = Dispatcher::<Navigator>::default();
+    d.on_button("BACK", |n, _| {
+        n.router.pop();
+    });
+
+    // Home.
+    d.on_button("DOWNLOADS", |n, _| n.open_downloads())
+        .on_button("SUSPENDED", |n, _| {
+            n.router.push(Screen::Suspended);
+            n.suspended_index = 0;
+            n.show_suspended(None);
+        })
+        .on_button("SETTINGS", |n, _| {
+            n.router.push(Screen::Settings);
+            n.show_settings_headers();
+            n.show_art_cache_usage();
+            n.check_epic_account();
+            n.show_mqtt();
+        })
+        .on_kind(FocusKind::Game, |n, f| n.open_details(f.payload()))
+        .on_kind(FocusKind::Pick, |n, f| n.open_details(f.payload()));
+
+    // Suspended games.
+    d.on_button("SUSPENDED_PREV", |n, _| n.step_suspended(-1))
+        .on_button("SUSPENDED_NEXT", |n, _| n.step_suspended(1))
+        .on_button("RESUME_SUSPENDED", |n, _| n.resume_suspended())
+        .on_button("QUIT_SUSPENDED", |n, _| n.quit_suspended());
+
+    // Settings.
+    d.on_button("BACKUP_LIBRARY", |n, _| n.backup_library())
+        .on_button("RESTORE_MERGE", |n, _| {
+            n.restore_library(RestoreMode::Merge)
+        })
+        .on_button("RESTORE_REPLACE", |n, _| {
+            n.confirm(
+                "restore-replace",
+                "confirm-restore-replace",
+                None,
+                Confirmed::RestoreReplace,
+            )
+        })
+        .on_button("FIND_DUPLICATES", |n, _| -> anyhow::Result<()> {
+            n.duplicates = n.library.lock().unwrap().duplicates();
+            n.router.rebuild(Screen::Duplicates)?;
+            n.router.push(Screen::Duplicates);
+            n.show_duplicates();
+            Ok(())
+        })
+        .on_button("TRASH", |n, _| {
+            n.trash_index = 0;
+            n.router.push(Screen::Trash);
+            n.show_trash(None);
+        })
+        .on_button("RESOLVE_MATCHES", |n, _| -> anyhow::Result<()> {
+            n.matches = Matches::load(&Matches::default_path())?;
+            n.router.push(Screen::Matches);
+            n.layout_matches()
+        })
+        .on_button("LANGUAGE", |n, _| n.next_locale())
+        .on_button("CLOCK", |n, _| {
+            n.router.push(Screen::Clock);
+            n.show_clock();
+        })
+        .on_button("SOFTWARE_UPDATE", |n, _| n.router.push(Screen::Update))
+        .on_button("DIAGNOSTICS", |n, _| n.toggle_diagnostics())
+        .on_button("STORAGE", |n, _| {
+            n.router.push(Screen::Storage);
+            if n.storage.is_none() {
+                n.storage = Usage::load(&Usage::default_path()).unwrap_or_else(|e| {
+                    warn!("failed to read the last disk usage scan: {:?}", e);
+                    None
+                });
+            }
+            n.scan_storage();
+        })
+        .on_button("CLEAR_ART_CACHE", |n, _| n.clear_art_cache())
+        .on_button("PROFILES", |n, _| {
+            n.router.push(Screen::Profiles);
+            n.show_profile();
+        })
+        .on_button("RESUME_LAST_GAME", |n, _| n.toggle_resume())
+        .on_button("QUIET_HOURS", |n, _| n.cycle_quiet())
+        .on_button("PLAY_STATS", |n, _| {
+            n.router.push(Screen::Stats);
+            n.show_stats();
+        })
+        .on_button("MQTT", |n, _| n.change_mqtt(|c| c.enabled = !c.enabled))
+        .on_button("MQTT_BROKER", |n, _| {
+            let broker = n.mqtt_config.lock().unwrap().broker.clone();
+            let title = n.i18n.tr("type-mqtt-broker");
+            n.open_keyboard(Typing::MqttBroker, &broker, title)
+        })
+        .on_button("MQTT_USERNAME", |n, _| {
+            let username = n.mqtt_config.lock().unwrap().username.clone();
+            let title = n.i18n.tr("mqtt-username");
+            n.open_keyboard(Typing::MqttUsername, &username.unwrap_or_default(), title)
+        })
+        .on_button("MQTT_PASSWORD", |n, _| {
+            let title = n.i18n.tr("mqtt-password");
+            n.open_keyboard(Typing::MqttPassword, "", title)
+        })
+        .on_button("EXIT_TO_DESKTOP", |n, _| n.exit_to_desktop());
+
+    // Stats.
+    d.on_button("STATS_WEEKLY", |n, _| {
+        n.stats_period = Period::Week;
+        n.show_stats();
+    })
+    .on_button("STATS_MONTHLY", |n, _| {
+        n.stats_period = Period::Month;
+        n.show_stats();
+    });
+
+    // Matches.
+    d.on_button("SKIP_MATCH", |n, _| n.skip_match())
+        .on_kind(FocusKind::Candidate, |n, f| n.pick_match(f.payload()));
+
+    // Duplicates.
+    d.on_button("MERGE_DUPLICATES", |n, _| n.merge_duplicates())
+        .on_button("SKIP_DUPLICATES", |n, _| {
+            if !n.duplicates.is_empty() {
+                n.duplicates.remove(0);
+            }
+            n.show_duplicates();
+        });
+
+    // Trash.
+    d.on_button("TRASH_PREV", |n, _| n.step_trash(-1))
+        .on_button("TRASH_NEXT", |n, _| n.step_trash(1))
+        .on_button("RESTORE_TRASHED", |n, _| n.restore_trashed())
+        .on_button("PURGE_TRASHED", |n, _| n.purge_trashed())
+        .on_button("EMPTY_TRASH", |n, _| {
+            n.confirm(
+                "empty-trash",
+                "confirm-empty-trash",
+                None,
+                Confirmed::EmptyTrash,
+            )
+        });
+
+    // Clock.
+    d.on_button("TZ_PREV", |n, _| n.step_timezone(-1))
+        .on_button("TZ_NEXT", |n, _| n.step_timezone(1))
+        .on_button("NTP", |n, _| {
+            let on = timedate::status().map_or(true, |s| !s.ntp);
+            let result = timedate::set_ntp(on);
+            n.show_clock_result(result);
+        });
+
+    // Update.
+    d.on_button("INSTALL_UPDATE", |n, _| n.install_update());
+
+    // Profiles.
+    d.on_button("PROFILE_PREV", |n, _| n.step_profile(-1))
+        .on_button("PROFILE_NEXT", |n, _| n.step_profile(1))
+        .on_button("NEW_PROFILE", |n, _| {
+            let name = n.profiles.lock().unwrap().add();
+            n.profile = Some(name);
+            n.save_profiles();
+        })
+        .on_button("GOVERNOR", |n, _| {
+            n.edit_profile(|p| p.governor = profile::next_governor(p.governor.as_deref()))
+        })
+        .on_button("HDR", |n, _| {
+            n.edit_profile(|p| p.hdr = profile::next_toggle(p.hdr))
+        })
+        .on_button("VRR", |n, _| {
+            n.edit_profile(|p| p.vrr = profile::next_toggle(p.vrr))
+        })
+        .on_button("BYPASS", |n, _| {
+            n.edit_profile(|p| p.compositor_bypass = profile::next_toggle(p.compositor_bypass))
+        });
+
+    // Storage.
+    d.on_button("RESCAN_STORAGE", |n, _| n.scan_storage())
+        .on_button("SUGGESTION_PREV", |n, _| n.step_suggestion(-1))
+        .on_button("SUGGESTION_NEXT", |n, _| n.step_suggestion(1))
+        .on_button("UNINSTALL_SUGGESTED", |n, _| n.uninstall_suggested());
+
+    // Game details and its saves.
+    d.on_button("PLAY", |n, _| -> anyhow::Result<()> {
+        if let Some(uuid) = n.details.as_ref().map(|d| d.uuid.clone()) {
+            n.play(&uuid)?;
+        }
+        Ok(())
+    })
+    .on_button("OVERLAY", |n, _| n.toggle_overlay())
+    .on_button("SAVES", |n, _| {
+        n.router.push(Screen::Saves);
+        n.save_index = 0;
+        n.show_saves(String::new());
+    })
+    .on_button("NOTES", |n, _| n.edit_notes())
+    .on_kind(FocusKind::Tab, |n, f| {
+        match n.router.controller().payload::<DetailsTab>(f) {
+            Some(tab) => n.select_tab(tab),
+            None => Ok(()),
+        }
+    })
+    .on_button("SAVE_PREV", |n, _| n.step_save(-1))
+    .on_button("SAVE_NEXT", |n, _| n.step_save(1))
+    .on_button("BACKUP_SAVES", |n, _| n.backup_saves())
+    .on_button("RESTORE_SAVE", |n, _| n.restore_save());
+
+    // Downloads.
+    d.on_kind(FocusKind::DownloadPause, |n, f| {
+        n.transfers.toggle_pause(f.payload().parse()?)
+    })
+    .on_kind(FocusKind::DownloadCancel, |n, f| {
+        n.transfers.cancel(f.payload().parse()?)
+    });
+
+    // Dialogs and menus.
+    d.on_button("CONFIRM_CANCEL", |n, _| n.answer_confirm(false))
+        .on_button("CONFIRM_OK", |n, _| n.answer_confirm(true))
+        .on_button("SYNC_CANCEL", |n, _| n.close_sync_prompt())
+        .on_button("SYNC_KEEP_LOCAL", |n, _| {
+            n.resolve_conflict(ConflictSide::Local)
+        })
+        .on_button("SYNC_KEEP_REMOTE", |n, _| {
+            n.resolve_conflict(ConflictSide::Remote)
+        })
+        .on_button("MENU_PLAY", |n, _| n.pick_menu_item(GameMenuItem::Play))
+        .on_button("MENU_FAVORITE", |n, _| {
+            n.pick_menu_item(GameMenuItem::Favorite)
+        })
+        .on_button("MENU_STATUS", |n, _| n.pick_menu_item(GameMenuItem::Status))
+        .on_button("MENU_UPDATE", |n, _| n.pick_menu_item(GameMenuItem::Update))
+        .on_button("MENU_UNINSTALL", |n, _| {
+            n.pick_menu_item(GameMenuItem::Uninstall)
+        })
+        .on_button("MENU_PROPERTIES", |n, _| {
+            n.pick_menu_item(GameMenuItem::Properties)
+        })
+        .on_button("BULK_RESCRAPE", |n, _| {
+            n.router.pop();
+            n.run_bulk(BulkAction::Rescrape, n.selected_games());
+        })
+        .on_button("BULK_TAG", |n, _| {
+            n.router.pop();
+            n.type_for_selection(Typing::Tag, "tag-for")
+        })
+        .on_button("BULK_COLLECTION", |n, _| {
+            n.router.pop();
+            n.type_for_selection(Typing::Collection, "collection-for")
+        })
+        .on_button("BULK_DELETE", |n, _| {
+            n.router.pop();
+            n.confirm_delete_selected();
+        })
+        .on_button("BULK_CLEAR", |n, _| {
+            n.router.pop();
+            n.clear_selection();
+        })
+        .on_prefix(FocusKind::Button, "PIN_", |n, f| {
+            n.press_pin_key(f.payload().trim_start_matches("PIN_"))
+        })
+        .on_prefix(FocusKind::Button, "KEY_", |n, f| {
+            n.press_keyboard_key(f.payload().trim_start_matches("KEY_"))
+        });
+
+    // Quick settings.
+    d.on_button("BRIGHTNESS_DOWN", |n, _| {
+        n.step_brightness(-quick_settings::STEP)
+    })
+    .on_button("BRIGHTNESS_UP", |n, _| {
+        n.step_brightness(quick_settings::STEP)
+    })
+    .on_button("VOLUME_DOWN", |n, _| n.step_volume(-quick_settings::STEP))
+    .on_button("VOLUME_UP", |n, _| n.step_volume(quick_settings::STEP))
+    .on_button("WIFI", |n, _| n.toggle_wifi())
+    .on_button("BLUETOOTH", |n, _| n.toggle_bluetooth());
+    d
+}
+
+impl Navigator {
+    fn steam_app_id(&self, uuid: &str) -> Option<steam::AppId> {
+        self.library
+            .lock()
+            .unwrap()
+            .get(uuid)
+            .and_then(|g| g.install_source.as_deref())
+            .and_then(steam::app_id_from_install_source)
+    }
+
+    /// Queue an install, update or uninstall of a steam game.
+    fn queue_steam_transfer(&self, uuid: &str, kind: TransferKind) {
+        let app_id = match self.steam_app_id(uuid) {
+            Some(app_id) => app_id,
+            // Not a steam game, nothing to do.
+            None => return,
+        };
+        if self.transfers.is_pending(uuid, kind) {
+            return;
+        }
+
+        let installed = self.steam.is_installed(app_id);
+        match kind {
+            TransferKind::Install if !installed => self.steam.install(uuid, app_id),
+            TransferKind::Update if installed => self.steam.update(uuid, app_id),
+            TransferKind::Uninstall if installed => self.steam.uninstall(uuid, app_id),
+            _ => return,
+        };
+    }
+
+    /// Add the pause/cancel buttons of new transfers to the downloads screen.
+    fn sync_downloads_layout(&mut self) -> anyhow::Result<()> {
+        for t in self.transfers.list() {
+            if self.listed_downloads.insert(t.id) {
+                self.router
+                    .controller_for(Screen::Downloads)?
+                    .with_sublayout(
+                        &LayoutId::sublayout("Downloads", "List"),
+                        |l| -> anyhow::Result<()> {
+                            let id = t.id.to_string();
+                            l.insert_to_growable_grid(FocusId::new(FocusKind::DownloadPause, &id))?;
+                            l.insert_to_growable_grid(FocusId::new(FocusKind::DownloadCancel, id))
+                        },
+                    )??;
+            }
+        }
+        Ok(())
+    }
+
+    /// Without the transfers which were over, for it and the transfers not
+    /// to pile up. Those which finish while it's shown stay until it's
+    /// opened again.
+    fn open_downloads(&mut self) {
+        self.router.push(Screen::Downloads);
+        let cleared = self.transfers.clear_finished();
+        if cleared.is_empty() {
+            return;
+        }
+        if let Err(e) = self.layout_downloads() {
+            warn!("failed to lay the downloads out: {:?}", e);
+        }
+        let cleared: Vec<String> = cleared.iter().map(|id| id.to_string()).collect();
+        self.ui
+            .update(move |e| {
+                let downloads = e.global::<HomeWindowFocus>().get_downloads();
+                if let Some(model) = downloads
+                    .as_any()
+                    .downcast_ref::<slint::VecModel<DownloadData>>()
+                {
+                    let rows: Vec<DownloadData> = model
+                        .iter()
+                        .filter(|d| !cleared.iter().any(|id| id == d.id.as_str()))
+                        .collect();
+                    model.set_vec(rows);
+                }
+            })
+            .unwrap();
+    }
+
+    /// Lay the downloads screen out from scratch, with a row per transfer.
+    fn layout_downloads(&mut self) -> anyhow::Result<()> {
+        self.router.rebuild(Screen::Downloads)?;
+        self.listed_downloads.clear();
+        self.sync_downloads_layout()
+    }
+
+    /// Quit, which ends the session in kiosk mode. Behind the PIN if there's
+    /// one.
+    fn exit_to_desktop(&mut self) {
+        if self.kiosk.enabled && self.kiosk.has_pin() {
+            self.pin_entry.clear();
+            self.router.push(Screen::Pin);
+            self.show_pin(String::new());
+        } else {
+            self.confirm(
+                "exit-to-desktop",
+                "confirm-exit",
+                None,
+                Confirmed::ExitToDesktop,
+            );
+        }
+    }
+
+    fn quit(&self) {
+        info!("exiting to the desktop");
+        self.ui
+            .update(|_| {
+                let _ = slint::quit_event_loop();
+            })
+            .unwrap();
+    }
+
+    fn press_pin_key(&mut self, key: &str) {
+        match key {
+            "DEL" => {
+                self.pin_entry.pop();
+            }
+            "OK" => {
+                let now = Instant::now();
+                if self.pin_lock.check(&self.kiosk, &self.pin_entry, now) {
+                    return self.quit();
+                }
+                warn!("refused the PIN to exit to the desktop");
+                self.pin_entry.clear();
+                let status = match self.pin_lock.locked(now) {
+                    Some(left) => {
+                        let seconds = left.as_secs_f32().ceil().to_string();
+                        let args = fluent::FluentArgs::from_iter([("seconds", seconds)]);
+                        self.i18n.tr_args("pin-locked", Some(&args))
+                    }
+                    None => self.i18n.tr("wrong-pin"),
+                };
+                return self.show_pin(status);
+            }
+            digit if self.pin_entry.len() < MAX_PIN_DIGITS => self.pin_entry.push_str(digit),
+            _ => {}
+        }
+        self.show_pin(String::new());
+    }
+
+    fn show_pin(&self, status: String) {
+        let entered = "●".repeat(self.pin_entry.len());
+        self.ui
+            .update(move |e| {
+                let state = e.global::<PinState>();
+                state.set_entered(entered.into());
+                state.set_status(status.into());
+            })
+            .unwrap();
+    }
+
+    fn edit_notes(&mut self) -> anyhow::Result<()> {
+        let Some(uuid) = self.details.as_ref().map(|d| d.uuid.clone()) else {
+            return Ok(());
+        };
+        let game = self.library.lock().unwrap().get(&uuid).cloned();
+        let Some(game) = game else {
+            anyhow::bail!("no game {} in the library", uuid);
+        };
+        let args = fluent::FluentArgs::from_iter([("title", game.title)]);
+        let title = self.i18n.tr_args("notes-for", Some(&args));
+        let notes = game.notes.unwrap_or_default();
+        self.open_keyboard(Typing::Notes(uuid), &notes, title)
+    }
+
+    /// A tag or collection for the selected games, `title` given how many.
+    fn type_for_selection(&mut self, typing: Typing, title: &str) -> anyhow::Result<()> {
+        let count = self.selected.lock().unwrap().len();
+        if count == 0 {
+            return Ok(());
+        }
+        let args = fluent::FluentArgs::from_iter([("title", self.game_count(count))]);
+        let title = self.i18n.tr_args(title, Some(&args));
+        self.open_keyboard(typing, "", title)
+    }
+
+    fn open_keyboard(&mut self, typing: Typing, text: &str, title: String) -> anyhow::Result<()> {
+        self.keyboard = Some(Keyboard::new(typing, text));
+        self.router.rebuild(Screen::Keyboard)?;
+        self.router.push(Screen::Keyboard);
+        self.ui
+            .update(move |e| e.global::<KeyboardState>().set_title(title.into()))
+            .unwrap();
+        self.show_keyboard();
+        Ok(())
+    }
+
+    fn press_keyboard_key(&mut self, key: &str) -> anyhow::Result<()> {
+        let Some(keyboard) = self.keyboard.as_mut() else {
+            return Ok(());
+        };
+        let Some(text) = keyboard.press(key) else {
+            self.show_keyboard();
+            return Ok(());
+        };
+        let typing = keyboard.typing.clone();
+        self.keyboard = None;
+        self.router.pop();
+        match typing {
+            Typing::Notes(uuid) => {
+                {
+                    let mut library = self.library.lock().unwrap();
+                    let Some(game) = library.get_mut(&uuid) else {
+                        anyhow::bail!("no game {} in the library", uuid);
+                    };
+                    game.notes = Some(text.trim_end().to_owned()).filter(|n| !n.is_empty());
+                    library.save()?;
+                }
+                self.game_changed(&uuid);
+            }
+            Typing::Tag | Typing::Collection => {
+                let text = text.trim();
+                if text.is_empty() {
+                    return Ok(());
+                }
+                let action = match typing {
+                    Typing::Tag => BulkAction::AddTag(text.to_owned()),
+                    _ => BulkAction::MoveTo(text.to_owned()),
+                };
+                self.run_bulk(action, self.selected_games());
+            }
+            Typing::MqttBroker => {
+                self.change_mqtt(|c| c.broker = text.trim().to_owned())?;
+            }
+            Typing::MqttUsername | Typing::MqttPassword => {
+                let text = Some(text.trim_end().to_owned()).filter(|t| !t.is_empty());
+                self.change_mqtt(|c| match typing {
+                    Typing::MqttUsername => c.username = text,
+                    _ => c.password = text,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn show_keyboard(&self) {
+        let Some(keyboard) = &self.keyboard else {
+            return;
+        };
+        let text = keyboard.text().to_owned();
+        let shift = keyboard.shift();
+        self.ui
+            .update(move |e| {
+                let state = e.global::<KeyboardState>();
+                state.set_text(text.into());
+                state.set_shift(shift);
+            })
+            .unwrap();
+    }
+
+    /// Install the game if it's missing, launch it otherwise.
+    fn play(&self, uuid: &str) -> anyhow::Result<()> {
+        if self.still_importing(uuid) {
+            return Ok(());
+        }
+        // Also when asked from elsewhere, e.g. the remote or a link.
+        if !self.library.lock().unwrap().is_visible(uuid) {
+            anyhow::bail!("{} isn't in the library or not for this profile", uuid);
+        }
+        if let Some(app_id) = self.steam_app_id(uuid) {
+            if !self.steam.is_installed(app_id) {
+                self.queue_steam_transfer(uuid, TransferKind::Install);
+                return Ok(());
+            }
+        }
+        if self.launcher.is_suspended(uuid) {
+            return self.launcher.resume(uuid);
+        }
+        let game = self.library.lock().unwrap().get(uuid).cloned();
+        let Some(game) = game else {
+            anyhow::bail!("no game {} in the library", uuid);
+        };
+        let unplugged = game
+            .root
+            .as_ref()
+            .is_some_and(|r| self.unavailable_roots.lock().unwrap().contains(r));
+        if unplugged {
+            let args = fluent::FluentArgs::from_iter([("title", game.title)]);
+            self.show_toast(self.i18n.tr_args("game-unavailable", Some(&args)));
+            return Ok(());
+        }
+        self.launcher.launch(game)
+    }
+
+    /// Handle the A button on the focused item.
+    fn activate(&mut self, focus_id: &FocusId) -> anyhow::Result<()> {
+        let handlers = self.handlers.clone();
+        handlers.dispatch(self, focus_id)?;
+        Ok(())
+    }
+
+    fn open_details(&mut self, uuid: &str) -> anyhow::Result<()> {
+        if self.still_importing(uuid) {
+            return Ok(());
+        }
+        let game = {
+            let library = self.library.lock().unwrap();
+            library
+                .get(uuid)
+                .filter(|_| library.is_visible(uuid))
+                .cloned()
+        };
+        let Some(game) = game else {
+            anyhow::bail!("no game {} in the library for this profile", uuid);
+        };
+        let screenshots = screenshots_of(uuid);
+        let paths = screenshots.clone();
+        let sessions: Vec<SessionData> = self
+            .sessions
+            .of(uuid)
+            .into_iter()
+            .map(|s| SessionData {
+                when: format!(
+                    "{} {}",
+                    self.i18n.format_date(&s.start),
+                    s.start.with_timezone(&chrono::Local).format("%H:%M")
+                )
+                .into(),
+                length: self.i18n.format_duration(&s.duration()).into(),
+            })
+            .collect();
+        self.details = Some(GameDetails {
+            uuid: uuid.to_owned(),
+            tab: DetailsTab::Overview,
+            achievements: 0,
+            screenshots,
+            news: 0,
+            sessions: sessions.len(),
+        });
+        self.layout_details(&FocusId::button("BACK"))?;
+        self.router.push(Screen::GameDetails);
+
+        let status = match (game.retroachievements_id, &self.retroachievements) {
+            (Some(game_id), Some(ra)) => {
+                let ra = ra.clone();
+                let events = self.events.clone();
+                let uuid = uuid.to_owned();
+                // Those of the game shown before aren't wanted any more.
+                let job = runtime::spawn_job(
+                    CHECK_TIMEOUT,
+                    move || ra.progress(game_id),
+                    move |progress| {
+                        let progress = progress.map_err(|e| e.to_string());
+                        let _ = events.send(NavigationEvent::Achievements { uuid, progress });
+                    },
+                );
+                if let Some(previous) = self.achievements_job.replace(job) {
+                    previous.cancel();
+                }
+                self.i18n.tr("achievements-loading")
+            }
+            (Some(_), None) => self.i18n.tr("achievements-not-set-up"),
+            (None, _) => self.i18n.tr("no-achievements"),
+        };
+        let news_status = if self.feeds.follows(&game) {
+            let feeds = self.feeds.clone();
+            let events = self.events.clone();
+            let (uuid, game) = (uuid.to_owned(), game.clone());
+            let job = runtime::spawn_job(
+                CHECK_TIMEOUT,
+                move || feeds.news(&game),
+                move |news| {
+                    let news = news.map_err(|e| e.to_string());
+                    let _ = events.send(NavigationEvent::News { uuid, news });
+                },
+            );
+            if let Some(previous) = self.news_job.replace(job) {
+                previous.cancel();
+            }
+            self.i18n.tr("news-loading")
+        } else {
+            self.i18n.tr("no-news")
+        };
+        self.show_game_facts(&game);
+        let (title, desc) = self.game_text(&game);
+        // Blurred when the background was downloaded, not as it's shown.
+        let backdrop = game
+            .bg_art
+            .as_ref()
+            .zip(self.art.as_ref())
+            .and_then(|(background, art)| art.backdrop(uuid, background));
+        let uuid = uuid.to_owned();
+        self.ui
+            .update(move |e| {
+                let state = e.global::<GameDetailsState>();
+                state.set_uuid(uuid.into());
+                let backdrop = backdrop.and_then(|p| slint::Image::load_from_path(&p).ok());
+                state.set_backdrop(backdrop.unwrap_or_default());
+                state.set_title(title.into());
+                state.set_description(desc.into());
+                state.set_notes(game.notes.clone().unwrap_or_default().into());
+                state.set_overlay(game.overlay);
+                state.set_tab(DetailsTab::Overview.name().into());
+                state.set_achievements(Default::default());
+                state.set_achievements_status(status.into());
+                state.set_news(Default::default());
+                state.set_news_status(news_status.into());
+                state.set_sessions(std::rc::Rc::new(slint::VecModel::from(sessions)).into());
+                let images: Vec<slint::Image> = paths
+                    .iter()
+                    .filter_map(|p| slint::Image::load_from_path(p).ok())
+                    .collect();
+                state.set_screenshots(std::rc::Rc::new(slint::VecModel::from(images)).into());
+                state.set_focused_row(0);
+            })
+            .unwrap();
+        Ok(())
+    }
+
+    /// Turn the performance overlay of the game shown on or off.
+    fn toggle_overlay(&mut self) -> anyhow::Result<()> {
+        let Some(uuid) = self.details.as_ref().map(|d| d.uuid.clone()) else {
+            return Ok(());
+        };
+        let overlay = {
+            let mut library = self.library.lock().unwrap();
+            let game = library
+                .get_mut(&uuid)
+                .ok_or(anyhow::anyhow!("no game {} in the library", uuid))?;
+            game.overlay = !game.overlay;
+            let overlay = game.overlay;
+            library.save()?;
+            overlay
+        };
+        self.ui
+            .update(move |e| e.global::<GameDetailsState>().set_overlay(overlay))?;
+        Ok(())
+    }
+
+    /// The release date, playtime and rating on the details screen, in the
+    /// locale.
+    fn show_game_facts(&self, game: &models::GameMetadata) {
+        let t = &self.i18n;
+        let fact = |id, arg, value: Option<String>| {
+            value
+                .map(|v| t.tr_args(id, Some(&fluent::FluentArgs::from_iter([(arg, v)]))))
+                .unwrap_or_default()
+        };
+        let released = fact(
+            "released",
+            "date",
+            game.relase_date.map(|d| t.format_date(&d)),
+        );
+        let played = fact(
+            "played",
+            "duration",
+            game.playtime.map(|d| t.format_duration(&d)),
+        );
+        let rating = game.rating_badge(t.locale()).unwrap_or_default();
+        let mature = game.min_age().is_some_and(|age| age >= models::MATURE_AGE);
+        self.ui
+            .update(move |e| {
+                let state = e.global::<GameDetailsState>();
+                state.set_release_date(released.into());
+                state.set_playtime(played.into());
+                state.set_rating(rating.into());
+                state.set_mature(mature);
+            })
+            .unwrap();
+    }
+
+    /// The title and description of a game in the locale, see
+    /// `GameMetadata::title_in`.
+    fn game_text(&self, game: &models::GameMetadata) -> (String, String) {
+        let locale = self.i18n.locale();
+        let desc = game.desc_in(locale).unwrap_or_default();
+        (game.title_in(locale).to_owned(), desc.to_owned())
+    }
+
+    /// Switch to the next language and keep it for next time.
+    fn next_locale(&mut self) -> anyhow::Result<()> {
+        self.i18n = Arc::new(self.i18n.next());
+        info!("switching to {}", self.i18n.locale());
+        *self.tile_locale.lock().unwrap() = self.i18n.locale().to_owned();
+        let t = self.i18n.clone();
+        self.ui.update(move |e| {
+            show_strings(e, &t);
+            tiles::apply(e, LibraryChange::Reset);
+        })?;
+        self.show_settings_headers();
+        self.show_selection();
+        let uuid = self.details.as_ref().map(|d| d.uuid.clone());
+        if let Some(game) = uuid.and_then(|u| self.library.lock().unwrap().get(&u).cloned()) {
+            self.show_game_facts(&game);
+            self.game_changed(&game.uuid);
+        }
+        let config = LocaleConfig {
+            locale: Some(self.i18n.locale().to_owned()),
+        };
+        config.save(&paths::config_dir().join("locale.yaml"))
+    }
+
+    /// The profile being edited, or the first one.
+    fn show_profile(&mut self) {
+        let (name, profile) = {
+            let config = self.profiles.lock().unwrap();
+            let shown = self
+                .profile
+                .as_ref()
+                .and_then(|n| config.profiles.get_key_value(n))
+                .or_else(|| config.profiles.iter().next());
+            match shown {
+                Some((name, profile)) => (name.clone(), profile.clone()),
+                None => (String::new(), Profile::default()),
+            }
+        };
+        self.profile = Some(name.clone()).filter(|n| !n.is_empty());
+        let t = &self.i18n;
+        let toggle = |v: Option<bool>| match v {
+            Some(true) => t.tr("on"),
+            Some(false) => t.tr("off"),
+            None => t.tr("unchanged"),
+        };
+        let governor = profile.governor.unwrap_or_else(|| t.tr("unchanged"));
+        let (hdr, vrr) = (toggle(profile.hdr), toggle(profile.vrr));
+        let bypass = toggle(profile.compositor_bypass);
+        self.ui
+            .update(move |e| {
+                let state = e.global::<ProfilesState>();
+                state.set_name(name.into());
+                state.set_governor(governor.into());
+                state.set_hdr(hdr.into());
+                state.set_vrr(vrr.into());
+                state.set_compositor_bypass(bypass.into());
+            })
+            .unwrap();
+    }
+
+    /// Go to the profile before or after the one shown.
+    fn step_profile(&mut self, step: isize) {
+        let next = {
+            let config = self.profiles.lock().unwrap();
+            let names: Vec<&String> = config.profiles.keys().collect();
+            let current = self.profile.as_ref();
+            let i = names.iter().position(|n| Some(*n) == current).unwrap_or(0) as isize;
+            let len = names.len() as isize;
+            (len > 0).then(|| names[(i + step).rem_euclid(len) as usize].clone())
+        };
+        self.profile = next;
+        self.show_profile();
+    }
+
+    fn edit_profile(&mut self, f: impl FnOnce(&mut Profile)) {
+        {
+            let mut config = self.profiles.lock().unwrap();
+            let Some(profile) = self
+                .profile
+                .as_ref()
+                .and_then(|n| config.profiles.get_mut(n))
+            else {
+                return;
+            };
+            f(profile);
+        }
+        self.save_profiles();
+    }
+
+    fn save_profiles(&mut self) {
+        let path = paths::config_dir().join("profiles.yaml");
+        let status = match self.profiles.lock().unwrap().save(&path) {
+            Ok(()) => String::new(),
+            Err(e) => {
+                warn!("failed to save the profiles: {:?}", e);
+                format!("{:#}", e)
+            }
+        };
+        self.ui
+            .update(move |e| e.global::<ProfilesState>().set_status(status.into()))
+            .unwrap();
+        self.show_profile();
+    }
+
+    /// The backups of the game shown, and `status` under them.
+    fn show_saves(&mut self, status: String) {
+        let Some(uuid) = self.details.as_ref().map(|d| d.uuid.clone()) else {
+            return;
+        };
+        let Some(game) = self.library.lock().unwrap().get(&uuid).cloned() else {
+            return;
+        };
+        let location = match self.saves.save_dir(&game) {
+            Some(Ok(dir)) => dir.display().to_string(),
+            Some(Err(e)) => format!("{:#}", e),
+            None => String::new(),
+        };
+        self.save_snapshots = self.saves.snapshots(&uuid);
+        self.save_index = self
+            .save_index
+            .min(self.save_snapshots.len().saturating_sub(1));
+        let t = &self.i18n;
+        let (snapshot, size) = match self.save_snapshots.get(self.save_index) {
+            Some(s) => {
+                let time = s.time.with_timezone(&chrono::Local).format("%H:%M");
+                let size = t.tr_args(
+                    "save-size",
+                    Some(&fluent::FluentArgs::from_iter([(
+                        "size",
+                        s.size().div_ceil(1000),
+                    )])),
+                );
+                (format!("{} {}", t.format_date(&s.time), time), size)
+            }
+            None => Default::default(),
+        };
+        let position = format!("{} / {}", self.save_index + 1, self.save_snapshots.len());
+        self.ui
+            .update(move |e| {
+                let state = e.global::<SavesState>();
+                state.set_title(game.title.into());
+                state.set_location(location.into());
+                state.set_snapshot(snapshot.into());
+                state.set_size(size.into());
+                state.set_position(position.into());
+                state.set_status(status.into());
+            })
+            .unwrap();
+    }
+
+    /// Go to an older or newer backup, without going around.
+    fn step_save(&mut self, step: isize) {
+        let last = self.save_snapshots.len().saturating_sub(1) as isize;
+        self.save_index = (self.save_index as isize + step).clamp(0, last) as usize;
+        self.show_saves(String::new());
+    }
+
+    fn backup_saves(&self) {
+        let Some(uuid) = self.details.as_ref().map(|d| d.uuid.clone()) else {
+            return;
+        };
+        let Some(game) = self.library.lock().unwrap().get(&uuid).cloned() else {
+            return;
+        };
+        let (saves, events) = (self.saves.clone(), self.events.clone());
+        let id = uuid.clone();
+        self.transfers.enqueue(
+            TransferKind::Backup,
+            &id,
+            Box::new(move |_| {
+                let snapshot = saves.snapshot(&game);
+                let status = match &snapshot {
+                    Ok(Some(_)) => Ok("saves-backed-up"),
+                    Ok(None) => Ok("saves-unchanged"),
+                    Err(e) => Err(format!("{:#}", e)),
+                };
+                let _ = events.send(NavigationEvent::Saves {
+                    uuid: uuid.clone(),
+                    status,
+                });
+                snapshot.map(|_| ())
+            }),
+        );
+    }
+
+    /// Put back the saves of the backup shown.
+    fn restore_save(&mut self) {
+        let Some(uuid) = self.details.as_ref().map(|d| d.uuid.clone()) else {
+            return;
+        };
+        let Some(snapshot) = self.save_snapshots.get(self.save_index).cloned() else {
+            return;
+        };
+        if self.saves.is_running(&uuid) {
+            return self.show_saves(self.i18n.tr("saves-game-running"));
+        }
+        let Some(game) = self.library.lock().unwrap().get(&uuid).cloned() else {
+            return;
+        };
+        let (saves, events) = (self.saves.clone(), self.events.clone());
+        let id = uuid.clone();
+        self.transfers.enqueue(
+            TransferKind::Backup,
+            &id,
+            Box::new(move |_| {
+                let restored = saves.restore(&game, &snapshot);
+                let status = match &restored {
+                    Ok(()) => Ok("saves-restored"),
+                    Err(e) => Err(format!("{:#}", e)),
+                };
+                let _ = events.send(NavigationEvent::Saves {
+                    uuid: uuid.clone(),
+                    status,
+                });
+                restored
+            }),
+        );
+    }
+
+    fn show_saves_result(&mut self, uuid: String, status: Result<&'static str, String>) {
+        if let Err(e) = &status {
+            warn!("failed to back up or restore the saves of {}: {}", uuid, e);
+        }
+        if self.details.as_ref().is_some_and(|d| d.uuid == uuid) {
+            // The newest is the one just taken.
+            self.save_index = 0;
+            self.show_saves(status.map_or_else(|e| e, |id| self.i18n.tr(id)));
+        }
+    }
+
+    /// Go through the installed games again, unless it's already being done.
+    fn scan_storage(&mut self) {
+        if !self.storage_scanning() {
+            let games: Vec<models::GameMetadata> =
+                self.library.lock().unwrap().iter().cloned().collect();
+            let events = self.events.clone();
+            let steamapps_dirs = self.steam.steamapps_dirs().to_vec();
+            let id = self.transfers.enqueue_task(
+                TransferKind::Scan,
+                "Disk usage",
+                Box::new(move |ctx| {
+                    let usage = storage::scan(ctx, &games, &steamapps_dirs);
+                    let result = match &usage {
+                        Ok(usage) => Ok(usage.clone()),
+                        Err(e) => Err(format!("{:#}", e)),
+                    };
+                    let _ = events.send(NavigationEvent::Storage(result));
+                    usage.map(|_| ())
+                }),
+            );
+            self.storage_scan = Some(id);
+        }
+        self.show_storage(None);
+    }
+
+    /// Queued, going or paused from the tasks screen.
+    fn storage_scanning(&self) -> bool {
+        self.storage_scan
+            .and_then(|id| self.transfers.get(id))
+            .is_some_and(|t| !t.state.is_terminal())
+    }
+
+    fn show_storage_result(&mut self, usage: Result<Usage, String>) {
+        match usage {
+            Ok(usage) => {
+                if let Err(e) = usage.save(&Usage::default_path()) {
+                    warn!("failed to cache the disk usage: {:?}", e);
+                }
+                self.storage = Some(usage);
+                self.show_storage(None);
+            }
+            Err(e) => {
+                warn!("failed to scan the disk usage: {}", e);
+                self.show_storage(Some(e));
+            }
+        }
+    }
+
+    fn show_art_cache_usage(&self) {
+        let usage = match self.art.as_ref().map(|a| a.usage()) {
+            Some(Ok(bytes)) => storage::format_bytes(bytes),
+            Some(Err(e)) => {
+                warn!("failed to read the art cache usage: {:?}", e);
+                return;
+            }
+            None => String::new(),
+        };
+        self.ui
+            .update(move |e| e.global::<SettingsState>().set_art_cache(usage.into()))
+            .unwrap();
+    }
+
+    /// The covers are downloaded again as they're shown.
+    fn clear_art_cache(&self) -> anyhow::Result<()> {
+        let Some(art) = &self.art else {
+            return Ok(());
+        };
+        let freed = art.clear()?;
+        let args = fluent::FluentArgs::from_iter([("size", storage::format_bytes(freed))]);
+        self.show_settings_status(self.i18n.tr_args("art-cache-cleared", Some(&args)));
+        self.show_art_cache_usage();
+        Ok(())
+    }
+
+    /// Games that can be uninstalled from here, for now those from Steam.
+    fn suggestions(&self) -> Vec<&storage::GameUsage> {
+        self.storage
+            .as_ref()
+            .map(|u| u.suggestions(|uuid| self.steam_app_id(uuid).is_some()))
+            .unwrap_or_default()
+    }
+
+    /// The drives, the largest games and a suggestion, or `error` as status.
+    fn show_storage(&mut self, error: Option<String>) {
+        let t = &self.i18n;
+        let status = match (error, &self.storage) {
+            (Some(e), _) => e,
+            _ if self.storage_scanning() => t.tr("storage-scanning"),
+            (None, Some(usage)) => t.tr_args(
+                "storage-scanned",
+                Some(&fluent::FluentArgs::from_iter([(
+                    "date",
+                    t.format_date(&usage.time),
+                )])),
+            ),
+            (None, None) => String::new(),
+        };
+        let (drives, games) = match &self.storage {
+            Some(usage) => {
+                let drives: Vec<DriveData> = usage
+                    .drives
+                    .iter()
+                    .map(|d| {
+                        let args = [
+                            ("games", storage::format_bytes(d.games)),
+                            ("free", storage::format_bytes(d.available)),
+                            ("size", storage::format_bytes(d.size)),
+                        ];
+                        DriveData {
+                            name: d.mount.display().to_string().into(),
+                            used: 1.0 - d.available as f32 / d.size.max(1) as f32,
+                            details: t
+                                .tr_args(
+                                    "drive-details",
+                                    Some(&fluent::FluentArgs::from_iter(args)),
+                                )
+                                .into(),
+                        }
+                    })
+                    .collect();
+                let games: Vec<GameSizeData> = usage
+                    .games
+                    .iter()
+                    .take(10)
+                    .map(|g| GameSizeData {
+                        title: g.title.clone().into(),
+                        size: storage::format_bytes(g.bytes).into(),
+                    })
+                    .collect();
+                (drives, games)
+            }
+            None => Default::default(),
+        };
+        let suggestions = self.suggestions();
+        let suggestion = self.suggestion.min(suggestions.len().saturating_sub(1));
+        let shown = suggestions
+            .get(suggestion)
+            .map(|g| format!("{}, {}", g.title, storage::format_bytes(g.bytes)))
+            .unwrap_or_default();
+        let position = format!("{} / {}", suggestion + 1, suggestions.len());
+        self.suggestion = suggestion;
+        self.ui
+            .update(move |e| {
+                let state = e.global::<StorageState>();
+                state.set_status(status.into());
+                state.set_drives(std::rc::Rc::new(slint::VecModel::from(drives)).into());
+                state.set_games(std::rc::Rc::new(slint::VecModel::from(games)).into());
+                state.set_suggestion(shown.into());
+                state.set_position(position.into());
+            })
+            .unwrap();
+    }
+
+    /// Go to a larger or smaller suggestion, without going around.
+    fn step_suggestion(&mut self, step: isize) {
+        let last = self.suggestions().len().saturating_sub(1) as isize;
+        self.suggestion = (self.suggestion as isize + step).clamp(0, last) as usize;
+        self.show_storage(None);
+    }
+
+    fn uninstall_suggested(&mut self) {
+        let uuid = self
+            .suggestions()
+            .get(self.suggestion)
+            .map(|g| g.uuid.clone());
+        if let Some(uuid) = uuid {
+            self.confirm_uninstall(uuid);
+        }
+    }
+
+    fn confirm_uninstall(&mut self, uuid: String) {
+        let Some(title) = self
+            .library
+            .lock()
+            .unwrap()
+            .get(&uuid)
+            .map(|g| g.title.clone())
+        else {
+            return;
+        };
+        self.confirm(
+            "uninstall",
+            "confirm-uninstall",
+            Some(title),
+            Confirmed::Uninstall(uuid),
+        );
+    }
+
+    fn uninstall(&mut self, uuid: &str) {
+        self.queue_steam_transfer(uuid, TransferKind::Uninstall);
+        // Gone from the list, the next scan has the space it frees.
+        let Some(usage) = &mut self.storage else {
+            return;
+        };
+        let Some(i) = usage.games.iter().position(|g| g.uuid == uuid) else {
+            return;
+        };
+        let title = usage.games.remove(i).title;
+        let status = self.i18n.tr_args(
+            "uninstalling",
+            Some(&fluent::FluentArgs::from_iter([("title", title)])),
+        );
+        self.show_storage(Some(status));
+    }
+
+    /// Ask before doing `then`, the messages are ids of translations, the
+    /// body given the title of the game if any.
+    fn confirm(&mut self, title: &str, body: &str, game: Option<String>, then: Confirmed) {
+        let args = game.map(|title| fluent::FluentArgs::from_iter([("title", title)]));
+        let body = self.i18n.tr_args(body, args.as_ref());
+        self.dialogs.confirm(self.i18n.tr(title), body, then);
+        self.show_dialog();
+    }
+
+    /// The oldest question unanswered, if the dialog isn't up already.
+    fn show_dialog(&mut self) {
+        let Some(asked) = self.dialogs.current() else {
+            return;
+        };
+        if self.router.current_screen() == Screen::Confirm {
+            return;
+        }
+        let (title, body) = (asked.title.clone(), asked.body.clone());
+        if let Err(e) = self.router.rebuild(Screen::Confirm) {
+            warn!("failed to lay out the dialog: {:?}", e);
+        }
+        self.router.push(Screen::Confirm);
+        self.ui
+            .update(move |e| {
+                let state = e.global::<ConfirmState>();
+                state.set_title(title.into());
+                state.set_body(body.into());
+            })
+            .unwrap();
+    }
+
+    /// Whether the app can pair, with the code it shows to check it's the
+    /// one asking.
+    fn confirm_pairing(&mut self, request: PairRequest) {
+        let args = fluent::FluentArgs::from_iter([("name", request.name), ("code", request.code)]);
+        let body = self.i18n.tr_args("confirm-pair-remote", Some(&args));
+        let title = self.i18n.tr("pair-remote");
+        let mut answered = self.dialogs.ask(title, body);
+        let (pairing, events) = (self.pairing.clone(), self.events.clone());
+        // The app waits for no as well.
+        runtime::spawn(async move {
+            let accepted = loop {
+                tokio::select! {
+                    accepted = &mut answered => break accepted.unwrap_or(false),
+                    _ = tokio::time::sleep(PAIR_CHECK) => {
+                        if !pairing.is_waiting(&request.id) {
+                            info!("the app asking to pair stopped waiting");
+                            drop(answered);
+                            let _ = events.send(NavigationEvent::Unawaited);
+                            return;
+                        }
+                    }
+                }
+            };
+            if !pairing.answer(&request.id, accepted) {
+                warn!("the app asking to pair stopped waiting");
+            }
+        });
+        self.show_dialog();
+    }
+
+    /// Take down the questions nobody awaits the answer of, see `ask`.
+    fn drop_unawaited_dialogs(&mut self) {
+        if self.dialogs.drop_unawaited() && self.router.current_screen() == Screen::Confirm {
+            self.router.pop();
+        }
+        self.show_dialog();
+    }
+
+    fn answer_confirm(&mut self, confirmed: bool) {
+        if self.router.current_screen() == Screen::Confirm {
+            self.router.pop();
+        }
+        match self.dialogs.answer(confirmed) {
+            Some(Confirmed::Uninstall(uuid)) => self.uninstall(&uuid),
+            Some(Confirmed::ExitToDesktop) => self.quit(),
+            Some(Confirmed::RestoreReplace) => self.restore_library(RestoreMode::Replace),
+            Some(Confirmed::DeleteGames(uuids)) => self.run_bulk(BulkAction::Delete, uuids),
+            Some(Confirmed::EmptyTrash) => self.empty_trash(),
+            None => {}
+        }
+        self.show_dialog();
+    }
+
+    /// The suspended game shown: its uuid, title and since when.
+    fn suspended_game(&self) -> Option<(String, String, chrono::DateTime<chrono::Utc>)> {
+        let game = self
+            .launcher
+            .suspended()
+            .into_iter()
+            .nth(self.suspended_index)?;
+        let title = self.library.lock().unwrap().get(&game.uuid)?.title.clone();
+        Some((game.uuid, title, game.since))
+    }
+
+    fn show_suspended(&mut self, status: Option<String>) {
+        let count = self.launcher.suspended().len();
+        self.suspended_index = self.suspended_index.min(count.saturating_sub(1));
+        let (title, since) = match self.suspended_game() {
+            Some((_, title, since)) => {
+                let duration = self.i18n.format_duration(&(chrono::Utc::now() - since));
+                let args = fluent::FluentArgs::from_iter([("duration", duration)]);
+                (title, self.i18n.tr_args("suspended-for", Some(&args)))
+            }
+            None => Default::default(),
+        };
+        let position = match count {
+            0 => String::new(),
+            _ => format!("{} / {}", self.suspended_index + 1, count),
+        };
+        let status = status.unwrap_or_default();
+        self.ui
+            .update(move |e| {
+                let state = e.global::<SuspendedState>();
+                state.set_title(title.into());
+                state.set_since(since.into());
+                state.set_position(position.into());
+                state.set_status(status.into());
+            })
+            .unwrap();
+    }
+
+    fn step_suspended(&mut self, step: isize) {
+        let last = self.launcher.suspended().len().saturating_sub(1) as isize;
+        self.suspended_index = (self.suspended_index as isize + step).clamp(0, last) as usize;
+        self.show_suspended(None);
+    }
+
+    fn resume_suspended(&mut self) {
+        let Some((uuid, _, _)) = self.suspended_game() else {
+            return;
+        };
+        match self.launcher.resume(&uuid) {
+            Ok(()) => while self.router.pop() {},
+            Err(e) => self.show_suspended(Some(format!("{:#}", e))),
+        }
+    }
+
+    fn quit_suspended(&mut self) {
+        let Some((uuid, _, _)) = self.suspended_game() else {
+            return;
+        };
+        let status = self.launcher.quit_suspended(&uuid).err();
+        self.show_suspended(status.map(|e| format!("{:#}", e)));
+    }
+
+    /// The clock settings as timedated has them.
+    fn show_clock(&self) {
+        let settings = timedate::status();
+        self.ui
+            .update(move |e| {
+                let state = e.global::<ClockState>();
+                match settings {
+                    Ok(s) => {
+                        state.set_timezone(s.timezone.into());
+                        state.set_ntp(s.ntp);
+                        state.set_ntp_synced(s.ntp_synced);
+                    }
+                    Err(e) => state.set_status(format!("{:#}", e).into()),
+                }
+            })
+            .unwrap();
+    }
+
+    /// Show how a change went and the settings after it.
+    fn show_clock_result(&self, result: anyhow::Result<()>) {
+        let status = match result {
+            Ok(()) => String::new(),
+            Err(e) => {
+                warn!("failed to change the clock settings: {:?}", e);
+                format!("{:#}", e)
+            }
+        };
+        self.ui
+            .update(move |e| e.global::<ClockState>().set_status(status.into()))
+            .unwrap();
+        self.show_clock();
+    }
+
+    /// Go to the time zone before or after the current one.
+    fn step_timezone(&mut self, step: isize) {
+        if self.timezones.is_empty() {
+            match timedate::timezones() {
+                Ok(zones) => self.timezones = zones,
+                Err(e) => return self.show_clock_result(Err(e)),
+            }
+        }
+        let result = timedate::status().and_then(|s| {
+            match timedate::step_timezone(&self.timezones, &s.timezone, step) {
+                Some(zone) => timedate::set_timezone(zone),
+                None => Ok(()),
+            }
+        });
+        self.show_clock_result(result);
+    }
+
+    fn show_updates(&mut self, releases: Vec<Release>) {
+        let available = releases
+            .first()
+            .map(|r| r.version.to_string())
+            .unwrap_or_default();
+        let changelog = releases
+            .iter()
+            .map(|r| format!("{}\n{}", r.version, r.changelog.trim()))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        self.releases = releases;
+        self.ui
+            .update(move |e| {
+                let state = e.global::<UpdateState>();
+                state.set_available_version(available.into());
+                state.set_changelog(changelog.into());
+            })
+            .unwrap();
+    }
+
+    /// Ask legendary who's signed in, if it's installed.
+    fn check_epic_account(&self) {
+        if !legendary::is_installed() {
+            return;
+        }
+        let events = self.events.clone();
+        runtime::spawn_job(
+            CHECK_TIMEOUT,
+            legendary::account,
+            move |account| match account {
+                Ok(account) => {
+                    let _ = events.send(NavigationEvent::EpicAccount(account));
+                }
+                Err(e) => warn!("failed to check the Epic account: {:?}", e),
+            },
+        );
+    }
+
+    fn show_epic_account(&self, account: Option<String>) {
+        let status = match account {
+            Some(account) => self.i18n.tr_args(
+                "epic-signed-in",
+                Some(&fluent::FluentArgs::from_iter([("account", account)])),
+            ),
+            None => self.i18n.tr("epic-signed-out"),
+        };
+        self.ui
+            .update(move |e| e.global::<SettingsState>().set_epic_account(status.into()))
+            .unwrap();
+    }
+
+    /// Download the newest release, it's put in place on the next start.
+    fn install_update(&self) {
+        let (Some(updater), Some(release)) = (&self.updater, self.releases.first()) else {
+            return;
+        };
+        if !updater.is_downloading() {
+            updater.download(release.clone());
+        }
+    }
+
+    /// Turn crash reports and session stats on or off, kept for next time.
+    fn toggle_diagnostics(&self) -> anyhow::Result<()> {
+        let enabled = !self.diagnostics.is_enabled();
+        self.diagnostics.set_enabled(enabled);
+        self.ui
+            .update(move |e| e.global::<SettingsState>().set_diagnostics(enabled))
+            .unwrap();
+        let path = paths::config_dir().join("diagnostics.yaml");
+        let config = DiagnosticsConfig {
+            enabled,
+            ..DiagnosticsConfig::load(&path)?
+        };
+        config.save(&path)
+    }
+
+    fn toggle_resume(&mut self) -> anyhow::Result<()> {
+        let enabled = !self.resume_config.enabled;
+        self.resume_config.enabled = enabled;
+        self.ui
+            .update(move |e| e.global::<SettingsState>().set_resume(enabled))
+            .unwrap();
+        self.resume_config.save(&ResumeConfig::default_path())
+    }
+
+    fn show_mqtt(&self) {
+        let config = self.mqtt_config.lock().unwrap().clone();
+        self.ui
+            .update(move |e| {
+                let state = e.global::<SettingsState>();
+                state.set_mqtt(config.enabled);
+                state.set_mqtt_broker(config.broker.as_str().into());
+                state.set_mqtt_username(config.username.clone().unwrap_or_default().into());
+                state.set_mqtt_password(config.password.is_some());
+            })
+            .unwrap();
+    }
+
+    /// Saved, the client picks it up.
+    fn change_mqtt(&mut self, change: impl FnOnce(&mut MqttConfig)) -> anyhow::Result<()> {
+        let config = {
+            let mut config = self.mqtt_config.lock().unwrap();
+            change(&mut config);
+            config.clone()
+        };
+        self.show_mqtt();
+        config.save(&MqttConfig::default_path())
+    }
+
+    fn handle_mqtt(&mut self, command: MqttCommand) -> anyhow::Result<()> {
+        match command {
+            MqttCommand::Launch { uuid } => self.play(&uuid),
+            MqttCommand::Suspend => self.launcher.suspend().map(|_| ()),
+            MqttCommand::PauseMusic => mqtt::pause_music(),
+        }
+    }
+
+    /// Whether it's quiet hours, as scheduled or overridden.
+    fn quiet(&self) -> bool {
+        self.quiet_override.unwrap_or(self.quiet_scheduled)
+    }
+
+    fn quiet_hours_changed(&mut self, quiet: bool) {
+        info!("quiet hours {}", if quiet { "started" } else { "ended" });
+        self.quiet_scheduled = quiet;
+        self.quiet_override = None;
+        self.show_quiet();
+    }
+
+    /// Scheduled, then forced on, then forced off.
+    fn cycle_quiet(&mut self) {
+        self.quiet_override = match self.quiet_override {
+            None => Some(true),
+            Some(true) => Some(false),
+            Some(false) => None,
+        };
+        self.show_quiet();
+    }
+
+    fn show_quiet(&self) {
+        let mode = match self.quiet_override {
+            None => "auto",
+            Some(true) => "on",
+            Some(false) => "off",
+        };
+        let dim = self.quiet_config.dim(self.quiet());
+        self.ui
+            .update(move |e| {
+                e.global::<SettingsState>().set_quiet(mode.into());
+                e.global::<QuietState>().set_dim(dim);
+            })
+            .unwrap();
+    }
+
+    fn open_game_menu(&mut self, uuid: &str) -> anyhow::Result<()> {
+        if self.still_importing(uuid) {
+            return Ok(());
+        }
+        let game = self.library.lock().unwrap().get(uuid).cloned();
+        let Some(game) = game else {
+            anyhow::bail!("no game {} in the library", uuid);
+        };
+        self.menu_game = Some(uuid.to_owned());
+        self.router.rebuild(Screen::GameMenu)?;
+        self.router.push(Screen::GameMenu);
+        let status = self.i18n.tr(&format!("status-{}", game.status.name()));
+        let args = fluent::FluentArgs::from_iter([("status", status)]);
+        let status = self.i18n.tr_args("menu-status", Some(&args));
+        self.ui
+            .update(move |e| {
+                let state = e.global::<GameMenuState>();
+                state.set_title(game.title.into());
+                state.set_favorite(game.favorate);
+                state.set_status(status.into());
+            })
+            .unwrap();
+        Ok(())
+    }
+
+    /// Close the menu and have the navigation do the entry.
+    fn pick_menu_item(&mut self, item: GameMenuItem) {
+        let Some(uuid) = self.menu_game.take() else {
+            return;
+        };
+        self.router.pop();
+        let _ = self.events.send(NavigationEvent::GameMenu { uuid, item });
+    }
+
+    fn run_menu_item(&mut self, uuid: &str, item: GameMenuItem) -> anyhow::Result<()> {
+        match item {
+            GameMenuItem::Play => self.play(uuid)?,
+            GameMenuItem::Favorite => self.toggle_favorite(uuid)?,
+            GameMenuItem::Status => self.next_status(uuid)?,
+            GameMenuItem::Update => self.queue_steam_transfer(uuid, TransferKind::Update),
+            GameMenuItem::Uninstall => self.confirm_uninstall(uuid.to_owned()),
+            GameMenuItem::Properties => self.open_details(uuid)?,
+        }
+        Ok(())
+    }
+
+    fn has_selection(&self) -> bool {
+        !self.selected.lock().unwrap().is_empty()
+    }
+
+    /// In library order.
+    fn selected_games(&self) -> Vec<String> {
+        let selected = self.selected.lock().unwrap();
+        let library = self.library.lock().unwrap();
+        library
+            .iter()
+            .filter(|g| selected.contains(&g.uuid))
+            .map(|g| g.uuid.clone())
+            .collect()
+    }
+
+    /// In or out of the selection for the bulk actions.
+    fn toggle_selected(&mut self, uuid: &str) {
+        if self.still_importing(uuid) {
+            return;
+        }
+        {
+            let mut selected = self.selected.lock().unwrap();
+            if !selected.remove(uuid) {
+                selected.insert(uuid.to_owned());
+            }
+        }
+        let change = LibraryChange::Changed(uuid.to_owned());
+        self.ui.update(move |e| tiles::apply(e, change)).unwrap();
+        self.show_selection();
+    }
+
+    fn clear_selection(&mut self) {
+        let cleared: Vec<String> = self.selected.lock().unwrap().drain().collect();
+        self.ui
+            .update(move |e| {
+                for uuid in &cleared {
+                    tiles::apply(e, LibraryChange::Changed(uuid.clone()));
+                }
+            })
+            .unwrap();
+        self.show_selection();
+    }
+
+    fn show_selection(&self) {
+        let count = self.selected.lock().unwrap().len();
+        let selection = match count {
+            0 => String::new(),
+            _ => {
+                let args = fluent::FluentArgs::from_iter([("count", count as i64)]);
+                self.i18n.tr_args("games-selected", Some(&args))
+            }
+        };
+        self.ui
+            .update(move |e| e.global::<BulkMenuState>().set_selection(selection.into()))
+            .unwrap();
+    }
+
+    /// E.g. "3 games".
+    fn game_count(&self, count: usize) -> String {
+        let args = fluent::FluentArgs::from_iter([("count", count as i64)]);
+        self.i18n.tr_args("game-count", Some(&args))
+    }
+
+    fn open_bulk_menu(&mut self) -> anyhow::Result<()> {
+        self.router.rebuild(Screen::BulkMenu)?;
+        self.router.push(Screen::BulkMenu);
+        Ok(())
+    }
+
+    fn confirm_delete_selected(&mut self) {
+        let uuids = self.selected_games();
+        if uuids.is_empty() {
+            return;
+        }
+        let games = self.game_count(uuids.len());
+        self.confirm(
+            "delete",
+            "confirm-delete-games",
+            Some(games),
+            Confirmed::DeleteGames(uuids),
+        );
+    }
+
+    /// As a task, see `library::bulk`. The selection is done with.
+    fn run_bulk(&mut self, action: BulkAction, uuids: Vec<String>) {
+        if uuids.is_empty() {
+            return;
+        }
+        self.clear_selection();
+        let title = format!("{} ({})", action.describe(), uuids.len());
+        let (library, events) = (self.library.clone(), self.events.clone());
+        let locale = self.i18n.locale().to_owned();
+        let mut bulk = Bulk::new(action, uuids);
+        self.transfers.enqueue_task(
+            TransferKind::Bulk,
+            &title,
+            Box::new(move |ctx| {
+                let outcome = bulk.run(ctx, &library, &locale);
+                let result = match &outcome {
+                    Ok(outcome) => Ok(outcome.clone()),
+                    Err(e) => Err(format!("{:#}", e)),
+                };
+                let _ = events.send(NavigationEvent::Bulk(result));
+                outcome?;
+                ctx.check_stop()
+            }),
+        );
+    }
+
+    /// Show what a run of the bulk actions did, the toast once it's over.
+    fn show_bulk_outcome(&mut self, outcome: Result<Outcome, String>) -> anyhow::Result<()> {
+        let outcome = match outcome {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                warn!("failed to edit the selected games: {}", e);
+                return Ok(());
+            }
+        };
+        for uuid in &outcome.removed {
+            controller::remove_game(self.router.controller_for(Screen::Home)?, uuid)?;
+            let change = LibraryChange::Removed(uuid.clone());
+            self.ui.update(move |e| tiles::apply(e, change))?;
+        }
+        if !outcome.removed.is_empty() {
+            self.show_games_rows()?;
+        }
+        for uuid in &outcome.changed {
+            self.game_changed(uuid);
+        }
+        if !outcome.removed.is_empty() {
+            let mut removed = match self.undo_delete.take() {
+                Some((removed, at)) if at.elapsed() < TOAST_DURATION => removed,
+                _ => vec![],
+            };
+            removed.extend(outcome.removed);
+            self.undo_delete = Some((removed, Instant::now()));
+        }
+        if outcome.finished {
+            let games = self.game_count(outcome.total);
+            let deleted = self.undo_delete.as_ref().filter(|_| outcome.failed == 0);
+            let message = match outcome.failed {
+                _ if deleted.is_some() => self.i18n.tr_args(
+                    "games-deleted",
+                    Some(&fluent::FluentArgs::from_iter([("title", games)])),
+                ),
+                0 => self.i18n.tr_args(
+                    "bulk-done",
+                    Some(&fluent::FluentArgs::from_iter([("title", games)])),
+                ),
+                failed => {
+                    let mut args = fluent::FluentArgs::from_iter([("title", games)]);
+                    args.set("failed", failed as i64);
+                    self.i18n.tr_args("bulk-failed", Some(&args))
+                }
+            };
+            self.show_toast(message);
+        }
+        Ok(())
+    }
+
+    /// While the toast saying they were deleted is up.
+    fn can_undo_delete(&self) -> bool {
+        let undo = self.undo_delete.as_ref();
+        undo.is_some_and(|(_, at)| at.elapsed() < TOAST_DURATION)
+    }
+
+    /// Put the games just deleted back, see `show_bulk_outcome`.
+    fn undo_delete(&mut self) -> anyhow::Result<()> {
+        if !self.can_undo_delete() {
+            return Ok(());
+        }
+        let Some((uuids, _)) = self.undo_delete.take() else {
+            return Ok(());
+        };
+        {
+            let mut library = self.library.lock().unwrap();
+            for uuid in &uuids {
+                if let Err(e) = library.untrash_game(uuid) {
+                    warn!("failed to undo deleting {}: {:?}", uuid, e);
+                }
+            }
+            library.save()?;
+        }
+        self.reload_games()?;
+        let args = fluent::FluentArgs::from_iter([("title", self.game_count(uuids.len()))]);
+        self.show_toast(self.i18n.tr_args("games-restored", Some(&args)));
+        Ok(())
+    }
+
+    /// Of the entry shown, in `Library::trashed`.
+    fn trashed_index(&self) -> Option<usize> {
+        let count = self.library.lock().unwrap().trashed().len();
+        count.checked_sub(self.trash_index + 1)
+    }
+
+    fn show_trash(&mut self, status: Option<String>) {
+        let count = self.library.lock().unwrap().trashed().len();
+        self.trash_index = self.trash_index.min(count.saturating_sub(1));
+        let shown = self
+            .trashed_index()
+            .and_then(|i| self.library.lock().unwrap().trashed().get(i).cloned());
+        let (title, removal) = match shown {
+            Some(trashed) => {
+                let id = match trashed.removal {
+                    Removal::Deleted => "trashed-deleted",
+                    Removal::Overwritten => "trashed-overwritten",
+                };
+                let date = self.i18n.format_date(&trashed.at);
+                let args = fluent::FluentArgs::from_iter([("date", date)]);
+                (trashed.game.title, self.i18n.tr_args(id, Some(&args)))
+            }
+            None => Default::default(),
+        };
+        let position = match count {
+            0 => String::new(),
+            _ => format!("{} / {}", self.trash_index + 1, count),
+        };
+        let status = status.unwrap_or_default();
+        self.ui
+            .update(move |e| {
+                let state = e.global::<TrashState>();
+                state.set_title(title.into());
+                state.set_removal(removal.into());
+                state.set_position(position.into());
+                state.set_status(status.into());
+            })
+            .unwrap();
+    }
+
+    fn step_trash(&mut self, step: isize) {
+        let last = self
+            .library
+            .lock()
+            .unwrap()
+            .trashed()
+            .len()
+            .saturating_sub(1) as isize;
+        self.trash_index = (self.trash_index as isize + step).clamp(0, last) as usize;
+        self.show_trash(None);
+    }
+
+    /// The game back in the library, or its metadata back as it was.
+    fn restore_trashed(&mut self) -> anyhow::Result<()> {
+        let Some(i) = self.trashed_index() else {
+            return Ok(());
+        };
+        let restored = {
+            let mut library = self.library.lock().unwrap();
+            let removal = library.trashed()[i].removal;
+            library
+                .untrash(i)
+                .and_then(|uuid| library.save().map(|()| (uuid, removal)))
+        };
+        match restored {
+            Ok((_, Removal::Deleted)) => self.reload_games()?,
+            Ok((uuid, Removal::Overwritten)) => self.game_changed(&uuid),
+            Err(e) => {
+                self.show_trash(Some(format!("{:#}", e)));
+                return Ok(());
+            }
+        }
+        self.show_trash(None);
+        Ok(())
+    }
+
+    fn purge_trashed(&mut self) -> anyhow::Result<()> {
+        let Some(i) = self.trashed_index() else {
+            return Ok(());
+        };
+        {
+            let mut library = self.library.lock().unwrap();
+            library.purge(i)?;
+            library.save()?;
+        }
+        self.show_trash(None);
+        Ok(())
+    }
+
+    fn empty_trash(&mut self) {
+        let emptied = {
+            let mut library = self.library.lock().unwrap();
+            library.empty_trash();
+            library.save()
+        };
+        self.show_trash(emptied.err().map(|e| format!("{:#}", e)));
+    }
+
+    fn toggle_favorite(&self, uuid: &str) -> anyhow::Result<()> {
+        {
+            let mut library = self.library.lock().unwrap();
+            let Some(game) = library.get_mut(uuid) else {
+                anyhow::bail!("no game {} in the library", uuid);
+            };
+            game.favorate = !game.favorate;
+            library.save()?;
+        }
+        self.game_changed(uuid);
+        Ok(())
+    }
+
+    fn next_status(&self, uuid: &str) -> anyhow::Result<()> {
+        {
+            let mut library = self.library.lock().unwrap();
+            let Some(game) = library.get_mut(uuid) else {
+                anyhow::bail!("no game {} in the library", uuid);
+            };
+            game.status = game.status.next();
+            library.save()?;
+        }
+        self.game_changed(uuid);
+        Ok(())
+    }
+
+    fn toggle_quick_settings(&mut self) {
+        if self.router.current_screen() == Screen::QuickSettings {
+            self.router.pop();
+            return;
+        }
+        self.router.push(Screen::QuickSettings);
+        self.quick_settings = QuickSettings::read();
+        self.show_quick_settings(String::new());
+    }
+
+    fn step_brightness(&mut self, step: f32) {
+        let Some(brightness) = self.quick_settings.brightness else {
+            return;
+        };
+        let brightness = (brightness + step).clamp(0.0, 1.0);
+        let status = quick_settings::set_brightness(brightness);
+        if status.is_ok() {
+            self.quick_settings.brightness = Some(brightness);
+        }
+        self.show_quick_settings_result(status);
+    }
+
+    fn step_volume(&mut self, step: f32) {
+        let Some(volume) = self.quick_settings.volume else {
+            return;
+        };
+        let volume = (volume + step).clamp(0.0, 1.0);
+        let status = quick_settings::set_volume(volume);
+        if status.is_ok() {
+            self.quick_settings.volume = Some(volume);
+        }
+        self.show_quick_settings_result(status);
+    }
+
+    fn toggle_wifi(&mut self) {
+        let Some(on) = self.quick_settings.wifi.map(|on| !on) else {
+            return;
+        };
+        let status = quick_settings::set_wifi(on);
+        if status.is_ok() {
+            self.quick_settings.wifi = Some(on);
+        }
+        self.show_quick_settings_result(status);
+    }
+
+    fn toggle_bluetooth(&mut self) {
+        let Some(on) = self.quick_settings.bluetooth.map(|on| !on) else {
+            return;
+        };
+        let status = quick_settings::set_bluetooth(on);
+        if status.is_ok() {
+            self.quick_settings.bluetooth = Some(on);
+        }
+        self.show_quick_settings_result(status);
+    }
+
+    fn show_quick_settings_result(&self, status: anyhow::Result<()>) {
+        let status = status.err().map(|e| format!("{:#}", e)).unwrap_or_default();
+        self.show_quick_settings(status);
+    }
+
+    /// The settings as last read or set, unavailable ones as -1 or "".
+    fn show_quick_settings(&self, status: String) {
+        let settings = self.quick_settings.clone();
+        let toggle = |on: Option<bool>| match on {
+            Some(true) => "on",
+            Some(false) => "off",
+            None => "",
+        };
+        let (wifi, bluetooth) = (toggle(settings.wifi), toggle(settings.bluetooth));
+        self.ui
+            .update(move |e| {
+                let state = e.global::<QuickSettingsState>();
+                state.set_brightness(settings.brightness.unwrap_or(-1.0));
+                state.set_volume(settings.volume.unwrap_or(-1.0));
+                state.set_wifi(wifi.into());
+                state.set_bluetooth(bluetooth.into());
+                state.set_status(status.into());
+            })
+            .unwrap();
+    }
+
+    /// Count down to launching the game played last, see `launcher::resume`.
+    fn resume_tick(&mut self, left: u32) {
+        let Some(uuid) = self.resuming.clone() else {
+            return;
+        };
+        if left > 0 {
+            let title = self
+                .library
+                .lock()
+                .unwrap()
+                .get(&uuid)
+                .map(|g| g.title.clone());
+            let args = fluent::FluentArgs::from_iter([
+                ("title", title.unwrap_or_else(|| uuid.clone())),
+                ("seconds", left.to_string()),
+            ]);
+            return self.show_resume(self.i18n.tr_args("resuming", Some(&args)));
+        }
+        self.resuming = None;
+        self.show_resume(String::new());
+        if let Err(e) = self.play(&uuid) {
+            warn!("failed to resume {}: {:?}", uuid, e);
+        }
+    }
+
+    fn cancel_resume(&mut self) {
+        info!("not resuming the last game");
+        self.resuming = None;
+        self.show_resume(String::new());
+    }
+
+    fn show_resume(&self, message: String) {
+        self.ui
+            .update(move |e| e.global::<ResumeState>().set_message(message.into()))
+            .unwrap();
+    }
+
+    /// Tell that a thread was restarted, or given up on.
+    fn show_restart(&self, restart: Restart) {
+        let component = self.i18n.tr(&format!("component-{}", restart.component));
+        let id = if restart.gave_up {
+            "component-gave-up"
+        } else {
+            "component-restarted"
+        };
+        let args = fluent::FluentArgs::from_iter([("component", component)]);
+        self.show_toast(self.i18n.tr_args(id, Some(&args)));
+    }
+
+    fn show_toast(&self, message: String) {
+        self.bus.publish(bus::Event::Notification {
+            message: message.clone(),
+        });
+        self.ui
+            .update(move |e| {
+                e.global::<ToastState>().set_message(message.clone().into());
+                let handle = e.as_weak();
+                slint::Timer::single_shot(TOAST_DURATION, move || {
+                    let Some(e) = handle.upgrade() else {
+                        return;
+                    };
+                    let toast = e.global::<ToastState>();
+                    // Unless another one took its place.
+                    if toast.get_message() == message.as_str() {
+                        toast.set_message(Default::default());
+                    }
+                });
+            })
+            .unwrap();
+    }
+
+    fn show_settings_status(&self, status: String) {
+        self.ui
+            .update(move |e| e.global::<SettingsState>().set_status(status.into()))
+            .unwrap();
+    }
+
+    /// As a task, the art is read and inlined which takes a while.
+    fn backup_library(&self) {
+        let (library, events) = (self.library.clone(), self.events.clone());
+        self.transfers.enqueue_task(
+            TransferKind::Backup,
+            "Library",
+            Box::new(move |ctx| {
+                let backup = Backup::new(&library.lock().unwrap(), true);
+                ctx.check_stop()?;
+                let path = Backup::default_dir().join(backup.file_name());
+                let saved = backup.save(&path);
+                let status = match &saved {
+                    Ok(()) => format!("Saved {} games to {}", backup.games.len(), path.display()),
+                    Err(e) => format!("Failed to back up the library: {}", e),
+                };
+                let _ = events.send(NavigationEvent::SettingsStatus(status));
+                saved
+            }),
+        );
+        self.show_settings_status("Backing up the library...".to_owned());
+    }
+
+    fn restore_library(&mut self, mode: RestoreMode) {
+        let Some(path) = Backup::latest(&Backup::default_dir()) else {
+            self.show_settings_status("No backup found.".to_owned());
+            return;
+        };
+        let restored = Backup::load(&path).and_then(|backup| {
+            let mut library = self.library.lock().unwrap();
+            let counts = library.restore(backup, mode)?;
+            library.save()?;
+            Ok(counts)
+        });
+        let status = match restored {
+            Ok((added, merged)) => {
+                if let Err(e) = self.reload_games() {
+                    warn!("failed to reload the games: {:?}", e);
+                }
+                format!(
+                    "Restored {}: {} games added, {} merged",
+                    path.display(),
+                    added,
+                    merged
+                )
+            }
+            Err(e) => format!("Failed to restore {}: {}", path.display(), e),
+        };
+        self.show_settings_status(status);
+    }
+
+    /// Show the first group of duplicates left.
+    fn show_duplicates(&self) {
+        let games: Vec<DuplicateData> = match self.duplicates.first() {
+            Some(group) => {
+                let library = self.library.lock().unwrap();
+                group
+                    .iter()
+                    .filter_map(|uuid| library.get(uuid))
+                    .enumerate()
+                    .map(|(i, g)| {
+                        let minutes = g.playtime.map(|p| p.num_minutes()).unwrap_or_default();
+                        DuplicateData {
+                            title: g.title.clone().into(),
+                            platform: g.platform.clone().unwrap_or_default().into(),
+                            source: g.install_source.clone().unwrap_or_default().into(),
+                            playtime: format!("{}h {}m", minutes / 60, minutes % 60).into(),
+                            keep: i == 0,
+                        }
+                    })
+                    .collect()
+            }
+            None => vec![],
+        };
+        let status = match self.duplicates.len() {
+            0 => "No duplicates found.".to_owned(),
+            1 => "1 group left.".to_owned(),
+            n => format!("{} groups left.", n),
+        };
+        self.ui
+            .update(move |e| {
+                let state = e.global::<DuplicatesState>();
+                state.set_games(std::rc::Rc::new(slint::VecModel::from(games)).into());
+                state.set_status(status.into());
+            })
+            .unwrap();
+    }
+
+    /// Lay out a card per candidate of the first game waiting for a pick.
+    fn layout_matches(&mut self) -> anyhow::Result<()> {
+        // Games removed since can't be picked for.
+        {
+            let library = self.library.lock().unwrap();
+            self.matches
+                .pending
+                .retain(|p| library.get(&p.uuid).is_some());
+        }
+        let count = self
+            .matches
+            .pending
+            .first()
+            .map_or(0, |p| p.candidates.len());
+        self.router.rebuild(Screen::Matches)?;
+        let controller = self.router.controller_for(Screen::Matches)?;
+        controller.with_sublayout(
+            &LayoutId::sublayout("Matches", "Candidates"),
+            |l| -> anyhow::Result<()> {
+                for i in 0..count {
+                    l.insert_to_growable_grid(FocusId::candidate(i))?;
+                }
+                Ok(())
+            },
+        )??;
+        let focus = match count {
+            0 => FocusId::button("BACK"),
+            _ => FocusId::candidate(0),
+        };
+        controller.focus(&focus)?;
+        self.show_matches();
+        Ok(())
+    }
+
+    /// The first game waiting for a pick and what it could be, the covers
+    /// showing up once downloaded.
+    fn show_matches(&self) {
+        let t = &self.i18n;
+        let pending = self.matches.pending.first();
+        let title = pending
+            .and_then(|p| {
+                self.library
+                    .lock()
+                    .unwrap()
+                    .get(&p.uuid)
+                    .map(|g| g.title.clone())
+            })
+            .map(|title| {
+                let args = fluent::FluentArgs::from_iter([("title", title)]);
+                t.tr_args("which-game", Some(&args))
+            })
+            .unwrap_or_default();
+        let args = fluent::FluentArgs::from_iter([("count", self.matches.pending.len() as i64)]);
+        let status = t.tr_args("matches-left", Some(&args));
+        let candidates: Vec<_> = pending
+            .map(|p| p.candidates.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|c| {
+                let cover = c
+                    .image
+                    .as_ref()
+                    .zip(self.art.as_ref())
+                    .and_then(|(url, art)| art.fetch(&match_art_key(c.app_id), url));
+                let year = c.year.map(|y| y.to_string()).unwrap_or_default();
+                (c.name.clone(), year, cover)
+            })
+            .collect();
+        self.ui
+            .update(move |e| {
+                let candidates: Vec<MatchData> = candidates
+                    .into_iter()
+                    .map(|(name, year, cover)| MatchData {
+                        name: name.into(),
+                        year: year.into(),
+                        cover: cover
+                            .and_then(|p| slint::Image::load_from_path(&p).ok())
+                            .unwrap_or_default(),
+                    })
+                    .collect();
+                let state = e.global::<MatchesState>();
+                state.set_title(title.into());
+                state.set_status(status.into());
+                state.set_candidates(std::rc::Rc::new(slint::VecModel::from(candidates)).into());
+            })
+            .unwrap();
+    }
+
+    /// The first game waiting is the candidate at `index`, remembered for
+    /// rescans. Its metadata is filled in from the store in the background.
+    fn pick_match(&mut self, index: &str) -> anyhow::Result<()> {
+        let Some(pending) = self.matches.pending.first() else {
+            return Ok(());
+        };
+        let candidate = index
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| pending.candidates.get(i))
+            .ok_or(anyhow::anyhow!("no candidate {}", index))?;
+        let (uuid, app_id) = (pending.uuid.clone(), candidate.app_id);
+        self.matches.resolve(&uuid, app_id);
+        self.matches.save(&Matches::default_path())?;
+        let (events, locale) = (self.events.clone(), self.i18n.locale());
+        runtime::spawn_job(
+            CHECK_TIMEOUT,
+            move || steam::store_details_in(app_id, locale),
+            move |details| {
+                let details = details.map(Box::new).map_err(|e| e.to_string());
+                let _ = events.send(NavigationEvent::Matched { uuid, details });
+            },
+        );
+        self.layout_matches()
+    }
+
+    /// None of the candidates, the game is asked about again on the next
+    /// scrape.
+    fn skip_match(&mut self) -> anyhow::Result<()> {
+        if let Some(uuid) = self.matches.pending.first().map(|p| p.uuid.clone()) {
+            self.matches.skip(&uuid);
+            self.matches.save(&Matches::default_path())?;
+        }
+        self.layout_matches()
+    }
+
+    /// Fill in the metadata of a game from the app picked for it.
+    fn apply_match(
+        &mut self,
+        uuid: String,
+        details: Result<Box<(steam::StoreDetails, Option<steam::StoreDetails>)>, String>,
+    ) -> anyhow::Result<()> {
+        let (details, localized) = match details {
+            Ok(details) => *details,
+            Err(e) => {
+                warn!("failed to scrape {}: {}", uuid, e);
+                return Ok(());
+            }
+        };
+        {
+            let mut library = self.library.lock().unwrap();
+            let Some(game) = library.get_mut(&uuid) else {
+                return Ok(());
+            };
+            steam::apply_details(game, details, localized, self.i18n.locale(), false);
+            library.save()?;
+        }
+        self.game_changed(&uuid);
+        Ok(())
+    }
+
+    /// Merge the group being reviewed into its first game.
+    fn merge_duplicates(&mut self) -> anyhow::Result<()> {
+        if self.duplicates.is_empty() {
+            return Ok(());
+        }
+        let group = self.duplicates.remove(0);
+        {
+            let mut library = self.library.lock().unwrap();
+            for duplicate in &group[1..] {
+                library.merge(&group[0], duplicate)?;
+            }
+            library.save()?;
+        }
+        for duplicate in &group[1..] {
+            controller::remove_game(self.router.controller_for(Screen::Home)?, duplicate)?;
+            let change = LibraryChange::Removed(duplicate.clone());
+            self.ui.update(move |e| tiles::apply(e, change))?;
+        }
+        // It may have picked up art from the others.
+        let kept = LibraryChange::Changed(group[0].clone());
+        self.ui.update(move |e| tiles::apply(e, kept))?;
+        self.show_games_rows()?;
+        self.show_duplicates();
+        Ok(())
+    }
+
+    /// Lay out the games of the library on the home screen.
+    fn layout_games(&mut self) -> anyhow::Result<()> {
+        {
+            let library = self.library.lock().unwrap();
+            let importing = self.importing.iter().filter(|u| library.get(u).is_none());
+            controller::layout_games(
+                self.router.controller_for(Screen::Home)?,
+                library
+                    .visible()
+                    .map(|g| g.uuid.as_str())
+                    .chain(importing.map(String::as_str)),
+            )?;
+            self.title_index = library.title_index();
+        }
+        self.show_jump_letters();
+        self.show_games_rows()
+    }
+
+    /// Size the scroll range of the home screen to the grid.
+    fn show_games_rows(&mut self) -> anyhow::Result<()> {
+        let rows = controller::games_rows(self.router.controller_for(Screen::Home)?)?;
+        self.viewport.set_rows(rows);
+        self.ui.update(move |e| {
+            e.global::<HomeWindowFocus>().set_games_rows(rows as i32);
+        })?;
+        Ok(())
+    }
+
+    /// Have the home screen read the game tiles again, in library order.
+    fn show_games(&self) -> anyhow::Result<()> {
+        self.ui.update(|e| tiles::apply(e, LibraryChange::Reset))?;
+        Ok(())
+    }
+
+    /// Scroll the games grid, see `controller::viewport`.
+    fn show_scroll(&self, hint: ScrollHint) {
+        let data = ScrollHintData {
+            row: hint.row as i32,
+            duration_ms: hint.duration.as_millis() as i32,
+            linear: hint.easing == Easing::Linear,
+            velocity: hint.velocity,
+        };
+        self.ui
+            .update(move |e| e.global::<HomeWindowFocus>().set_games_scroll(data.clone()))
+            .unwrap();
+    }
+
+    /// Dim the letters of the jump strip no game starts with.
+    /// The group headers of the settings list, translated, for the UI to keep
+    /// the one scrolled into at the top.
+    fn show_settings_headers(&mut self) {
+        let list = LayoutId::sublayout("Settings", "List");
+        let headers = match self
+            .router
+            .controller_for(Screen::Settings)
+            .and_then(|c| c.with_sublayout(&list, |l| l.list_headers()))
+        {
+            Ok(headers) => headers,
+            Err(e) => {
+                warn!("no settings list: {}", e);
+                return;
+            }
+        };
+        let headers: Vec<ListHeaderData> = headers
+            .into_iter()
+            .map(|h| ListHeaderData {
+                title: self.i18n.tr(&h.title).into(),
+                row: h.row as i32,
+                end: h.end as i32,
+            })
+            .collect();
+        self.ui
+            .update(move |e| {
+                let headers = std::rc::Rc::new(slint::VecModel::from(headers.clone()));
+                e.global::<SettingsState>().set_headers(headers.into());
+            })
+            .unwrap();
+    }
+
+    fn show_jump_letters(&self) {
+        let letters: Vec<JumpLetter> = index::letters()
+            .map(|l| JumpLetter {
+                letter: l.to_string().into(),
+                has_games: self.title_index.has(l),
+            })
+            .collect();
+        self.ui
+            .update(move |e| {
+                let letters = std::rc::Rc::new(slint::VecModel::from(letters.clone()));
+                e.global::<JumpState>().set_letters(letters.into());
+            })
+            .unwrap();
+    }
+
+    /// Focus the first game under the next letter with games, or the
+    /// previous one, from the letter of the focused game. From outside the
+    /// games, the first letter or the last.
+    fn jump_letter(&mut self, forward: bool) -> anyhow::Result<()> {
+        if self.router.current_screen() != Screen::Home || self.router.controller().held().is_some()
+        {
+            return Ok(());
+        }
+        let focused = self.router.controller().get_current_focus_id().clone();
+        let from = focused
+            .filter(|f| f.kind() == FocusKind::Game)
+            .and_then(|f| {
+                let library = self.library.lock().unwrap();
+                library.get(f.payload()).map(|g| index::letter_of(&g.title))
+            });
+        let letter = match from {
+            Some(from) => self.title_index.next_letter(from, forward),
+            None => {
+                let mut letters = index::letters().filter(|&l| self.title_index.has(l));
+                if forward {
+                    letters.next()
+                } else {
+                    letters.last()
+                }
+            }
+        };
+        let Some(letter) = letter else {
+            return Ok(());
+        };
+        let Some(uuid) = self.title_index.first_with(letter).map(str::to_owned) else {
+            return Ok(());
+        };
+        controller::focus_game(self.router.controller(), &uuid)?;
+        let letter = letter.to_string();
+        self.ui
+            .update(move |e| {
+                e.global::<JumpState>().set_letter(letter.clone().into());
+                let handle = e.as_weak();
+                let letter = letter.clone();
+                slint::Timer::single_shot(JUMP_HIGHLIGHT, move || {
+                    let Some(e) = handle.upgrade() else {
+                        return;
+                    };
+                    let state = e.global::<JumpState>();
+                    // Unless jumped again since.
+                    if state.get_letter() == letter.as_str() {
+                        state.set_letter(Default::default());
+                    }
+                });
+            })
+            .unwrap();
+        Ok(())
+    }
+
+    /// Show games about to be added as placeholders, after the others.
+    fn show_importing(&mut self, uuids: Vec<String>) -> anyhow::Result<()> {
+        self.importing.extend(uuids.iter().cloned());
+        self.layout_games()?;
+        self.ui
+            .update(move |e| tiles::apply(e, LibraryChange::Importing(uuids.clone())))?;
+        Ok(())
+    }
+
+    /// Fill in the placeholder of a game now in the library.
+    fn show_imported(&mut self, uuid: String) {
+        self.importing.retain(|u| *u != uuid);
+        self.ui
+            .update(move |e| tiles::apply(e, LibraryChange::Imported(uuid.clone())))
+            .unwrap();
+    }
+
+    /// Placeholders can be focused but not opened, say so if it's one.
+    fn still_importing(&self, uuid: &str) -> bool {
+        let importing = self.importing.iter().any(|u| u == uuid);
+        if importing {
+            self.show_toast(self.i18n.tr("game-importing"));
+        }
+        importing
+    }
+
+    /// Show the library again after it changed as a whole, e.g. on restore.
+    fn reload_games(&mut self) -> anyhow::Result<()> {
+        self.router.rebuild(Screen::Home)?;
+        self.layout_games()?;
+        self.show_games()
+    }
+
+    /// Lay a screen out again after its layout file changed, with what it
+    /// shows and the focus where it was when it's still there.
+    fn reload_layout(&mut self, screen: Screen) -> anyhow::Result<()> {
+        info!("reloading the {:?} layout", screen);
+        let focus_id = self
+            .router
+            .controller_for(screen)?
+            .get_current_focus_id()
+            .clone();
+        match screen {
+            Screen::Home => {
+                self.router.rebuild(Screen::Home)?;
+                self.layout_games()?;
+            }
+            Screen::GameDetails => {
+                let back = FocusId::button("BACK");
+                self.layout_details(focus_id.as_ref().unwrap_or(&back))?;
+            }
+            Screen::Downloads => self.layout_downloads()?,
+            _ => self.router.rebuild(screen)?,
+        }
+        let controller = self.router.controller_for(screen)?;
+        if let Some(focus_id) = focus_id {
+            if controller.focus(&focus_id).is_err() {
+                info!("{} is gone from the {:?} layout", focus_id, screen);
+            }
+        }
+        Ok(())
+    }
+
+    fn select_tab(&mut self, tab: DetailsTab) -> anyhow::Result<()> {
+        let Some(ref mut details) = self.details else {
+            return Ok(());
+        };
+        details.tab = tab;
+        self.router
+            .controller_for(Screen::GameDetails)?
+            .select_tab(&LayoutId::sublayout("GameDetails", "Tabs"), &tab.layout())?;
+        self.show_tab(tab);
+        Ok(())
+    }
+
+    /// Show the tab `steps` tabs to the right of the one shown on the
+    /// details screen, wrapping around. The focus stays where it was in each
+    /// tab, or goes to the tab's button when it has nothing to focus.
+    fn cycle_tab(&mut self, steps: isize) -> anyhow::Result<()> {
+        if self.router.current_screen() != Screen::GameDetails {
+            return Ok(());
+        }
+        let Some(ref mut details) = self.details else {
+            return Ok(());
+        };
+        let controller = self.router.controller_for(Screen::GameDetails)?;
+        let root = Some(LayoutId::root("GameDetails"));
+        let in_tabs = controller.current_layout_id() != root;
+        let shown = controller.cycle_tab(&LayoutId::sublayout("GameDetails", "Tabs"), steps)?;
+        let Some(tab) = DetailsTab::ALL.into_iter().find(|t| t.layout() == shown) else {
+            anyhow::bail!("no details tab for {}", shown);
+        };
+        if in_tabs && controller.current_layout_id() == root {
+            controller.focus(&tab.focus_id())?;
+        }
+        details.tab = tab;
+        self.show_tab(tab);
+        Ok(())
+    }
+
+    fn show_tab(&self, tab: DetailsTab) {
+        self.ui
+            .update(move |e| {
+                e.global::<GameDetailsState>().set_tab(tab.name().into());
+            })
+            .unwrap();
+    }
+
+    /// Lay out the details screen with a row per achievement and news item
+    /// and a tile per screenshot, showing the current tab.
+    fn layout_details(&mut self, focus_id: &FocusId) -> anyhow::Result<()> {
+        let Some(ref details) = self.details else {
+            return Ok(());
+        };
+        let (achievements, screenshots) = (details.achievements, details.screenshots.len());
+        let (news, sessions) = (details.news, details.sessions);
+        let tab = details.tab;
+        self.router.rebuild(Screen::GameDetails)?;
+        let controller = self.router.controller_for(Screen::GameDetails)?;
+        let fill = |section, count, id: fn(usize) -> FocusId, per_row| {
+            controller.with_sublayout(
+                &LayoutId::sublayout("GameDetails", section),
+                |l| -> anyhow::Result<()> {
+                    for i in 0..count {
+                        l.insert_to_growable_grid(id(i))?;
+                        l.set_payload(&id(i), DetailsRow(i / per_row))?;
+                    }
+                    Ok(())
+                },
+            )?
+        };
+        fill("Achievements", achievements, FocusId::achievement, 1)?;
+        fill("Screenshots", screenshots, FocusId::screenshot, 3)?;
+        fill("News", news, FocusId::news, 1)?;
+        fill("Sessions", sessions, FocusId::session, 1)?;
+        for tab in DetailsTab::ALL {
+            controller.set_payload(&tab.focus_id(), tab)?;
+        }
+        controller.select_tab(&LayoutId::sublayout("GameDetails", "Tabs"), &tab.layout())?;
+        if controller.focus(focus_id).is_err() {
+            controller.focus(&FocusId::button("BACK"))?;
+        }
+        Ok(())
+    }
+
+    fn show_achievements(&mut self, uuid: String, progress: Result<GameProgress, String>) {
+        let Some(ref mut details) = self.details else {
+            return;
+        };
+        if details.uuid != uuid {
+            return;
+        }
+        let (status, rows) = match progress {
+            Ok(progress) => (
+                progress.summary(),
+                progress
+                    .achievements
+                    .iter()
+                    .map(|a| AchievementData {
+                        title: a.title.clone().into(),
+                        description: a.description.clone().into(),
+                        points: a.points as i32,
+                        unlocked: a.unlocked_at.is_some(),
+                        unlocked_at: a
+                            .unlocked_at
+                            .map(|d| d.format("%Y-%m-%d").to_string())
+                            .unwrap_or_default()
+                            .into(),
+                        hardcore: a.hardcore,
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Err(e) => (e, vec![]),
+        };
+        details.achievements = rows.len();
+        if self.router.current_screen() == Screen::GameDetails {
+            let focus_id = self
+                .router
+                .controller()
+                .get_current_focus_id()
+                .clone()
+                .unwrap_or_else(|| FocusId::button("BACK"));
+            if let Err(e) = self.layout_details(&focus_id) {
+                warn!("failed to lay out the achievements: {:?}", e);
+            }
+        }
+        self.ui
+            .update(move |e| {
+                let state = e.global::<GameDetailsState>();
+                state.set_achievements(std::rc::Rc::new(slint::VecModel::from(rows)).into());
+                state.set_achievements_status(status.into());
+            })
+            .unwrap();
+    }
+
+    fn show_news(&mut self, uuid: String, news: Result<Vec<NewsItem>, String>) {
+        let Some(ref mut details) = self.details else {
+            return;
+        };
+        if details.uuid != uuid {
+            return;
+        }
+        let (status, rows) = match news {
+            Ok(items) if items.is_empty() => (self.i18n.tr("no-news"), vec![]),
+            Ok(items) => (
+                String::new(),
+                items.iter().map(|i| news_data(i, "")).collect(),
+            ),
+            Err(e) => (e, vec![]),
+        };
+        details.news = rows.len();
+        if self.router.current_screen() == Screen::GameDetails {
+            let focus_id = self
+                .router
+                .controller()
+                .get_current_focus_id()
+                .clone()
+                .unwrap_or_else(|| FocusId::button("BACK"));
+            if let Err(e) = self.layout_details(&focus_id) {
+                warn!("failed to lay out the news: {:?}", e);
+            }
+        }
+        self.ui
+            .update(move |e| {
+                let state = e.global::<GameDetailsState>();
+                state.set_news(std::rc::Rc::new(slint::VecModel::from(rows)).into());
+                state.set_news_status(status.into());
+            })
+            .unwrap();
+    }
+
+    /// The News row of the home screen.
+    fn show_latest_news(&self, headlines: Vec<Headline>) {
+        let rows: Vec<_> = headlines
+            .iter()
+            .map(|h| news_data(&h.item, &h.game))
+            .collect();
+        self.ui
+            .update(move |e| {
+                e.global::<NewsState>()
+                    .set_latest(std::rc::Rc::new(slint::VecModel::from(rows)).into());
+            })
+            .unwrap();
+    }
+
+    /// Put the suggestions in the row of the home screen, the focus staying
+    /// in it when it was there.
+    fn show_picks(&mut self, picks: Vec<Pick>) -> anyhow::Result<()> {
+        let controller = self.router.controller_for(Screen::Home)?;
+        let focused = controller.get_current_focus_id().clone();
+        controller::layout_picks(controller, picks.iter().map(|p| p.uuid.as_str()))?;
+        if focused.is_some_and(|f| f.kind() == FocusKind::Pick) {
+            let to = match picks.first() {
+                Some(first) => FocusId::pick(&first.uuid),
+                None => FocusId::button("GAMES"),
+            };
+            controller.focus(&to)?;
+        }
+        self.picks = picks;
+        // Shown with the next update.
+        self.picks_shown.clear();
+        Ok(())
+    }
+
+    /// The suggestions in each cell of the row as it's turned, when that
+    /// changed.
+    fn show_picks_turned(&mut self) {
+        let controller = self.router.controller();
+        let mut cells = vec![None; PICK_CELLS];
+        for pick in &self.picks {
+            if let Some(r) = controller.find_rect(&FocusId::pick(&pick.uuid)) {
+                if let Some(cell) = cells.get_mut(r.rect.x()) {
+                    *cell = Some(pick.uuid.clone());
+                }
+            }
+        }
+        if cells == self.picks_shown {
+            return;
+        }
+        self.picks_shown = cells.clone();
+        // None at all hides the row.
+        let shown: Vec<_> = cells
+            .iter()
+            .filter(|_| !self.picks.is_empty())
+            .map(|uuid| {
+                let pick = self.picks.iter().find(|p| Some(&p.uuid) == uuid.as_ref())?;
+                let game = self.library.lock().unwrap().get(&pick.uuid).cloned()?;
+                let cover = game
+                    .cover_art
+                    .and_then(|source| self.art_path(&game.uuid, source, models::ArtKind::Cover));
+                let reason = match &pick.reason {
+                    Reason::UnfinishedFavorite => self.i18n.tr("pick-unfinished-favorite"),
+                    Reason::LongUnplayed(Some(days)) => {
+                        let args = fluent::FluentArgs::from_iter([("days", *days)]);
+                        self.i18n.tr_args("pick-long-unplayed", Some(&args))
+                    }
+                    Reason::LongUnplayed(None) => self.i18n.tr("pick-not-played-lately"),
+                    Reason::SimilarTo(title) => {
+                        let args = fluent::FluentArgs::from_iter([("title", title.clone())]);
+                        self.i18n.tr_args("pick-similar", Some(&args))
+                    }
+                };
+                let placeholder = Placeholder::new(&game.uuid, &game.title);
+                Some((game.uuid, game.title, reason, cover, placeholder))
+            })
+            .collect();
+        self.ui
+            .update(move |e| {
+                let cells: Vec<PickData> = shown
+                    .into_iter()
+                    .map(|pick| {
+                        let Some((uuid, title, reason, cover, placeholder)) = pick else {
+                            return PickData::default();
+                        };
+                        let cover = cover.and_then(|(_, p)| slint::Image::load_from_path(&p).ok());
+                        let [r, g, b] = placeholder.color;
+                        PickData {
+                            uuid: uuid.into(),
+                            title: title.into(),
+                            reason: reason.into(),
+                            cover: cover.unwrap_or_default(),
+                            initials: placeholder.initials.into(),
+                            placeholder_color: slint::Color::from_rgb_u8(r, g, b),
+                        }
+                    })
+                    .collect();
+                e.global::<PicksState>()
+                    .set_cells(std::rc::Rc::new(slint::VecModel::from(cells)).into());
+            })
+            .unwrap();
+    }
+
+    /// Show the glyphs of the gamepad in use.
+    fn show_pad(&self, kind: PadKind) {
+        info!("using a {} layout gamepad", kind.name());
+        self.ui
+            .update(move |e| {
+                let glyphs = e.global::<PadGlyphs>();
+                glyphs.set_kind(kind.name().into());
+                glyphs.set_south(kind.glyph(Button::South).into());
+                glyphs.set_east(kind.glyph(Button::East).into());
+                glyphs.set_north(kind.glyph(Button::North).into());
+                glyphs.set_west(kind.glyph(Button::West).into());
+            })
+            .unwrap();
+    }
+
+    /// Clicking a tile focuses it, clicking it again plays it. Buttons are
+    /// pressed on the first click.
+    fn handle_pointer(&mut self, press: PointerPress, now: Instant) -> anyhow::Result<()> {
+        if self.router.controller().held().is_some() {
+            return Ok(());
+        }
+        match press {
+            PointerPress::At { layout, x, y } => {
+                if self.sync_prompt.is_some() {
+                    return Ok(());
+                }
+                let Some(focus_id) = self.router.controller().focus_at_point(&layout, x, y)? else {
+                    return Ok(());
+                };
+                let double = self
+                    .last_click
+                    .take()
+                    .is_some_and(|(id, t)| id == focus_id && now.duration_since(t) < DOUBLE_CLICK);
+                if double {
+                    return self.handle_button(Button::South);
+                }
+                self.last_click = Some((focus_id, now));
+            }
+            PointerPress::On(focus_id) => {
+                self.last_click = None;
+                self.router.controller().focus(&focus_id)?;
+                self.handle_button(Button::South)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn focus_rect(&mut self) -> Option<FocusRect> {
+        let controller = self.router.controller();
+        controller.find_rect(controller.get_current_focus_id().as_ref()?)
+    }
+
+    fn handle_action(&mut self, action: Action) -> anyhow::Result<()> {
+        match action {
+            Action::ExitGame => self.launcher.stop_all()?,
+            Action::SuspendGame => {
+                self.launcher.suspend()?;
+            }
+            Action::Screenshot => self.take_screenshot()?,
+            Action::ToggleCursor => self.toggle_cursor(),
+            Action::ToggleDebugOverlay => self.debug_overlay = !self.debug_overlay,
+            Action::QuickSettings => self.toggle_quick_settings(),
+            Action::PreviousLetter => self.jump_letter(false)?,
+            Action::NextLetter => self.jump_letter(true)?,
+            Action::GameOptions => {
+                let focus_id = self.router.controller().get_current_focus_id().clone();
+                if let Some(f) = focus_id.filter(|f| f.kind() == FocusKind::Game) {
+                    self.open_details(f.payload())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the screenshot command with where the screenshot goes.
+    fn take_screenshot(&self) -> anyhow::Result<()> {
+        let Some((program, args)) = self.screenshot_command.split_first() else {
+            anyhow::bail!("no screenshot_command in chords.yaml");
+        };
+        let mut dir = paths::data_dir().join("screenshots");
+        if let Some(ref uuid) = self.playing {
+            dir.push(uuid);
+        }
+        std::fs::create_dir_all(&dir)?;
+        let name = chrono::Local::now()
+            .format("%Y-%m-%d_%H-%M-%S.png")
+            .to_string();
+        std::process::Command::new(program)
+            .args(args)
+            .arg(dir.join(name))
+            .spawn()?;
+        Ok(())
+    }
+
+    /// What the debug overlay shows.
+    fn debug_text(&mut self) -> String {
+        let screen = self.router.current_screen();
+        let controller = self.router.controller();
+        let focus = controller
+            .get_current_focus_id()
+            .as_ref()
+            .map(|f| f.to_string());
+        let layout = controller.current_layout_id().map(|l| l.to_string());
+        let mut text = format!(
+            "screen: {:?}\nlayout: {}\nfocus: {}",
+            screen,
+            layout.unwrap_or_default(),
+            focus.unwrap_or_default()
+        );
+        if let Some(ref c) = self.cursor {
+            text += &format!("\ncursor: {} {:.2} {:.2}", c.layout, c.x, c.y);
+        }
+        text += &format!("\n{}", self.metrics.overlay_text());
+        for event in &self.recent_events {
+            text += &format!("\n> {}", event);
+        }
+        text
+    }
+
+    fn note_event(&mut self, event: &NavigationEvent) {
+        let mut line = format!("{:?}", event);
+        if let Some((i, _)) = line.char_indices().nth(60) {
+            line.truncate(i);
+            line += "…";
+        }
+        if self.recent_events.len() == DEBUG_EVENTS {
+            self.recent_events.pop_front();
+        }
+        self.recent_events.push_back(line);
+    }
+
+    /// Fill the debug overlay with the current layout's cells, or hide it.
+    fn show_debug_overlay(&mut self) {
+        let debug = self.debug_overlay.then(|| {
+            (
+                self.debug_text(),
+                self.router.controller().current_snapshot(),
+            )
+        });
+        self.ui
+            .update(move |e| {
+                let state = e.global::<DebugState>();
+                state.set_visible(debug.is_some());
+                let Some((text, snapshot)) = debug else {
+                    return;
+                };
+                state.set_text(text.into());
+                let (width, height) = snapshot.as_ref().map_or((0, 0), |g| (g.width, g.height));
+                let cells: Vec<i32> = snapshot
+                    .iter()
+                    .flat_map(|g| &g.cells)
+                    .map(|c| match c {
+                        controller::CellKind::Empty => 0,
+                        controller::CellKind::Element => 1,
+                        controller::CellKind::Sublayout => 2,
+                    })
+                    .collect();
+                state.set_grid_width(width as i32);
+                state.set_grid_height(height as i32);
+                state.set_cells(std::rc::Rc::new(slint::VecModel::from(cells)).into());
+                let focus = snapshot.and_then(|g| g.focus);
+                let (x, y) = focus.map_or((-1, -1), |(x, y)| (x as i32, y as i32));
+                state.set_focus_x(x);
+                state.set_focus_y(y);
+            })
+            .unwrap();
+    }
+
+    /// Turn the cursor on over the focused element, or off.
+    fn toggle_cursor(&mut self) {
+        self.cursor = match self.cursor.take() {
+            Some(_) => None,
+            None => self.focus_rect().map(|r| Cursor::on(&r)),
+        };
+    }
+
+    /// Move the cursor with the stick, the focus follows it onto elements.
+    /// False when it didn't move.
+    fn move_cursor(&mut self, stick: (f32, f32), dt: Duration) -> anyhow::Result<bool> {
+        let Some(ref mut cursor) = self.cursor else {
+            return Ok(false);
+        };
+        let controller = self.router.controller();
+        if !cursor.nudge(stick, dt, controller.layout_size(&cursor.layout)?) {
+            return Ok(false);
+        }
+        controller.focus_at_point(&cursor.layout, cursor.x, cursor.y)?;
+        Ok(true)
+    }
+
+    /// Bring the cursor back to the focus after it moved some other way.
+    fn follow_cursor(&mut self) {
+        if self.cursor.is_none() {
+            return;
+        }
+        match self.focus_rect() {
+            Some(rect) => self.cursor.as_mut().unwrap().follow(&rect),
+            None => self.cursor = None,
+        }
+    }
+
+    fn handle_buttons(&mut self, buttons: &[Button]) {
+        for &b in buttons {
+            if let Err(e) = self.handle_button(b) {
+                warn!("failed to handle button {:?}: {:?}", b, e);
+            }
+        }
+    }
+
+    fn handle_button(&mut self, b: Button) -> anyhow::Result<()> {
+        if let Some(ref prompt) = self.sync_prompt {
+            if prompt.busy {
+                return Ok(());
+            }
+            // Only the close button is shown without a conflict.
+            if !prompt.conflict && !matches!(b, Button::South | Button::East) {
+                return Ok(());
+            }
+        }
+
+        if self.router.controller().held().is_some() {
+            return self.handle_held_button(b);
+        }
+
+        let focus_id = self.router.controller().get_current_focus_id().clone();
+        let game_uuid = focus_id
+            .as_ref()
+            .filter(|f| f.kind() == FocusKind::Game)
+            .map(|f| f.payload());
+        let direction = match b {
+            Button::DPadUp => Some(controller::Direction::Up),
+            Button::DPadDown => Some(controller::Direction::Down),
+            Button::DPadLeft => Some(controller::Direction::Left),
+            Button::DPadRight => Some(controller::Direction::Right),
+            _ => None,
+        };
+        if let Some(d) = direction {
+            self.router
+                .controller()
+                .navigate(controller::NavigationDirective::Direction(d))?;
+            if let Some(ref mut recorder) = self.recorder {
+                let step = replay::Step {
+                    screen: self.router.current_screen(),
+                    from: focus_id,
+                    direction: d,
+                    to: self.router.controller().get_current_focus_id().clone(),
+                };
+                if let Err(e) = recorder.record(&step) {
+                    warn!("failed to record {:?}: {:?}", step, e);
+                }
+            }
+            return Ok(());
+        }
+
+        match b {
+            Button::South => {
+                if let Some(ref focus_id) = focus_id {
+                    self.activate(focus_id)?;
+                }
+            }
+            // What can be done with the selected games, or the focused one.
+            Button::Select if self.can_undo_delete() => self.undo_delete()?,
+            Button::West | Button::Start if self.has_selection() && game_uuid.is_some() => {
+                self.open_bulk_menu()?
+            }
+            Button::West | Button::Start => {
+                if let Some(uuid) = game_uuid {
+                    self.open_game_menu(uuid)?;
+                }
+            }
+            Button::North => {
+                if let Some(uuid) = game_uuid {
+                    self.toggle_selected(uuid);
+                }
+            }
+            Button::East if self.sync_prompt.is_some() => self.close_sync_prompt(),
+            Button::East if self.router.current_screen() == Screen::Confirm => {
+                self.answer_confirm(false)
+            }
+            Button::East
+                if self.router.current_screen() == Screen::Home && self.has_selection() =>
+            {
+                self.clear_selection()
+            }
+            Button::East => {
+                self.router.pop();
+            }
+            Button::LeftTrigger => self.cycle_tab(-1)?,
+            Button::RightTrigger => self.cycle_tab(1)?,
+            _ => {}
+        }
+        Ok(())
     }
 
-    let mut active_gamepad = None;
+    /// Pick up the focused game to move it around the home screen.
+    fn pick_up(&mut self) -> anyhow::Result<()> {
+        if self.sync_prompt.is_some() || self.router.current_screen() != Screen::Home {
+            return Ok(());
+        }
+        let controller = self.router.controller();
+        match controller.get_current_focus_id() {
+            Some(f)
+                if f.kind() == FocusKind::Game
+                    && !self.importing.iter().any(|u| u == f.payload()) => {}
+            _ => return Ok(()),
+        }
+        let focus_id = controller.pick_up()?;
+        self.show_held(Some(focus_id));
+        Ok(())
+    }
 
-    loop {
-        // Examine new events
-        while let Some(Event { id, event, time }) = gilrs.next_event() {
-            println!("{:?} New event from {}: {:?}", time, id, event);
-            active_gamepad = Some(id);
-            match event {
-                EventType::ButtonPressed(b, _) => tx.send(b).unwrap(),
-                _ => (),
+    /// While a game is held, the D-pad moves it, A drops it and B puts it
+    /// back.
+    fn handle_held_button(&mut self, b: Button) -> anyhow::Result<()> {
+        let controller = self.router.controller();
+        let reorder = match b {
+            Button::DPadUp => controller.move_held(controller::Direction::Up)?,
+            Button::DPadDown => controller.move_held(controller::Direction::Down)?,
+            Button::DPadLeft => controller.move_held(controller::Direction::Left)?,
+            Button::DPadRight => controller.move_held(controller::Direction::Right)?,
+            Button::South => return self.drop_held(),
+            Button::East => return self.cancel_held(),
+            _ => None,
+        };
+        if let Some(r) = reorder {
+            self.move_tile(r.from, r.to);
+        }
+        Ok(())
+    }
+
+    /// Save the new position of the held game.
+    fn drop_held(&mut self) -> anyhow::Result<()> {
+        let reorder = self.router.controller().drop_held()?;
+        self.show_held(None);
+        match reorder {
+            Some(r) if r.from != r.to => {
+                let mut library = self.library.lock().unwrap();
+                library.move_game(r.focus_id.payload(), r.to)?;
+                library.save()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn cancel_held(&mut self) -> anyhow::Result<()> {
+        let reorder = self.router.controller().cancel_held()?;
+        self.show_held(None);
+        if let Some(r) = reorder {
+            self.move_tile(r.from, r.to);
+        }
+        Ok(())
+    }
+
+    fn show_held(&self, focus_id: Option<FocusId>) {
+        let held = focus_id.map(|f| f.to_string()).unwrap_or_default();
+        self.ui
+            .update(move |e| {
+                e.global::<HomeWindowFocus>().set_held_id(held.into());
+            })
+            .unwrap();
+    }
+
+    /// Move a game tile on the home screen, the same way as in its layout.
+    fn move_tile(&self, from: usize, to: usize) {
+        self.ui
+            .update(move |e| tiles::apply(e, LibraryChange::Moved { from, to }))
+            .unwrap();
+    }
+
+    /// Reflect metadata edits on the tile and the details screen.
+    fn game_changed(&self, uuid: &str) {
+        let Some(game) = self.library.lock().unwrap().get(uuid).cloned() else {
+            return;
+        };
+        let (title, desc) = self.game_text(&game);
+        let uuid = uuid.to_owned();
+        self.ui
+            .update(move |e| {
+                tiles::apply(e, LibraryChange::Changed(uuid.clone()));
+                let details = e.global::<GameDetailsState>();
+                if details.get_uuid().as_str() == uuid {
+                    details.set_title(title.into());
+                    details.set_description(desc.into());
+                    details.set_notes(game.notes.unwrap_or_default().into());
+                }
+            })
+            .unwrap();
+    }
+
+    fn handle_remote(&mut self, command: RemoteCommand) -> anyhow::Result<()> {
+        match command {
+            RemoteCommand::Button(b) => return self.handle_button(b),
+            RemoteCommand::GameChanged(uuid) => self.game_changed(&uuid),
+            RemoteCommand::Pair(request) => self.confirm_pairing(request),
+            // Leave the sync dialog to the buttons.
+            _ if self.sync_prompt.is_some() => {}
+            RemoteCommand::Focus(focus_id) => {
+                self.cancel_held()?;
+                while self.router.pop() {}
+                self.router.controller().focus(&focus_id)?;
+            }
+            RemoteCommand::Launch(uuid) => self.play(&uuid)?,
+        }
+        Ok(())
+    }
+
+    fn handle_instance_message(&mut self, message: instance::Message) -> anyhow::Result<()> {
+        let _ = self.ui.update(|ui| {
+            if let Err(e) = ui.show() {
+                warn!("failed to show the window: {:?}", e);
+            }
+        });
+        match message {
+            instance::Message::Activate => Ok(()),
+            // Leave the sync dialog to the buttons.
+            _ if self.sync_prompt.is_some() => Ok(()),
+            instance::Message::Open { uuid } => {
+                self.cancel_held()?;
+                while self.router.pop() {}
+                self.router.controller().focus(&FocusId::game(&uuid))?;
+                self.open_details(&uuid)
+            }
+            instance::Message::Launch { uuid } => self.handle_remote(RemoteCommand::Launch(uuid)),
+        }
+    }
+
+    fn handle_launch_event(&mut self, event: LaunchEvent) {
+        match event {
+            LaunchEvent::WaitingForSync { uuid } => self.show_sync_prompt(
+                uuid,
+                HookStage::PreLaunch,
+                "Waiting for the last sync to finish.".to_owned(),
+                false,
+            ),
+            LaunchEvent::Syncing { uuid, stage } => {
+                let message = match stage {
+                    HookStage::PreLaunch => "Syncing saves before launching.",
+                    HookStage::PostExit => "Syncing saves.",
+                };
+                self.show_sync_prompt(uuid, stage, message.to_owned(), false)
+            }
+            LaunchEvent::Conflict {
+                uuid,
+                stage,
+                detail,
+            } => self.show_sync_prompt(
+                uuid,
+                stage,
+                format!("Both copies of the saves changed.\n{}", detail),
+                true,
+            ),
+            LaunchEvent::Failed { uuid, error } => {
+                self.diagnostics.record_launch(true);
+                self.presence.clear();
+                self.bus.publish(bus::Event::Idle);
+                self.show_sync_prompt(uuid, HookStage::PreLaunch, error, false);
+                if let Some(ref mut prompt) = self.sync_prompt {
+                    prompt.busy = false;
+                }
+                self.update_sync_prompt_ui();
+            }
+            LaunchEvent::Running { uuid } => {
+                self.diagnostics.record_launch(false);
+                self.playing = Some(uuid.clone());
+                let session = SessionState {
+                    last_game: Some(uuid.clone()),
+                };
+                if let Err(e) = session.save(&SessionState::default_path()) {
+                    warn!("failed to save the session: {:?}", e);
+                }
+                let mut plays = self.plays.lock().unwrap();
+                plays.played(&uuid, chrono::Utc::now());
+                if let Err(e) = plays.save(&PlayLog::default_path()) {
+                    warn!("failed to save when {} was played: {:?}", uuid, e);
+                }
+                drop(plays);
+                self.session_started = Some((uuid.clone(), chrono::Utc::now()));
+                if let Some(game) = self.library.lock().unwrap().get(&uuid) {
+                    self.presence.playing(&game.title);
+                    self.saves.game_started(game.clone());
+                    self.bus.publish(bus::Event::Playing {
+                        uuid: uuid.clone(),
+                        title: game.title.clone(),
+                    });
+                }
+                self.close_sync_prompt_of(&uuid);
+            }
+            LaunchEvent::Suspended { uuid } => {
+                self.presence.clear();
+                self.bus.publish(bus::Event::Idle);
+                self.playing = None;
+                self.end_session(&uuid);
+                if let Some(game) = self.library.lock().unwrap().get(&uuid) {
+                    let args = fluent::FluentArgs::from_iter([("title", game.title.clone())]);
+                    self.show_toast(self.i18n.tr_args("game-suspended", Some(&args)));
+                }
+            }
+            LaunchEvent::Resumed { uuid } => {
+                self.playing = Some(uuid.clone());
+                self.session_started = Some((uuid.clone(), chrono::Utc::now()));
+                if let Some(game) = self.library.lock().unwrap().get(&uuid) {
+                    self.presence.playing(&game.title);
+                    self.bus.publish(bus::Event::Playing {
+                        uuid: uuid.clone(),
+                        title: game.title.clone(),
+                    });
+                }
+            }
+            LaunchEvent::Exited { uuid } => {
+                info!("{} exited", uuid);
+                self.playing = None;
+                self.end_session(&uuid);
+                if self.router.current_screen() == Screen::Suspended {
+                    self.show_suspended(None);
+                }
+                self.presence.clear();
+                self.bus.publish(bus::Event::Idle);
+                if let Some(game) = self.library.lock().unwrap().get(&uuid) {
+                    self.saves.game_exited(game.clone());
+                }
+            }
+            LaunchEvent::Done { uuid } => self.close_sync_prompt_of(&uuid),
+        }
+    }
+
+    /// Log the session of the game if it was running, and add it to the
+    /// game's playtime.
+    fn end_session(&mut self, uuid: &str) {
+        let Some((_, start)) = self.session_started.take_if(|(playing, _)| playing == uuid) else {
+            return;
+        };
+        let session = Session {
+            uuid: uuid.to_owned(),
+            start,
+            end: chrono::Utc::now(),
+        };
+        let duration = session.duration();
+        self.sessions.record(session);
+        if let Err(e) = self.sessions.save(&SessionLog::default_path()) {
+            warn!("failed to save the session of {}: {:?}", uuid, e);
+        }
+        let mut library = self.library.lock().unwrap();
+        if let Some(game) = library.get_mut(uuid) {
+            game.playtime = Some(game.playtime.unwrap_or_else(chrono::Duration::zero) + duration);
+            if let Err(e) = library.save() {
+                warn!("failed to save the playtime of {}: {:?}", uuid, e);
+            }
+        }
+    }
+
+    /// The stats screen: the playtime of the last weeks or months charted,
+    /// in all, by genre and by game, and the days in a row played.
+    fn show_stats(&self) {
+        use chrono::Datelike;
+        let t = &self.i18n;
+        let now = chrono::Local::now();
+        let totals = stats::totals(&self.sessions, self.stats_period, STATS_PERIODS, now);
+        let games: Vec<models::GameMetadata> =
+            self.library.lock().unwrap().visible().cloned().collect();
+        let dashboard = stats::dashboard(&self.sessions, &games, now.date_naive(), STATS_TOP);
+        let sessions = |count: usize| {
+            let args = fluent::FluentArgs::from_iter([("count", count as i64)]);
+            t.tr_args("stat-sessions", Some(&args))
+        };
+        let rows = |shares: &[stats::Share]| -> Vec<StatData> {
+            let longest = shares
+                .first()
+                .map(|s| s.playtime.num_seconds())
+                .unwrap_or(0);
+            shares
+                .iter()
+                .map(|share| StatData {
+                    label: share.name.clone().into(),
+                    playtime: t.format_duration(&share.playtime).into(),
+                    sessions: sessions(share.sessions).into(),
+                    share: share.playtime.num_seconds() as f32 / longest.max(1) as f32,
+                })
+                .collect()
+        };
+        let (genres, games) = (rows(&dashboard.genres), rows(&dashboard.games));
+        let label = |start: chrono::NaiveDate| match self.stats_period {
+            Period::Week => t.format_date(&start.and_time(chrono::NaiveTime::MIN).and_utc()),
+            Period::Month => {
+                let month = t.tr(&format!("month-{}", start.month()));
+                format!("{} {}", month, start.year())
+            }
+        };
+        let chart_from = totals.first().map(|t| label(t.start)).unwrap_or_default();
+        let chart_to = totals.last().map(|t| label(t.start)).unwrap_or_default();
+        let chart = stats::chart_path(
+            &totals
+                .iter()
+                .map(|t| t.playtime.num_minutes() as f32)
+                .collect::<Vec<_>>(),
+        );
+        let playtime = fluent::FluentArgs::from_iter([
+            ("duration", t.format_duration(&dashboard.playtime)),
+            ("sessions", sessions(dashboard.sessions)),
+        ]);
+        let playtime = t.tr_args("stats-playtime", Some(&playtime));
+        let streak = fluent::FluentArgs::from_iter([
+            ("days", dashboard.streak as i64),
+            ("longest", dashboard.longest_streak as i64),
+        ]);
+        let streak = t.tr_args("stats-streak", Some(&streak));
+        let title = t.tr(match self.stats_period {
+            Period::Week => "weekly",
+            Period::Month => "monthly",
+        });
+        self.ui
+            .update(move |e| {
+                let state = e.global::<StatsState>();
+                state.set_title(title.into());
+                state.set_playtime(playtime.into());
+                state.set_streak(streak.into());
+                state.set_chart(chart.into());
+                state.set_chart_from(chart_from.into());
+                state.set_chart_to(chart_to.into());
+                state.set_genres(std::rc::Rc::new(slint::VecModel::from(genres)).into());
+                state.set_games(std::rc::Rc::new(slint::VecModel::from(games)).into());
+            })
+            .unwrap();
+    }
+
+    fn show_sync_prompt(
+        &mut self,
+        uuid: String,
+        stage: HookStage,
+        message: String,
+        conflict: bool,
+    ) {
+        let title = self
+            .library
+            .lock()
+            .unwrap()
+            .get(&uuid)
+            .map(|g| g.title.clone())
+            .unwrap_or_else(|| uuid.clone());
+        self.sync_prompt = Some(SyncPrompt {
+            uuid,
+            stage,
+            conflict,
+            busy: !conflict,
+        });
+        if self.router.current_screen() != Screen::SyncDialog {
+            self.router.push(Screen::SyncDialog);
+            if let Err(e) = self.router.controller().reset() {
+                warn!("failed to reset the sync dialog focus: {:?}", e);
+            }
+        }
+        self.ui
+            .update(move |e| {
+                let state = e.global::<SyncDialogState>();
+                state.set_title(title.into());
+                state.set_message(message.into());
+            })
+            .unwrap();
+        self.update_sync_prompt_ui();
+    }
+
+    fn close_sync_prompt(&mut self) {
+        if let Some(prompt) = self.sync_prompt.take() {
+            if prompt.conflict {
+                warn!(
+                    "left the {} sync conflict of {} unresolved",
+                    prompt.stage.name(),
+                    prompt.uuid
+                );
+            }
+            if self.router.current_screen() == Screen::SyncDialog {
+                self.router.pop();
+            }
+        }
+    }
+
+    /// Another game might have run into a conflict meanwhile, leave that one be.
+    fn close_sync_prompt_of(&mut self, uuid: &str) {
+        if self.sync_prompt.as_ref().is_some_and(|p| p.uuid == uuid) {
+            self.close_sync_prompt();
+        }
+    }
+
+    fn resolve_conflict(&mut self, keep: ConflictSide) -> anyhow::Result<()> {
+        let Some(ref mut prompt) = self.sync_prompt else {
+            return Ok(());
+        };
+        let game = self.library.lock().unwrap().get(&prompt.uuid).cloned();
+        let Some(game) = game else {
+            anyhow::bail!("no game {} in the library", prompt.uuid);
+        };
+        self.launcher.resolve_conflict(game, prompt.stage, keep)?;
+        prompt.conflict = false;
+        prompt.busy = true;
+        self.update_sync_prompt_ui();
+        Ok(())
+    }
+
+    fn update_sync_prompt_ui(&self) {
+        let (busy, conflict) = self
+            .sync_prompt
+            .as_ref()
+            .map_or((false, false), |p| (p.busy, p.conflict));
+        self.ui
+            .update(move |e| {
+                let state = e.global::<SyncDialogState>();
+                state.set_busy(busy);
+                state.set_conflict(conflict);
+            })
+            .unwrap();
+    }
+
+    /// Show the current screen and focus.
+    fn update_ui(&mut self) {
+        let screen = self.router.current_screen();
+        self.show_debug_overlay();
+        let cursor = self.cursor.clone();
+        self.ui
+            .update(move |e| {
+                let state = e.global::<CursorState>();
+                state.set_active(cursor.is_some());
+                if let Some(c) = cursor {
+                    state.set_layout(c.layout.to_string().into());
+                    state.set_x(c.x);
+                    state.set_y(c.y);
+                }
+            })
+            .unwrap();
+        // The screen underneath a modal one stays scrolled as it was.
+        let transition = self.router.controller().last_transition().cloned();
+        if let Some(t) = transition.filter(|_| !screen.is_modal()) {
+            self.ui.set_scroll(t.from.as_ref(), &t.to).unwrap();
+        }
+        if screen == Screen::Home {
+            let hint = self
+                .focus_rect()
+                .and_then(|to| self.viewport.follow(&to, Instant::now()));
+            if let Some(hint) = hint {
+                self.show_scroll(hint);
+            }
+        }
+        if let Some(f_id) = self.router.controller().get_current_focus_id().clone() {
+            self.ui.set_focus(screen, &f_id).unwrap();
+            self.publish_focus(screen, &f_id);
+            let row = self.router.controller().current_payload::<DetailsRow>();
+            if let Some(DetailsRow(row)) = row {
+                let row = row as i32;
+                self.ui
+                    .update(move |e| e.global::<GameDetailsState>().set_focused_row(row))
+                    .unwrap();
+            }
+        }
+        if screen == Screen::Home {
+            self.show_picks_turned();
+        }
+        self.follow_hero(screen);
+    }
+
+    fn publish_focus(&self, screen: Screen, focus: &FocusId) {
+        let game = matches!(focus.kind(), FocusKind::Game | FocusKind::Pick)
+            .then(|| focus.payload().to_owned());
+        let title = game
+            .as_ref()
+            .and_then(|uuid| Some(self.library.lock().unwrap().get(uuid)?.title.clone()));
+        self.bus.publish(bus::Event::Focus {
+            screen: screen.name().to_owned(),
+            id: focus.to_string(),
+            game,
+            title,
+        });
+    }
+
+    /// Show the background of the game focused on the home screen, played
+    /// when it's animated and that's allowed.
+    fn follow_hero(&mut self, screen: Screen) {
+        let focus = self.router.controller().get_current_focus_id().clone();
+        let uuid = focus
+            .filter(|f| {
+                screen == Screen::Home && matches!(f.kind(), FocusKind::Game | FocusKind::Pick)
+            })
+            .map(|f| f.payload().to_owned());
+        if uuid == self.hero {
+            return;
+        }
+        self.hero_player = None;
+        self.hero = uuid.clone();
+        self.follow_theme();
+        let art = uuid.as_ref().and_then(|uuid| {
+            let bg_art = self.library.lock().unwrap().get(uuid)?.bg_art.clone();
+            self.art_path(uuid, bg_art?, models::ArtKind::Background)
+        });
+        // Still art, and the first frame of an APNG while it's decoded.
+        let still = art.as_ref().map(|(_, path)| path.clone());
+        self.ui
+            .update(move |e| {
+                let image = still.and_then(|p| slint::Image::load_from_path(&p).ok());
+                e.global::<HeroState>()
+                    .set_background(image.unwrap_or_default());
+            })
+            .unwrap();
+        let (Some(uuid), Some((name, path))) = (uuid, art) else {
+            return;
+        };
+        let animation = Animation::of(&path, &name).filter(|_| self.playback_config.plays());
+        if let Some(animation) = animation {
+            let events = self.events.clone();
+            let played = Player::start(animation, path, &self.playback_config, move |frame| {
+                let uuid = uuid.clone();
+                events
+                    .send(NavigationEvent::HeroFrame { uuid, frame })
+                    .is_ok()
+            });
+            match played {
+                Ok(player) => self.hero_player = Some(player),
+                Err(e) => warn!("failed to play the background of {}: {:?}", name, e),
+            }
+        }
+    }
+
+    /// Where the art is, with what it's named after, None if it's not
+    /// downloaded yet.
+    fn art_path(
+        &self,
+        uuid: &str,
+        source: models::ImageSource,
+        kind: models::ArtKind,
+    ) -> Option<(String, PathBuf)> {
+        match source {
+            models::ImageSource::FilePath(path) => Some((path.clone(), PathBuf::from(path))),
+            models::ImageSource::Url(url) => {
+                let art = self.art.as_ref()?;
+                let path = match kind {
+                    models::ArtKind::Cover => art.fetch(uuid, &url),
+                    models::ArtKind::Background => art.fetch_background(uuid, &url),
+                }?;
+                Some((url, path))
             }
+            // Only in backups, inlined on restore.
+            models::ImageSource::Base64(_) => None,
+        }
+    }
+
+    /// Take on the colors of the focused game's cover, worked out in the
+    /// background the first time.
+    fn follow_theme(&mut self) {
+        if let Some(job) = self.palette_job.take() {
+            job.cancel();
+        }
+        let Some(uuid) = self.hero.clone() else {
+            self.show_theme(None);
+            return;
+        };
+        if let Some(&palette) = self.palettes.get(&uuid) {
+            self.show_theme(palette);
+            return;
+        }
+        let cover = self
+            .library
+            .lock()
+            .unwrap()
+            .get(&uuid)
+            .and_then(|g| g.cover_art.clone());
+        // Until it's downloaded.
+        let path = cover.and_then(|cover| self.art_path(&uuid, cover, models::ArtKind::Cover));
+        let Some((_, path)) = path else {
+            self.show_theme(None);
+            return;
+        };
+        let events = self.events.clone();
+        let job = runtime::spawn_job(
+            CHECK_TIMEOUT,
+            move || palette::of_file(&path),
+            move |palette| {
+                let palette = palette.map_err(|e| format!("{:#}", e));
+                let _ = events.send(NavigationEvent::Palette { uuid, palette });
+            },
+        );
+        self.palette_job = Some(job);
+    }
+
+    fn show_palette(&mut self, uuid: String, palette: Result<Palette, String>) {
+        let palette = palette
+            .map_err(|e| warn!("failed to read the colors of {}: {}", uuid, e))
+            .ok();
+        if self.hero.as_ref() == Some(&uuid) {
+            self.show_theme(palette);
+        }
+        self.palettes.insert(uuid, palette);
+    }
+
+    fn show_theme(&self, palette: Option<Palette>) {
+        self.ui
+            .update(move |e| {
+                let theme = e.global::<ThemeState>();
+                theme.set_active(palette.is_some());
+                let Some(palette) = palette else {
+                    return;
+                };
+                let color = |[r, g, b]: palette::Rgb| slint::Color::from_rgb_u8(r, g, b);
+                theme.set_dominant(color(palette.dominant));
+                let white = slint::Color::from_rgb_u8(255, 255, 255);
+                theme.set_accent(palette.accent.map_or(white, color));
+                theme.set_shade(color(palette.shade));
+            })
+            .unwrap();
+    }
+
+    fn show_hero_frame(&self, uuid: String, frame: Frame) {
+        if self.hero.as_ref() != Some(&uuid) {
+            return;
         }
+        self.ui
+            .update(move |e| {
+                let buffer = slint::SharedPixelBuffer::<slint::Rgba8Pixel>::clone_from_slice(
+                    &frame.rgba,
+                    frame.width,
+                    frame.height,
+                );
+                e.global::<HeroState>()
+                    .set_background(slint::Image::from_rgba8(buffer));
+            })
+            .unwrap();
     }
 }
 
-fn navigation_controller_thread(handle: slint::Weak<HomeWindow>, rx: mpsc::Receiver<Button>) {
-    let mut controller = controller::create_home_window_controller().unwrap();
+async fn navigation_task(
+    nav: &mut Navigator,
+    rx: &mut UnboundedReceiver<NavigationEvent>,
+    limiter: &mut InputLimiter,
+) {
     // TODO: Refactor grid navigation for games.
-    controller.with_sublayout("Home@Games", |l| {
-        l.insert_to_growable_grid("GAME@aaaa").unwrap();
-        l.insert_to_growable_grid("GAME@bbbb").unwrap();
-    }).unwrap();
+    nav.layout_games().unwrap();
     loop {
-        match rx.recv() {
-            Ok(b) => {
-                match b {
-                    Button::DPadUp => controller.navigate(
-                        controller::NavigationDirective::Direction(controller::Direction::Up),
-                    ),
-                    Button::DPadDown => controller.navigate(
-                        controller::NavigationDirective::Direction(controller::Direction::Down),
-                    ),
-                    Button::DPadLeft => controller.navigate(
-                        controller::NavigationDirective::Direction(controller::Direction::Left),
-                    ),
-                    Button::DPadRight => controller.navigate(
-                        controller::NavigationDirective::Direction(controller::Direction::Right),
-                    ),
-                    _ => Ok(controller::NavigationResult::NoNextItem),
+        let layout = nav.router.controller().current_layout_id();
+        // Wait for the next event, or for a held back move to be due.
+        let event = tokio::select! {
+            event = rx.recv() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            _ = runtime::until(limiter.deadline(layout.as_ref())) => {
+                if let Some(b) = limiter.due(layout.as_ref(), Instant::now()) {
+                    nav.handle_buttons(&[b]);
+                    nav.update_ui();
                 }
-                .unwrap();
-                match controller.get_current_focus_id() {
-                    Some(ref f_id) => {
-                        let f_id_clone = f_id.clone();
-                        handle
-                            .upgrade_in_event_loop(move |e| {
-                                e.global::<HomeWindowFocus>()
-                                    .set_focused_id(f_id_clone.into());
-                            })
-                            .unwrap();
-                    }
-                    None => {}
+                continue;
+            }
+        };
+        if !matches!(
+            event,
+            NavigationEvent::Stick { .. } | NavigationEvent::HeroFrame { .. }
+        ) {
+            nav.note_event(&event);
+        }
+        let started = Instant::now();
+        let pressed = matches!(event, NavigationEvent::Button(_));
+        if let Err(e) = nav.sync_downloads_layout() {
+            warn!("failed to sync the downloads layout: {:?}", e);
+        }
+        match event {
+            // Any press cancels resuming the last game, and does nothing else.
+            NavigationEvent::Button(_) | NavigationEvent::Pointer(_) if nav.resuming.is_some() => {
+                nav.cancel_resume()
+            }
+            NavigationEvent::Button(b) => {
+                nav.handle_buttons(&limiter.press(layout.as_ref(), b, Instant::now()))
+            }
+            NavigationEvent::Held(Button::South) => {
+                if let Err(e) = nav.pick_up() {
+                    warn!("failed to pick up: {:?}", e);
+                }
+            }
+            NavigationEvent::Released(Button::South) => {
+                if let Err(e) = nav.drop_held() {
+                    warn!("failed to drop: {:?}", e);
+                }
+            }
+            NavigationEvent::Held(_) | NavigationEvent::Released(_) => {}
+            NavigationEvent::Launch(event) => nav.handle_launch_event(event),
+            NavigationEvent::Unawaited => nav.drop_unawaited_dialogs(),
+            NavigationEvent::Remote(command) => {
+                if let Err(e) = nav.handle_remote(command.clone()) {
+                    warn!("failed to handle remote command {:?}: {:?}", command, e);
+                }
+            }
+            NavigationEvent::Instance(message) => {
+                if let Err(e) = nav.handle_instance_message(message.clone()) {
+                    warn!("failed to handle instance message {:?}: {:?}", message, e);
+                }
+            }
+            NavigationEvent::Pad(kind) => nav.show_pad(kind),
+            NavigationEvent::Stick { x, y, dt } => {
+                match nav.move_cursor((x, y), dt) {
+                    Ok(true) => nav.update_ui(),
+                    Ok(false) => {}
+                    Err(e) => warn!("failed to move the cursor: {:?}", e),
+                }
+                // Not snapped back to the focus, it's free over empty cells.
+                continue;
+            }
+            NavigationEvent::Action(action) => {
+                if let Err(e) = nav.handle_action(action) {
+                    warn!("failed to {:?}: {:?}", action, e);
+                }
+            }
+            NavigationEvent::LayoutChanged(screen) => {
+                if let Err(e) = nav.reload_layout(screen) {
+                    warn!("failed to reload the {:?} layout: {:?}", screen, e);
+                }
+            }
+            NavigationEvent::Pointer(press) => {
+                if let Err(e) = nav.handle_pointer(press.clone(), Instant::now()) {
+                    warn!("failed to handle the pointer {:?}: {:?}", press, e);
+                }
+            }
+            NavigationEvent::Achievements { uuid, progress } => {
+                nav.show_achievements(uuid, progress)
+            }
+            NavigationEvent::News { uuid, news } => nav.show_news(uuid, news),
+            NavigationEvent::LatestNews(headlines) => nav.show_latest_news(headlines),
+            NavigationEvent::Bulk(outcome) => {
+                if let Err(e) = nav.show_bulk_outcome(outcome) {
+                    warn!("failed to show the edited games: {:?}", e);
+                }
+            }
+            NavigationEvent::Picks(picks) => {
+                if let Err(e) = nav.show_picks(picks) {
+                    warn!("failed to show the suggestions: {:?}", e);
+                }
+            }
+            NavigationEvent::ArtDownloaded(key) => {
+                if key.starts_with(MATCH_ART) && nav.router.current_screen() == Screen::Matches {
+                    nav.show_matches();
+                }
+            }
+            NavigationEvent::Matched { uuid, details } => {
+                if let Err(e) = nav.apply_match(uuid, details) {
+                    warn!("failed to fill in the picked match: {:?}", e);
+                }
+            }
+            NavigationEvent::Updates(releases) => nav.show_updates(releases),
+            NavigationEvent::Restarted(restart) => nav.show_restart(restart),
+            NavigationEvent::EpicAccount(account) => nav.show_epic_account(account),
+            NavigationEvent::Saves { uuid, status } => nav.show_saves_result(uuid, status),
+            NavigationEvent::Storage(usage) => nav.show_storage_result(usage),
+            NavigationEvent::SettingsStatus(status) => nav.show_settings_status(status),
+            NavigationEvent::Roots(RootsEvent::Importing(uuids)) => {
+                if let Err(e) = nav.show_importing(uuids) {
+                    warn!("failed to show the games being imported: {:?}", e);
+                }
+            }
+            NavigationEvent::Roots(RootsEvent::Imported(uuid)) => nav.show_imported(uuid),
+            NavigationEvent::Roots(RootsEvent::Changed) => {
+                if let Err(e) = nav.reload_games() {
+                    warn!("failed to show the games after a drive change: {:?}", e);
                 }
             }
-            Err(_) => {} // TODO: Handle error.
+            NavigationEvent::Mqtt(command) => {
+                if let Err(e) = nav.handle_mqtt(command.clone()) {
+                    warn!("failed to handle MQTT command {:?}: {:?}", command, e);
+                }
+            }
+            NavigationEvent::Synced(uuids) => {
+                info!("{} games changed on other devices", uuids.len());
+                if let Err(e) = nav.reload_games() {
+                    warn!("failed to show the games synced: {:?}", e);
+                }
+            }
+            NavigationEvent::ResumeTick(left) => nav.resume_tick(left),
+            NavigationEvent::QuietHours(quiet) => nav.quiet_hours_changed(quiet),
+            NavigationEvent::GameMenu { uuid, item } => {
+                if let Err(e) = nav.run_menu_item(&uuid, item) {
+                    warn!("failed to {:?} {}: {:?}", item, uuid, e);
+                }
+            }
+            NavigationEvent::Palette { uuid, palette } => nav.show_palette(uuid, palette),
+            NavigationEvent::HeroFrame { uuid, frame } => {
+                nav.show_hero_frame(uuid, frame);
+                // Nothing else changed.
+                continue;
+            }
+        }
+        nav.follow_cursor();
+        nav.update_ui();
+        if pressed {
+            nav.diagnostics.record_navigation(started.elapsed());
+            // Run on the UI thread with the frame showing the new focus.
+            let metrics = nav.metrics.clone();
+            nav.ui
+                .update(move |_| metrics.record_navigation(started.elapsed()))
+                .unwrap();
         }
     }
 }
 
-fn main() -> Result<(), slint::PlatformError> {
-    let ui = HomeWindow::new()?;
+/// Reflect transfers on the game tiles and in the tasks screen. Downloaded
+/// art is told to the navigator too, for what isn't a tile.
+fn transfer_event_thread(
+    handle: slint::Weak<HomeWindow>,
+    rx: mpsc::Receiver<TransferInfo>,
+    events: UnboundedSender<NavigationEvent>,
+) {
+    let mut bandwidths: HashMap<TransferId, u64> = HashMap::new();
+    while let Ok(t) = rx.recv() {
+        if t.kind == TransferKind::Art && t.state == TransferState::Completed {
+            let _ = events.send(NavigationEvent::ArtDownloaded(t.uuid.clone()));
+        }
+        if t.state.is_terminal() {
+            bandwidths.remove(&t.id);
+        } else {
+            bandwidths.insert(t.id, t.bandwidth);
+        }
+        let total_bandwidth = bandwidths.values().sum();
+        handle
+            .upgrade_in_event_loop(move |e| {
+                let focus = e.global::<HomeWindowFocus>();
+                let unfinished = !t.state.is_terminal();
+
+                let games = focus.get_games();
+                let tiles = games.as_any().downcast_ref::<GameTiles>();
+                let title = t
+                    .title
+                    .clone()
+                    .or_else(|| tiles.and_then(|tiles| tiles.title(&t.uuid)))
+                    .unwrap_or_else(|| t.uuid.clone());
+                if let Some(tiles) = tiles {
+                    match t.kind {
+                        TransferKind::Install | TransferKind::Update => {
+                            let progress = Some(t.progress).filter(|_| unfinished);
+                            tiles.apply(LibraryChange::Progress(t.uuid.clone(), progress));
+                        }
+                        // The cover is on disk now.
+                        TransferKind::Art if t.state == TransferState::Completed => {
+                            tiles.apply(LibraryChange::Changed(t.uuid.clone()))
+                        }
+                        _ => {}
+                    }
+                }
 
-    let mut game_tiles: Vec<GameData> = ui.global::<HomeWindowFocus>().get_games().iter().collect();
+                if t.kind == TransferKind::SelfUpdate {
+                    let update = e.global::<UpdateState>();
+                    update.set_status(t.describe().into());
+                    update.set_staged(t.state == TransferState::Completed);
+                }
+
+                let downloads = focus.get_downloads();
+                let row = DownloadData {
+                    id: t.id.to_string().into(),
+                    uuid: t.uuid.clone().into(),
+                    title: title.into(),
+                    kind: t.kind.name().into(),
+                    progress: t.progress,
+                    status: match &t.state {
+                        // The details go below, they can be long.
+                        TransferState::Failed(_) => "Failed".into(),
+                        _ => t.describe().into(),
+                    },
+                    error: match &t.state {
+                        TransferState::Failed(e) => e.into(),
+                        _ => Default::default(),
+                    },
+                    bandwidth: if t.state == TransferState::Active {
+                        transfer::format_bandwidth(t.bandwidth).into()
+                    } else {
+                        Default::default()
+                    },
+                    paused: t.state == TransferState::Paused,
+                    finished: !unfinished,
+                };
+                let id = row.id.clone();
+                match downloads.iter().position(|d| d.id == id) {
+                    Some(i) => downloads.set_row_data(i, row),
+                    None => {
+                        if let Some(model) = downloads
+                            .as_any()
+                            .downcast_ref::<slint::VecModel<DownloadData>>()
+                        {
+                            model.push(row);
+                        }
+                    }
+                }
+                focus.set_download_bandwidth(transfer::format_bandwidth(total_bandwidth).into());
+            })
+            .unwrap();
+    }
+}
+
+fn main() -> Result<(), slint::PlatformError> {
+    let args = cli::Args::parse_from(cli::args());
+    let done = match args.command {
+        Some(cli::Command::Cli { library, command }) => Some(cli::run(library, command)),
+        Some(cli::Command::Session { inner: true }) => Some(kiosk::run_inner_session()),
+        Some(cli::Command::Session { inner: false }) => Some(
+            KioskConfig::load(&KioskConfig::default_path())
+                .and_then(|config| kiosk::run_session(&config)),
+        ),
+        None => None,
+    };
+    if let Some(done) = done {
+        if let Err(e) = done {
+            eprintln!("error: {:?}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
-    game_tiles.push(GameData {
-        title: "aaaa".into(),
-        uuid: "aaaa".into(),
+    let message = match (args.uri, args.launch) {
+        (Some(uri), _) => match instance::deeplink::parse(&uri) {
+            Ok(message) => message,
+            Err(e) => {
+                eprintln!("error: {:?}", e);
+                std::process::exit(1);
+            }
+        },
+        (None, Some(uuid)) => instance::Message::Launch { uuid },
+        (None, None) => instance::Message::Activate,
+    };
+    let instance = match SingleInstance::acquire(&SingleInstance::default_path(), &message) {
+        Ok(Some(instance)) => Some(instance),
+        Ok(None) => {
+            info!("anubis is already running, handed over to it");
+            return Ok(());
+        }
+        Err(e) => {
+            warn!("failed to check for a running instance: {:?}", e);
+            None
+        }
+    };
+    // Downloaded last time, run instead of this one. Only by the instance
+    // running, not under it. The socket is closed on exec, the updated one
+    // takes it over.
+    let update_config = UpdateConfig::load(&UpdateConfig::default_path()).unwrap_or_else(|e| {
+        warn!("failed to load the update config: {:?}", e);
+        UpdateConfig::default()
     });
-    game_tiles.push(GameData {
-        title: "bbbb".into(),
-        uuid: "bbbb".into(),
+    match updater::apply_staged(&Updater::default_dir(), &update_config) {
+        Ok(Some(binary)) => {
+            let e = std::process::Command::new(&binary)
+                .args(std::env::args_os().skip(1))
+                .exec();
+            warn!("failed to start the updated {:?}: {:?}", binary, e);
+        }
+        Ok(None) => {}
+        Err(e) => warn!("failed to apply the update: {:?}", e),
+    }
+
+    let diagnostics_config = DiagnosticsConfig::load(&paths::config_dir().join("diagnostics.yaml"))
+        .unwrap_or_else(|e| {
+            warn!("failed to load the diagnostics config: {:?}", e);
+            DiagnosticsConfig::default()
+        });
+    let diagnostics = Diagnostics::new(Diagnostics::default_dir(), diagnostics_config.enabled);
+    diagnostics.install_panic_hook();
+    if let (true, Some(url)) = (diagnostics_config.enabled, diagnostics_config.upload_url) {
+        let diagnostics = diagnostics.clone();
+        runtime::spawn_job(
+            UPLOAD_TIMEOUT,
+            move || diagnostics.upload(&url),
+            |uploaded| {
+                if let Err(e) = uploaded {
+                    warn!("failed to upload the diagnostics: {:?}", e);
+                }
+            },
+        );
+    }
+
+    let recorder = args
+        .record
+        .and_then(|path| match replay::Recorder::create(&path) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                warn!("failed to record to {:?}: {:?}", path, e);
+                None
+            }
+        });
+
+    let input_recorder = args
+        .record_input
+        .and_then(|path| match InputRecorder::create(&path) {
+            Ok(recorder) => Some(Arc::new(Mutex::new(recorder))),
+            Err(e) => {
+                warn!("failed to record the input to {:?}: {:?}", path, e);
+                None
+            }
+        });
+    let input_playback = args
+        .play_input
+        .and_then(|path| match input_log::load(&path) {
+            Ok(recorded) => Some(recorded),
+            Err(e) => {
+                warn!(
+                    "failed to load the input to play back from {:?}: {:?}",
+                    path, e
+                );
+                None
+            }
+        });
+    let input_playback = Arc::new(Mutex::new(input_playback));
+
+    let ui = HomeWindow::new()?;
+    let kiosk = KioskConfig::load(&KioskConfig::default_path()).unwrap_or_else(|e| {
+        warn!("failed to load the kiosk config: {:?}", e);
+        KioskConfig::default()
     });
+    if kiosk.enabled {
+        // Only "Exit to desktop" closes it, not Alt+F4 and such.
+        ui.window().set_fullscreen(true);
+        ui.window()
+            .on_close_requested(|| slint::CloseRequestResponse::KeepWindowShown);
+    }
+    let i18n = Arc::new(Translations::load(&paths::config_dir().join("locale.yaml")));
+    show_strings(&ui, &i18n);
+    // The top bar clock, kept until the window closes.
+    let clock = slint::Timer::default();
+    let clock_handle = ui.as_weak();
+    let tick = move || {
+        if let Some(ui) = clock_handle.upgrade() {
+            let time = chrono::Local::now().format("%H:%M").to_string();
+            ui.global::<ClockState>().set_time(time.into());
+        }
+    };
+    tick();
+    clock.start(slint::TimerMode::Repeated, CLOCK_INTERVAL, tick);
+    ui.global::<SettingsState>()
+        .set_diagnostics(diagnostics.is_enabled());
+    ui.global::<UpdateState>()
+        .set_current_version(updater::current_version().to_string().into());
+
+    let downloads_model = std::rc::Rc::new(slint::VecModel::<DownloadData>::default());
+    ui.global::<HomeWindowFocus>()
+        .set_downloads(downloads_model.into());
 
-    let tiles_model = std::rc::Rc::new(slint::VecModel::from(game_tiles));
-    ui.global::<HomeWindowFocus>().set_games(tiles_model.into());
+    let (tx, mut rx) = unbounded_channel();
+    let (restart_tx, restart_rx) = mpsc::channel();
+    let supervisor = Supervisor::new(restart_tx);
+    runtime::forward(restart_rx, tx.clone(), NavigationEvent::Restarted);
+    let (transfer_tx, transfer_rx) = mpsc::channel();
+    let transfers = TransferManager::new(transfer_tx);
+    let transfer_handle = ui.as_weak();
+    let transfer_events = tx.clone();
+    thread::spawn(move || transfer_event_thread(transfer_handle, transfer_rx, transfer_events));
 
-    let (tx, rx) = mpsc::channel();
+    if let Some(instance) = instance {
+        let (instance_tx, instance_rx) = mpsc::channel();
+        instance.spawn(instance_tx);
+        runtime::forward(instance_rx, tx.clone(), NavigationEvent::Instance);
+    }
+    // Our own link or --launch, handled once booted.
+    let launching = message != instance::Message::Activate;
+    if launching {
+        tx.send(NavigationEvent::Instance(message)).unwrap();
+    }
+
+    let pointer_tx = tx.clone();
+    ui.global::<HomeWindowFocus>()
+        .on_pointer_clicked(move |layout, x, y| match layout.parse() {
+            Ok(layout) => {
+                let press = PointerPress::At { layout, x, y };
+                let _ = pointer_tx.send(NavigationEvent::Pointer(press));
+            }
+            Err(e) => warn!("bad layout {} from the UI: {:?}", layout, e),
+        });
+    let pointer_tx = tx.clone();
+    ui.global::<HomeWindowFocus>()
+        .on_on_focus_id_press(move |focus_id| match focus_id.parse() {
+            Ok(focus_id) => {
+                let _ = pointer_tx.send(NavigationEvent::Pointer(PointerPress::On(focus_id)));
+            }
+            Err(e) => warn!("bad focus id {} from the UI: {:?}", focus_id, e),
+        });
 
+    // The rest is loaded behind the splash, see `boot`.
     let handle = ui.as_weak();
-    thread::spawn(move || controller_loop(tx));
-    thread::spawn(move || navigation_controller_thread(handle, rx));
+    let boot_diagnostics = diagnostics.clone();
+    thread::spawn(move || {
+        let diagnostics = boot_diagnostics;
+        let boot_handle = handle.clone();
+        let boot_i18n = i18n.clone();
+        let mut pipeline = boot::Pipeline::new(move |stage: boot::Stage, progress| {
+            let message = boot_i18n.tr(stage.message_id());
+            let _ = boot_handle.upgrade_in_event_loop(move |e| {
+                let boot = e.global::<BootState>();
+                boot.set_stage(message.into());
+                boot.set_progress(progress);
+            });
+        });
+
+        pipeline.start(boot::Stage::Config);
+        let roots = RootsConfig::load(&RootsConfig::default_path()).unwrap_or_else(|e| {
+            warn!("failed to load the library roots: {:?}", e);
+            RootsConfig::default()
+        });
+        let sync_config =
+            SyncConfig::load(&paths::config_dir().join("sync.yaml")).unwrap_or_else(|e| {
+                warn!("failed to load the sync config: {:?}", e);
+                SyncConfig::default()
+            });
+        let profiles = ProfileConfig::load(&paths::config_dir().join("profiles.yaml"))
+            .unwrap_or_else(|e| {
+                warn!("failed to load the launch profiles: {:?}", e);
+                ProfileConfig::default()
+            });
+        let save_config = SaveConfig::load(&SaveConfig::default_path()).unwrap_or_else(|e| {
+            warn!("failed to load the save backup config: {:?}", e);
+            SaveConfig::default()
+        });
+        let remote_config = RemoteConfig::load(&paths::config_dir().join("remote.yaml"))
+            .unwrap_or_else(|e| {
+                warn!("failed to load the remote config: {:?}", e);
+                RemoteConfig::default()
+            });
+        let retroachievements = match RetroAchievementsConfig::load(
+            &paths::config_dir().join("retroachievements.yaml"),
+        ) {
+            Ok(config) => config
+                .map(|c| RetroAchievements::new(c, paths::cache_dir().join("retroachievements"))),
+            Err(e) => {
+                warn!("failed to load the retroachievements config: {:?}", e);
+                None
+            }
+        };
+        let presence = Presence::new(
+            PresenceConfig::load(&paths::config_dir().join("presence.yaml")).unwrap_or_else(|e| {
+                warn!("failed to load the presence config: {:?}", e);
+                PresenceConfig::default()
+            }),
+        );
+        let chord_config = ChordConfig::load(&paths::config_dir().join("chords.yaml"))
+            .unwrap_or_else(|e| {
+                warn!("failed to load the chords: {:?}", e);
+                ChordConfig::default()
+            });
+        let resume_config = ResumeConfig::load(&ResumeConfig::default_path()).unwrap_or_else(|e| {
+            warn!("failed to load the resume config: {:?}", e);
+            ResumeConfig::default()
+        });
+        let suspend_config =
+            SuspendConfig::load(&SuspendConfig::default_path()).unwrap_or_else(|e| {
+                warn!("failed to load the suspend config: {:?}", e);
+                SuspendConfig::default()
+            });
+        let quiet_config = QuietConfig::load(&QuietConfig::default_path()).unwrap_or_else(|e| {
+            warn!("failed to load the quiet hours: {:?}", e);
+            QuietConfig::default()
+        });
+        let session = SessionState::load(&SessionState::default_path()).unwrap_or_else(|e| {
+            warn!("failed to load the last session: {:?}", e);
+            SessionState::default()
+        });
+        let playback_config = PlaybackConfig::load(&paths::config_dir().join("playback.yaml"))
+            .unwrap_or_else(|e| {
+                warn!("failed to load the playback config: {:?}", e);
+                PlaybackConfig::default()
+            });
+        let input_config = InputConfig::load(&paths::config_dir().join("input.yaml"))
+            .unwrap_or_else(|e| {
+                warn!("failed to load the input config: {:?}", e);
+                InputConfig::default()
+            });
+        let router = Router::with_layout_dir(args.layouts.clone()).unwrap_or_else(|e| {
+            warn!(
+                "failed to load the layouts, using the built-in ones: {:?}",
+                e
+            );
+            Router::new().unwrap()
+        });
+        if let Some(dir) = args.layouts {
+            let tx = tx.clone();
+            let changed = runtime::debounce(LAYOUT_SETTLE, move |screen| {
+                tx.send(NavigationEvent::LayoutChanged(screen)).is_ok()
+            });
+            let watched = layout_file::watch(dir, move |screen| changed.send(screen).is_ok());
+            if let Err(e) = watched {
+                warn!("failed to watch the layouts: {:?}", e);
+            }
+        }
+
+        pipeline.start(boot::Stage::Library);
+        let mut library = Library::open(&Library::default_path()).unwrap_or_else(|e| {
+            warn!("failed to load the library: {:?}", e);
+            Library::new()
+        });
+        if library.purge_expired(chrono::Utc::now()) > 0 {
+            if let Err(e) = library.save() {
+                warn!("failed to save the library: {:?}", e);
+            }
+        }
+        let parental = ParentalConfig::load(&ParentalConfig::default_path())
+            .and_then(|c| c.max_age())
+            .unwrap_or_else(|e| {
+                // Rather too little shown than too much.
+                warn!("failed to load the parental controls: {:?}", e);
+                Some(0)
+            });
+        library.restrict(parental);
+        let library = Arc::new(Mutex::new(library));
+        let unavailable_roots = Arc::new(Mutex::new(roots.unavailable()));
+        let roots_tx = tx.clone();
+        roots::watch(
+            roots,
+            library.clone(),
+            unavailable_roots.clone(),
+            move |event| roots_tx.send(NavigationEvent::Roots(event)).is_ok(),
+        );
+
+        pipeline.start(boot::Stage::Input);
+        // Started over with fresh chord state, the pad is opened again.
+        let (chords, sequences) = (chord_config.chords, chord_config.sequences);
+        let press_config = input_config.presses.clone();
+        let pad_tx = tx.clone();
+        let (cec_tx, cec_rx) = mpsc::channel();
+        // Kept when the input task is restarted.
+        let cec_rx = Arc::new(Mutex::new(cec_rx));
+        supervisor
+            .spawn("gamepad", move || {
+                let chords = ChordDetector::new(chords.clone());
+                let sequences = SequenceMatcher::from_config(sequences.clone());
+                let presses = PressClassifier::new(press_config.clone());
+                let mut pipeline = InputPipeline {
+                    tx: pad_tx.clone(),
+                    chords,
+                    sequences,
+                    presses,
+                    south_pressed: None,
+                    holding: false,
+                    recorder: input_recorder.clone(),
+                };
+                // Played back once, not again when the pad is opened again.
+                let playback = input_playback.lock().unwrap().take();
+                let others = cec_rx.lock().unwrap();
+                runtime::block_on(async {
+                    if let Some(recorded) = playback {
+                        play_inputs(&mut pipeline, recorded).await;
+                    }
+                    input_task(pipeline, &others).await
+                })
+            })
+            .unwrap();
+        let cec_config = CecConfig::load(&CecConfig::default_path()).unwrap_or_else(|e| {
+            warn!("failed to load the CEC config: {:?}", e);
+            CecConfig::default()
+        });
+        if cec_config.enabled {
+            let cec = Cec::new(cec_config, cec_tx);
+            if let Err(e) = supervisor.spawn("cec", move || cec.run()) {
+                warn!("failed to start reading the TV remote: {:?}", e);
+            }
+        }
+
+        pipeline.start(boot::Stage::Art);
+        // Covers are fetched as their tiles are shown, those of the first
+        // rows right away.
+        let art_config = art::ArtConfig::load(&paths::config_dir().join("art.yaml"))
+            .unwrap_or_else(|e| {
+                warn!("failed to load the art config: {:?}", e);
+                art::ArtConfig::default()
+            });
+        let art_cache = art::ArtCache::new(
+            art::ArtCache::default_dir(),
+            transfers.clone(),
+            art_config.disk_budget(),
+        )
+        .map_err(|e| warn!("failed to open the art cache: {:?}", e))
+        .ok();
+        if let Some(art_cache) = &art_cache {
+            art_cache.warm(library.lock().unwrap().iter(), WARM_COVERS);
+        }
+        pipeline.finish();
+
+        let steam_config = steam::SteamConfig::load(&steam::SteamConfig::default_path())
+            .unwrap_or_else(|e| {
+                warn!("failed to load the Steam config: {:?}", e);
+                steam::SteamConfig::default()
+            });
+        let steam = steam::SteamInstaller::from_config(&steam_config, transfers.clone());
+        let tile_locale = Arc::new(Mutex::new(i18n.locale().to_owned()));
+        let selected = Arc::new(Mutex::new(HashSet::new()));
+        let sources = TileSources {
+            art: art_cache.clone(),
+            steam: Some(steam.clone()),
+            unavailable_roots: unavailable_roots.clone(),
+            cover_budget: Some(art_config.memory_budget()),
+            locale: tile_locale.clone(),
+            selected: selected.clone(),
+        };
+        let tiles_library = library.clone();
+        let resume_enabled = resume_config.enabled;
+        let _ = handle.upgrade_in_event_loop(move |e| {
+            e.global::<SettingsState>().set_resume(resume_enabled);
+            let tiles = GameTiles::new(tiles_library, sources);
+            e.global::<HomeWindowFocus>()
+                .set_games(std::rc::Rc::new(tiles).into());
+            e.global::<BootState>().set_done(true);
+        });
+
+        let updater = Updater::new(update_config, Updater::default_dir(), transfers.clone())
+            .unwrap_or_else(|e| {
+                warn!("failed to set up updates: {:?}", e);
+                None
+            });
+        if let Some(updater) = updater.clone() {
+            let updates_tx = tx.clone();
+            runtime::spawn_job(
+                CHECK_TIMEOUT,
+                move || updater.check(),
+                move |checked| match checked {
+                    Ok(releases) if !releases.is_empty() => {
+                        let _ = updates_tx.send(NavigationEvent::Updates(releases));
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("failed to check for updates: {:?}", e),
+                },
+            );
+        }
+
+        let feeds_config = FeedsConfig::load(&paths::config_dir().join("feeds.yaml"))
+            .unwrap_or_else(|e| {
+                warn!("failed to load the feeds config: {:?}", e);
+                FeedsConfig::default()
+            });
+        let feeds = Feeds::new(feeds_config, paths::cache_dir().join("feeds"));
+        {
+            let (feeds, library, news_tx) = (feeds.clone(), library.clone(), tx.clone());
+            let games = move || {
+                library
+                    .lock()
+                    .unwrap()
+                    .visible()
+                    .cloned()
+                    .collect::<Vec<_>>()
+            };
+            let following = supervisor.spawn("feeds", move || {
+                feeds.follow(&games, |latest| {
+                    news_tx.send(NavigationEvent::LatestNews(latest)).is_ok()
+                })
+            });
+            if let Err(e) = following {
+                warn!("failed to start following the news: {:?}", e);
+            }
+        }
+
+        let plays = PlayLog::load(&PlayLog::default_path()).unwrap_or_else(|e| {
+            warn!("failed to load when the games were played: {:?}", e);
+            PlayLog::default()
+        });
+        let plays = Arc::new(Mutex::new(plays));
+        {
+            let (library, plays, picks_tx) = (library.clone(), plays.clone(), tx.clone());
+            let suggesting = supervisor.spawn("picks", move || {
+                recommend::follow(
+                    || library.lock().unwrap().visible().cloned().collect(),
+                    || plays.lock().unwrap().clone(),
+                    |picks| picks_tx.send(NavigationEvent::Picks(picks)).is_ok(),
+                )
+            });
+            if let Err(e) = suggesting {
+                warn!("failed to start suggesting what to play: {:?}", e);
+            }
+        }
+
+        let sessions = SessionLog::load(&SessionLog::default_path()).unwrap_or_else(|e| {
+            warn!("failed to load the play sessions: {:?}", e);
+            SessionLog::default()
+        });
+
+        let (launch_tx, launch_rx) = mpsc::channel();
+        let profiles = Arc::new(Mutex::new(profiles));
+        let launcher = Launcher::new(sync_config, suspend_config, profiles.clone(), launch_tx);
+        launcher.watch_memory();
+        runtime::forward(launch_rx, tx.clone(), NavigationEvent::Launch);
+
+        let metrics = Metrics::default();
+        let metrics_config = MetricsConfig::load(&paths::config_dir().join("metrics.yaml"))
+            .unwrap_or_else(|e| {
+                warn!("failed to load the metrics config: {:?}", e);
+                MetricsConfig::default()
+            });
+        if let Some(listen) = metrics_config.listen {
+            let metrics = metrics.clone();
+            if let Err(e) = supervisor.spawn("metrics", move || metrics.serve(&listen)) {
+                warn!("failed to start the metrics endpoint: {:?}", e);
+            }
+        }
+        let pairing = Pairing::default();
+        let bus = Bus::default();
+        // Nothing is played yet.
+        bus.publish(bus::Event::Idle);
+        if remote_config.enabled {
+            let (remote_tx, remote_rx) = mpsc::channel();
+            let started = RemoteServer::new(
+                library.clone(),
+                remote_tx,
+                paths::data_dir().join("art"),
+                pairing.clone(),
+                remote_config,
+                bus.clone(),
+            )
+            .and_then(|server| Ok(supervisor.spawn("remote", move || server.serve())?));
+            if let Err(e) = started {
+                warn!("failed to start the remote control server: {:?}", e);
+            }
+            // Buttons take the same path as the gamepad.
+            runtime::forward(remote_rx, tx.clone(), |command| match command {
+                RemoteCommand::Button(b) => NavigationEvent::Button(b),
+                command => NavigationEvent::Remote(command),
+            });
+        }
+        // Of the library with other devices, not the saves.
+        let peers_config = sync::SyncConfig::load(&sync::SyncConfig::default_path())
+            .unwrap_or_else(|e| {
+                warn!("failed to load the library sync config: {:?}", e);
+                Default::default()
+            });
+        if peers_config.enabled {
+            let syncer = DeviceKey::load_or_create(&DeviceKey::default_path())
+                .and_then(|key| Syncer::new(library.clone(), peers_config, key));
+            match syncer {
+                Ok(syncer) => {
+                    let server = syncer.clone();
+                    if let Err(e) = supervisor.spawn("sync-server", move || server.serve()) {
+                        warn!("failed to start the sync server: {:?}", e);
+                    }
+                    let synced = tx.clone();
+                    let pulled = supervisor.spawn("sync", move || {
+                        syncer.run(|uuids| synced.send(NavigationEvent::Synced(uuids)).is_ok())
+                    });
+                    if let Err(e) = pulled {
+                        warn!("failed to start syncing: {:?}", e);
+                    }
+                }
+                Err(e) => warn!("failed to start syncing: {:?}", e),
+            }
+        }
+        // Idle until enabled in the settings.
+        let mqtt_config = Arc::new(Mutex::new(
+            MqttConfig::load(&MqttConfig::default_path()).unwrap_or_else(|e| {
+                warn!("failed to load the MQTT config: {:?}", e);
+                MqttConfig::default()
+            }),
+        ));
+        let (mqtt_tx, mqtt_rx) = mpsc::channel();
+        let mqtt = Mqtt::new(mqtt_config.clone(), bus.clone(), mqtt_tx);
+        if let Err(e) = supervisor.spawn("mqtt", move || mqtt.run()) {
+            warn!("failed to start MQTT: {:?}", e);
+        }
+        runtime::forward(mqtt_rx, tx.clone(), NavigationEvent::Mqtt);
+
+        // Unless told what to launch.
+        let resuming = session
+            .to_resume(&resume_config, |uuid| {
+                library.lock().unwrap().get(uuid).is_some()
+            })
+            .filter(|_| !launching)
+            .map(str::to_owned);
+        let resume_on_start = resuming.is_some();
+        let navigator = Navigator {
+            ui: Box::new(FrameCoalescer::new(handle, metrics.clone()).unwrap()),
+            events: tx.clone(),
+            router,
+            library,
+            steam,
+            transfers,
+            launcher,
+            retroachievements,
+            feeds,
+            presence,
+            listed_downloads: HashSet::new(),
+            sync_prompt: None,
+            details: None,
+            duplicates: vec![],
+            matches: Matches::default(),
+            recorder,
+            last_click: None,
+            cursor: None,
+            screenshot_command: chord_config.screenshot_command,
+            debug_overlay: false,
+            recent_events: VecDeque::new(),
+            i18n,
+            timezones: vec![],
+            updater,
+            releases: vec![],
+            diagnostics,
+            metrics,
+            profiles,
+            profile: None,
+            saves: SaveBackups::new(save_config),
+            save_snapshots: vec![],
+            save_index: 0,
+            storage: None,
+            art: art_cache,
+            storage_scan: None,
+            suggestion: 0,
+            unavailable_roots,
+            tile_locale,
+            kiosk,
+            playback_config,
+            hero: None,
+            hero_player: None,
+            pin_entry: String::new(),
+            pin_lock: PinLock::default(),
+            keyboard: None,
+            selected: Default::default(),
+            undo_delete: None,
+            trash_index: 0,
+            pairing,
+            bus,
+            mqtt_config,
+            resume_config,
+            resuming,
+            suspended_index: 0,
+            quiet_config: quiet_config.clone(),
+            quiet_scheduled: false,
+            quiet_override: None,
+            quick_settings: QuickSettings::default(),
+            menu_game: None,
+            dialogs: Dialogs::default(),
+            importing: vec![],
+            title_index: TitleIndex::default(),
+            viewport: Viewport::new(LayoutId::sublayout("Home", "Games")),
+            playing: None,
+            handlers: Arc::new(handlers()),
+            achievements_job: None,
+            news_job: None,
+            palette_job: None,
+            palettes: HashMap::new(),
+            plays,
+            sessions,
+            session_started: None,
+            stats_period: Period::Week,
+            picks: vec![],
+            picks_shown: vec![],
+        };
+        let (mut navigator, mut limiter) = (navigator, InputLimiter::new(input_config));
+        supervisor
+            .spawn("navigation", move || {
+                runtime::block_on(navigation_task(&mut navigator, &mut rx, &mut limiter));
+                Ok(())
+            })
+            .unwrap();
+        let quiet_tx = tx.clone();
+        quiet::watch(quiet_config, move |quiet| {
+            quiet_tx.send(NavigationEvent::QuietHours(quiet)).is_ok()
+        });
+        if resume_on_start {
+            resume::countdown(move |left| tx.send(NavigationEvent::ResumeTick(left)).is_ok());
+        }
+    });
+
+    ui.run()?;
+    if let Err(e) = diagnostics.end_session() {
+        warn!("failed to write the session report: {:?}", e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::GameMetadata;
+
+    /// A focus change as the UI would show it.
+    struct FocusUpdate {
+        screen: Screen,
+        focus_id: FocusId,
+    }
+
+    /// Records the focus changes, there's no window to update.
+    struct FocusLog(mpsc::Sender<FocusUpdate>);
+
+    impl UiBridge for FocusLog {
+        type Window = HomeWindow;
 
-    ui.run()
+        fn set_focus(&self, screen: Screen, focus: &FocusId) -> anyhow::Result<()> {
+            // Nobody's watching when the test is over.
+            let _ = self.0.send(FocusUpdate {
+                screen,
+                focus_id: focus.clone(),
+            });
+            Ok(())
+        }
+
+        fn set_scroll(&self, _: Option<&FocusRect>, _: &FocusRect) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn push_model_update(&self, _: ModelUpdate<HomeWindow>) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reads_the_tv_remote_without_gamepads() {
+        let (tx, mut rx) = unbounded_channel();
+        let chord_config = ChordConfig::default();
+        let mut pipeline = InputPipeline {
+            tx,
+            chords: ChordDetector::new(chord_config.chords),
+            sequences: SequenceMatcher::from_config(chord_config.sequences),
+            presses: PressClassifier::new(InputConfig::default().presses),
+            south_pressed: None,
+            holding: false,
+            recorder: None,
+        };
+        let (cec_tx, cec_rx) = mpsc::channel();
+        cec_tx.send(PadInput::Pressed(Button::DPadDown)).unwrap();
+        cec_tx.send(PadInput::Released(Button::DPadDown)).unwrap();
+        poll_inputs(&mut pipeline, None, &cec_rx, Instant::now());
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(NavigationEvent::Button(Button::DPadDown))
+        ));
+        assert!(rx.try_recv().is_err());
+    }
+
+    /// A navigator on its own thread with a few games, without a window.
+    /// The focus changes come out of the receiver.
+    fn headless_navigator(
+        games: usize,
+    ) -> (
+        UnboundedSender<NavigationEvent>,
+        mpsc::Receiver<FocusUpdate>,
+    ) {
+        let mut library = Library::new();
+        for i in 0..games {
+            library
+                .insert(GameMetadata {
+                    title: format!("Game {}", i),
+                    uuid: format!("game-{}", i),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let (tx, mut rx) = unbounded_channel();
+        let (focus_tx, focus_rx) = mpsc::channel();
+        let (transfer_tx, _) = mpsc::channel();
+        let transfers = TransferManager::new(transfer_tx);
+        let (launch_tx, _) = mpsc::channel();
+        let nav = Navigator {
+            ui: Box::new(FocusLog(focus_tx)),
+            events: tx.clone(),
+            router: Router::new().unwrap(),
+            library: Arc::new(Mutex::new(library)),
+            steam: steam::SteamInstaller::new(steam::SteamBackend::default(), transfers.clone()),
+            transfers,
+            launcher: Launcher::new(
+                SyncConfig::default(),
+                SuspendConfig::default(),
+                Default::default(),
+                launch_tx,
+            ),
+            retroachievements: None,
+            feeds: Feeds::new(
+                FeedsConfig {
+                    steam: false,
+                    ..Default::default()
+                },
+                std::env::temp_dir().join("anubis-feeds"),
+            ),
+            presence: Presence::new(PresenceConfig::default()),
+            listed_downloads: HashSet::new(),
+            sync_prompt: None,
+            details: None,
+            duplicates: vec![],
+            matches: Matches::default(),
+            recorder: None,
+            last_click: None,
+            cursor: None,
+            screenshot_command: vec![],
+            debug_overlay: false,
+            recent_events: VecDeque::new(),
+            i18n: Arc::new(Translations::new("en-US")),
+            timezones: vec![],
+            updater: None,
+            releases: vec![],
+            diagnostics: Diagnostics::new(std::env::temp_dir(), false),
+            metrics: Metrics::default(),
+            profiles: Default::default(),
+            profile: None,
+            saves: SaveBackups::new(SaveConfig::default()),
+            save_snapshots: vec![],
+            save_index: 0,
+            storage: None,
+            art: None,
+            storage_scan: None,
+            suggestion: 0,
+            unavailable_roots: Default::default(),
+            tile_locale: Default::default(),
+            kiosk: KioskConfig::default(),
+            playback_config: PlaybackConfig::default(),
+            hero: None,
+            hero_player: None,
+            pin_entry: String::new(),
+            pin_lock: PinLock::default(),
+            keyboard: None,
+            selected: Default::default(),
+            undo_delete: None,
+            trash_index: 0,
+            pairing: Pairing::default(),
+            bus: Bus::default(),
+            mqtt_config: Default::default(),
+            resume_config: ResumeConfig::default(),
+            resuming: None,
+            suspended_index: 0,
+            quiet_config: QuietConfig::default(),
+            quiet_scheduled: false,
+            quiet_override: None,
+            quick_settings: QuickSettings::default(),
+            menu_game: None,
+            dialogs: Dialogs::default(),
+            importing: vec![],
+            title_index: TitleIndex::default(),
+            viewport: Viewport::new(LayoutId::sublayout("Home", "Games")),
+            playing: None,
+            handlers: Arc::new(handlers()),
+            achievements_job: None,
+            news_job: None,
+            palette_job: None,
+            palettes: HashMap::new(),
+            plays: Default::default(),
+            sessions: SessionLog::default(),
+            session_started: None,
+            stats_period: Period::Week,
+            picks: vec![],
+            picks_shown: vec![],
+        };
+        // No waiting between moves, the script presses as fast as it can.
+        let config: InputConfig = serde_yaml::from_str("direction_interval: 0").unwrap();
+        thread::spawn(move || {
+            let (mut nav, mut limiter) = (nav, InputLimiter::new(config));
+            runtime::block_on(navigation_task(&mut nav, &mut rx, &mut limiter))
+        });
+        (tx, focus_rx)
+    }
+
+    fn press(
+        tx: &UnboundedSender<NavigationEvent>,
+        focus: &mpsc::Receiver<FocusUpdate>,
+        b: Button,
+    ) -> String {
+        tx.send(NavigationEvent::Button(b)).unwrap();
+        let update = focus.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(update.screen, Screen::Home);
+        update.focus_id.to_string()
+    }
+
+    #[test]
+    fn scripted_presses_move_the_focus() {
+        let (tx, focus) = headless_navigator(9);
+        let script = [
+            Button::DPadDown,
+            Button::DPadRight,
+            Button::DPadRight,
+            Button::DPadDown,
+            Button::DPadLeft,
+            Button::DPadUp,
+            Button::DPadUp,
+        ];
+        let focused: Vec<String> = script.iter().map(|&b| press(&tx, &focus, b)).collect();
+        assert_eq!(
+            focused,
+            [
+                "GAME@game-0",
+                "GAME@game-1",
+                "GAME@game-2",
+                // The second row is shorter, down goes to its last game.
+                "GAME@game-8",
+                "GAME@game-7",
+                "GAME@game-0",
+                "BTN@GAMES",
+            ]
+        );
+    }
 }
@@ -0,0 +1,98 @@
+use anyhow::{anyhow, Result};
+use log::warn;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use std::{
+    collections::HashMap,
+    fs,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+// Being found by, and finding, other devices on the LAN over mDNS.
+
+/// The name of this machine, e.g. `living-room`.
+pub fn hostname() -> String {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .map(|h| h.trim().to_owned())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "anubis".to_owned())
+}
+
+/// Advertised until dropped.
+pub struct Advertised {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+impl Drop for Advertised {
+    fn drop(&mut self) {
+        if let Err(e) = self.daemon.unregister(&self.fullname) {
+            warn!("failed to stop advertising {}: {}", self.fullname, e);
+        }
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// Advertise a service, e.g. `_anubis-sync._tcp.local.`, on every address of
+/// the machine, named after it.
+pub fn advertise(
+    service_type: &str,
+    port: u16,
+    properties: HashMap<String, String>,
+) -> Result<Advertised> {
+    let daemon = ServiceDaemon::new()?;
+    let name = hostname();
+    let info = ServiceInfo::new(
+        service_type,
+        &name,
+        &format!("{}.local.", name),
+        "",
+        port,
+        properties,
+    )?
+    .enable_addr_auto();
+    let fullname = info.get_fullname().to_owned();
+    daemon.register(info)?;
+    Ok(Advertised { daemon, fullname })
+}
+
+/// A service found on the LAN.
+#[derive(Debug, Clone)]
+pub struct Found {
+    pub name: String,
+    pub addresses: Vec<SocketAddr>,
+    pub properties: HashMap<String, String>,
+}
+
+/// The services of the type answering within `wait`.
+pub fn browse(service_type: &str, wait: Duration) -> Result<Vec<Found>> {
+    let daemon = ServiceDaemon::new()?;
+    let events = daemon.browse(service_type)?;
+    let deadline = Instant::now() + wait;
+    let mut found = vec![];
+    while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+        let info = match events.recv_timeout(left) {
+            Ok(ServiceEvent::ServiceResolved(info)) => info,
+            Ok(_) => continue,
+            Err(_) => break,
+        };
+        found.push(Found {
+            name: info.get_fullname().to_owned(),
+            addresses: info
+                .get_addresses()
+                .iter()
+                .map(|ip| SocketAddr::new(*ip, info.get_port()))
+                .collect(),
+            properties: info
+                .get_properties()
+                .iter()
+                .map(|p| (p.key().to_owned(), p.val_str().to_owned()))
+                .collect(),
+        });
+    }
+    daemon
+        .shutdown()
+        .map_err(|e| anyhow!("failed to stop browsing: {}", e))?;
+    Ok(found)
+}
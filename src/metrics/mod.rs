@@ -0,0 +1,207 @@
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use serde::Deserialize;
+use std::{
+    fmt::Write,
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tiny_http::{Header, Response, Server};
+
+// How fast the UI answers, to catch it getting slower as the layouts grow:
+// the time from a button press to the new focus being shown, and how long
+// the UI thread takes to apply a frame's model updates. Both go in
+// histograms, shown in the debug overlay and, when configured, served for
+// Prometheus to scrape.
+
+/// Upper bounds of the buckets, in milliseconds. A frame at 60Hz is 16.
+const BUCKETS_MS: [u64; 11] = [1, 2, 4, 8, 16, 33, 50, 100, 250, 500, 1000];
+
+/// The endpoint is off unless it has an address.
+///
+/// ```yaml
+/// # Serves /metrics there.
+/// listen: 127.0.0.1:9187
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MetricsConfig {
+    pub listen: Option<String>,
+}
+
+impl MetricsConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Histogram {
+    /// In each bucket, the last one is for what's over all of them.
+    counts: [u64; BUCKETS_MS.len() + 1],
+    sum: Duration,
+}
+
+impl Histogram {
+    pub fn observe(&mut self, took: Duration) {
+        let ms = took.as_secs_f64() * 1000.0;
+        let i = BUCKETS_MS
+            .iter()
+            .position(|&le| ms <= le as f64)
+            .unwrap_or(BUCKETS_MS.len());
+        self.counts[i] += 1;
+        self.sum += took;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The bucket `q` of them are under, None past the last one or without
+    /// any.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        let rank = (q * self.count() as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return BUCKETS_MS.get(i).map(|&ms| Duration::from_millis(ms));
+            }
+        }
+        None
+    }
+
+    /// One line, like `p50 <4ms p95 <16ms p99 <33ms of 120`.
+    pub fn summary(&self) -> String {
+        let mut text = String::new();
+        for (name, q) in [("p50", 0.5), ("p95", 0.95), ("p99", 0.99)] {
+            match self.quantile(q) {
+                Some(d) => write!(text, "{} <{}ms ", name, d.as_millis()),
+                None if self.count() == 0 => write!(text, "{} - ", name),
+                None => write!(text, "{} >{}ms ", name, BUCKETS_MS[BUCKETS_MS.len() - 1]),
+            }
+            .unwrap();
+        }
+        text + &format!("of {}", self.count())
+    }
+
+    /// In the Prometheus text format, in seconds as it likes.
+    fn write_prometheus(&self, out: &mut String, name: &str, help: &str) {
+        writeln!(out, "# HELP {} {}", name, help).unwrap();
+        writeln!(out, "# TYPE {} histogram", name).unwrap();
+        let mut seen = 0;
+        for (le, count) in BUCKETS_MS.iter().zip(&self.counts) {
+            seen += count;
+            let le = *le as f64 / 1000.0;
+            writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, le, seen).unwrap();
+        }
+        writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, self.count()).unwrap();
+        writeln!(out, "{}_sum {}", name, self.sum.as_secs_f64()).unwrap();
+        writeln!(out, "{}_count {}", name, self.count()).unwrap();
+    }
+}
+
+#[derive(Debug, Default)]
+struct Histograms {
+    navigation: Histogram,
+    frames: Histogram,
+}
+
+/// Shared by whatever measures something, cheap to clone.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    histograms: Arc<Mutex<Histograms>>,
+}
+
+impl Metrics {
+    /// From the press to the new focus being shown.
+    pub fn record_navigation(&self, took: Duration) {
+        self.histograms.lock().unwrap().navigation.observe(took);
+    }
+
+    /// Applying the model updates of a frame, on the UI thread.
+    pub fn record_frame(&self, took: Duration) {
+        self.histograms.lock().unwrap().frames.observe(took);
+    }
+
+    /// For the debug overlay.
+    pub fn overlay_text(&self) -> String {
+        let histograms = self.histograms.lock().unwrap();
+        format!(
+            "nav: {}\nframe: {}",
+            histograms.navigation.summary(),
+            histograms.frames.summary()
+        )
+    }
+
+    pub fn prometheus(&self) -> String {
+        let histograms = self.histograms.lock().unwrap();
+        let mut out = String::new();
+        histograms.navigation.write_prometheus(
+            &mut out,
+            "anubis_navigation_seconds",
+            "Time from a button press to the UI showing the new focus.",
+        );
+        histograms.frames.write_prometheus(
+            &mut out,
+            "anubis_frame_update_seconds",
+            "Time the UI thread took to apply a frame's model updates.",
+        );
+        out
+    }
+
+    /// Serve `/metrics` until the server fails.
+    pub fn serve(&self, listen: &str) -> Result<()> {
+        let server = Server::http(listen).map_err(|e| anyhow!("{}", e))?;
+        info!("serving metrics on {}", listen);
+        for request in server.incoming_requests() {
+            let response = if request.url() == "/metrics" {
+                let content_type = "text/plain; version=0.0.4";
+                Response::from_string(self.prometheus())
+                    .with_header(Header::from_bytes("Content-Type", content_type).unwrap())
+            } else {
+                Response::from_string("not found").with_status_code(404)
+            };
+            if let Err(e) = request.respond(response) {
+                warn!("failed to answer a metrics request: {:?}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_what_it_measures() {
+        let sut = Metrics::default();
+        for ms in [3, 3, 3, 12, 40, 2000] {
+            sut.record_navigation(Duration::from_millis(ms));
+        }
+        sut.record_frame(Duration::from_micros(500));
+        {
+            let h = sut.histograms.lock().unwrap();
+            assert_eq!(h.navigation.count(), 6);
+            assert_eq!(h.navigation.quantile(0.5), Some(Duration::from_millis(4)));
+            assert_eq!(h.navigation.quantile(0.8), Some(Duration::from_millis(50)));
+            assert_eq!(h.navigation.quantile(1.0), None);
+        }
+        assert_eq!(
+            sut.overlay_text(),
+            "nav: p50 <4ms p95 >1000ms p99 >1000ms of 6\nframe: p50 <1ms p95 <1ms p99 <1ms of 1"
+        );
+
+        let text = sut.prometheus();
+        assert!(text.contains("anubis_navigation_seconds_bucket{le=\"0.004\"} 3\n"));
+        assert!(text.contains("anubis_navigation_seconds_bucket{le=\"1\"} 5\n"));
+        assert!(text.contains("anubis_navigation_seconds_bucket{le=\"+Inf\"} 6\n"));
+        assert!(text.contains("anubis_navigation_seconds_sum 2.061\n"));
+        assert!(text.contains("anubis_frame_update_seconds_count 1\n"));
+    }
+}
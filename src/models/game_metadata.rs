@@ -1,48 +1,344 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use slint::Image;
+use std::collections::HashMap;
 
 
 /// Metadata for games.
 /// The SoT can be from sources like igdb.com
 
 /// Image source, can be either a path on the fs, or a based64 encoded image.
-enum ImageSource {
+/// Remote images are downloaded to the art cache.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ImageSource {
 
     FilePath(String),
     Base64(String),
+    Url(String),
 }
 
-struct GameMetadata {
+/// Which art of a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtKind {
+    Cover,
+    Background,
+}
+
+impl ArtKind {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "cover" => Some(ArtKind::Cover),
+            "background" => Some(ArtKind::Background),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ArtKind::Cover => "cover",
+            ArtKind::Background => "background",
+        }
+    }
+}
+
+/// How far along a game is, set from its menu and shown on its tile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionStatus {
+    #[default]
+    Backlog,
+    Playing,
+    Completed,
+    Abandoned,
+}
+
+impl CompletionStatus {
+    pub const ALL: [CompletionStatus; 4] = [
+        CompletionStatus::Backlog,
+        CompletionStatus::Playing,
+        CompletionStatus::Completed,
+        CompletionStatus::Abandoned,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CompletionStatus::Backlog => "backlog",
+            CompletionStatus::Playing => "playing",
+            CompletionStatus::Completed => "completed",
+            CompletionStatus::Abandoned => "abandoned",
+        }
+    }
+
+    /// The one after, the first again after the last, for the menu.
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|s| *s == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// Rated this old and up, a game is for adults, badged as such.
+pub const MATURE_AGE: u8 = 17;
+
+/// Rating of the ESRB, for the Americas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Esrb {
+    EarlyChildhood,
+    Everyone,
+    Everyone10,
+    Teen,
+    Mature,
+    AdultsOnly,
+    Pending,
+}
+
+impl Esrb {
+    /// As the stores give it, e.g. `m` or `e10+`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "ec" => Some(Esrb::EarlyChildhood),
+            "e" => Some(Esrb::Everyone),
+            "e10+" => Some(Esrb::Everyone10),
+            "t" => Some(Esrb::Teen),
+            "m" => Some(Esrb::Mature),
+            "ao" => Some(Esrb::AdultsOnly),
+            "rp" => Some(Esrb::Pending),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Esrb::EarlyChildhood => "EC",
+            Esrb::Everyone => "E",
+            Esrb::Everyone10 => "E10+",
+            Esrb::Teen => "T",
+            Esrb::Mature => "M",
+            Esrb::AdultsOnly => "AO",
+            Esrb::Pending => "RP",
+        }
+    }
+
+    /// None while pending.
+    pub fn min_age(self) -> Option<u8> {
+        match self {
+            Esrb::EarlyChildhood => Some(3),
+            Esrb::Everyone => Some(6),
+            Esrb::Everyone10 => Some(10),
+            Esrb::Teen => Some(13),
+            Esrb::Mature => Some(17),
+            Esrb::AdultsOnly => Some(18),
+            Esrb::Pending => None,
+        }
+    }
+}
+
+/// The title and description in another language, from the scrapers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocalizedText {
+    pub title: Option<String>,
+    pub desc: Option<String>,
+}
+
+/// How to run a Windows game on Linux, see `launcher::compat`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompatConfig {
+    /// Name of the Proton or Wine version, e.g. `GE-Proton8-25`.
+    pub tool: String,
+    /// Wine prefix, one per game under the data dir when unset.
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+/// A game played in the browser, e.g. on Xbox Cloud Gaming or GeForce NOW,
+/// see `launcher::web_app`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebAppConfig {
+    pub url: String,
+    /// Browser profile, the games with the same one share their logins.
+    /// One per game when unset.
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Let the page see the controllers, sandboxed browsers don't otherwise.
+    #[serde(default = "default_gamepad")]
+    pub gamepad: bool,
+}
+
+fn default_gamepad() -> bool {
+    true
+}
+
+/// chrono::Duration has no serde support, stored as seconds.
+mod duration_secs {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        d: &Option<chrono::Duration>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        d.map(|d| d.num_seconds()).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Option<chrono::Duration>, D::Error> {
+        Ok(Option::<i64>::deserialize(d)?.map(chrono::Duration::seconds))
+    }
+}
+
+/// Saved in the library, see `library::migrations` before changing how.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameMetadata {
     /// Title of the game.
-    title: String,
+    pub title: String,
     /// Description of the game.
-    desc: Option<String>,
+    pub desc: Option<String>,
     /// Genres of the game, can be multiple.
     /// All lower case formatted.
-    genres: Vec<String>,
+    pub genres: Vec<String>,
     /// Release date.
     /// TZ unaware really.
-    relase_date: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "release_date")]
+    pub relase_date: Option<chrono::DateTime<chrono::Utc>>,
     /// Devs, publishers.
-    developers: Vec<String>,
-    publishers: Vec<String>,
+    pub developers: Vec<String>,
+    pub publishers: Vec<String>,
     /// The actually platform
-    platform: Option<String>,
+    pub platform: Option<String>,
     /// Links if any.
-    links: Vec<String>,
+    pub links: Vec<String>,
     /// User defined tags.
-    tags: Vec<String>,
+    pub tags: Vec<String>,
     /// Cover art to display.
-    cover_art: Option<ImageSource>,
+    pub cover_art: Option<ImageSource>,
     /// Bg art to display.
-    bg_art: Option<ImageSource>,
+    pub bg_art: Option<ImageSource>,
     /// Playtime.
-    playtime: Option<chrono::Duration>,
+    #[serde(with = "duration_secs")]
+    pub playtime: Option<chrono::Duration>,
     /// Fav.
-    favorate: bool,
-    /// UUID. Required for all games, given by the application.
-    uuid: Option<String>,
+    #[serde(rename = "favorite")]
+    pub favorate: bool,
+    /// UUID. Required for all games, given by the library on insert.
+    /// Empty until then.
+    pub uuid: String,
     /// Install source.
-    install_source: Option<String>,
+    /// Formatted as `${SOURCE}:${ID}`, e.g. `steam:440`.
+    pub install_source: Option<String>,
     /// Launch options.
-    launch_options: Vec<String>,
-}
\ No newline at end of file
+    pub launch_options: Vec<String>,
+    /// Run the launch options through Proton or Wine, natively when unset.
+    pub compat: Option<CompatConfig>,
+    /// Open a page in the browser instead, for cloud gaming.
+    pub web_app: Option<WebAppConfig>,
+    /// Show the performance overlay, see `launcher::overlay`.
+    pub overlay: bool,
+    /// Game ID on retroachievements.org, for emulated games.
+    pub retroachievements_id: Option<u32>,
+    /// Name of the library root it was found under, see `library::roots`.
+    pub root: Option<String>,
+    /// Backlog until set otherwise.
+    pub status: CompletionStatus,
+    /// The player's own, e.g. cheat codes or where they saved last.
+    pub notes: Option<String>,
+    /// Title and description by locale, e.g. `fr`, when the source has them.
+    pub localized: HashMap<String, LocalizedText>,
+    pub esrb: Option<Esrb>,
+    /// Age of the PEGI rating, for Europe, e.g. 16.
+    pub pegi: Option<u8>,
+    /// Named by the player, e.g. "Retro", see `library::bulk`.
+    pub collection: Option<String>,
+}
+
+impl GameMetadata {
+    pub fn set_art(&mut self, kind: ArtKind, source: Option<ImageSource>) {
+        match kind {
+            ArtKind::Cover => self.cover_art = source,
+            ArtKind::Background => self.bg_art = source,
+        }
+    }
+
+    /// The variant for the locale, or one in the same language, e.g. `fr`
+    /// for `fr-CA`.
+    fn localized(&self, locale: &str) -> Option<&LocalizedText> {
+        let language = locale.split(['-', '_']).next().unwrap_or_default();
+        self.localized.get(locale).or_else(|| {
+            self.localized
+                .iter()
+                .find(|(l, _)| l.split(['-', '_']).next() == Some(language))
+                .map(|(_, text)| text)
+        })
+    }
+
+    /// The rating for the region of the locale, e.g. `ESRB M` for `en-US`
+    /// or `PEGI 18` for `fr`, the other one when it's missing.
+    pub fn rating_badge(&self, locale: &str) -> Option<String> {
+        let esrb = self.esrb.map(|r| format!("ESRB {}", r.label()));
+        let pegi = self.pegi.map(|age| format!("PEGI {}", age));
+        let region = locale.split(['-', '_']).nth(1).unwrap_or_default();
+        if ["US", "CA", "MX"].contains(&region) {
+            esrb.or(pegi)
+        } else {
+            pegi.or(esrb)
+        }
+    }
+
+    /// The youngest it's rated for, by the stricter rating.
+    pub fn min_age(&self) -> Option<u8> {
+        self.esrb.and_then(Esrb::min_age).max(self.pegi)
+    }
+
+    /// The title in the locale, the one scraped first when it's missing.
+    pub fn title_in(&self, locale: &str) -> &str {
+        self.localized(locale)
+            .and_then(|t| t.title.as_deref())
+            .unwrap_or(&self.title)
+    }
+
+    /// The description in the locale, the one scraped first when it's missing.
+    pub fn desc_in(&self, locale: &str) -> Option<&str> {
+        self.localized(locale)
+            .and_then(|t| t.desc.as_deref())
+            .or(self.desc.as_deref())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_when_the_locale_is_missing() {
+        let mut game = GameMetadata {
+            title: "The Witness".to_owned(),
+            desc: Some("An island.".to_owned()),
+            ..Default::default()
+        };
+        game.localized.insert(
+            "fr".to_owned(),
+            LocalizedText {
+                title: None,
+                desc: Some("Une île.".to_owned()),
+            },
+        );
+        assert_eq!(game.title_in("fr-CA"), "The Witness");
+        assert_eq!(game.desc_in("fr-CA"), Some("Une île."));
+        assert_eq!(game.desc_in("en-US"), Some("An island."));
+    }
+
+    #[test]
+    fn badges_the_rating_of_the_region() {
+        let mut game = GameMetadata {
+            esrb: Esrb::parse("T"),
+            pegi: Some(18),
+            ..Default::default()
+        };
+        assert_eq!(game.rating_badge("en-US").as_deref(), Some("ESRB T"));
+        assert_eq!(game.rating_badge("fr").as_deref(), Some("PEGI 18"));
+        assert_eq!(game.min_age(), Some(18));
+        game.pegi = None;
+        assert_eq!(game.rating_badge("fr").as_deref(), Some("ESRB T"));
+        assert_eq!(game.min_age(), Some(13));
+    }
+}
@@ -1 +1,6 @@
-mod game_metadata;
\ No newline at end of file
+mod game_metadata;
+
+pub use self::game_metadata::{
+    ArtKind, CompatConfig, CompletionStatus, Esrb, GameMetadata, ImageSource, LocalizedText,
+    WebAppConfig, MATURE_AGE,
+};
@@ -0,0 +1,377 @@
+use crate::{
+    bus::{Bus, Event},
+    mdns, paths,
+};
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use rumqttc::{Client, LastWill, MqttOptions, Outgoing, Packet, QoS, RecvTimeoutError};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+// Home automation over MQTT, e.g. Home Assistant: what's played is published
+// under `${TOPIC}/`, retained, and commands are taken on `${TOPIC}/command`.
+// Home Assistant finds the entities by itself, see `discovery`.
+//
+//   anubis/availability   online, offline
+//   anubis/state          playing, idle
+//   anubis/playing        {"uuid": "…", "title": "Celeste"}, empty when idle
+//   anubis/notification   the toasts, not retained
+//   anubis/command        {"command": "launch", "uuid": "…"}
+//                         {"command": "suspend"}
+//                         {"command": "pause_music"}
+
+/// Between checks of the config while the broker is quiet.
+const POLL: Duration = Duration::from_millis(500);
+
+/// Before connecting again after an error.
+const RETRY: Duration = Duration::from_secs(5);
+
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+fn default_broker() -> String {
+    "localhost:1883".to_owned()
+}
+
+fn default_topic() -> String {
+    "anubis".to_owned()
+}
+
+fn default_discovery() -> bool {
+    true
+}
+
+/// Off unless enabled, set from the settings.
+///
+/// ```yaml
+/// enabled: true
+/// broker: homeassistant.local:1883
+/// username: anubis
+/// password: hunter2
+/// topic: anubis
+/// # Announce the entities to Home Assistant.
+/// discovery: true
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MqttConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// `host:port`, 1883 without a port.
+    #[serde(default = "default_broker")]
+    pub broker: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default = "default_topic")]
+    pub topic: String,
+    #[serde(default = "default_discovery")]
+    pub discovery: bool,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            broker: default_broker(),
+            username: None,
+            password: None,
+            topic: default_topic(),
+            discovery: default_discovery(),
+        }
+    }
+}
+
+impl MqttConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Readable by the user only, it has the password.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        Ok(())
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::config_dir().join("mqtt.yaml")
+    }
+
+    fn host_port(&self) -> Result<(String, u16)> {
+        match self.broker.rsplit_once(':') {
+            Some((host, port)) => Ok((host.to_owned(), port.parse()?)),
+            None if !self.broker.is_empty() => Ok((self.broker.clone(), 1883)),
+            None => Err(anyhow!("no MQTT broker")),
+        }
+    }
+}
+
+/// The body of a message on `${TOPIC}/command`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum MqttCommand {
+    Launch {
+        uuid: String,
+    },
+    /// The game played, as with the suspend chord.
+    Suspend,
+    /// Whatever media player is playing on the box.
+    PauseMusic,
+}
+
+/// Over MPRIS, for every player.
+pub fn pause_music() -> Result<()> {
+    let status = Command::new("playerctl")
+        .args(["--all-players", "pause"])
+        .status()?;
+    if !status.success() {
+        return Err(anyhow!("playerctl failed with {}", status));
+    }
+    Ok(())
+}
+
+/// What's published of an event, topic and whether it's retained.
+fn message(topic: &str, event: &Event) -> Vec<(String, String, bool)> {
+    match event {
+        Event::Playing { uuid, title } => vec![
+            (format!("{}/state", topic), "playing".to_owned(), true),
+            (
+                format!("{}/playing", topic),
+                json!({ "uuid": uuid, "title": title }).to_string(),
+                true,
+            ),
+        ],
+        Event::Idle => vec![
+            (format!("{}/state", topic), "idle".to_owned(), true),
+            (format!("{}/playing", topic), String::new(), true),
+        ],
+        Event::Notification { message } => {
+            vec![(format!("{}/notification", topic), message.clone(), false)]
+        }
+        Event::Focus { .. } => vec![],
+    }
+}
+
+/// The retained configs Home Assistant makes the entities from: whether a
+/// game runs, its title, and buttons to suspend it and pause the music.
+fn discovery(topic: &str) -> Vec<(String, String)> {
+    let host = mdns::hostname();
+    let id = format!(
+        "anubis_{}",
+        host.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+    );
+    let device = json!({
+        "identifiers": [id],
+        "name": format!("Anubis ({})", host),
+        "sw_version": env!("CARGO_PKG_VERSION"),
+    });
+    let availability = format!("{}/availability", topic);
+    let command = format!("{}/command", topic);
+    let entities = [
+        (
+            "binary_sensor",
+            "running",
+            json!({
+                "name": "Game running",
+                "state_topic": format!("{}/state", topic),
+                "payload_on": "playing",
+                "payload_off": "idle",
+            }),
+        ),
+        (
+            "sensor",
+            "game",
+            json!({
+                "name": "Game",
+                "state_topic": format!("{}/playing", topic),
+                "value_template": "{{ value_json.title if value else 'None' }}",
+            }),
+        ),
+        (
+            "button",
+            "suspend",
+            json!({
+                "name": "Suspend game",
+                "command_topic": command,
+                "payload_press": json!({ "command": "suspend" }).to_string(),
+            }),
+        ),
+        (
+            "button",
+            "pause_music",
+            json!({
+                "name": "Pause music",
+                "command_topic": command,
+                "payload_press": json!({ "command": "pause_music" }).to_string(),
+            }),
+        ),
+    ];
+    entities
+        .into_iter()
+        .map(|(component, object, mut config)| {
+            config["unique_id"] = json!(format!("{}_{}", id, object));
+            config["availability_topic"] = json!(availability);
+            config["device"] = device.clone();
+            (
+                format!("homeassistant/{}/{}/{}/config", component, id, object),
+                config.to_string(),
+            )
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+pub struct Mqtt {
+    /// Shared with the settings, connected again when it changes.
+    config: Arc<Mutex<MqttConfig>>,
+    bus: Bus,
+    commands: mpsc::Sender<MqttCommand>,
+}
+
+impl Mqtt {
+    pub fn new(
+        config: Arc<Mutex<MqttConfig>>,
+        bus: Bus,
+        commands: mpsc::Sender<MqttCommand>,
+    ) -> Self {
+        Self {
+            config,
+            bus,
+            commands,
+        }
+    }
+
+    /// Stay connected while enabled, until the navigation is gone. Meant to
+    /// run on a thread of its own, see the supervisor.
+    pub fn run(&self) -> Result<()> {
+        loop {
+            let config = self.config.lock().unwrap().clone();
+            if !config.enabled {
+                thread::sleep(POLL);
+                continue;
+            }
+            match self.connect(&config) {
+                Ok(true) => info!("MQTT settings changed, connecting again"),
+                Ok(false) => return Ok(()),
+                Err(e) => {
+                    warn!("MQTT failed: {:#}", e);
+                    thread::sleep(RETRY);
+                }
+            }
+        }
+    }
+
+    /// Until the config changes, true then, or the navigation is gone.
+    fn connect(&self, config: &MqttConfig) -> Result<bool> {
+        let (host, port) = config.host_port()?;
+        let mut options = MqttOptions::new(format!("anubis-{}", mdns::hostname()), host, port);
+        options.set_keep_alive(KEEP_ALIVE);
+        if let Some(ref username) = config.username {
+            options.set_credentials(username, config.password.clone().unwrap_or_default());
+        }
+        let availability = format!("{}/availability", config.topic);
+        options.set_last_will(LastWill::new(
+            &availability,
+            "offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+        let (client, mut connection) = Client::new(options, 64);
+        let mut events = self.bus.subscribe();
+        loop {
+            for event in events.try_iter() {
+                for (topic, payload, retain) in message(&config.topic, &event) {
+                    // Dropped while the broker can't be reached for long.
+                    if let Err(e) = client.try_publish(topic, QoS::AtLeastOnce, retain, payload) {
+                        warn!("failed to publish over MQTT: {}", e);
+                    }
+                }
+            }
+            match connection.recv_timeout(POLL) {
+                Ok(Ok(rumqttc::Event::Incoming(Packet::ConnAck(_)))) => {
+                    info!("connected to the MQTT broker {}", config.broker);
+                    client.subscribe(format!("{}/command", config.topic), QoS::AtLeastOnce)?;
+                    if config.discovery {
+                        for (topic, payload) in discovery(&config.topic) {
+                            client.publish(topic, QoS::AtLeastOnce, true, payload)?;
+                        }
+                    }
+                    client.publish(&availability, QoS::AtLeastOnce, true, "online")?;
+                    // The state again, the broker may have lost it.
+                    events = self.bus.subscribe();
+                }
+                Ok(Ok(rumqttc::Event::Incoming(Packet::Publish(publish)))) => {
+                    match serde_json::from_slice(&publish.payload) {
+                        Ok(command) => {
+                            if self.commands.send(command).is_err() {
+                                return Ok(false);
+                            }
+                        }
+                        Err(e) => warn!("bad MQTT command: {}", e),
+                    }
+                }
+                Ok(Ok(_)) | Err(RecvTimeoutError::Timeout) => {}
+                Ok(Err(e)) => {
+                    warn!("MQTT connection failed: {}", e);
+                    thread::sleep(RETRY);
+                }
+                Err(RecvTimeoutError::Disconnected) => return Ok(true),
+            }
+            if *self.config.lock().unwrap() != *config {
+                // Offline on purpose, the will is only sent when it's not.
+                client.publish(&availability, QoS::AtLeastOnce, true, "offline")?;
+                client.disconnect()?;
+                while let Ok(Ok(event)) = connection.recv_timeout(POLL) {
+                    if event == rumqttc::Event::Outgoing(Outgoing::Disconnect) {
+                        break;
+                    }
+                }
+                return Ok(true);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn publishes_what_is_played() {
+        let playing = Event::Playing {
+            uuid: "a".to_owned(),
+            title: "Celeste".to_owned(),
+        };
+        let published = message("anubis", &playing);
+        assert_eq!(
+            published[0],
+            ("anubis/state".to_owned(), "playing".to_owned(), true)
+        );
+        assert_eq!(published[1].0, "anubis/playing");
+        assert_eq!(message("anubis", &Event::Idle)[1].1, "");
+
+        assert_eq!(
+            serde_json::from_str::<MqttCommand>(r#"{"command": "launch", "uuid": "a"}"#).unwrap(),
+            MqttCommand::Launch {
+                uuid: "a".to_owned()
+            }
+        );
+        let config: MqttConfig = serde_yaml::from_str("broker: ha.local").unwrap();
+        assert_eq!(config.host_port().unwrap(), ("ha.local".to_owned(), 1883));
+    }
+}
@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+// Well known directories, following the XDG base directory spec.
+
+fn xdg_home(var: &str, fallback: &str) -> PathBuf {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(fallback)))
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+fn xdg_dir(var: &str, fallback: &str) -> PathBuf {
+    xdg_home(var, fallback).join("anubis")
+}
+
+/// `$XDG_CONFIG_HOME/anubis`, or `~/.config/anubis`.
+pub fn config_dir() -> PathBuf {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// `$XDG_DATA_HOME/anubis`, or `~/.local/share/anubis`.
+pub fn data_dir() -> PathBuf {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+/// `$XDG_CONFIG_HOME`, or `~/.config`.
+pub fn xdg_config_home() -> PathBuf {
+    xdg_home("XDG_CONFIG_HOME", ".config")
+}
+
+/// `$XDG_DATA_HOME`, or `~/.local/share`.
+pub fn xdg_data_home() -> PathBuf {
+    xdg_home("XDG_DATA_HOME", ".local/share")
+}
+
+/// `$XDG_DATA_HOME/applications`, where desktop entries go.
+pub fn applications_dir() -> PathBuf {
+    xdg_data_home().join("applications")
+}
+
+/// `$XDG_CACHE_HOME/anubis`, or `~/.cache/anubis`.
+pub fn cache_dir() -> PathBuf {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+/// `$XDG_RUNTIME_DIR/anubis`, for sockets. Falls back to the cache dir.
+pub fn runtime_dir() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|d| PathBuf::from(d).join("anubis"))
+        .unwrap_or_else(cache_dir)
+}
@@ -0,0 +1,367 @@
+use anyhow::{anyhow, bail, Result};
+use log::warn;
+use png::{BlendOp, ColorType, DisposeOp, FrameControl, Transformations};
+use serde::Deserialize;
+use std::{
+    fmt, fs,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+// The animated backgrounds of the games, shown behind the home screen for
+// the focused one. APNGs are decoded whole up front, videos are decoded as
+// they play by ffmpeg, scaled down, since a background doesn't need more.
+// Both play in a loop on a thread of their own, handing frames over until
+// stopped. Consoles on battery or with few cores only get the still art.
+
+/// Videos are scaled to this.
+const VIDEO_SIZE: (u32, u32) = (960, 540);
+/// Frames per second of the videos.
+const VIDEO_FPS: u32 = 24;
+/// Shorter delays are from broken files, browsers slow them down too.
+const MIN_DELAY: Duration = Duration::from_millis(20);
+/// Fewer cores than this, it's a low power device.
+const LOW_POWER_CPUS: usize = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Animated {
+    /// Unless on battery or on a slow CPU.
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+fn default_video_program() -> String {
+    "ffmpeg".to_owned()
+}
+
+/// ```yaml
+/// # auto, on or off.
+/// animated: auto
+/// video_program: ffmpeg
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaybackConfig {
+    #[serde(default)]
+    pub animated: Animated,
+    #[serde(default = "default_video_program")]
+    pub video_program: String,
+}
+
+impl Default for PlaybackConfig {
+    fn default() -> Self {
+        Self {
+            animated: Animated::default(),
+            video_program: default_video_program(),
+        }
+    }
+}
+
+impl PlaybackConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Whether backgrounds are played, or only their still art shown.
+    pub fn plays(&self) -> bool {
+        match self.animated {
+            Animated::Auto => !low_power(),
+            Animated::On => true,
+            Animated::Off => false,
+        }
+    }
+}
+
+fn low_power() -> bool {
+    let cpus = thread::available_parallelism().map_or(1, |n| n.get());
+    cpus < LOW_POWER_CPUS || on_battery()
+}
+
+fn on_battery() -> bool {
+    let Ok(supplies) = fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    supplies.flatten().any(|supply| {
+        fs::read_to_string(supply.path().join("status")).is_ok_and(|s| s.trim() == "Discharging")
+    })
+}
+
+/// What kind of animated background it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Animation {
+    Apng,
+    Video,
+}
+
+impl Animation {
+    /// Of the file, by the extension of its `name`, the URL for cached art.
+    /// None for still art.
+    pub fn of(path: &Path, name: &str) -> Option<Self> {
+        let extension = Path::new(name).extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "apng" => Some(Self::Apng),
+            // Most PNGs aren't animated.
+            "png" => is_apng(path).then_some(Self::Apng),
+            "mp4" | "webm" | "mkv" | "mov" | "m4v" => Some(Self::Video),
+            _ => None,
+        }
+    }
+}
+
+fn is_apng(path: &Path) -> bool {
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    png::Decoder::new(BufReader::new(file))
+        .read_info()
+        .is_ok_and(|r| r.info().animation_control().is_some())
+}
+
+/// A whole picture, shown for `delay`.
+#[derive(Clone)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    /// Shared by the loops of an APNG.
+    pub rgba: Arc<Vec<u8>>,
+    pub delay: Duration,
+}
+
+impl fmt::Debug for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Frame({}x{}, {:?})", self.width, self.height, self.delay)
+    }
+}
+
+fn delay(fc: &FrameControl) -> Duration {
+    // A denominator of 0 is 100, by the spec.
+    let den = if fc.delay_den == 0 { 100 } else { fc.delay_den };
+    Duration::from_millis(fc.delay_num as u64 * 1000 / den as u64).max(MIN_DELAY)
+}
+
+fn to_rgba(data: &[u8], color_type: ColorType) -> Result<Vec<u8>> {
+    Ok(match color_type {
+        ColorType::Rgba => data.to_vec(),
+        ColorType::Rgb => data
+            .chunks(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        ColorType::GrayscaleAlpha => data
+            .chunks(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        ColorType::Grayscale => data.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        ColorType::Indexed => bail!("palette left after expanding"),
+    })
+}
+
+/// Draw the frame's part of the picture on the canvas.
+fn compose(canvas: &mut [u8], width: u32, fc: &FrameControl, rgba: &[u8]) {
+    for y in 0..fc.height {
+        for x in 0..fc.width {
+            let from = ((y * fc.width + x) * 4) as usize;
+            let to = (((fc.y_offset + y) * width + fc.x_offset + x) * 4) as usize;
+            let (src, dst) = (&rgba[from..from + 4], &mut canvas[to..to + 4]);
+            let alpha = src[3] as u32;
+            match fc.blend_op {
+                BlendOp::Over if alpha < 255 => {
+                    for c in 0..3 {
+                        dst[c] =
+                            ((src[c] as u32 * alpha + dst[c] as u32 * (255 - alpha)) / 255) as u8;
+                    }
+                    dst[3] = (alpha + dst[3] as u32 * (255 - alpha) / 255) as u8;
+                }
+                _ => dst.copy_from_slice(src),
+            }
+        }
+    }
+}
+
+fn clear(canvas: &mut [u8], width: u32, fc: &FrameControl) {
+    for y in fc.y_offset..fc.y_offset + fc.height {
+        let start = ((y * width + fc.x_offset) * 4) as usize;
+        canvas[start..start + fc.width as usize * 4].fill(0);
+    }
+}
+
+/// Every frame of an APNG, put together.
+pub fn decode_apng(r: impl Read) -> Result<Vec<Frame>> {
+    let mut decoder = png::Decoder::new(r);
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let (width, height) = (reader.info().width, reader.info().height);
+    let num_frames = match reader.info().animation_control() {
+        Some(actl) => actl.num_frames as usize,
+        None => bail!("not animated"),
+    };
+    // Without a frame control, the default image isn't part of it.
+    let mut skip_default = reader.info().frame_control().is_none();
+    let mut canvas = vec![0; (width * height * 4) as usize];
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let mut frames = vec![];
+    while frames.len() < num_frames {
+        let out = reader.next_frame(&mut buf)?;
+        if skip_default {
+            skip_default = false;
+            continue;
+        }
+        let fc = *reader
+            .info()
+            .frame_control()
+            .ok_or_else(|| anyhow!("frame {} has no frame control", frames.len()))?;
+        let rgba = to_rgba(&buf[..out.buffer_size()], out.color_type)?;
+        let previous = (fc.dispose_op == DisposeOp::Previous).then(|| canvas.clone());
+        compose(&mut canvas, width, &fc, &rgba);
+        frames.push(Frame {
+            width,
+            height,
+            rgba: Arc::new(canvas.clone()),
+            delay: delay(&fc),
+        });
+        match (fc.dispose_op, previous) {
+            (DisposeOp::Background, _) => clear(&mut canvas, width, &fc),
+            (DisposeOp::Previous, Some(previous)) => canvas = previous,
+            _ => (),
+        }
+    }
+    Ok(frames)
+}
+
+/// Plays a background on its own thread, until dropped.
+pub struct Player {
+    stop: Arc<AtomicBool>,
+}
+
+impl Player {
+    /// Hands the frames to `emit` when they're due, until it gives false.
+    pub fn start(
+        animation: Animation,
+        path: PathBuf,
+        config: &PlaybackConfig,
+        mut emit: impl FnMut(Frame) -> bool + Send + 'static,
+    ) -> Result<Self> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let program = config.video_program.clone();
+        thread::Builder::new()
+            .name("playback".to_owned())
+            .spawn(move || {
+                let mut emit = |frame: Frame| {
+                    let delay = frame.delay;
+                    let go_on = !stopped.load(Ordering::Relaxed) && emit(frame);
+                    thread::sleep(delay);
+                    go_on && !stopped.load(Ordering::Relaxed)
+                };
+                let played = match animation {
+                    Animation::Apng => play_apng(&path, &mut emit),
+                    Animation::Video => play_video(&path, &program, &mut emit),
+                };
+                if let Err(e) = played {
+                    warn!("failed to play {:?}: {:?}", path, e);
+                }
+            })?;
+        Ok(Self { stop })
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn play_apng(path: &Path, emit: &mut impl FnMut(Frame) -> bool) -> Result<()> {
+    let frames = decode_apng(BufReader::new(fs::File::open(path)?))?;
+    loop {
+        for frame in &frames {
+            if !emit(frame.clone()) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Looped by ffmpeg, read a frame at a time.
+fn play_video(path: &Path, program: &str, emit: &mut impl FnMut(Frame) -> bool) -> Result<()> {
+    let (width, height) = VIDEO_SIZE;
+    let mut child = Command::new(program)
+        .args(["-loglevel", "error", "-stream_loop", "-1", "-i"])
+        .arg(path)
+        .args(["-an", "-vf", &format!("scale={}:{}", width, height)])
+        .args(["-r", &VIDEO_FPS.to_string()])
+        .args(["-f", "rawvideo", "-pix_fmt", "rgba", "-"])
+        .stdout(Stdio::piped())
+        .stdin(Stdio::null())
+        .spawn()?;
+    let mut stdout = child.stdout.take().unwrap();
+    let played = loop {
+        let mut rgba = vec![0; (width * height * 4) as usize];
+        if let Err(e) = stdout.read_exact(&mut rgba) {
+            break Err(e.into());
+        }
+        let frame = Frame {
+            width,
+            height,
+            rgba: Arc::new(rgba),
+            delay: Duration::from_secs(1) / VIDEO_FPS,
+        };
+        if !emit(frame) {
+            break Ok(());
+        }
+    };
+    child.kill()?;
+    child.wait()?;
+    played
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const CLEAR: [u8; 4] = [0; 4];
+
+    #[test]
+    fn puts_the_frames_together() -> Result<()> {
+        let mut apng = vec![];
+        let mut encoder = png::Encoder::new(&mut apng, 2, 1);
+        encoder.set_color(ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(2, 0)?;
+        let mut writer = encoder.write_header()?;
+        writer.set_frame_delay(1, 10)?;
+        writer.write_image_data(&[RED, CLEAR].concat())?;
+        // Drawn over the first one.
+        writer.set_frame_delay(1, 5)?;
+        writer.set_blend_op(BlendOp::Over)?;
+        writer.write_image_data(&[CLEAR, BLUE].concat())?;
+        writer.finish()?;
+
+        let frames = decode_apng(apng.as_slice())?;
+        assert_eq!(frames.len(), 2);
+        assert_eq!(*frames[0].rgba, [RED, CLEAR].concat());
+        assert_eq!(frames[0].delay, Duration::from_millis(100));
+        assert_eq!(*frames[1].rgba, [RED, BLUE].concat());
+        assert_eq!(frames[1].delay, Duration::from_millis(200));
+
+        assert_eq!(
+            Animation::of(Path::new("/nowhere"), "a/b.WebM"),
+            Some(Animation::Video)
+        );
+        assert_eq!(Animation::of(Path::new("/nowhere"), "b.png"), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use self::discord::DiscordIpc;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::debug;
+use serde::Deserialize;
+use serde_json::json;
+use std::{collections::HashMap, fs, path::Path, sync::mpsc, thread};
+
+mod discord;
+
+fn default_profile() -> String {
+    "default".to_owned()
+}
+
+fn yes() -> bool {
+    true
+}
+
+/// What gets shared, per profile.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PresenceSettings {
+    #[serde(default = "yes")]
+    pub enabled: bool,
+    #[serde(default = "yes")]
+    pub show_elapsed: bool,
+}
+
+impl Default for PresenceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            show_elapsed: true,
+        }
+    }
+}
+
+/// Rich presence settings. Profiles without an entry share everything.
+///
+/// ```yaml
+/// client_id: "1234567890"
+/// profile: streaming
+/// profiles:
+///   streaming:
+///     enabled: false
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PresenceConfig {
+    /// Discord application the presence shows up as, presence is off without one.
+    pub client_id: Option<String>,
+    #[serde(default = "default_profile")]
+    pub profile: String,
+    #[serde(default)]
+    pub profiles: HashMap<String, PresenceSettings>,
+}
+
+impl PresenceConfig {
+    /// Load the config, a missing file means no presence.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Settings of the active profile.
+    pub fn settings(&self) -> PresenceSettings {
+        self.profiles
+            .get(&self.profile)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+enum PresenceUpdate {
+    Playing {
+        title: String,
+        started: DateTime<Utc>,
+    },
+    Idle,
+}
+
+/// Publishes the game being played to Discord.
+/// Talking to Discord happens on a worker thread, a no-op when disabled.
+pub struct Presence {
+    tx: Option<mpsc::Sender<PresenceUpdate>>,
+}
+
+impl Presence {
+    pub fn new(config: PresenceConfig) -> Self {
+        let settings = config.settings();
+        let tx = match config.client_id {
+            Some(client_id) if settings.enabled => {
+                let (tx, rx) = mpsc::channel();
+                thread::spawn(move || presence_thread(client_id, settings, rx));
+                Some(tx)
+            }
+            _ => None,
+        };
+        Self { tx }
+    }
+
+    fn send(&self, update: PresenceUpdate) {
+        if let Some(ref tx) = self.tx {
+            let _ = tx.send(update);
+        }
+    }
+
+    pub fn playing(&self, title: &str) {
+        self.send(PresenceUpdate::Playing {
+            title: title.to_owned(),
+            started: Utc::now(),
+        });
+    }
+
+    pub fn clear(&self) {
+        self.send(PresenceUpdate::Idle);
+    }
+}
+
+/// Connect if needed, then set the activity.
+fn set_activity(
+    ipc: &mut Option<DiscordIpc>,
+    client_id: &str,
+    activity: Option<serde_json::Value>,
+) -> Result<()> {
+    if ipc.is_none() {
+        *ipc = Some(DiscordIpc::connect(client_id)?);
+    }
+    let res = ipc.as_mut().unwrap().set_activity(activity);
+    if res.is_err() {
+        *ipc = None;
+    }
+    res
+}
+
+fn presence_thread(
+    client_id: String,
+    settings: PresenceSettings,
+    rx: mpsc::Receiver<PresenceUpdate>,
+) {
+    let mut ipc = None;
+    while let Ok(update) = rx.recv() {
+        let activity = match update {
+            PresenceUpdate::Playing { title, started } => {
+                let mut activity = json!({ "details": title });
+                if settings.show_elapsed {
+                    activity["timestamps"] = json!({ "start": started.timestamp() });
+                }
+                Some(activity)
+            }
+            PresenceUpdate::Idle => None,
+        };
+        // The connection is stale when Discord got restarted, try again once.
+        let res = set_activity(&mut ipc, &client_id, activity.clone())
+            .or_else(|_| set_activity(&mut ipc, &client_id, activity));
+        if let Err(e) = res {
+            // Most likely Discord isn't running.
+            debug!("failed to update the discord presence: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_profile_settings() {
+        let config: PresenceConfig = serde_yaml::from_str(
+            "client_id: \"1234\"\nprofile: streaming\nprofiles:\n  streaming:\n    enabled: false\n",
+        )
+        .unwrap();
+        let settings = config.settings();
+        assert!(!settings.enabled);
+        assert!(settings.show_elapsed);
+
+        let config = PresenceConfig {
+            profile: "other".to_owned(),
+            ..config
+        };
+        assert!(config.settings().enabled);
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::{bail, Context, Result};
+use std::process::Command;
+
+// What the quick settings panel changes, through the usual desktop tools:
+// brightnessctl for the screen, wpctl for PipeWire's default output,
+// nmcli for Wi-Fi and bluetoothctl for Bluetooth. Those missing leave their
+// setting unavailable.
+
+/// How much a slider moves per press, of the whole range.
+pub const STEP: f32 = 0.1;
+/// Not all the way down, a black screen can't be turned back up.
+const MIN_BRIGHTNESS: f32 = 0.05;
+
+/// What the system reports, None when it can't be read.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuickSettings {
+    /// Within [0, 1].
+    pub brightness: Option<f32>,
+    /// Within [0, 1].
+    pub volume: Option<f32>,
+    pub wifi: Option<bool>,
+    pub bluetooth: Option<bool>,
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("failed to run {}", program))?;
+    if !output.status.success() {
+        bail!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// From `brightnessctl -m`, e.g. `intel_backlight,backlight,420,42%,1000`.
+fn parse_brightness(output: &str) -> Option<f32> {
+    let fields: Vec<&str> = output.lines().next()?.split(',').collect();
+    let current: f32 = fields.get(2)?.parse().ok()?;
+    let max: f32 = fields.get(4)?.parse().ok()?;
+    (max > 0.0).then(|| current / max)
+}
+
+/// From `wpctl get-volume`, e.g. `Volume: 0.40 [MUTED]`. Muted is 0.
+fn parse_volume(output: &str) -> Option<f32> {
+    let volume: f32 = output.split_whitespace().nth(1)?.parse().ok()?;
+    Some(if output.contains("[MUTED]") { 0.0 } else { volume })
+}
+
+/// From `bluetoothctl show`, its `Powered: yes` line.
+fn parse_powered(output: &str) -> Option<bool> {
+    let line = output.lines().find(|l| l.trim().starts_with("Powered:"))?;
+    Some(line.trim().ends_with("yes"))
+}
+
+impl QuickSettings {
+    pub fn read() -> Self {
+        Self {
+            brightness: run("brightnessctl", &["-m"])
+                .ok()
+                .and_then(|o| parse_brightness(&o)),
+            volume: run("wpctl", &["get-volume", "@DEFAULT_AUDIO_SINK@"])
+                .ok()
+                .and_then(|o| parse_volume(&o)),
+            wifi: run("nmcli", &["radio", "wifi"])
+                .ok()
+                .map(|o| o.trim() == "enabled"),
+            bluetooth: run("bluetoothctl", &["show"])
+                .ok()
+                .and_then(|o| parse_powered(&o)),
+        }
+    }
+}
+
+pub fn set_brightness(brightness: f32) -> Result<()> {
+    let percent = (brightness.clamp(MIN_BRIGHTNESS, 1.0) * 100.0).round();
+    run("brightnessctl", &["set", &format!("{}%", percent)])?;
+    Ok(())
+}
+
+/// Unmuted too, turning it up should be heard.
+pub fn set_volume(volume: f32) -> Result<()> {
+    let volume = format!("{:.2}", volume.clamp(0.0, 1.0));
+    run("wpctl", &["set-volume", "@DEFAULT_AUDIO_SINK@", &volume])?;
+    run("wpctl", &["set-mute", "@DEFAULT_AUDIO_SINK@", "0"])?;
+    Ok(())
+}
+
+pub fn set_wifi(on: bool) -> Result<()> {
+    run("nmcli", &["radio", "wifi", if on { "on" } else { "off" }])?;
+    Ok(())
+}
+
+pub fn set_bluetooth(on: bool) -> Result<()> {
+    run("bluetoothctl", &["power", if on { "on" } else { "off" }])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_tools_output() {
+        assert_eq!(
+            parse_brightness("intel_backlight,backlight,420,42%,1000\n"),
+            Some(0.42)
+        );
+        assert_eq!(parse_brightness(""), None);
+        assert_eq!(parse_volume("Volume: 0.40\n"), Some(0.4));
+        assert_eq!(parse_volume("Volume: 0.40 [MUTED]\n"), Some(0.0));
+        assert_eq!(
+            parse_powered("Controller 00:1A:7D:DA:71:13 (public)\n\tPowered: yes\n"),
+            Some(true)
+        );
+        assert_eq!(parse_powered("No default controller available\n"), None);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::paths;
+use anyhow::Result;
+use chrono::{Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+// Quiet hours, for a living room at night: between `start` and `end` the UI
+// is dimmed. anubis makes no sounds and doesn't rumble the pads yet, those
+// should check `Navigator::quiet` once they do. The schedule can be
+// overridden from the settings until it next changes.
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// ```yaml
+/// enabled: true
+/// start: "22:00"
+/// end: "07:30"
+/// # Of the UI while quiet, within [0, 1].
+/// brightness: 0.5
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuietConfig {
+    pub enabled: bool,
+    pub start: NaiveTime,
+    /// The next day when before `start`.
+    pub end: NaiveTime,
+    pub brightness: f32,
+}
+
+impl Default for QuietConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            brightness: 0.5,
+        }
+    }
+}
+
+impl QuietConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::config_dir().join("quiet.yaml")
+    }
+
+    /// Whether the schedule has `time` in the quiet hours.
+    pub fn is_quiet(&self, time: NaiveTime) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// How much the UI is darkened, 0 when it isn't.
+    pub fn dim(&self, quiet: bool) -> f32 {
+        match quiet {
+            true => 1.0 - self.brightness.clamp(0.0, 1.0),
+            false => 0.0,
+        }
+    }
+}
+
+/// Report whether it's quiet hours now and whenever that changes, in the
+/// background. Stops once `on_change` returns false.
+pub fn watch(config: QuietConfig, on_change: impl Fn(bool) -> bool + Send + 'static) {
+    thread::spawn(move || {
+        let mut quiet = None;
+        loop {
+            let now = config.is_quiet(Local::now().time());
+            if quiet != Some(now) {
+                quiet = Some(now);
+                if !on_change(now) {
+                    return;
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiet_hours_span_midnight() {
+        let config: QuietConfig =
+            serde_yaml::from_str("enabled: true\nstart: \"22:00\"\nend: \"07:30\"\n").unwrap();
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert!(config.is_quiet(at(23, 0)));
+        assert!(config.is_quiet(at(3, 0)));
+        assert!(config.is_quiet(at(7, 29)));
+        assert!(!config.is_quiet(at(7, 30)));
+        assert!(!config.is_quiet(at(12, 0)));
+        assert_eq!(config.dim(true), 0.5);
+
+        let day = QuietConfig {
+            start: at(13, 0),
+            end: at(15, 0),
+            ..config.clone()
+        };
+        assert!(day.is_quiet(at(14, 0)));
+        assert!(!day.is_quiet(at(23, 0)));
+        assert!(!QuietConfig::default().is_quiet(at(23, 0)));
+    }
+}
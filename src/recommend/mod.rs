@@ -0,0 +1,225 @@
+use crate::{models::GameMetadata, paths};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    thread,
+};
+
+// What to play, for the row at the top of the home screen: favorites not
+// played much yet, games not played in a long while, and games sharing
+// genres with those played lately. Worked out again every day in the
+// background, suggestions as good as each other trading places from one day
+// to the next so the row doesn't always show the same.
+
+/// Suggested at most.
+const PICKS: usize = 9;
+/// Played this long, a game is likely finished.
+const FINISHED_HOURS: i64 = 15;
+/// Played since, a game is one of the recent ones.
+const RECENT_DAYS: i64 = 14;
+/// Not played since, a game is long unplayed.
+const UNPLAYED_DAYS: i64 = 60;
+
+/// When each game was last played, kept in the data dir.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayLog {
+    pub last_played: HashMap<String, DateTime<Utc>>,
+}
+
+impl PlayLog {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::data_dir().join("plays.yaml")
+    }
+
+    pub fn played(&mut self, uuid: &str, at: DateTime<Utc>) {
+        self.last_played.insert(uuid.to_owned(), at);
+    }
+}
+
+/// Why a game is suggested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    UnfinishedFavorite,
+    /// Not played in that many days, or ever since the log was kept.
+    LongUnplayed(Option<i64>),
+    /// Shares genres with this recently played game.
+    SimilarTo(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pick {
+    pub uuid: String,
+    pub reason: Reason,
+}
+
+/// The suggestions for the day, the best first.
+pub fn suggest(games: &[GameMetadata], log: &PlayLog, now: DateTime<Utc>) -> Vec<Pick> {
+    let played_days = |g: &GameMetadata| {
+        log.last_played
+            .get(&g.uuid)
+            .map(|at| (now - *at).num_days())
+    };
+    let playtime = |g: &GameMetadata| g.playtime.unwrap_or_else(Duration::zero);
+    let recent: Vec<&GameMetadata> = games
+        .iter()
+        .filter(|g| played_days(g).is_some_and(|d| d < RECENT_DAYS))
+        .collect();
+    let recent_genres: HashSet<&str> = recent
+        .iter()
+        .flat_map(|g| g.genres.iter().map(String::as_str))
+        .collect();
+
+    let mut scored: Vec<(u32, Pick)> = games
+        .iter()
+        .filter(|g| !recent.iter().any(|r| r.uuid == g.uuid))
+        .filter_map(|g| {
+            let days = played_days(g);
+            let started = days.is_some() || playtime(g) > Duration::zero();
+            let similar = recent
+                .iter()
+                .map(|r| {
+                    let shared = r.genres.iter().filter(|x| g.genres.contains(x)).count();
+                    (shared, *r)
+                })
+                .filter(|(shared, _)| *shared > 0)
+                .max_by_key(|(shared, r)| (*shared, Reverse(r.uuid.clone())));
+            let (score, reason) = if g.favorate && playtime(g).num_hours() < FINISHED_HOURS {
+                (30, Reason::UnfinishedFavorite)
+            } else if let Some((_, r)) = similar.filter(|_| !started) {
+                // Closer the more of the recent genres it has.
+                let overlap = g
+                    .genres
+                    .iter()
+                    .filter(|x| recent_genres.contains(x.as_str()))
+                    .count();
+                (20 + overlap as u32, Reason::SimilarTo(r.title.clone()))
+            } else if started && days.is_none_or(|d| d >= UNPLAYED_DAYS) {
+                (10, Reason::LongUnplayed(days))
+            } else {
+                return None;
+            };
+            Some((
+                score,
+                Pick {
+                    uuid: g.uuid.clone(),
+                    reason,
+                },
+            ))
+        })
+        .collect();
+    // Ties in a different order each day.
+    let day = now.with_timezone(&Local).date_naive().to_string();
+    scored.sort_by_key(|(score, pick)| {
+        (
+            Reverse(*score),
+            crc32fast::hash(format!("{}{}", day, pick.uuid).as_bytes()),
+        )
+    });
+    scored.into_iter().take(PICKS).map(|(_, p)| p).collect()
+}
+
+/// Give `emit` the suggestions now and again each day after midnight, until
+/// it gives false. Blocks, run it on a thread of its own.
+pub fn follow(
+    games: impl Fn() -> Vec<GameMetadata>,
+    log: impl Fn() -> PlayLog,
+    mut emit: impl FnMut(Vec<Pick>) -> bool,
+) -> Result<()> {
+    loop {
+        if !emit(suggest(&games(), &log(), Utc::now())) {
+            return Ok(());
+        }
+        thread::sleep(until_tomorrow(Local::now()));
+    }
+}
+
+/// Until a minute past the next midnight.
+fn until_tomorrow(now: DateTime<Local>) -> std::time::Duration {
+    let tomorrow = now
+        .date_naive()
+        .succ_opt()
+        .and_then(|d| d.and_hms_opt(0, 1, 0));
+    tomorrow
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+        .and_then(|t| (t - now).to_std().ok())
+        .unwrap_or(std::time::Duration::from_secs(24 * 60 * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn game(uuid: &str, genres: &[&str], hours: i64, favorite: bool) -> GameMetadata {
+        GameMetadata {
+            title: uuid.to_uppercase(),
+            uuid: uuid.to_owned(),
+            genres: genres.iter().map(|g| g.to_string()).collect(),
+            playtime: Some(Duration::hours(hours)),
+            favorate: favorite,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn suggests_unfinished_similar_and_forgotten_games() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let games = [
+            game("recent", &["platformer", "indie"], 3, false),
+            game("favorite", &["racing"], 2, true),
+            game("finished", &["racing"], 40, true),
+            game("similar", &["indie", "platformer"], 0, false),
+            game("forgotten", &["puzzle"], 8, false),
+            game("lately", &["puzzle"], 8, false),
+            game("unrelated", &["sports"], 0, false),
+        ];
+        let mut log = PlayLog::default();
+        log.played("recent", now - Duration::days(1));
+        log.played("forgotten", now - Duration::days(90));
+        log.played("lately", now - Duration::days(30));
+
+        let picks = suggest(&games, &log, now);
+        let reasons: Vec<_> = picks
+            .iter()
+            .map(|p| (p.uuid.as_str(), p.reason.clone()))
+            .collect();
+        assert_eq!(reasons[0], ("favorite", Reason::UnfinishedFavorite));
+        assert_eq!(
+            reasons[1],
+            ("similar", Reason::SimilarTo("RECENT".to_owned()))
+        );
+        // Equally good, in the order of the day.
+        let mut forgotten = reasons[2..].to_vec();
+        forgotten.sort_by_key(|(uuid, _)| *uuid);
+        assert_eq!(
+            forgotten,
+            [
+                ("finished", Reason::LongUnplayed(None)),
+                ("forgotten", Reason::LongUnplayed(Some(90)))
+            ]
+        );
+        assert_eq!(picks, suggest(&games, &log, now));
+        assert!(until_tomorrow(Local::now()) <= std::time::Duration::from_secs(25 * 60 * 60));
+    }
+}
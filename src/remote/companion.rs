@@ -0,0 +1,146 @@
+use crate::models::{ArtKind, CompletionStatus, GameMetadata};
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// Metadata editing from another device, typing on a TV is painful.
+
+/// Largest art upload accepted.
+pub const MAX_ART_SIZE: usize = 20 * 1024 * 1024;
+
+const ART_EXTENSIONS: [&str; 3] = ["png", "jpg", "webp"];
+
+/// Fields to change, missing ones are left as is. Not the launch options, that
+/// would run whatever the other device likes.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct GameEdit {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub platform: Option<String>,
+    pub genres: Option<Vec<String>>,
+    pub developers: Option<Vec<String>>,
+    pub publishers: Option<Vec<String>>,
+    pub tags: Option<Vec<String>>,
+    pub favorite: Option<bool>,
+    pub status: Option<CompletionStatus>,
+    pub notes: Option<String>,
+}
+
+impl GameEdit {
+    pub fn apply(self, game: &mut GameMetadata) -> Result<()> {
+        if let Some(title) = self.title {
+            let title = title.trim();
+            if title.is_empty() {
+                bail!("the title can't be empty");
+            }
+            game.title = title.to_owned();
+        }
+        if let Some(desc) = self.description {
+            game.desc = Some(desc).filter(|d| !d.is_empty());
+        }
+        if let Some(platform) = self.platform {
+            game.platform = Some(platform).filter(|p| !p.is_empty());
+        }
+        if let Some(genres) = self.genres {
+            game.genres = genres.iter().map(|g| g.to_lowercase()).collect();
+        }
+        if let Some(developers) = self.developers {
+            game.developers = developers;
+        }
+        if let Some(publishers) = self.publishers {
+            game.publishers = publishers;
+        }
+        if let Some(tags) = self.tags {
+            game.tags = tags;
+        }
+        if let Some(favorite) = self.favorite {
+            game.favorate = favorite;
+        }
+        if let Some(notes) = self.notes {
+            game.notes = Some(notes).filter(|n| !n.trim().is_empty());
+        }
+        if let Some(status) = self.status {
+            game.status = status;
+        }
+        Ok(())
+    }
+}
+
+fn extension_for(content_type: &str) -> Option<&'static str> {
+    match content_type.split(';').next()?.trim() {
+        "image/png" => Some("png"),
+        "image/jpeg" => Some("jpg"),
+        "image/webp" => Some("webp"),
+        _ => None,
+    }
+}
+
+/// Save uploaded art as `${UUID}-${KIND}.${EXT}`, replacing the previous one.
+pub fn save_art(
+    dir: &Path,
+    uuid: &str,
+    kind: ArtKind,
+    content_type: &str,
+    data: &[u8],
+) -> Result<PathBuf> {
+    let ext =
+        extension_for(content_type).ok_or(anyhow!("unsupported image type {}", content_type))?;
+    if data.is_empty() || data.len() > MAX_ART_SIZE {
+        bail!("art must be within 1 and {} bytes", MAX_ART_SIZE);
+    }
+    fs::create_dir_all(dir)?;
+    let name = |ext: &str| dir.join(format!("{}-{}.{}", uuid, kind.name(), ext));
+    for old in ART_EXTENSIONS {
+        let _ = fs::remove_file(name(old));
+    }
+    let path = name(ext);
+    fs::write(&path, data)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_edits() {
+        let mut game = GameMetadata {
+            title: "celeste".to_owned(),
+            desc: Some("old".to_owned()),
+            ..Default::default()
+        };
+        let edit: GameEdit = serde_json::from_str(
+            r#"{"title": " Celeste ", "description": "", "genres": ["Platformer"], "notes": " "}"#,
+        )
+        .unwrap();
+        edit.apply(&mut game).unwrap();
+        assert_eq!(game.title, "Celeste");
+        assert_eq!(game.desc, None);
+        assert_eq!(game.genres, vec!["platformer"]);
+        assert_eq!(game.notes, None);
+
+        let edit = GameEdit {
+            title: Some(" ".to_owned()),
+            ..Default::default()
+        };
+        assert!(edit.apply(&mut game).is_err());
+
+        let edit: GameEdit = serde_json::from_str(r#"{"launch_options": ["sh"]}"#).unwrap();
+        edit.apply(&mut game).unwrap();
+        assert!(game.launch_options.is_empty());
+    }
+
+    #[test]
+    fn replaces_art() {
+        let dir = std::env::temp_dir().join(format!("anubis-art-test-{}", std::process::id()));
+        save_art(&dir, "aaaa", ArtKind::Cover, "image/png", b"png").unwrap();
+        let path = save_art(&dir, "aaaa", ArtKind::Cover, "image/jpeg", b"jpg").unwrap();
+        assert_eq!(path, dir.join("aaaa-cover.jpg"));
+        assert!(!dir.join("aaaa-cover.png").exists());
+        assert!(save_art(&dir, "aaaa", ArtKind::Cover, "text/plain", b"txt").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,630 @@
+use self::{
+    companion::{GameEdit, MAX_ART_SIZE},
+    pairing::{PairRequest, Pairing, PAIR_WAIT},
+    tokens::{RemoteToken, RemoteTokens, SharedTokens},
+};
+use crate::{
+    bus::Bus,
+    controller::FocusId,
+    library::Library,
+    mdns,
+    models::{ArtKind, CompletionStatus, GameMetadata, ImageSource},
+};
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use gilrs::Button;
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use tiny_http::{Header, Method, ReadWrite, Request, Response, Server, SslConfig};
+use tungstenite::{handshake::derive_accept_key, protocol::Role, Message, WebSocket};
+
+mod companion;
+pub mod pairing;
+pub mod tokens;
+
+/// Remote control page, served at `/`.
+const REMOTE_PAGE: &str = include_str!("remote.html");
+
+/// Between pings on `/api/events` when nothing happens, to notice the apps
+/// gone.
+const KEEPALIVE: Duration = Duration::from_secs(30);
+
+/// Advertised over mDNS for companion apps, with the version of the API in
+/// the TXT record.
+pub const SERVICE_TYPE: &str = "_anubis-remote._tcp.local.";
+
+fn default_listen() -> String {
+    "0.0.0.0:7878".to_owned()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// PEM files.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TlsConfig {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+/// The server is off unless enabled.
+///
+/// ```yaml
+/// enabled: true
+/// listen: 0.0.0.0:7878
+/// # Over mDNS.
+/// advertise: true
+/// # Only paired apps get in, see `tokens`.
+/// require_token: true
+/// tls:
+///   cert: /etc/anubis/remote.crt
+///   key: /etc/anubis/remote.key
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_listen")]
+    pub listen: String,
+    #[serde(default = "default_true")]
+    pub advertise: bool,
+    #[serde(default = "default_true")]
+    pub require_token: bool,
+    /// Plain HTTP without.
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: default_listen(),
+            advertise: true,
+            require_token: true,
+            tls: None,
+        }
+    }
+}
+
+impl RemoteConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+/// What the remote asks the navigation thread to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteCommand {
+    /// Same as a gamepad button press.
+    Button(Button),
+    Focus(FocusId),
+    Launch(String),
+    /// The metadata of the game got edited.
+    GameChanged(String),
+    /// An app asks to pair, see `pairing`.
+    Pair(PairRequest),
+}
+
+/// Requests, the JSON body of `POST /api/action` or a websocket message.
+///
+/// ```json
+/// {"action": "button", "button": "up"}
+/// {"action": "search", "query": "celeste"}
+/// {"action": "search", "query": "", "status": "playing"}
+/// ```
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum Action {
+    Button {
+        button: String,
+    },
+    Focus {
+        uuid: String,
+    },
+    Launch {
+        uuid: String,
+    },
+    Search {
+        query: String,
+        #[serde(default)]
+        status: Option<CompletionStatus>,
+    },
+    Library,
+    Game {
+        uuid: String,
+    },
+    EditGame {
+        uuid: String,
+        changes: Box<GameEdit>,
+    },
+}
+
+/// Body of `POST /api/pair`.
+#[derive(Debug, Deserialize)]
+struct PairBody {
+    /// Of the app, shown in the dialog.
+    name: String,
+}
+
+fn parse_button(name: &str) -> Option<Button> {
+    Some(match name {
+        "up" => Button::DPadUp,
+        "down" => Button::DPadDown,
+        "left" => Button::DPadLeft,
+        "right" => Button::DPadRight,
+        "a" | "south" => Button::South,
+        "b" | "east" => Button::East,
+        "x" | "west" => Button::West,
+        "y" | "north" => Button::North,
+        "start" => Button::Start,
+        "select" => Button::Select,
+        _ => return None,
+    })
+}
+
+fn game_json(game: &GameMetadata) -> Value {
+    json!({
+        "uuid": game.uuid,
+        "title": game.title,
+        "platform": game.platform,
+        "description": game.desc,
+        "genres": game.genres,
+        "developers": game.developers,
+        "publishers": game.publishers,
+        "tags": game.tags,
+        "collection": game.collection,
+        "favorite": game.favorate,
+        "status": game.status,
+        "notes": game.notes,
+        "launch_options": game.launch_options,
+    })
+}
+
+/// Edits shouldn't fail because the disk did, they're kept in memory.
+fn save(library: &mut Library) {
+    if let Err(e) = library.save() {
+        warn!("failed to save the library: {:?}", e);
+    }
+}
+
+fn header(field: &str, value: &str) -> Header {
+    Header::from_bytes(field.as_bytes(), value.as_bytes()).unwrap()
+}
+
+fn json_response(status: u16, body: &Value) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(header("Content-Type", "application/json"))
+}
+
+/// Lets a phone browser act as a remote control, and edit the library.
+/// Input goes through the navigation thread, like the gamepad.
+#[derive(Clone)]
+pub struct RemoteServer {
+    library: Arc<Mutex<Library>>,
+    commands: mpsc::Sender<RemoteCommand>,
+    /// Where uploaded art goes.
+    art_dir: PathBuf,
+    pairing: Pairing,
+    config: RemoteConfig,
+    bus: Bus,
+    tokens: SharedTokens,
+}
+
+impl RemoteServer {
+    pub fn new(
+        library: Arc<Mutex<Library>>,
+        commands: mpsc::Sender<RemoteCommand>,
+        art_dir: PathBuf,
+        pairing: Pairing,
+        config: RemoteConfig,
+        bus: Bus,
+    ) -> Result<Self> {
+        Ok(Self {
+            library,
+            commands,
+            art_dir,
+            pairing,
+            config,
+            bus,
+            tokens: SharedTokens::load(RemoteTokens::default_path())?,
+        })
+    }
+
+    /// Serve until the server stops accepting connections, a thread per
+    /// connection, advertised over mDNS meanwhile if asked. Meant to run on a
+    /// thread of its own, see the supervisor.
+    pub fn serve(&self) -> Result<()> {
+        let listen = &self.config.listen;
+        let server = match self.config.tls {
+            Some(ref tls) => Server::https(
+                listen,
+                SslConfig {
+                    certificate: fs::read(&tls.cert)?,
+                    private_key: fs::read(&tls.key)?,
+                },
+            ),
+            None => Server::http(listen),
+        }
+        .map_err(|e| anyhow!("failed to listen on {}: {}", listen, e))?;
+        info!("remote control listening on {}", listen);
+        let _advertised = match server.server_addr().to_ip() {
+            Some(address) if self.config.advertise => {
+                let properties = HashMap::from([
+                    ("version".to_owned(), env!("CARGO_PKG_VERSION").to_owned()),
+                    ("api".to_owned(), "/api".to_owned()),
+                    ("tls".to_owned(), self.config.tls.is_some().to_string()),
+                ]);
+                mdns::advertise(SERVICE_TYPE, address.port(), properties)
+                    .map_err(|e| warn!("failed to advertise the remote control: {:?}", e))
+                    .ok()
+            }
+            _ => None,
+        };
+        let this = Arc::new(self.clone());
+        for request in server.incoming_requests() {
+            let this = this.clone();
+            thread::spawn(move || {
+                if let Err(e) = this.handle(request) {
+                    warn!("remote request failed: {:?}", e);
+                }
+            });
+        }
+        bail!("stopped accepting connections on {}", listen)
+    }
+
+    fn handle(&self, mut request: Request) -> Result<()> {
+        let path = request
+            .url()
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .to_owned();
+        // The page pairs itself.
+        let open = path == "/" || path.starts_with("/api/pair");
+        if !open && self.config.require_token && self.authorized(&request, &path).is_none() {
+            let error = json!({ "error": "not paired, see /api/pair" });
+            return Ok(request.respond(json_response(401, &error))?);
+        }
+        match (request.method(), path.as_str()) {
+            (Method::Get, "/") => request.respond(
+                Response::from_string(REMOTE_PAGE)
+                    .with_header(header("Content-Type", "text/html; charset=utf-8")),
+            )?,
+            (Method::Get, "/api/library") => {
+                let res = self.perform(Action::Library)?;
+                request.respond(json_response(200, &res))?
+            }
+            (Method::Post, "/api/action") => {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body)?;
+                let res = serde_json::from_str(&body)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|a| self.perform(a));
+                match res {
+                    Ok(v) => request.respond(json_response(200, &v))?,
+                    Err(e) => {
+                        request.respond(json_response(400, &json!({ "error": e.to_string() })))?
+                    }
+                }
+            }
+            (Method::Get, "/api/ws") => self.serve_websocket(request)?,
+            (Method::Get, "/api/events") => self.serve_events(request)?,
+            (Method::Post, "/api/pair") => {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body)?;
+                match serde_json::from_str::<PairBody>(&body) {
+                    Ok(PairBody { name }) => match self.pairing.request(&name) {
+                        Some(asked) => {
+                            let res = json!({ "id": asked.id, "code": asked.code });
+                            self.commands.send(RemoteCommand::Pair(asked))?;
+                            request.respond(json_response(200, &res))?
+                        }
+                        None => {
+                            let error =
+                                json!({ "error": "another app is pairing, try again later" });
+                            request.respond(json_response(409, &error))?
+                        }
+                    },
+                    Err(e) => {
+                        request.respond(json_response(400, &json!({ "error": e.to_string() })))?
+                    }
+                }
+            }
+            // Answered once the player did, with the token of the app.
+            (Method::Get, p) if p.starts_with("/api/pair/") => {
+                let id = &p["/api/pair/".len()..];
+                let res = match self.pairing.wait(id, PAIR_WAIT) {
+                    Ok(Some(name)) => self.issue_token(&name).map(|token| {
+                        info!("paired {} with the remote control", name);
+                        json!({ "paired": true, "token": token })
+                    }),
+                    Ok(None) => Ok(json!({ "paired": false })),
+                    Err(e) => {
+                        let error = json!({ "error": e.to_string() });
+                        return Ok(request.respond(json_response(408, &error))?);
+                    }
+                };
+                match res {
+                    Ok(v) => request.respond(json_response(200, &v))?,
+                    Err(e) => {
+                        request.respond(json_response(500, &json!({ "error": e.to_string() })))?
+                    }
+                }
+            }
+            // /api/games/${UUID}/art/${KIND}, the body is the image.
+            (Method::Post, p) if p.starts_with("/api/games/") => {
+                let res = match p.split('/').collect::<Vec<_>>()[..] {
+                    ["", "api", "games", uuid, "art", kind] => match ArtKind::parse(kind) {
+                        Some(kind) => self.upload_art(&mut request, uuid, kind),
+                        None => Err(anyhow!("unknown art kind {}", kind)),
+                    },
+                    _ => Err(anyhow!("unknown path {}", p)),
+                };
+                match res {
+                    Ok(v) => request.respond(json_response(200, &v))?,
+                    Err(e) => {
+                        request.respond(json_response(400, &json!({ "error": e.to_string() })))?
+                    }
+                }
+            }
+            _ => request.respond(Response::empty(404))?,
+        }
+        Ok(())
+    }
+
+    /// Who sent the request, by its token. In the query only for the
+    /// websocket and the events, which can't have headers, not to end up in
+    /// logs and history otherwise.
+    fn authorized(&self, request: &Request, path: &str) -> Option<RemoteToken> {
+        let bearer = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .and_then(|h| h.value.as_str().strip_prefix("Bearer ").map(str::to_owned));
+        let streams = ["/api/ws", "/api/events"];
+        let query = request
+            .url()
+            .split_once('?')
+            .filter(|_| streams.contains(&path));
+        let query = query.and_then(|(_, query)| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("token="))
+                .map(str::to_owned)
+        });
+        self.tokens.check(&bearer.or(query)?)
+    }
+
+    fn issue_token(&self, name: &str) -> Result<String> {
+        self.tokens.issue(name, Utc::now())
+    }
+
+    fn perform(&self, action: Action) -> Result<Value> {
+        let command = match action {
+            Action::Button { button } => match parse_button(&button) {
+                Some(b) => RemoteCommand::Button(b),
+                None => bail!("unknown button {}", button),
+            },
+            Action::Focus { uuid } => RemoteCommand::Focus(FocusId::game(&uuid)),
+            Action::Launch { uuid } => RemoteCommand::Launch(uuid),
+            Action::Search { query, status } => {
+                let library = self.library.lock().unwrap();
+                let games: Vec<_> = library
+                    .search(&query, status)
+                    .into_iter()
+                    .map(game_json)
+                    .collect();
+                return Ok(json!({ "games": games }));
+            }
+            Action::Library => {
+                let library = self.library.lock().unwrap();
+                let games: Vec<_> = library.visible().map(game_json).collect();
+                return Ok(json!({ "games": games }));
+            }
+            Action::Game { uuid } => {
+                let library = self.library.lock().unwrap();
+                let game = library
+                    .get(&uuid)
+                    .filter(|_| library.is_visible(&uuid))
+                    .ok_or(anyhow!("no game {}", uuid))?;
+                return Ok(game_json(game));
+            }
+            Action::EditGame { uuid, changes } => {
+                let game = {
+                    let mut library = self.library.lock().unwrap();
+                    if !library.is_visible(&uuid) {
+                        bail!("no game {}", uuid);
+                    }
+                    let game = library.get_mut(&uuid).ok_or(anyhow!("no game {}", uuid))?;
+                    changes.apply(game)?;
+                    let game = game_json(game);
+                    save(&mut library);
+                    game
+                };
+                self.notify_changed(uuid)?;
+                return Ok(game);
+            }
+        };
+        self.commands
+            .send(command)
+            .map_err(|_| anyhow!("the launcher is shutting down"))?;
+        Ok(json!({ "ok": true }))
+    }
+
+    fn notify_changed(&self, uuid: String) -> Result<()> {
+        self.commands
+            .send(RemoteCommand::GameChanged(uuid))
+            .map_err(|_| anyhow!("the launcher is shutting down"))
+    }
+
+    fn upload_art(&self, request: &mut Request, uuid: &str, kind: ArtKind) -> Result<Value> {
+        if self.library.lock().unwrap().get(uuid).is_none() {
+            bail!("no game {}", uuid);
+        }
+        let content_type = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Content-Type"))
+            .map(|h| h.value.to_string())
+            .unwrap_or_default();
+        let mut data = vec![];
+        request
+            .as_reader()
+            .take(MAX_ART_SIZE as u64 + 1)
+            .read_to_end(&mut data)?;
+        let path = companion::save_art(&self.art_dir, uuid, kind, &content_type, &data)?;
+
+        {
+            let mut library = self.library.lock().unwrap();
+            if let Some(game) = library.get_mut(uuid) {
+                let source = ImageSource::FilePath(path.to_string_lossy().into_owned());
+                game.set_art(kind, Some(source));
+            }
+            save(&mut library);
+        }
+        self.notify_changed(uuid.to_owned())?;
+        Ok(json!({ "ok": true }))
+    }
+
+    /// Same actions as `POST /api/action`, one reply per message.
+    fn upgrade(request: Request) -> Result<WebSocket<Box<dyn ReadWrite + Send>>> {
+        let key = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Sec-WebSocket-Key"))
+            .map(|h| h.value.to_string())
+            .ok_or(anyhow!("not a websocket request"))?;
+        let response = Response::empty(101)
+            .with_header(header("Upgrade", "websocket"))
+            .with_header(header("Connection", "Upgrade"))
+            .with_header(header(
+                "Sec-WebSocket-Accept",
+                &derive_accept_key(key.as_bytes()),
+            ));
+        let stream = request.upgrade("websocket", response);
+        Ok(WebSocket::from_raw_socket(stream, Role::Server, None))
+    }
+
+    fn serve_websocket(&self, request: Request) -> Result<()> {
+        let mut ws = Self::upgrade(request)?;
+        loop {
+            let text = match ws.read() {
+                Ok(Message::Text(text)) => text,
+                Ok(_) => continue,
+                Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+                Err(e) => return Err(e.into()),
+            };
+            let reply = serde_json::from_str(&text)
+                .map_err(anyhow::Error::from)
+                .and_then(|a| self.perform(a))
+                .unwrap_or_else(|e| json!({ "error": e.to_string() }));
+            ws.send(Message::Text(reply.to_string()))?;
+        }
+    }
+
+    /// Pushes the events of the bus as JSON, what's focused and played first,
+    /// until the app is gone.
+    ///
+    /// ```json
+    /// {"event": "focus", "screen": "Home", "id": "GAME@…", "game": "…", "title": "Celeste"}
+    /// {"event": "playing", "uuid": "…", "title": "Celeste"}
+    /// {"event": "idle"}
+    /// {"event": "notification", "message": "Celeste was suspended"}
+    /// ```
+    fn serve_events(&self, request: Request) -> Result<()> {
+        let events = self.bus.subscribe();
+        let mut ws = Self::upgrade(request)?;
+        loop {
+            let message = match events.recv_timeout(KEEPALIVE) {
+                Ok(event) => Message::Text(serde_json::to_string(&event)?),
+                Err(mpsc::RecvTimeoutError::Timeout) => Message::Ping(vec![]),
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+            };
+            // Gone, pongs and all else it sends are left unread.
+            if ws.send(message).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_actions() {
+        assert_eq!(
+            serde_json::from_str::<Action>(r#"{"action": "button", "button": "up"}"#).unwrap(),
+            Action::Button {
+                button: "up".to_owned()
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<Action>(r#"{"action": "library"}"#).unwrap(),
+            Action::Library
+        );
+        assert_eq!(
+            serde_json::from_str::<Action>(
+                r#"{"action": "search", "query": "", "status": "completed"}"#
+            )
+            .unwrap(),
+            Action::Search {
+                query: String::new(),
+                status: Some(CompletionStatus::Completed)
+            }
+        );
+        assert!(serde_json::from_str::<Action>(r#"{"action": "reboot"}"#).is_err());
+    }
+
+    #[test]
+    fn sends_commands_to_the_navigation_thread() {
+        let (tx, rx) = mpsc::channel();
+        let server = RemoteServer::new(
+            Arc::new(Mutex::new(Library::new())),
+            tx,
+            std::env::temp_dir(),
+            Pairing::default(),
+            RemoteConfig::default(),
+            Bus::default(),
+        )
+        .unwrap();
+        server
+            .perform(Action::Button {
+                button: "a".to_owned(),
+            })
+            .unwrap();
+        server
+            .perform(Action::Focus {
+                uuid: "aaaa".to_owned(),
+            })
+            .unwrap();
+        assert!(server
+            .perform(Action::Button {
+                button: "turbo".to_owned()
+            })
+            .is_err());
+        assert_eq!(rx.try_recv(), Ok(RemoteCommand::Button(Button::South)));
+        assert_eq!(
+            rx.try_recv(),
+            Ok(RemoteCommand::Focus(FocusId::game("aaaa")))
+        );
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::{anyhow, bail, Result};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::{
+    collections::HashMap,
+    sync::{mpsc, Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+// Companion apps found over mDNS ask to pair with `POST /api/pair`, get a
+// code to show, and wait on `GET /api/pair/${ID}` while the launcher asks the
+// player in a dialog whether the code on the phone is the one on the TV. Once
+// it did, the app gets a token, see `tokens`.
+
+/// How long an app waits for the player to answer, the request is dropped
+/// after even if it doesn't.
+pub const PAIR_WAIT: Duration = Duration::from_secs(120);
+
+/// Of the name of an app, it's shown in the dialog.
+const MAX_NAME: usize = 40;
+
+/// What the dialog asks about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairRequest {
+    /// Hard to guess, only the app asking knows it.
+    pub id: String,
+    /// Of the app, e.g. "Pixel 8".
+    pub name: String,
+    /// Shown on both ends, 6 digits.
+    pub code: String,
+}
+
+struct Pending {
+    name: String,
+    asked: Instant,
+    /// By the player, the dialog is gone.
+    decided: bool,
+    answer: mpsc::Sender<bool>,
+    /// Taken by who waits.
+    answered: Option<mpsc::Receiver<bool>>,
+}
+
+/// The requests not answered yet, shared by the server and the navigation.
+#[derive(Clone, Default)]
+pub struct Pairing {
+    pending: Arc<Mutex<HashMap<String, Pending>>>,
+}
+
+fn random_bytes(n: usize) -> Vec<u8> {
+    let mut bytes = vec![0; n];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("no randomness from the system");
+    bytes
+}
+
+/// `n` random bytes, in hex.
+pub(super) fn random_hex(n: usize) -> String {
+    random_bytes(n)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl Pairing {
+    /// None while the player is asked about another app, one dialog at a time.
+    pub fn request(&self, name: &str) -> Option<PairRequest> {
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, p| p.asked.elapsed() < PAIR_WAIT);
+        if pending.values().any(|p| !p.decided) {
+            return None;
+        }
+        let id = random_hex(16);
+        let number = random_bytes(4).iter().fold(0u32, |n, b| n << 8 | *b as u32);
+        let request = PairRequest {
+            id,
+            name: name.chars().take(MAX_NAME).collect(),
+            code: format!("{:06}", number % 1_000_000),
+        };
+        let (answer, answered) = mpsc::channel();
+        let asked = Pending {
+            name: request.name.clone(),
+            asked: Instant::now(),
+            decided: false,
+            answer,
+            answered: Some(answered),
+        };
+        pending.insert(request.id.clone(), asked);
+        Some(request)
+    }
+
+    /// From the dialog, false when nobody waits anymore.
+    pub fn answer(&self, id: &str, accepted: bool) -> bool {
+        let mut pending = self.pending.lock().unwrap();
+        pending.get_mut(id).is_some_and(|p| {
+            p.decided = true;
+            p.answer.send(accepted).is_ok()
+        })
+    }
+
+    /// Whether the app still waits for the player to answer, the dialog can
+    /// go otherwise.
+    pub fn is_waiting(&self, id: &str) -> bool {
+        let pending = self.pending.lock().unwrap();
+        pending
+            .get(id)
+            .is_some_and(|p| !p.decided && p.asked.elapsed() < PAIR_WAIT)
+    }
+
+    /// Until the player answers, or for `wait` at most, the name of the app
+    /// if it can pair. The request is gone after either way.
+    pub fn wait(&self, id: &str, wait: Duration) -> Result<Option<String>> {
+        let (name, answered) = {
+            let mut pending = self.pending.lock().unwrap();
+            let pending = pending
+                .get_mut(id)
+                .ok_or(anyhow!("no pairing request {}", id))?;
+            let answered = pending
+                .answered
+                .take()
+                .ok_or(anyhow!("already waiting on {}", id))?;
+            (pending.name.clone(), answered)
+        };
+        let answer = answered.recv_timeout(wait);
+        self.pending.lock().unwrap().remove(id);
+        match answer {
+            Ok(accepted) => Ok(accepted.then_some(name)),
+            Err(mpsc::RecvTimeoutError::Timeout) => bail!("nobody answered"),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_for_the_answer() {
+        let pairing = Pairing::default();
+        let request = pairing.request("Pixel 8").unwrap();
+        assert!(pairing.request("Pixel 7").is_none());
+        assert_eq!(request.code.len(), 6);
+        assert_eq!(request.id.len(), 32);
+        assert!(pairing.is_waiting(&request.id));
+        assert!(pairing.answer(&request.id, true));
+        assert!(!pairing.is_waiting(&request.id));
+        assert_eq!(
+            pairing
+                .wait(&request.id, Duration::ZERO)
+                .unwrap()
+                .as_deref(),
+            Some("Pixel 8")
+        );
+        assert!(!pairing.answer(&request.id, true));
+
+        let request = pairing.request("Pixel 8").unwrap();
+        assert!(pairing.wait(&request.id, Duration::ZERO).is_err());
+        assert!(!pairing.is_waiting(&request.id));
+        let request = pairing.request("Pixel 8").unwrap();
+        // Nobody waits on it, gone once it's too late to answer.
+        let stale = Instant::now().checked_sub(PAIR_WAIT).unwrap();
+        for p in pairing.pending.lock().unwrap().values_mut() {
+            p.asked = stale;
+        }
+        assert!(!pairing.is_waiting(&request.id));
+        assert!(pairing.request("Pixel 7").is_some());
+        assert_eq!(pairing.pending.lock().unwrap().len(), 1);
+        assert!(pairing.wait("nope", Duration::ZERO).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use super::pairing::random_hex;
+use crate::paths;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::warn;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+// Apps paired with the remote control get a token to send with every request,
+// as `Authorization: Bearer ${TOKEN}`, or `?token=` where headers can't be
+// set, i.e. for the websocket and the events. Only its hash is kept. Each app
+// has its own, revoked with `anubis cli remote-revoke`.
+
+fn hash(token: &str) -> String {
+    digest(&SHA256, token.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteToken {
+    /// Short, to revoke it by.
+    pub id: String,
+    /// Of the app, as it asked to pair.
+    pub name: String,
+    pub paired_at: DateTime<Utc>,
+    /// Hex.
+    sha256: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RemoteTokens {
+    #[serde(default)]
+    pub tokens: Vec<RemoteToken>,
+}
+
+impl RemoteTokens {
+    /// `$XDG_DATA_HOME/anubis/remote-tokens.yaml`.
+    pub fn default_path() -> PathBuf {
+        paths::data_dir().join("remote-tokens.yaml")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Readable by the user only.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        Ok(())
+    }
+
+    /// A new token for the app, the only time it's seen as is.
+    pub fn issue(&mut self, name: &str, at: DateTime<Utc>) -> String {
+        let token = random_hex(32);
+        self.tokens.push(RemoteToken {
+            id: random_hex(4),
+            name: name.to_owned(),
+            paired_at: at,
+            sha256: hash(&token),
+        });
+        token
+    }
+
+    /// Who the token was issued to, unless revoked.
+    pub fn check(&self, token: &str) -> Option<&RemoteToken> {
+        let sha256 = hash(token);
+        self.tokens.iter().find(|t| t.sha256 == sha256)
+    }
+
+    /// False when there's no such token.
+    pub fn revoke(&mut self, id: &str) -> bool {
+        let before = self.tokens.len();
+        self.tokens.retain(|t| t.id != id);
+        self.tokens.len() < before
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// The tokens as the server knows them, loaded once. Loaded again when the
+/// file changes, it's revoked from another process.
+#[derive(Clone)]
+pub struct SharedTokens {
+    path: PathBuf,
+    inner: Arc<Mutex<(RemoteTokens, Option<SystemTime>)>>,
+}
+
+impl SharedTokens {
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let loaded = (RemoteTokens::load(&path)?, modified(&path));
+        Ok(Self {
+            path,
+            inner: Arc::new(Mutex::new(loaded)),
+        })
+    }
+
+    /// See `RemoteTokens::check`.
+    pub fn check(&self, token: &str) -> Option<RemoteToken> {
+        let mut inner = self.inner.lock().unwrap();
+        let now = modified(&self.path);
+        if now != inner.1 {
+            match RemoteTokens::load(&self.path) {
+                Ok(tokens) => *inner = (tokens, now),
+                // None to be safe, it may have been revoked.
+                Err(e) => {
+                    warn!("failed to load the remote tokens: {:?}", e);
+                    return None;
+                }
+            }
+        }
+        inner.0.check(token).cloned()
+    }
+
+    /// See `RemoteTokens::issue`, saved.
+    pub fn issue(&self, name: &str, at: DateTime<Utc>) -> Result<String> {
+        let mut inner = self.inner.lock().unwrap();
+        let token = inner.0.issue(name, at);
+        inner.0.save(&self.path)?;
+        inner.1 = modified(&self.path);
+        Ok(token)
+    }
+
+    /// See `RemoteTokens::revoke`, saved.
+    pub fn revoke(&self, id: &str) -> Result<bool> {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.0.revoke(id) {
+            return Ok(false);
+        }
+        inner.0.save(&self.path)?;
+        inner.1 = modified(&self.path);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_and_revokes_tokens() {
+        let mut tokens = RemoteTokens::default();
+        let token = tokens.issue("Pixel 8", Utc::now());
+        let other = tokens.issue("iPad", Utc::now());
+        assert_eq!(tokens.check(&token).unwrap().name, "Pixel 8");
+        assert!(tokens.check("nope").is_none());
+
+        let id = tokens.check(&token).unwrap().id.clone();
+        assert!(tokens.revoke(&id));
+        assert!(!tokens.revoke(&id));
+        assert!(tokens.check(&token).is_none());
+        assert_eq!(tokens.check(&other).unwrap().name, "iPad");
+        assert!(!serde_yaml::to_string(&tokens).unwrap().contains(&other));
+
+        // Revoked by another process.
+        let dir = std::env::temp_dir().join(format!("anubis-tokens-{}", std::process::id()));
+        let path = dir.join("remote-tokens.yaml");
+        let shared = SharedTokens::load(path.clone()).unwrap();
+        let token = shared.issue("Pixel 8", Utc::now()).unwrap();
+        let id = shared.check(&token).unwrap().id;
+        let mut saved = RemoteTokens::load(&path).unwrap();
+        assert!(saved.revoke(&id));
+        let later = SystemTime::now() + std::time::Duration::from_secs(1);
+        fs::write(&path, serde_yaml::to_string(&saved).unwrap()).unwrap();
+        fs::File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(later))
+            .unwrap();
+        assert!(shared.check(&token).is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
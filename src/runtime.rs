@@ -0,0 +1,184 @@
+use anyhow::{anyhow, Result};
+use std::{
+    future::Future,
+    sync::{mpsc, OnceLock},
+    time::{Duration, Instant},
+};
+use tokio::{
+    runtime::Runtime,
+    sync::mpsc::{unbounded_channel, UnboundedSender},
+    task::AbortHandle,
+};
+
+// The async core: the gamepad and the navigation run as tasks, each on its
+// own supervised thread, talking over an unbounded channel. What the
+// navigation starts in the background runs as jobs on the runtime, given up
+// on after a timeout or when their result isn't wanted any more. The modules
+// still sending on std channels are bridged with `forward`.
+
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("async")
+            .build()
+            .expect("failed to start the async runtime")
+    })
+}
+
+/// Run the task on this thread until it's done, e.g. the navigation.
+pub fn block_on<F: Future>(task: F) -> F::Output {
+    runtime().block_on(task)
+}
+
+/// Run the task in the background, e.g. what awaits an answer.
+pub fn spawn(task: impl Future<Output = ()> + Send + 'static) {
+    runtime().spawn(task);
+}
+
+/// Wait until the deadline, forever without one.
+pub async fn until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Started with `spawn_job`.
+#[derive(Debug)]
+pub struct Job {
+    handle: AbortHandle,
+}
+
+impl Job {
+    /// Drop what the job gives back, `done` isn't called. The blocking work
+    /// itself still runs to its end.
+    pub fn cancel(&self) {
+        self.handle.abort();
+    }
+}
+
+/// Do the blocking work in the background and give its result to `done`,
+/// or the error if it took longer than `timeout`.
+pub fn spawn_job<T: Send + 'static>(
+    timeout: Duration,
+    work: impl FnOnce() -> Result<T> + Send + 'static,
+    done: impl FnOnce(Result<T>) + Send + 'static,
+) -> Job {
+    let task = runtime().spawn(async move {
+        let result = match tokio::time::timeout(timeout, tokio::task::spawn_blocking(work)).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => Err(anyhow!("the job panicked: {}", e)),
+            Err(_) => Err(anyhow!("gave up after {:?}", timeout)),
+        };
+        done(result);
+    });
+    Job {
+        handle: task.abort_handle(),
+    }
+}
+
+/// Pass what comes out of a std channel on as events, until either end is
+/// gone.
+pub fn forward<T: Send + 'static, E: Send + 'static>(
+    rx: mpsc::Receiver<T>,
+    tx: UnboundedSender<E>,
+    event: impl Fn(T) -> E + Send + 'static,
+) {
+    runtime().spawn_blocking(move || {
+        for t in rx {
+            if tx.send(event(t)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// What's sent to the sender is passed to `emit` once nothing more came for
+/// `quiet`, each distinct item once, e.g. for the few writes of a file being
+/// saved. Stops when `emit` gives false or the sender is dropped.
+pub fn debounce<T: PartialEq + Send + 'static>(
+    quiet: Duration,
+    mut emit: impl FnMut(T) -> bool + Send + 'static,
+) -> UnboundedSender<T> {
+    let (tx, mut rx) = unbounded_channel();
+    runtime().spawn(async move {
+        while let Some(first) = rx.recv().await {
+            let mut pending = vec![first];
+            let mut open = true;
+            loop {
+                match tokio::time::timeout(quiet, rx.recv()).await {
+                    Ok(Some(item)) if pending.contains(&item) => {}
+                    Ok(Some(item)) => pending.push(item),
+                    Ok(None) => {
+                        open = false;
+                        break;
+                    }
+                    Err(_) => break,
+                }
+            }
+            for item in pending {
+                if !emit(item) {
+                    return;
+                }
+            }
+            if !open {
+                return;
+            }
+        }
+    });
+    tx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jobs_time_out_and_debounce_settles() {
+        let (tx, rx) = mpsc::channel();
+        let sent = tx.clone();
+        spawn_job(
+            Duration::from_secs(5),
+            || Ok(1),
+            move |r| sent.send(r.ok()).unwrap(),
+        );
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), Some(1));
+        let sent = tx.clone();
+        spawn_job(
+            Duration::from_millis(10),
+            || {
+                std::thread::sleep(Duration::from_millis(200));
+                Ok(2)
+            },
+            move |r| sent.send(r.ok()).unwrap(),
+        );
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), None);
+        let sent = tx.clone();
+        let job = spawn_job(
+            Duration::from_secs(5),
+            || {
+                std::thread::sleep(Duration::from_millis(100));
+                Ok(3)
+            },
+            move |r| sent.send(r.ok()).unwrap(),
+        );
+        job.cancel();
+        assert!(rx.recv_timeout(Duration::from_millis(300)).is_err());
+
+        let (settled_tx, settled) = mpsc::channel();
+        let writes = debounce(Duration::from_millis(50), move |i| {
+            settled_tx.send(i).is_ok()
+        });
+        for i in [1, 2, 1, 2] {
+            writes.send(i).unwrap();
+        }
+        assert!(settled.recv_timeout(Duration::from_millis(20)).is_err());
+        let first = settled.recv_timeout(Duration::from_secs(5)).unwrap();
+        let second = settled.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!((first, second), (1, 2));
+        drop(writes);
+        assert!(settled.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+}
@@ -0,0 +1,423 @@
+use crate::{launcher::compat, models::GameMetadata, paths};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+// Snapshots of the save files of games, to go back to when a save gets
+// corrupted or overwritten. Where a game saves is set per game, with templates
+// for the engines that always save in the same place. A snapshot is taken when
+// a game exits, and every so often while it runs if set up, unless nothing
+// changed since the last one. Only the newest snapshots are kept.
+
+/// Names of the snapshot directories, their time in UTC.
+const SNAPSHOT_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// Save locations by engine, `{}` is what's after the engine in the config,
+/// e.g. `unity:Team Cherry/Hollow Knight`.
+pub const TEMPLATES: [(&str, &str); 5] = [
+    ("unity", "{xdg_config}/unity3d/{}"),
+    ("godot", "{xdg_data}/godot/app_userdata/{}"),
+    ("renpy", "{home}/.renpy/{}"),
+    ("gamemaker", "{xdg_config}/{}"),
+    // Windows builds, in the game's Wine prefix.
+    ("unreal", "{windows_home}/AppData/Local/{}/Saved/SaveGames"),
+];
+
+/// ```yaml
+/// keep: 10
+/// interval_minutes: 30
+/// games:
+///   0a1b2c3d: "{xdg_data}/Celeste/Saves"
+///   4e5f6a7b: unity:Team Cherry/Hollow Knight
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveConfig {
+    /// Where the snapshots go, `saves` in the data dir when unset.
+    pub backup_dir: Option<PathBuf>,
+    /// Snapshots kept per game, the oldest are removed first.
+    pub keep: usize,
+    /// Also take one every this many minutes while a game runs.
+    pub interval_minutes: Option<u64>,
+    /// Where each game saves, by UUID. A path, which can start with
+    /// `{home}`, `{xdg_data}`, `{xdg_config}` or `{windows_home}`, or one
+    /// of the `TEMPLATES`.
+    pub games: HashMap<String, String>,
+}
+
+impl Default for SaveConfig {
+    fn default() -> Self {
+        Self {
+            backup_dir: None,
+            keep: 10,
+            interval_minutes: None,
+            games: HashMap::new(),
+        }
+    }
+}
+
+impl SaveConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::config_dir().join("saves.yaml")
+    }
+
+    fn backup_dir(&self) -> PathBuf {
+        self.backup_dir
+            .clone()
+            .unwrap_or_else(|| paths::data_dir().join("saves"))
+    }
+}
+
+/// The user's home in the Wine prefix of a game. Proton keeps the prefix in
+/// `pfx` and always names the user `steamuser`.
+fn windows_home(game: &GameMetadata) -> PathBuf {
+    let prefix = game
+        .compat
+        .as_ref()
+        .and_then(|c| c.prefix.as_ref())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| compat::default_prefix(&game.uuid));
+    if prefix.join("pfx").is_dir() {
+        return prefix.join("pfx/drive_c/users/steamuser");
+    }
+    let user = env::var("USER").unwrap_or_default();
+    prefix.join("drive_c/users").join(user)
+}
+
+/// The template filled in, `{...}` at the start replaced.
+fn expand(location: &str, game: &GameMetadata) -> Result<PathBuf> {
+    let location = match location.split_once(':') {
+        Some((engine, name)) => match TEMPLATES.iter().find(|(e, _)| *e == engine) {
+            Some((_, template)) => template.replace("{}", name),
+            None => location.to_owned(),
+        },
+        None => location.to_owned(),
+    };
+    let Some(rest) = location.strip_prefix('{') else {
+        return Ok(PathBuf::from(location));
+    };
+    let (var, rest) = rest
+        .split_once('}')
+        .ok_or(anyhow!("unclosed {{ in {}", location))?;
+    let base = match var {
+        "home" => env::var_os("HOME")
+            .map(PathBuf::from)
+            .ok_or(anyhow!("HOME isn't set"))?,
+        "xdg_data" => paths::xdg_data_home(),
+        "xdg_config" => paths::xdg_config_home(),
+        "windows_home" => windows_home(game),
+        _ => bail!("unknown {{{}}} in {}", var, location),
+    };
+    Ok(base.join(rest.trim_start_matches('/')))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub time: DateTime<Utc>,
+    pub path: PathBuf,
+}
+
+impl Snapshot {
+    /// Of the files in it, in bytes.
+    pub fn size(&self) -> u64 {
+        dir_size(&self.path)
+    }
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|e| match e.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&e.path()),
+            _ => e.metadata().map(|m| m.len()).unwrap_or_default(),
+        })
+        .sum()
+}
+
+/// Whether the two directories have the same files, with the same contents.
+fn same_files(a: &Path, b: &Path) -> Result<bool> {
+    let names = |dir: &Path| -> Result<Vec<_>> {
+        let mut names = fs::read_dir(dir)?
+            .map(|e| Ok(e?.file_name()))
+            .collect::<Result<Vec<_>>>()?;
+        names.sort();
+        Ok(names)
+    };
+    let files = names(a)?;
+    if files != names(b)? {
+        return Ok(false);
+    }
+    for name in files {
+        let (a, b) = (a.join(&name), b.join(&name));
+        let same = match (a.is_dir(), b.is_dir()) {
+            (true, true) => same_files(&a, &b)?,
+            (false, false) => {
+                fs::metadata(&a)?.len() == fs::metadata(&b)?.len() && fs::read(&a)? == fs::read(&b)?
+            }
+            _ => false,
+        };
+        if !same {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// The snapshots in the directory of a game, newest first.
+fn list(dir: &Path) -> Vec<Snapshot> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut snapshots: Vec<Snapshot> = entries
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            let time = NaiveDateTime::parse_from_str(&name, SNAPSHOT_FORMAT).ok()?;
+            Some(Snapshot {
+                time: time.and_utc(),
+                path: e.path(),
+            })
+        })
+        .collect();
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.time));
+    snapshots
+}
+
+/// Copy the saves into a new snapshot, unless they haven't changed since the
+/// last one. Only the newest `keep` are left.
+fn take(saves: &Path, dir: &Path, keep: usize) -> Result<Option<Snapshot>> {
+    if !saves.is_dir() {
+        bail!("{} isn't a directory", saves.display());
+    }
+    let snapshots = list(dir);
+    // By content, the times of the files can't be trusted to tell, e.g.
+    // written within the same tick of a coarse clock.
+    if let Some(latest) = snapshots.first() {
+        if same_files(saves, &latest.path)? {
+            return Ok(None);
+        }
+    }
+    // To the millisecond, as it's listed.
+    let time = DateTime::from_timestamp_millis(Utc::now().timestamp_millis())
+        .ok_or(anyhow!("the clock is off"))?;
+    let name = time.format(SNAPSHOT_FORMAT).to_string();
+    let path = dir.join(&name);
+    // Copied aside first, a half copied snapshot is never listed.
+    let partial = dir.join(format!("{}.partial", name));
+    copy_dir(saves, &partial)?;
+    fs::rename(&partial, &path)?;
+    for old in list(dir).iter().skip(keep.max(1)) {
+        fs::remove_dir_all(&old.path)?;
+    }
+    Ok(Some(Snapshot { time, path }))
+}
+
+/// Replace the saves with the snapshot's.
+fn restore_into(snapshot: &Snapshot, saves: &Path) -> Result<()> {
+    if saves.exists() {
+        fs::remove_dir_all(saves)?;
+    }
+    copy_dir(&snapshot.path, saves)
+}
+
+/// Cheap to clone, shared with the threads taking the snapshots.
+#[derive(Clone)]
+pub struct SaveBackups {
+    config: Arc<SaveConfig>,
+    /// UUIDs of the games running.
+    running: Arc<Mutex<HashSet<String>>>,
+    /// One snapshot or restore at a time.
+    busy: Arc<Mutex<()>>,
+}
+
+impl SaveBackups {
+    pub fn new(config: SaveConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            running: Arc::default(),
+            busy: Arc::default(),
+        }
+    }
+
+    /// Where the game saves, None when not set up.
+    pub fn save_dir(&self, game: &GameMetadata) -> Option<Result<PathBuf>> {
+        let location = self.config.games.get(&game.uuid)?;
+        Some(expand(location, game))
+    }
+
+    fn snapshots_dir(&self, uuid: &str) -> PathBuf {
+        self.config.backup_dir().join(uuid)
+    }
+
+    /// Newest first.
+    pub fn snapshots(&self, uuid: &str) -> Vec<Snapshot> {
+        list(&self.snapshots_dir(uuid))
+    }
+
+    /// None when the saves didn't change since the last one.
+    pub fn snapshot(&self, game: &GameMetadata) -> Result<Option<Snapshot>> {
+        let saves = self
+            .save_dir(game)
+            .ok_or(anyhow!("no save location set for {}", game.title))??;
+        let _busy = self.busy.lock().unwrap();
+        let snapshot = take(&saves, &self.snapshots_dir(&game.uuid), self.config.keep)?;
+        if let Some(snapshot) = &snapshot {
+            info!(
+                "backed up the saves of {} to {:?}",
+                game.title, snapshot.path
+            );
+        }
+        Ok(snapshot)
+    }
+
+    /// Put the saves of the snapshot back. The current ones are backed up
+    /// first, so it can be undone.
+    pub fn restore(&self, game: &GameMetadata, snapshot: &Snapshot) -> Result<()> {
+        if self.is_running(&game.uuid) {
+            bail!("{} is running", game.title);
+        }
+        let saves = self
+            .save_dir(game)
+            .ok_or(anyhow!("no save location set for {}", game.title))??;
+        if saves.is_dir() {
+            self.snapshot(game)?;
+        }
+        let _busy = self.busy.lock().unwrap();
+        restore_into(snapshot, &saves)?;
+        info!(
+            "restored the saves of {} from {:?}",
+            game.title, snapshot.path
+        );
+        Ok(())
+    }
+
+    pub fn is_running(&self, uuid: &str) -> bool {
+        self.running.lock().unwrap().contains(uuid)
+    }
+
+    fn snapshot_in_background(&self, game: GameMetadata) {
+        let this = self.clone();
+        thread::spawn(move || {
+            if let Err(e) = this.snapshot(&game) {
+                warn!("failed to back up the saves of {}: {:?}", game.title, e);
+            }
+        });
+    }
+
+    /// Take snapshots every `interval_minutes` while it runs.
+    pub fn game_started(&self, game: GameMetadata) {
+        if !self.config.games.contains_key(&game.uuid) {
+            return;
+        }
+        self.running.lock().unwrap().insert(game.uuid.clone());
+        let Some(minutes) = self.config.interval_minutes.filter(|m| *m > 0) else {
+            return;
+        };
+        let this = self.clone();
+        thread::spawn(move || loop {
+            thread::sleep(Duration::from_secs(minutes * 60));
+            if !this.is_running(&game.uuid) {
+                return;
+            }
+            if let Err(e) = this.snapshot(&game) {
+                warn!("failed to back up the saves of {}: {:?}", game.title, e);
+            }
+        });
+    }
+
+    /// Take a snapshot of what it saved.
+    pub fn game_exited(&self, game: GameMetadata) {
+        if self.running.lock().unwrap().remove(&game.uuid) {
+            self.snapshot_in_background(game);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_and_restores_snapshots() {
+        let game = GameMetadata {
+            uuid: "aaaa".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(
+            expand("renpy:DDLC-1454445547", &game).unwrap(),
+            PathBuf::from(env::var("HOME").unwrap()).join(".renpy/DDLC-1454445547")
+        );
+        assert_eq!(
+            expand("/saves/{xdg_data}", &game).unwrap(),
+            PathBuf::from("/saves/{xdg_data}")
+        );
+        assert!(expand("{nope}/saves", &game).is_err());
+
+        let root = env::temp_dir().join(format!("anubis-saves-test-{}", std::process::id()));
+        let (saves, backups) = (root.join("saves"), root.join("backups"));
+        fs::create_dir_all(saves.join("slot1")).unwrap();
+        fs::write(saves.join("slot1/save.dat"), "1").unwrap();
+
+        let first = take(&saves, &backups, 2).unwrap().unwrap();
+        assert_eq!(take(&saves, &backups, 2).unwrap(), None);
+        for i in 2..4 {
+            thread::sleep(Duration::from_millis(20));
+            fs::write(saves.join("slot1/save.dat"), i.to_string()).unwrap();
+            take(&saves, &backups, 2).unwrap().unwrap();
+        }
+        // Written again, but the same.
+        fs::write(saves.join("slot1/save.dat"), "3").unwrap();
+        assert_eq!(take(&saves, &backups, 2).unwrap(), None);
+        let snapshots = list(&backups);
+        assert_eq!(snapshots.len(), 2);
+        assert!(!snapshots.contains(&first));
+
+        restore_into(&snapshots[1], &saves).unwrap();
+        assert_eq!(
+            fs::read_to_string(saves.join("slot1/save.dat")).unwrap(),
+            "2"
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+use crate::paths;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub mod stats;
+
+// Each time a game was played, from when it started running to when it was
+// paused or exited, kept in the data dir. The details screen lists the
+// sessions of a game, `stats` adds them all up for the stats screen.
+
+/// A game played once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub uuid: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Session {
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionLog {
+    /// The oldest first.
+    pub sessions: Vec<Session>,
+}
+
+impl SessionLog {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::data_dir().join("sessions.yaml")
+    }
+
+    pub fn record(&mut self, session: Session) {
+        self.sessions.push(session);
+    }
+
+    /// The sessions of the game, the newest first.
+    pub fn of(&self, uuid: &str) -> Vec<&Session> {
+        self.sessions
+            .iter()
+            .rev()
+            .filter(|s| s.uuid == uuid)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn lists_the_sessions_of_a_game() {
+        let start = Utc.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap();
+        let session = |uuid: &str, days, hours| Session {
+            uuid: uuid.to_owned(),
+            start: start + Duration::days(days),
+            end: start + Duration::days(days) + Duration::hours(hours),
+        };
+        let mut log = SessionLog::default();
+        log.record(session("a", 0, 2));
+        log.record(session("b", 2, 1));
+        log.record(session("a", 5, 3));
+        let sessions = log.of("a");
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].duration(), Duration::hours(3));
+
+        let path =
+            std::env::temp_dir().join(format!("anubis-sessions-{}.yaml", std::process::id()));
+        log.save(&path).unwrap();
+        assert_eq!(SessionLog::load(&path).unwrap(), log);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,305 @@
+use super::{Session, SessionLog};
+use crate::models::GameMetadata;
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate};
+use std::collections::{BTreeSet, HashMap};
+
+// What the stats screen shows of the sessions: the playtime by week or month
+// charted, in all, by genre and by game, and the days in a row played. The
+// charts are drawn by the UI from the paths made here.
+
+/// What the stats screen adds the playtime up by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// From Monday.
+    Week,
+    Month,
+}
+
+impl Period {
+    /// First day of the period the date is in.
+    fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Period::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    fn before(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Week => start - Duration::weeks(1),
+            Period::Month => start - Months::new(1),
+        }
+    }
+}
+
+/// The sessions of a week or month.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Total {
+    /// First day of the period.
+    pub start: NaiveDate,
+    pub playtime: Duration,
+    pub sessions: usize,
+}
+
+/// The playtime of the last `count` periods up to `now`, the oldest first,
+/// those without sessions included. A session counts in the period it
+/// started in.
+pub fn totals(log: &SessionLog, period: Period, count: usize, now: DateTime<Local>) -> Vec<Total> {
+    let mut start = period.start_of(now.date_naive());
+    let mut totals = vec![];
+    for _ in 0..count {
+        totals.push(Total {
+            start,
+            playtime: Duration::zero(),
+            sessions: 0,
+        });
+        start = period.before(start);
+    }
+    totals.reverse();
+    for session in &log.sessions {
+        let day = session.start.with_timezone(&Local).date_naive();
+        let start = period.start_of(day);
+        if let Some(total) = totals.iter_mut().find(|t| t.start == start) {
+            total.playtime += session.duration();
+            total.sessions += 1;
+        }
+    }
+    totals
+}
+
+/// The playtime of a genre or a game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Share {
+    /// The genre, or the title of the game.
+    pub name: String,
+    pub playtime: Duration,
+    pub sessions: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dashboard {
+    pub playtime: Duration,
+    pub sessions: usize,
+    /// The most played first, `top` at most.
+    pub genres: Vec<Share>,
+    pub games: Vec<Share>,
+    /// Days in a row played up to today, or yesterday when today isn't yet.
+    pub streak: u32,
+    pub longest_streak: u32,
+}
+
+/// Everything added up, with the `top` genres and games. Sessions of games
+/// gone from the library count in all but not by genre or game.
+pub fn dashboard(
+    log: &SessionLog,
+    games: &[GameMetadata],
+    today: NaiveDate,
+    top: usize,
+) -> Dashboard {
+    let by_uuid: HashMap<&str, &GameMetadata> =
+        games.iter().map(|g| (g.uuid.as_str(), g)).collect();
+    let mut genres: HashMap<&str, Share> = HashMap::new();
+    let mut played: HashMap<&str, Share> = HashMap::new();
+    for session in &log.sessions {
+        let Some(game) = by_uuid.get(session.uuid.as_str()) else {
+            continue;
+        };
+        for genre in &game.genres {
+            add(genres.entry(genre).or_insert_with(|| share(genre)), session);
+        }
+        let game_share = played
+            .entry(&game.uuid)
+            .or_insert_with(|| share(&game.title));
+        add(game_share, session);
+    }
+    let days: BTreeSet<NaiveDate> = log
+        .sessions
+        .iter()
+        .map(|s| s.start.with_timezone(&Local).date_naive())
+        .collect();
+    Dashboard {
+        playtime: log
+            .sessions
+            .iter()
+            .map(Session::duration)
+            .fold(Duration::zero(), |a, b| a + b),
+        sessions: log.sessions.len(),
+        genres: most(genres.into_values(), top),
+        games: most(played.into_values(), top),
+        streak: streak(&days, today),
+        longest_streak: longest_streak(&days),
+    }
+}
+
+fn share(name: &str) -> Share {
+    Share {
+        name: name.to_owned(),
+        playtime: Duration::zero(),
+        sessions: 0,
+    }
+}
+
+fn add(share: &mut Share, session: &Session) {
+    share.playtime += session.duration();
+    share.sessions += 1;
+}
+
+fn most(shares: impl Iterator<Item = Share>, top: usize) -> Vec<Share> {
+    let mut shares: Vec<Share> = shares.collect();
+    shares.sort_by(|a, b| {
+        b.playtime
+            .cmp(&a.playtime)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    shares.truncate(top);
+    shares
+}
+
+fn streak(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> u32 {
+    let mut day = match days.contains(&today) {
+        true => today,
+        false => today - Duration::days(1),
+    };
+    let mut streak = 0;
+    while days.contains(&day) {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+    streak
+}
+
+fn longest_streak(days: &BTreeSet<NaiveDate>) -> u32 {
+    let (mut longest, mut run) = (0, 0);
+    let mut last: Option<NaiveDate> = None;
+    for day in days {
+        run = match last {
+            Some(last) if *day - last == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        last = Some(*day);
+    }
+    longest
+}
+
+/// An area chart of the values, left to right, as SVG path commands within
+/// a 100 x 100 box, the largest value reaching the top.
+pub fn chart_path(values: &[f32]) -> String {
+    if values.is_empty() {
+        return String::new();
+    }
+    let largest = values.iter().cloned().fold(0.0, f32::max);
+    let step = 100.0 / (values.len().max(2) - 1) as f32;
+    let mut path = String::from("M 0 100");
+    for (i, value) in values.iter().enumerate() {
+        let height = if largest > 0.0 {
+            value / largest * 100.0
+        } else {
+            0.0
+        };
+        path.push_str(&format!(" L {:.1} {:.1}", i as f32 * step, 100.0 - height));
+    }
+    path.push_str(&format!(" L {:.1} 100 Z", (values.len() - 1) as f32 * step));
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn at(d: u32, h: u32) -> DateTime<Utc> {
+        Local
+            .with_ymd_and_hms(2024, 5, d, h, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn session(uuid: &str, d: u32, h: u32, hours: u32) -> Session {
+        Session {
+            uuid: uuid.to_owned(),
+            start: at(d, h),
+            end: at(d, h + hours),
+        }
+    }
+
+    #[test]
+    fn adds_up_sessions_by_week_and_month() {
+        let mut log = SessionLog::default();
+        // Wednesday the 1st, Friday the 3rd and Monday the 6th.
+        log.record(session("a", 1, 10, 2));
+        log.record(session("b", 3, 20, 1));
+        log.record(session("a", 6, 12, 3));
+
+        let now = Local.with_ymd_and_hms(2024, 5, 8, 12, 0, 0).unwrap();
+        let weeks = totals(&log, Period::Week, 3, now);
+        let week = |d| NaiveDate::from_ymd_opt(2024, 4, 1).unwrap() + Duration::days(d);
+        assert_eq!(
+            weeks,
+            [
+                Total {
+                    start: week(21),
+                    playtime: Duration::zero(),
+                    sessions: 0
+                },
+                Total {
+                    start: week(28),
+                    playtime: Duration::hours(3),
+                    sessions: 2
+                },
+                Total {
+                    start: week(35),
+                    playtime: Duration::hours(3),
+                    sessions: 1
+                },
+            ]
+        );
+        let months = totals(&log, Period::Month, 2, now);
+        assert_eq!(months[0].start, week(0));
+        assert_eq!(months[0].sessions, 0);
+        assert_eq!(months[1].playtime, Duration::hours(6));
+    }
+
+    #[test]
+    fn sums_up_genres_games_and_streaks() {
+        let game = |uuid: &str, genres: &[&str]| GameMetadata {
+            title: uuid.to_uppercase(),
+            uuid: uuid.to_owned(),
+            genres: genres.iter().map(|g| g.to_string()).collect(),
+            ..Default::default()
+        };
+        let games = [game("a", &["platformer", "indie"]), game("b", &["indie"])];
+        let mut log = SessionLog::default();
+        for (uuid, d, hours) in [
+            ("a", 1, 2),
+            ("b", 3, 1),
+            ("b", 4, 4),
+            ("a", 5, 1),
+            ("gone", 7, 1),
+        ] {
+            log.record(session(uuid, d, 10, hours));
+        }
+        let today = NaiveDate::from_ymd_opt(2024, 5, 8).unwrap();
+        let dashboard = dashboard(&log, &games, today, 1);
+        assert_eq!(dashboard.playtime, Duration::hours(9));
+        assert_eq!(dashboard.sessions, 5);
+        assert_eq!(
+            dashboard.genres,
+            [Share {
+                name: "indie".to_owned(),
+                playtime: Duration::hours(8),
+                sessions: 4
+            }]
+        );
+        assert_eq!(dashboard.games[0].name, "B");
+        // Played yesterday, not yet today.
+        assert_eq!(dashboard.streak, 1);
+        assert_eq!(dashboard.longest_streak, 3);
+
+        assert_eq!(chart_path(&[]), "");
+        assert_eq!(
+            chart_path(&[0.0, 2.0, 1.0]),
+            "M 0 100 L 0.0 100.0 L 50.0 0.0 L 100.0 50.0 L 100.0 100 Z"
+        );
+    }
+}
@@ -0,0 +1,301 @@
+use crate::{
+    integrations::steam, launcher::compat, library::roms, models::GameMetadata, paths,
+    transfer::TransferContext,
+};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// How much space the installed games take, per game and per drive. A game's
+// files are found from where it's installed from: the Steam install dir, the
+// ROM, or the folder of its executable, plus its Wine prefix. Walking them
+// all takes a while, so it's done as a task, and the last scan is kept in the
+// cache dir to show right away.
+
+/// Files found for a game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameUsage {
+    pub uuid: String,
+    pub title: String,
+    /// Taken on disk, in bytes.
+    pub bytes: u64,
+    /// Mount point of the drive it's on, that of its largest folder.
+    pub drive: PathBuf,
+    pub played: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Drive {
+    pub mount: PathBuf,
+    /// e.g. `/dev/nvme0n1p2`.
+    pub device: String,
+    pub size: u64,
+    pub available: u64,
+    /// Taken by the games on it.
+    pub games: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub time: DateTime<Utc>,
+    /// Largest first.
+    pub games: Vec<GameUsage>,
+    pub drives: Vec<Drive>,
+}
+
+impl Usage {
+    pub fn default_path() -> PathBuf {
+        paths::cache_dir().join("storage.yaml")
+    }
+
+    /// The last scan, None if there wasn't one.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_yaml::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// The largest games never played, as candidates to uninstall, among
+    /// those `can_uninstall`.
+    pub fn suggestions(&self, can_uninstall: impl Fn(&str) -> bool) -> Vec<&GameUsage> {
+        self.games
+            .iter()
+            .filter(|g| !g.played && g.bytes > 0 && can_uninstall(&g.uuid))
+            .collect()
+    }
+}
+
+/// In decimal units like the drives are sold in, e.g. "45.2 GB".
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    for unit in UNITS {
+        size /= 1000.0;
+        if size < 1000.0 || unit == "TB" {
+            return format!("{:.1} {}", size, unit);
+        }
+    }
+    unreachable!()
+}
+
+/// Where the files of a game are.
+fn install_paths(game: &GameMetadata, steamapps_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut found = vec![];
+    let source = game.install_source.as_deref().unwrap_or_default();
+    if let Some(rom) = source.strip_prefix(roms::INSTALL_SOURCE_PREFIX) {
+        found.push(PathBuf::from(rom));
+    }
+    if let Some(app_id) = steam::app_id_from_install_source(source) {
+        found.extend(steam::install_dir(steamapps_dirs, app_id));
+    }
+    if let Some(program) = game.launch_options.first().map(Path::new) {
+        // Not programs on the PATH like emulators, those aren't the game's.
+        let on_path = env::var_os("PATH")
+            .is_some_and(|p| env::split_paths(&p).any(|d| program.parent() == Some(&d)));
+        if program.is_absolute() && !on_path {
+            found.extend(program.parent().map(Path::to_owned));
+        }
+    }
+    if let Some(config) = &game.compat {
+        found.push(match &config.prefix {
+            Some(prefix) => PathBuf::from(prefix),
+            None => compat::default_prefix(&game.uuid),
+        });
+    }
+    found.retain(|p| p.exists());
+    found.dedup();
+    found
+}
+
+/// Taken on disk by the files under the path, links not followed.
+fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    let own = metadata.blocks() * 512;
+    if !metadata.is_dir() {
+        return own;
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        return own;
+    };
+    own + entries
+        .flatten()
+        .map(|e| disk_usage(&e.path()))
+        .sum::<u64>()
+}
+
+/// `df -B1 --output=source,target,size,avail` of the paths, in order.
+fn parse_df(output: &str) -> Vec<Drive> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [device, mount @ .., size, available] = fields.as_slice() else {
+                return None;
+            };
+            Some(Drive {
+                // Mount points can have spaces.
+                mount: PathBuf::from(mount.join(" ")),
+                device: device.to_string(),
+                size: size.parse().ok()?,
+                available: available.parse().ok()?,
+                games: 0,
+            })
+        })
+        .collect()
+}
+
+fn drives_of(paths: &[&Path]) -> Result<Vec<Drive>> {
+    let output = Command::new("df")
+        .args(["-B1", "--output=source,target,size,avail"])
+        .args(paths)
+        .output()
+        .context("failed to run df")?;
+    // It still lists the others when one is missing.
+    let drives = parse_df(&String::from_utf8_lossy(&output.stdout));
+    if drives.len() != paths.len() {
+        bail!(
+            "df failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(drives)
+}
+
+/// Go through the files of the games, a game at a time so it can be stopped.
+pub fn scan(
+    ctx: &TransferContext,
+    games: &[GameMetadata],
+    steamapps_dirs: &[PathBuf],
+) -> Result<Usage> {
+    let mut found: Vec<(&GameMetadata, Vec<(PathBuf, u64)>)> = vec![];
+    for (i, game) in games.iter().enumerate() {
+        ctx.check_stop()?;
+        ctx.report_progress(i as f32 / games.len() as f32);
+        let sizes: Vec<(PathBuf, u64)> = install_paths(game, steamapps_dirs)
+            .into_iter()
+            .map(|p| {
+                let bytes = disk_usage(&p);
+                (p, bytes)
+            })
+            .collect();
+        if !sizes.is_empty() {
+            found.push((game, sizes));
+        }
+    }
+    // The library is always there, so the drive it's on is always listed.
+    let library_dir = paths::data_dir();
+    fs::create_dir_all(&library_dir)?;
+    let mut paths: Vec<&Path> = vec![&library_dir];
+    paths.extend(
+        found
+            .iter()
+            .flat_map(|(_, s)| s.iter().map(|(p, _)| p.as_path())),
+    );
+    let mut mounts = drives_of(&paths)?.into_iter();
+    let mut drives: Vec<Drive> = mounts.next().into_iter().collect();
+    let mut usage = vec![];
+    for (game, sizes) in found {
+        let mut largest: Option<(u64, PathBuf)> = None;
+        for ((_, bytes), mount) in sizes.iter().zip(mounts.by_ref()) {
+            if largest.as_ref().is_none_or(|(b, _)| bytes > b) {
+                largest = Some((*bytes, mount.mount.clone()));
+            }
+            match drives.iter_mut().find(|d| d.mount == mount.mount) {
+                Some(drive) => drive.games += bytes,
+                None => drives.push(Drive {
+                    games: *bytes,
+                    ..mount
+                }),
+            }
+        }
+        usage.push(GameUsage {
+            uuid: game.uuid.clone(),
+            title: game.title.clone(),
+            bytes: sizes.iter().map(|(_, b)| b).sum(),
+            drive: largest.map(|(_, m)| m).unwrap_or_default(),
+            played: game.playtime.is_some_and(|p| p > chrono::Duration::zero()),
+        });
+    }
+    usage.sort_by_key(|g| std::cmp::Reverse(g.bytes));
+    Ok(Usage {
+        time: Utc::now(),
+        games: usage,
+        drives,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_drives_and_suggests_unplayed_games() {
+        let drives = parse_df(
+            "Filesystem         Mounted on         1B-blocks         Avail\n\
+             /dev/nvme0n1p2     /                  511000000000      200000000000\n\
+             /dev/mmcblk0p1     /run/media/deck/SD Card  1000000000000  900000000000\n",
+        );
+        assert_eq!(drives.len(), 2);
+        assert_eq!(drives[1].mount, PathBuf::from("/run/media/deck/SD Card"));
+        assert_eq!(drives[1].device, "/dev/mmcblk0p1");
+        assert_eq!(drives[1].available, 900_000_000_000);
+
+        let dir = env::temp_dir().join(format!("anubis-storage-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("game/data")).unwrap();
+        fs::write(dir.join("game/data/pak0"), vec![0; 64 * 1024]).unwrap();
+        let exe = dir.join("game/run.sh");
+        fs::write(&exe, "").unwrap();
+        let game = GameMetadata {
+            uuid: "a".to_owned(),
+            launch_options: vec![exe.to_string_lossy().into_owned()],
+            ..Default::default()
+        };
+        assert_eq!(install_paths(&game, &[]), [dir.join("game")]);
+        assert!(disk_usage(&dir.join("game")) >= 64 * 1024);
+        fs::remove_dir_all(dir).unwrap();
+
+        let usage = |uuid: &str, bytes, played| GameUsage {
+            uuid: uuid.to_owned(),
+            title: uuid.to_owned(),
+            bytes,
+            drive: "/".into(),
+            played,
+        };
+        let usage = Usage {
+            time: Utc::now(),
+            games: vec![
+                usage("big-played", 30, true),
+                usage("big", 20, false),
+                usage("not-steam", 15, false),
+                usage("small", 10, false),
+            ],
+            drives,
+        };
+        let suggested: Vec<_> = usage
+            .suggestions(|uuid| uuid != "not-steam")
+            .iter()
+            .map(|g| g.uuid.as_str())
+            .collect();
+        assert_eq!(suggested, ["big", "small"]);
+        assert_eq!(format_bytes(45_230_000_000), "45.2 GB");
+        assert_eq!(format_bytes(512), "0.5 KB");
+    }
+}
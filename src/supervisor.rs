@@ -0,0 +1,142 @@
+use anyhow::Result;
+use log::{info, warn};
+use std::{
+    any::Any,
+    io,
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+// Keeps the long running threads going. A component that panics or fails is
+// run again after a growing delay, instead of e.g. the gamepad silently going
+// dead. Whatever it captured is kept across restarts, so the navigation keeps
+// its state.
+
+/// Before the first restart, doubled after each quick failure.
+const RESTART_DELAY: Duration = Duration::from_millis(100);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(10);
+/// Ran at least this long, the failure isn't counted against it.
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+/// Quick failures in a row before giving up.
+const MAX_RESTARTS: u32 = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Restart {
+    /// As given to `Supervisor::spawn`, e.g. `gamepad`.
+    pub component: &'static str,
+    /// The panic message or error.
+    pub reason: String,
+    /// Quick failures in a row so far.
+    pub restarts: u32,
+    /// Failed too many times, not run again.
+    pub gave_up: bool,
+}
+
+#[derive(Clone)]
+pub struct Supervisor {
+    restarts: mpsc::Sender<Restart>,
+}
+
+impl Supervisor {
+    pub fn new(restarts: mpsc::Sender<Restart>) -> Self {
+        Self { restarts }
+    }
+
+    /// Run `component` on its own thread, again each time it panics or
+    /// fails, until it returns Ok.
+    pub fn spawn(
+        &self,
+        component: &'static str,
+        mut run: impl FnMut() -> Result<()> + Send + 'static,
+    ) -> io::Result<JoinHandle<()>> {
+        let restarts = self.restarts.clone();
+        thread::Builder::new()
+            .name(component.to_owned())
+            .spawn(move || {
+                let mut failures = 0;
+                loop {
+                    let started = Instant::now();
+                    let reason = match panic::catch_unwind(AssertUnwindSafe(&mut run)) {
+                        Ok(Ok(())) => return,
+                        Ok(Err(e)) => format!("{:#}", e),
+                        Err(payload) => panic_message(payload),
+                    };
+                    if started.elapsed() >= STABLE_AFTER {
+                        failures = 0;
+                    }
+                    failures += 1;
+                    let gave_up = failures > MAX_RESTARTS;
+                    let restart = Restart {
+                        component,
+                        reason,
+                        restarts: failures,
+                        gave_up,
+                    };
+                    if gave_up {
+                        warn!("{} keeps failing, giving up: {}", component, restart.reason);
+                    } else {
+                        info!("restarting {}: {}", component, restart.reason);
+                    }
+                    let _ = restarts.send(restart);
+                    if gave_up {
+                        return;
+                    }
+                    thread::sleep((RESTART_DELAY * 2u32.pow(failures - 1)).min(MAX_RESTART_DELAY));
+                }
+            })
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    payload
+        .downcast_ref::<String>()
+        .cloned()
+        .unwrap_or_else(|| "panicked".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn restarts_failed_components() {
+        let (tx, rx) = mpsc::channel();
+        let mut runs = 0;
+        let handle = Supervisor::new(tx)
+            .spawn("flaky", move || {
+                runs += 1;
+                match runs {
+                    1 => panic!("run {}", runs),
+                    2 => Err(anyhow!("run {}", runs)),
+                    _ => Ok(()),
+                }
+            })
+            .unwrap();
+        handle.join().unwrap();
+
+        let restarts: Vec<Restart> = rx.iter().collect();
+        assert_eq!(
+            restarts,
+            [
+                Restart {
+                    component: "flaky",
+                    reason: "run 1".to_owned(),
+                    restarts: 1,
+                    gave_up: false,
+                },
+                Restart {
+                    component: "flaky",
+                    reason: "run 2".to_owned(),
+                    restarts: 2,
+                    gave_up: false,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use crate::paths;
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::{
+    rand::SystemRandom,
+    signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519},
+};
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+// The Ed25519 key a device signs what it sends to its peers with. They trust
+// it once paired, see `anubis cli sync-pair`.
+
+pub struct DeviceKey(Ed25519KeyPair);
+
+impl DeviceKey {
+    /// `$XDG_DATA_HOME/anubis/sync-key`, PKCS#8.
+    pub fn default_path() -> PathBuf {
+        paths::data_dir().join("sync-key")
+    }
+
+    /// Made up the first time, readable by the user only.
+    pub fn load_or_create(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+                .map_err(|_| anyhow!("failed to make up a sync key"))?;
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, pkcs8.as_ref())?;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+        let pair = Ed25519KeyPair::from_pkcs8(&fs::read(path)?)
+            .map_err(|e| anyhow!("bad sync key in {:?}: {}", path, e))?;
+        Ok(Self(pair))
+    }
+
+    /// Base64, what peers pair with.
+    pub fn public_key(&self) -> String {
+        STANDARD.encode(self.0.public_key().as_ref())
+    }
+
+    /// Base64.
+    pub fn sign(&self, message: &[u8]) -> String {
+        STANDARD.encode(self.0.sign(message).as_ref())
+    }
+}
+
+/// Both base64.
+pub fn verify(public_key: &str, message: &[u8], signature: &str) -> Result<()> {
+    let public_key = STANDARD.decode(public_key)?;
+    let signature = STANDARD.decode(signature)?;
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(message, &signature)
+        .map_err(|_| anyhow!("bad signature"))
+}
+
+/// What's signed: who sent it, when, and the body.
+pub fn message(device: &str, at: i64, body: &[u8]) -> Vec<u8> {
+    let mut message = format!("{}\n{}\n", device, at).into_bytes();
+    message.extend_from_slice(body);
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_signed_messages() {
+        let path = std::env::temp_dir().join(format!("anubis-sync-key-{}", std::process::id()));
+        let key = DeviceKey::load_or_create(&path).unwrap();
+        assert_eq!(
+            DeviceKey::load_or_create(&path).unwrap().public_key(),
+            key.public_key()
+        );
+        let signed = message("a", 1700000000, b"{}");
+        let signature = key.sign(&signed);
+        verify(&key.public_key(), &signed, &signature).unwrap();
+        let forged = message("a", 1700000001, b"{}");
+        assert!(verify(&key.public_key(), &forged, &signature).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,341 @@
+use self::keys::DeviceKey;
+use crate::{
+    library::{journal::Entry, Library},
+    mdns, paths,
+};
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::Read,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+// Keeping the libraries of installs on the LAN the same, e.g. the desktop's
+// and the living room box's: favorites, playtime, collections. They find each
+// other over mDNS and only answer the devices paired, by their key. Each
+// pulls the journal entries it hasn't seen from the others and replays them,
+// see `library::journal`. Signed, not encrypted.
+
+pub mod keys;
+
+/// Advertised with the device in the TXT record.
+pub const SERVICE_TYPE: &str = "_anubis-sync._tcp.local.";
+
+/// How far apart the clocks of two devices can be, older pulls are refused.
+const MAX_SKEW_SECS: i64 = 300;
+
+const BROWSE_WAIT: Duration = Duration::from_secs(3);
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+const DEVICE_HEADER: &str = "X-Anubis-Device";
+const TIME_HEADER: &str = "X-Anubis-Time";
+const SIGNATURE_HEADER: &str = "X-Anubis-Signature";
+
+fn default_listen() -> String {
+    "0.0.0.0:7879".to_owned()
+}
+
+fn default_interval_secs() -> u64 {
+    300
+}
+
+/// A device trusted to sync with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Peer {
+    /// Its ID in the journal.
+    pub device: String,
+    pub name: String,
+    /// Base64 Ed25519, see `keys`.
+    pub key: String,
+}
+
+/// Off unless enabled, devices are paired with `anubis cli sync-pair`.
+///
+/// ```yaml
+/// enabled: true
+/// listen: 0.0.0.0:7879
+/// interval_secs: 300
+/// peers:
+/// - device: 0190a3c2-5c4e-7d1a-9b7f-2e4c9c1f7e2d
+///   name: living-room
+///   key: <base64 Ed25519 public key>
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub enabled: bool,
+    pub listen: String,
+    pub interval_secs: u64,
+    pub peers: Vec<Peer>,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: default_listen(),
+            interval_secs: default_interval_secs(),
+            peers: vec![],
+        }
+    }
+}
+
+impl SyncConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn default_path() -> PathBuf {
+        paths::config_dir().join("sync.yaml")
+    }
+
+    pub fn peer(&self, device: &str) -> Option<&Peer> {
+        self.peers.iter().find(|p| p.device == device)
+    }
+
+    /// Trust the device, again with a new key or name if paired already.
+    pub fn pair(&mut self, peer: Peer) {
+        self.peers.retain(|p| p.device != peer.device);
+        self.peers.push(peer);
+    }
+}
+
+/// Body of `POST /sync/pull`, the next seq of each device in the journal.
+#[derive(Debug, Serialize, Deserialize)]
+struct Pull {
+    seen: HashMap<String, u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Pulled {
+    entries: Vec<Entry>,
+}
+
+fn header(request: &Request, field: &'static str) -> Result<String> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(field))
+        .map(|h| h.value.to_string())
+        .ok_or(anyhow!("no {} header", field))
+}
+
+#[derive(Clone)]
+pub struct Syncer {
+    library: Arc<Mutex<Library>>,
+    config: SyncConfig,
+    key: Arc<DeviceKey>,
+    /// This device in the journal.
+    device: String,
+}
+
+impl Syncer {
+    pub fn new(library: Arc<Mutex<Library>>, config: SyncConfig, key: DeviceKey) -> Result<Self> {
+        let device = library
+            .lock()
+            .unwrap()
+            .device()
+            .ok_or(anyhow!("the library has no journal to sync"))?
+            .to_owned();
+        Ok(Self {
+            library,
+            config,
+            key: Arc::new(key),
+            device,
+        })
+    }
+
+    /// Advertise and answer the pulls of the peers until the server stops
+    /// accepting connections. Meant to run on a thread of its own, see the
+    /// supervisor.
+    pub fn serve(&self) -> Result<()> {
+        let listen = &self.config.listen;
+        let server =
+            Server::http(listen).map_err(|e| anyhow!("failed to listen on {}: {}", listen, e))?;
+        let port = server
+            .server_addr()
+            .to_ip()
+            .ok_or(anyhow!("{} isn't an IP address", listen))?
+            .port();
+        let properties = HashMap::from([("device".to_owned(), self.device.clone())]);
+        let _advertised = mdns::advertise(SERVICE_TYPE, port, properties)?;
+        info!("sync listening on {}", listen);
+        for request in server.incoming_requests() {
+            let this = self.clone();
+            thread::spawn(move || {
+                if let Err(e) = this.handle(request) {
+                    warn!("sync request failed: {:?}", e);
+                }
+            });
+        }
+        bail!("stopped accepting connections on {}", listen)
+    }
+
+    fn handle(&self, mut request: Request) -> Result<()> {
+        if (request.method(), request.url()) != (&Method::Post, "/sync/pull") {
+            return Ok(request.respond(Response::empty(404))?);
+        }
+        let mut body = vec![];
+        request.as_reader().read_to_end(&mut body)?;
+        let at = match self.check(&request, &body) {
+            Ok(at) => at,
+            Err(e) => {
+                warn!("refused to sync: {:#}", e);
+                return Ok(request.respond(Response::empty(403))?);
+            }
+        };
+        let pull: Pull = serde_json::from_slice(&body)?;
+        let entries = self.library.lock().unwrap().unseen_by(&pull.seen)?;
+        let body = serde_json::to_vec(&Pulled { entries })?;
+        // Over the time of the pull, so it can't be answered with an old one.
+        let signature = self.key.sign(&keys::message(&self.device, at, &body));
+        let response = Response::from_data(body)
+            .with_header(Header::from_bytes("Content-Type", "application/json").unwrap())
+            .with_header(Header::from_bytes(SIGNATURE_HEADER, signature).unwrap());
+        request.respond(response)?;
+        Ok(())
+    }
+
+    /// The time of the pull, when signed by a peer not long ago.
+    fn check(&self, request: &Request, body: &[u8]) -> Result<i64> {
+        let device = header(request, DEVICE_HEADER)?;
+        let peer = self
+            .config
+            .peer(&device)
+            .ok_or(anyhow!("{} isn't paired", device))?;
+        let at: i64 = header(request, TIME_HEADER)?.parse()?;
+        if (Utc::now().timestamp() - at).abs() > MAX_SKEW_SECS {
+            bail!("the clock of {} is off", peer.name);
+        }
+        let signature = header(request, SIGNATURE_HEADER)?;
+        keys::verify(&peer.key, &keys::message(&device, at, body), &signature)
+            .map_err(|e| anyhow!("{} from {}", e, peer.name))?;
+        Ok(at)
+    }
+
+    /// Pull from the paired devices found on the LAN, returns the UUIDs of
+    /// the games changed.
+    pub fn sync(&self) -> Result<Vec<String>> {
+        let mut changed = vec![];
+        for found in mdns::browse(SERVICE_TYPE, BROWSE_WAIT)? {
+            let device = found.properties.get("device");
+            if device == Some(&self.device) {
+                continue;
+            }
+            let Some(peer) = device.and_then(|d| self.config.peer(d)) else {
+                info!("{} isn't paired, not syncing with it", found.name);
+                continue;
+            };
+            match self.pull(peer, &found.addresses) {
+                Ok(games) => {
+                    info!("synced {} games from {}", games.len(), peer.name);
+                    changed.extend(games);
+                }
+                Err(e) => warn!("failed to sync with {}: {:#}", peer.name, e),
+            }
+        }
+        changed.sort();
+        changed.dedup();
+        Ok(changed)
+    }
+
+    fn pull(&self, peer: &Peer, addresses: &[SocketAddr]) -> Result<Vec<String>> {
+        // IPv4 first, link-local IPv6 addresses need a scope.
+        let address = addresses
+            .iter()
+            .find(|a| a.is_ipv4())
+            .or(addresses.first())
+            .ok_or(anyhow!("no address"))?;
+        let seen = self.library.lock().unwrap().seen();
+        let body = serde_json::to_vec(&Pull { seen })?;
+        let at = Utc::now().timestamp();
+        let signature = self.key.sign(&keys::message(&self.device, at, &body));
+        let response = ureq::post(&format!("http://{}/sync/pull", address))
+            .timeout(TIMEOUT)
+            .set("Content-Type", "application/json")
+            .set(DEVICE_HEADER, &self.device)
+            .set(TIME_HEADER, &at.to_string())
+            .set(SIGNATURE_HEADER, &signature)
+            .send_bytes(&body)?;
+        let signature = response
+            .header(SIGNATURE_HEADER)
+            .ok_or(anyhow!("the answer isn't signed"))?
+            .to_owned();
+        let mut pulled = vec![];
+        response.into_reader().read_to_end(&mut pulled)?;
+        keys::verify(
+            &peer.key,
+            &keys::message(&peer.device, at, &pulled),
+            &signature,
+        )?;
+        let Pulled { entries } = serde_json::from_slice(&pulled)?;
+
+        let mut library = self.library.lock().unwrap();
+        let mut changed = vec![];
+        for entry in &entries {
+            changed.extend(library.replay(entry)?);
+        }
+        if !entries.is_empty() {
+            library.save()?;
+        }
+        Ok(changed)
+    }
+
+    /// Sync every `interval_secs`, passing the games changed to `synced`
+    /// until it returns false. Meant to run on a thread of its own.
+    pub fn run(&self, synced: impl Fn(Vec<String>) -> bool) -> Result<()> {
+        loop {
+            match self.sync() {
+                Ok(changed) if changed.is_empty() => {}
+                Ok(changed) => {
+                    if !synced(changed) {
+                        return Ok(());
+                    }
+                }
+                Err(e) => warn!("failed to sync: {:#}", e),
+            }
+            thread::sleep(Duration::from_secs(self.config.interval_secs));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_devices_once() {
+        let peer = |key: &str| Peer {
+            device: "a".to_owned(),
+            name: "living-room".to_owned(),
+            key: key.to_owned(),
+        };
+        let mut config: SyncConfig = serde_yaml::from_str("enabled: true").unwrap();
+        assert_eq!(config.listen, "0.0.0.0:7879");
+        config.pair(peer("old"));
+        config.pair(peer("new"));
+        assert_eq!(config.peers, [peer("new")]);
+        assert!(config.peer("b").is_none());
+    }
+}
@@ -0,0 +1,457 @@
+use crate::{
+    art::{placeholder::Placeholder, ArtCache},
+    integrations::{
+        moonlight,
+        steam::{self, SteamInstaller},
+    },
+    library::Library,
+    models::{self, GameMetadata, ImageSource},
+    CompletionStatus, GameData, HomeWindow, HomeWindowFocus, InstallState,
+};
+use slint::{Color, ComponentHandle, Image, Model, ModelNotify, ModelTracker};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+// The game tiles of the home screen, read from the library as the UI asks
+// for them instead of all up front. Only the rows around what's been asked
+// for lately are kept, covers are loaded with their row. Games being imported
+// show up first as loading placeholders, filled in once in the library.
+
+/// Rows read from the library at once.
+const CHUNK: usize = 64;
+/// Chunks kept around, older ones are read again when needed.
+const CACHED_CHUNKS: usize = 8;
+/// Of a pixel of a loaded cover.
+const PIXEL_BYTES: u64 = 4;
+
+/// What happened to the library, for the tiles to follow.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LibraryChange {
+    /// Games were added, removed, or the library was replaced.
+    Reset,
+    /// The metadata or art of a game changed.
+    Changed(String),
+    /// A game left the library, e.g. merged into another.
+    Removed(String),
+    /// A tile moved, e.g. while being dragged around.
+    Moved { from: usize, to: usize },
+    /// Install progress of a game, None once done either way.
+    Progress(String, Option<f32>),
+    /// Games about to be added, by UUID, shown loading until `Imported`.
+    Importing(Vec<String>),
+    /// A game being imported is in the library now.
+    Imported(String),
+}
+
+/// What's needed besides the metadata to make a tile.
+#[derive(Clone, Default)]
+pub struct TileSources {
+    pub art: Option<ArtCache>,
+    /// Tells if Steam games are installed.
+    pub steam: Option<SteamInstaller>,
+    /// Names of the library roots whose drive is unplugged.
+    pub unavailable_roots: Arc<Mutex<HashSet<String>>>,
+    /// Bytes of covers kept loaded, None for only `CACHED_CHUNKS`.
+    pub cover_budget: Option<u64>,
+    /// Of the titles, see `GameMetadata::title_in`.
+    pub locale: Arc<Mutex<String>>,
+    /// UUIDs of the games picked for the bulk actions.
+    pub selected: Arc<Mutex<HashSet<String>>>,
+}
+
+impl TileSources {
+    /// The tile of a game.
+    pub fn tile(&self, game: &GameMetadata) -> GameData {
+        let locale = self.locale.lock().unwrap().clone();
+        let title = game.title_in(&locale).to_owned();
+        let placeholder = Placeholder::new(&game.uuid, &title);
+        let [r, g, b] = placeholder.color;
+        GameData {
+            title: title.into(),
+            uuid: game.uuid.clone().into(),
+            cover: self.cover(game),
+            initials: placeholder.initials.into(),
+            placeholder_color: Color::from_rgb_u8(r, g, b),
+            favorite: game.favorate,
+            status: match game.status {
+                models::CompletionStatus::Backlog => CompletionStatus::Backlog,
+                models::CompletionStatus::Playing => CompletionStatus::Playing,
+                models::CompletionStatus::Completed => CompletionStatus::Completed,
+                models::CompletionStatus::Abandoned => CompletionStatus::Abandoned,
+            },
+            rating: game.rating_badge(&locale).unwrap_or_default().into(),
+            mature: game.min_age().is_some_and(|age| age >= models::MATURE_AGE),
+            selected: self.selected.lock().unwrap().contains(&game.uuid),
+            install_state: self.install_state(game),
+            downloading: false,
+            download_progress: 0.0,
+            loading: false,
+        }
+    }
+
+    /// The cover if it's on disk, remote ones are downloaded on first sight
+    /// and show up with the `Changed` after.
+    fn cover(&self, game: &GameMetadata) -> Image {
+        let path = match &game.cover_art {
+            Some(ImageSource::FilePath(path)) => Some(path.into()),
+            Some(ImageSource::Url(url)) => self.art.as_ref().and_then(|a| a.fetch(&game.uuid, url)),
+            // Only in backups, inlined on restore.
+            Some(ImageSource::Base64(_)) | None => None,
+        };
+        path.and_then(|p| Image::load_from_path(&p).ok())
+            .unwrap_or_default()
+    }
+
+    fn install_state(&self, game: &GameMetadata) -> InstallState {
+        if moonlight::is_streamed(game) {
+            return InstallState::Streamed;
+        }
+        if let Some(root) = &game.root {
+            if self.unavailable_roots.lock().unwrap().contains(root) {
+                return InstallState::Unavailable;
+            }
+        }
+        let app_id = game
+            .install_source
+            .as_deref()
+            .and_then(steam::app_id_from_install_source);
+        match (app_id, &self.steam) {
+            (Some(app_id), Some(steam)) if steam.is_installed(app_id) => InstallState::Installed,
+            (Some(_), Some(_)) => InstallState::NotInstalled,
+            _ => InstallState::Local,
+        }
+    }
+}
+
+pub struct GameTiles {
+    library: Arc<Mutex<Library>>,
+    sources: TileSources,
+    /// UUIDs in tile order.
+    order: RefCell<Vec<String>>,
+    /// Rows read by chunk, the latest read last.
+    chunks: RefCell<VecDeque<(usize, Vec<GameData>)>>,
+    /// Install progress by game, over whatever's read.
+    progress: RefCell<HashMap<String, f32>>,
+    /// Games being imported, after those in the library until they're in.
+    importing: RefCell<Vec<String>>,
+    notify: ModelNotify,
+}
+
+impl GameTiles {
+    pub fn new(library: Arc<Mutex<Library>>, sources: TileSources) -> Self {
+        let tiles = Self {
+            library,
+            sources,
+            order: RefCell::default(),
+            chunks: RefCell::default(),
+            progress: RefCell::default(),
+            importing: RefCell::default(),
+            notify: ModelNotify::default(),
+        };
+        tiles.read_order();
+        tiles
+    }
+
+    fn read_order(&self) {
+        let library = self.library.lock().unwrap();
+        let mut order: Vec<String> = library.visible().map(|g| g.uuid.clone()).collect();
+        let importing = self.importing.borrow();
+        order.extend(
+            importing
+                .iter()
+                .filter(|u| library.get(u).is_none())
+                .cloned(),
+        );
+        *self.order.borrow_mut() = order;
+        self.chunks.borrow_mut().clear();
+    }
+
+    pub fn apply(&self, change: LibraryChange) {
+        match change {
+            LibraryChange::Reset => {
+                self.read_order();
+                self.notify.reset();
+            }
+            LibraryChange::Changed(uuid) => {
+                if let Some(row) = self.row_of(&uuid) {
+                    self.forget_chunk(row / CHUNK);
+                    self.notify.row_changed(row);
+                }
+            }
+            LibraryChange::Removed(uuid) => {
+                let Some(row) = self.row_of(&uuid) else {
+                    return;
+                };
+                self.order.borrow_mut().remove(row);
+                // The rows after it shifted.
+                self.chunks.borrow_mut().retain(|(c, _)| *c < row / CHUNK);
+                self.progress.borrow_mut().remove(&uuid);
+                self.notify.row_removed(row, 1);
+            }
+            LibraryChange::Moved { from, to } => {
+                {
+                    let mut order = self.order.borrow_mut();
+                    if from >= order.len() || to >= order.len() {
+                        return;
+                    }
+                    let uuid = order.remove(from);
+                    order.insert(to, uuid);
+                }
+                // Every row in between shifted, cheaper to read them again.
+                self.chunks.borrow_mut().clear();
+                self.notify.row_removed(from, 1);
+                self.notify.row_added(to, 1);
+            }
+            LibraryChange::Progress(uuid, progress) => {
+                let done = match progress {
+                    Some(p) => {
+                        self.progress.borrow_mut().insert(uuid.clone(), p);
+                        false
+                    }
+                    None => self.progress.borrow_mut().remove(&uuid).is_some(),
+                };
+                if let Some(row) = self.row_of(&uuid) {
+                    // Installed now, or not after all.
+                    if done {
+                        self.forget_chunk(row / CHUNK);
+                    }
+                    self.notify.row_changed(row);
+                }
+            }
+            LibraryChange::Importing(uuids) => {
+                let start = self.row_count();
+                let added: Vec<String> = uuids
+                    .into_iter()
+                    .filter(|u| self.row_of(u).is_none())
+                    .collect();
+                if added.is_empty() {
+                    return;
+                }
+                self.importing.borrow_mut().extend(added.iter().cloned());
+                self.order.borrow_mut().extend(added.iter().cloned());
+                self.notify.row_added(start, added.len());
+            }
+            LibraryChange::Imported(uuid) => {
+                self.importing.borrow_mut().retain(|u| *u != uuid);
+                if let Some(row) = self.row_of(&uuid) {
+                    self.forget_chunk(row / CHUNK);
+                    self.notify.row_changed(row);
+                }
+            }
+        }
+    }
+
+    fn row_of(&self, uuid: &str) -> Option<usize> {
+        self.order.borrow().iter().position(|u| u == uuid)
+    }
+
+    pub fn title(&self, uuid: &str) -> Option<String> {
+        let library = self.library.lock().unwrap();
+        let locale = self.sources.locale.lock().unwrap();
+        library.get(uuid).map(|g| g.title_in(&locale).to_owned())
+    }
+
+    fn forget_chunk(&self, chunk: usize) {
+        self.chunks.borrow_mut().retain(|(c, _)| *c != chunk);
+    }
+
+    /// The rows of the chunk, read from the library if they aren't already.
+    fn chunk(&self, chunk: usize) -> Vec<GameData> {
+        let mut chunks = self.chunks.borrow_mut();
+        if let Some(i) = chunks.iter().position(|(c, _)| *c == chunk) {
+            let cached = chunks.remove(i).unwrap();
+            chunks.push_back(cached);
+            return chunks.back().unwrap().1.clone();
+        }
+
+        let order = self.order.borrow();
+        let start = (chunk * CHUNK).min(order.len());
+        let uuids = &order[start..(start + CHUNK).min(order.len())];
+        let rows: Vec<GameData> = {
+            let library = self.library.lock().unwrap();
+            let wanted: HashSet<&str> = uuids.iter().map(String::as_str).collect();
+            let games: HashMap<&str, &GameMetadata> = library
+                .iter()
+                .filter(|g| wanted.contains(g.uuid.as_str()))
+                .map(|g| (g.uuid.as_str(), g))
+                .collect();
+            uuids
+                .iter()
+                .map(|uuid| match games.get(uuid.as_str()) {
+                    Some(game) => self.sources.tile(game),
+                    // Not in yet, or gone with a reset on the way.
+                    None => GameData {
+                        uuid: uuid.as_str().into(),
+                        loading: self.importing.borrow().contains(uuid),
+                        ..Default::default()
+                    },
+                })
+                .collect()
+        };
+        chunks.push_back((chunk, rows.clone()));
+        // Those read longest ago go first, the one just read stays.
+        let over_budget = |chunks: &VecDeque<(usize, Vec<GameData>)>| {
+            self.sources
+                .cover_budget
+                .is_some_and(|budget| cover_bytes(chunks) > budget)
+        };
+        while chunks.len() > 1 && (chunks.len() > CACHED_CHUNKS || over_budget(&chunks)) {
+            chunks.pop_front();
+        }
+        rows
+    }
+}
+
+/// What the loaded covers take in memory.
+fn cover_bytes(chunks: &VecDeque<(usize, Vec<GameData>)>) -> u64 {
+    chunks
+        .iter()
+        .flat_map(|(_, rows)| rows)
+        .map(|tile| {
+            let size = tile.cover.size();
+            size.width as u64 * size.height as u64 * PIXEL_BYTES
+        })
+        .sum()
+}
+
+impl Model for GameTiles {
+    type Data = GameData;
+
+    fn row_count(&self) -> usize {
+        self.order.borrow().len()
+    }
+
+    fn row_data(&self, row: usize) -> Option<GameData> {
+        if row >= self.row_count() {
+            return None;
+        }
+        let mut tile = self.chunk(row / CHUNK).swap_remove(row % CHUNK);
+        if let Some(&p) = self.progress.borrow().get(tile.uuid.as_str()) {
+            tile.downloading = true;
+            tile.download_progress = p;
+            tile.install_state = InstallState::Installing;
+        }
+        Some(tile)
+    }
+
+    fn model_tracker(&self) -> &dyn ModelTracker {
+        &self.notify
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Pass a library change on to the home screen's tiles.
+pub fn apply(window: &HomeWindow, change: LibraryChange) {
+    let games = window.global::<HomeWindowFocus>().get_games();
+    if let Some(tiles) = games.as_any().downcast_ref::<GameTiles>() {
+        tiles.apply(change);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(games: usize) -> Arc<Mutex<Library>> {
+        let mut library = Library::new();
+        for i in 0..games {
+            library
+                .insert(GameMetadata {
+                    title: format!("Game {}", i),
+                    uuid: format!("game-{}", i),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        Arc::new(Mutex::new(library))
+    }
+
+    fn cached(sut: &GameTiles) -> Vec<usize> {
+        sut.chunks.borrow().iter().map(|(c, _)| *c).collect()
+    }
+
+    #[test]
+    fn reads_the_rows_shown() {
+        let sut = GameTiles::new(library(CHUNK * 20), TileSources::default());
+        assert_eq!(sut.row_count(), CHUNK * 20);
+        assert!(cached(&sut).is_empty());
+
+        let row = sut.row_data(CHUNK * 3 + 1).unwrap();
+        assert_eq!(row.title, format!("Game {}", CHUNK * 3 + 1));
+        assert_eq!(cached(&sut), [3]);
+        assert_eq!(sut.row_data(CHUNK * 20), None);
+
+        // Scrolling down, the first ones read go.
+        for chunk in 4..(4 + CACHED_CHUNKS) {
+            sut.row_data(chunk * CHUNK);
+        }
+        assert_eq!(cached(&sut).len(), CACHED_CHUNKS);
+        assert!(!cached(&sut).contains(&3));
+    }
+
+    #[test]
+    fn follows_the_library() {
+        let library = library(3);
+        let sut = GameTiles::new(library.clone(), TileSources::default());
+        sut.apply(LibraryChange::Moved { from: 0, to: 2 });
+        let uuids: Vec<_> = sut.iter().map(|t| t.uuid.to_string()).collect();
+        assert_eq!(uuids, ["game-1", "game-2", "game-0"]);
+
+        sut.apply(LibraryChange::Progress("game-2".to_owned(), Some(0.5)));
+        let tile = sut.row_data(1).unwrap();
+        assert!(tile.downloading);
+        assert_eq!(tile.download_progress, 0.5);
+
+        assert_eq!(tile.install_state, InstallState::Installing);
+        sut.apply(LibraryChange::Progress("game-2".to_owned(), None));
+        assert!(!sut.row_data(1).unwrap().downloading);
+
+        library.lock().unwrap().merge("game-1", "game-2").unwrap();
+        sut.apply(LibraryChange::Removed("game-2".to_owned()));
+        let uuids: Vec<_> = sut.iter().map(|t| t.uuid.to_string()).collect();
+        assert_eq!(uuids, ["game-1", "game-0"]);
+        assert_eq!(sut.title("game-0").as_deref(), Some("Game 0"));
+    }
+
+    #[test]
+    fn fills_in_imported_games() {
+        let library = library(2);
+        let sut = GameTiles::new(library.clone(), TileSources::default());
+        sut.apply(LibraryChange::Importing(vec![
+            "game-2".to_owned(),
+            "game-3".to_owned(),
+        ]));
+        assert_eq!(sut.row_count(), 4);
+        let tile = sut.row_data(2).unwrap();
+        assert!(tile.loading);
+        assert_eq!(tile.uuid, "game-2");
+        assert!(!sut.row_data(1).unwrap().loading);
+
+        library
+            .lock()
+            .unwrap()
+            .insert(GameMetadata {
+                title: "Game 2".to_owned(),
+                uuid: "game-2".to_owned(),
+                ..Default::default()
+            })
+            .unwrap();
+        sut.apply(LibraryChange::Imported("game-2".to_owned()));
+        let tile = sut.row_data(2).unwrap();
+        assert!(!tile.loading);
+        assert_eq!(tile.title, "Game 2");
+
+        // Still there after a reset, until it's in too.
+        sut.apply(LibraryChange::Reset);
+        let uuids: Vec<_> = sut.iter().map(|t| t.uuid.to_string()).collect();
+        assert_eq!(uuids, ["game-0", "game-1", "game-2", "game-3"]);
+        assert!(sut.row_data(3).unwrap().loading);
+    }
+}
@@ -0,0 +1,117 @@
+use anyhow::{bail, Context, Result};
+use std::process::Command;
+
+// The system clock settings, through systemd-timedated like the desktop
+// settings do, by way of `timedatectl`. Changing them goes through polkit,
+// on a console set up for it there's no prompt.
+
+/// What timedated reports.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimeSettings {
+    /// e.g. `Europe/Paris`.
+    pub timezone: String,
+    /// Whether the clock is set from the network.
+    pub ntp: bool,
+    /// Whether it has been yet.
+    pub ntp_synced: bool,
+}
+
+impl TimeSettings {
+    /// From the `key=value` lines of `timedatectl show`.
+    fn parse(output: &str) -> Self {
+        let mut settings = Self::default();
+        for (key, value) in output.lines().filter_map(|l| l.split_once('=')) {
+            match key {
+                "Timezone" => settings.timezone = value.to_owned(),
+                "NTP" => settings.ntp = value == "yes",
+                "NTPSynchronized" => settings.ntp_synced = value == "yes",
+                _ => {}
+            }
+        }
+        settings
+    }
+}
+
+fn timedatectl(args: &[&str]) -> Result<String> {
+    let output = Command::new("timedatectl")
+        .args(args)
+        .output()
+        .context("failed to run timedatectl")?;
+    if !output.status.success() {
+        bail!(
+            "timedatectl {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn status() -> Result<TimeSettings> {
+    Ok(TimeSettings::parse(&timedatectl(&["show"])?))
+}
+
+/// All the time zones the system knows, sorted.
+pub fn timezones() -> Result<Vec<String>> {
+    Ok(timedatectl(&["list-timezones"])?
+        .lines()
+        .map(str::to_owned)
+        .collect())
+}
+
+pub fn set_timezone(timezone: &str) -> Result<()> {
+    timedatectl(&["set-timezone", timezone])?;
+    Ok(())
+}
+
+pub fn set_ntp(on: bool) -> Result<()> {
+    timedatectl(&["set-ntp", if on { "true" } else { "false" }])?;
+    Ok(())
+}
+
+/// The zone `step` away from `current` in `zones`, wrapping around. The
+/// first one when `current` isn't there.
+pub fn step_timezone<'a>(zones: &'a [String], current: &str, step: isize) -> Option<&'a str> {
+    if zones.is_empty() {
+        return None;
+    }
+    let next = match zones.iter().position(|z| z == current) {
+        Some(i) => (i as isize + step).rem_euclid(zones.len() as isize) as usize,
+        None => 0,
+    };
+    Some(&zones[next])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_timedated() {
+        let settings = TimeSettings::parse(
+            "Timezone=Europe/Paris\nLocalRTC=no\nCanNTP=yes\nNTP=yes\nNTPSynchronized=no\n",
+        );
+        assert_eq!(
+            settings,
+            TimeSettings {
+                timezone: "Europe/Paris".to_owned(),
+                ntp: true,
+                ntp_synced: false,
+            }
+        );
+
+        let zones: Vec<String> = ["Europe/Paris", "UTC", "US/Pacific"]
+            .map(str::to_owned)
+            .to_vec();
+        assert_eq!(step_timezone(&zones, "UTC", 1), Some("US/Pacific"));
+        assert_eq!(
+            step_timezone(&zones, "Europe/Paris", -1),
+            Some("US/Pacific")
+        );
+        assert_eq!(
+            step_timezone(&zones, "Mars/Olympus", 1),
+            Some("Europe/Paris")
+        );
+        assert_eq!(step_timezone(&[], "UTC", 1), None);
+    }
+}
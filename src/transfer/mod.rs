@@ -0,0 +1,645 @@
+use anyhow::{anyhow, bail, Result};
+use log::{debug, warn};
+use std::{
+    fs,
+    io::{Read, Write},
+    path::Path,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+// The transfer manager runs long running jobs, like game installs, art
+// downloads, disk scans and backups, so they can be queued, paused and
+// cancelled from a single place (the tasks screen).
+//
+// Jobs are closures run on a worker thread. They report progress through a
+// `TransferContext` and are expected to poll `TransferContext::should_stop`,
+// returning early when it's set, or bail out with `check_stop`. A paused job
+// is simply run again on resume, so jobs must be able to pick up where they
+// left off (or start over).
+
+pub type TransferId = u64;
+
+/// Work done by a transfer.
+pub type TransferJob = Box<dyn FnMut(&TransferContext) -> Result<()> + Send>;
+
+/// Size of the buffer used when downloading.
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Minimum interval between two progress events of a transfer.
+const REPORT_INTERVAL: Duration = Duration::from_millis(250);
+/// Smoothing factor of the bandwidth moving average.
+const BANDWIDTH_SMOOTHING: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferKind {
+    Install,
+    Update,
+    Uninstall,
+    Art,
+    /// anubis itself, see the updater.
+    SelfUpdate,
+    /// Going through files, e.g. for the disk usage.
+    Scan,
+    /// Of the library or of saves.
+    Backup,
+    /// Several games edited at once, see `library::bulk`.
+    Bulk,
+}
+
+impl TransferKind {
+    /// How many transfers of the kind can be active at once.
+    /// Installs share the disk, art downloads are small.
+    fn max_active(self) -> usize {
+        match self {
+            TransferKind::Install | TransferKind::Update | TransferKind::Uninstall => 1,
+            TransferKind::Art => 4,
+            TransferKind::SelfUpdate => 1,
+            TransferKind::Scan | TransferKind::Backup => 1,
+            TransferKind::Bulk => 1,
+        }
+    }
+
+    fn lane(self) -> u8 {
+        match self {
+            TransferKind::Install | TransferKind::Update | TransferKind::Uninstall => 0,
+            TransferKind::Art => 1,
+            TransferKind::SelfUpdate => 2,
+            // Off the installs' lane, but not walking the disk at once.
+            TransferKind::Scan | TransferKind::Backup => 3,
+            TransferKind::Bulk => 4,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TransferKind::Install => "Install",
+            TransferKind::Update => "Update",
+            TransferKind::Uninstall => "Uninstall",
+            TransferKind::Art => "Art",
+            TransferKind::SelfUpdate => "Self update",
+            TransferKind::Scan => "Scan",
+            TransferKind::Backup => "Backup",
+            TransferKind::Bulk => "Bulk edit",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferState {
+    Queued,
+    Active,
+    Paused,
+    Completed,
+    Cancelled,
+    Failed(String),
+}
+
+impl TransferState {
+    /// Whether the transfer is done, regardless of the outcome.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TransferState::Completed | TransferState::Cancelled | TransferState::Failed(_)
+        )
+    }
+}
+
+/// Snapshot of a transfer, sent as an event whenever it changes.
+#[derive(Debug, Clone)]
+pub struct TransferInfo {
+    pub id: TransferId,
+    pub kind: TransferKind,
+    /// UUID of the game the transfer is for, empty for tasks.
+    pub uuid: String,
+    /// What a task is about, e.g. "Library", see `TransferManager::enqueue_task`.
+    pub title: Option<String>,
+    pub state: TransferState,
+    /// Optional sub state reported by the job, e.g. "Verifying".
+    pub phase: Option<String>,
+    /// Within [0, 1].
+    pub progress: f32,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    /// Bytes per second.
+    pub bandwidth: u64,
+}
+
+impl TransferInfo {
+    /// Short, human readable status.
+    pub fn describe(&self) -> String {
+        match self.state {
+            TransferState::Queued => "Queued".to_owned(),
+            TransferState::Active => format!(
+                "{} {:.0}%",
+                self.phase.as_deref().unwrap_or("Downloading"),
+                self.progress * 100.0
+            ),
+            TransferState::Paused => format!("Paused {:.0}%", self.progress * 100.0),
+            TransferState::Completed => "Completed".to_owned(),
+            TransferState::Cancelled => "Cancelled".to_owned(),
+            TransferState::Failed(ref e) => format!("Failed: {}", e),
+        }
+    }
+}
+
+/// Download `url` to `dest`, reporting progress on the way.
+pub fn download(ctx: &TransferContext, url: &str, dest: &Path) -> Result<()> {
+    let resp = ureq::get(url).call()?;
+    let total: u64 = resp
+        .header("Content-Length")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(0);
+
+    // Download next to the destination, so a partial file is never picked up.
+    let part = dest.with_extension("part");
+    let mut file = fs::File::create(&part)?;
+    let mut reader = resp.into_reader();
+    let mut buf = vec![0; CHUNK_SIZE];
+    let mut done = 0;
+    loop {
+        if ctx.should_stop() {
+            // Start over when resumed, no ranges.
+            fs::remove_file(&part)?;
+            return Ok(());
+        }
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n])?;
+        done += n as u64;
+        ctx.report(done, total.max(done));
+    }
+    if done == 0 {
+        fs::remove_file(&part)?;
+        bail!("empty response from {}", url);
+    }
+    fs::rename(part, dest)?;
+    Ok(())
+}
+
+/// Format a bandwidth, e.g. `1.5 MB/s`.
+pub fn format_bandwidth(bytes_per_sec: u64) -> String {
+    const UNITS: [&str; 4] = ["B/s", "KB/s", "MB/s", "GB/s"];
+    let mut value = bytes_per_sec as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+struct Entry {
+    info: TransferInfo,
+    /// None while the job is running.
+    job: Option<TransferJob>,
+    pause_requested: bool,
+    cancel_requested: bool,
+    last_sample: Option<(Instant, u64)>,
+    last_report: Option<Instant>,
+}
+
+struct Inner {
+    next_id: TransferId,
+    entries: Vec<Entry>,
+    events: mpsc::Sender<TransferInfo>,
+}
+
+impl Inner {
+    fn entry_mut(&mut self, id: TransferId) -> Result<&mut Entry> {
+        self.entries
+            .iter_mut()
+            .find(|e| e.info.id == id)
+            .ok_or(anyhow!("no transfer with id {}", id))
+    }
+
+    fn emit(&self, id: TransferId) {
+        if let Some(e) = self.entries.iter().find(|e| e.info.id == id) {
+            // The receiver is gone when the application is shutting down.
+            let _ = self.events.send(e.info.clone());
+        }
+    }
+}
+
+/// Handle given to a running job.
+pub struct TransferContext {
+    id: TransferId,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl TransferContext {
+    /// Report the bytes transferred so far.
+    pub fn report(&self, bytes_done: u64, bytes_total: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        let Ok(entry) = inner.entry_mut(self.id) else {
+            return;
+        };
+        let now = Instant::now();
+        if let Some((then, bytes_then)) = entry.last_sample {
+            let secs = now.duration_since(then).as_secs_f64();
+            if secs > 0.0 && bytes_done >= bytes_then {
+                let sample = (bytes_done - bytes_then) as f64 / secs;
+                entry.info.bandwidth = (BANDWIDTH_SMOOTHING * sample
+                    + (1.0 - BANDWIDTH_SMOOTHING) * entry.info.bandwidth as f64)
+                    as u64;
+            }
+        }
+        entry.last_sample = Some((now, bytes_done));
+        entry.info.bytes_done = bytes_done;
+        entry.info.bytes_total = bytes_total;
+        if bytes_total > 0 {
+            entry.info.progress = (bytes_done as f32 / bytes_total as f32).clamp(0.0, 1.0);
+        }
+        self.maybe_emit(&mut inner, now);
+    }
+
+    /// Report the progress when the byte count is unknown.
+    pub fn report_progress(&self, progress: f32) {
+        let mut inner = self.inner.lock().unwrap();
+        let Ok(entry) = inner.entry_mut(self.id) else {
+            return;
+        };
+        entry.info.progress = progress.clamp(0.0, 1.0);
+        self.maybe_emit(&mut inner, Instant::now());
+    }
+
+    pub fn set_phase(&self, phase: Option<&str>) {
+        let mut inner = self.inner.lock().unwrap();
+        let Ok(entry) = inner.entry_mut(self.id) else {
+            return;
+        };
+        if entry.info.phase.as_deref() != phase {
+            entry.info.phase = phase.map(str::to_owned);
+            inner.emit(self.id);
+        }
+    }
+
+    /// Whether the job should return as soon as possible, because the
+    /// transfer is paused or cancelled.
+    pub fn should_stop(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.entry_mut(self.id) {
+            Ok(e) => e.pause_requested || e.cancel_requested,
+            Err(_) => true,
+        }
+    }
+
+    /// An error when the job should stop, to return with `?` from deep in it.
+    /// The transfer ends paused or cancelled all the same, not failed.
+    pub fn check_stop(&self) -> Result<()> {
+        if self.should_stop() {
+            bail!("stopped");
+        }
+        Ok(())
+    }
+
+    fn maybe_emit(&self, inner: &mut Inner, now: Instant) {
+        let Ok(entry) = inner.entry_mut(self.id) else {
+            return;
+        };
+        let due = entry
+            .last_report
+            .is_none_or(|t| now.duration_since(t) >= REPORT_INTERVAL);
+        if due {
+            entry.last_report = Some(now);
+            inner.emit(self.id);
+        }
+    }
+}
+
+/// Queue of transfers, cheap to clone and share between threads.
+#[derive(Clone)]
+pub struct TransferManager {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl TransferManager {
+    pub fn new(events: mpsc::Sender<TransferInfo>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                next_id: 0,
+                entries: vec![],
+                events,
+            })),
+        }
+    }
+
+    /// Queue a job, it's started once there is room in its lane.
+    pub fn enqueue(&self, kind: TransferKind, uuid: &str, job: TransferJob) -> TransferId {
+        self.push(kind, uuid, None, job)
+    }
+
+    /// Queue a job which isn't about a game, shown as `title`.
+    pub fn enqueue_task(&self, kind: TransferKind, title: &str, job: TransferJob) -> TransferId {
+        self.push(kind, "", Some(title.to_owned()), job)
+    }
+
+    fn push(
+        &self,
+        kind: TransferKind,
+        uuid: &str,
+        title: Option<String>,
+        job: TransferJob,
+    ) -> TransferId {
+        let id = {
+            let mut inner = self.inner.lock().unwrap();
+            let id = inner.next_id;
+            inner.next_id += 1;
+            inner.entries.push(Entry {
+                info: TransferInfo {
+                    id,
+                    kind,
+                    uuid: uuid.to_owned(),
+                    title,
+                    state: TransferState::Queued,
+                    phase: None,
+                    progress: 0.0,
+                    bytes_done: 0,
+                    bytes_total: 0,
+                    bandwidth: 0,
+                },
+                job: Some(job),
+                pause_requested: false,
+                cancel_requested: false,
+                last_sample: None,
+                last_report: None,
+            });
+            inner.emit(id);
+            id
+        };
+        debug!("queued {:?} transfer {} for {}", kind, id, uuid);
+        self.schedule();
+        id
+    }
+
+    /// Snapshots of all the transfers, in queue order.
+    pub fn list(&self) -> Vec<TransferInfo> {
+        self.inner
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .map(|e| e.info.clone())
+            .collect()
+    }
+
+    pub fn get(&self, id: TransferId) -> Option<TransferInfo> {
+        self.inner
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .find(|e| e.info.id == id)
+            .map(|e| e.info.clone())
+    }
+
+    /// Whether the game has an unfinished transfer of the kind.
+    pub fn is_pending(&self, uuid: &str, kind: TransferKind) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .any(|e| e.info.uuid == uuid && e.info.kind == kind && !e.info.state.is_terminal())
+    }
+
+    /// Pause an active or queued transfer, or resume a paused one.
+    pub fn toggle_pause(&self, id: TransferId) -> Result<()> {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            let entry = inner.entry_mut(id)?;
+            match entry.info.state {
+                TransferState::Active => entry.pause_requested = true,
+                TransferState::Queued => entry.info.state = TransferState::Paused,
+                TransferState::Paused => {
+                    entry.pause_requested = false;
+                    entry.info.state = TransferState::Queued;
+                }
+                ref s => bail!("can't pause transfer {} in state {:?}", id, s),
+            }
+            inner.emit(id);
+        }
+        self.schedule();
+        Ok(())
+    }
+
+    pub fn cancel(&self, id: TransferId) -> Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        let entry = inner.entry_mut(id)?;
+        match entry.info.state {
+            TransferState::Active => entry.cancel_requested = true,
+            TransferState::Queued | TransferState::Paused => {
+                entry.info.state = TransferState::Cancelled;
+                entry.job = None;
+            }
+            ref s => bail!("can't cancel transfer {} in state {:?}", id, s),
+        }
+        inner.emit(id);
+        Ok(())
+    }
+
+    /// Forget the transfers which are over, returns their IDs. They're kept
+    /// until then for the tasks screen to show how they went.
+    pub fn clear_finished(&self) -> Vec<TransferId> {
+        let mut inner = self.inner.lock().unwrap();
+        let finished = inner
+            .entries
+            .iter()
+            .filter(|e| e.info.state.is_terminal())
+            .map(|e| e.info.id)
+            .collect();
+        inner.entries.retain(|e| !e.info.state.is_terminal());
+        finished
+    }
+
+    /// Start queued transfers while there is room in their lanes.
+    fn schedule(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        let mut to_start = vec![];
+        for i in 0..inner.entries.len() {
+            let kind = inner.entries[i].info.kind;
+            if inner.entries[i].info.state != TransferState::Queued {
+                continue;
+            }
+            let active = inner
+                .entries
+                .iter()
+                .filter(|e| e.info.kind.lane() == kind.lane())
+                .filter(|e| e.info.state == TransferState::Active)
+                .count();
+            if active >= kind.max_active() {
+                continue;
+            }
+            let entry = &mut inner.entries[i];
+            entry.info.state = TransferState::Active;
+            entry.info.bandwidth = 0;
+            entry.last_sample = None;
+            let id = entry.info.id;
+            to_start.push((id, entry.job.take()));
+            inner.emit(id);
+        }
+        drop(inner);
+
+        for (id, job) in to_start {
+            let Some(job) = job else {
+                warn!("transfer {} has no job to run", id);
+                continue;
+            };
+            let this = self.clone();
+            let spawned = thread::Builder::new()
+                .name(format!("transfer-{}", id))
+                .spawn(move || this.run(id, job));
+            if let Err(e) = spawned {
+                warn!("failed to start transfer {}: {:?}", id, e);
+            }
+        }
+    }
+
+    fn run(&self, id: TransferId, mut job: TransferJob) {
+        let ctx = TransferContext {
+            id,
+            inner: self.inner.clone(),
+        };
+        let res = job(&ctx);
+        {
+            let mut inner = self.inner.lock().unwrap();
+            let Ok(entry) = inner.entry_mut(id) else {
+                return;
+            };
+            entry.info.bandwidth = 0;
+            entry.info.state = if entry.cancel_requested {
+                TransferState::Cancelled
+            } else if entry.pause_requested {
+                // Keep the job around for resuming.
+                entry.job = Some(job);
+                TransferState::Paused
+            } else {
+                match res {
+                    Ok(()) => {
+                        entry.info.progress = 1.0;
+                        TransferState::Completed
+                    }
+                    Err(e) => {
+                        warn!("transfer {} failed: {:?}", id, e);
+                        // With the causes, it's all the tasks screen has.
+                        TransferState::Failed(format!("{:#}", e))
+                    }
+                }
+            };
+            inner.emit(id);
+        }
+        self.schedule();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    fn wait_for(rx: &mpsc::Receiver<TransferInfo>, id: TransferId, state: TransferState) {
+        loop {
+            let info = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            if info.id == id && info.state == state {
+                return;
+            }
+        }
+    }
+
+    #[test]
+    fn runs_queued_transfers_in_order() {
+        let (tx, rx) = mpsc::channel();
+        let sut = TransferManager::new(tx);
+        let (gate_tx, gate_rx) = mpsc::channel::<()>();
+        let gate_rx = Arc::new(Mutex::new(gate_rx));
+
+        let first = sut.enqueue(
+            TransferKind::Install,
+            "aaaa",
+            Box::new(move |ctx| {
+                gate_rx.lock().unwrap().recv()?;
+                ctx.report(10, 10);
+                Ok(())
+            }),
+        );
+        let second = sut.enqueue(TransferKind::Install, "bbbb", Box::new(|_| Ok(())));
+
+        // Only one install at a time.
+        wait_for(&rx, first, TransferState::Active);
+        assert_eq!(sut.list()[1].state, TransferState::Queued);
+
+        gate_tx.send(()).unwrap();
+        wait_for(&rx, first, TransferState::Completed);
+        wait_for(&rx, second, TransferState::Completed);
+        assert!(!sut.is_pending("bbbb", TransferKind::Install));
+        assert_eq!(sut.clear_finished(), [first, second]);
+        assert!(sut.list().is_empty());
+    }
+
+    #[test]
+    fn pause_resume_and_cancel() {
+        let (tx, rx) = mpsc::channel();
+        let sut = TransferManager::new(tx);
+        let id = sut.enqueue(
+            TransferKind::Art,
+            "aaaa",
+            Box::new(|ctx| {
+                while !ctx.should_stop() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                Ok(())
+            }),
+        );
+        wait_for(&rx, id, TransferState::Active);
+
+        sut.toggle_pause(id).unwrap();
+        wait_for(&rx, id, TransferState::Paused);
+        sut.toggle_pause(id).unwrap();
+        wait_for(&rx, id, TransferState::Active);
+
+        sut.cancel(id).unwrap();
+        wait_for(&rx, id, TransferState::Cancelled);
+        assert!(sut.cancel(id).is_err());
+    }
+
+    #[test]
+    fn tasks_stop_and_fail_with_details() {
+        let (tx, rx) = mpsc::channel();
+        let sut = TransferManager::new(tx);
+        let id = sut.enqueue_task(
+            TransferKind::Scan,
+            "Disk usage",
+            Box::new(|ctx| loop {
+                ctx.check_stop()?;
+                thread::sleep(Duration::from_millis(1));
+            }),
+        );
+        wait_for(&rx, id, TransferState::Active);
+        let info = sut.get(id).unwrap();
+        assert_eq!(
+            (info.uuid.as_str(), info.title.as_deref()),
+            ("", Some("Disk usage"))
+        );
+        sut.cancel(id).unwrap();
+        wait_for(&rx, id, TransferState::Cancelled);
+
+        let id = sut.enqueue_task(
+            TransferKind::Backup,
+            "Library",
+            Box::new(|_| Err(anyhow!("disk full")).context("failed to write the backup")),
+        );
+        wait_for(
+            &rx,
+            id,
+            TransferState::Failed("failed to write the backup: disk full".to_owned()),
+        );
+    }
+
+    #[test]
+    fn formats_bandwidth() {
+        assert_eq!(format_bandwidth(512), "512.0 B/s");
+        assert_eq!(format_bandwidth(1_500_000), "1.5 MB/s");
+    }
+}
@@ -0,0 +1,327 @@
+use crate::transfer::{self, TransferKind, TransferManager};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::info;
+use ring::{
+    digest::{digest, SHA256},
+    signature::{UnparsedPublicKey, ED25519},
+};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+// Updates of anubis itself, for consoles without a package manager to do it.
+// A release feed lists the versions, each signed with the release key along
+// with the SHA-256 of its download, so an old release can't pass for a new
+// one. A newer one is downloaded in the background, checked against the key,
+// and checked again when it's put in place of the running binary the next
+// time anubis starts.
+//
+// {
+//   "releases": [{
+//     "version": "0.1.0",
+//     "url": "https://example.com/anubis-0.1.0.AppImage",
+//     "signature": "<base64 Ed25519 signature of `${VERSION}\n${SHA256}`>",
+//     "changelog": "Faster library loading."
+//   }]
+// }
+
+/// The "game" the update is for, in the transfers.
+pub const TRANSFER_UUID: &str = "anubis";
+/// What's been checked and waits for the next start.
+const STAGED: &str = "staged.yaml";
+
+/// Where to look for updates, there are none unless both are set.
+///
+/// ```yaml
+/// feed: https://example.com/anubis/releases.json
+/// public_key: <base64 Ed25519 public key>
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateConfig {
+    pub feed: Option<String>,
+    pub public_key: Option<String>,
+}
+
+impl UpdateConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn default_path() -> PathBuf {
+        crate::paths::config_dir().join("update.yaml")
+    }
+
+    /// Decoded, None when updates aren't set up.
+    fn public_key(&self) -> Result<Option<Vec<u8>>> {
+        let Some(ref key) = self.public_key else {
+            return Ok(None);
+        };
+        let key = STANDARD
+            .decode(key.trim())
+            .context("bad public key encoding")?;
+        Ok(Some(key))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Release {
+    pub version: Version,
+    pub url: String,
+    pub signature: String,
+    #[serde(default)]
+    pub changelog: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Feed {
+    releases: Vec<Release>,
+}
+
+/// A downloaded release, see `apply_staged`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Staged {
+    version: Version,
+    path: PathBuf,
+    /// Of the release, checked again before it's put in place.
+    signature: String,
+}
+
+pub fn current_version() -> Version {
+    env!("CARGO_PKG_VERSION").parse().unwrap()
+}
+
+/// The releases newer than `current`, the newest first.
+fn newer_releases(mut releases: Vec<Release>, current: &Version) -> Vec<Release> {
+    releases.retain(|r| r.version > *current);
+    releases.sort_by(|a, b| b.version.cmp(&a.version));
+    releases
+}
+
+/// What's signed of a release, its version and the SHA-256 of the download.
+fn signed(version: &Version, download: &[u8]) -> Vec<u8> {
+    let sha256: String = digest(&SHA256, download)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("{}\n{}", version, sha256).into_bytes()
+}
+
+fn verify(public_key: &[u8], version: &Version, download: &[u8], signature: &str) -> Result<()> {
+    let signature = STANDARD
+        .decode(signature.trim())
+        .context("bad signature encoding")?;
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(&signed(version, download), &signature)
+        .map_err(|_| anyhow!("bad signature"))
+}
+
+#[derive(Clone)]
+pub struct Updater {
+    feed: String,
+    public_key: Vec<u8>,
+    /// Where downloads are staged.
+    dir: PathBuf,
+    transfers: TransferManager,
+}
+
+impl Updater {
+    /// None when updates aren't set up.
+    pub fn new(
+        config: UpdateConfig,
+        dir: PathBuf,
+        transfers: TransferManager,
+    ) -> Result<Option<Self>> {
+        let (Some(feed), Some(public_key)) = (config.feed.clone(), config.public_key()?) else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            feed,
+            public_key,
+            dir,
+            transfers,
+        }))
+    }
+
+    pub fn default_dir() -> PathBuf {
+        crate::paths::data_dir().join("update")
+    }
+
+    /// The releases newer than this one, the newest first.
+    pub fn check(&self) -> Result<Vec<Release>> {
+        let feed: Feed = ureq::get(&self.feed).call()?.into_json()?;
+        Ok(newer_releases(feed.releases, &current_version()))
+    }
+
+    pub fn is_downloading(&self) -> bool {
+        self.transfers
+            .is_pending(TRANSFER_UUID, TransferKind::SelfUpdate)
+    }
+
+    /// Download and check the release, staged for the next start once the
+    /// transfer completes.
+    pub fn download(&self, release: Release) {
+        let this = self.clone();
+        self.transfers.enqueue(
+            TransferKind::SelfUpdate,
+            TRANSFER_UUID,
+            Box::new(move |ctx| {
+                fs::create_dir_all(&this.dir)?;
+                let path = this.dir.join(format!("anubis-{}", release.version));
+                transfer::download(ctx, &release.url, &path)?;
+                if !path.exists() {
+                    // Stopped.
+                    return Ok(());
+                }
+                ctx.set_phase(Some("Verifying"));
+                let download = fs::read(&path)?;
+                if let Err(e) = verify(
+                    &this.public_key,
+                    &release.version,
+                    &download,
+                    &release.signature,
+                ) {
+                    fs::remove_file(&path)?;
+                    return Err(e);
+                }
+                let staged = Staged {
+                    version: release.version.clone(),
+                    path,
+                    signature: release.signature.clone(),
+                };
+                fs::write(this.dir.join(STAGED), serde_yaml::to_string(&staged)?)?;
+                Ok(())
+            }),
+        );
+    }
+}
+
+/// The binary to replace, the AppImage when run from one.
+fn target() -> Result<PathBuf> {
+    match env::var_os("APPIMAGE") {
+        Some(path) => Ok(path.into()),
+        None => Ok(env::current_exe()?),
+    }
+}
+
+fn replace(target: &Path, new: &Path) -> Result<()> {
+    // Copied next to it first, a rename doesn't cross file systems but is
+    // atomic, there's never a half written binary.
+    let tmp = target.with_extension("new");
+    fs::copy(new, &tmp)?;
+    fs::set_permissions(&tmp, fs::Permissions::from_mode(0o755))?;
+    fs::rename(&tmp, target)?;
+    Ok(())
+}
+
+/// Put a staged update in place, returns the updated binary if there was
+/// one. It's checked against the key of `config` again, anything could have
+/// written the staged file since. The running process is still the old one,
+/// it's up to the caller to start that instead.
+pub fn apply_staged(dir: &Path, config: &UpdateConfig) -> Result<Option<PathBuf>> {
+    let staged_path = dir.join(STAGED);
+    if !staged_path.exists() {
+        return Ok(None);
+    }
+    let staged: Staged = serde_yaml::from_str(&fs::read_to_string(&staged_path)?)?;
+    // Whatever happens, only try once.
+    fs::remove_file(&staged_path)?;
+    if staged.version <= current_version() {
+        fs::remove_file(&staged.path).ok();
+        return Ok(None);
+    }
+    if !staged.path.exists() {
+        bail!("staged update {:?} is gone", staged.path);
+    }
+    let checked = match config.public_key() {
+        Ok(Some(key)) => fs::read(&staged.path)
+            .map_err(anyhow::Error::from)
+            .and_then(|download| verify(&key, &staged.version, &download, &staged.signature)),
+        Ok(None) => Err(anyhow!("updates aren't set up")),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = checked {
+        fs::remove_file(&staged.path).ok();
+        return Err(e.context(format!("refused the staged update {:?}", staged.path)));
+    }
+    let target = target()?;
+    replace(&target, &staged.path).with_context(|| format!("failed to replace {:?}", target))?;
+    fs::remove_file(&staged.path)?;
+    info!("updated {:?} to {}", target, staged.version);
+    Ok(Some(target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::{
+        rand::SystemRandom,
+        signature::{Ed25519KeyPair, KeyPair},
+    };
+
+    fn release(version: &str) -> Release {
+        Release {
+            version: version.parse().unwrap(),
+            url: format!("https://example.com/anubis-{}", version),
+            signature: String::new(),
+            changelog: format!("What's new in {}.", version),
+        }
+    }
+
+    #[test]
+    fn finds_signed_updates() {
+        let feed: Feed = serde_json::from_str(
+            r#"{"releases": [
+                {"version": "0.0.1", "url": "https://example.com/anubis-0.0.1", "signature": ""},
+                {"version": "1.0.0", "url": "https://example.com/anubis-1.0.0", "signature": "", "changelog": "What's new in 1.0.0."},
+                {"version": "0.3.0", "url": "https://example.com/anubis-0.3.0", "signature": "", "changelog": "What's new in 0.3.0."}
+            ]}"#,
+        )
+        .unwrap();
+        let current = "0.2.0".parse().unwrap();
+        assert_eq!(
+            newer_releases(feed.releases, &current),
+            [release("1.0.0"), release("0.3.0")]
+        );
+
+        let rng = SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let public_key = key.public_key().as_ref();
+        let version: Version = "1.0.0".parse().unwrap();
+        let signature = STANDARD.encode(key.sign(&signed(&version, b"anubis")).as_ref());
+        assert!(verify(public_key, &version, b"anubis", &signature).is_ok());
+        assert!(verify(public_key, &version, b"tampered", &signature).is_err());
+        // An old release relabeled as a newer one.
+        let relabeled = "99.0.0".parse().unwrap();
+        assert!(verify(public_key, &relabeled, b"anubis", &signature).is_err());
+        assert!(verify(public_key, &version, b"anubis", "not base64!").is_err());
+
+        // Swapped after it was downloaded.
+        let dir = env::temp_dir().join(format!("anubis-update-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("anubis-99.0.0");
+        fs::write(&path, "evil").unwrap();
+        let staged = Staged {
+            version: relabeled,
+            path: path.clone(),
+            signature,
+        };
+        fs::write(dir.join(STAGED), serde_yaml::to_string(&staged).unwrap()).unwrap();
+        let config = UpdateConfig {
+            feed: None,
+            public_key: Some(STANDARD.encode(public_key)),
+        };
+        assert!(apply_staged(&dir, &config).is_err());
+        assert!(!path.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}